@@ -1,4 +1,4 @@
-use anyhow::Result;
+use crate::Result;
 use chrono::{NaiveDateTime, Utc};
 use serde::{Deserialize, Serialize};
 use std::fmt;
@@ -62,6 +62,11 @@ impl<E: DomainEvent> DomainEventEnvelope<E> {
     pub fn occurred_on(&self) -> NaiveDateTime {
         self.occurred_on
     }
+
+    /// backdate replaces occurred_on, for events imported from other stores.
+    pub fn backdate(&mut self, occurred_on: NaiveDateTime) {
+        self.occurred_on = occurred_on;
+    }
 }
 
 /// Aggregate ID.
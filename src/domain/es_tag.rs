@@ -0,0 +1,232 @@
+use crate::Result;
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::ddd::component::{Command, DomainEvent, DomainEventEnvelope};
+use crate::domain::es_task::Tag;
+
+const TAG_DOMAIN_EVENT_VERSION: i32 = 1;
+
+/// TagError is raised when a tag rule is broken.
+#[derive(Error, Debug, PartialEq, Eq)]
+pub enum TagError {
+    #[error("the tag `{0}` can not be renamed to itself")]
+    SameName(String),
+}
+
+/// TagCommand is the message to mutate TagCatalog.
+#[derive(Debug)]
+pub enum TagCommand {
+    Rename { from: Tag, to: Tag },
+}
+
+impl Command for TagCommand {}
+
+/// TagDomainEvent is a event issued when TagCatalog mutated.
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+#[serde(tag = "type")]
+pub enum TagDomainEvent {
+    Renamed { from: Tag, to: Tag },
+}
+
+impl DomainEvent for TagDomainEvent {}
+
+/// TagCatalog is the aggregate of tag names shared by all tasks.
+/// Tasks keep the names which they were tagged with, and the catalog tells their current names,
+/// so that renaming a tag does not touch the event stream of each task.
+/// There is one catalog per database, so it has no id.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct TagCatalog {
+    version: i32,
+    events: Vec<DomainEventEnvelope<TagDomainEvent>>,
+    /// renames in the order they occurred.
+    renames: Vec<Rename>,
+}
+
+/// Rename is a rename of a tag which occurred on the time.
+#[derive(Debug, PartialEq, Eq)]
+struct Rename {
+    from: Tag,
+    to: Tag,
+    occurred_on: NaiveDateTime,
+}
+
+impl TagCatalog {
+    /// reconstruct the TagCatalog from events.
+    pub fn recreate(events: Vec<DomainEventEnvelope<TagDomainEvent>>) -> TagCatalog {
+        let mut catalog = TagCatalog::default();
+        for event in events {
+            catalog.apply(&event);
+            catalog.version += 1;
+        }
+        catalog
+    }
+
+    /// resolve returns the current name of the tag given on the time.
+    /// Only renames which occurred afterwards apply, so a tag named after an old name is kept.
+    pub fn resolve(&self, tag: &Tag, tagged_on: NaiveDateTime) -> Tag {
+        self.renames
+            .iter()
+            .filter(|r| r.occurred_on >= tagged_on)
+            .fold(tag.clone(), |current, r| {
+                if current == r.from {
+                    r.to.clone()
+                } else {
+                    current
+                }
+            })
+    }
+
+    /// get version, the number of stored events.
+    pub fn version(&self) -> i32 {
+        self.version
+    }
+
+    /// execute Command.
+    pub fn execute(&mut self, command: TagCommand) -> Result<()> {
+        match command {
+            TagCommand::Rename { from, to } => {
+                if from == to {
+                    return Err(TagError::SameName(from.as_str().to_owned()).into());
+                }
+                self.record_event(TagDomainEvent::Renamed { from, to });
+            }
+        }
+        Ok(())
+    }
+
+    /// get events which are not saved yet.
+    pub fn events(&self) -> &[DomainEventEnvelope<TagDomainEvent>] {
+        &self.events
+    }
+
+    /// clear events after they are saved.
+    pub fn clear_events(&mut self) {
+        self.events.clear();
+    }
+
+    /// apply DomainEvent.
+    fn apply(&mut self, ee: &DomainEventEnvelope<TagDomainEvent>) {
+        match ee.event() {
+            TagDomainEvent::Renamed { from, to } => self.renames.push(Rename {
+                from: from.clone(),
+                to: to.clone(),
+                occurred_on: ee.occurred_on(),
+            }),
+        }
+    }
+
+    /// record_event mutates the catalog and stores the event.
+    fn record_event(&mut self, event: TagDomainEvent) {
+        let ee = DomainEventEnvelope::new(event, self.version, TAG_DOMAIN_EVENT_VERSION);
+        self.apply(&ee);
+        self.events.push(ee);
+        self.version += 1;
+    }
+}
+
+/// ITagCatalogRepository define interface of tag catalog repository.
+pub trait ITagCatalogRepository {
+    /// load the catalog. It is empty if no tag has been renamed.
+    fn load(&self) -> Result<TagCatalog>;
+
+    /// save events of the catalog which are not saved yet.
+    fn save(&self, catalog: &mut TagCatalog) -> Result<()>;
+}
+
+/// ITagCatalogRepositoryComponent returns ITagCatalogRepository.
+pub trait ITagCatalogRepositoryComponent {
+    type TagCatalogRepository: ITagCatalogRepository;
+
+    /// tag_catalog_repository returns ITagCatalogRepository.
+    fn tag_catalog_repository(&self) -> &Self::TagCatalogRepository;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tag(s: &str) -> Tag {
+        Tag::new(s).unwrap()
+    }
+
+    #[test]
+    fn test_resolve() {
+        #[derive(Debug)]
+        struct TestCase {
+            args: Vec<(&'static str, &'static str)>,
+            want: Vec<(&'static str, &'static str)>,
+            name: String,
+        }
+
+        let table = [
+            TestCase {
+                name: String::from("normal: rename once"),
+                args: vec![("work", "job")],
+                want: vec![("job", "job"), ("work", "job"), ("home", "home")],
+            },
+            TestCase {
+                name: String::from("normal: rename twice"),
+                args: vec![("work", "job"), ("job", "gig")],
+                want: vec![("work", "gig"), ("job", "gig"), ("gig", "gig")],
+            },
+            TestCase {
+                name: String::from("normal: rename back"),
+                args: vec![("work", "job"), ("job", "work")],
+                want: vec![("work", "work"), ("job", "work")],
+            },
+        ];
+
+        for test_case in table {
+            let mut catalog = TagCatalog::default();
+            for (from, to) in &test_case.args {
+                catalog
+                    .execute(TagCommand::Rename {
+                        from: tag(from),
+                        to: tag(to),
+                    })
+                    .unwrap();
+            }
+
+            let events = serde_json::to_string(catalog.events()).unwrap();
+            let recreated = TagCatalog::recreate(serde_json::from_str(&events).unwrap());
+            assert_eq!(recreated.version(), test_case.args.len() as i32);
+            for (name, current) in &test_case.want {
+                assert_eq!(
+                    recreated.resolve(&tag(name), NaiveDateTime::default()),
+                    tag(current),
+                    "Failed in the \"{}\".",
+                    test_case.name
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_resolve_after_rename() {
+        let mut catalog = TagCatalog::default();
+        catalog
+            .execute(TagCommand::Rename {
+                from: tag("work"),
+                to: tag("job"),
+            })
+            .unwrap();
+        let later = chrono::Utc::now().naive_utc() + chrono::Duration::hours(1);
+        assert_eq!(catalog.resolve(&tag("work"), later), tag("work"));
+    }
+
+    #[test]
+    fn test_rename_to_itself() {
+        let err = TagCatalog::default()
+            .execute(TagCommand::Rename {
+                from: tag("work"),
+                to: tag("work"),
+            })
+            .unwrap_err();
+        assert_eq!(
+            err.downcast_ref::<TagError>(),
+            Some(&TagError::SameName(String::from("work")))
+        );
+    }
+}
@@ -1,6 +1,11 @@
+use std::fmt;
+use std::iter::Sum;
+use std::ops::{Add, Sub};
+use std::str::FromStr;
 use std::time::Duration;
 
-use anyhow::Result;
+use crate::Result;
+use chrono::{Datelike, NaiveDate, NaiveDateTime};
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
@@ -8,6 +13,83 @@ use crate::ddd::component::{
     AggregateID, AggregateRoot, Command, DomainEvent, DomainEventEnvelope, Entity, Repository,
     ValueObject,
 };
+use crate::domain::es_tag::TagCatalog;
+
+/// impl_score implements Display, FromStr and TryFrom<i64> for a score kept as i32.
+macro_rules! impl_score {
+    ($t:ident, $name:literal) => {
+        impl fmt::Display for $t {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                write!(f, "{}", self.0)
+            }
+        }
+
+        impl TryFrom<i64> for $t {
+            type Error = TaskError;
+
+            fn try_from(value: i64) -> Result<Self, Self::Error> {
+                i32::try_from(value)
+                    .map($t)
+                    .map_err(|_| TaskError::InvalidScore($name, value.to_string()))
+            }
+        }
+
+        impl FromStr for $t {
+            type Err = TaskError;
+
+            fn from_str(s: &str) -> Result<Self, Self::Err> {
+                s.parse()
+                    .map($t)
+                    .map_err(|_| TaskError::InvalidScore($name, s.to_owned()))
+            }
+        }
+    };
+}
+
+/// impl_arithmetic implements saturating arithmetic for a score, clamping results into the range.
+/// `+` and `-` saturate too, so that sums of many tasks never overflow.
+macro_rules! impl_arithmetic {
+    ($t:ident, $range:expr) => {
+        impl $t {
+            /// from_clamped constructs the score of the value clamped into the domain range.
+            fn from_clamped(value: i64) -> Self {
+                let range = $range;
+                $t(value.clamp(i64::from(*range.start()), i64::from(*range.end())) as i32)
+            }
+
+            /// saturating_add adds other, clamping the result into the domain range.
+            pub fn saturating_add(self, other: Self) -> Self {
+                Self::from_clamped(i64::from(self.0) + i64::from(other.0))
+            }
+
+            /// saturating_sub subtracts other, clamping the result into the domain range.
+            pub fn saturating_sub(self, other: Self) -> Self {
+                Self::from_clamped(i64::from(self.0) - i64::from(other.0))
+            }
+
+            /// scale_percent scales this by percent, such as 150 for 1.5 times, rounding toward zero.
+            pub fn scale_percent(self, percent: u32) -> Self {
+                Self::from_clamped(i64::from(self.0) * i64::from(percent) / 100)
+            }
+        }
+
+        impl Add for $t {
+            type Output = Self;
+
+            fn add(self, other: Self) -> Self {
+                self.saturating_add(other)
+            }
+        }
+
+        impl Sub for $t {
+            type Output = Self;
+
+            fn sub(self, other: Self) -> Self {
+                self.saturating_sub(other)
+            }
+        }
+    };
+}
 
 /// Sequential ID.
 /// This ID is for shortcut to specifying the task.
@@ -30,8 +112,38 @@ impl SequentialID {
 
 impl ValueObject for SequentialID {}
 
-/// Task Priority.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+impl fmt::Display for SequentialID {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl TryFrom<i64> for SequentialID {
+    type Error = TaskError;
+
+    /// try_from accepts positive ids, since sequential ids are numbered from 1.
+    fn try_from(id: i64) -> Result<Self, Self::Error> {
+        match id {
+            1.. => Ok(SequentialID(id)),
+            _ => Err(TaskError::InvalidSequentialID(id.to_string())),
+        }
+    }
+}
+
+impl FromStr for SequentialID {
+    type Err = TaskError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let id: i64 = s
+            .parse()
+            .map_err(|_| TaskError::InvalidSequentialID(s.to_owned()))?;
+        SequentialID::try_from(id)
+    }
+}
+
+/// Task Priority. It is the importance dimension of the score.
+/// A priority may be negative, for tasks which are less important than the default.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
 pub struct Priority(i32);
 
 impl Priority {
@@ -48,10 +160,60 @@ impl Priority {
 
 impl ValueObject for Priority {}
 
+impl_score!(Priority, "priority");
+impl_arithmetic!(Priority, i32::MIN..=i32::MAX);
+
 const DEFAULT_PRIORITY: Priority = Priority(10);
 
-/// Task Cost.
+/// Task Urgency. It is the urgency dimension of the score.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Urgency(i32);
+
+impl Urgency {
+    /// construct a task urgency.
+    pub fn new(urgency: i32) -> Self {
+        Urgency(urgency)
+    }
+
+    /// get a task urgency as primitive type.
+    pub fn to_i32(&self) -> i32 {
+        self.0
+    }
+}
+
+impl ValueObject for Urgency {}
+
+impl_score!(Urgency, "urgency");
+
+const DEFAULT_URGENCY: Urgency = Urgency(10);
+
+/// ScoreWeights combines the importance and the urgency of a task into one score.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ScoreWeights {
+    pub importance: i32,
+    pub urgency: i32,
+}
+
+impl ScoreWeights {
+    /// score returns the weighted sum of the dimensions. A higher score is to be done earlier.
+    pub fn score(&self, priority: Priority, urgency: Urgency) -> i64 {
+        i64::from(self.importance) * i64::from(priority.to_i32())
+            + i64::from(self.urgency) * i64::from(urgency.to_i32())
+    }
+}
+
+impl Default for ScoreWeights {
+    fn default() -> Self {
+        ScoreWeights {
+            importance: 1,
+            urgency: 1,
+        }
+    }
+}
+
+/// Task Cost.
+/// Results of arithmetic are never negative, since no task takes negative effort.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
 pub struct Cost(i32);
 
 impl Cost {
@@ -68,15 +230,266 @@ impl Cost {
 
 impl ValueObject for Cost {}
 
+impl_score!(Cost, "cost");
+impl_arithmetic!(Cost, 0..=i32::MAX);
+
+impl Sum for Cost {
+    fn sum<I: Iterator<Item = Cost>>(iter: I) -> Self {
+        iter.fold(Cost(0), Add::add)
+    }
+}
+
 const DEFAULT_COST: Cost = Cost(10);
 
+/// HabitPeriod is the period which the target of a habit counts check-ins in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HabitPeriod {
+    Day,
+    Week,
+    Month,
+}
+
+/// HabitTarget is how many check-ins a habit aims at in a period, such as `5/week`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HabitTarget {
+    times: u32,
+    period: HabitPeriod,
+}
+
+impl HabitTarget {
+    /// construct a HabitTarget. A habit must aim at least one check-in.
+    pub fn new(times: u32, period: HabitPeriod) -> Result<Self, TaskError> {
+        if times == 0 {
+            return Err(TaskError::InvalidHabitTarget(format!("0/{}", period)));
+        }
+        Ok(HabitTarget { times, period })
+    }
+
+    /// get the number of check-ins aimed at in a period.
+    pub fn times(&self) -> u32 {
+        self.times
+    }
+
+    /// get the period.
+    pub fn period(&self) -> HabitPeriod {
+        self.period
+    }
+}
+
+impl ValueObject for HabitTarget {}
+
+impl HabitPeriod {
+    /// start returns the beginning of the period containing the time.
+    /// A week begins on Monday.
+    pub fn start(self, at: NaiveDateTime) -> NaiveDateTime {
+        let date = at.date();
+        let date = match self {
+            HabitPeriod::Day => date,
+            HabitPeriod::Week => {
+                date - chrono::Duration::days(i64::from(date.weekday().num_days_from_monday()))
+            }
+            HabitPeriod::Month => date.with_day(1).unwrap(),
+        };
+        date.and_hms_opt(0, 0, 0).unwrap()
+    }
+}
+
+impl fmt::Display for HabitPeriod {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HabitPeriod::Day => write!(f, "day"),
+            HabitPeriod::Week => write!(f, "week"),
+            HabitPeriod::Month => write!(f, "month"),
+        }
+    }
+}
+
+impl FromStr for HabitTarget {
+    type Err = TaskError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || TaskError::InvalidHabitTarget(s.to_owned());
+        let (times, period) = s.split_once('/').ok_or_else(invalid)?;
+        let times: u32 = times.parse().map_err(|_| invalid())?;
+        let period = match period {
+            "day" => HabitPeriod::Day,
+            "week" => HabitPeriod::Week,
+            "month" => HabitPeriod::Month,
+            _ => return Err(invalid()),
+        };
+        HabitTarget::new(times, period).map_err(|_| invalid())
+    }
+}
+
+/// Task Tag.
+/// A tag is a non-empty label without whitespace and commas.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub struct Tag(String);
+
+impl Tag {
+    /// construct a task tag.
+    pub fn new(tag: &str) -> Result<Self, TaskError> {
+        if tag.is_empty() || tag.chars().any(|c| c.is_whitespace() || c == ',') {
+            return Err(TaskError::InvalidTag(tag.to_owned()));
+        }
+        Ok(Tag(tag.to_owned()))
+    }
+
+    /// get a task tag as str.
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl ValueObject for Tag {}
+
+/// Energy is how much focus a task takes. It is kept as a tag such as `energy:deep`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Energy {
+    Shallow,
+    Deep,
+}
+
+impl Energy {
+    /// all energies from the lowest.
+    const ALL: [Energy; 2] = [Energy::Shallow, Energy::Deep];
+
+    /// tag returns the tag which marks a task taking the energy.
+    pub fn tag(self) -> Tag {
+        Tag(format!("energy:{}", self))
+    }
+
+    /// of returns the energy marked by the tags. The highest one wins if several are marked.
+    pub fn of(tags: &[Tag]) -> Option<Energy> {
+        Energy::ALL
+            .into_iter()
+            .rev()
+            .find(|e| tags.contains(&e.tag()))
+    }
+}
+
+impl ValueObject for Energy {}
+
+impl fmt::Display for Energy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Energy::Shallow => write!(f, "shallow"),
+            Energy::Deep => write!(f, "deep"),
+        }
+    }
+}
+
+/// DueDate is the day by which a task should be closed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct DueDate(NaiveDate);
+
+impl DueDate {
+    /// construct a DueDate.
+    pub fn new(due_on: NaiveDate) -> Self {
+        DueDate(due_on)
+    }
+
+    /// get the day.
+    pub fn to_naive_date(self) -> NaiveDate {
+        self.0
+    }
+
+    /// is_overdue returns whether the day has passed on the day.
+    pub fn is_overdue(self, today: NaiveDate) -> bool {
+        self.0 < today
+    }
+}
+
+impl ValueObject for DueDate {}
+
+impl fmt::Display for DueDate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl FromStr for DueDate {
+    type Err = TaskError;
+
+    /// parse `YYYY-MM-DD`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        NaiveDate::parse_from_str(s, "%Y-%m-%d")
+            .map(DueDate)
+            .map_err(|_| TaskError::InvalidDueDate(s.to_owned()))
+    }
+}
+
+/// WaitingOn is an external person whom a task waits on, with the day to follow up if any.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WaitingOn {
+    person: String,
+    follow_up_on: Option<NaiveDate>,
+}
+
+impl WaitingOn {
+    /// construct a WaitingOn. The person is trimmed and must not be empty.
+    pub fn new(person: &str, follow_up_on: Option<NaiveDate>) -> Result<Self, TaskError> {
+        let person = person.trim();
+        if person.is_empty() {
+            return Err(TaskError::InvalidPerson);
+        }
+        Ok(WaitingOn {
+            person: person.to_owned(),
+            follow_up_on,
+        })
+    }
+
+    /// get the person.
+    pub fn person(&self) -> &str {
+        &self.person
+    }
+
+    /// get the day to follow up.
+    pub fn follow_up_on(&self) -> Option<NaiveDate> {
+        self.follow_up_on
+    }
+
+    /// is_due returns whether the day to follow up has come on the day.
+    pub fn is_due(&self, today: NaiveDate) -> bool {
+        self.follow_up_on.is_some_and(|d| d <= today)
+    }
+}
+
+impl ValueObject for WaitingOn {}
+
 /// TaskCommand is a command set to mutate the Task.
 #[derive(Debug, PartialEq, Eq)]
 pub enum TaskCommand {
     Close,
     EditTitle { title: String },
+    EditDescription { description: Option<String> },
     RescoreCost { cost: Cost },
     RescorePriority { priority: Priority },
+    RescoreUrgency { urgency: Urgency },
+    Tag { tag: Tag },
+    Untag { tag: Tag },
+    Lock,
+    Unlock,
+    MakePrivate,
+    MakePublic,
+    LogTime { duration: Duration },
+    StartTimer,
+    StopTimer,
+    CompletePomodoro,
+    MakeHabit { target: HabitTarget },
+    CheckIn,
+    WaitOn { waiting_on: WaitingOn },
+    StopWaiting,
+    FollowUp { predecessor: SequentialID },
+    AssignParent { parent: SequentialID },
+    Capture,
+    Triage,
+    SetDueDate { due: Option<DueDate> },
+    Defer,
+    Promote,
+    Delete,
+    Archive,
 }
 
 impl Command for TaskCommand {}
@@ -95,16 +508,71 @@ pub enum TaskDomainEvent {
     TitleEdited {
         title: String,
     },
+    DescriptionEdited {
+        description: Option<String>,
+    },
     CostRescored {
         cost: Cost,
     },
     PriorityRescored {
         priority: Priority,
     },
+    UrgencyRescored {
+        urgency: Urgency,
+    },
+    Tagged {
+        tag: Tag,
+    },
+    Untagged {
+        tag: Tag,
+    },
+    Locked,
+    Unlocked,
+    MadePrivate,
+    MadePublic,
+    TimeLogged {
+        seconds: u64,
+    },
+    TimerStarted,
+    TimerStopped {
+        seconds: u64,
+    },
+    PomodoroCompleted,
+    MadeHabit {
+        target: HabitTarget,
+    },
+    CheckedIn,
+    WaitedOn {
+        waiting_on: WaitingOn,
+    },
+    StoppedWaiting,
+    FollowedUp {
+        predecessor: SequentialID,
+    },
+    ParentAssigned {
+        parent: SequentialID,
+    },
+    Captured,
+    Triaged,
+    DueDateSet {
+        due: Option<DueDate>,
+    },
+    Deferred,
+    Promoted,
+    Deleted,
+    Archived,
 }
 
 impl DomainEvent for TaskDomainEvent {}
 
+/// TimeLog is time spent on a task.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TimeLog {
+    /// logged_on in UTC.
+    pub logged_on: NaiveDateTime,
+    pub duration: Duration,
+}
+
 /// Task is a entity representing what you should do.
 #[derive(Debug, PartialEq, Eq)]
 pub struct Task {
@@ -113,10 +581,30 @@ pub struct Task {
     sequential_id: SequentialID,
     events: Vec<DomainEventEnvelope<TaskDomainEvent>>,
     title: String,
+    description: Option<String>,
     is_closed: bool,
     priority: Priority,
+    urgency: Urgency,
     cost: Cost,
+    tags: Vec<Tag>,
+    is_locked: bool,
+    is_private: bool,
     elapsed_time: Duration,
+    time_logs: Vec<TimeLog>,
+    timer_started_at: Option<NaiveDateTime>,
+    pomodoros: u32,
+    created_at: NaiveDateTime,
+    closed_at: Option<NaiveDateTime>,
+    habit: Option<HabitTarget>,
+    check_ins: Vec<NaiveDateTime>,
+    waiting_on: Option<WaitingOn>,
+    follows: Option<SequentialID>,
+    parent: Option<SequentialID>,
+    in_inbox: bool,
+    due: Option<DueDate>,
+    is_someday: bool,
+    deleted_at: Option<NaiveDateTime>,
+    is_archived: bool,
 }
 
 #[derive(Debug)]
@@ -126,6 +614,119 @@ pub struct TaskSource {
     pub title: String,
     pub priority: Option<Priority>,
     pub cost: Option<Cost>,
+    pub tags: Vec<Tag>,
+}
+
+/// ImportCheckpoint is how far an import of a file went, so that an interrupted import can be resumed.
+/// Files are told apart by the digest of their content.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ImportCheckpoint {
+    pub digest: String,
+    /// last_line is the line of the last row saved from the file.
+    pub last_line: usize,
+}
+
+/// TaskReadModel is the state of a task projected for queries, so that listing tasks does not
+/// replay their events. What depends on the time of reading, such as whether the task is
+/// overdue, is derived on reading.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TaskReadModel {
+    pub sequential_id: SequentialID,
+    pub title: String,
+    /// description is None in rows projected before descriptions were added, as it was.
+    #[serde(default)]
+    pub description: Option<String>,
+    pub priority: Priority,
+    pub urgency: Urgency,
+    pub cost: Cost,
+    pub tags: Vec<Tag>,
+    pub is_closed: bool,
+    pub is_private: bool,
+    pub in_inbox: bool,
+    pub is_someday: bool,
+    pub habit: Option<HabitTarget>,
+    /// check_ins in UTC.
+    pub check_ins: Vec<NaiveDateTime>,
+    pub waiting_on: Option<WaitingOn>,
+    pub follows: Option<SequentialID>,
+    pub parent: Option<SequentialID>,
+    pub due: Option<DueDate>,
+    pub elapsed_time: Duration,
+    /// timer_started_at in UTC.
+    pub timer_started_at: Option<NaiveDateTime>,
+    pub pomodoros: u32,
+    /// is_deleted is true for archived tasks too.
+    #[serde(default)]
+    pub is_deleted: bool,
+    #[serde(default)]
+    pub is_archived: bool,
+}
+
+impl TaskReadModel {
+    /// count check-ins since the time in UTC.
+    pub fn check_ins_since(&self, since: NaiveDateTime) -> usize {
+        self.check_ins.iter().filter(|c| **c >= since).count()
+    }
+
+    /// is_overdue returns whether the task is open past its due date on the day.
+    pub fn is_overdue(&self, today: NaiveDate) -> bool {
+        !self.is_closed && self.due.is_some_and(|d| d.is_overdue(today))
+    }
+}
+
+impl From<&Task> for TaskReadModel {
+    fn from(task: &Task) -> Self {
+        TaskReadModel {
+            sequential_id: task.sequential_id,
+            title: task.title.clone(),
+            description: task.description.clone(),
+            priority: task.priority,
+            urgency: task.urgency,
+            cost: task.cost,
+            tags: task.tags.clone(),
+            is_closed: task.is_closed,
+            is_private: task.is_private,
+            in_inbox: task.in_inbox,
+            is_someday: task.is_someday,
+            habit: task.habit,
+            check_ins: task.check_ins.clone(),
+            waiting_on: task.waiting_on.clone(),
+            follows: task.follows,
+            parent: task.parent,
+            due: task.due,
+            elapsed_time: task.elapsed_time,
+            timer_started_at: task.timer_started_at,
+            pomodoros: task.pomodoros,
+            is_deleted: task.is_deleted(),
+            is_archived: task.is_archived,
+        }
+    }
+}
+
+/// SearchField is the fields of tasks which a search looks into.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SearchField {
+    /// titles and descriptions, where a match in the title weighs more.
+    #[default]
+    All,
+    /// titles only.
+    Title,
+    /// descriptions only, which are the notes of tasks.
+    Description,
+}
+
+/// SnippetPart is a piece of the text around matches, which is a matched word or not.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SnippetPart {
+    pub text: String,
+    pub is_match: bool,
+}
+
+/// SearchMatch is a task found by a search with the snippet of the field matching best.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SearchMatch {
+    pub task: TaskReadModel,
+    pub snippet: Vec<SnippetPart>,
 }
 
 impl Task {
@@ -147,9 +748,62 @@ impl Task {
             task.rescore_cost(c);
         }
 
+        for tag in task_source.tags {
+            task.tag(tag);
+        }
+
+        task
+    }
+
+    /// import a task from another store.
+    /// Events of the creation occur at `created_at`, and Closed occurs at `closed_at` if given.
+    pub fn import(
+        task_source: TaskSource,
+        created_at: NaiveDateTime,
+        closed_at: Option<NaiveDateTime>,
+    ) -> Task {
+        let mut task = Task::create(task_source);
+        for event in task.events.iter_mut() {
+            event.backdate(created_at);
+        }
+        task.created_at = created_at;
+
+        if let Some(closed_at) = closed_at {
+            task.close();
+            task.backdate_last_event(closed_at);
+        }
+
         task
     }
 
+    /// backdate the latest uncommitted event to `occurred_on`, for histories from other sources.
+    /// Times derived from the event, such as closed_at, follow it.
+    pub fn backdate_last_event(&mut self, occurred_on: NaiveDateTime) {
+        let Some(event) = self.events.last_mut() else {
+            return;
+        };
+        event.backdate(occurred_on);
+        match event.event() {
+            TaskDomainEvent::Created { .. } => self.created_at = occurred_on,
+            TaskDomainEvent::Closed => self.closed_at = Some(occurred_on),
+            TaskDomainEvent::TimeLogged { .. } | TaskDomainEvent::TimerStopped { .. } => {
+                if let Some(log) = self.time_logs.last_mut() {
+                    log.logged_on = occurred_on;
+                }
+            }
+            TaskDomainEvent::TimerStarted => self.timer_started_at = Some(occurred_on),
+            TaskDomainEvent::CheckedIn => {
+                if let Some(check_in) = self.check_ins.last_mut() {
+                    *check_in = occurred_on;
+                }
+            }
+            TaskDomainEvent::Deleted | TaskDomainEvent::Archived => {
+                self.deleted_at = Some(occurred_on)
+            }
+            _ => {}
+        }
+    }
+
     /// construct new default Task.
     fn new(aggregate_id: AggregateID, sequential_id: SequentialID) -> Task {
         Task {
@@ -158,10 +812,30 @@ impl Task {
             sequential_id,
             events: vec![],
             title: "".into(),
+            description: None,
             is_closed: false,
             priority: DEFAULT_PRIORITY,
+            urgency: DEFAULT_URGENCY,
             cost: DEFAULT_COST,
+            tags: vec![],
+            is_locked: false,
+            is_private: false,
             elapsed_time: Duration::from_secs(0),
+            time_logs: vec![],
+            timer_started_at: None,
+            pomodoros: 0,
+            created_at: NaiveDateTime::default(),
+            closed_at: None,
+            habit: None,
+            check_ins: vec![],
+            waiting_on: None,
+            follows: None,
+            parent: None,
+            in_inbox: false,
+            due: None,
+            is_someday: false,
+            deleted_at: None,
+            is_archived: false,
         }
     }
 
@@ -170,11 +844,31 @@ impl Task {
         aggregate_id: AggregateID,
         sequential_id: SequentialID,
         events: Vec<DomainEventEnvelope<TaskDomainEvent>>,
+    ) -> Task {
+        Task::recreate_with_tags(aggregate_id, sequential_id, events, &TagCatalog::default())
+    }
+
+    /// reconstruct the Task from events, naming tags as the catalog does now.
+    pub fn recreate_with_tags(
+        aggregate_id: AggregateID,
+        sequential_id: SequentialID,
+        events: Vec<DomainEventEnvelope<TaskDomainEvent>>,
+        catalog: &TagCatalog,
     ) -> Task {
         let mut task = Task::new(aggregate_id, sequential_id);
 
         for event in events {
-            task.apply(event.event());
+            let resolve = |tag| catalog.resolve(tag, event.occurred_on());
+            match event.event() {
+                TaskDomainEvent::Tagged { tag } => {
+                    task.apply(&TaskDomainEvent::Tagged { tag: resolve(tag) })
+                }
+                TaskDomainEvent::Untagged { tag } => {
+                    task.apply(&TaskDomainEvent::Untagged { tag: resolve(tag) })
+                }
+                e => task.apply(e),
+            }
+            task.track_time(&event);
             task.increment_version();
         }
 
@@ -186,6 +880,11 @@ impl Task {
         self.aggregate_id
     }
 
+    /// get version, the number of events of the task including uncommitted ones.
+    pub fn version(&self) -> i32 {
+        self.version
+    }
+
     /// increment version.
     /// This function is invoked every time when TaskDomainEvent is issued.
     fn increment_version(&mut self) {
@@ -207,13 +906,60 @@ impl Task {
         self.record_event(TaskDomainEvent::TitleEdited { title });
     }
 
+    /// get the description, the detail of the task which may span lines.
+    pub fn description(&self) -> Option<&str> {
+        self.description.as_deref()
+    }
+
+    /// edit the description, or clear it by None or a blank text.
+    /// Nothing happens if it does not change.
+    fn edit_description(&mut self, description: Option<String>) {
+        let description = description.filter(|d| !d.trim().is_empty());
+        if self.description == description {
+            return;
+        }
+        self.record_event(TaskDomainEvent::DescriptionEdited { description });
+    }
+
     /// get is_closed flag.
     pub fn is_closed(&self) -> bool {
         self.is_closed
     }
 
-    /// close the task.
+    /// get created_at in UTC.
+    pub fn created_at(&self) -> NaiveDateTime {
+        self.created_at
+    }
+
+    /// get closed_at in UTC. It is None while the task is open.
+    pub fn closed_at(&self) -> Option<NaiveDateTime> {
+        self.closed_at
+    }
+
+    /// track_time keeps when the event occurred if the time matters.
+    fn track_time(&mut self, event: &DomainEventEnvelope<TaskDomainEvent>) {
+        match event.event() {
+            TaskDomainEvent::Created { .. } => self.created_at = event.occurred_on(),
+            TaskDomainEvent::Closed => self.closed_at = Some(event.occurred_on()),
+            TaskDomainEvent::TimeLogged { seconds } | TaskDomainEvent::TimerStopped { seconds } => {
+                self.time_logs.push(TimeLog {
+                    logged_on: event.occurred_on(),
+                    duration: Duration::from_secs(*seconds),
+                })
+            }
+            TaskDomainEvent::TimerStarted => self.timer_started_at = Some(event.occurred_on()),
+            TaskDomainEvent::CheckedIn => self.check_ins.push(event.occurred_on()),
+            TaskDomainEvent::Deleted | TaskDomainEvent::Archived => {
+                self.deleted_at = Some(event.occurred_on())
+            }
+            _ => {}
+        }
+    }
+
+    /// close the task. A running timer is stopped first.
     fn close(&mut self) {
+        // NOTE: it fails only if no timer is running, which leaves nothing to stop.
+        let _ = self.stop_timer();
         self.record_event(TaskDomainEvent::Closed);
     }
 
@@ -227,6 +973,21 @@ impl Task {
         self.record_event(TaskDomainEvent::PriorityRescored { priority });
     }
 
+    /// get urgency.
+    pub fn urgency(&self) -> Urgency {
+        self.urgency
+    }
+
+    /// rescore urgency.
+    pub fn rescore_urgency(&mut self, urgency: Urgency) {
+        self.record_event(TaskDomainEvent::UrgencyRescored { urgency });
+    }
+
+    /// score combines the priority and the urgency by the weights.
+    pub fn score(&self, weights: &ScoreWeights) -> i64 {
+        weights.score(self.priority, self.urgency)
+    }
+
     /// get cost.
     pub fn cost(&self) -> Cost {
         self.cost
@@ -237,10 +998,303 @@ impl Task {
         self.record_event(TaskDomainEvent::CostRescored { cost });
     }
 
-    /// get elapsed_time.
+    /// get tags.
+    pub fn tags(&self) -> &[Tag] {
+        &self.tags
+    }
+
+    /// tag the task. Nothing happens if the task already has the tag.
+    fn tag(&mut self, tag: Tag) {
+        if self.tags.contains(&tag) {
+            return;
+        }
+        self.record_event(TaskDomainEvent::Tagged { tag });
+    }
+
+    /// untag the task. Nothing happens if the task does not have the tag.
+    fn untag(&mut self, tag: Tag) {
+        if !self.tags.contains(&tag) {
+            return;
+        }
+        self.record_event(TaskDomainEvent::Untagged { tag });
+    }
+
+    /// get is_locked flag. A locked task must not be edited or closed.
+    pub fn is_locked(&self) -> bool {
+        self.is_locked
+    }
+
+    /// lock the task. Nothing happens if the task is already locked.
+    fn lock(&mut self) {
+        if self.is_locked {
+            return;
+        }
+        self.record_event(TaskDomainEvent::Locked);
+    }
+
+    /// unlock the task. Nothing happens if the task is not locked.
+    fn unlock(&mut self) {
+        if !self.is_locked {
+            return;
+        }
+        self.record_event(TaskDomainEvent::Unlocked);
+    }
+
+    /// get is_private flag. A private task is hidden unless it is asked for explicitly.
+    pub fn is_private(&self) -> bool {
+        self.is_private
+    }
+
+    /// make the task private. Nothing happens if the task is already private.
+    pub fn make_private(&mut self) {
+        if self.is_private {
+            return;
+        }
+        self.record_event(TaskDomainEvent::MadePrivate);
+    }
+
+    /// make the task public. Nothing happens if the task is not private.
+    fn make_public(&mut self) {
+        if !self.is_private {
+            return;
+        }
+        self.record_event(TaskDomainEvent::MadePublic);
+    }
+
+    /// get elapsed_time, which is the total of logged time and stopped timers.
     pub fn elapsed_time(&self) -> Duration {
         self.elapsed_time
     }
+
+    /// log time spent on the task.
+    fn log_time(&mut self, duration: Duration) {
+        self.record_event(TaskDomainEvent::TimeLogged {
+            seconds: duration.as_secs(),
+        });
+    }
+
+    /// get when the running timer started in UTC. It is None if no timer is running.
+    pub fn timer_started_at(&self) -> Option<NaiveDateTime> {
+        self.timer_started_at
+    }
+
+    /// start the timer of the task.
+    fn start_timer(&mut self) -> Result<()> {
+        if self.is_closed {
+            return Err(TaskError::AlreadyClosed.into());
+        }
+        if self.timer_started_at.is_some() {
+            return Err(TaskError::TimerRunning.into());
+        }
+        self.record_event(TaskDomainEvent::TimerStarted);
+        Ok(())
+    }
+
+    /// stop the running timer, logging the time from its start to now, and return the time.
+    fn stop_timer(&mut self) -> Result<Duration> {
+        let started_at = self.timer_started_at.ok_or(TaskError::TimerNotRunning)?;
+        let elapsed = (chrono::Utc::now().naive_utc() - started_at)
+            .to_std()
+            .unwrap_or_default();
+        self.record_event(TaskDomainEvent::TimerStopped {
+            seconds: elapsed.as_secs(),
+        });
+        Ok(elapsed)
+    }
+
+    /// get the number of pomodoros completed on the task.
+    pub fn pomodoros(&self) -> u32 {
+        self.pomodoros
+    }
+
+    /// complete a pomodoro, stopping the timer which timed its work.
+    fn complete_pomodoro(&mut self) -> Result<()> {
+        self.stop_timer()?;
+        self.record_event(TaskDomainEvent::PomodoroCompleted);
+        Ok(())
+    }
+
+    /// get time logs in the logged order.
+    pub fn time_logs(&self) -> &[TimeLog] {
+        &self.time_logs
+    }
+
+    /// get the target if the task is a habit. A habit is never closed but checked in repeatedly.
+    pub fn habit(&self) -> Option<HabitTarget> {
+        self.habit
+    }
+
+    /// make the task a habit, or change the target of the habit.
+    fn make_habit(&mut self, target: HabitTarget) -> Result<()> {
+        if self.is_closed {
+            return Err(TaskError::AlreadyClosed.into());
+        }
+        self.record_event(TaskDomainEvent::MadeHabit { target });
+        Ok(())
+    }
+
+    /// check in the habit.
+    fn check_in(&mut self) -> Result<()> {
+        if self.habit.is_none() {
+            return Err(TaskError::NotHabit.into());
+        }
+        self.record_event(TaskDomainEvent::CheckedIn);
+        Ok(())
+    }
+
+    /// get check-ins in UTC in the checked order.
+    pub fn check_ins(&self) -> &[NaiveDateTime] {
+        &self.check_ins
+    }
+
+    /// count check-ins since the time in UTC.
+    pub fn check_ins_since(&self, since: NaiveDateTime) -> usize {
+        self.check_ins.iter().filter(|c| **c >= since).count()
+    }
+
+    /// get the person whom the task waits on.
+    pub fn waiting_on(&self) -> Option<&WaitingOn> {
+        self.waiting_on.as_ref()
+    }
+
+    /// wait on a person, or change the person or the day to follow up.
+    fn wait_on(&mut self, waiting_on: WaitingOn) -> Result<()> {
+        if self.is_closed {
+            return Err(TaskError::AlreadyClosed.into());
+        }
+        if self.waiting_on.as_ref() == Some(&waiting_on) {
+            return Ok(());
+        }
+        self.record_event(TaskDomainEvent::WaitedOn { waiting_on });
+        Ok(())
+    }
+
+    /// get the task which this task follows up, which spawned it on closing.
+    pub fn follows(&self) -> Option<SequentialID> {
+        self.follows
+    }
+
+    /// get the parent task which this task is a subtask of.
+    pub fn parent(&self) -> Option<SequentialID> {
+        self.parent
+    }
+
+    /// stop waiting. Nothing happens if the task does not wait on anyone.
+    fn stop_waiting(&mut self) {
+        if self.waiting_on.is_none() {
+            return;
+        }
+        self.record_event(TaskDomainEvent::StoppedWaiting);
+    }
+
+    /// is_in_inbox returns whether the task was captured and is not triaged yet.
+    pub fn is_in_inbox(&self) -> bool {
+        self.in_inbox
+    }
+
+    /// capture puts the task into the inbox to be clarified later.
+    fn capture(&mut self) -> Result<()> {
+        if self.is_closed {
+            return Err(TaskError::AlreadyClosed.into());
+        }
+        if self.in_inbox {
+            return Ok(());
+        }
+        self.record_event(TaskDomainEvent::Captured);
+        Ok(())
+    }
+
+    /// get the due date.
+    pub fn due(&self) -> Option<DueDate> {
+        self.due
+    }
+
+    /// is_overdue returns whether the task is open and its due date has passed on the day.
+    pub fn is_overdue(&self, today: NaiveDate) -> bool {
+        !self.is_closed && self.due.is_some_and(|d| d.is_overdue(today))
+    }
+
+    /// set the due date, or clear it by None. Nothing happens if it does not change.
+    fn set_due_date(&mut self, due: Option<DueDate>) -> Result<()> {
+        if self.is_closed {
+            return Err(TaskError::AlreadyClosed.into());
+        }
+        if self.due == due {
+            return Ok(());
+        }
+        self.record_event(TaskDomainEvent::DueDateSet { due });
+        Ok(())
+    }
+
+    /// is_someday returns whether the task is deferred to someday, out of the active backlog.
+    pub fn is_someday(&self) -> bool {
+        self.is_someday
+    }
+
+    /// defer moves the task to someday. Nothing happens if it is already there.
+    fn defer(&mut self) -> Result<()> {
+        if self.is_closed {
+            return Err(TaskError::AlreadyClosed.into());
+        }
+        if self.is_someday {
+            return Ok(());
+        }
+        self.record_event(TaskDomainEvent::Deferred);
+        Ok(())
+    }
+
+    /// promote moves the task from someday into the active backlog. Nothing happens if it is not someday.
+    fn promote(&mut self) {
+        if !self.is_someday {
+            return;
+        }
+        self.record_event(TaskDomainEvent::Promoted);
+    }
+
+    /// is_deleted returns whether the task is deleted or archived, out of queries.
+    pub fn is_deleted(&self) -> bool {
+        self.deleted_at.is_some()
+    }
+
+    /// get deleted_at in UTC, when the task was deleted or archived.
+    pub fn deleted_at(&self) -> Option<NaiveDateTime> {
+        self.deleted_at
+    }
+
+    /// is_archived returns whether the task was archived after it had been closed.
+    pub fn is_archived(&self) -> bool {
+        self.is_archived
+    }
+
+    /// delete the task. A running timer is stopped first. Nothing happens if it is already deleted.
+    fn delete(&mut self) {
+        if self.is_deleted() {
+            return;
+        }
+        // NOTE: it fails only if no timer is running, which leaves nothing to stop.
+        let _ = self.stop_timer();
+        self.record_event(TaskDomainEvent::Deleted);
+    }
+
+    /// archive the closed task. Nothing happens if it is already deleted.
+    fn archive(&mut self) -> Result<()> {
+        if !self.is_closed {
+            return Err(TaskError::NotClosed.into());
+        }
+        if self.is_deleted() {
+            return Ok(());
+        }
+        self.record_event(TaskDomainEvent::Archived);
+        Ok(())
+    }
+
+    /// triage takes the task out of the inbox. Nothing happens if it is not in the inbox.
+    fn triage(&mut self) {
+        if !self.in_inbox {
+            return;
+        }
+        self.record_event(TaskDomainEvent::Triaged);
+    }
 }
 
 impl Entity for Task {
@@ -256,6 +1310,32 @@ impl Entity for Task {
 pub enum TaskError {
     #[error("the event cannot apply")]
     InvalidEvent,
+    #[error("`{0}` is not a valid tag, a tag must be non-empty without whitespace and commas")]
+    InvalidTag(String),
+    #[error(
+        "`{0}` is not a valid habit target such as 5/week, where the period is day, week or month"
+    )]
+    InvalidHabitTarget(String),
+    #[error("the task is not a habit")]
+    NotHabit,
+    #[error("a habit is never closed, only checked in")]
+    ClosedHabit,
+    #[error("the task is already closed")]
+    AlreadyClosed,
+    #[error("the task is not closed yet")]
+    NotClosed,
+    #[error("the timer of the task is already running")]
+    TimerRunning,
+    #[error("the timer of the task is not running")]
+    TimerNotRunning,
+    #[error("the person to wait on must not be empty")]
+    InvalidPerson,
+    #[error("`{0}` is not a valid id, an id is a positive integer")]
+    InvalidSequentialID(String),
+    #[error("`{1}` is not a valid {0}, it must be an integer")]
+    InvalidScore(&'static str, String),
+    #[error("`{0}` is not a valid due date, it must be YYYY-MM-DD")]
+    InvalidDueDate(String),
 }
 
 impl AggregateRoot for Task {
@@ -264,10 +1344,42 @@ impl AggregateRoot for Task {
 
     fn execute(&mut self, command: Self::Command) -> Result<()> {
         match command {
+            TaskCommand::Close if self.habit.is_some() => return Err(TaskError::ClosedHabit.into()),
             TaskCommand::Close => self.close(),
             TaskCommand::EditTitle { title } => self.edit_title(title),
+            TaskCommand::EditDescription { description } => self.edit_description(description),
             TaskCommand::RescoreCost { cost } => self.rescore_cost(cost),
             TaskCommand::RescorePriority { priority } => self.rescore_priority(priority),
+            TaskCommand::RescoreUrgency { urgency } => self.rescore_urgency(urgency),
+            TaskCommand::Tag { tag } => self.tag(tag),
+            TaskCommand::Untag { tag } => self.untag(tag),
+            TaskCommand::Lock => self.lock(),
+            TaskCommand::Unlock => self.unlock(),
+            TaskCommand::MakePrivate => self.make_private(),
+            TaskCommand::MakePublic => self.make_public(),
+            TaskCommand::LogTime { duration } => self.log_time(duration),
+            TaskCommand::StartTimer => self.start_timer()?,
+            TaskCommand::StopTimer => {
+                self.stop_timer()?;
+            }
+            TaskCommand::CompletePomodoro => self.complete_pomodoro()?,
+            TaskCommand::MakeHabit { target } => self.make_habit(target)?,
+            TaskCommand::CheckIn => self.check_in()?,
+            TaskCommand::WaitOn { waiting_on } => self.wait_on(waiting_on)?,
+            TaskCommand::StopWaiting => self.stop_waiting(),
+            TaskCommand::FollowUp { predecessor } => {
+                self.record_event(TaskDomainEvent::FollowedUp { predecessor })
+            }
+            TaskCommand::AssignParent { parent } => {
+                self.record_event(TaskDomainEvent::ParentAssigned { parent })
+            }
+            TaskCommand::Capture => self.capture()?,
+            TaskCommand::Triage => self.triage(),
+            TaskCommand::SetDueDate { due } => self.set_due_date(due)?,
+            TaskCommand::Defer => self.defer()?,
+            TaskCommand::Promote => self.promote(),
+            TaskCommand::Delete => self.delete(),
+            TaskCommand::Archive => self.archive()?,
         }
         Ok(())
     }
@@ -275,10 +1387,47 @@ impl AggregateRoot for Task {
     fn apply(&mut self, event: &Self::DomainEvent) {
         match event {
             TaskDomainEvent::Created { aggregate_id, .. } => self.aggregate_id = *aggregate_id,
-            TaskDomainEvent::Closed { .. } => self.is_closed = true,
+            TaskDomainEvent::Closed => self.is_closed = true,
             TaskDomainEvent::TitleEdited { title, .. } => title.clone_into(&mut self.title),
+            TaskDomainEvent::DescriptionEdited { description } => {
+                self.description = description.clone()
+            }
             TaskDomainEvent::CostRescored { cost, .. } => self.cost = *cost,
             TaskDomainEvent::PriorityRescored { priority, .. } => self.priority = *priority,
+            TaskDomainEvent::UrgencyRescored { urgency } => self.urgency = *urgency,
+            // NOTE: renamed tags can merge into a tag which the task already has.
+            TaskDomainEvent::Tagged { tag } => {
+                if !self.tags.contains(tag) {
+                    self.tags.push(tag.clone())
+                }
+            }
+            TaskDomainEvent::Untagged { tag } => self.tags.retain(|t| t != tag),
+            TaskDomainEvent::Locked => self.is_locked = true,
+            TaskDomainEvent::Unlocked => self.is_locked = false,
+            TaskDomainEvent::MadePrivate => self.is_private = true,
+            TaskDomainEvent::MadePublic => self.is_private = false,
+            TaskDomainEvent::TimeLogged { seconds } => {
+                self.elapsed_time += Duration::from_secs(*seconds)
+            }
+            TaskDomainEvent::TimerStarted => {}
+            TaskDomainEvent::TimerStopped { seconds } => {
+                self.elapsed_time += Duration::from_secs(*seconds);
+                self.timer_started_at = None;
+            }
+            TaskDomainEvent::PomodoroCompleted => self.pomodoros += 1,
+            TaskDomainEvent::MadeHabit { target } => self.habit = Some(*target),
+            TaskDomainEvent::CheckedIn => {}
+            TaskDomainEvent::WaitedOn { waiting_on } => self.waiting_on = Some(waiting_on.clone()),
+            TaskDomainEvent::StoppedWaiting => self.waiting_on = None,
+            TaskDomainEvent::FollowedUp { predecessor } => self.follows = Some(*predecessor),
+            TaskDomainEvent::ParentAssigned { parent } => self.parent = Some(*parent),
+            TaskDomainEvent::Captured => self.in_inbox = true,
+            TaskDomainEvent::Triaged => self.in_inbox = false,
+            TaskDomainEvent::DueDateSet { due } => self.due = *due,
+            TaskDomainEvent::Deferred => self.is_someday = true,
+            TaskDomainEvent::Promoted => self.is_someday = false,
+            TaskDomainEvent::Deleted => {}
+            TaskDomainEvent::Archived => self.is_archived = true,
         }
     }
 
@@ -293,6 +1442,7 @@ impl AggregateRoot for Task {
     fn record_event(&mut self, event: Self::DomainEvent) {
         self.apply(&event);
         let ee = DomainEventEnvelope::new(event, self.version, TASK_DOMAIN_EVENT_VERSION);
+        self.track_time(&ee);
         self.events.push(ee);
         self.increment_version();
     }
@@ -301,13 +1451,138 @@ impl AggregateRoot for Task {
 /// IESTaskRepository define interface of task repository.
 pub trait IESTaskRepository: Repository<Task> {
     /// issue_sequential_id issue SequentialID incremented from latest serial number.
+    /// Use save_new to add a task, otherwise a crash before saving leaves the id without events.
     fn issue_sequential_id(&self, aggregate_id: AggregateID) -> Result<SequentialID>;
 
-    /// load_by_sequential_id loads Task by sequential_id.
-    fn load_by_sequential_id(&self, sequential_id: SequentialID) -> Result<Option<Task>>;
+    /// save_new issues a SequentialID, builds the task by `create` and saves its events atomically.
+    /// `create` may be called more than once while the database is busy.
+    fn save_new(
+        &self,
+        aggregate_id: AggregateID,
+        create: &dyn Fn(SequentialID) -> Task,
+    ) -> Result<Task> {
+        self.save_new_with(aggregate_id, create, &mut [])
+    }
+
+    /// save_new_with is save_new saving events of the other tasks in the same transaction,
+    /// so that a task spawned by a change of another is saved with the change or not at all.
+    fn save_new_with(
+        &self,
+        aggregate_id: AggregateID,
+        create: &dyn Fn(SequentialID) -> Task,
+        others: &mut [Task],
+    ) -> Result<Task>;
+
+    /// save_all saves events of the tasks in one transaction, so that all or none of them are saved.
+    fn save_all(&self, tasks: &mut [Task]) -> Result<()>;
+
+    /// save_new_all is save_new for `count` tasks, which are saved all or none of them.
+    /// `create` builds the task of the index in `0..count` by the aggregate id and the issued
+    /// SequentialID. It is called for all of them again while the database is busy.
+    fn save_new_all(
+        &self,
+        count: usize,
+        create: &dyn Fn(usize, AggregateID, SequentialID) -> Task,
+    ) -> Result<()> {
+        for i in 0..count {
+            let aggregate_id = AggregateID::new();
+            self.save_new(aggregate_id, &|sequential_id| {
+                create(i, aggregate_id, sequential_id)
+            })?;
+        }
+        Ok(())
+    }
+
+    /// save_imported is save_new_all recording the checkpoint in the same transaction,
+    /// so that the checkpoint tells exactly which rows of the file are saved.
+    fn save_imported(
+        &self,
+        count: usize,
+        create: &dyn Fn(usize, AggregateID, SequentialID) -> Task,
+        checkpoint: &ImportCheckpoint,
+    ) -> Result<()>;
+
+    /// load_import_checkpoint returns the last line saved from the file of the digest,
+    /// if its import is unfinished.
+    fn load_import_checkpoint(&self, digest: &str) -> Result<Option<usize>>;
+
+    /// clear_import_checkpoint drops the checkpoint of the file of the digest, as its import is finished.
+    fn clear_import_checkpoint(&self, digest: &str) -> Result<()>;
+
+    /// load_by_sequential_id loads Task by sequential_id. Deleted tasks are not found.
+    fn load_by_sequential_id(&self, sequential_id: SequentialID) -> Result<Option<Task>> {
+        Ok(self
+            .load_any_by_sequential_id(sequential_id)?
+            .filter(|t| !t.is_deleted()))
+    }
+
+    /// load_any_by_sequential_id loads Task by sequential_id even if it is deleted.
+    fn load_any_by_sequential_id(&self, sequential_id: SequentialID) -> Result<Option<Task>>;
+
+    /// load_events loads events of the task as they were stored, in the order they occurred.
+    /// Tags are named as they were then, not renamed by the catalog.
+    fn load_events(
+        &self,
+        aggregate_id: AggregateID,
+    ) -> Result<Vec<DomainEventEnvelope<TaskDomainEvent>>>;
 
-    /// load_all_sequential_ids loads all sequential_ids.
+    /// load_all_sequential_ids loads all sequential_ids in ascending order.
     fn load_all_sequential_ids(&self) -> Result<Vec<SequentialID>>;
+
+    /// release_sequential_ids marks ids of deleted or archived tasks as free. The tasks keep the
+    /// ids until the reuse policy gives them to new tasks.
+    fn release_sequential_ids(&self, sequential_ids: &[SequentialID]) -> Result<()>;
+
+    /// load_children loads subtasks of the parent in the order of sequential ids.
+    fn load_children(&self, parent: SequentialID) -> Result<Vec<Task>> {
+        let mut children = vec![];
+        self.for_each_task(&mut |task| {
+            if task.parent() == Some(parent) {
+                children.push(task);
+            }
+            Ok(())
+        })?;
+        Ok(children)
+    }
+
+    /// search finds tasks having all words of the query as prefixes of their words in the field,
+    /// the best match first. Deleted tasks are not found.
+    fn search(&self, query: &str, field: SearchField) -> Result<Vec<SearchMatch>>;
+
+    /// for_each_read_model passes read models of tasks to `f` in the order of sequential ids.
+    /// Deleted tasks are passed only if `include_deleted` is true.
+    /// Repositories keeping a projection override it, so that events are not replayed.
+    fn for_each_read_model(
+        &self,
+        include_deleted: bool,
+        f: &mut dyn FnMut(TaskReadModel) -> Result<()>,
+    ) -> Result<()> {
+        let mut sequential_ids = self.load_all_sequential_ids()?;
+        sequential_ids.sort_by_key(|s| s.to_i64());
+        for sequential_id in sequential_ids {
+            match self.load_any_by_sequential_id(sequential_id)? {
+                Some(task) if include_deleted || !task.is_deleted() => {
+                    f(TaskReadModel::from(&task))?
+                }
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    /// for_each_task loads tasks one by one in the order of sequential ids and passes each to `f`,
+    /// so that only one task is in memory at a time. It stops at the first error of `f`.
+    /// Deleted tasks are left out.
+    fn for_each_task(&self, f: &mut dyn FnMut(Task) -> Result<()>) -> Result<()> {
+        let mut sequential_ids = self.load_all_sequential_ids()?;
+        sequential_ids.sort_by_key(|s| s.to_i64());
+        for sequential_id in sequential_ids {
+            if let Some(task) = self.load_by_sequential_id(sequential_id)? {
+                f(task)?;
+            }
+        }
+        Ok(())
+    }
 }
 
 /// RepositoryComponent returns Repository.
@@ -324,12 +1599,10 @@ pub trait IESTaskRepositoryComponent {
 mod tests {
     use super::*;
 
-    fn assert_events(got: &Vec<DomainEventEnvelope<TaskDomainEvent>>, want: &Vec<TaskDomainEvent>) {
-        let mut counter: i32 = 0;
-        for (g, w) in got.iter().zip(want.iter()) {
+    fn assert_events(got: &[DomainEventEnvelope<TaskDomainEvent>], want: &[TaskDomainEvent]) {
+        for (counter, (g, w)) in (0_i32..).zip(got.iter().zip(want.iter())) {
             assert_eq!(g.aggregate_version(), counter);
             assert_eq!(g.event(), w);
-            counter += 1;
         }
     }
 
@@ -356,11 +1629,12 @@ mod tests {
             TestCase {
                 name: String::from("with priority and cost"),
                 args: TaskSource {
-                    aggregate_id: aggregate_id.clone(),
+                    aggregate_id,
                     sequential_id: SequentialID::new(10),
                     title: String::from("title1"),
                     priority: Some(Priority(100)),
                     cost: Some(Cost(100)),
+                    tags: vec![],
                 },
                 want_state: TargetState {
                     title: "title1".into(),
@@ -369,7 +1643,7 @@ mod tests {
                 },
                 want_events: vec![
                     TaskDomainEvent::Created {
-                        aggregate_id: aggregate_id.clone(),
+                        aggregate_id,
                         sequential_id: SequentialID::new(10),
                     },
                     TaskDomainEvent::TitleEdited {
@@ -383,11 +1657,12 @@ mod tests {
             TestCase {
                 name: String::from("withtout priority and cost"),
                 args: TaskSource {
-                    aggregate_id: aggregate_id.clone(),
+                    aggregate_id,
                     sequential_id: SequentialID::new(10),
                     title: String::from("title2"),
                     priority: None,
                     cost: None,
+                    tags: vec![Tag::new("work").unwrap(), Tag::new("work").unwrap()],
                 },
                 want_state: TargetState {
                     title: "title2".into(),
@@ -396,12 +1671,15 @@ mod tests {
                 },
                 want_events: vec![
                     TaskDomainEvent::Created {
-                        aggregate_id: aggregate_id.clone(),
+                        aggregate_id,
                         sequential_id: SequentialID::new(10),
                     },
                     TaskDomainEvent::TitleEdited {
                         title: "title2".into(),
                     },
+                    TaskDomainEvent::Tagged {
+                        tag: Tag::new("work").unwrap(),
+                    },
                 ],
             },
         ];
@@ -460,7 +1738,7 @@ mod tests {
                 },
                 want_events: vec![
                     TaskDomainEvent::Created {
-                        aggregate_id: aggregate_id.clone(),
+                        aggregate_id,
                         sequential_id: SequentialID::new(10),
                     },
                     TaskDomainEvent::TitleEdited {
@@ -483,7 +1761,7 @@ mod tests {
                 },
                 want_events: vec![
                     TaskDomainEvent::Created {
-                        aggregate_id: aggregate_id.clone(),
+                        aggregate_id,
                         sequential_id: SequentialID::new(10),
                     },
                     TaskDomainEvent::TitleEdited {
@@ -508,7 +1786,7 @@ mod tests {
                 },
                 want_events: vec![
                     TaskDomainEvent::Created {
-                        aggregate_id: aggregate_id.clone(),
+                        aggregate_id,
                         sequential_id: SequentialID::new(10),
                     },
                     TaskDomainEvent::TitleEdited {
@@ -533,7 +1811,7 @@ mod tests {
                 },
                 want_events: vec![
                     TaskDomainEvent::Created {
-                        aggregate_id: aggregate_id.clone(),
+                        aggregate_id,
                         sequential_id: SequentialID::new(10),
                     },
                     TaskDomainEvent::TitleEdited {
@@ -548,11 +1826,12 @@ mod tests {
 
         for test_case in table {
             let mut task = Task::create(TaskSource {
-                aggregate_id: aggregate_id.clone(),
+                aggregate_id,
                 sequential_id: SequentialID::new(10),
                 title: TITLE.to_owned(),
                 priority: None,
                 cost: None,
+                tags: vec![],
             });
             task.execute(test_case.command).unwrap();
             let got_state = TargetState {
@@ -572,4 +1851,795 @@ mod tests {
             assert_events(task.events(), &test_case.want_events);
         }
     }
+
+    #[test]
+    fn test_tag() {
+        #[derive(Debug)]
+        struct TestCase {
+            commands: Vec<TaskCommand>,
+            want_tags: Vec<Tag>,
+            want_events: usize,
+            name: String,
+        }
+
+        let work = Tag::new("work").unwrap();
+        let home = Tag::new("home").unwrap();
+
+        let table = [
+            TestCase {
+                name: String::from("tag"),
+                commands: vec![TaskCommand::Tag { tag: home.clone() }],
+                want_tags: vec![work.clone(), home.clone()],
+                want_events: 4,
+            },
+            TestCase {
+                name: String::from("tag twice issues no event"),
+                commands: vec![TaskCommand::Tag { tag: work.clone() }],
+                want_tags: vec![work.clone()],
+                want_events: 3,
+            },
+            TestCase {
+                name: String::from("untag"),
+                commands: vec![
+                    TaskCommand::Untag { tag: work.clone() },
+                    TaskCommand::Untag { tag: home.clone() },
+                ],
+                want_tags: vec![],
+                want_events: 4,
+            },
+        ];
+
+        for test_case in table {
+            let mut task = Task::create(TaskSource {
+                aggregate_id: AggregateID::new(),
+                sequential_id: SequentialID::new(1),
+                title: String::from("title"),
+                priority: None,
+                cost: None,
+                tags: vec![work.clone()],
+            });
+            for command in test_case.commands {
+                task.execute(command).unwrap();
+            }
+
+            assert_eq!(
+                task.tags(),
+                test_case.want_tags.as_slice(),
+                "Failed in the \"{}\".",
+                test_case.name,
+            );
+            assert_eq!(
+                task.events().len(),
+                test_case.want_events,
+                "Failed in the \"{}\".",
+                test_case.name,
+            );
+        }
+    }
+
+    #[test]
+    fn test_lock() {
+        #[derive(Debug)]
+        struct TestCase {
+            commands: Vec<TaskCommand>,
+            want_locked: bool,
+            want_events: usize,
+            name: String,
+        }
+
+        let table = [
+            TestCase {
+                name: String::from("lock"),
+                commands: vec![TaskCommand::Lock],
+                want_locked: true,
+                want_events: 3,
+            },
+            TestCase {
+                name: String::from("lock twice issues no event"),
+                commands: vec![TaskCommand::Lock, TaskCommand::Lock],
+                want_locked: true,
+                want_events: 3,
+            },
+            TestCase {
+                name: String::from("unlock"),
+                commands: vec![TaskCommand::Lock, TaskCommand::Unlock],
+                want_locked: false,
+                want_events: 4,
+            },
+            TestCase {
+                name: String::from("unlock an unlocked task issues no event"),
+                commands: vec![TaskCommand::Unlock],
+                want_locked: false,
+                want_events: 2,
+            },
+        ];
+
+        for test_case in table {
+            let mut task = Task::create(TaskSource {
+                aggregate_id: AggregateID::new(),
+                sequential_id: SequentialID::new(1),
+                title: String::from("title"),
+                priority: None,
+                cost: None,
+                tags: vec![],
+            });
+            for command in test_case.commands {
+                task.execute(command).unwrap();
+            }
+
+            assert_eq!(
+                task.is_locked(),
+                test_case.want_locked,
+                "Failed in the \"{}\".",
+                test_case.name,
+            );
+            assert_eq!(
+                task.events().len(),
+                test_case.want_events,
+                "Failed in the \"{}\".",
+                test_case.name,
+            );
+
+            let events = task
+                .events()
+                .iter()
+                .map(|e| serde_json::from_str(&serde_json::to_string(e).unwrap()).unwrap())
+                .collect();
+            let recreated = Task::recreate(task.aggregate_id(), task.sequential_id(), events);
+            assert_eq!(
+                recreated.is_locked(),
+                test_case.want_locked,
+                "Failed in the \"{}\".",
+                test_case.name,
+            );
+        }
+    }
+
+    #[test]
+    fn test_score() {
+        #[derive(Debug)]
+        struct TestCase {
+            args: (ScoreWeights, Option<Urgency>),
+            want: i64,
+            name: String,
+        }
+
+        let table = [
+            TestCase {
+                name: String::from("normal: default weights and urgency"),
+                args: (ScoreWeights::default(), None),
+                want: 30,
+            },
+            TestCase {
+                name: String::from("normal: rescored urgency"),
+                args: (ScoreWeights::default(), Some(Urgency::new(5))),
+                want: 25,
+            },
+            TestCase {
+                name: String::from("normal: weighted"),
+                args: (
+                    ScoreWeights {
+                        importance: 2,
+                        urgency: 3,
+                    },
+                    Some(Urgency::new(5)),
+                ),
+                want: 55,
+            },
+        ];
+
+        for test_case in table {
+            let (weights, urgency) = test_case.args;
+            let mut task = Task::create(TaskSource {
+                aggregate_id: AggregateID::new(),
+                sequential_id: SequentialID::new(1),
+                title: String::from("title"),
+                priority: Some(Priority::new(20)),
+                cost: None,
+                tags: vec![],
+            });
+            if let Some(urgency) = urgency {
+                task.execute(TaskCommand::RescoreUrgency { urgency })
+                    .unwrap();
+            }
+
+            assert_eq!(
+                task.score(&weights),
+                test_case.want,
+                "Failed in the \"{}\".",
+                test_case.name
+            );
+        }
+    }
+
+    #[test]
+    fn test_new_tag() {
+        assert!(Tag::new("project-x").is_ok());
+        assert!(Tag::new("repo:taskmr").is_ok());
+        assert!(Tag::new("").is_err());
+        assert!(Tag::new("a b").is_err());
+        assert!(Tag::new("a,b").is_err());
+    }
+
+    #[test]
+    fn test_closed_at() {
+        let mut task = Task::create(TaskSource {
+            aggregate_id: AggregateID::new(),
+            sequential_id: SequentialID::new(1),
+            title: String::from("title"),
+            priority: None,
+            cost: None,
+            tags: vec![],
+        });
+        assert_eq!(task.closed_at(), None);
+
+        task.execute(TaskCommand::Close).unwrap();
+        let closed_at = task.closed_at();
+        assert!(closed_at.is_some());
+
+        let events = task
+            .events()
+            .iter()
+            .map(|e| serde_json::from_str(&serde_json::to_string(e).unwrap()).unwrap())
+            .collect();
+        let recreated = Task::recreate(task.aggregate_id(), task.sequential_id(), events);
+        assert_eq!(recreated.closed_at(), closed_at);
+    }
+
+    #[test]
+    fn test_log_time() {
+        let mut task = Task::create(TaskSource {
+            aggregate_id: AggregateID::new(),
+            sequential_id: SequentialID::new(1),
+            title: String::from("title"),
+            priority: None,
+            cost: None,
+            tags: vec![],
+        });
+        task.execute(TaskCommand::LogTime {
+            duration: Duration::from_secs(1800),
+        })
+        .unwrap();
+        task.execute(TaskCommand::LogTime {
+            duration: Duration::from_secs(3600),
+        })
+        .unwrap();
+        assert_eq!(task.elapsed_time(), Duration::from_secs(5400));
+        assert_eq!(task.time_logs().len(), 2);
+
+        let events = task
+            .events()
+            .iter()
+            .map(|e| serde_json::from_str(&serde_json::to_string(e).unwrap()).unwrap())
+            .collect();
+        let recreated = Task::recreate(task.aggregate_id(), task.sequential_id(), events);
+        assert_eq!(recreated.elapsed_time(), Duration::from_secs(5400));
+        assert_eq!(recreated.time_logs(), task.time_logs());
+    }
+
+    #[test]
+    fn test_timer() {
+        let mut task = Task::create(TaskSource {
+            aggregate_id: AggregateID::new(),
+            sequential_id: SequentialID::new(1),
+            title: String::from("title"),
+            priority: None,
+            cost: None,
+            tags: vec![],
+        });
+        assert!(task.execute(TaskCommand::StopTimer).is_err());
+
+        task.execute(TaskCommand::StartTimer).unwrap();
+        let started_at = chrono::Utc::now().naive_utc() - chrono::Duration::minutes(25);
+        task.backdate_last_event(started_at);
+        assert_eq!(task.timer_started_at(), Some(started_at));
+        assert!(task.execute(TaskCommand::StartTimer).is_err());
+
+        task.execute(TaskCommand::StopTimer).unwrap();
+        assert_eq!(task.timer_started_at(), None);
+        assert_eq!(task.elapsed_time().as_secs() / 60, 25);
+        assert_eq!(task.time_logs().len(), 1);
+
+        task.execute(TaskCommand::StartTimer).unwrap();
+        task.backdate_last_event(chrono::Utc::now().naive_utc() - chrono::Duration::minutes(5));
+        task.execute(TaskCommand::Close).unwrap();
+        assert_eq!(task.timer_started_at(), None);
+        assert_eq!(task.elapsed_time().as_secs() / 60, 30);
+        assert!(task.execute(TaskCommand::StartTimer).is_err());
+
+        let events = task
+            .events()
+            .iter()
+            .map(|e| serde_json::from_str(&serde_json::to_string(e).unwrap()).unwrap())
+            .collect();
+        let recreated = Task::recreate(task.aggregate_id(), task.sequential_id(), events);
+        assert_eq!(recreated.elapsed_time(), task.elapsed_time());
+        assert_eq!(recreated.time_logs(), task.time_logs());
+        assert_eq!(recreated.timer_started_at(), None);
+    }
+
+    #[test]
+    fn test_complete_pomodoro() {
+        let mut task = Task::create(TaskSource {
+            aggregate_id: AggregateID::new(),
+            sequential_id: SequentialID::new(1),
+            title: String::from("title"),
+            priority: None,
+            cost: None,
+            tags: vec![],
+        });
+        assert!(task.execute(TaskCommand::CompletePomodoro).is_err());
+
+        task.execute(TaskCommand::StartTimer).unwrap();
+        task.backdate_last_event(chrono::Utc::now().naive_utc() - chrono::Duration::minutes(25));
+        task.execute(TaskCommand::CompletePomodoro).unwrap();
+        assert_eq!(task.pomodoros(), 1);
+        assert_eq!(task.timer_started_at(), None);
+        assert_eq!(task.elapsed_time().as_secs() / 60, 25);
+
+        let events = task
+            .events()
+            .iter()
+            .map(|e| serde_json::from_str(&serde_json::to_string(e).unwrap()).unwrap())
+            .collect();
+        let recreated = Task::recreate(task.aggregate_id(), task.sequential_id(), events);
+        assert_eq!(recreated.pomodoros(), 1);
+    }
+
+    #[test]
+    fn test_habit_target_from_str() {
+        #[derive(Debug)]
+        struct TestCase {
+            args: &'static str,
+            want: Option<(u32, HabitPeriod)>,
+            name: String,
+        }
+
+        let table = [
+            TestCase {
+                name: String::from("normal: week"),
+                args: "5/week",
+                want: Some((5, HabitPeriod::Week)),
+            },
+            TestCase {
+                name: String::from("normal: day"),
+                args: "1/day",
+                want: Some((1, HabitPeriod::Day)),
+            },
+            TestCase {
+                name: String::from("abnormal: zero"),
+                args: "0/week",
+                want: None,
+            },
+            TestCase {
+                name: String::from("abnormal: unknown period"),
+                args: "5/fortnight",
+                want: None,
+            },
+            TestCase {
+                name: String::from("abnormal: no period"),
+                args: "5",
+                want: None,
+            },
+        ];
+
+        for test_case in table {
+            let got = HabitTarget::from_str(test_case.args)
+                .ok()
+                .map(|t| (t.times(), t.period()));
+            assert_eq!(got, test_case.want, "Failed in the \"{}\".", test_case.name);
+        }
+    }
+
+    #[test]
+    fn test_sequential_id_from_str() {
+        #[derive(Debug)]
+        struct TestCase {
+            args: &'static str,
+            want: Option<SequentialID>,
+            name: String,
+        }
+
+        let table = [
+            TestCase {
+                name: String::from("normal: positive"),
+                args: "12",
+                want: Some(SequentialID::new(12)),
+            },
+            TestCase {
+                name: String::from("abnormal: zero"),
+                args: "0",
+                want: None,
+            },
+            TestCase {
+                name: String::from("abnormal: word"),
+                args: "abc",
+                want: None,
+            },
+        ];
+
+        for test_case in table {
+            let got = SequentialID::from_str(test_case.args).ok();
+            assert_eq!(got, test_case.want, "Failed in the \"{}\".", test_case.name);
+            if let Some(id) = got {
+                assert_eq!(id.to_string(), test_case.args);
+            }
+        }
+    }
+
+    #[test]
+    fn test_score_conversions() {
+        assert_eq!(Priority::from_str("-3").unwrap(), Priority::new(-3));
+        assert_eq!(Cost::try_from(8_i64).unwrap(), Cost::new(8));
+        assert_eq!(Urgency::new(20).to_string(), "20");
+        assert_eq!(
+            Cost::from_str("big").unwrap_err().to_string(),
+            "`big` is not a valid cost, it must be an integer"
+        );
+        assert!(Priority::try_from(i64::MAX).is_err());
+    }
+
+    #[test]
+    fn test_arithmetic() {
+        assert_eq!(Cost::new(3) + Cost::new(5), Cost::new(8));
+        assert_eq!(Cost::new(3) - Cost::new(5), Cost::new(0));
+        assert_eq!(Cost::new(i32::MAX) + Cost::new(1), Cost::new(i32::MAX));
+        assert_eq!(Priority::new(3) - Priority::new(5), Priority::new(-2));
+        assert_eq!(
+            Priority::new(i32::MIN) - Priority::new(1),
+            Priority::new(i32::MIN)
+        );
+
+        assert_eq!(Priority::new(10).scale_percent(150), Priority::new(15));
+        assert_eq!(Cost::new(5).scale_percent(50), Cost::new(2));
+        assert_eq!(Cost::new(i32::MAX).scale_percent(200), Cost::new(i32::MAX));
+
+        assert!(Priority::new(20) > Priority::new(10));
+        assert_eq!(
+            [Cost::new(1), Cost::new(2), Cost::new(3)]
+                .into_iter()
+                .sum::<Cost>(),
+            Cost::new(6)
+        );
+    }
+
+    #[test]
+    fn test_energy_of() {
+        #[derive(Debug)]
+        struct TestCase {
+            args: Vec<&'static str>,
+            want: Option<Energy>,
+            name: String,
+        }
+
+        let table = [
+            TestCase {
+                name: String::from("normal: deep"),
+                args: vec!["work", "energy:deep"],
+                want: Some(Energy::Deep),
+            },
+            TestCase {
+                name: String::from("normal: the highest wins"),
+                args: vec!["energy:shallow", "energy:deep"],
+                want: Some(Energy::Deep),
+            },
+            TestCase {
+                name: String::from("normal: not marked"),
+                args: vec!["energy"],
+                want: None,
+            },
+        ];
+
+        for test_case in table {
+            let tags: Vec<Tag> = test_case
+                .args
+                .iter()
+                .map(|t| Tag::new(t).unwrap())
+                .collect();
+            assert_eq!(
+                Energy::of(&tags),
+                test_case.want,
+                "Failed in the \"{}\".",
+                test_case.name
+            );
+        }
+    }
+
+    #[test]
+    fn test_habit_period_start() {
+        #[derive(Debug)]
+        struct TestCase {
+            args: HabitPeriod,
+            want: &'static str,
+            name: String,
+        }
+
+        let table = [
+            TestCase {
+                name: String::from("normal: day"),
+                args: HabitPeriod::Day,
+                want: "2024-05-16 00:00:00",
+            },
+            TestCase {
+                name: String::from("normal: week from monday"),
+                args: HabitPeriod::Week,
+                want: "2024-05-13 00:00:00",
+            },
+            TestCase {
+                name: String::from("normal: month"),
+                args: HabitPeriod::Month,
+                want: "2024-05-01 00:00:00",
+            },
+        ];
+
+        let at = NaiveDateTime::parse_from_str("2024-05-16 13:14:15", "%Y-%m-%d %H:%M:%S").unwrap();
+        for test_case in table {
+            assert_eq!(
+                test_case.args.start(at).to_string(),
+                test_case.want,
+                "Failed in the \"{}\".",
+                test_case.name
+            );
+        }
+    }
+
+    #[test]
+    fn test_habit() {
+        let mut task = Task::create(TaskSource {
+            aggregate_id: AggregateID::new(),
+            sequential_id: SequentialID::new(1),
+            title: String::from("stretch"),
+            priority: None,
+            cost: None,
+            tags: vec![],
+        });
+        assert!(task.execute(TaskCommand::CheckIn).is_err());
+
+        let target = HabitTarget::new(5, HabitPeriod::Week).unwrap();
+        task.execute(TaskCommand::MakeHabit { target }).unwrap();
+        task.execute(TaskCommand::CheckIn).unwrap();
+        task.execute(TaskCommand::CheckIn).unwrap();
+        assert!(task.execute(TaskCommand::Close).is_err());
+        assert!(!task.is_closed());
+
+        let events = task
+            .events()
+            .iter()
+            .map(|e| serde_json::from_str(&serde_json::to_string(e).unwrap()).unwrap())
+            .collect();
+        let recreated = Task::recreate(task.aggregate_id(), task.sequential_id(), events);
+        assert_eq!(recreated.habit(), Some(target));
+        assert_eq!(recreated.check_ins(), task.check_ins());
+        assert_eq!(recreated.check_ins_since(NaiveDateTime::default()), 2);
+        let later = chrono::Utc::now().naive_utc() + chrono::Duration::hours(1);
+        assert_eq!(recreated.check_ins_since(later), 0);
+    }
+
+    #[test]
+    fn test_waiting_on() {
+        let mut task = Task::create(TaskSource {
+            aggregate_id: AggregateID::new(),
+            sequential_id: SequentialID::new(1),
+            title: String::from("review the contract"),
+            priority: None,
+            cost: None,
+            tags: vec![],
+        });
+        assert!(WaitingOn::new("  ", None).is_err());
+
+        let follow_up_on = NaiveDate::from_ymd_opt(2024, 5, 10).unwrap();
+        let waiting_on = WaitingOn::new(" Alice ", Some(follow_up_on)).unwrap();
+        assert_eq!(waiting_on.person(), "Alice");
+        assert!(!waiting_on.is_due(NaiveDate::from_ymd_opt(2024, 5, 9).unwrap()));
+        assert!(waiting_on.is_due(follow_up_on));
+
+        task.execute(TaskCommand::WaitOn {
+            waiting_on: waiting_on.clone(),
+        })
+        .unwrap();
+        let version = task.events().len();
+        task.execute(TaskCommand::WaitOn {
+            waiting_on: waiting_on.clone(),
+        })
+        .unwrap();
+        assert_eq!(task.events().len(), version);
+
+        let events = task
+            .events()
+            .iter()
+            .map(|e| serde_json::from_str(&serde_json::to_string(e).unwrap()).unwrap())
+            .collect();
+        let recreated = Task::recreate(task.aggregate_id(), task.sequential_id(), events);
+        assert_eq!(recreated.waiting_on(), Some(&waiting_on));
+
+        task.execute(TaskCommand::StopWaiting).unwrap();
+        assert_eq!(task.waiting_on(), None);
+        task.execute(TaskCommand::Close).unwrap();
+        assert!(task.execute(TaskCommand::WaitOn { waiting_on }).is_err());
+    }
+
+    #[test]
+    fn test_inbox() {
+        let mut task = Task::create(TaskSource {
+            aggregate_id: AggregateID::new(),
+            sequential_id: SequentialID::new(1),
+            title: String::from("call the dentist?"),
+            priority: None,
+            cost: None,
+            tags: vec![],
+        });
+        task.execute(TaskCommand::Capture).unwrap();
+        let version = task.events().len();
+        task.execute(TaskCommand::Capture).unwrap();
+        assert_eq!(task.events().len(), version);
+
+        let events = task
+            .events()
+            .iter()
+            .map(|e| serde_json::from_str(&serde_json::to_string(e).unwrap()).unwrap())
+            .collect();
+        let recreated = Task::recreate(task.aggregate_id(), task.sequential_id(), events);
+        assert!(recreated.is_in_inbox());
+
+        task.execute(TaskCommand::Triage).unwrap();
+        assert!(!task.is_in_inbox());
+        task.execute(TaskCommand::Triage).unwrap();
+        assert_eq!(task.events().len(), version + 1);
+        task.execute(TaskCommand::Close).unwrap();
+        assert!(task.execute(TaskCommand::Capture).is_err());
+    }
+
+    #[test]
+    fn test_someday() {
+        let mut task = Task::create(TaskSource {
+            aggregate_id: AggregateID::new(),
+            sequential_id: SequentialID::new(1),
+            title: String::from("learn the cello"),
+            priority: None,
+            cost: None,
+            tags: vec![],
+        });
+        task.execute(TaskCommand::Defer).unwrap();
+        let version = task.events().len();
+        task.execute(TaskCommand::Defer).unwrap();
+        assert_eq!(task.events().len(), version);
+
+        let events = task
+            .events()
+            .iter()
+            .map(|e| serde_json::from_str(&serde_json::to_string(e).unwrap()).unwrap())
+            .collect();
+        let recreated = Task::recreate(task.aggregate_id(), task.sequential_id(), events);
+        assert!(recreated.is_someday());
+
+        task.execute(TaskCommand::Promote).unwrap();
+        assert!(!task.is_someday());
+        task.execute(TaskCommand::Promote).unwrap();
+        assert_eq!(task.events().len(), version + 1);
+        task.execute(TaskCommand::Close).unwrap();
+        assert!(task.execute(TaskCommand::Defer).is_err());
+    }
+
+    #[test]
+    fn test_due_date() {
+        let mut task = Task::create(TaskSource {
+            aggregate_id: AggregateID::new(),
+            sequential_id: SequentialID::new(1),
+            title: String::from("file the tax return"),
+            priority: None,
+            cost: None,
+            tags: vec![],
+        });
+        assert!("2024-02-30".parse::<DueDate>().is_err());
+        let due: DueDate = "2024-05-10".parse().unwrap();
+        assert_eq!(due.to_string(), "2024-05-10");
+
+        task.execute(TaskCommand::SetDueDate { due: Some(due) })
+            .unwrap();
+        let version = task.events().len();
+        task.execute(TaskCommand::SetDueDate { due: Some(due) })
+            .unwrap();
+        assert_eq!(task.events().len(), version);
+        assert!(!task.is_overdue(due.to_naive_date()));
+        assert!(task.is_overdue(due.to_naive_date().succ_opt().unwrap()));
+
+        let events = task
+            .events()
+            .iter()
+            .map(|e| serde_json::from_str(&serde_json::to_string(e).unwrap()).unwrap())
+            .collect();
+        let recreated = Task::recreate(task.aggregate_id(), task.sequential_id(), events);
+        assert_eq!(recreated.due(), Some(due));
+
+        task.execute(TaskCommand::SetDueDate { due: None }).unwrap();
+        assert_eq!(task.due(), None);
+        task.execute(TaskCommand::Close).unwrap();
+        assert!(task
+            .execute(TaskCommand::SetDueDate { due: Some(due) })
+            .is_err());
+    }
+
+    #[test]
+    fn test_description() {
+        let mut task = Task::create(TaskSource {
+            aggregate_id: AggregateID::new(),
+            sequential_id: SequentialID::new(1),
+            title: String::from("fix login bug"),
+            priority: None,
+            cost: None,
+            tags: vec![],
+        });
+        let description = String::from("Steps:\n1. sign in twice\n2. see the error");
+        task.execute(TaskCommand::EditDescription {
+            description: Some(description.clone()),
+        })
+        .unwrap();
+        let version = task.events().len();
+        task.execute(TaskCommand::EditDescription {
+            description: Some(description.clone()),
+        })
+        .unwrap();
+        assert_eq!(task.events().len(), version);
+
+        let events = task
+            .events()
+            .iter()
+            .map(|e| serde_json::from_str(&serde_json::to_string(e).unwrap()).unwrap())
+            .collect();
+        let recreated = Task::recreate(task.aggregate_id(), task.sequential_id(), events);
+        assert_eq!(recreated.description(), Some(description.as_str()));
+
+        task.execute(TaskCommand::EditDescription {
+            description: Some(String::from(" \n")),
+        })
+        .unwrap();
+        assert_eq!(task.description(), None);
+        assert_eq!(task.events().len(), version + 1);
+    }
+
+    #[test]
+    fn test_import() {
+        let created_at =
+            NaiveDateTime::parse_from_str("2020-01-02 03:04:05", "%Y-%m-%d %H:%M:%S").unwrap();
+        let closed_at =
+            NaiveDateTime::parse_from_str("2020-02-03 04:05:06", "%Y-%m-%d %H:%M:%S").unwrap();
+        let task = Task::import(
+            TaskSource {
+                aggregate_id: AggregateID::new(),
+                sequential_id: SequentialID::new(1),
+                title: String::from("title"),
+                priority: Some(Priority::new(20)),
+                cost: Some(Cost::new(3)),
+                tags: vec![],
+            },
+            created_at,
+            Some(closed_at),
+        );
+
+        assert_events(
+            task.events(),
+            &[
+                TaskDomainEvent::Created {
+                    aggregate_id: task.aggregate_id(),
+                    sequential_id: SequentialID::new(1),
+                },
+                TaskDomainEvent::TitleEdited {
+                    title: String::from("title"),
+                },
+                TaskDomainEvent::PriorityRescored {
+                    priority: Priority::new(20),
+                },
+                TaskDomainEvent::CostRescored { cost: Cost::new(3) },
+                TaskDomainEvent::Closed,
+            ],
+        );
+        let occurred_on: Vec<NaiveDateTime> =
+            task.events().iter().map(|e| e.occurred_on()).collect();
+        assert_eq!(
+            occurred_on,
+            vec![created_at, created_at, created_at, created_at, closed_at]
+        );
+        assert!(task.is_closed());
+        assert_eq!(task.closed_at(), Some(closed_at));
+    }
 }
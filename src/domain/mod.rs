@@ -2,5 +2,14 @@
 //!
 //! domain is a layer which has business rules that are the most important parts of this system.
 
+#[cfg(feature = "es")]
+pub mod es_tag;
+#[cfg(feature = "es")]
 pub mod es_task;
+#[cfg(feature = "es")]
+pub mod scoring;
+#[cfg(feature = "es")]
+pub mod sprint;
+#[cfg(feature = "legacy")]
 pub mod task;
+pub mod task_builder;
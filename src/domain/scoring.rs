@@ -0,0 +1,168 @@
+//! # scoring
+//!
+//! scoring is a domain service which orders tasks by a score, the highest to be done first.
+//! Strategies are chosen by the config, and embedders may implement their own.
+
+use std::fmt;
+
+use chrono::NaiveDate;
+
+use crate::domain::es_task::{Cost, DueDate, Priority, ScoreWeights, Task, Urgency};
+
+/// TaskView is what a scoring strategy sees of a task.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TaskView {
+    pub priority: Priority,
+    pub urgency: Urgency,
+    pub cost: Cost,
+    /// due_on is the day by which the task should be closed, if any.
+    pub due_on: Option<NaiveDate>,
+}
+
+impl From<&Task> for TaskView {
+    fn from(task: &Task) -> Self {
+        TaskView {
+            priority: task.priority(),
+            urgency: task.urgency(),
+            cost: task.cost(),
+            due_on: task.due().map(DueDate::to_naive_date),
+        }
+    }
+}
+
+/// ScoringStrategy scores a task. A higher score is to be done earlier.
+pub trait ScoringStrategy: fmt::Debug {
+    fn score(&self, task: &TaskView) -> f64;
+}
+
+/// ScoreWeights scores the weighted sum of the importance and the urgency.
+impl ScoringStrategy for ScoreWeights {
+    fn score(&self, task: &TaskView) -> f64 {
+        ScoreWeights::score(self, task.priority, task.urgency) as f64
+    }
+}
+
+/// job_size is the cost as the divisor of ratios. Costs below 1 count as 1.
+fn job_size(cost: Cost) -> f64 {
+    f64::from(cost.to_i32().max(1))
+}
+
+/// PriorityPerCost scores the priority per cost, so that cheap important tasks come first.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PriorityPerCost;
+
+impl ScoringStrategy for PriorityPerCost {
+    fn score(&self, task: &TaskView) -> f64 {
+        f64::from(task.priority.to_i32()) / job_size(task.cost)
+    }
+}
+
+/// WeightedShortestJobFirst scores the cost of delay, the priority plus the urgency, per cost.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct WeightedShortestJobFirst;
+
+impl ScoringStrategy for WeightedShortestJobFirst {
+    fn score(&self, task: &TaskView) -> f64 {
+        let cost_of_delay = i64::from(task.priority.to_i32()) + i64::from(task.urgency.to_i32());
+        cost_of_delay as f64 / job_size(task.cost)
+    }
+}
+
+/// DeadlineAware scores by the weights, raising tasks as their due dates come near.
+/// The score is doubled on the due date and after it, and is 1.5 times the day before.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DeadlineAware {
+    pub weights: ScoreWeights,
+    pub today: NaiveDate,
+}
+
+impl ScoringStrategy for DeadlineAware {
+    fn score(&self, task: &TaskView) -> f64 {
+        let score = ScoringStrategy::score(&self.weights, task);
+        match task.due_on {
+            Some(due_on) => {
+                let days_left = (due_on - self.today).num_days().max(0);
+                score * (1.0 + 1.0 / (days_left + 1) as f64)
+            }
+            None => score,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn view(priority: i32, urgency: i32, cost: i32, due_on: Option<NaiveDate>) -> TaskView {
+        TaskView {
+            priority: Priority::new(priority),
+            urgency: Urgency::new(urgency),
+            cost: Cost::new(cost),
+            due_on,
+        }
+    }
+
+    #[test]
+    fn test_score() {
+        let today = NaiveDate::from_ymd_opt(2024, 5, 10).unwrap();
+        let deadline = DeadlineAware {
+            weights: ScoreWeights::default(),
+            today,
+        };
+
+        #[derive(Debug)]
+        struct TestCase {
+            args: (Box<dyn ScoringStrategy>, TaskView),
+            want: f64,
+            name: String,
+        }
+
+        let table = [
+            TestCase {
+                name: String::from("normal: weights"),
+                args: (Box::new(ScoreWeights::default()), view(10, 20, 5, None)),
+                want: 30.0,
+            },
+            TestCase {
+                name: String::from("normal: priority per cost"),
+                args: (Box::new(PriorityPerCost), view(10, 20, 4, None)),
+                want: 2.5,
+            },
+            TestCase {
+                name: String::from("normal: cost below 1 counts as 1"),
+                args: (Box::new(PriorityPerCost), view(10, 20, 0, None)),
+                want: 10.0,
+            },
+            TestCase {
+                name: String::from("normal: weighted shortest job first"),
+                args: (Box::new(WeightedShortestJobFirst), view(10, 20, 5, None)),
+                want: 6.0,
+            },
+            TestCase {
+                name: String::from("normal: deadline without due date"),
+                args: (Box::new(deadline), view(10, 20, 5, None)),
+                want: 30.0,
+            },
+            TestCase {
+                name: String::from("normal: deadline tomorrow"),
+                args: (Box::new(deadline), view(10, 20, 5, today.succ_opt())),
+                want: 45.0,
+            },
+            TestCase {
+                name: String::from("normal: deadline passed"),
+                args: (Box::new(deadline), view(10, 20, 5, today.pred_opt())),
+                want: 60.0,
+            },
+        ];
+
+        for test_case in table {
+            let (strategy, task) = test_case.args;
+            assert_eq!(
+                strategy.score(&task),
+                test_case.want,
+                "Failed in the \"{}\".",
+                test_case.name
+            );
+        }
+    }
+}
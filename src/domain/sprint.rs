@@ -0,0 +1,368 @@
+use std::str::FromStr;
+
+use crate::Result;
+use chrono::{Duration, NaiveDate};
+use thiserror::Error;
+
+use crate::ddd::component::{Entity, ValueObject};
+use crate::domain::es_task::{Cost, SequentialID};
+
+/// SprintError is raised when a sprint rule is broken.
+#[derive(Error, Debug, PartialEq, Eq)]
+pub enum SprintError {
+    #[error("sprint name `{0}` must be non-empty and must not contain whitespace")]
+    InvalidName(String),
+    #[error(
+        "sprint length `{0}` must be a positive number of days or weeks such as `10d` or `2w`"
+    )]
+    InvalidLength(String),
+    #[error("committed cost {committed} exceeds the capacity {capacity}")]
+    OverCapacity { committed: i32, capacity: i32 },
+    #[error("sprint `{0}` has no tasks")]
+    Empty(String),
+    #[error("sprint `{0}` has already been closed")]
+    AlreadyClosed(String),
+}
+
+/// Sprint Name.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SprintName(String);
+
+impl SprintName {
+    /// construct a sprint name.
+    pub fn new(name: &str) -> Result<Self, SprintError> {
+        if name.is_empty() || name.chars().any(char::is_whitespace) {
+            return Err(SprintError::InvalidName(name.to_owned()));
+        }
+        Ok(SprintName(name.to_owned()))
+    }
+
+    /// get a sprint name as str.
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl ValueObject for SprintName {}
+
+/// Sprint Length in days.
+/// It is written as `<n>d` or `<n>w`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SprintLength(u32);
+
+impl SprintLength {
+    /// construct a sprint length.
+    pub fn from_days(days: u32) -> Self {
+        SprintLength(days)
+    }
+
+    /// get the length in days.
+    pub fn days(&self) -> u32 {
+        self.0
+    }
+}
+
+impl FromStr for SprintLength {
+    type Err = SprintError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || SprintError::InvalidLength(s.to_owned());
+        let unit = s.chars().last().ok_or_else(invalid)?;
+        let n: u32 = s[..s.len() - unit.len_utf8()]
+            .parse()
+            .map_err(|_| invalid())?;
+        let days = match unit {
+            'd' => n,
+            'w' => n.checked_mul(7).ok_or_else(invalid)?,
+            _ => return Err(invalid()),
+        };
+        if days == 0 {
+            return Err(invalid());
+        }
+        Ok(SprintLength(days))
+    }
+}
+
+impl ValueObject for SprintLength {}
+
+/// SprintTask is a task committed to the sprint with its cost at that time.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SprintTask {
+    pub sequential_id: SequentialID,
+    pub cost: Cost,
+}
+
+/// Sprint is a time box which a selection of tasks is committed to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Sprint {
+    name: SprintName,
+    start_on: NaiveDate,
+    length: SprintLength,
+    capacity: Cost,
+    tasks: Vec<SprintTask>,
+    closed_on: Option<NaiveDate>,
+}
+
+impl Sprint {
+    /// create a Sprint.
+    /// It fails if the committed cost exceeds the capacity, unless `allow_over_capacity`.
+    pub fn create(
+        name: SprintName,
+        start_on: NaiveDate,
+        length: SprintLength,
+        capacity: Cost,
+        tasks: Vec<SprintTask>,
+        allow_over_capacity: bool,
+    ) -> Result<Sprint, SprintError> {
+        if tasks.is_empty() {
+            return Err(SprintError::Empty(name.as_str().to_owned()));
+        }
+
+        let sprint = Sprint::recreate(name, start_on, length, capacity, tasks, None);
+        let committed = sprint.committed_cost();
+        if committed > capacity && !allow_over_capacity {
+            return Err(SprintError::OverCapacity {
+                committed: committed.to_i32(),
+                capacity: capacity.to_i32(),
+            });
+        }
+
+        Ok(sprint)
+    }
+
+    /// reconstruct the Sprint from stored values.
+    pub fn recreate(
+        name: SprintName,
+        start_on: NaiveDate,
+        length: SprintLength,
+        capacity: Cost,
+        tasks: Vec<SprintTask>,
+        closed_on: Option<NaiveDate>,
+    ) -> Sprint {
+        Sprint {
+            name,
+            start_on,
+            length,
+            capacity,
+            tasks,
+            closed_on,
+        }
+    }
+
+    /// get name.
+    pub fn name(&self) -> &SprintName {
+        &self.name
+    }
+
+    /// get start_on.
+    pub fn start_on(&self) -> NaiveDate {
+        self.start_on
+    }
+
+    /// get length.
+    pub fn length(&self) -> SprintLength {
+        self.length
+    }
+
+    /// end_on returns the last day of the sprint.
+    pub fn end_on(&self) -> NaiveDate {
+        self.start_on + Duration::days(i64::from(self.length.days()) - 1)
+    }
+
+    /// get capacity.
+    pub fn capacity(&self) -> Cost {
+        self.capacity
+    }
+
+    /// get tasks.
+    pub fn tasks(&self) -> &[SprintTask] {
+        &self.tasks
+    }
+
+    /// committed_cost returns the sum of costs of the committed tasks.
+    pub fn committed_cost(&self) -> Cost {
+        self.tasks.iter().map(|t| t.cost).sum()
+    }
+
+    /// get closed_on.
+    pub fn closed_on(&self) -> Option<NaiveDate> {
+        self.closed_on
+    }
+
+    /// close the sprint.
+    pub fn close(&mut self, closed_on: NaiveDate) -> Result<(), SprintError> {
+        if self.closed_on.is_some() {
+            return Err(SprintError::AlreadyClosed(self.name.as_str().to_owned()));
+        }
+        self.closed_on = Some(closed_on);
+        Ok(())
+    }
+}
+
+impl Entity for Sprint {
+    type Id = SprintName;
+
+    fn id(&self) -> Self::Id {
+        self.name.clone()
+    }
+}
+
+/// ISprintRepository define interface of sprint repository.
+pub trait ISprintRepository {
+    /// save the sprint. The sprint of the same name is overwritten.
+    fn save(&self, sprint: &Sprint) -> Result<()>;
+
+    /// load the sprint by name.
+    fn load(&self, name: &SprintName) -> Result<Option<Sprint>>;
+
+    /// exists reports whether the sprint of the name exists.
+    fn exists(&self, name: &SprintName) -> Result<bool> {
+        Ok(self.load(name)?.is_some())
+    }
+}
+
+/// ISprintRepositoryComponent returns ISprintRepository.
+pub trait ISprintRepositoryComponent {
+    type SprintRepository: ISprintRepository;
+
+    /// sprint_repository returns ISprintRepository.
+    fn sprint_repository(&self) -> &Self::SprintRepository;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn task(id: i64, cost: i32) -> SprintTask {
+        SprintTask {
+            sequential_id: SequentialID::new(id),
+            cost: Cost::new(cost),
+        }
+    }
+
+    #[test]
+    fn test_parse_length() {
+        #[derive(Debug)]
+        struct TestCase {
+            args: &'static str,
+            want: Result<SprintLength, SprintError>,
+            name: String,
+        }
+
+        let table = [
+            TestCase {
+                name: String::from("normal: weeks"),
+                args: "2w",
+                want: Ok(SprintLength::from_days(14)),
+            },
+            TestCase {
+                name: String::from("normal: days"),
+                args: "10d",
+                want: Ok(SprintLength::from_days(10)),
+            },
+            TestCase {
+                name: String::from("abnormal: zero"),
+                args: "0w",
+                want: Err(SprintError::InvalidLength(String::from("0w"))),
+            },
+            TestCase {
+                name: String::from("abnormal: no unit"),
+                args: "14",
+                want: Err(SprintError::InvalidLength(String::from("14"))),
+            },
+            TestCase {
+                name: String::from("abnormal: multibyte unit"),
+                args: "2週",
+                want: Err(SprintError::InvalidLength(String::from("2週"))),
+            },
+            TestCase {
+                name: String::from("abnormal: empty"),
+                args: "",
+                want: Err(SprintError::InvalidLength(String::from(""))),
+            },
+        ];
+
+        for test_case in table {
+            assert_eq!(
+                test_case.args.parse::<SprintLength>(),
+                test_case.want,
+                "Failed in the \"{}\".",
+                test_case.name
+            );
+        }
+    }
+
+    #[test]
+    fn test_create() {
+        #[derive(Debug)]
+        struct TestCase {
+            args: (Vec<SprintTask>, bool),
+            want: Result<i32, SprintError>,
+            name: String,
+        }
+
+        let table = [
+            TestCase {
+                name: String::from("normal: within capacity"),
+                args: (vec![task(1, 10), task(2, 30)], false),
+                want: Ok(40),
+            },
+            TestCase {
+                name: String::from("normal: over capacity allowed"),
+                args: (vec![task(1, 10), task(2, 31)], true),
+                want: Ok(41),
+            },
+            TestCase {
+                name: String::from("abnormal: over capacity"),
+                args: (vec![task(1, 10), task(2, 31)], false),
+                want: Err(SprintError::OverCapacity {
+                    committed: 41,
+                    capacity: 40,
+                }),
+            },
+            TestCase {
+                name: String::from("abnormal: empty"),
+                args: (vec![], false),
+                want: Err(SprintError::Empty(String::from("s1"))),
+            },
+        ];
+
+        for test_case in table {
+            let got = Sprint::create(
+                SprintName::new("s1").unwrap(),
+                NaiveDate::from_ymd_opt(2026, 10, 1).unwrap(),
+                SprintLength::from_days(14),
+                Cost::new(40),
+                test_case.args.0,
+                test_case.args.1,
+            )
+            .map(|s| s.committed_cost().to_i32());
+            assert_eq!(got, test_case.want, "Failed in the \"{}\".", test_case.name);
+        }
+    }
+
+    #[test]
+    fn test_close() {
+        let mut sprint = Sprint::create(
+            SprintName::new("s1").unwrap(),
+            NaiveDate::from_ymd_opt(2026, 10, 1).unwrap(),
+            SprintLength::from_days(14),
+            Cost::new(40),
+            vec![task(1, 10)],
+            false,
+        )
+        .unwrap();
+        assert_eq!(
+            sprint.end_on(),
+            NaiveDate::from_ymd_opt(2026, 10, 14).unwrap()
+        );
+
+        let closed_on = NaiveDate::from_ymd_opt(2026, 10, 10).unwrap();
+        assert_eq!(sprint.close(closed_on), Ok(()));
+        assert_eq!(sprint.closed_on(), Some(closed_on));
+        assert_eq!(
+            sprint.close(closed_on),
+            Err(SprintError::AlreadyClosed(String::from("s1")))
+        );
+    }
+}
@@ -1,6 +1,9 @@
 use std::time::Duration;
 
-use anyhow::Result;
+use crate::Result;
+use thiserror::Error;
+
+use crate::domain::task_builder::TaskBuilder;
 
 /// Task ID.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -56,33 +59,30 @@ pub struct Task {
     id: ID,
     title: String,
     is_closed: bool,
+    is_deleted: bool,
     priority: Priority,
     cost: Cost,
     elapsed_time: Duration,
+    /// created_at and updated_at are `YYYY-MM-DD HH:MM:SS` in local time, kept by repositories.
+    /// They are empty until the task is stored, or if the repository has no clock.
+    created_at: String,
+    updated_at: String,
 }
 
 impl Task {
-    /// construct new Task.
-    pub fn new(title: String, a_priority: Option<Priority>, a_cost: Option<Cost>) -> Task {
-        let default_priorty = Priority(10);
-        let priority = match a_priority {
-            Some(p) => p,
-            _ => default_priorty,
-        };
-
-        let default_cost = Cost(10);
-        let cost = match a_cost {
-            Some(c) => c,
-            _ => default_cost,
-        };
-
+    /// construct new Task from the builder.
+    /// Urgency, tags and privacy of the builder are ignored, since this model does not have them.
+    pub fn new(builder: TaskBuilder) -> Task {
         Task {
             id: ID(0),
-            title,
+            title: builder.title,
             is_closed: false,
-            priority,
-            cost,
+            is_deleted: false,
+            priority: Priority(builder.priority.unwrap_or(10)),
+            cost: Cost(builder.cost.unwrap_or(10)),
             elapsed_time: Duration::from_secs(0),
+            created_at: String::new(),
+            updated_at: String::new(),
         }
     }
 
@@ -91,23 +91,35 @@ impl Task {
         self.is_closed = true;
     }
 
+    /// delete this task. Repositories keep it, but leave it out of queries.
+    pub fn delete(&mut self) {
+        self.is_deleted = true;
+    }
+
     /// construct new Task from repository.
     /// WARNING: don't use this function any layer other than repository.
+    #[allow(clippy::too_many_arguments)]
     pub fn from_repository(
         id: ID,
         title: String,
         is_closed: bool,
+        is_deleted: bool,
         priority: Priority,
         cost: Cost,
         elapsed_time: Duration,
+        created_at: String,
+        updated_at: String,
     ) -> Task {
         Task {
             id,
             title,
             is_closed,
+            is_deleted,
             priority,
             cost,
             elapsed_time,
+            created_at,
+            updated_at,
         }
     }
 
@@ -131,6 +143,11 @@ impl Task {
         self.is_closed
     }
 
+    /// get is_deleted.
+    pub fn is_deleted(&self) -> bool {
+        self.is_deleted
+    }
+
     /// get priority.
     pub fn priority(&self) -> Priority {
         self.priority
@@ -155,20 +172,83 @@ impl Task {
     pub fn elapsed_time(&self) -> Duration {
         self.elapsed_time
     }
+
+    /// get created_at.
+    pub fn created_at(&self) -> &str {
+        &self.created_at
+    }
+
+    /// get updated_at.
+    pub fn updated_at(&self) -> &str {
+        &self.updated_at
+    }
+}
+
+/// TaskOrder is the order of tasks which ITaskRepository returns.
+/// Ties are broken by id, so that the order is the same across calls.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum TaskOrder {
+    /// from the oldest id.
+    #[default]
+    Id,
+    /// from the highest priority.
+    Priority,
+    /// from the latest created.
+    CreatedAt,
+    /// from the latest updated.
+    UpdatedAt,
+}
+
+/// JournalChange is a kind of change recorded in the journal.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JournalChange {
+    /// the task was added, so that undoing it removes the task.
+    Added,
+    /// the task was updated, so that undoing it restores the task before the update.
+    Updated,
+}
+
+/// JournalEntry is a change of a task recorded to undo it.
+#[derive(Debug, PartialEq, Eq)]
+pub struct JournalEntry {
+    pub task_id: ID,
+    pub change: JournalChange,
+    /// before is the task before the change. It is None for an added task.
+    pub before: Option<Task>,
+    /// recorded_at is `YYYY-MM-DD HH:MM:SS` in local time, or empty if the repository has no clock.
+    pub recorded_at: String,
+}
+
+/// TaskRepositoryError is raised by implementations of ITaskRepository.
+#[derive(Error, Debug, PartialEq, Eq)]
+pub enum TaskRepositoryError {
+    #[error("the task for id `{0}` is not stored")]
+    NotFound(i64),
 }
 
 /// ITaskRepository define interface of task repository.
+/// Implementations outside this crate can be checked against `infra::memory::task_repository`.
 pub trait ITaskRepository {
-    /// find a task by id.
+    /// find a task by id. It is None if the task is not stored or deleted.
     fn find_by_id(&self, id: ID) -> Result<Option<Task>>;
-    /// find tasks which is not closed.
-    fn find_opening(&self) -> Result<Vec<Task>>;
-    /// fetch all tasks regardless whether it is closed.
-    fn fetch_all(&self) -> Result<Vec<Task>>;
-    /// add a task, and then return ID of the task.
+    /// find tasks which is not closed in the order. Deleted tasks are left out.
+    fn find_opening(&self, order: TaskOrder) -> Result<Vec<Task>>;
+    /// fetch all tasks regardless whether it is closed in the order. Deleted tasks are left out.
+    fn fetch_all(&self, order: TaskOrder) -> Result<Vec<Task>>;
+    /// find deleted tasks regardless whether it is closed in the order.
+    fn find_deleted(&self, order: TaskOrder) -> Result<Vec<Task>>;
+    /// search tasks whose titles have words beginning with each word of the query,
+    /// the best match first. Deleted tasks are left out.
+    fn search(&self, query: &str) -> Result<Vec<Task>>;
+    /// add a task, and then return ID of the task. The ID of `a_task` is ignored.
     fn add(&self, a_task: Task) -> Result<ID>;
-    /// update the task.
+    /// update the task. It fails with TaskRepositoryError::NotFound if the task is not stored.
     fn update(&self, a_task: Task) -> Result<()>;
+    /// undo reverts the latest change of `add` or `update` and returns it.
+    /// It is None if no change is left to undo.
+    fn undo(&self) -> Result<Option<JournalEntry>>;
+    /// history returns changes left to undo from the latest, only of the task if id is given.
+    fn history(&self, id: Option<ID>) -> Result<Vec<JournalEntry>>;
 }
 
 #[cfg(test)]
@@ -179,9 +259,7 @@ mod tests {
     fn test_new() {
         #[derive(Debug)]
         struct Args {
-            title: String,
-            priority: Option<Priority>,
-            cost: Option<Cost>,
+            builder: TaskBuilder,
         }
 
         #[derive(Debug)]
@@ -195,9 +273,7 @@ mod tests {
             TestCase {
                 name: String::from("normal: with priority and cost"),
                 args: Args {
-                    title: String::from("title1"),
-                    priority: Some(Priority(100)),
-                    cost: Some(Cost(100)),
+                    builder: TaskBuilder::new("title1").priority(100).cost(100),
                 },
                 expected: Task {
                     id: ID(0),
@@ -206,14 +282,15 @@ mod tests {
                     priority: Priority(100),
                     cost: Cost(100),
                     elapsed_time: Duration::from_secs(0),
+                    created_at: String::new(),
+                    updated_at: String::new(),
+                    is_deleted: false,
                 },
             },
             TestCase {
                 name: String::from("normal: withtout priority and cost"),
                 args: Args {
-                    title: String::from("title2"),
-                    priority: None,
-                    cost: None,
+                    builder: TaskBuilder::new("title2"),
                 },
                 expected: Task {
                     id: ID(0),
@@ -222,17 +299,16 @@ mod tests {
                     priority: Priority(10),
                     cost: Cost(10),
                     elapsed_time: Duration::from_secs(0),
+                    created_at: String::new(),
+                    updated_at: String::new(),
+                    is_deleted: false,
                 },
             },
         ];
 
         for test_case in table {
             assert_eq!(
-                Task::new(
-                    test_case.args.title,
-                    test_case.args.priority,
-                    test_case.args.cost
-                ),
+                Task::new(test_case.args.builder),
                 test_case.expected,
                 "Failed in the \"{}\".",
                 test_case.name,
@@ -251,7 +327,7 @@ mod tests {
 
         let table = [TestCase {
             name: String::from("nominal"),
-            given: Task::new("hoge".to_owned(), None, None),
+            given: Task::new(TaskBuilder::new("hoge")),
             want: Task {
                 id: ID(0),
                 title: String::from("hoge"),
@@ -259,6 +335,9 @@ mod tests {
                 priority: Priority(10),
                 cost: Cost(10),
                 elapsed_time: Duration::from_secs(0),
+                created_at: String::new(),
+                updated_at: String::new(),
+                is_deleted: false,
             },
         }];
 
@@ -282,6 +361,8 @@ mod tests {
             priority: Priority,
             cost: Cost,
             elapsed_time: Duration,
+            created_at: String,
+            updated_at: String,
         }
 
         #[derive(Debug)]
@@ -292,6 +373,8 @@ mod tests {
             priority: Priority,
             cost: Cost,
             elapsed_time: Duration,
+            created_at: &'w str,
+            updated_at: &'w str,
         }
 
         #[derive(Debug)]
@@ -310,6 +393,8 @@ mod tests {
                 priority: Priority(2),
                 cost: Cost(3),
                 elapsed_time: Duration::from_secs(4),
+                created_at: String::from("2024-05-01 09:00:00"),
+                updated_at: String::from("2024-05-02 10:00:00"),
             },
             want: Wants {
                 id: ID(1),
@@ -318,6 +403,8 @@ mod tests {
                 priority: Priority(2),
                 cost: Cost(3),
                 elapsed_time: Duration::from_secs(4),
+                created_at: "2024-05-01 09:00:00",
+                updated_at: "2024-05-02 10:00:00",
             },
         }];
 
@@ -326,9 +413,12 @@ mod tests {
                 test_case.args.id,
                 test_case.args.title,
                 test_case.args.is_closed,
+                false,
                 test_case.args.priority,
                 test_case.args.cost,
                 test_case.args.elapsed_time,
+                test_case.args.created_at,
+                test_case.args.updated_at,
             );
             assert_eq!(
                 got.id(),
@@ -366,6 +456,18 @@ mod tests {
                 "Failed in the \"{}\".",
                 test_case.name
             );
+            assert_eq!(
+                got.created_at(),
+                test_case.want.created_at,
+                "Failed in the \"{}\".",
+                test_case.name
+            );
+            assert_eq!(
+                got.updated_at(),
+                test_case.want.updated_at,
+                "Failed in the \"{}\".",
+                test_case.name
+            );
         }
     }
 }
@@ -0,0 +1,237 @@
+//! # task_builder
+//!
+//! task_builder builds a new task by naming its optional values, instead of positional `Option`s.
+//! It is shared by both engines, and values which an engine does not have are ignored by it.
+
+/// TaskBuilder is a new task whose title is required and the others are optional.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TaskBuilder {
+    pub(crate) title: String,
+    pub(crate) priority: Option<i32>,
+    pub(crate) urgency: Option<i32>,
+    pub(crate) cost: Option<i32>,
+    pub(crate) tags: Vec<String>,
+    pub(crate) private: bool,
+    #[cfg(feature = "es")]
+    pub(crate) due: Option<chrono::NaiveDate>,
+}
+
+impl TaskBuilder {
+    /// construct TaskBuilder with the title.
+    pub fn new(title: impl Into<String>) -> Self {
+        TaskBuilder {
+            title: title.into(),
+            priority: None,
+            urgency: None,
+            cost: None,
+            tags: vec![],
+            private: false,
+            #[cfg(feature = "es")]
+            due: None,
+        }
+    }
+
+    /// set priority.
+    pub fn priority(mut self, priority: i32) -> Self {
+        self.priority = Some(priority);
+        self
+    }
+
+    /// set urgency, which only the Event Sourcing model has.
+    pub fn urgency(mut self, urgency: i32) -> Self {
+        self.urgency = Some(urgency);
+        self
+    }
+
+    /// set cost.
+    pub fn cost(mut self, cost: i32) -> Self {
+        self.cost = Some(cost);
+        self
+    }
+
+    /// add a tag, which only the Event Sourcing model has.
+    pub fn tag(mut self, tag: impl Into<String>) -> Self {
+        self.tags.push(tag.into());
+        self
+    }
+
+    /// add tags, which only the Event Sourcing model has.
+    pub fn tags<I, S>(mut self, tags: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.tags.extend(tags.into_iter().map(Into::into));
+        self
+    }
+
+    /// make the task private, which only the Event Sourcing model has.
+    pub fn private(mut self) -> Self {
+        self.private = true;
+        self
+    }
+
+    /// set the due date, which only the Event Sourcing model has.
+    #[cfg(feature = "es")]
+    pub fn due(mut self, due: chrono::NaiveDate) -> Self {
+        self.due = Some(due);
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_builder() {
+        #[derive(Debug)]
+        struct TestCase {
+            args: TaskBuilder,
+            want: TaskBuilder,
+            name: String,
+        }
+
+        let table = [
+            TestCase {
+                name: String::from("normal: defaults"),
+                args: TaskBuilder::new("title"),
+                want: TaskBuilder {
+                    title: String::from("title"),
+                    priority: None,
+                    urgency: None,
+                    cost: None,
+                    tags: vec![],
+                    private: false,
+                    #[cfg(feature = "es")]
+                    due: None,
+                },
+            },
+            TestCase {
+                name: String::from("normal: priority"),
+                args: TaskBuilder::new("title").priority(20),
+                want: TaskBuilder {
+                    priority: Some(20),
+                    ..TaskBuilder::new("title")
+                },
+            },
+            TestCase {
+                name: String::from("normal: urgency"),
+                args: TaskBuilder::new("title").urgency(30),
+                want: TaskBuilder {
+                    urgency: Some(30),
+                    ..TaskBuilder::new("title")
+                },
+            },
+            TestCase {
+                name: String::from("normal: cost"),
+                args: TaskBuilder::new("title").cost(3),
+                want: TaskBuilder {
+                    cost: Some(3),
+                    ..TaskBuilder::new("title")
+                },
+            },
+            TestCase {
+                name: String::from("normal: tag and tags in order"),
+                args: TaskBuilder::new("title").tag("home").tags(["bug", "ui"]),
+                want: TaskBuilder {
+                    tags: vec![
+                        String::from("home"),
+                        String::from("bug"),
+                        String::from("ui"),
+                    ],
+                    ..TaskBuilder::new("title")
+                },
+            },
+            TestCase {
+                name: String::from("normal: private"),
+                args: TaskBuilder::new("title").private(),
+                want: TaskBuilder {
+                    private: true,
+                    ..TaskBuilder::new("title")
+                },
+            },
+            #[cfg(feature = "es")]
+            TestCase {
+                name: String::from("normal: due"),
+                args: TaskBuilder::new("title")
+                    .due(chrono::NaiveDate::from_ymd_opt(2024, 5, 1).unwrap()),
+                want: TaskBuilder {
+                    due: chrono::NaiveDate::from_ymd_opt(2024, 5, 1),
+                    ..TaskBuilder::new("title")
+                },
+            },
+            TestCase {
+                name: String::from("normal: the last value wins"),
+                args: TaskBuilder::new("title").priority(20).priority(5),
+                want: TaskBuilder {
+                    priority: Some(5),
+                    ..TaskBuilder::new("title")
+                },
+            },
+        ];
+
+        for test_case in table {
+            assert_eq!(
+                test_case.args, test_case.want,
+                "Failed in the \"{}\".",
+                test_case.name
+            );
+        }
+    }
+
+    // NOTE: the builder keeps tags as given, and they are validated when the task is created.
+    #[cfg(feature = "es")]
+    #[test]
+    fn test_tags_validation() {
+        use crate::domain::es_task::Tag;
+
+        #[derive(Debug)]
+        struct TestCase {
+            args: TaskBuilder,
+            want: Option<Vec<&'static str>>,
+            name: String,
+        }
+
+        let table = [
+            TestCase {
+                name: String::from("normal: valid tags"),
+                args: TaskBuilder::new("title").tags(["repo:taskmr", "bug"]),
+                want: Some(vec!["repo:taskmr", "bug"]),
+            },
+            TestCase {
+                name: String::from("abnormal: empty tag"),
+                args: TaskBuilder::new("title").tag(""),
+                want: None,
+            },
+            TestCase {
+                name: String::from("abnormal: whitespace"),
+                args: TaskBuilder::new("title").tag("two words"),
+                want: None,
+            },
+            TestCase {
+                name: String::from("abnormal: comma"),
+                args: TaskBuilder::new("title").tag("bug").tag("a,b"),
+                want: None,
+            },
+        ];
+
+        for test_case in table {
+            let got = test_case
+                .args
+                .tags
+                .iter()
+                .map(|t| Tag::new(t))
+                .collect::<Result<Vec<Tag>, _>>()
+                .ok();
+            assert_eq!(
+                got,
+                test_case
+                    .want
+                    .map(|tags| tags.into_iter().map(|t| Tag::new(t).unwrap()).collect()),
+                "Failed in the \"{}\".",
+                test_case.name
+            );
+        }
+    }
+}
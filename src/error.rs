@@ -0,0 +1,201 @@
+//! # error
+//!
+//! error defines the error of the taskmr library, classified by the layer which failed,
+//! so that library consumers can match on the category and still reach the cause.
+
+use std::error::Error as StdError;
+use std::fmt;
+
+use thiserror::Error;
+
+/// BoxError is the cause held by Error.
+pub type BoxError = Box<dyn StdError + Send + Sync + 'static>;
+
+/// Result is the result of the taskmr library.
+pub type Result<T, E = Error> = std::result::Result<T, E>;
+
+/// Error is the error of the taskmr library.
+/// It is displayed as its cause, so that messages do not change with the category.
+#[derive(Error, Debug)]
+pub enum Error {
+    /// Domain is a violation of business rules, such as closing a habit.
+    #[error(transparent)]
+    Domain(BoxError),
+    /// UseCase is a failure of an application rule, such as a task which is not found.
+    #[error(transparent)]
+    UseCase(BoxError),
+    /// Storage is a failure of the database or files holding tasks.
+    #[error(transparent)]
+    Storage(BoxError),
+    /// Config is a failure of reading, writing or moving the config.
+    #[error(transparent)]
+    Config(BoxError),
+    /// Presentation is a failure of commands, such as an unknown row number or a plugin.
+    #[error(transparent)]
+    Presentation(BoxError),
+}
+
+impl Error {
+    /// storage wraps the cause with the message of what failed, such as `failed to open <path>`.
+    pub fn storage(message: impl Into<String>, cause: impl Into<BoxError>) -> Error {
+        Error::Storage(Box::new(Context::new(message, cause)))
+    }
+
+    /// config wraps the cause with the message of what failed, such as `invalid config <path>`.
+    pub fn config(message: impl Into<String>, cause: impl Into<BoxError>) -> Error {
+        Error::Config(Box::new(Context::new(message, cause)))
+    }
+
+    /// cause returns the error which this wraps.
+    pub fn cause(&self) -> &(dyn StdError + Send + Sync + 'static) {
+        match self {
+            Error::Domain(cause)
+            | Error::UseCase(cause)
+            | Error::Storage(cause)
+            | Error::Config(cause)
+            | Error::Presentation(cause) => cause.as_ref(),
+        }
+    }
+
+    /// downcast_ref finds an error of the type in the chain of causes.
+    pub fn downcast_ref<E: StdError + 'static>(&self) -> Option<&E> {
+        let mut cause: Option<&(dyn StdError + 'static)> = Some(self.cause());
+        while let Some(err) = cause {
+            if let Some(err) = err.downcast_ref::<E>() {
+                return Some(err);
+            }
+            cause = err.source();
+        }
+        None
+    }
+
+    /// downcast takes the cause out if it is of the type, and returns self otherwise.
+    pub fn downcast<E: StdError + 'static>(self) -> Result<E, Error> {
+        if self.cause().downcast_ref::<E>().is_none() {
+            return Err(self);
+        }
+        let cause = match self {
+            Error::Domain(cause)
+            | Error::UseCase(cause)
+            | Error::Storage(cause)
+            | Error::Config(cause)
+            | Error::Presentation(cause) => cause,
+        };
+        Ok(*cause.downcast::<E>().expect("the type is checked above"))
+    }
+}
+
+/// Context is a cause with the message of what failed.
+/// It is displayed as the message, and the cause is its source.
+#[derive(Debug)]
+pub struct Context {
+    message: String,
+    cause: BoxError,
+}
+
+impl Context {
+    /// construct Context.
+    pub fn new(message: impl Into<String>, cause: impl Into<BoxError>) -> Context {
+        Context {
+            message: message.into(),
+            cause: cause.into(),
+        }
+    }
+}
+
+impl fmt::Display for Context {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl StdError for Context {
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
+        Some(self.cause.as_ref())
+    }
+}
+
+/// impl_from classifies error types into a category of Error, so that `?` converts them.
+macro_rules! impl_from {
+    ($variant:ident: $($(#[$meta:meta])* $t:ty),+ $(,)?) => {
+        $(
+            $(#[$meta])*
+            impl From<$t> for Error {
+                fn from(err: $t) -> Self {
+                    Error::$variant(Box::new(err))
+                }
+            }
+        )+
+    };
+}
+
+impl_from!(Domain:
+    #[cfg(feature = "es")]
+    crate::domain::es_task::TaskError,
+    #[cfg(feature = "es")]
+    crate::domain::es_tag::TagError,
+    #[cfg(feature = "es")]
+    crate::domain::sprint::SprintError,
+);
+impl_from!(UseCase: crate::usecase::error::UseCaseError);
+impl_from!(Storage:
+    #[cfg(feature = "legacy")]
+    crate::domain::task::TaskRepositoryError,
+    #[cfg(feature = "es")]
+    crate::infra::sqlite::es_task_repository::TaskRepositoryError,
+    crate::infra::sqlite::access::StorageError,
+    crate::infra::sqlite::schema::SchemaError,
+    crate::infra::sqlite::schedule::ScheduleError,
+    rusqlite::Error,
+    serde_json::Error,
+    std::io::Error,
+    std::num::ParseIntError,
+    #[cfg(feature = "es")]
+    uuid::Error,
+    #[cfg(feature = "es")]
+    chrono::ParseError,
+);
+impl_from!(Config:
+    crate::infra::bundle::BundleError,
+    toml::de::Error,
+    toml::ser::Error,
+);
+impl_from!(Presentation:
+    crate::infra::selection::SelectionError,
+    #[cfg(feature = "scripting")]
+    crate::infra::script::ScriptError,
+    #[cfg(feature = "plugin")]
+    crate::plugin::PluginError,
+    crate::presentation::command::init::InitError,
+    #[cfg(feature = "webhook")]
+    ureq::Error,
+);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::usecase::error::UseCaseError;
+
+    #[test]
+    fn test_downcast() {
+        let not_found = || -> Result<()> { Err(UseCaseError::NotFound(1))? };
+        let err = not_found().unwrap_err();
+        assert!(matches!(err, Error::UseCase(_)));
+        assert_eq!(err.to_string(), UseCaseError::NotFound(1).to_string());
+        assert!(err.downcast_ref::<UseCaseError>().is_some());
+        assert!(err.downcast::<std::io::Error>().is_err());
+
+        let io = std::io::Error::new(std::io::ErrorKind::NotFound, "no such file");
+        let err = Error::config("failed to read config.toml", io);
+        assert!(matches!(err, Error::Config(_)));
+        assert_eq!(err.to_string(), "failed to read config.toml");
+        assert_eq!(
+            err.source().map(|e| e.to_string()),
+            Some(String::from("no such file"))
+        );
+        assert_eq!(
+            err.downcast_ref::<std::io::Error>().map(|e| e.kind()),
+            Some(std::io::ErrorKind::NotFound)
+        );
+    }
+}
@@ -0,0 +1,272 @@
+//! # bundle
+//!
+//! bundle packs the config and saved filters into one portable TOML or JSON file,
+//! so that taskmr can be set up on another machine by one command.
+//!
+//! Saved filters are the scripts in `<config dir>/taskmr/scripts`. They are bundled as plain
+//! text, so they travel even if this build does not run scripts.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::{Error, Result};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::infra::config::Config;
+
+/// BUNDLE_VERSION is the version of the bundle format.
+pub const BUNDLE_VERSION: u32 = 1;
+
+/// SCRIPT_EXTENSION is the extension of saved filters.
+const SCRIPT_EXTENSION: &str = "rhai";
+
+/// BundleError is raised when a bundle can not be imported.
+#[derive(Error, Debug, PartialEq, Eq)]
+pub enum BundleError {
+    #[error("bundle version {0} is not supported, this taskmr reads version {BUNDLE_VERSION}")]
+    UnsupportedVersion(u32),
+    #[error("script name `{0}` must consist of alphanumerics, `-` and `_`")]
+    InvalidScriptName(String),
+    #[error("{} already exist, pass --force to overwrite them", .0.join(", "))]
+    Conflict(Vec<String>),
+}
+
+/// BundleFormat is the file format of a bundle.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BundleFormat {
+    Toml,
+    Json,
+}
+
+impl BundleFormat {
+    /// from_path guesses the format from the extension. TOML is the default.
+    pub fn from_path(path: &Path) -> BundleFormat {
+        match path.extension().and_then(|e| e.to_str()) {
+            Some("json") => BundleFormat::Json,
+            _ => BundleFormat::Toml,
+        }
+    }
+}
+
+/// Bundle is the portable set of settings.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Bundle {
+    pub version: u32,
+    #[serde(default)]
+    pub config: Config,
+    /// scripts maps names of saved filters to their sources.
+    #[serde(default)]
+    pub scripts: BTreeMap<String, String>,
+}
+
+impl Bundle {
+    /// collect reads the config file and the scripts. Missing ones are left out.
+    pub fn collect(config_path: &Path, scripts_dir: &Path) -> Result<Bundle> {
+        let config = Config::load(config_path)?;
+
+        let mut scripts = BTreeMap::new();
+        if scripts_dir.is_dir() {
+            for entry in fs::read_dir(scripts_dir)? {
+                let path = entry?.path();
+                if path.extension().and_then(|e| e.to_str()) != Some(SCRIPT_EXTENSION) {
+                    continue;
+                }
+                let Some(name) = path.file_stem().and_then(|s| s.to_str()) else {
+                    continue;
+                };
+                let source = fs::read_to_string(&path).map_err(|err| {
+                    Error::config(format!("failed to read {}", path.display()), err)
+                })?;
+                scripts.insert(name.to_owned(), source);
+            }
+        }
+
+        Ok(Bundle {
+            version: BUNDLE_VERSION,
+            config,
+            scripts,
+        })
+    }
+
+    /// render renders the bundle in the format.
+    pub fn render(&self, format: BundleFormat) -> Result<String> {
+        Ok(match format {
+            BundleFormat::Toml => toml::to_string_pretty(self)?,
+            BundleFormat::Json => serde_json::to_string_pretty(self)? + "\n",
+        })
+    }
+
+    /// parse parses a bundle in the format and validates it.
+    pub fn parse(source: &str, format: BundleFormat) -> Result<Bundle> {
+        let bundle: Bundle = match format {
+            BundleFormat::Toml => toml::from_str(source)?,
+            BundleFormat::Json => serde_json::from_str(source)?,
+        };
+
+        if bundle.version != BUNDLE_VERSION {
+            return Err(BundleError::UnsupportedVersion(bundle.version).into());
+        }
+        for name in bundle.scripts.keys() {
+            let valid = !name.is_empty()
+                && name
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+            if !valid {
+                return Err(BundleError::InvalidScriptName(name.to_owned()).into());
+            }
+        }
+
+        Ok(bundle)
+    }
+
+    /// install writes the config and the scripts and returns the written files.
+    /// Nothing is written if an existing file has other contents, unless `force`.
+    pub fn install(
+        &self,
+        config_path: &Path,
+        scripts_dir: &Path,
+        force: bool,
+    ) -> Result<Vec<PathBuf>> {
+        let mut files = vec![(
+            config_path.to_path_buf(),
+            toml::to_string_pretty(&self.config)?,
+        )];
+        for (name, source) in &self.scripts {
+            let mut path = scripts_dir.join(name);
+            path.set_extension(SCRIPT_EXTENSION);
+            files.push((path, source.to_owned()));
+        }
+
+        if !force {
+            let conflicts: Vec<String> = files
+                .iter()
+                .filter(|(path, content)| {
+                    path.is_file() && fs::read_to_string(path).ok().as_ref() != Some(content)
+                })
+                .map(|(path, _)| path.display().to_string())
+                .collect();
+            if !conflicts.is_empty() {
+                return Err(BundleError::Conflict(conflicts).into());
+            }
+        }
+
+        let mut written = Vec::new();
+        for (path, content) in files {
+            if let Some(dir) = path.parent() {
+                fs::create_dir_all(dir)?;
+            }
+            fs::write(&path, content)
+                .map_err(|err| Error::config(format!("failed to write {}", path.display()), err))?;
+            written.push(path);
+        }
+
+        Ok(written)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::infra::config::AutoContext;
+
+    fn temp_dir() -> PathBuf {
+        let nanos = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_nanos();
+        let dir =
+            std::env::temp_dir().join(format!("taskmr-bundle-{}-{}", std::process::id(), nanos));
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let src = temp_dir();
+        fs::write(src.join("config.toml"), "auto_context = \"git\"\n").unwrap();
+        fs::create_dir_all(src.join("scripts")).unwrap();
+        fs::write(
+            src.join("scripts").join("expensive.rhai"),
+            "fn filter(task) { task.cost >= 5 }\n",
+        )
+        .unwrap();
+        fs::write(src.join("scripts").join("notes.txt"), "not a script").unwrap();
+
+        let bundle = Bundle::collect(&src.join("config.toml"), &src.join("scripts")).unwrap();
+        assert_eq!(bundle.config.auto_context, AutoContext::Git);
+        assert_eq!(bundle.scripts.len(), 1);
+
+        for format in [BundleFormat::Toml, BundleFormat::Json] {
+            let rendered = bundle.render(format).unwrap();
+            assert_eq!(Bundle::parse(&rendered, format).unwrap(), bundle);
+        }
+
+        let dst = temp_dir();
+        let written = bundle
+            .install(&dst.join("config.toml"), &dst.join("scripts"), false)
+            .unwrap();
+        assert_eq!(written.len(), 2);
+        assert_eq!(
+            Bundle::collect(&dst.join("config.toml"), &dst.join("scripts")).unwrap(),
+            bundle
+        );
+
+        // installing the same bundle again is not a conflict.
+        bundle
+            .install(&dst.join("config.toml"), &dst.join("scripts"), false)
+            .unwrap();
+
+        fs::write(dst.join("config.toml"), "").unwrap();
+        let err = bundle
+            .install(&dst.join("config.toml"), &dst.join("scripts"), false)
+            .unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<BundleError>(),
+            Some(BundleError::Conflict(_))
+        ));
+        bundle
+            .install(&dst.join("config.toml"), &dst.join("scripts"), true)
+            .unwrap();
+
+        fs::remove_dir_all(src).unwrap();
+        fs::remove_dir_all(dst).unwrap();
+    }
+
+    #[test]
+    fn test_parse() {
+        #[derive(Debug)]
+        struct TestCase {
+            args: &'static str,
+            want: Result<(), BundleError>,
+            name: String,
+        }
+
+        let table = [
+            TestCase {
+                name: String::from("normal: minimum"),
+                args: "version = 1",
+                want: Ok(()),
+            },
+            TestCase {
+                name: String::from("abnormal: unsupported version"),
+                args: "version = 2",
+                want: Err(BundleError::UnsupportedVersion(2)),
+            },
+            TestCase {
+                name: String::from("abnormal: path in a script name"),
+                args: "version = 1\n[scripts]\n\"../evil\" = \"\"",
+                want: Err(BundleError::InvalidScriptName(String::from("../evil"))),
+            },
+        ];
+
+        for test_case in table {
+            let got = Bundle::parse(test_case.args, BundleFormat::Toml)
+                .map(|_| ())
+                .map_err(|e| e.downcast::<BundleError>().unwrap());
+            assert_eq!(got, test_case.want, "Failed in the \"{}\".", test_case.name);
+        }
+    }
+}
@@ -0,0 +1,861 @@
+//! # config
+//!
+//! config loads user settings from `<config dir>/taskmr/config.toml`.
+//!
+//! ```toml
+//! # tag tasks added inside a git repository with the repository and the branch.
+//! auto_context = "git"
+//! # colorize the output: "auto", "always" or "never".
+//! color = "auto"
+//! # the task database. A relative path is resolved against the config directory.
+//! database = "/home/me/tasks/taskmr.db"
+//! # the storage of `add`, `close`, `edit` and `list`: "es" or "legacy".
+//! engine = "es"
+//!
+//! [cron]
+//! # deliver reports due to the same destination in one digest, rather than one post each.
+//! digest = true
+//!
+//! [defaults]
+//! # priority and cost of added tasks when they are not given.
+//! priority = 10
+//! cost = 5
+//!
+//! [ids]
+//! # "monotonic" never gives out ids again, "reuse" gives the smallest id not held by a live task,
+//! # taking over ids of deleted and archived tasks.
+//! policy = "monotonic"
+//!
+//! [lint]
+//! # conventions of titles checked on `add`, `edit` and `lint`. No rule is checked by default.
+//! max_length = 72
+//! no_trailing_period = true
+//! first_words = ["add", "fix", "write"]
+//! first_letter = "upper"
+//! # "warn" prints violations, "error" refuses the title.
+//! level = "warn"
+//!
+//! [list]
+//! # show a summary footer such as `12 open · total cost 74 · 2 overdue`.
+//! summary = true
+//!
+//! [metrics]
+//! # record call counts and latencies of repositories for `stats --internal`. Every command
+//! # writes them to the task database then, even `list`.
+//! record = true
+//!
+//! [next]
+//! # minutes which a cost takes, to tell whether a task fits the time given to `next`.
+//! minutes_per_cost = 15
+//!
+//! [outstanding]
+//! # print the open cost after `add` and `close`, in weeks of work if a capacity is given.
+//! show = true
+//! weekly_capacity = 20
+//! # warn after `add` if more weeks of work than this are queued. It needs the capacity.
+//! max_weeks = 4
+//!
+//! [quota]
+//! # time allowed per tag in a day, a week or a month. Logging time past it warns.
+//! meetings = "5h/week"
+//!
+//! [score]
+//! # how `next`, `list --group-by matrix` and agendas order tasks:
+//! # "weighted", "priority-per-cost", "wsjf" or "deadline".
+//! strategy = "weighted"
+//! # weights of the priority and the urgency, and the value from which they count as high.
+//! importance = 1
+//! urgency = 1
+//! threshold = 20
+//!
+//! [tags.bug]
+//! # show the tag in red with an icon in tables.
+//! color = "red"
+//! icon = "🐛"
+//!
+//! [[rules]]
+//! # tag and raise the priority of added tasks whose titles match and which have all of the tags.
+//! when_title = "bug|fix"
+//! when_tags = ["work"]
+//! add_tags = ["bug"]
+//! min_priority = 60
+//! ```
+//!
+//! A `.taskmr.toml` in a directory scopes invocations inside the directory tree.
+//! The nearest one from the current directory wins, like direnv.
+//!
+//! ```toml
+//! # add these tags to new tasks and list only tasks having them.
+//! tags = ["taskmr"]
+//! ```
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::{Error, Result};
+use serde::{Deserialize, Serialize};
+
+/// CONFIG_FILE_NAME is the name of the config file in the taskmr config directory.
+pub const CONFIG_FILE_NAME: &str = "config.toml";
+
+/// DATABASE_FILE_NAME is the name of the default task database in the taskmr config directory.
+pub const DATABASE_FILE_NAME: &str = "taskmr.db";
+
+/// SCOPE_FILE_NAME is the name of the file scoping a directory tree.
+pub const SCOPE_FILE_NAME: &str = ".taskmr.toml";
+
+/// AutoContext decides where the context of added tasks is detected from.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AutoContext {
+    #[default]
+    None,
+    Git,
+}
+
+/// Engine is the storage which unprefixed task subcommands such as `add` use.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Engine {
+    /// Event Sourcing.
+    Es,
+    /// The simple table.
+    Legacy,
+}
+
+impl Default for Engine {
+    /// The simple table is the default as long as it is built, for compatibility.
+    fn default() -> Self {
+        if cfg!(feature = "legacy") {
+            Engine::Legacy
+        } else {
+            Engine::Es
+        }
+    }
+}
+
+impl fmt::Display for Engine {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Engine::Es => write!(f, "es"),
+            Engine::Legacy => write!(f, "legacy"),
+        }
+    }
+}
+
+/// ColorChoice decides whether the output is colorized.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ColorChoice {
+    /// colorize only when stdout is a terminal.
+    #[default]
+    Auto,
+    Always,
+    Never,
+}
+
+/// TagColor is the color of a tag in tables.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TagColor {
+    Black,
+    Red,
+    Green,
+    Yellow,
+    Blue,
+    Magenta,
+    Cyan,
+    White,
+}
+
+/// TagStyle is how a tag is displayed. It does not change the tag itself.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct TagStyle {
+    /// color is used only when the output is colorized.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub color: Option<TagColor>,
+    /// icon is put before the tag, such as an emoji.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub icon: Option<String>,
+}
+
+/// IdPolicy decides whether ids of removed tasks are given to new tasks.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum IdPolicy {
+    /// ids always grow, so that an id cached by scripts never refers to another task.
+    #[default]
+    Monotonic,
+    /// the smallest id not held by a live task is given, so that ids stay small.
+    /// Deleted and archived tasks hand their ids over and are shown by negative ids.
+    Reuse,
+}
+
+/// Scope is settings pinned to a directory tree by `.taskmr.toml`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Scope {
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// path is the path of the `.taskmr.toml`.
+    #[serde(skip)]
+    pub path: PathBuf,
+}
+
+/// LintLevel is how a title breaking the lint rules is treated.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LintLevel {
+    /// print the violations and accept the title.
+    #[default]
+    Warn,
+    /// refuse the title.
+    Error,
+}
+
+/// FirstLetter is the case which titles begin with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FirstLetter {
+    Upper,
+    Lower,
+}
+
+/// LintConfig is conventions of titles.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct LintConfig {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_length: Option<usize>,
+    pub no_trailing_period: bool,
+    /// first_words are words which titles begin with, such as verbs.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub first_words: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub first_letter: Option<FirstLetter>,
+    pub level: LintLevel,
+}
+
+/// RuleConfig is a rule applied to added tasks. A rule without conditions applies to every task.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RuleConfig {
+    /// when_title is a regular expression matched against titles.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub when_title: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub when_tags: Vec<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub add_tags: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min_priority: Option<i32>,
+}
+
+/// CronConfig is settings of reports delivered by `cron`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct CronConfig {
+    /// digest joins reports due to the same destination in a run into one delivery.
+    #[serde(default)]
+    pub digest: bool,
+}
+
+/// EventLogConfig is settings of the log of task events.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct EventLogConfig {
+    /// chain links each saved event to the previous one by a hash, to detect tampering.
+    #[serde(default)]
+    pub chain: bool,
+    /// key_file signs the links with its content. A relative path is in the config directory.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub key_file: Option<PathBuf>,
+}
+
+/// ListConfig is settings of list subcommands.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct ListConfig {
+    #[serde(default)]
+    pub summary: bool,
+}
+
+/// OutstandingConfig is settings of the open cost printed after `add` and `close`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct OutstandingConfig {
+    #[serde(default)]
+    pub show: bool,
+    /// weekly_capacity is the cost done in a week.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub weekly_capacity: Option<i32>,
+    /// max_weeks is the weeks of work queued beyond which `add` warns, regardless of show.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_weeks: Option<u32>,
+}
+
+/// DefaultsConfig is values of added tasks when they are not given.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct DefaultsConfig {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub priority: Option<i32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cost: Option<i32>,
+}
+
+/// IdsConfig is settings of sequential ids of tasks.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct IdsConfig {
+    #[serde(default)]
+    pub policy: IdPolicy,
+}
+
+/// MetricsConfig is settings of repository metrics shown by `stats --internal`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct MetricsConfig {
+    /// record is false by default, so that commands which only read tasks write nothing.
+    #[serde(default)]
+    pub record: bool,
+}
+
+/// NextConfig is settings of tasks suggested by `next`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct NextConfig {
+    /// minutes_per_cost is the time which a cost takes.
+    pub minutes_per_cost: u32,
+}
+
+impl Default for NextConfig {
+    fn default() -> Self {
+        NextConfig {
+            minutes_per_cost: 15,
+        }
+    }
+}
+
+/// QuotaPeriod is the period which a quota limits time in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QuotaPeriod {
+    Day,
+    Week,
+    Month,
+}
+
+impl fmt::Display for QuotaPeriod {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            QuotaPeriod::Day => write!(f, "day"),
+            QuotaPeriod::Week => write!(f, "week"),
+            QuotaPeriod::Month => write!(f, "month"),
+        }
+    }
+}
+
+/// Quota is the time allowed for a tag in a period, written such as `5h/week` or `1h30m/day`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct Quota {
+    pub minutes: u32,
+    pub period: QuotaPeriod,
+}
+
+impl TryFrom<String> for Quota {
+    type Error = String;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        let invalid = || format!("`{}` is not a quota such as 5h/week", s);
+        let (time, period) = s.split_once('/').ok_or_else(invalid)?;
+        let period = match period {
+            "day" => QuotaPeriod::Day,
+            "week" => QuotaPeriod::Week,
+            "month" => QuotaPeriod::Month,
+            _ => return Err(invalid()),
+        };
+
+        let mut minutes: u32 = 0;
+        let mut digits = String::new();
+        for c in time.chars() {
+            if c.is_ascii_digit() {
+                digits.push(c);
+                continue;
+            }
+            let unit = match c {
+                'h' => 60,
+                'm' => 1,
+                _ => return Err(invalid()),
+            };
+            let n: u32 = digits.parse().map_err(|_| invalid())?;
+            minutes = n
+                .checked_mul(unit)
+                .and_then(|m| minutes.checked_add(m))
+                .ok_or_else(invalid)?;
+            digits.clear();
+        }
+        if !digits.is_empty() || minutes == 0 {
+            return Err(invalid());
+        }
+
+        Ok(Quota { minutes, period })
+    }
+}
+
+impl From<Quota> for String {
+    fn from(quota: Quota) -> Self {
+        quota.to_string()
+    }
+}
+
+impl fmt::Display for Quota {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (self.minutes / 60, self.minutes % 60) {
+            (0, m) => write!(f, "{}m/{}", m, self.period),
+            (h, 0) => write!(f, "{}h/{}", h, self.period),
+            (h, m) => write!(f, "{}h{}m/{}", h, m, self.period),
+        }
+    }
+}
+
+/// ScoringStrategyKind is the strategy which scores tasks.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ScoringStrategyKind {
+    /// the weighted sum of the importance and the urgency.
+    #[default]
+    Weighted,
+    /// the priority per cost.
+    PriorityPerCost,
+    /// weighted shortest job first, the priority plus the urgency per cost.
+    Wsjf,
+    /// the weighted sum raised as the due date comes near.
+    Deadline,
+}
+
+/// ScoreConfig is weights of the dimensions combined into the score of a task.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ScoreConfig {
+    /// strategy scores tasks with the weights.
+    pub strategy: ScoringStrategyKind,
+    /// importance is the weight of the priority.
+    pub importance: i32,
+    /// urgency is the weight of the urgency.
+    pub urgency: i32,
+    /// threshold is the value from which a dimension counts as high in the matrix.
+    pub threshold: i32,
+}
+
+impl Default for ScoreConfig {
+    fn default() -> Self {
+        ScoreConfig {
+            strategy: ScoringStrategyKind::default(),
+            importance: 1,
+            urgency: 1,
+            threshold: 20,
+        }
+    }
+}
+
+/// Config is user settings.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    #[serde(default)]
+    pub auto_context: AutoContext,
+    #[serde(default)]
+    pub color: ColorChoice,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub database: Option<PathBuf>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub engine: Option<Engine>,
+    #[serde(default)]
+    pub event_log: EventLogConfig,
+    #[serde(default)]
+    pub cron: CronConfig,
+    #[serde(default)]
+    pub defaults: DefaultsConfig,
+    #[serde(default)]
+    pub ids: IdsConfig,
+    #[serde(default)]
+    pub lint: LintConfig,
+    #[serde(default)]
+    pub list: ListConfig,
+    #[serde(default)]
+    pub metrics: MetricsConfig,
+    #[serde(default)]
+    pub next: NextConfig,
+    #[serde(default)]
+    pub outstanding: OutstandingConfig,
+    /// quota is time allowed per tag.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub quota: BTreeMap<String, Quota>,
+    /// rules are applied to added tasks in order.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub rules: Vec<RuleConfig>,
+    #[serde(default)]
+    pub score: ScoreConfig,
+    #[serde(skip)]
+    pub scope: Option<Scope>,
+    /// tags is styles of tags by name.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub tags: BTreeMap<String, TagStyle>,
+}
+
+impl Config {
+    /// load reads the config file. The default Config is returned if the file does not exist.
+    pub fn load(path: &Path) -> Result<Config> {
+        if !path.is_file() {
+            return Ok(Config::default());
+        }
+
+        let source = fs::read_to_string(path)
+            .map_err(|err| Error::config(format!("failed to read {}", path.display()), err))?;
+        Config::parse(&source)
+            .map_err(|err| Error::config(format!("invalid config {}", path.display()), err))
+    }
+
+    /// parse parses the content of a config file.
+    pub fn parse(source: &str) -> Result<Config> {
+        Ok(toml::from_str(source)?)
+    }
+
+    /// with_scope_from applies the nearest `.taskmr.toml` found by walking up from `dir`.
+    pub fn with_scope_from(mut self, dir: &Path) -> Result<Config> {
+        let path = match find_scope_file(dir) {
+            Some(path) => path,
+            None => return Ok(self),
+        };
+
+        let source = fs::read_to_string(&path)
+            .map_err(|err| Error::config(format!("failed to read {}", path.display()), err))?;
+        let mut scope: Scope = toml::from_str(&source)
+            .map_err(|err| Error::config(format!("invalid scope {}", path.display()), err))?;
+        scope.path = path;
+        self.scope = Some(scope);
+
+        Ok(self)
+    }
+
+    /// database_path returns the task database, resolving a relative path against `config_dir`.
+    pub fn database_path(&self, config_dir: &Path) -> PathBuf {
+        match &self.database {
+            Some(path) => config_dir.join(path),
+            None => config_dir.join(DATABASE_FILE_NAME),
+        }
+    }
+
+    /// event_log_key reads the key signing the event chain, resolving a relative path against
+    /// `config_dir`. It is None if no key file is set.
+    pub fn event_log_key(&self, config_dir: &Path) -> Result<Option<Vec<u8>>> {
+        let Some(path) = &self.event_log.key_file else {
+            return Ok(None);
+        };
+        let path = config_dir.join(path);
+        let key = fs::read(&path)
+            .map_err(|err| Error::config(format!("failed to read {}", path.display()), err))?;
+        Ok(Some(key))
+    }
+
+    /// scope_tags returns tags pinned by the scope.
+    pub fn scope_tags(&self) -> &[String] {
+        self.scope
+            .as_ref()
+            .map(|s| s.tags.as_slice())
+            .unwrap_or(&[])
+    }
+}
+
+/// find_scope_file returns the nearest `.taskmr.toml` in `dir` or its ancestors.
+pub fn find_scope_file(dir: &Path) -> Option<PathBuf> {
+    dir.ancestors()
+        .map(|d| d.join(SCOPE_FILE_NAME))
+        .find(|p| p.is_file())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        #[derive(Debug)]
+        struct TestCase {
+            args: &'static str,
+            want: Option<Config>,
+            name: String,
+        }
+
+        let table = [
+            TestCase {
+                name: String::from("normal: empty"),
+                args: "",
+                want: Some(Config::default()),
+            },
+            TestCase {
+                name: String::from("normal: git"),
+                args: r#"auto_context = "git""#,
+                want: Some(Config {
+                    auto_context: AutoContext::Git,
+                    ..Config::default()
+                }),
+            },
+            TestCase {
+                name: String::from("normal: rules"),
+                args: "[[rules]]\nwhen_title = \"bug|fix\"\nadd_tags = [\"bug\"]\nmin_priority = 60\n[[rules]]\nwhen_tags = [\"bug\"]\nadd_tags = [\"triage\"]",
+                want: Some(Config {
+                    rules: vec![
+                        RuleConfig {
+                            when_title: Some(String::from("bug|fix")),
+                            add_tags: vec![String::from("bug")],
+                            min_priority: Some(60),
+                            ..RuleConfig::default()
+                        },
+                        RuleConfig {
+                            when_tags: vec![String::from("bug")],
+                            add_tags: vec![String::from("triage")],
+                            ..RuleConfig::default()
+                        },
+                    ],
+                    ..Config::default()
+                }),
+            },
+            TestCase {
+                name: String::from("normal: cron section"),
+                args: "[cron]\ndigest = true",
+                want: Some(Config {
+                    cron: CronConfig { digest: true },
+                    ..Config::default()
+                }),
+            },
+            TestCase {
+                name: String::from("normal: event_log section"),
+                args: "[event_log]\nchain = true\nkey_file = \"chain.key\"",
+                want: Some(Config {
+                    event_log: EventLogConfig {
+                        chain: true,
+                        key_file: Some(PathBuf::from("chain.key")),
+                    },
+                    ..Config::default()
+                }),
+            },
+            TestCase {
+                name: String::from("normal: list section"),
+                args: "[list]\nsummary = true",
+                want: Some(Config {
+                    list: ListConfig { summary: true },
+                    ..Config::default()
+                }),
+            },
+            TestCase {
+                name: String::from("normal: defaults section"),
+                args: "color = \"never\"\n[defaults]\npriority = 20",
+                want: Some(Config {
+                    color: ColorChoice::Never,
+                    defaults: DefaultsConfig {
+                        priority: Some(20),
+                        cost: None,
+                    },
+                    ..Config::default()
+                }),
+            },
+            TestCase {
+                name: String::from("normal: ids section"),
+                args: "[ids]\npolicy = \"reuse\"",
+                want: Some(Config {
+                    ids: IdsConfig {
+                        policy: IdPolicy::Reuse,
+                    },
+                    ..Config::default()
+                }),
+            },
+            TestCase {
+                name: String::from("normal: lint section"),
+                args: "[lint]\nmax_length = 72\nfirst_words = [\"fix\"]\nfirst_letter = \"upper\"\nlevel = \"error\"",
+                want: Some(Config {
+                    lint: LintConfig {
+                        max_length: Some(72),
+                        no_trailing_period: false,
+                        first_words: vec![String::from("fix")],
+                        first_letter: Some(FirstLetter::Upper),
+                        level: LintLevel::Error,
+                    },
+                    ..Config::default()
+                }),
+            },
+            TestCase {
+                name: String::from("normal: metrics section"),
+                args: "[metrics]\nrecord = true",
+                want: Some(Config {
+                    metrics: MetricsConfig { record: true },
+                    ..Config::default()
+                }),
+            },
+            TestCase {
+                name: String::from("normal: next section"),
+                args: "[next]\nminutes_per_cost = 30",
+                want: Some(Config {
+                    next: NextConfig {
+                        minutes_per_cost: 30,
+                    },
+                    ..Config::default()
+                }),
+            },
+            TestCase {
+                name: String::from("normal: outstanding section"),
+                args: "[outstanding]\nshow = true\nweekly_capacity = 20\nmax_weeks = 4",
+                want: Some(Config {
+                    outstanding: OutstandingConfig {
+                        show: true,
+                        weekly_capacity: Some(20),
+                        max_weeks: Some(4),
+                    },
+                    ..Config::default()
+                }),
+            },
+            TestCase {
+                name: String::from("normal: quota section"),
+                args: "[quota]\nmeetings = \"5h/week\"\nreview = \"1h30m/day\"",
+                want: Some(Config {
+                    quota: BTreeMap::from([
+                        (
+                            String::from("meetings"),
+                            Quota {
+                                minutes: 300,
+                                period: QuotaPeriod::Week,
+                            },
+                        ),
+                        (
+                            String::from("review"),
+                            Quota {
+                                minutes: 90,
+                                period: QuotaPeriod::Day,
+                            },
+                        ),
+                    ]),
+                    ..Config::default()
+                }),
+            },
+            TestCase {
+                name: String::from("abnormal: quota without period"),
+                args: "[quota]\nmeetings = \"5h\"",
+                want: None,
+            },
+            TestCase {
+                name: String::from("normal: score section"),
+                args: "[score]\nstrategy = \"wsjf\"\nurgency = 2",
+                want: Some(Config {
+                    score: ScoreConfig {
+                        strategy: ScoringStrategyKind::Wsjf,
+                        urgency: 2,
+                        ..ScoreConfig::default()
+                    },
+                    ..Config::default()
+                }),
+            },
+            TestCase {
+                name: String::from("normal: tags section"),
+                args: "[tags.bug]\ncolor = \"red\"\n[tags.idea]\nicon = \"💡\"",
+                want: Some(Config {
+                    tags: BTreeMap::from([
+                        (
+                            String::from("bug"),
+                            TagStyle {
+                                color: Some(TagColor::Red),
+                                icon: None,
+                            },
+                        ),
+                        (
+                            String::from("idea"),
+                            TagStyle {
+                                color: None,
+                                icon: Some(String::from("💡")),
+                            },
+                        ),
+                    ]),
+                    ..Config::default()
+                }),
+            },
+            TestCase {
+                name: String::from("abnormal: unknown tag color"),
+                args: "[tags.bug]\ncolor = \"crimson\"",
+                want: None,
+            },
+            TestCase {
+                name: String::from("normal: engine"),
+                args: r#"engine = "es""#,
+                want: Some(Config {
+                    engine: Some(Engine::Es),
+                    ..Config::default()
+                }),
+            },
+            TestCase {
+                name: String::from("abnormal: unknown context"),
+                args: r#"auto_context = "svn""#,
+                want: None,
+            },
+            TestCase {
+                name: String::from("abnormal: unknown key"),
+                args: r#"auto_contxt = "git""#,
+                want: None,
+            },
+        ];
+
+        for test_case in table {
+            let got = Config::parse(test_case.args).ok();
+            assert_eq!(got, test_case.want, "Failed in the \"{}\".", test_case.name);
+        }
+    }
+
+    #[test]
+    fn test_database_path() {
+        let config_dir = Path::new("/home/me/.config/taskmr");
+        let mut config = Config::default();
+        assert_eq!(
+            config.database_path(config_dir),
+            config_dir.join(DATABASE_FILE_NAME)
+        );
+
+        config.database = Some(PathBuf::from("db/tasks.db"));
+        assert_eq!(
+            config.database_path(config_dir),
+            config_dir.join("db/tasks.db")
+        );
+
+        config.database = Some(PathBuf::from("/srv/tasks.db"));
+        assert_eq!(
+            config.database_path(config_dir),
+            PathBuf::from("/srv/tasks.db")
+        );
+    }
+
+    #[test]
+    fn test_with_scope_from() {
+        let mut root = std::env::temp_dir();
+        root.push(format!("taskmr-scope-test-{}", std::process::id()));
+        let nested = root.join("project").join("src");
+        fs::create_dir_all(&nested).unwrap();
+        fs::write(
+            root.join("project").join(SCOPE_FILE_NAME),
+            r#"tags = ["project"]"#,
+        )
+        .unwrap();
+
+        let got = Config::default().with_scope_from(&nested).unwrap();
+        assert_eq!(got.scope_tags(), &[String::from("project")]);
+        assert_eq!(
+            got.scope.unwrap().path,
+            root.join("project").join(SCOPE_FILE_NAME)
+        );
+
+        let got = Config::default().with_scope_from(&root).unwrap();
+        assert!(got.scope_tags().is_empty());
+
+        fs::remove_dir_all(root).unwrap();
+    }
+}
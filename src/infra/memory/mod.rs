@@ -0,0 +1,7 @@
+//! # memory
+//!
+//! memory keeps tasks in memory. It serves tests which need no database,
+//! and shows what implementations of the repository interfaces have to do.
+
+#[cfg(feature = "legacy")]
+pub mod task_repository;
@@ -0,0 +1,262 @@
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+
+use crate::Result;
+
+use crate::domain::task::{
+    ITaskRepository, JournalChange, JournalEntry, Task, TaskOrder, TaskRepositoryError, ID,
+};
+
+/// Implementation of ITaskRepository keeping tasks in memory.
+#[derive(Debug, Default)]
+pub struct TaskRepository {
+    tasks: RefCell<BTreeMap<i64, Task>>,
+    /// journal keeps changes from the oldest.
+    journal: RefCell<Vec<JournalEntry>>,
+}
+
+impl TaskRepository {
+    /// Construct an empty TaskRepository.
+    pub fn new() -> TaskRepository {
+        TaskRepository::default()
+    }
+
+    /// list stored tasks which satisfy `predicate` in the order.
+    fn list(&self, predicate: impl Fn(&Task) -> bool, order: TaskOrder) -> Vec<Task> {
+        let mut tasks: Vec<Task> = self
+            .tasks
+            .borrow()
+            .values()
+            .filter(|t| predicate(t))
+            .map(copy)
+            .collect();
+        // NOTE: the sort is stable, so that ties stay in the order of ids.
+        match order {
+            TaskOrder::Id => {}
+            TaskOrder::Priority => tasks.sort_by_key(|t| std::cmp::Reverse(t.priority().get())),
+            TaskOrder::CreatedAt => {
+                tasks.sort_by(|a, b| b.created_at().cmp(a.created_at()));
+            }
+            TaskOrder::UpdatedAt => {
+                tasks.sort_by(|a, b| b.updated_at().cmp(a.updated_at()));
+            }
+        }
+        tasks
+    }
+
+    /// record appends the change to the journal.
+    /// NOTE: recorded_at is left empty, since this repository has no clock without the es feature.
+    fn record(&self, task_id: ID, change: JournalChange, before: Option<Task>) {
+        self.journal.borrow_mut().push(JournalEntry {
+            task_id,
+            change,
+            before,
+            recorded_at: String::new(),
+        });
+    }
+}
+
+impl ITaskRepository for TaskRepository {
+    /// find a Task by id.
+    fn find_by_id(&self, id: ID) -> Result<Option<Task>> {
+        Ok(self
+            .tasks
+            .borrow()
+            .get(&id.get())
+            .filter(|t| !t.is_deleted())
+            .map(copy))
+    }
+
+    /// find tasks that is not closed.
+    fn find_opening(&self, order: TaskOrder) -> Result<Vec<Task>> {
+        Ok(self.list(|t| !t.is_closed() && !t.is_deleted(), order))
+    }
+
+    /// fetch all tasks regardless it is closed.
+    fn fetch_all(&self, order: TaskOrder) -> Result<Vec<Task>> {
+        Ok(self.list(|t| !t.is_deleted(), order))
+    }
+
+    /// find deleted tasks regardless it is closed.
+    fn find_deleted(&self, order: TaskOrder) -> Result<Vec<Task>> {
+        Ok(self.list(|t| t.is_deleted(), order))
+    }
+
+    /// search tasks whose titles have words beginning with each word of the query, in the order of ids.
+    fn search(&self, query: &str) -> Result<Vec<Task>> {
+        let query = query.to_lowercase();
+        let words: Vec<&str> = query.split_whitespace().collect();
+        if words.is_empty() {
+            return Ok(vec![]);
+        }
+        Ok(self.list(
+            |t| {
+                let title = t.title().to_lowercase();
+                !t.is_deleted()
+                    && words.iter().all(|w| {
+                        title
+                            .split(|c: char| !c.is_alphanumeric())
+                            .any(|tw| tw.starts_with(w))
+                    })
+            },
+            TaskOrder::Id,
+        ))
+    }
+
+    /// add a Task. ID is the largest stored ID plus one, like INTEGER PRIMARY KEY of SQLite.
+    fn add(&self, a_task: Task) -> Result<ID> {
+        let mut tasks = self.tasks.borrow_mut();
+        let id = ID::new(tasks.keys().next_back().map_or(1, |id| id + 1));
+        tasks.insert(id.get(), with_id(&a_task, id));
+        self.record(id, JournalChange::Added, None);
+        Ok(id)
+    }
+
+    /// update a Task.
+    fn update(&self, a_task: Task) -> Result<()> {
+        let mut tasks = self.tasks.borrow_mut();
+        let stored = tasks
+            .get_mut(&a_task.id().get())
+            .ok_or(TaskRepositoryError::NotFound(a_task.id().get()))?;
+        let before = std::mem::replace(stored, a_task);
+        self.record(before.id(), JournalChange::Updated, Some(before));
+        Ok(())
+    }
+
+    /// undo reverts the latest change in the journal and removes it from the journal.
+    fn undo(&self) -> Result<Option<JournalEntry>> {
+        let Some(entry) = self.journal.borrow_mut().pop() else {
+            return Ok(None);
+        };
+        let mut tasks = self.tasks.borrow_mut();
+        match &entry.before {
+            None => tasks.remove(&entry.task_id.get()),
+            Some(before) => tasks.insert(before.id().get(), copy(before)),
+        };
+        Ok(Some(entry))
+    }
+
+    /// history reads the journal from the latest change.
+    fn history(&self, id: Option<ID>) -> Result<Vec<JournalEntry>> {
+        Ok(self
+            .journal
+            .borrow()
+            .iter()
+            .rev()
+            .filter(|e| id.is_none_or(|id| e.task_id == id))
+            .map(|e| JournalEntry {
+                task_id: e.task_id,
+                change: e.change,
+                before: e.before.as_ref().map(copy),
+                recorded_at: e.recorded_at.clone(),
+            })
+            .collect())
+    }
+}
+
+/// copy copies the task, since tasks are entities which are not Clone.
+fn copy(task: &Task) -> Task {
+    with_id(task, task.id())
+}
+
+/// with_id copies the task with the id.
+fn with_id(task: &Task, id: ID) -> Task {
+    Task::from_repository(
+        id,
+        task.title().to_owned(),
+        task.is_closed(),
+        task.is_deleted(),
+        task.priority(),
+        task.cost(),
+        task.elapsed_time(),
+        task.created_at().to_owned(),
+        task.updated_at().to_owned(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::task::{Cost, Priority};
+    use crate::domain::task_builder::TaskBuilder;
+    use crate::usecase::close_task_usecase::{CloseTaskUseCase, CloseTaskUseCaseInput};
+    use std::rc::Rc;
+
+    #[test]
+    fn test_repository() {
+        let task_repository = TaskRepository::new();
+        for (title, priority) in [("low", 1), ("high", 30), ("also high", 30)] {
+            task_repository
+                .add(Task::new(
+                    TaskBuilder::new(title).priority(priority).cost(1),
+                ))
+                .unwrap();
+        }
+
+        let titles = |tasks: Vec<Task>| -> Vec<String> {
+            tasks.iter().map(|t| t.title().to_owned()).collect()
+        };
+        assert_eq!(
+            titles(task_repository.fetch_all(TaskOrder::Priority).unwrap()),
+            ["high", "also high", "low"]
+        );
+
+        let mut task = task_repository.find_by_id(ID::new(2)).unwrap().unwrap();
+        task.close();
+        task_repository.update(task).unwrap();
+        assert_eq!(
+            titles(task_repository.find_opening(TaskOrder::Id).unwrap()),
+            ["low", "also high"]
+        );
+        assert_eq!(task_repository.find_by_id(ID::new(4)).unwrap(), None);
+
+        let err = task_repository
+            .update(Task::from_repository(
+                ID::new(4),
+                "missing".to_owned(),
+                false,
+                false,
+                Priority::new(1),
+                Cost::new(1),
+                std::time::Duration::ZERO,
+                String::new(),
+                String::new(),
+            ))
+            .unwrap_err();
+        assert_eq!(
+            err.downcast_ref::<TaskRepositoryError>(),
+            Some(&TaskRepositoryError::NotFound(4))
+        );
+    }
+
+    #[test]
+    fn test_with_usecase() {
+        let task_repository = Rc::new(TaskRepository::new());
+        task_repository
+            .add(Task::new(TaskBuilder::new("title")))
+            .unwrap();
+
+        let usecase = CloseTaskUseCase::new(task_repository.clone());
+        usecase.execute(CloseTaskUseCaseInput { id: 1 }).unwrap();
+        assert!(task_repository
+            .find_by_id(ID::new(1))
+            .unwrap()
+            .unwrap()
+            .is_closed());
+
+        assert_eq!(task_repository.history(Some(ID::new(1))).unwrap().len(), 2);
+        let entry = task_repository.undo().unwrap().unwrap();
+        assert_eq!(entry.change, JournalChange::Updated);
+        assert!(!task_repository
+            .find_by_id(ID::new(1))
+            .unwrap()
+            .unwrap()
+            .is_closed());
+        assert_eq!(
+            task_repository.undo().unwrap().unwrap().change,
+            JournalChange::Added
+        );
+        assert_eq!(task_repository.find_by_id(ID::new(1)).unwrap(), None);
+        assert_eq!(task_repository.undo().unwrap(), None);
+    }
+}
@@ -2,4 +2,12 @@
 //!
 //! infra is a layer which has responsibility to communicate external services.
 
+pub mod bundle;
+pub mod config;
+pub mod memory;
+#[cfg(feature = "scripting")]
+pub mod script;
+pub mod selection;
 pub mod sqlite;
+#[cfg(feature = "webhook")]
+pub mod webhook;
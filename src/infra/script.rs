@@ -0,0 +1,364 @@
+//! # script
+//!
+//! script runs user scripts written in Rhai to define custom list filters and reports.
+//!
+//! A script is `<scripts dir>/<name>.rhai` and defines some of the functions below.
+//!
+//! - `fn filter(task)` returns true if the task should be listed.
+//! - `fn report(tasks)` returns a string, or an array of lines, to be printed.
+//!
+//! A task is an object map which has `id`, `title`, `priority`, `cost` and `tags`.
+//! `waiting_on` is the person whom the task waits on or `()`, and `nudge` tells whether to follow up.
+//! Scripts are sandboxed, they can not touch files, import modules nor run forever.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::Result;
+use rhai::module_resolvers::DummyModuleResolver;
+use rhai::{Array, Dynamic, Engine, Map, Scope, AST};
+use thiserror::Error;
+
+/// SCRIPT_EXTENSION is the extension of script files.
+const SCRIPT_EXTENSION: &str = "rhai";
+
+/// MAX_OPERATIONS limits operations of one function call to stop infinite loops.
+const MAX_OPERATIONS: u64 = 1_000_000;
+
+/// ScriptError is raised when a user script can not be used.
+#[derive(Error, Debug, PartialEq, Eq)]
+pub enum ScriptError {
+    #[error("script `{0}` is not found")]
+    NotFound(String),
+    #[error("script `{name}` has a syntax error: {message}")]
+    Compile { name: String, message: String },
+    #[error("script `{name}` failed in `{function}`: {message}")]
+    Runtime {
+        name: String,
+        function: &'static str,
+        message: String,
+    },
+    #[error("`{function}` of script `{name}` must return {expected}")]
+    InvalidReturn {
+        name: String,
+        function: &'static str,
+        expected: &'static str,
+    },
+}
+
+/// ScriptTask is a task exposed to scripts.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScriptTask {
+    pub id: i64,
+    pub title: String,
+    pub priority: i32,
+    pub cost: i32,
+    pub tags: Vec<String>,
+    pub waiting_on: Option<String>,
+    /// nudge is true if the day to follow up with the person has come.
+    pub nudge: bool,
+}
+
+impl ScriptTask {
+    /// to_map converts the task into an object map of Rhai.
+    fn to_map(&self) -> Map {
+        let mut map = Map::new();
+        map.insert("id".into(), Dynamic::from(self.id));
+        map.insert("title".into(), Dynamic::from(self.title.clone()));
+        map.insert("priority".into(), Dynamic::from(self.priority as i64));
+        map.insert("cost".into(), Dynamic::from(self.cost as i64));
+        let tags: Array = self.tags.iter().cloned().map(Dynamic::from).collect();
+        map.insert("tags".into(), Dynamic::from(tags));
+        let waiting_on = match &self.waiting_on {
+            Some(person) => Dynamic::from(person.clone()),
+            None => Dynamic::UNIT,
+        };
+        map.insert("waiting_on".into(), waiting_on);
+        map.insert("nudge".into(), Dynamic::from(self.nudge));
+        map
+    }
+}
+
+/// Script is a compiled user script.
+pub struct Script {
+    name: String,
+    ast: AST,
+}
+
+impl Script {
+    /// get name.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+/// ScriptEngine loads and runs user scripts in a sandbox.
+pub struct ScriptEngine {
+    engine: Engine,
+    scripts_dir: PathBuf,
+}
+
+impl ScriptEngine {
+    /// construct a ScriptEngine which loads scripts from `scripts_dir`.
+    pub fn new(scripts_dir: PathBuf) -> Self {
+        let mut engine = Engine::new();
+        engine
+            .set_max_operations(MAX_OPERATIONS)
+            .set_max_call_levels(32)
+            .set_max_expr_depths(64, 32)
+            .set_max_string_size(1 << 16)
+            .set_max_array_size(1 << 16)
+            .set_max_map_size(1 << 16)
+            .set_module_resolver(DummyModuleResolver::new())
+            .disable_symbol("eval");
+
+        ScriptEngine {
+            engine,
+            scripts_dir,
+        }
+    }
+
+    /// get scripts_dir.
+    pub fn scripts_dir(&self) -> &Path {
+        &self.scripts_dir
+    }
+
+    /// list_scripts returns names of scripts in the scripts directory ordered by name.
+    pub fn list_scripts(&self) -> Result<Vec<String>> {
+        if !self.scripts_dir.is_dir() {
+            return Ok(Vec::new());
+        }
+
+        let mut names = Vec::new();
+        for entry in fs::read_dir(&self.scripts_dir)? {
+            let path = entry?.path();
+            if path.extension().and_then(|e| e.to_str()) != Some(SCRIPT_EXTENSION) {
+                continue;
+            }
+            if let Some(stem) = path.file_stem().and_then(|s| s.to_str()) {
+                names.push(stem.to_owned());
+            }
+        }
+        names.sort();
+
+        Ok(names)
+    }
+
+    /// load compiles `<scripts dir>/<name>.rhai`.
+    pub fn load(&self, name: &str) -> Result<Script> {
+        let mut path = self.scripts_dir.join(name);
+        path.set_extension(SCRIPT_EXTENSION);
+        if !path.is_file() {
+            return Err(ScriptError::NotFound(name.to_owned()).into());
+        }
+
+        let source = fs::read_to_string(&path)?;
+        Ok(self.compile(name, &source)?)
+    }
+
+    /// compile compiles the source as a script named `name`.
+    pub fn compile(&self, name: &str, source: &str) -> Result<Script, ScriptError> {
+        let ast = self
+            .engine
+            .compile(source)
+            .map_err(|err| ScriptError::Compile {
+                name: name.to_owned(),
+                message: err.to_string(),
+            })?;
+
+        Ok(Script {
+            name: name.to_owned(),
+            ast,
+        })
+    }
+
+    /// filter keeps tasks for which `filter(task)` of the script returns true.
+    pub fn filter(
+        &self,
+        script: &Script,
+        tasks: Vec<ScriptTask>,
+    ) -> Result<Vec<ScriptTask>, ScriptError> {
+        let mut filtered = Vec::new();
+        for task in tasks {
+            let keep = self.call(script, "filter", task.to_map())?;
+            match keep.as_bool() {
+                Ok(true) => filtered.push(task),
+                Ok(false) => {}
+                Err(_) => {
+                    return Err(ScriptError::InvalidReturn {
+                        name: script.name.clone(),
+                        function: "filter",
+                        expected: "a bool",
+                    })
+                }
+            }
+        }
+
+        Ok(filtered)
+    }
+
+    /// report returns the output of `report(tasks)` of the script.
+    pub fn report(&self, script: &Script, tasks: &[ScriptTask]) -> Result<String, ScriptError> {
+        let tasks: Array = tasks.iter().map(|t| Dynamic::from(t.to_map())).collect();
+        let output = self.call(script, "report", tasks)?;
+
+        if output.is_string() {
+            return Ok(output.to_string());
+        }
+
+        match output.try_cast::<Array>() {
+            Some(lines) => Ok(lines
+                .iter()
+                .map(|l| l.to_string())
+                .collect::<Vec<_>>()
+                .join("\n")),
+            None => Err(ScriptError::InvalidReturn {
+                name: script.name.clone(),
+                function: "report",
+                expected: "a string or an array",
+            }),
+        }
+    }
+
+    /// call calls the function of the script with one argument.
+    fn call<A: Into<Dynamic>>(
+        &self,
+        script: &Script,
+        function: &'static str,
+        arg: A,
+    ) -> Result<Dynamic, ScriptError> {
+        self.engine
+            .call_fn::<Dynamic>(&mut Scope::new(), &script.ast, function, (arg.into(),))
+            .map_err(|err| ScriptError::Runtime {
+                name: script.name.clone(),
+                function,
+                message: err.to_string(),
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tasks() -> Vec<ScriptTask> {
+        vec![
+            ScriptTask {
+                id: 1,
+                title: String::from("write docs"),
+                priority: 10,
+                cost: 3,
+                tags: vec![String::from("docs")],
+                waiting_on: Some(String::from("Alice")),
+                nudge: true,
+            },
+            ScriptTask {
+                id: 2,
+                title: String::from("fix bug"),
+                priority: 1,
+                cost: 8,
+                tags: vec![],
+                waiting_on: None,
+                nudge: false,
+            },
+        ]
+    }
+
+    #[test]
+    fn test_filter() {
+        #[derive(Debug)]
+        struct TestCase {
+            args: &'static str,
+            want: Result<Vec<i64>, ScriptError>,
+            name: String,
+        }
+
+        let table = [
+            TestCase {
+                name: String::from("normal: filter by priority"),
+                args: "fn filter(task) { task.priority >= 5 }",
+                want: Ok(vec![1]),
+            },
+            TestCase {
+                name: String::from("normal: filter by title"),
+                args: r#"fn filter(task) { task.title.contains("bug") }"#,
+                want: Ok(vec![2]),
+            },
+            TestCase {
+                name: String::from("normal: filter by tag"),
+                args: r#"fn filter(task) { "docs" in task.tags }"#,
+                want: Ok(vec![1]),
+            },
+            TestCase {
+                name: String::from("normal: filter by nudge"),
+                args: r#"fn filter(task) { task.nudge && task.waiting_on == "Alice" }"#,
+                want: Ok(vec![1]),
+            },
+            TestCase {
+                name: String::from("normal: filter by no one to wait on"),
+                args: "fn filter(task) { task.waiting_on == () }",
+                want: Ok(vec![2]),
+            },
+            TestCase {
+                name: String::from("abnormal: not bool"),
+                args: "fn filter(task) { task.cost }",
+                want: Err(ScriptError::InvalidReturn {
+                    name: String::from("test"),
+                    function: "filter",
+                    expected: "a bool",
+                }),
+            },
+        ];
+
+        let engine = ScriptEngine::new(PathBuf::new());
+        for test_case in table {
+            let script = engine.compile("test", test_case.args).unwrap();
+            let got = engine
+                .filter(&script, tasks())
+                .map(|ts| ts.iter().map(|t| t.id).collect::<Vec<_>>());
+            assert_eq!(got, test_case.want, "Failed in the \"{}\".", test_case.name);
+        }
+    }
+
+    #[test]
+    fn test_report() {
+        let engine = ScriptEngine::new(PathBuf::new());
+
+        let script = engine
+            .compile(
+                "cost",
+                r#"fn report(tasks) {
+                    let total = 0;
+                    for t in tasks { total += t.cost; }
+                    ["tasks: " + tasks.len(), "cost: " + total]
+                }"#,
+            )
+            .unwrap();
+        assert_eq!(
+            engine.report(&script, &tasks()).unwrap(),
+            "tasks: 2\ncost: 11"
+        );
+    }
+
+    #[test]
+    fn test_sandbox() {
+        let engine = ScriptEngine::new(PathBuf::new());
+
+        let script = engine
+            .compile("loop", "fn report(tasks) { loop {} }")
+            .unwrap();
+        assert!(matches!(
+            engine.report(&script, &tasks()),
+            Err(ScriptError::Runtime { .. })
+        ));
+
+        assert!(engine
+            .compile("eval", r#"fn report(tasks) { eval("1") }"#)
+            .is_err());
+
+        let script = engine
+            .compile("import", r#"import "fs" as fs; fn report(tasks) { "" }"#)
+            .unwrap();
+        assert!(engine.report(&script, &tasks()).is_err());
+    }
+}
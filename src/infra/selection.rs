@@ -0,0 +1,129 @@
+//! # selection
+//!
+//! selection persists row numbers of the last numbered list, so that the next command can refer
+//! to a task as `%2` instead of its id.
+
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+
+use crate::Result;
+use thiserror::Error;
+
+/// SelectionError is raised when a row number can not be resolved.
+#[derive(Error, Debug, PartialEq, Eq)]
+pub enum SelectionError {
+    #[error("no numbered list is found, run list with --number first")]
+    Empty,
+    #[error("row %{row} is not found, the last numbered list has {len} rows")]
+    OutOfRange { row: usize, len: usize },
+}
+
+/// SelectionStore saves ids of the rows in a plain text file, one id per line.
+pub struct SelectionStore {
+    path: PathBuf,
+}
+
+impl SelectionStore {
+    /// construct a SelectionStore.
+    pub fn new(path: PathBuf) -> Self {
+        SelectionStore { path }
+    }
+
+    /// save replaces the selection by ids ordered as the rows.
+    pub fn save(&self, ids: &[i64]) -> Result<()> {
+        let content: String = ids.iter().map(|id| format!("{}\n", id)).collect();
+        fs::write(&self.path, content)?;
+        Ok(())
+    }
+
+    /// load loads ids ordered as the rows.
+    pub fn load(&self) -> Result<Vec<i64>> {
+        let content = match fs::read_to_string(&self.path) {
+            Ok(content) => content,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(vec![]),
+            Err(err) => return Err(err.into()),
+        };
+
+        Ok(content
+            .lines()
+            .map(|l| l.trim().parse::<i64>())
+            .collect::<Result<Vec<_>, _>>()?)
+    }
+
+    /// resolve returns the id of the row numbered from 1.
+    pub fn resolve(&self, row: usize) -> Result<i64> {
+        let ids = self.load()?;
+        if ids.is_empty() {
+            return Err(SelectionError::Empty.into());
+        }
+
+        match row.checked_sub(1).and_then(|i| ids.get(i)) {
+            Some(id) => Ok(*id),
+            None => Err(SelectionError::OutOfRange {
+                row,
+                len: ids.len(),
+            }
+            .into()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolve() {
+        #[derive(Debug)]
+        struct TestCase {
+            given: Option<Vec<i64>>,
+            args: usize,
+            want: Result<i64, SelectionError>,
+            name: String,
+        }
+
+        let table = [
+            TestCase {
+                name: String::from("normal: resolve"),
+                given: Some(vec![4, 9, 2]),
+                args: 2,
+                want: Ok(9),
+            },
+            TestCase {
+                name: String::from("abnormal: out of range"),
+                given: Some(vec![4, 9, 2]),
+                args: 4,
+                want: Err(SelectionError::OutOfRange { row: 4, len: 3 }),
+            },
+            TestCase {
+                name: String::from("abnormal: zero"),
+                given: Some(vec![4]),
+                args: 0,
+                want: Err(SelectionError::OutOfRange { row: 0, len: 1 }),
+            },
+            TestCase {
+                name: String::from("abnormal: never numbered"),
+                given: None,
+                args: 1,
+                want: Err(SelectionError::Empty),
+            },
+        ];
+
+        for (i, test_case) in table.into_iter().enumerate() {
+            let mut path = std::env::temp_dir();
+            path.push(format!("taskmr-selection-{}-{}", std::process::id(), i));
+            let store = SelectionStore::new(path.clone());
+            if let Some(ids) = &test_case.given {
+                store.save(ids).unwrap();
+            }
+
+            let got = store
+                .resolve(test_case.args)
+                .map_err(|e| e.downcast::<SelectionError>().unwrap());
+            assert_eq!(got, test_case.want, "Failed in the \"{}\".", test_case.name);
+
+            let _ = fs::remove_file(path);
+        }
+    }
+}
@@ -0,0 +1,280 @@
+use std::cell::Cell;
+use std::thread;
+use std::time::Duration;
+
+use rusqlite::{Connection, ErrorCode};
+use thiserror::Error;
+
+/// StorageError is a typed error raised by the SQLite access layer.
+#[derive(Error, Debug)]
+pub enum StorageError {
+    #[error("the database is locked by another process, gave up after {retries} retries")]
+    Locked { retries: u32 },
+    #[error(transparent)]
+    Sqlite(#[from] rusqlite::Error),
+}
+
+/// RetryPolicy decides how many times and how long to wait when the database is busy.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetryPolicy {
+    max_retries: u32,
+    initial_backoff: Duration,
+    max_backoff: Duration,
+}
+
+impl RetryPolicy {
+    /// construct a RetryPolicy.
+    pub fn new(max_retries: u32, initial_backoff: Duration, max_backoff: Duration) -> Self {
+        RetryPolicy {
+            max_retries,
+            initial_backoff,
+            max_backoff,
+        }
+    }
+
+    /// get max_retries.
+    pub fn max_retries(&self) -> u32 {
+        self.max_retries
+    }
+
+    /// backoff returns the duration to wait before the given retry (counted from 1).
+    /// The duration doubles every retry and is capped by max_backoff.
+    pub fn backoff(&self, retry: u32) -> Duration {
+        let factor = 2u32.saturating_pow(retry.saturating_sub(1));
+        self.initial_backoff
+            .saturating_mul(factor)
+            .min(self.max_backoff)
+    }
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        // NOTE: about 3 seconds in total, as SQLite no longer waits by itself.
+        RetryPolicy::new(10, Duration::from_millis(20), Duration::from_millis(500))
+    }
+}
+
+/// AccessMetrics counts how the database is accessed.
+#[derive(Debug, Default)]
+pub struct AccessMetrics {
+    queries: Cell<u64>,
+    retries: Cell<u64>,
+    failures: Cell<u64>,
+}
+
+impl AccessMetrics {
+    /// get the number of executed queries including retried ones.
+    pub fn queries(&self) -> u64 {
+        self.queries.get()
+    }
+
+    /// get the number of retries caused by a busy database.
+    pub fn retries(&self) -> u64 {
+        self.retries.get()
+    }
+
+    /// get the number of queries which finally failed.
+    pub fn failures(&self) -> u64 {
+        self.failures.get()
+    }
+}
+
+/// RetryingConnection wraps rusqlite::Connection and retries queries while the database is busy.
+/// Every repository accesses SQLite through this layer because several taskmr processes may
+/// contend for one database file.
+pub struct RetryingConnection {
+    conn: Connection,
+    policy: RetryPolicy,
+    metrics: AccessMetrics,
+}
+
+impl RetryingConnection {
+    /// construct a RetryingConnection with the default RetryPolicy.
+    /// The busy timeout of SQLite is turned off, so that waits follow only the RetryPolicy
+    /// instead of stacking the backoff on the timeout of every attempt.
+    pub fn new(conn: Connection) -> Self {
+        if let Err(err) = conn.busy_timeout(Duration::ZERO) {
+            tracing::warn!(%err, "failed to turn off the busy timeout");
+        }
+        RetryingConnection {
+            conn,
+            policy: RetryPolicy::default(),
+            metrics: AccessMetrics::default(),
+        }
+    }
+
+    /// replace the RetryPolicy.
+    pub fn with_retry_policy(mut self, policy: RetryPolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    /// get metrics.
+    pub fn metrics(&self) -> &AccessMetrics {
+        &self.metrics
+    }
+
+    /// run executes `f` with the underlying connection.
+    /// `f` is invoked again with backoff while SQLite reports SQLITE_BUSY or SQLITE_LOCKED,
+    /// so `f` must not have side effects other than the database operations.
+    pub fn run<T, F>(&self, mut f: F) -> Result<T, StorageError>
+    where
+        F: FnMut(&Connection) -> rusqlite::Result<T>,
+    {
+        let mut retry = 0;
+        loop {
+            self.metrics.queries.set(self.metrics.queries.get() + 1);
+
+            let err = match f(&self.conn) {
+                Ok(v) => return Ok(v),
+                Err(err) => err,
+            };
+
+            if !is_busy(&err) {
+                self.metrics.failures.set(self.metrics.failures.get() + 1);
+                return Err(err.into());
+            }
+
+            if retry >= self.policy.max_retries() {
+                self.metrics.failures.set(self.metrics.failures.get() + 1);
+                tracing::warn!(
+                    retries = retry,
+                    queries = self.metrics.queries(),
+                    "gave up on a busy database"
+                );
+                return Err(StorageError::Locked { retries: retry });
+            }
+
+            retry += 1;
+            self.metrics.retries.set(self.metrics.retries.get() + 1);
+            let backoff = self.policy.backoff(retry);
+            tracing::debug!(
+                retry,
+                backoff_ms = backoff.as_millis() as u64,
+                total_retries = self.metrics.retries(),
+                "database is busy, retrying"
+            );
+            thread::sleep(backoff);
+        }
+    }
+}
+
+impl Drop for RetryingConnection {
+    fn drop(&mut self) {
+        tracing::debug!(
+            queries = self.metrics.queries(),
+            retries = self.metrics.retries(),
+            failures = self.metrics.failures(),
+            "sqlite access metrics"
+        );
+    }
+}
+
+/// is_busy reports whether the error is caused by another connection holding a lock.
+fn is_busy(err: &rusqlite::Error) -> bool {
+    matches!(
+        err.sqlite_error_code(),
+        Some(ErrorCode::DatabaseBusy) | Some(ErrorCode::DatabaseLocked)
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_db_path() -> std::path::PathBuf {
+        let mut path = std::env::temp_dir();
+        let nanos = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_nanos();
+        path.push(format!("taskmr-access-{}-{}.db", std::process::id(), nanos));
+        path
+    }
+
+    #[test]
+    fn test_backoff() {
+        #[derive(Debug)]
+        struct TestCase {
+            retry: u32,
+            want: Duration,
+            name: String,
+        }
+
+        let policy = RetryPolicy::new(10, Duration::from_millis(10), Duration::from_millis(50));
+
+        let table = [
+            TestCase {
+                name: String::from("first retry"),
+                retry: 1,
+                want: Duration::from_millis(10),
+            },
+            TestCase {
+                name: String::from("doubled"),
+                retry: 3,
+                want: Duration::from_millis(40),
+            },
+            TestCase {
+                name: String::from("capped"),
+                retry: 4,
+                want: Duration::from_millis(50),
+            },
+        ];
+
+        for test_case in table {
+            assert_eq!(
+                policy.backoff(test_case.retry),
+                test_case.want,
+                "Failed in the \"{}\".",
+                test_case.name,
+            );
+        }
+    }
+
+    #[test]
+    fn test_run_counts_queries() {
+        let conn = RetryingConnection::new(Connection::open_in_memory().unwrap());
+        conn.run(|c| c.execute("CREATE TABLE t (v INTEGER)", []))
+            .unwrap();
+        conn.run(|c| c.execute("INSERT INTO t (v) VALUES (1)", []))
+            .unwrap();
+        conn.run(|c| c.execute("INSERT INTO unknown (v) VALUES (1)", []))
+            .unwrap_err();
+
+        assert_eq!(conn.metrics().queries(), 3);
+        assert_eq!(conn.metrics().retries(), 0);
+        assert_eq!(conn.metrics().failures(), 1);
+    }
+
+    #[test]
+    fn test_run_gives_up_on_locked_database() {
+        let path = temp_db_path();
+
+        let holder = Connection::open(&path).unwrap();
+        holder.execute("CREATE TABLE t (v INTEGER)", []).unwrap();
+        holder.execute_batch("BEGIN EXCLUSIVE").unwrap();
+
+        let contender = Connection::open(&path).unwrap();
+        let conn = RetryingConnection::new(contender).with_retry_policy(RetryPolicy::new(
+            2,
+            Duration::from_millis(1),
+            Duration::from_millis(1),
+        ));
+
+        let err = conn
+            .run(|c| c.execute("INSERT INTO t (v) VALUES (1)", []))
+            .unwrap_err();
+
+        assert!(matches!(err, StorageError::Locked { retries: 2 }));
+        assert_eq!(conn.metrics().queries(), 3);
+        assert_eq!(conn.metrics().retries(), 2);
+
+        holder.execute_batch("COMMIT").unwrap();
+        conn.run(|c| c.execute("INSERT INTO t (v) VALUES (1)", []))
+            .unwrap();
+
+        drop(conn);
+        drop(holder);
+        std::fs::remove_file(path).unwrap();
+    }
+}
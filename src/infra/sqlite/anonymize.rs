@@ -0,0 +1,192 @@
+//! # anonymize
+//!
+//! anonymize replaces what users wrote, such as titles, tags and names, with pseudonyms, so that
+//! a database can be attached to a bug report. Ids, priorities, costs and times are kept.
+
+use hmac::{Hmac, Mac};
+use rusqlite::{params, Connection};
+use serde_json::Value;
+use sha2::Sha256;
+
+use super::snapshot::has_table;
+use crate::Result;
+
+/// KEPT_TAG_NAMESPACES are namespaces whose tags are a fixed vocabulary of taskmr, not user text.
+const KEPT_TAG_NAMESPACES: &[&str] = &["energy"];
+
+/// Anonymizer gives the same pseudonym to the same text, so that duplicates stay visible.
+/// Pseudonyms are keyed by a random key which is not kept, so that short texts can not be
+/// guessed back by hashing candidates.
+#[derive(Debug, Clone)]
+pub struct Anonymizer {
+    key: [u8; 32],
+}
+
+impl Default for Anonymizer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Anonymizer {
+    /// Construct an Anonymizer with a random key.
+    pub fn new() -> Self {
+        let mut key = [0; 32];
+        key.iter_mut().for_each(|b| *b = fastrand::u8(..));
+        Anonymizer { key }
+    }
+
+    /// title returns the pseudonym of a title.
+    pub fn title(&self, title: &str) -> String {
+        self.pseudonym("task", title)
+    }
+
+    /// description returns the pseudonym of a description.
+    pub fn description(&self, description: &str) -> String {
+        self.pseudonym("description", description)
+    }
+
+    /// tag returns the pseudonym of a tag. The namespace before `:` is kept, such as `repo:`.
+    pub fn tag(&self, tag: &str) -> String {
+        match tag.split_once(':') {
+            Some((ns, _)) if KEPT_TAG_NAMESPACES.contains(&ns) => tag.to_owned(),
+            Some((ns, value)) => format!("{}:{}", ns, self.pseudonym("tag", value)),
+            None => self.pseudonym("tag", tag),
+        }
+    }
+
+    /// person returns the pseudonym of a person whom a task waits on.
+    pub fn person(&self, person: &str) -> String {
+        self.pseudonym("person", person)
+    }
+
+    /// sprint returns the pseudonym of a sprint name.
+    pub fn sprint(&self, name: &str) -> String {
+        self.pseudonym("sprint", name)
+    }
+
+    /// pseudonym is the kind and 8 hex digits of the HMAC of the text.
+    fn pseudonym(&self, kind: &str, text: &str) -> String {
+        let mut mac =
+            Hmac::<Sha256>::new_from_slice(&self.key).expect("HMAC takes keys of any size");
+        mac.update(text.as_bytes());
+        let digest = mac.finalize().into_bytes();
+        let hex: String = digest[..4].iter().map(|b| format!("{:02x}", b)).collect();
+        format!("{}-{}", kind, hex)
+    }
+
+    /// json replaces texts in a stored event or read model, found by their keys.
+    fn json(&self, value: &mut Value) {
+        match value {
+            Value::Object(map) => {
+                for (key, value) in map.iter_mut() {
+                    match (key.as_str(), value) {
+                        ("title", Value::String(s)) => *s = self.title(s),
+                        ("description", Value::String(s)) => *s = self.description(s),
+                        ("tag" | "from" | "to", Value::String(s)) => *s = self.tag(s),
+                        ("tags", Value::Array(tags)) => tags.iter_mut().for_each(|t| {
+                            if let Value::String(s) = t {
+                                *s = self.tag(s);
+                            }
+                        }),
+                        ("person", Value::String(s)) => *s = self.person(s),
+                        (_, value) => self.json(value),
+                    }
+                }
+            }
+            Value::Array(values) => values.iter_mut().for_each(|v| self.json(v)),
+            _ => {}
+        }
+    }
+}
+
+/// anonymize_database replaces user texts in every table of the database in one transaction.
+/// The event chain is cleared, since rewritten events no longer match their hashes.
+pub fn anonymize_database(conn: &Connection, anonymizer: &Anonymizer) -> Result<()> {
+    let tx = conn.unchecked_transaction()?;
+    for (table, column) in [("tasks", "title"), ("task_journal", "title")] {
+        if has_table(&tx, table)? {
+            rewrite(&tx, table, column, |s| Ok(anonymizer.title(s)))?;
+        }
+    }
+    for (table, column) in [
+        ("task_events", "event"),
+        ("task_read_model", "task"),
+        ("tag_events", "event"),
+    ] {
+        if has_table(&tx, table)? {
+            rewrite(&tx, table, column, |s| {
+                let mut value: Value = serde_json::from_str(s)?;
+                anonymizer.json(&mut value);
+                Ok(value.to_string())
+            })?;
+        }
+    }
+    for (table, column) in [("sprints", "name"), ("sprint_tasks", "sprint_name")] {
+        if has_table(&tx, table)? {
+            rewrite(&tx, table, column, |s| Ok(anonymizer.sprint(s)))?;
+        }
+    }
+    if has_table(&tx, "event_chain")? {
+        tx.execute("DELETE FROM event_chain", [])?;
+    }
+    tx.commit()?;
+
+    Ok(())
+}
+
+/// rewrite replaces each text of the column by `f`.
+fn rewrite(
+    conn: &Connection,
+    table: &str,
+    column: &str,
+    f: impl Fn(&str) -> Result<String>,
+) -> Result<()> {
+    let rows = conn
+        .prepare(&format!(
+            "SELECT rowid, {} FROM {} WHERE {} IS NOT NULL",
+            column, table, column
+        ))?
+        .query_map([], |row| {
+            Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?))
+        })?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+    let mut stmt = conn.prepare(&format!(
+        "UPDATE {} SET {} = ?1 WHERE rowid = ?2",
+        table, column
+    ))?;
+    for (rowid, text) in rows {
+        stmt.execute(params![f(&text)?, rowid])?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_anonymizer() {
+        let anonymizer = Anonymizer::new();
+        assert_eq!(anonymizer.title("fix bug"), anonymizer.title("fix bug"));
+        assert_ne!(anonymizer.title("fix bug"), anonymizer.title("fix bugs"));
+        assert_ne!(
+            anonymizer.title("fix bug"),
+            Anonymizer::new().title("fix bug")
+        );
+        assert!(anonymizer.tag("repo:acme").starts_with("repo:tag-"));
+        assert_eq!(anonymizer.tag("energy:deep"), "energy:deep");
+
+        let mut event: Value = serde_json::from_str(
+            r#"{"event":{"type":"WaitedOn","waiting_on":{"person":"alice","follow_up_on":null}},"aggregate_version":3}"#,
+        )
+        .unwrap();
+        anonymizer.json(&mut event);
+        assert_eq!(
+            event["event"]["waiting_on"]["person"],
+            anonymizer.person("alice")
+        );
+        assert_eq!(event["aggregate_version"], 3);
+    }
+}
@@ -0,0 +1,158 @@
+//! # command_history
+//!
+//! command_history keeps invoked commands, so that `taskmr last` can run the latest change again.
+
+use crate::Result;
+use rusqlite::{params, Connection};
+
+use crate::infra::sqlite::access::RetryingConnection;
+
+/// MAX_ENTRIES is the number of commands kept. Older ones are dropped as new ones are recorded.
+const MAX_ENTRIES: i64 = 1000;
+
+/// CommandEntry is a recorded command.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CommandEntry {
+    pub id: i64,
+    /// args are the arguments after `taskmr`, with secrets redacted.
+    pub args: Vec<String>,
+    /// is_mutating is whether the command changes tasks, so that running it again repeats it.
+    pub is_mutating: bool,
+    /// is_redacted is whether secrets were dropped from args, so that it can not run again.
+    pub is_redacted: bool,
+    /// recorded_at is `YYYY-MM-DD HH:MM:SS` in local time.
+    pub recorded_at: String,
+}
+
+/// CommandHistoryStore persists invoked commands into the task database.
+pub struct CommandHistoryStore {
+    conn: RetryingConnection,
+}
+
+impl CommandHistoryStore {
+    /// Construct a CommandHistoryStore.
+    pub fn new(conn: Connection) -> CommandHistoryStore {
+        CommandHistoryStore {
+            conn: RetryingConnection::new(conn),
+        }
+    }
+
+    /// Create table for the history.
+    /// This function is to be called at first time.
+    pub fn create_table_if_not_exists(&self) -> Result<()> {
+        self.conn.run(|conn| {
+            conn.execute(
+                "CREATE TABLE if not exists command_history (
+                    id INTEGER PRIMARY KEY,
+                    args TEXT NOT NULL,
+                    is_mutating INTEGER NOT NULL,
+                    is_redacted INTEGER NOT NULL DEFAULT 0,
+                    recorded_at TEXT NOT NULL DEFAULT (datetime(CURRENT_TIMESTAMP, 'localtime'))
+                )",
+                [],
+            )
+        })?;
+
+        Ok(())
+    }
+
+    /// record stores the command, and drops the oldest ones beyond MAX_ENTRIES.
+    pub fn record(&self, args: &[String], is_mutating: bool, is_redacted: bool) -> Result<()> {
+        let args = serde_json::to_string(args)?;
+        self.conn.run(|conn| {
+            let tx = conn.unchecked_transaction()?;
+            tx.execute(
+                "INSERT INTO command_history (args, is_mutating, is_redacted) VALUES (?1, ?2, ?3)",
+                params![args, is_mutating, is_redacted],
+            )?;
+            tx.execute(
+                "DELETE FROM command_history WHERE id <= (SELECT MAX(id) FROM command_history) - ?1",
+                [MAX_ENTRIES],
+            )?;
+            tx.commit()
+        })?;
+
+        Ok(())
+    }
+
+    /// recent returns the latest commands up to the limit, from the latest.
+    pub fn recent(&self, limit: usize) -> Result<Vec<CommandEntry>> {
+        self.select(
+            "SELECT id, args, is_mutating, is_redacted, recorded_at FROM command_history
+             ORDER BY id DESC LIMIT ?1",
+            limit as i64,
+        )
+    }
+
+    /// last_mutating returns the latest command which changes tasks.
+    pub fn last_mutating(&self) -> Result<Option<CommandEntry>> {
+        Ok(self
+            .select(
+                "SELECT id, args, is_mutating, is_redacted, recorded_at FROM command_history
+                 WHERE is_mutating = 1 ORDER BY id DESC LIMIT ?1",
+                1,
+            )?
+            .pop())
+    }
+
+    /// select reads entries by the query taking a limit.
+    fn select(&self, sql: &str, limit: i64) -> Result<Vec<CommandEntry>> {
+        let rows = self.conn.run(|conn| {
+            conn.prepare(sql)?
+                .query_map([limit], |row| {
+                    Ok((
+                        row.get::<_, i64>(0)?,
+                        row.get::<_, String>(1)?,
+                        row.get::<_, bool>(2)?,
+                        row.get::<_, bool>(3)?,
+                        row.get::<_, String>(4)?,
+                    ))
+                })?
+                .collect::<rusqlite::Result<Vec<_>>>()
+        })?;
+
+        rows.into_iter()
+            .map(|(id, args, is_mutating, is_redacted, recorded_at)| {
+                Ok(CommandEntry {
+                    id,
+                    args: serde_json::from_str(&args)?,
+                    is_mutating,
+                    is_redacted,
+                    recorded_at,
+                })
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_record() {
+        let store = CommandHistoryStore::new(Connection::open_in_memory().unwrap());
+        store.create_table_if_not_exists().unwrap();
+        let args = |s: &str| s.split(' ').map(String::from).collect::<Vec<_>>();
+
+        assert_eq!(store.last_mutating().unwrap(), None);
+        store.record(&args("add fix bug"), true, false).unwrap();
+        store
+            .record(&args("list --regex ^fix (a|b)"), false, false)
+            .unwrap();
+
+        let recent = store.recent(5).unwrap();
+        assert_eq!(recent.len(), 2);
+        assert_eq!(recent[0].args, args("list --regex ^fix (a|b)"));
+        assert_eq!(
+            store.last_mutating().unwrap().map(|e| e.args),
+            Some(args("add fix bug"))
+        );
+
+        for _ in 0..MAX_ENTRIES {
+            store.record(&args("list"), false, false).unwrap();
+        }
+        assert_eq!(store.recent(usize::MAX).unwrap().len() as i64, MAX_ENTRIES);
+        assert_eq!(store.last_mutating().unwrap(), None);
+    }
+}
@@ -0,0 +1,144 @@
+use crate::Result;
+use rusqlite::Connection;
+
+use crate::ddd::component::DomainEventEnvelope;
+use crate::domain::es_tag::{ITagCatalogRepository, TagCatalog, TagDomainEvent};
+use crate::infra::sqlite::access::RetryingConnection;
+
+/// Implementation of ITagCatalogRepository.
+pub struct TagCatalogRepository {
+    conn: RetryingConnection,
+}
+
+impl TagCatalogRepository {
+    /// Construct a TagCatalogRepository.
+    pub fn new(conn: Connection) -> TagCatalogRepository {
+        TagCatalogRepository {
+            conn: RetryingConnection::new(conn),
+        }
+    }
+
+    /// Create table tag_events.
+    /// This function is to be called at first time.
+    pub fn create_table_if_not_exists(&self) -> Result<()> {
+        self.conn.run(create_table)?;
+        Ok(())
+    }
+}
+
+/// create_table creates tag_events.
+/// The task repository creates it as well, since loading tasks reads the catalog.
+pub(crate) fn create_table(conn: &Connection) -> rusqlite::Result<()> {
+    conn.execute(
+        "CREATE TABLE if not exists tag_events (
+            aggregate_version INTEGER PRIMARY KEY,
+            event TEXT NOT NULL,
+            event_version INTEGER NOT NULL,
+            occurred_on TEXT NOT NULL
+        )",
+        [],
+    )?;
+    Ok(())
+}
+
+/// load_catalog reads all tag events and reconstructs the catalog.
+pub(crate) fn load_catalog(conn: &Connection) -> rusqlite::Result<TagCatalog> {
+    let mut stmt = conn.prepare("SELECT event FROM tag_events ORDER BY aggregate_version")?;
+    let events = stmt
+        .query_map([], |row| {
+            let event: String = row.get(0)?;
+            serde_json::from_str::<DomainEventEnvelope<TagDomainEvent>>(&event).map_err(|err| {
+                rusqlite::Error::FromSqlConversionFailure(
+                    0,
+                    rusqlite::types::Type::Text,
+                    err.into(),
+                )
+            })
+        })?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+    Ok(TagCatalog::recreate(events))
+}
+
+impl ITagCatalogRepository for TagCatalogRepository {
+    fn load(&self) -> Result<TagCatalog> {
+        Ok(self.conn.run(load_catalog)?)
+    }
+
+    /// save the catalog events.
+    /// A version which is already stored fails, so that concurrent renames do not interleave.
+    fn save(&self, catalog: &mut TagCatalog) -> Result<()> {
+        self.conn.run(|conn| {
+            let tx = conn.unchecked_transaction()?;
+            {
+                let mut stmt = tx.prepare(
+                    "INSERT INTO tag_events (
+                        aggregate_version,
+                        event,
+                        event_version,
+                        occurred_on
+                     ) VALUES (?1, ?2, ?3, ?4)",
+                )?;
+                for ee in catalog.events() {
+                    let event = serde_json::to_string(ee)
+                        .map_err(|err| rusqlite::Error::ToSqlConversionFailure(Box::new(err)))?;
+                    stmt.insert(rusqlite::params![
+                        ee.aggregate_version(),
+                        event,
+                        ee.event_version(),
+                        ee.occurred_on().format("%Y-%m-%d %H:%M:%S").to_string(),
+                    ])?;
+                }
+            }
+            tx.commit()
+        })?;
+
+        catalog.clear_events();
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::es_tag::TagCommand;
+    use crate::domain::es_task::Tag;
+    use chrono::NaiveDateTime;
+
+    #[test]
+    fn test_save_and_load() {
+        let repository = TagCatalogRepository::new(Connection::open_in_memory().unwrap());
+        repository.create_table_if_not_exists().unwrap();
+        repository.create_table_if_not_exists().unwrap();
+
+        let mut catalog = repository.load().unwrap();
+        assert_eq!(catalog, TagCatalog::default());
+
+        for (from, to) in [("work", "job"), ("job", "gig")] {
+            catalog
+                .execute(TagCommand::Rename {
+                    from: Tag::new(from).unwrap(),
+                    to: Tag::new(to).unwrap(),
+                })
+                .unwrap();
+        }
+        repository.save(&mut catalog).unwrap();
+        assert!(catalog.events().is_empty());
+
+        let loaded = repository.load().unwrap();
+        assert_eq!(loaded.version(), 2);
+        assert_eq!(
+            loaded.resolve(&Tag::new("work").unwrap(), NaiveDateTime::default()),
+            Tag::new("gig").unwrap()
+        );
+
+        let mut stale = TagCatalog::default();
+        stale
+            .execute(TagCommand::Rename {
+                from: Tag::new("home").unwrap(),
+                to: Tag::new("house").unwrap(),
+            })
+            .unwrap();
+        assert!(repository.save(&mut stale).is_err());
+    }
+}
@@ -1,173 +1,789 @@
-use anyhow::Result;
-use rusqlite::Connection;
+use std::rc::Rc;
+
+use crate::Result;
+use rusqlite::{Connection, OptionalExtension, Transaction, TransactionBehavior};
+use thiserror::Error;
 
 use crate::ddd::component::{AggregateID, AggregateRoot, DomainEventEnvelope, Entity, Repository};
-use crate::domain::es_task::{IESTaskRepository, SequentialID, Task, TaskDomainEvent};
+use crate::domain::es_task::{
+    IESTaskRepository, ImportCheckpoint, SearchField, SearchMatch, SequentialID, SnippetPart, Task,
+    TaskDomainEvent, TaskReadModel,
+};
+use crate::infra::config::IdPolicy;
+use crate::infra::sqlite::access::RetryingConnection;
+use crate::infra::sqlite::es_tag_repository;
+use crate::infra::sqlite::event_chain::{self, EventChain, EventRow};
+use crate::infra::sqlite::fts;
+use crate::infra::sqlite::import_checkpoint;
+use crate::infra::sqlite::metrics::RepositoryMetrics;
+
+/// TaskRepositoryError is raised when stored tasks are inconsistent.
+#[derive(Error, Debug, PartialEq, Eq)]
+pub enum TaskRepositoryError {
+    #[error(
+        "the sequential id of the task {0} is missing, run `taskmr doctor` to check the database"
+    )]
+    MissingSequentialID(String),
+}
 
 /// Implementation of TaskRepository.
 pub struct TaskRepository {
-    conn: rusqlite::Connection,
+    conn: RetryingConnection,
+    metrics: Rc<RepositoryMetrics>,
+    id_policy: IdPolicy,
+    event_chain: Option<EventChain>,
 }
 
 impl TaskRepository {
     /// Construct a TaskRepository.
     pub fn new(conn: Connection) -> TaskRepository {
         conn.execute("PRAGMA foreign_keys = ON", []).unwrap();
-        TaskRepository { conn }
+        TaskRepository {
+            conn: RetryingConnection::new(conn),
+            metrics: Rc::new(RepositoryMetrics::new()),
+            id_policy: IdPolicy::default(),
+            event_chain: None,
+        }
+    }
+
+    /// replace IdPolicy which decides sequential ids of new tasks.
+    pub fn with_id_policy(mut self, id_policy: IdPolicy) -> Self {
+        self.id_policy = id_policy;
+        self
+    }
+
+    /// replace EventChain which links saved events. Events are not linked if None.
+    pub fn with_event_chain(mut self, event_chain: Option<EventChain>) -> Self {
+        self.event_chain = event_chain;
+        self
+    }
+
+    /// replace RepositoryMetrics to share it with other repositories.
+    pub fn with_metrics(mut self, metrics: Rc<RepositoryMetrics>) -> Self {
+        self.metrics = metrics;
+        self
+    }
+
+    /// get the access layer which holds metrics about the connection.
+    pub fn connection(&self) -> &RetryingConnection {
+        &self.conn
     }
 
     /// Create table tasks.
     /// This function is to be called at first time.
     pub fn create_table_if_not_exists(&self) -> Result<()> {
-        self.conn.execute(
-            "CREATE TABLE if not exists task_events (
-                aggregate_id TEXT NOT NULL,
-                aggregate_version INTEGER NOT NULL,
-                event TEXT NOT NULL,
-                event_version INTEGER NOT NULL,
-                occurred_on TEXT NOT NULL,
-                PRIMARY KEY(aggregate_id, aggregate_version),
-                FOREIGN KEY (aggregate_id) REFERENCES task_sequential_ids(task_id)
-            )",
-            [],
-        )?;
-
-        // NOTE: phantom_version is needed to define FOREIGN KEY.
-        self.conn.execute(
-            "CREATE TABLE if not exists task_sequential_ids (
-                sequential_id INTEGER PRIMARY KEY AUTOINCREMENT,
-                task_id TEXT NOT NULL UNIQUE
-            )",
-            [],
-        )?;
+        self.conn.run(|conn| {
+            conn.execute(
+                "CREATE TABLE if not exists task_events (
+                    aggregate_id TEXT NOT NULL,
+                    aggregate_version INTEGER NOT NULL,
+                    event TEXT NOT NULL,
+                    event_version INTEGER NOT NULL,
+                    occurred_on TEXT NOT NULL,
+                    PRIMARY KEY(aggregate_id, aggregate_version),
+                    FOREIGN KEY (aggregate_id) REFERENCES task_sequential_ids(task_id)
+                )",
+                [],
+            )?;
+
+            // NOTE: phantom_version is needed to define FOREIGN KEY.
+            // released is 1 when the task is deleted or archived, so that its id is free to reuse.
+            conn.execute(
+                "CREATE TABLE if not exists task_sequential_ids (
+                    sequential_id INTEGER PRIMARY KEY AUTOINCREMENT,
+                    task_id TEXT NOT NULL UNIQUE,
+                    released INTEGER NOT NULL DEFAULT 0
+                )",
+                [],
+            )?;
+
+            // NOTE: version and tag_version are the numbers of task events and tag events
+            // which the row is projected from, to tell whether the row is stale.
+            conn.execute(
+                "CREATE TABLE if not exists task_read_model (
+                    task_id TEXT PRIMARY KEY,
+                    version INTEGER NOT NULL,
+                    tag_version INTEGER NOT NULL,
+                    task TEXT NOT NULL
+                )",
+                [],
+            )?;
+
+            // NOTE: tasks deleted or archived before ids were released release theirs here.
+            conn.execute(
+                "UPDATE task_sequential_ids SET released = 1
+                 WHERE released = 0 AND task_id IN (
+                    SELECT task_id FROM task_read_model
+                    WHERE json_extract(task, '$.is_deleted') = 1)",
+                [],
+            )?;
+
+            event_chain::create_table(conn)?;
+            migrate_occurred_on(conn)?;
+            import_checkpoint::create_table(conn)?;
+            es_tag_repository::create_table(conn)
+        })?;
+
+        Ok(())
+    }
+
+    /// insert_new_all saves `count` new tasks, and the checkpoint if it is given, in one transaction.
+    /// The whole transaction is run again while the database is busy, building all tasks again.
+    fn insert_new_all(
+        &self,
+        count: usize,
+        create: &dyn Fn(usize, AggregateID, SequentialID) -> Task,
+        checkpoint: Option<&ImportCheckpoint>,
+    ) -> Result<()> {
+        self.conn.run(|conn| {
+            let tx = Transaction::new_unchecked(conn, TransactionBehavior::Immediate)?;
+            for i in 0..count {
+                let aggregate_id = AggregateID::new();
+                let rowid = insert_sequential_id(&tx, aggregate_id, self.id_policy)?;
+                let task = create(i, aggregate_id, SequentialID::new(rowid));
+                insert_events(&tx, &task, self.event_chain.as_ref())?;
+                upsert_read_model(&tx, &task)?;
+            }
+            if let Some(checkpoint) = checkpoint {
+                import_checkpoint::record(&tx, &checkpoint.digest, checkpoint.last_line)?;
+            }
+            tx.commit()
+        })?;
 
         Ok(())
     }
 
     /// sequential_id_by_aggregate_id returns sequential_id by aggregate_id.
     fn sequential_id_by_aggregate_id(&self, aggregate_id: AggregateID) -> Result<SequentialID> {
-        let mut stmt = self.conn.prepare(
-            "SELECT sequential_id
-             FROM task_sequential_ids
-             WHERE task_id = ?",
-        )?;
-
-        let mut rows = stmt.query([aggregate_id.to_string()])?;
-
-        match rows.next()? {
-            Some(row) => Ok(SequentialID::new(row.get(0)?)),
-            // NOTE: None shoud never occur.
-            // TODO: revise this error message.
-            None => panic!("SequentialID could not found by AggregateID {}, but it is impossible. Your taskmr may be broken.", aggregate_id),
+        let sequential_id = self.conn.run(|conn| {
+            let mut stmt = conn.prepare(
+                "SELECT sequential_id
+                 FROM task_sequential_ids
+                 WHERE task_id = ?",
+            )?;
+
+            let mut rows = stmt.query([aggregate_id.to_string()])?;
+
+            match rows.next()? {
+                Some(row) => Ok(Some(SequentialID::new(row.get(0)?))),
+                None => Ok(None),
+            }
+        })?;
+
+        sequential_id.ok_or_else(|| {
+            TaskRepositoryError::MissingSequentialID(aggregate_id.to_string()).into()
+        })
+    }
+
+    /// find_orphans returns sequential ids without events.
+    /// They are left when taskmr crashed while adding a task before `save_new` was introduced.
+    pub fn find_orphans(&self) -> Result<Vec<SequentialID>> {
+        let ids = self.conn.run(|conn| {
+            let mut stmt = conn.prepare(&format!("SELECT sequential_id {}", ORPHANS))?;
+            let iter = stmt.query_map([], |row| row.get::<_, i64>(0))?;
+            iter.collect::<rusqlite::Result<Vec<i64>>>()
+        })?;
+
+        Ok(ids.into_iter().map(SequentialID::new).collect())
+    }
+
+    /// find_duplicates returns sequential ids shared by several tasks and ids of tasks having
+    /// several sequential ids. Both are prevented by constraints unless the table was altered.
+    /// Ids shared by several live tasks in the read model are returned too, as reused ids of
+    /// deleted or archived tasks must not be given to two live tasks.
+    pub fn find_duplicates(&self) -> Result<Vec<String>> {
+        let duplicates = self.conn.run(|conn| {
+            let mut stmt = conn.prepare(
+                "SELECT '#' || sequential_id FROM task_sequential_ids
+                 GROUP BY sequential_id HAVING COUNT(*) > 1
+                 UNION ALL
+                 SELECT task_id FROM task_sequential_ids
+                 GROUP BY task_id HAVING COUNT(*) > 1
+                 UNION ALL
+                 SELECT '#' || json_extract(task, '$.sequential_id') FROM task_read_model
+                 WHERE COALESCE(json_extract(task, '$.is_deleted'), 0) = 0
+                 GROUP BY json_extract(task, '$.sequential_id') HAVING COUNT(*) > 1",
+            )?;
+            let iter = stmt.query_map([], |row| row.get::<_, String>(0))?;
+            iter.collect::<rusqlite::Result<Vec<String>>>()
+        })?;
+
+        Ok(duplicates)
+    }
+
+    /// remove_orphans deletes sequential ids without events and returns them.
+    pub fn remove_orphans(&self) -> Result<Vec<SequentialID>> {
+        let ids = self.conn.run(|conn| {
+            let tx = Transaction::new_unchecked(conn, TransactionBehavior::Immediate)?;
+            let ids = {
+                let mut stmt = tx.prepare(&format!("SELECT sequential_id {}", ORPHANS))?;
+                let iter = stmt.query_map([], |row| row.get::<_, i64>(0))?;
+                iter.collect::<rusqlite::Result<Vec<i64>>>()?
+            };
+            tx.execute(
+                &format!(
+                    "DELETE FROM task_sequential_ids WHERE sequential_id IN (SELECT sequential_id {})",
+                    ORPHANS
+                ),
+                [],
+            )?;
+            tx.commit()?;
+            Ok(ids)
+        })?;
+
+        Ok(ids.into_iter().map(SequentialID::new).collect())
+    }
+}
+
+/// SELECT_READ_MODELS selects projected tasks in the order of sequential ids.
+const SELECT_READ_MODELS: &str = "SELECT task FROM task_read_model
+     ORDER BY json_extract(task, '$.sequential_id') ASC";
+
+/// SELECT_LIVE_READ_MODELS is SELECT_READ_MODELS without deleted and archived tasks, which is
+/// the hot path of listing. It is served by the index `task_read_model_live`. Rows projected
+/// before deletion was added have no is_deleted.
+const SELECT_LIVE_READ_MODELS: &str = "SELECT task FROM task_read_model
+     WHERE COALESCE(json_extract(task, '$.is_deleted'), 0) = 0
+     ORDER BY json_extract(task, '$.sequential_id') ASC";
+
+/// snippet_parts splits a snippet whose matches are put between STX and ETX.
+fn snippet_parts(snippet: &str) -> Vec<SnippetPart> {
+    let mut parts = Vec::new();
+    for (i, piece) in snippet.split(['\u{2}', '\u{3}']).enumerate() {
+        if !piece.is_empty() {
+            parts.push(SnippetPart {
+                text: piece.to_owned(),
+                is_match: i % 2 == 1,
+            });
         }
     }
+    parts
 }
 
-impl Repository<Task> for TaskRepository {
-    /// load a Task by id.
-    fn load(&self, aggregate_id: AggregateID) -> Result<Task> {
-        let mut stmt = self.conn.prepare(
-            "SELECT aggregate_id,
-                    aggregate_version,
-                    event,
-                    event_version,
-                    occurred_on
-             FROM task_events
-             WHERE aggregate_id = ?
-             ORDER BY aggregate_version ASC",
-        )?;
-
-        let event_iter =
-            stmt.query_map([aggregate_id.to_string()], |row| row.get::<_, String>(2))?;
-
-        let mut events = Vec::new();
-        for e in event_iter {
-            let event: DomainEventEnvelope<TaskDomainEvent> = serde_json::from_str(&e?)?;
-            events.push(event);
+/// ORPHANS selects sequential ids which no event refers to.
+const ORPHANS: &str = "FROM task_sequential_ids AS s
+     WHERE NOT EXISTS (SELECT 1 FROM task_events AS e WHERE e.aggregate_id = s.task_id)
+     ORDER BY sequential_id ASC";
+
+/// insert_sequential_id issues a sequential id of the task following the policy.
+fn insert_sequential_id(
+    conn: &Connection,
+    aggregate_id: AggregateID,
+    id_policy: IdPolicy,
+) -> rusqlite::Result<i64> {
+    match id_policy {
+        // NOTE: AUTOINCREMENT never gives out ids which have ever been used.
+        IdPolicy::Monotonic => conn
+            .prepare("INSERT INTO task_sequential_ids (task_id) VALUES (?1)")?
+            .insert([aggregate_id.to_string()]),
+        IdPolicy::Reuse => {
+            let sequential_id: i64 = conn.query_row(
+                "SELECT CASE
+                    WHEN NOT EXISTS (
+                        SELECT 1 FROM task_sequential_ids WHERE sequential_id = 1 AND released = 0)
+                    THEN 1
+                    ELSE (SELECT MIN(s.sequential_id) + 1
+                          FROM task_sequential_ids AS s
+                          WHERE s.released = 0 AND NOT EXISTS (
+                              SELECT 1 FROM task_sequential_ids AS t
+                              WHERE t.released = 0 AND t.sequential_id = s.sequential_id + 1))
+                 END",
+                [],
+                |row| row.get(0),
+            )?;
+            move_released_id(conn, sequential_id)?;
+            conn.prepare(
+                "INSERT INTO task_sequential_ids (sequential_id, task_id) VALUES (?1, ?2)",
+            )?
+            .insert(rusqlite::params![sequential_id, aggregate_id.to_string()])
         }
+    }
+}
 
-        let sequential_id = self.sequential_id_by_aggregate_id(aggregate_id)?;
+/// move_released_id moves the released task holding the id to a negative id, so that the id can
+/// be given to a new task. The released task, which is deleted or archived, is shown by the new id.
+fn move_released_id(conn: &Connection, sequential_id: i64) -> rusqlite::Result<()> {
+    let task_id: Option<String> = conn
+        .query_row(
+            "SELECT task_id FROM task_sequential_ids WHERE sequential_id = ?1 AND released = 1",
+            [sequential_id],
+            |row| row.get(0),
+        )
+        .optional()?;
+    let Some(task_id) = task_id else {
+        return Ok(());
+    };
+
+    let moved: i64 = conn.query_row(
+        "SELECT MIN(0, MIN(sequential_id)) - 1 FROM task_sequential_ids",
+        [],
+        |row| row.get(0),
+    )?;
+    conn.execute(
+        "UPDATE task_sequential_ids SET sequential_id = ?1 WHERE sequential_id = ?2",
+        [moved, sequential_id],
+    )?;
+    conn.execute(
+        "UPDATE task_read_model SET task = json_set(task, '$.sequential_id', ?1) WHERE task_id = ?2",
+        rusqlite::params![moved, task_id],
+    )?;
+
+    Ok(())
+}
 
-        let task = Task::recreate(aggregate_id, sequential_id, events);
+/// migrate_occurred_on rewrites occurred_on written with `%H:%m:%s` (month and epoch seconds)
+/// by older versions into `%H:%M:%S`, which the epoch seconds tell exactly. Rows linked by the
+/// event chain keep the text, since the chain hashes it as stored.
+fn migrate_occurred_on(conn: &Connection) -> rusqlite::Result<()> {
+    conn.execute(
+        "UPDATE task_events
+         SET occurred_on = strftime('%Y-%m-%d %H:%M:%S', CAST(substr(occurred_on, 18) AS INTEGER),
+                                    'unixepoch')
+         WHERE length(occurred_on) > 19
+           AND NOT EXISTS (
+               SELECT 1 FROM event_chain AS c
+               WHERE c.aggregate_id = task_events.aggregate_id
+                 AND c.aggregate_version = task_events.aggregate_version)",
+        [],
+    )?;
 
-        Ok(task)
+    Ok(())
+}
+
+/// insert_events appends the uncommitted events of the task, and links them to the chain if any.
+fn insert_events(
+    conn: &Connection,
+    task: &Task,
+    event_chain: Option<&EventChain>,
+) -> rusqlite::Result<()> {
+    let mut stmt = conn.prepare(
+        "INSERT INTO task_events (
+            aggregate_id,
+            aggregate_version,
+            event,
+            event_version,
+            occurred_on
+         ) VALUES (?1, ?2, ?3, ?4, ?5)",
+    )?;
+
+    for te in task.events() {
+        let event = serde_json::to_string(&te)
+            .map_err(|err| rusqlite::Error::ToSqlConversionFailure(Box::new(err)))?;
+        let row = EventRow {
+            aggregate_id: task.id().to_string(),
+            aggregate_version: i64::from(te.aggregate_version()),
+            event,
+            event_version: i64::from(te.event_version()),
+            // NOTE: rows written with `%H:%m:%s` before are fixed by `migrate_occurred_on`.
+            occurred_on: te.occurred_on().format("%Y-%m-%d %H:%M:%S").to_string(),
+        };
+        stmt.insert(rusqlite::params![
+            row.aggregate_id,
+            row.aggregate_version,
+            row.event,
+            row.event_version,
+            row.occurred_on,
+        ])?;
+        if let Some(event_chain) = event_chain {
+            event_chain.append(conn, &row)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// upsert_read_model projects the task, with its events applied, into the read model.
+fn upsert_read_model(conn: &Connection, task: &Task) -> rusqlite::Result<()> {
+    let model = serde_json::to_string(&TaskReadModel::from(task))
+        .map_err(|err| rusqlite::Error::ToSqlConversionFailure(Box::new(err)))?;
+    conn.execute(
+        "INSERT INTO task_read_model (task_id, version, tag_version, task)
+         VALUES (?1, ?2, (SELECT COUNT(*) FROM tag_events), ?3)
+         ON CONFLICT (task_id) DO UPDATE SET
+            version = excluded.version,
+            tag_version = excluded.tag_version,
+            task = excluded.task",
+        rusqlite::params![task.id().to_string(), task.version(), model],
+    )?;
+
+    Ok(())
+}
+
+impl Repository<Task> for TaskRepository {
+    /// load a Task by id.
+    fn load(&self, aggregate_id: AggregateID) -> Result<Task> {
+        self.metrics.observe("task_events.load", || {
+            let events = self.load_events(aggregate_id)?;
+            let sequential_id = self.sequential_id_by_aggregate_id(aggregate_id)?;
+            let catalog = self.conn.run(es_tag_repository::load_catalog)?;
+
+            self.metrics.observe("task_events.replay", || {
+                Ok(Task::recreate_with_tags(
+                    aggregate_id,
+                    sequential_id,
+                    events,
+                    &catalog,
+                ))
+            })
+        })
     }
 
     /// save the task events.
     /// The reason why an argument `task` as `mut` is to clear events associated to the task.
     fn save(&self, task: &mut Task) -> Result<()> {
-        let mut stmt = self.conn.prepare(
-            "INSERT INTO task_events (
-                aggregate_id,
-                aggregate_version,
-                event,
-                event_version,
-                occurred_on
-             ) VALUES (?1, ?2, ?3, ?4, ?5)",
-        )?;
-
-        for te in task.events() {
-            stmt.insert(rusqlite::params![
-                task.id().to_string(),
-                te.aggregate_version(),
-                serde_json::to_string(&te)?,
-                te.event_version(),
-                te.occurred_on().format("%Y-%m-%d %H:%m:%s").to_string(),
-            ])?;
-        }
+        self.metrics.observe("task_events.save", || {
+            self.conn.run(|conn| {
+                let tx = conn.unchecked_transaction()?;
+                insert_events(&tx, task, self.event_chain.as_ref())?;
+                upsert_read_model(&tx, task)?;
+                tx.commit()
+            })?;
 
-        task.clear_events();
+            task.clear_events();
 
-        Ok(())
+            Ok(())
+        })
     }
 }
 
 impl IESTaskRepository for TaskRepository {
-    fn issue_sequential_id(&self, aggregate_id: AggregateID) -> Result<SequentialID> {
-        let mut stmt = self.conn.prepare(
-            "INSERT INTO task_sequential_ids (
-                task_id
-             ) VALUES (?1)",
-        )?;
+    fn load_events(
+        &self,
+        aggregate_id: AggregateID,
+    ) -> Result<Vec<DomainEventEnvelope<TaskDomainEvent>>> {
+        let raw_events = self.conn.run(|conn| {
+            let mut stmt = conn.prepare(
+                "SELECT event
+                 FROM task_events
+                 WHERE aggregate_id = ?
+                 ORDER BY aggregate_version ASC",
+            )?;
 
-        let rowid = stmt.insert(rusqlite::params![aggregate_id.to_string()])?;
+            let event_iter =
+                stmt.query_map([aggregate_id.to_string()], |row| row.get::<_, String>(0))?;
 
-        Ok(SequentialID::new(rowid))
+            event_iter.collect::<rusqlite::Result<Vec<String>>>()
+        })?;
+
+        raw_events
+            .iter()
+            .map(|e| Ok(serde_json::from_str(e)?))
+            .collect()
     }
 
-    fn load_by_sequential_id(&self, sequential_id: SequentialID) -> Result<Option<Task>> {
-        let mut stmt = self.conn.prepare(
-            "SELECT task_id
-             FROM task_sequential_ids
-             WHERE sequential_id = ?",
-        )?;
+    fn issue_sequential_id(&self, aggregate_id: AggregateID) -> Result<SequentialID> {
+        self.metrics.observe("task_sequential_ids.issue", || {
+            let rowid = self
+                .conn
+                .run(|conn| insert_sequential_id(conn, aggregate_id, self.id_policy))?;
 
-        let mut rows = stmt.query([sequential_id.to_i64()])?;
+            Ok(SequentialID::new(rowid))
+        })
+    }
 
-        match rows.next()? {
-            Some(row) => {
-                let id_s: String = row.get(0)?;
-                Ok(Some(self.load(id_s.parse()?)?))
+    fn save_new_with(
+        &self,
+        aggregate_id: AggregateID,
+        create: &dyn Fn(SequentialID) -> Task,
+        others: &mut [Task],
+    ) -> Result<Task> {
+        self.metrics.observe("task_events.save_new", || {
+            let mut task = self.conn.run(|conn| {
+                // NOTE: IMMEDIATE takes the write lock first, so that concurrent additions wait
+                // in the retry of RetryingConnection instead of failing on upgrading the lock.
+                let tx = Transaction::new_unchecked(conn, TransactionBehavior::Immediate)?;
+                let rowid = insert_sequential_id(&tx, aggregate_id, self.id_policy)?;
+                let task = create(SequentialID::new(rowid));
+                insert_events(&tx, &task, self.event_chain.as_ref())?;
+                upsert_read_model(&tx, &task)?;
+                for other in others.iter() {
+                    insert_events(&tx, other, self.event_chain.as_ref())?;
+                    upsert_read_model(&tx, other)?;
+                }
+                tx.commit()?;
+                Ok(task)
+            })?;
+
+            task.clear_events();
+            for other in others.iter_mut() {
+                other.clear_events();
             }
-            None => Ok(None),
-        }
+
+            Ok(task)
+        })
+    }
+
+    fn save_all(&self, tasks: &mut [Task]) -> Result<()> {
+        self.metrics.observe("task_events.save_all", || {
+            self.conn.run(|conn| {
+                let tx = conn.unchecked_transaction()?;
+                for task in tasks.iter() {
+                    insert_events(&tx, task, self.event_chain.as_ref())?;
+                    upsert_read_model(&tx, task)?;
+                }
+                tx.commit()
+            })?;
+
+            for task in tasks.iter_mut() {
+                task.clear_events();
+            }
+
+            Ok(())
+        })
+    }
+
+    fn save_new_all(
+        &self,
+        count: usize,
+        create: &dyn Fn(usize, AggregateID, SequentialID) -> Task,
+    ) -> Result<()> {
+        self.metrics.observe("task_events.save_new_all", || {
+            self.insert_new_all(count, create, None)
+        })
+    }
+
+    fn save_imported(
+        &self,
+        count: usize,
+        create: &dyn Fn(usize, AggregateID, SequentialID) -> Task,
+        checkpoint: &ImportCheckpoint,
+    ) -> Result<()> {
+        self.metrics.observe("task_events.save_imported", || {
+            self.insert_new_all(count, create, Some(checkpoint))
+        })
+    }
+
+    fn load_import_checkpoint(&self, digest: &str) -> Result<Option<usize>> {
+        Ok(self
+            .conn
+            .run(|conn| import_checkpoint::last_line(conn, digest))?)
+    }
+
+    fn clear_import_checkpoint(&self, digest: &str) -> Result<()> {
+        self.conn
+            .run(|conn| import_checkpoint::clear(conn, digest))?;
+
+        Ok(())
+    }
+
+    fn load_any_by_sequential_id(&self, sequential_id: SequentialID) -> Result<Option<Task>> {
+        self.metrics
+            .observe("task_sequential_ids.load_by_sequential_id", || {
+                let task_id = self.conn.run(|conn| {
+                    let mut stmt = conn.prepare(
+                        "SELECT task_id
+                     FROM task_sequential_ids
+                     WHERE sequential_id = ?",
+                    )?;
+
+                    let mut rows = stmt.query([sequential_id.to_i64()])?;
+
+                    match rows.next()? {
+                        Some(row) => Ok(Some(row.get::<_, String>(0)?)),
+                        None => Ok(None),
+                    }
+                })?;
+
+                match task_id {
+                    Some(id_s) => Ok(Some(self.load(id_s.parse()?)?)),
+                    None => Ok(None),
+                }
+            })
+    }
+
+    fn release_sequential_ids(&self, sequential_ids: &[SequentialID]) -> Result<()> {
+        self.metrics.observe("task_sequential_ids.release", || {
+            self.conn.run(|conn| {
+                let tx = conn.unchecked_transaction()?;
+                {
+                    let mut stmt = tx.prepare(
+                        "UPDATE task_sequential_ids SET released = 1 WHERE sequential_id = ?1",
+                    )?;
+                    for sequential_id in sequential_ids {
+                        stmt.execute([sequential_id.to_i64()])?;
+                    }
+                }
+                tx.commit()
+            })?;
+
+            Ok(())
+        })
     }
 
     fn load_all_sequential_ids(&self) -> Result<Vec<SequentialID>> {
-        let mut stmt = self.conn.prepare(
-            "SELECT sequential_id
-             FROM task_sequential_ids",
-        )?;
+        self.metrics.observe("task_sequential_ids.load_all", || {
+            let seq_ids = self.conn.run(|conn| {
+                let mut stmt = conn.prepare(
+                    "SELECT sequential_id
+                     FROM task_sequential_ids
+                     ORDER BY sequential_id ASC",
+                )?;
 
-        let seq_id_iter = stmt.query_map([], |row| row.get::<_, i64>(0))?;
+                let seq_id_iter = stmt.query_map([], |row| row.get::<_, i64>(0))?;
 
-        let mut sequential_ids = Vec::new();
-        for s_id_i64 in seq_id_iter {
-            let sequential_id = SequentialID::new(s_id_i64?);
-            sequential_ids.push(sequential_id);
-        }
+                seq_id_iter.collect::<rusqlite::Result<Vec<i64>>>()
+            })?;
 
-        Ok(sequential_ids)
+            let mut sequential_ids = Vec::new();
+            for s_id_i64 in seq_ids {
+                let sequential_id = SequentialID::new(s_id_i64);
+                sequential_ids.push(sequential_id);
+            }
+
+            Ok(sequential_ids)
+        })
+    }
+
+    /// for_each_read_model reads the projection instead of replaying events. Rows of tasks whose
+    /// events or tag renames are newer than the rows, such as tasks saved by older versions of
+    /// taskmr, are projected again first.
+    fn for_each_read_model(
+        &self,
+        include_deleted: bool,
+        f: &mut dyn FnMut(TaskReadModel) -> Result<()>,
+    ) -> Result<()> {
+        self.metrics.observe("task_read_model.for_each", || {
+            let stale = self.conn.run(|conn| {
+                let mut stmt = conn.prepare(
+                    "SELECT sequential_id FROM (
+                        SELECT s.sequential_id,
+                               r.version AS projected,
+                               r.tag_version,
+                               (SELECT MAX(e.aggregate_version) + 1
+                                FROM task_events e
+                                WHERE e.aggregate_id = s.task_id) AS version
+                        FROM task_sequential_ids s
+                        LEFT JOIN task_read_model r ON r.task_id = s.task_id
+                     )
+                     WHERE version IS NOT NULL
+                       AND (projected IS NOT version
+                            OR tag_version IS NOT (SELECT COUNT(*) FROM tag_events))",
+                )?;
+                let iter = stmt.query_map([], |row| row.get::<_, i64>(0))?;
+                iter.collect::<rusqlite::Result<Vec<i64>>>()
+            })?;
+            if !stale.is_empty() {
+                let mut tasks = Vec::new();
+                for sequential_id in stale {
+                    if let Some(task) =
+                        self.load_any_by_sequential_id(SequentialID::new(sequential_id))?
+                    {
+                        tasks.push(task);
+                    }
+                }
+                self.conn.run(|conn| {
+                    let tx = conn.unchecked_transaction()?;
+                    for task in tasks.iter() {
+                        upsert_read_model(&tx, task)?;
+                    }
+                    tx.commit()
+                })?;
+            }
+
+            let rows = self.conn.run(|conn| {
+                let mut stmt = conn.prepare(if include_deleted {
+                    SELECT_READ_MODELS
+                } else {
+                    SELECT_LIVE_READ_MODELS
+                })?;
+                let iter = stmt.query_map([], |row| row.get::<_, String>(0))?;
+                iter.collect::<rusqlite::Result<Vec<String>>>()
+            })?;
+            for row in rows {
+                f(serde_json::from_str(&row)?)?;
+            }
+
+            Ok(())
+        })
+    }
+
+    /// search indexes live read models into a temporary FTS5 table, which is built for each
+    /// search, so that the task database keeps no index to be updated by every save.
+    fn search(&self, query: &str, field: SearchField) -> Result<Vec<SearchMatch>> {
+        let Some(query) = fts::prefix_query(query) else {
+            return Ok(vec![]);
+        };
+        let query = match field {
+            SearchField::All => query,
+            SearchField::Title => format!("title : ({})", query),
+            SearchField::Description => format!("description : ({})", query),
+        };
+
+        let mut models = Vec::new();
+        self.for_each_read_model(false, &mut |model| {
+            models.push(model);
+            Ok(())
+        })?;
+
+        self.metrics.observe("task_read_model.search", || {
+            let hits = self.conn.run(|conn| {
+                conn.execute_batch(
+                    "CREATE VIRTUAL TABLE IF NOT EXISTS temp.task_search
+                        USING fts5(title, description);
+                     DELETE FROM temp.task_search;",
+                )?;
+                {
+                    let mut stmt = conn.prepare(
+                        "INSERT INTO temp.task_search (rowid, title, description)
+                         VALUES (?1, ?2, ?3)",
+                    )?;
+                    for (i, model) in models.iter().enumerate() {
+                        stmt.execute(rusqlite::params![
+                            i as i64,
+                            model.title,
+                            model.description.as_deref().unwrap_or_default()
+                        ])?;
+                    }
+                }
+                // NOTE: bm25 weighs a match in the title 10 times a match in the description.
+                let mut stmt = conn.prepare(
+                    "SELECT rowid, snippet(task_search, -1, char(2), char(3), '…', 12)
+                     FROM temp.task_search
+                     WHERE task_search MATCH ?1
+                     ORDER BY bm25(task_search, 10.0, 1.0), rowid ASC",
+                )?;
+                let iter = stmt.query_map([&query], |row| {
+                    Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?))
+                })?;
+                iter.collect::<rusqlite::Result<Vec<(i64, String)>>>()
+            })?;
+
+            Ok(hits
+                .into_iter()
+                .map(|(i, snippet)| SearchMatch {
+                    task: models[i as usize].clone(),
+                    snippet: snippet_parts(&snippet),
+                })
+                .collect())
+        })
+    }
+
+    /// load_children finds subtasks by ParentAssigned events instead of loading every task.
+    fn load_children(&self, parent: SequentialID) -> Result<Vec<Task>> {
+        self.metrics.observe("task_events.load_children", || {
+            let task_ids = self.conn.run(|conn| {
+                let mut stmt = conn.prepare(
+                    "SELECT s.task_id
+                     FROM task_events e
+                     JOIN task_sequential_ids s ON s.task_id = e.aggregate_id
+                     WHERE json_extract(e.event, '$.event.type') = 'ParentAssigned'
+                       AND json_extract(e.event, '$.event.parent') = ?
+                     ORDER BY s.sequential_id",
+                )?;
+
+                let task_id_iter =
+                    stmt.query_map([parent.to_i64()], |row| row.get::<_, String>(0))?;
+
+                task_id_iter.collect::<rusqlite::Result<Vec<String>>>()
+            })?;
+
+            let mut children = task_ids
+                .into_iter()
+                .map(|id_s| self.load(id_s.parse()?))
+                .collect::<Result<Vec<Task>>>()?;
+            children.retain(|t| !t.is_deleted());
+            Ok(children)
+        })
     }
 }
 
@@ -202,6 +818,7 @@ mod tests {
             title: "test this task".into(),
             priority: Some(Priority::new(11)),
             cost: Some(Cost::new(12)),
+            tags: vec![],
         });
 
         task.execute(TaskCommand::EditTitle {
@@ -258,6 +875,7 @@ mod tests {
             title: "test this task".into(),
             priority: Some(Priority::new(11)),
             cost: Some(Cost::new(12)),
+            tags: vec![],
         });
 
         task_repository.save(&mut task1).unwrap();
@@ -272,11 +890,680 @@ mod tests {
             title: "test this task".into(),
             priority: Some(Priority::new(21)),
             cost: Some(Cost::new(22)),
+            tags: vec![],
         });
 
         task_repository.save(&mut task2).unwrap();
     }
 
+    #[test]
+    fn test_save_new() {
+        let task_repository = TaskRepository::new(rusqlite::Connection::open_in_memory().unwrap());
+        task_repository.create_table_if_not_exists().unwrap();
+
+        let aggregate_id = AggregateID::new();
+        let task = task_repository
+            .save_new(aggregate_id, &|sequential_id| {
+                Task::create(TaskSource {
+                    aggregate_id,
+                    sequential_id,
+                    title: "test this task".into(),
+                    priority: None,
+                    cost: None,
+                    tags: vec![],
+                })
+            })
+            .unwrap();
+        assert_eq!(task.sequential_id(), SequentialID::new(1));
+        assert!(task.events().is_empty());
+        assert_eq!(task_repository.load(aggregate_id).unwrap(), task);
+
+        // a task failing to be saved leaves no sequential id behind.
+        let aggregate_id = AggregateID::new();
+        task_repository
+            .save_new(aggregate_id, &|sequential_id| {
+                Task::create(TaskSource {
+                    aggregate_id: AggregateID::new(),
+                    sequential_id,
+                    title: "refers to an unknown aggregate".into(),
+                    priority: None,
+                    cost: None,
+                    tags: vec![],
+                })
+            })
+            .unwrap_err();
+        assert_eq!(
+            task_repository.load_all_sequential_ids().unwrap(),
+            vec![SequentialID::new(1)]
+        );
+        assert_eq!(
+            task_repository
+                .load(aggregate_id)
+                .unwrap_err()
+                .downcast::<TaskRepositoryError>()
+                .unwrap(),
+            TaskRepositoryError::MissingSequentialID(aggregate_id.to_string())
+        );
+    }
+
+    #[test]
+    fn test_save_all() {
+        let task_repository = TaskRepository::new(rusqlite::Connection::open_in_memory().unwrap());
+        task_repository.create_table_if_not_exists().unwrap();
+
+        let aggregate_ids = [AggregateID::new(), AggregateID::new()];
+        for aggregate_id in aggregate_ids {
+            task_repository
+                .save_new(aggregate_id, &|sequential_id| {
+                    Task::create(TaskSource {
+                        aggregate_id,
+                        sequential_id,
+                        title: "test this task".into(),
+                        priority: None,
+                        cost: None,
+                        tags: vec![],
+                    })
+                })
+                .unwrap();
+        }
+        let load = || -> Vec<Task> {
+            aggregate_ids
+                .iter()
+                .map(|id| task_repository.load(*id).unwrap())
+                .collect()
+        };
+
+        // a stale task fails the whole batch.
+        let mut tasks = load();
+        let mut stale = load().remove(1);
+        stale.execute(TaskCommand::Lock).unwrap();
+        task_repository.save(&mut stale).unwrap();
+        for task in tasks.iter_mut() {
+            task.execute(TaskCommand::Close).unwrap();
+        }
+        assert!(task_repository.save_all(&mut tasks).is_err());
+        assert!(load().iter().all(|t| !t.is_closed()));
+
+        let mut tasks = load();
+        for task in tasks.iter_mut() {
+            task.execute(TaskCommand::Close).unwrap();
+        }
+        task_repository.save_all(&mut tasks).unwrap();
+        assert!(tasks.iter().all(|t| t.events().is_empty()));
+        assert!(load().iter().all(|t| t.is_closed()));
+    }
+
+    #[test]
+    fn test_save_new_with() {
+        let task_repository = TaskRepository::new(rusqlite::Connection::open_in_memory().unwrap());
+        task_repository.create_table_if_not_exists().unwrap();
+
+        let create = |aggregate_id| {
+            move |sequential_id| {
+                Task::create(TaskSource {
+                    aggregate_id,
+                    sequential_id,
+                    title: "test this task".into(),
+                    priority: None,
+                    cost: None,
+                    tags: vec![],
+                })
+            }
+        };
+        let aggregate_id = AggregateID::new();
+        task_repository
+            .save_new(aggregate_id, &create(aggregate_id))
+            .unwrap();
+
+        // a stale task fails the new task too.
+        let mut stale = task_repository.load(aggregate_id).unwrap();
+        let mut fresh = task_repository.load(aggregate_id).unwrap();
+        fresh.execute(TaskCommand::Lock).unwrap();
+        task_repository.save(&mut fresh).unwrap();
+        stale.execute(TaskCommand::Close).unwrap();
+        let new_id = AggregateID::new();
+        assert!(task_repository
+            .save_new_with(new_id, &create(new_id), std::slice::from_mut(&mut stale))
+            .is_err());
+        assert_eq!(task_repository.load_all_sequential_ids().unwrap().len(), 1);
+
+        let mut task = task_repository.load(aggregate_id).unwrap();
+        task.execute(TaskCommand::Close).unwrap();
+        let new = task_repository
+            .save_new_with(new_id, &create(new_id), std::slice::from_mut(&mut task))
+            .unwrap();
+        assert!(task.events().is_empty());
+        assert!(task_repository.load(aggregate_id).unwrap().is_closed());
+        assert_eq!(new.sequential_id(), SequentialID::new(2));
+    }
+
+    #[test]
+    fn test_for_each_task() {
+        let task_repository = TaskRepository::new(rusqlite::Connection::open_in_memory().unwrap());
+        task_repository.create_table_if_not_exists().unwrap();
+        for title in ["first", "second", "third"] {
+            let aggregate_id = AggregateID::new();
+            task_repository
+                .save_new(aggregate_id, &|sequential_id| {
+                    Task::create(TaskSource {
+                        aggregate_id,
+                        sequential_id,
+                        title: title.into(),
+                        priority: None,
+                        cost: None,
+                        tags: vec![],
+                    })
+                })
+                .unwrap();
+        }
+
+        let mut titles = vec![];
+        task_repository
+            .for_each_task(&mut |task| {
+                titles.push(task.title().to_owned());
+                Ok(())
+            })
+            .unwrap();
+        assert_eq!(titles, ["first", "second", "third"]);
+
+        // it stops at the first error.
+        let mut titles = vec![];
+        task_repository
+            .for_each_task(&mut |task| {
+                titles.push(task.title().to_owned());
+                match titles.len() {
+                    2 => Err(crate::Error::UseCase("stop".into())),
+                    _ => Ok(()),
+                }
+            })
+            .unwrap_err();
+        assert_eq!(titles, ["first", "second"]);
+    }
+
+    #[test]
+    fn test_load_children() {
+        let task_repository = TaskRepository::new(rusqlite::Connection::open_in_memory().unwrap());
+        task_repository.create_table_if_not_exists().unwrap();
+        // NOTE: (title, parent)
+        let tasks = [
+            ("root", None),
+            ("a", Some(1)),
+            ("b", Some(1)),
+            ("a-1", Some(2)),
+        ];
+        for (title, parent) in tasks {
+            let aggregate_id = AggregateID::new();
+            task_repository
+                .save_new(aggregate_id, &|sequential_id| {
+                    let mut task = Task::create(TaskSource {
+                        aggregate_id,
+                        sequential_id,
+                        title: title.into(),
+                        priority: None,
+                        cost: None,
+                        tags: vec![],
+                    });
+                    if let Some(parent) = parent {
+                        task.execute(TaskCommand::AssignParent {
+                            parent: SequentialID::new(parent),
+                        })
+                        .unwrap();
+                    }
+                    task
+                })
+                .unwrap();
+        }
+
+        let titles = |parent| {
+            task_repository
+                .load_children(SequentialID::new(parent))
+                .unwrap()
+                .iter()
+                .map(|t| t.title().to_owned())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(titles(1), ["a", "b"]);
+        assert_eq!(titles(2), ["a-1"]);
+        assert!(titles(3).is_empty());
+    }
+
+    #[test]
+    fn test_search() {
+        #[derive(Debug)]
+        struct TestCase {
+            args: (&'static str, SearchField),
+            want: Vec<(i64, String)>,
+            name: String,
+        }
+
+        let task_repository = TaskRepository::new(rusqlite::Connection::open_in_memory().unwrap());
+        task_repository.create_table_if_not_exists().unwrap();
+        for (title, description) in [
+            ("fix the flaky test", None),
+            ("write docs", Some("the test of the parser is flaky")),
+            ("fix the deleted test", None),
+        ] {
+            let aggregate_id = AggregateID::new();
+            let mut task = task_repository
+                .save_new(aggregate_id, &|sequential_id| {
+                    Task::create(TaskSource {
+                        aggregate_id,
+                        sequential_id,
+                        title: title.into(),
+                        priority: None,
+                        cost: None,
+                        tags: vec![],
+                    })
+                })
+                .unwrap();
+            if let Some(description) = description {
+                task.execute(TaskCommand::EditDescription {
+                    description: Some(description.into()),
+                })
+                .unwrap();
+            }
+            if title.contains("deleted") {
+                task.execute(TaskCommand::Delete).unwrap();
+            }
+            task_repository.save(&mut task).unwrap();
+        }
+
+        // NOTE: snippets are rendered with matches in brackets to compare them.
+        let render = |m: &SearchMatch| {
+            let snippet = m
+                .snippet
+                .iter()
+                .map(|p| {
+                    if p.is_match {
+                        format!("[{}]", p.text)
+                    } else {
+                        p.text.clone()
+                    }
+                })
+                .collect::<String>();
+            (m.task.sequential_id.to_i64(), snippet)
+        };
+
+        let table = [
+            TestCase {
+                name: String::from("normal: a match in the title comes first"),
+                args: ("flaky", SearchField::All),
+                want: vec![
+                    (1, String::from("fix the [flaky] test")),
+                    (2, String::from("the test of the parser is [flaky]")),
+                ],
+            },
+            TestCase {
+                name: String::from("normal: titles only"),
+                args: ("TES", SearchField::Title),
+                want: vec![(1, String::from("fix the flaky [test]"))],
+            },
+            TestCase {
+                name: String::from("normal: descriptions only"),
+                args: ("pars", SearchField::Description),
+                want: vec![(2, String::from("the test of the [parser] is flaky"))],
+            },
+            TestCase {
+                name: String::from("normal: operators are words"),
+                args: ("\"fix\" OR", SearchField::All),
+                want: vec![],
+            },
+            TestCase {
+                name: String::from("normal: no word"),
+                args: (" ", SearchField::All),
+                want: vec![],
+            },
+        ];
+
+        for test_case in table {
+            let got: Vec<(i64, String)> = task_repository
+                .search(test_case.args.0, test_case.args.1)
+                .unwrap()
+                .iter()
+                .map(render)
+                .collect();
+            assert_eq!(got, test_case.want, "Failed in the \"{}\".", test_case.name);
+        }
+    }
+
+    #[test]
+    fn test_for_each_read_model() {
+        let task_repository = TaskRepository::new(rusqlite::Connection::open_in_memory().unwrap());
+        task_repository.create_table_if_not_exists().unwrap();
+        for title in ["a", "b"] {
+            let aggregate_id = AggregateID::new();
+            task_repository
+                .save_new(aggregate_id, &|sequential_id| {
+                    Task::create(TaskSource {
+                        aggregate_id,
+                        sequential_id,
+                        title: title.into(),
+                        priority: None,
+                        cost: None,
+                        tags: vec![],
+                    })
+                })
+                .unwrap();
+        }
+        // NOTE: an orphan has no events to project.
+        task_repository
+            .issue_sequential_id(AggregateID::new())
+            .unwrap();
+
+        let mut task = task_repository
+            .load_by_sequential_id(SequentialID::new(1))
+            .unwrap()
+            .unwrap();
+        task.execute(TaskCommand::Close).unwrap();
+        task_repository.save(&mut task).unwrap();
+
+        let read_models = |include_deleted| {
+            let mut read_models = vec![];
+            task_repository
+                .for_each_read_model(include_deleted, &mut |t| {
+                    read_models.push(t);
+                    Ok(())
+                })
+                .unwrap();
+            read_models
+        };
+        let mut replayed = vec![];
+        task_repository
+            .for_each_task(&mut |t| {
+                if t.version() > 0 {
+                    replayed.push(TaskReadModel::from(&t));
+                }
+                Ok(())
+            })
+            .unwrap();
+        assert_eq!(read_models(false), replayed);
+        assert!(replayed[0].is_closed);
+
+        // NOTE: rows missing or behind the events, as written by older versions, are projected.
+        task_repository
+            .connection()
+            .run(|conn| {
+                conn.execute("DELETE FROM task_read_model WHERE rowid = 1", [])?;
+                conn.execute("UPDATE task_read_model SET version = 1", [])
+            })
+            .unwrap();
+        assert_eq!(read_models(false), replayed);
+
+        let mut task = task_repository
+            .load_by_sequential_id(SequentialID::new(2))
+            .unwrap()
+            .unwrap();
+        task.execute(TaskCommand::Delete).unwrap();
+        task_repository.save(&mut task).unwrap();
+        assert!(task_repository
+            .load_by_sequential_id(SequentialID::new(2))
+            .unwrap()
+            .is_none());
+        assert_eq!(read_models(false).len(), 1);
+        assert!(read_models(true)[1].is_deleted);
+    }
+
+    #[test]
+    fn test_remove_orphans() {
+        let task_repository = TaskRepository::new(rusqlite::Connection::open_in_memory().unwrap());
+        task_repository.create_table_if_not_exists().unwrap();
+
+        let aggregate_id = AggregateID::new();
+        task_repository
+            .save_new(aggregate_id, &|sequential_id| {
+                Task::create(TaskSource {
+                    aggregate_id,
+                    sequential_id,
+                    title: "test this task".into(),
+                    priority: None,
+                    cost: None,
+                    tags: vec![],
+                })
+            })
+            .unwrap();
+        let orphan = task_repository
+            .issue_sequential_id(AggregateID::new())
+            .unwrap();
+
+        assert_eq!(task_repository.find_orphans().unwrap(), vec![orphan]);
+        assert_eq!(task_repository.remove_orphans().unwrap(), vec![orphan]);
+        assert_eq!(task_repository.find_orphans().unwrap(), vec![]);
+        assert_eq!(
+            task_repository.load_all_sequential_ids().unwrap(),
+            vec![SequentialID::new(1)]
+        );
+    }
+
+    #[test]
+    fn test_id_policy() {
+        #[derive(Debug)]
+        struct TestCase {
+            args: IdPolicy,
+            want: Vec<i64>,
+            name: String,
+        }
+
+        let table = [
+            TestCase {
+                name: String::from("normal: monotonic"),
+                args: IdPolicy::Monotonic,
+                want: vec![5, 6],
+            },
+            TestCase {
+                name: String::from("normal: reuse"),
+                args: IdPolicy::Reuse,
+                want: vec![1, 3],
+            },
+        ];
+
+        for test_case in table {
+            let task_repository =
+                TaskRepository::new(rusqlite::Connection::open_in_memory().unwrap())
+                    .with_id_policy(test_case.args);
+            task_repository.create_table_if_not_exists().unwrap();
+
+            for _ in 0..4 {
+                task_repository
+                    .issue_sequential_id(AggregateID::new())
+                    .unwrap();
+            }
+            task_repository
+                .conn
+                .run(|conn| {
+                    conn.execute(
+                        "DELETE FROM task_sequential_ids WHERE sequential_id IN (1, 3)",
+                        [],
+                    )
+                })
+                .unwrap();
+
+            let got: Vec<i64> = (0..2)
+                .map(|_| {
+                    task_repository
+                        .issue_sequential_id(AggregateID::new())
+                        .unwrap()
+                        .to_i64()
+                })
+                .collect();
+            assert_eq!(got, test_case.want, "Failed in the \"{}\".", test_case.name);
+            assert_eq!(
+                task_repository.find_duplicates().unwrap(),
+                Vec::<String>::new(),
+                "Failed in the \"{}\".",
+                test_case.name
+            );
+        }
+    }
+
+    #[test]
+    fn test_migrate_occurred_on() {
+        let task_repository = TaskRepository::new(rusqlite::Connection::open_in_memory().unwrap());
+        task_repository.create_table_if_not_exists().unwrap();
+
+        // 2024-05-02 12:34:56 UTC was written as below, with the month and the epoch seconds.
+        let old = "2024-05-02 12:05:1714653296";
+        task_repository
+            .conn
+            .run(|conn| {
+                for (task_id, chained) in [("plain", false), ("chained", true)] {
+                    conn.execute(
+                        "INSERT INTO task_sequential_ids (task_id) VALUES (?1)",
+                        [task_id],
+                    )?;
+                    conn.execute(
+                        "INSERT INTO task_events
+                         (aggregate_id, aggregate_version, event, event_version, occurred_on)
+                         VALUES (?1, 0, '{}', 1, ?2)",
+                        [task_id, old],
+                    )?;
+                    if chained {
+                        conn.execute(
+                            "INSERT INTO event_chain
+                             (aggregate_id, aggregate_version, prev_hash, hash)
+                             VALUES (?1, 0, '', '')",
+                            [task_id],
+                        )?;
+                    }
+                }
+                Ok(())
+            })
+            .unwrap();
+        task_repository.create_table_if_not_exists().unwrap();
+
+        let got: Vec<(String, String)> = task_repository
+            .conn
+            .run(|conn| {
+                let mut stmt = conn.prepare(
+                    "SELECT aggregate_id, occurred_on FROM task_events ORDER BY aggregate_id",
+                )?;
+                let iter = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?;
+                iter.collect()
+            })
+            .unwrap();
+        assert_eq!(
+            got,
+            vec![
+                (String::from("chained"), String::from(old)),
+                (String::from("plain"), String::from("2024-05-02 12:34:56")),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_select_live_read_models_uses_index() {
+        let mut path = std::env::temp_dir();
+        path.push(format!("taskmr-es-plan-{}.db", std::process::id()));
+        crate::infra::sqlite::setup::create_database(&path).unwrap();
+
+        let conn = rusqlite::Connection::open(&path).unwrap();
+        let plan: Vec<String> = conn
+            .prepare(&format!("EXPLAIN QUERY PLAN {}", SELECT_LIVE_READ_MODELS))
+            .unwrap()
+            .query_map([], |row| row.get(3))
+            .unwrap()
+            .collect::<rusqlite::Result<_>>()
+            .unwrap();
+        assert!(
+            plan.iter()
+                .any(|p| p.contains("USING INDEX task_read_model_live")),
+            "{:?}",
+            plan
+        );
+        assert!(
+            !plan.iter().any(|p| p.contains("TEMP B-TREE")),
+            "{:?}",
+            plan
+        );
+
+        drop(conn);
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_release_sequential_ids() {
+        #[derive(Debug)]
+        struct TestCase {
+            args: IdPolicy,
+            want: (i64, Vec<i64>),
+            name: String,
+        }
+
+        let table = [
+            TestCase {
+                name: String::from("normal: monotonic keeps the id of the deleted task"),
+                args: IdPolicy::Monotonic,
+                want: (5, vec![2, 4]),
+            },
+            TestCase {
+                name: String::from("normal: reuse gives the id of the deleted task"),
+                args: IdPolicy::Reuse,
+                want: (2, vec![-1, 4]),
+            },
+        ];
+
+        for test_case in table {
+            let task_repository =
+                TaskRepository::new(rusqlite::Connection::open_in_memory().unwrap())
+                    .with_id_policy(test_case.args);
+            task_repository.create_table_if_not_exists().unwrap();
+
+            let mut aggregate_ids = Vec::new();
+            for _ in 0..4 {
+                let aggregate_id = AggregateID::new();
+                task_repository
+                    .save_new(aggregate_id, &|sequential_id| {
+                        Task::create(TaskSource {
+                            aggregate_id,
+                            sequential_id,
+                            title: "test this task".into(),
+                            priority: None,
+                            cost: None,
+                            tags: vec![],
+                        })
+                    })
+                    .unwrap();
+                aggregate_ids.push(aggregate_id);
+            }
+            // the newest id is released too, so that monotonic is told from reuse.
+            let mut deleted = Vec::new();
+            for aggregate_id in [aggregate_ids[1], aggregate_ids[3]] {
+                let mut task = task_repository.load(aggregate_id).unwrap();
+                task.execute(TaskCommand::Delete).unwrap();
+                task_repository.save(&mut task).unwrap();
+                deleted.push(task.sequential_id());
+            }
+            task_repository
+                .release_sequential_ids(&deleted[..1])
+                .unwrap();
+
+            let got = task_repository
+                .issue_sequential_id(AggregateID::new())
+                .unwrap();
+            let mut deleted = Vec::new();
+            task_repository
+                .for_each_read_model(true, &mut |model| {
+                    if model.is_deleted {
+                        deleted.push(model.sequential_id.to_i64());
+                    }
+                    Ok(())
+                })
+                .unwrap();
+            deleted.sort();
+            assert_eq!(
+                (got.to_i64(), deleted),
+                test_case.want,
+                "Failed in the \"{}\".",
+                test_case.name
+            );
+            assert_eq!(
+                task_repository.find_duplicates().unwrap(),
+                Vec::<String>::new(),
+                "Failed in the \"{}\".",
+                test_case.name
+            );
+        }
+    }
+
     #[test]
     fn test_succeed_load_all_sequential_ids() {
         let task_repository = TaskRepository::new(rusqlite::Connection::open_in_memory().unwrap());
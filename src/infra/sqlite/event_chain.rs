@@ -0,0 +1,310 @@
+//! # event_chain
+//!
+//! event_chain links task events by hashes, so that altered or removed events can be detected
+//! when the task log serves as a work record.
+
+use hmac::{Hmac, Mac};
+use rusqlite::{params, Connection, OptionalExtension};
+use sha2::{Digest, Sha256};
+
+use crate::Result;
+
+/// GENESIS is the previous hash of the first link.
+const GENESIS: &str = "0000000000000000000000000000000000000000000000000000000000000000";
+
+/// EventChain appends a link for each task event. Links are signed if the key is given.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct EventChain {
+    key: Option<Vec<u8>>,
+}
+
+impl EventChain {
+    /// Construct an EventChain which signs links with the key.
+    pub fn new(key: Option<Vec<u8>>) -> Self {
+        EventChain { key }
+    }
+
+    /// append links the stored columns of the event to the last link.
+    pub fn append(&self, conn: &Connection, event: &EventRow) -> rusqlite::Result<()> {
+        let prev_hash: String = conn
+            .query_row(
+                "SELECT hash FROM event_chain ORDER BY position DESC LIMIT 1",
+                [],
+                |row| row.get(0),
+            )
+            .optional()?
+            .unwrap_or_else(|| GENESIS.to_owned());
+        let hash = event.hash(&prev_hash);
+        let signature = self.key.as_deref().map(|key| sign(key, &hash));
+        conn.execute(
+            "INSERT INTO event_chain (aggregate_id, aggregate_version, prev_hash, hash, signature)
+             VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![
+                event.aggregate_id,
+                event.aggregate_version,
+                prev_hash,
+                hash,
+                signature
+            ],
+        )?;
+
+        Ok(())
+    }
+}
+
+/// EventRow is the columns of a task event as stored, which the hash covers.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EventRow {
+    pub aggregate_id: String,
+    pub aggregate_version: i64,
+    pub event: String,
+    pub event_version: i64,
+    pub occurred_on: String,
+}
+
+impl EventRow {
+    /// hash returns the hex SHA-256 of the previous hash and the columns.
+    fn hash(&self, prev_hash: &str) -> String {
+        let columns = serde_json::json!([
+            self.aggregate_id,
+            self.aggregate_version,
+            self.event,
+            self.event_version,
+            self.occurred_on,
+        ]);
+        let mut hasher = Sha256::new();
+        hasher.update(prev_hash.as_bytes());
+        hasher.update(columns.to_string().as_bytes());
+        hex(&hasher.finalize())
+    }
+}
+
+/// ChainReport is the result of verifying the chain.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ChainReport {
+    /// verified is the number of links whose events match.
+    pub verified: usize,
+    /// uncovered is the number of events stored before the chain was enabled.
+    pub uncovered: usize,
+    /// unchecked_signatures is the number of signatures which were not checked for lack of the key.
+    pub unchecked_signatures: usize,
+    /// problems are signs of tampering or corruption. The chain is intact if it is empty.
+    pub problems: Vec<String>,
+}
+
+/// create_table creates the table of links.
+pub fn create_table(conn: &Connection) -> rusqlite::Result<()> {
+    conn.execute(
+        "CREATE TABLE if not exists event_chain (
+            position INTEGER PRIMARY KEY AUTOINCREMENT,
+            aggregate_id TEXT NOT NULL,
+            aggregate_version INTEGER NOT NULL,
+            prev_hash TEXT NOT NULL,
+            hash TEXT NOT NULL,
+            signature TEXT
+        )",
+        [],
+    )?;
+
+    Ok(())
+}
+
+/// verify walks the chain from the first link, recomputing hashes from the stored events.
+/// Signatures are checked if the key is given.
+/// Events stored after the first link without their own link count as problems too, since
+/// they were written outside the chain.
+pub fn verify(conn: &Connection, key: Option<&[u8]>) -> Result<ChainReport> {
+    let mut report = ChainReport::default();
+    let mut stmt = conn.prepare(
+        "SELECT c.position, c.aggregate_id, c.aggregate_version, c.prev_hash, c.hash,
+                c.signature, e.event, e.event_version, e.occurred_on, s.sequential_id
+         FROM event_chain AS c
+         LEFT JOIN task_events AS e
+            ON e.aggregate_id = c.aggregate_id AND e.aggregate_version = c.aggregate_version
+         LEFT JOIN task_sequential_ids AS s ON s.task_id = c.aggregate_id
+         ORDER BY c.position",
+    )?;
+    let mut rows = stmt.query([])?;
+    let mut last_hash = GENESIS.to_owned();
+    while let Some(row) = rows.next()? {
+        let position: i64 = row.get(0)?;
+        let aggregate_id: String = row.get(1)?;
+        let aggregate_version: i64 = row.get(2)?;
+        let prev_hash: String = row.get(3)?;
+        let hash: String = row.get(4)?;
+        let signature: Option<String> = row.get(5)?;
+        let task = match row.get::<_, Option<i64>>(9)? {
+            Some(id) => format!("#{}", id),
+            None => aggregate_id.clone(),
+        };
+        let event = format!("the event {} of the task {}", aggregate_version, task);
+
+        if prev_hash != last_hash {
+            report.problems.push(format!(
+                "link {} does not follow the previous one",
+                position
+            ));
+        }
+        last_hash = hash.clone();
+
+        let Some(stored) = row.get::<_, Option<String>>(6)? else {
+            report.problems.push(format!("{} is missing", event));
+            continue;
+        };
+        let columns = EventRow {
+            aggregate_id,
+            aggregate_version,
+            event: stored,
+            event_version: row.get(7)?,
+            occurred_on: row.get(8)?,
+        };
+        if columns.hash(&prev_hash) != hash {
+            report.problems.push(format!("{} was altered", event));
+            continue;
+        }
+        match (key, signature) {
+            (Some(key), Some(signature)) if verify_signature(key, &hash, &signature) => {}
+            (Some(_), Some(_)) => {
+                report
+                    .problems
+                    .push(format!("{} has an invalid signature", event));
+                continue;
+            }
+            (Some(_), None) => {
+                report.problems.push(format!("{} is not signed", event));
+                continue;
+            }
+            (None, Some(_)) => report.unchecked_signatures += 1,
+            (None, None) => {}
+        }
+        report.verified += 1;
+    }
+
+    let (uncovered, unchained): (usize, usize) = conn.query_row(
+        "WITH first AS (
+            SELECT MIN(e.rowid) AS rowid FROM task_events AS e
+            JOIN event_chain AS c
+                ON c.aggregate_id = e.aggregate_id AND c.aggregate_version = e.aggregate_version
+         )
+         SELECT
+            COUNT(*) FILTER (WHERE first.rowid IS NULL OR e.rowid < first.rowid),
+            COUNT(*) FILTER (WHERE e.rowid > first.rowid)
+         FROM task_events AS e, first
+         WHERE NOT EXISTS (
+            SELECT 1 FROM event_chain AS c
+            WHERE c.aggregate_id = e.aggregate_id AND c.aggregate_version = e.aggregate_version
+         )",
+        [],
+        |row| Ok((row.get(0)?, row.get(1)?)),
+    )?;
+    report.uncovered = uncovered;
+    match unchained {
+        0 => {}
+        1 => report
+            .problems
+            .push("an event was stored outside the chain".to_owned()),
+        n => report
+            .problems
+            .push(format!("{} events were stored outside the chain", n)),
+    }
+
+    Ok(report)
+}
+
+/// sign returns the hex HMAC-SHA256 of the hash by the key.
+fn sign(key: &[u8], hash: &str) -> String {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC takes keys of any size");
+    mac.update(hash.as_bytes());
+    hex(&mac.finalize().into_bytes())
+}
+
+/// verify_signature checks the hex signature of the hash in constant time.
+fn verify_signature(key: &[u8], hash: &str, signature: &str) -> bool {
+    let Some(bytes) = unhex(signature) else {
+        return false;
+    };
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC takes keys of any size");
+    mac.update(hash.as_bytes());
+    mac.verify_slice(&bytes).is_ok()
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn unhex(s: &str) -> Option<Vec<u8>> {
+    if !s.len().is_multiple_of(2) {
+        return None;
+    }
+    (0..s.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(s.get(i..i + 2)?, 16).ok())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ddd::component::{AggregateID, AggregateRoot, Repository};
+    use crate::domain::es_task::{IESTaskRepository, Task, TaskCommand, TaskSource};
+    use crate::infra::sqlite::es_task_repository::TaskRepository;
+
+    #[test]
+    fn test_verify() {
+        let path = std::env::temp_dir().join(format!("taskmr-chain-{}.db", AggregateID::new()));
+        let key = b"secret".to_vec();
+        let repository = TaskRepository::new(Connection::open(&path).unwrap())
+            .with_event_chain(Some(EventChain::new(Some(key.clone()))));
+        repository.create_table_if_not_exists().unwrap();
+        for title in ["write", "review"] {
+            let aggregate_id = AggregateID::new();
+            let mut task = repository
+                .save_new(aggregate_id, &|sequential_id| {
+                    Task::create(TaskSource {
+                        aggregate_id,
+                        sequential_id,
+                        title: title.to_owned(),
+                        priority: None,
+                        cost: None,
+                        tags: vec![],
+                    })
+                })
+                .unwrap();
+            task.execute(TaskCommand::Close).unwrap();
+            repository.save(&mut task).unwrap();
+        }
+
+        let conn = Connection::open(&path).unwrap();
+        let report = verify(&conn, Some(&key)).unwrap();
+        assert_eq!(report.problems, Vec::<String>::new());
+        assert_eq!(report.verified, 6);
+        assert_eq!(verify(&conn, None).unwrap().unchecked_signatures, 6);
+        // NOTE: every signature is invalid by another key.
+        assert_eq!(verify(&conn, Some(b"other")).unwrap().problems.len(), 6);
+
+        conn.execute(
+            "UPDATE task_events SET event = replace(event, 'review', 'skip')",
+            [],
+        )
+        .unwrap();
+        assert_eq!(
+            verify(&conn, Some(&key)).unwrap().problems,
+            vec!["the event 1 of the task #2 was altered"]
+        );
+
+        conn.execute("DELETE FROM event_chain WHERE position = 2", [])
+            .unwrap();
+        assert_eq!(
+            verify(&conn, Some(&key)).unwrap().problems,
+            vec![
+                "link 3 does not follow the previous one",
+                "the event 1 of the task #2 was altered",
+                "an event was stored outside the chain",
+            ]
+        );
+
+        drop(conn);
+        drop(repository);
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,13 @@
+//! # fts
+//!
+//! fts builds queries of SQLite FTS5 from words typed by users.
+
+/// prefix_query translates words of a search into an FTS5 query matching all of them as prefixes,
+/// so that operators of FTS5 in the words are not interpreted. It is None if there is no word.
+pub fn prefix_query(query: &str) -> Option<String> {
+    let terms: Vec<String> = query
+        .split_whitespace()
+        .map(|w| format!("\"{}\"*", w.replace('"', "\"\"")))
+        .collect();
+    (!terms.is_empty()).then(|| terms.join(" "))
+}
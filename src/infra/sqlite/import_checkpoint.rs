@@ -0,0 +1,71 @@
+//! # import_checkpoint
+//!
+//! import_checkpoint keeps how far an import went, so that `taskmr import --resume` continues it
+//! after it is interrupted. The checkpoint is written in the transaction of the saved tasks.
+
+use rusqlite::{params, Connection, OptionalExtension};
+
+/// create_table creates the table of checkpoints, keyed by the digest of the imported file.
+pub fn create_table(conn: &Connection) -> rusqlite::Result<()> {
+    conn.execute(
+        "CREATE TABLE if not exists import_checkpoints (
+            digest TEXT PRIMARY KEY,
+            last_line INTEGER NOT NULL,
+            recorded_at TEXT NOT NULL DEFAULT (datetime(CURRENT_TIMESTAMP, 'localtime'))
+        )",
+        [],
+    )?;
+
+    Ok(())
+}
+
+/// record stores the last line saved from the file of the digest.
+pub fn record(conn: &Connection, digest: &str, last_line: usize) -> rusqlite::Result<()> {
+    conn.execute(
+        "INSERT OR REPLACE INTO import_checkpoints (digest, last_line) VALUES (?1, ?2)",
+        params![digest, last_line as i64],
+    )?;
+
+    Ok(())
+}
+
+/// last_line returns the last line saved from the file of the digest, if its import is unfinished.
+pub fn last_line(conn: &Connection, digest: &str) -> rusqlite::Result<Option<usize>> {
+    let last_line = conn
+        .query_row(
+            "SELECT last_line FROM import_checkpoints WHERE digest = ?1",
+            [digest],
+            |row| row.get::<_, i64>(0),
+        )
+        .optional()?;
+
+    Ok(last_line.map(|l| l as usize))
+}
+
+/// clear drops the checkpoint of the file of the digest, as its import is finished.
+pub fn clear(conn: &Connection, digest: &str) -> rusqlite::Result<()> {
+    conn.execute("DELETE FROM import_checkpoints WHERE digest = ?1", [digest])?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_record() {
+        let conn = Connection::open_in_memory().unwrap();
+        create_table(&conn).unwrap();
+
+        assert_eq!(last_line(&conn, "abc").unwrap(), None);
+        record(&conn, "abc", 500).unwrap();
+        record(&conn, "abc", 1000).unwrap();
+        record(&conn, "def", 3).unwrap();
+        assert_eq!(last_line(&conn, "abc").unwrap(), Some(1000));
+
+        clear(&conn, "abc").unwrap();
+        assert_eq!(last_line(&conn, "abc").unwrap(), None);
+        assert_eq!(last_line(&conn, "def").unwrap(), Some(3));
+    }
+}
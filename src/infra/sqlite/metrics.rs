@@ -0,0 +1,437 @@
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::time::{Duration, Instant};
+
+use crate::Result;
+use rusqlite::Connection;
+
+use crate::infra::sqlite::access::RetryingConnection;
+
+/// Calls slower than this are recorded as slow queries by default.
+const DEFAULT_SLOW_THRESHOLD: Duration = Duration::from_millis(100);
+
+/// MAX_SLOW_QUERIES is the number of slow queries kept. Older ones are dropped on flush.
+const MAX_SLOW_QUERIES: i64 = 1000;
+
+/// MethodStats is an accumulation of calls of one repository method.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct MethodStats {
+    pub calls: u64,
+    pub total: Duration,
+    pub max: Duration,
+}
+
+impl MethodStats {
+    /// record a call which took `elapsed`.
+    fn record(&mut self, elapsed: Duration) {
+        self.calls += 1;
+        self.total += elapsed;
+        self.max = self.max.max(elapsed);
+    }
+
+    /// merge other stats into this.
+    fn merge(&mut self, other: &MethodStats) {
+        self.calls += other.calls;
+        self.total += other.total;
+        self.max = self.max.max(other.max);
+    }
+
+    /// get the average latency.
+    pub fn average(&self) -> Duration {
+        match self.total.as_nanos().checked_div(u128::from(self.calls)) {
+            Some(nanos) => Duration::from_nanos(u64::try_from(nanos).unwrap_or(u64::MAX)),
+            None => Duration::ZERO,
+        }
+    }
+}
+
+/// SlowCall is a repository call of this process which took longer than the threshold.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SlowCall {
+    pub method: String,
+    pub elapsed: Duration,
+}
+
+/// SlowQuery is a SlowCall persisted by MetricsStore.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SlowQuery {
+    pub method: String,
+    pub elapsed: Duration,
+    pub occurred_on: String,
+}
+
+/// RepositoryMetrics records call counts and latencies per repository method.
+/// One instance is shared by every repository of the process.
+#[derive(Debug)]
+pub struct RepositoryMetrics {
+    methods: RefCell<BTreeMap<&'static str, MethodStats>>,
+    slow_calls: RefCell<Vec<SlowCall>>,
+    slow_threshold: Duration,
+}
+
+impl RepositoryMetrics {
+    /// construct RepositoryMetrics with the default slow query threshold.
+    pub fn new() -> Self {
+        Self::with_slow_threshold(DEFAULT_SLOW_THRESHOLD)
+    }
+
+    /// construct RepositoryMetrics with the given slow query threshold.
+    pub fn with_slow_threshold(slow_threshold: Duration) -> Self {
+        RepositoryMetrics {
+            methods: RefCell::new(BTreeMap::new()),
+            slow_calls: RefCell::new(Vec::new()),
+            slow_threshold,
+        }
+    }
+
+    /// observe measures `f` and records it as a call of `method`.
+    pub fn observe<T, F: FnOnce() -> T>(&self, method: &'static str, f: F) -> T {
+        let started = Instant::now();
+        let result = f();
+        let elapsed = started.elapsed();
+
+        self.methods
+            .borrow_mut()
+            .entry(method)
+            .or_default()
+            .record(elapsed);
+
+        if elapsed >= self.slow_threshold {
+            tracing::warn!(
+                method,
+                elapsed_ms = elapsed.as_millis() as u64,
+                "slow repository call"
+            );
+            self.slow_calls.borrow_mut().push(SlowCall {
+                method: method.to_owned(),
+                elapsed,
+            });
+        }
+
+        result
+    }
+
+    /// get stats of the method.
+    pub fn get(&self, method: &str) -> Option<MethodStats> {
+        self.methods.borrow().get(method).copied()
+    }
+
+    /// snapshot returns stats of all observed methods ordered by method name.
+    pub fn snapshot(&self) -> Vec<(String, MethodStats)> {
+        self.methods
+            .borrow()
+            .iter()
+            .map(|(m, s)| (m.to_string(), *s))
+            .collect()
+    }
+
+    /// get recorded slow calls.
+    pub fn slow_calls(&self) -> Vec<SlowCall> {
+        self.slow_calls.borrow().clone()
+    }
+}
+
+impl Default for RepositoryMetrics {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// MetricsStore persists RepositoryMetrics of invocations so that they can be inspected later.
+/// They are recorded only if `record` of `[metrics]` is set in the config.
+pub struct MetricsStore {
+    conn: RetryingConnection,
+}
+
+impl MetricsStore {
+    /// Construct a MetricsStore.
+    pub fn new(conn: Connection) -> MetricsStore {
+        MetricsStore {
+            conn: RetryingConnection::new(conn),
+        }
+    }
+
+    /// Create tables for metrics.
+    /// This function is to be called at first time.
+    pub fn create_table_if_not_exists(&self) -> Result<()> {
+        self.conn.run(|conn| {
+            conn.execute_batch(
+                "CREATE TABLE if not exists repository_metrics (
+                    method TEXT PRIMARY KEY,
+                    calls INTEGER NOT NULL,
+                    total_micros INTEGER NOT NULL,
+                    max_micros INTEGER NOT NULL
+                );
+                CREATE TABLE if not exists slow_queries (
+                    id INTEGER PRIMARY KEY,
+                    method TEXT NOT NULL,
+                    elapsed_micros INTEGER NOT NULL,
+                    occurred_on TEXT NOT NULL
+                );",
+            )
+        })?;
+
+        Ok(())
+    }
+
+    /// flush accumulates the metrics of this process into the store, and drops the oldest
+    /// slow queries beyond MAX_SLOW_QUERIES.
+    /// Nothing is written if no repository method was called, such as by `config` commands.
+    pub fn flush(&self, metrics: &RepositoryMetrics) -> Result<()> {
+        let snapshot = metrics.snapshot();
+        let slow_calls = metrics.slow_calls();
+        if snapshot.is_empty() {
+            return Ok(());
+        }
+
+        self.conn.run(|conn| {
+            let tx = conn.unchecked_transaction()?;
+            for (method, stats) in &snapshot {
+                tx.execute(
+                    "INSERT INTO repository_metrics (method, calls, total_micros, max_micros)
+                     VALUES (?1, ?2, ?3, ?4)
+                     ON CONFLICT(method) DO UPDATE SET
+                        calls = calls + excluded.calls,
+                        total_micros = total_micros + excluded.total_micros,
+                        max_micros = max(max_micros, excluded.max_micros)",
+                    rusqlite::params![
+                        method,
+                        stats.calls as i64,
+                        stats.total.as_micros() as i64,
+                        stats.max.as_micros() as i64,
+                    ],
+                )?;
+            }
+            for sc in &slow_calls {
+                tx.execute(
+                    "INSERT INTO slow_queries (method, elapsed_micros, occurred_on)
+                     VALUES (?1, ?2, datetime(CURRENT_TIMESTAMP, 'localtime'))",
+                    rusqlite::params![sc.method, sc.elapsed.as_micros() as i64],
+                )?;
+            }
+            if !slow_calls.is_empty() {
+                tx.execute(
+                    "DELETE FROM slow_queries WHERE id <= (SELECT MAX(id) FROM slow_queries) - ?1",
+                    [MAX_SLOW_QUERIES],
+                )?;
+            }
+            tx.commit()
+        })?;
+
+        Ok(())
+    }
+
+    /// load accumulated metrics ordered by method name.
+    pub fn load(&self) -> Result<Vec<(String, MethodStats)>> {
+        let stats = self.conn.run(|conn| {
+            let mut stmt = conn.prepare(
+                "SELECT method, calls, total_micros, max_micros
+                 FROM repository_metrics
+                 ORDER BY method ASC",
+            )?;
+
+            let iter = stmt.query_map([], |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    MethodStats {
+                        calls: row.get::<_, i64>(1)? as u64,
+                        total: Duration::from_micros(row.get::<_, i64>(2)? as u64),
+                        max: Duration::from_micros(row.get::<_, i64>(3)? as u64),
+                    },
+                ))
+            })?;
+
+            iter.collect::<rusqlite::Result<Vec<_>>>()
+        })?;
+
+        Ok(stats)
+    }
+
+    /// load the latest slow queries, newest first.
+    pub fn load_slow_queries(&self, limit: usize) -> Result<Vec<SlowQuery>> {
+        let slow_queries = self.conn.run(|conn| {
+            let mut stmt = conn.prepare(
+                "SELECT method, elapsed_micros, occurred_on
+                 FROM slow_queries
+                 ORDER BY id DESC
+                 LIMIT ?",
+            )?;
+
+            let iter = stmt.query_map([limit as i64], |row| {
+                Ok(SlowQuery {
+                    method: row.get(0)?,
+                    elapsed: Duration::from_micros(row.get::<_, i64>(1)? as u64),
+                    occurred_on: row.get(2)?,
+                })
+            })?;
+
+            iter.collect::<rusqlite::Result<Vec<_>>>()
+        })?;
+
+        Ok(slow_queries)
+    }
+}
+
+/// render_prometheus renders metrics in the Prometheus text exposition format.
+pub fn render_prometheus(stats: &[(String, MethodStats)]) -> String {
+    let mut merged: BTreeMap<&str, MethodStats> = BTreeMap::new();
+    for (method, s) in stats {
+        merged.entry(method.as_str()).or_default().merge(s);
+    }
+
+    let mut out = String::new();
+    out.push_str("# HELP taskmr_repository_calls_total Number of repository method calls.\n");
+    out.push_str("# TYPE taskmr_repository_calls_total counter\n");
+    for (method, s) in &merged {
+        out.push_str(&format!(
+            "taskmr_repository_calls_total{{method=\"{}\"}} {}\n",
+            method, s.calls
+        ));
+    }
+    out.push_str(
+        "# HELP taskmr_repository_duration_seconds_total Total time spent in repository methods.\n",
+    );
+    out.push_str("# TYPE taskmr_repository_duration_seconds_total counter\n");
+    for (method, s) in &merged {
+        out.push_str(&format!(
+            "taskmr_repository_duration_seconds_total{{method=\"{}\"}} {}\n",
+            method,
+            s.total.as_secs_f64()
+        ));
+    }
+    out.push_str("# HELP taskmr_repository_duration_seconds_max Slowest repository method call.\n");
+    out.push_str("# TYPE taskmr_repository_duration_seconds_max gauge\n");
+    for (method, s) in &merged {
+        out.push_str(&format!(
+            "taskmr_repository_duration_seconds_max{{method=\"{}\"}} {}\n",
+            method,
+            s.max.as_secs_f64()
+        ));
+    }
+
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_observe() {
+        let metrics = RepositoryMetrics::with_slow_threshold(Duration::from_millis(5));
+
+        let got = metrics.observe("tasks.find_by_id", || 1);
+        assert_eq!(got, 1);
+        metrics.observe("tasks.find_by_id", || ());
+        metrics.observe("tasks.add", || std::thread::sleep(Duration::from_millis(6)));
+
+        let find = metrics.get("tasks.find_by_id").unwrap();
+        assert_eq!(find.calls, 2);
+        assert_eq!(metrics.get("tasks.add").unwrap().calls, 1);
+        assert_eq!(metrics.get("tasks.update"), None);
+
+        let slow_calls = metrics.slow_calls();
+        assert_eq!(slow_calls.len(), 1);
+        assert_eq!(slow_calls[0].method, "tasks.add");
+    }
+
+    #[test]
+    fn test_flush_and_load() {
+        let store = MetricsStore::new(Connection::open_in_memory().unwrap());
+        store.create_table_if_not_exists().unwrap();
+        store.create_table_if_not_exists().unwrap();
+
+        let metrics = RepositoryMetrics::with_slow_threshold(Duration::ZERO);
+        metrics.observe("tasks.add", || ());
+        metrics.observe("tasks.fetch_all", || ());
+
+        store.flush(&metrics).unwrap();
+        store.flush(&metrics).unwrap();
+
+        let got = store.load().unwrap();
+        let methods: Vec<(&str, u64)> = got.iter().map(|(m, s)| (m.as_str(), s.calls)).collect();
+        assert_eq!(methods, vec![("tasks.add", 2), ("tasks.fetch_all", 2)]);
+
+        assert_eq!(store.load_slow_queries(3).unwrap().len(), 3);
+
+        for _ in 0..MAX_SLOW_QUERIES {
+            metrics.observe("tasks.add", || ());
+        }
+        store.flush(&metrics).unwrap();
+        assert_eq!(
+            store.load_slow_queries(usize::MAX).unwrap().len() as i64,
+            MAX_SLOW_QUERIES
+        );
+    }
+
+    #[test]
+    fn test_flush_without_calls() {
+        // NOTE: the store has no tables, so that any write fails.
+        let store = MetricsStore::new(Connection::open_in_memory().unwrap());
+        store.flush(&RepositoryMetrics::new()).unwrap();
+    }
+
+    #[test]
+    fn test_average() {
+        #[derive(Debug)]
+        struct TestCase {
+            args: MethodStats,
+            want: Duration,
+            name: String,
+        }
+
+        let table = [
+            TestCase {
+                name: String::from("no calls"),
+                args: MethodStats::default(),
+                want: Duration::ZERO,
+            },
+            TestCase {
+                name: String::from("normal"),
+                args: MethodStats {
+                    calls: 4,
+                    total: Duration::from_millis(10),
+                    max: Duration::from_millis(4),
+                },
+                want: Duration::from_micros(2500),
+            },
+            TestCase {
+                name: String::from("calls beyond u32"),
+                args: MethodStats {
+                    calls: 1 << 32,
+                    total: Duration::from_millis(1 << 32),
+                    max: Duration::from_millis(2),
+                },
+                want: Duration::from_millis(1),
+            },
+        ];
+
+        for test_case in table {
+            assert_eq!(
+                test_case.args.average(),
+                test_case.want,
+                "Failed in the \"{}\".",
+                test_case.name
+            );
+        }
+    }
+
+    #[test]
+    fn test_render_prometheus() {
+        let stats = vec![(
+            String::from("tasks.add"),
+            MethodStats {
+                calls: 3,
+                total: Duration::from_millis(1500),
+                max: Duration::from_millis(1000),
+            },
+        )];
+
+        let got = render_prometheus(&stats);
+        assert!(got.contains("taskmr_repository_calls_total{method=\"tasks.add\"} 3\n"));
+        assert!(
+            got.contains("taskmr_repository_duration_seconds_total{method=\"tasks.add\"} 1.5\n")
+        );
+        assert!(got.contains("taskmr_repository_duration_seconds_max{method=\"tasks.add\"} 1\n"));
+    }
+}
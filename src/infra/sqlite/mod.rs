@@ -2,5 +2,26 @@
 //!
 //! sqlite module manipulate SQLite3 with rusqlite.
 
+pub mod access;
+#[cfg(feature = "es")]
+pub mod anonymize;
+pub mod command_history;
+#[cfg(feature = "es")]
+pub mod es_tag_repository;
+#[cfg(feature = "es")]
 pub mod es_task_repository;
+#[cfg(feature = "es")]
+pub mod event_chain;
+pub mod fts;
+#[cfg(feature = "es")]
+pub mod import_checkpoint;
+pub mod metrics;
+pub mod schedule;
+pub mod schema;
+pub mod setup;
+#[cfg(feature = "es")]
+pub mod snapshot;
+#[cfg(feature = "es")]
+pub mod sprint_repository;
+#[cfg(feature = "legacy")]
 pub mod task_repository;
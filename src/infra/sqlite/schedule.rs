@@ -0,0 +1,419 @@
+use std::fmt;
+use std::path::PathBuf;
+use std::str::FromStr;
+
+use crate::Result;
+use rusqlite::{params, Connection};
+use thiserror::Error;
+
+use crate::infra::sqlite::access::RetryingConnection;
+
+/// ScheduleError is raised when a stored schedule can not be read.
+#[derive(Error, Debug, PartialEq, Eq)]
+pub enum ScheduleError {
+    #[error("unknown period `{0}`")]
+    UnknownPeriod(String),
+    #[error("unknown delivery `{0}`")]
+    UnknownDelivery(String),
+}
+
+/// Period is how often a report is delivered.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Period {
+    Daily,
+    Weekly,
+}
+
+impl fmt::Display for Period {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Period::Daily => write!(f, "daily"),
+            Period::Weekly => write!(f, "weekly"),
+        }
+    }
+}
+
+impl FromStr for Period {
+    type Err = ScheduleError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "daily" => Ok(Period::Daily),
+            "weekly" => Ok(Period::Weekly),
+            _ => Err(ScheduleError::UnknownPeriod(s.to_owned())),
+        }
+    }
+}
+
+/// Delivery is where a report goes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Delivery {
+    Stdout,
+    /// the report is appended to the file.
+    File(PathBuf),
+    /// the report is posted to the URL.
+    Webhook(String),
+}
+
+impl Delivery {
+    /// to_columns returns the kind and the target stored in the table.
+    fn to_columns(&self) -> (&'static str, Option<String>) {
+        match self {
+            Delivery::Stdout => ("stdout", None),
+            Delivery::File(path) => ("file", Some(path.to_string_lossy().into_owned())),
+            Delivery::Webhook(url) => ("webhook", Some(url.to_owned())),
+        }
+    }
+
+    /// from_columns restores the delivery stored in the table.
+    fn from_columns(kind: &str, target: Option<String>) -> Result<Self, ScheduleError> {
+        match (kind, target) {
+            ("stdout", _) => Ok(Delivery::Stdout),
+            ("file", Some(path)) => Ok(Delivery::File(PathBuf::from(path))),
+            ("webhook", Some(url)) => Ok(Delivery::Webhook(url)),
+            _ => Err(ScheduleError::UnknownDelivery(kind.to_owned())),
+        }
+    }
+}
+
+impl fmt::Display for Delivery {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Delivery::Stdout => write!(f, "stdout"),
+            Delivery::File(path) => write!(f, "file {}", path.display()),
+            Delivery::Webhook(url) => write!(f, "webhook {}", url),
+        }
+    }
+}
+
+/// Schedule is a report delivered periodically by `taskmr cron`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Schedule {
+    pub id: i64,
+    pub report: String,
+    pub period: Period,
+    pub delivery: Delivery,
+    /// last_run_on is the local date of the last delivery in `YYYY-MM-DD`.
+    pub last_run_on: Option<String>,
+}
+
+/// OutboxEntry is a rendered report waiting in the outbox to be delivered.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OutboxEntry {
+    pub id: i64,
+    pub delivery: Delivery,
+    pub payload: String,
+    /// attempts is the number of failed deliveries so far.
+    pub attempts: u32,
+}
+
+/// group_by_delivery groups schedules by their destinations, in the order each destination
+/// first appears, so that reports due together can be delivered as one digest.
+pub fn group_by_delivery(schedules: Vec<Schedule>) -> Vec<(Delivery, Vec<Schedule>)> {
+    let mut groups: Vec<(Delivery, Vec<Schedule>)> = vec![];
+    for schedule in schedules {
+        match groups.iter_mut().find(|(d, _)| *d == schedule.delivery) {
+            Some((_, group)) => group.push(schedule),
+            None => groups.push((schedule.delivery.clone(), vec![schedule])),
+        }
+    }
+    groups
+}
+
+/// ScheduleStore persists report schedules and the outbox of their reports into the task database.
+/// A report is put in the outbox as its schedule is marked run, so that a report which fails to
+/// be delivered is kept and retried rather than missed until the next period.
+pub struct ScheduleStore {
+    conn: RetryingConnection,
+}
+
+impl ScheduleStore {
+    /// Construct a ScheduleStore.
+    pub fn new(conn: Connection) -> ScheduleStore {
+        ScheduleStore {
+            conn: RetryingConnection::new(conn),
+        }
+    }
+
+    /// Create table for schedules.
+    /// This function is to be called at first time.
+    pub fn create_table_if_not_exists(&self) -> Result<()> {
+        self.conn.run(|conn| {
+            conn.execute(
+                "CREATE TABLE if not exists report_schedules (
+                    id INTEGER PRIMARY KEY,
+                    report TEXT NOT NULL,
+                    period TEXT NOT NULL,
+                    delivery TEXT NOT NULL,
+                    target TEXT,
+                    last_run_on TEXT
+                )",
+                [],
+            )?;
+            conn.execute(
+                "CREATE TABLE if not exists outbox (
+                    id INTEGER PRIMARY KEY,
+                    delivery TEXT NOT NULL,
+                    target TEXT,
+                    payload TEXT NOT NULL,
+                    attempts INTEGER NOT NULL DEFAULT 0,
+                    last_error TEXT
+                )",
+                [],
+            )
+        })?;
+
+        Ok(())
+    }
+
+    /// add stores a new schedule and returns its id.
+    pub fn add(&self, report: &str, period: Period, delivery: &Delivery) -> Result<i64> {
+        let (kind, target) = delivery.to_columns();
+        let id = self.conn.run(|conn| {
+            conn.execute(
+                "INSERT INTO report_schedules (report, period, delivery, target)
+                 VALUES (?1, ?2, ?3, ?4)",
+                params![report, period.to_string(), kind, target],
+            )?;
+            Ok(conn.last_insert_rowid())
+        })?;
+
+        Ok(id)
+    }
+
+    /// remove deletes the schedule and reports whether it existed.
+    pub fn remove(&self, id: i64) -> Result<bool> {
+        let deleted = self
+            .conn
+            .run(|conn| conn.execute("DELETE FROM report_schedules WHERE id = ?", [id]))?;
+
+        Ok(deleted > 0)
+    }
+
+    /// list returns all schedules ordered by id.
+    pub fn list(&self) -> Result<Vec<Schedule>> {
+        self.query("SELECT id, report, period, delivery, target, last_run_on FROM report_schedules ORDER BY id ASC")
+    }
+
+    /// due returns schedules which have not run within their period as of today in local time.
+    pub fn due(&self) -> Result<Vec<Schedule>> {
+        self.query(
+            "SELECT id, report, period, delivery, target, last_run_on
+             FROM report_schedules
+             WHERE last_run_on IS NULL
+                OR julianday(date('now', 'localtime')) - julianday(last_run_on)
+                   >= CASE period WHEN 'weekly' THEN 7 ELSE 1 END
+             ORDER BY id ASC",
+        )
+    }
+
+    /// enqueue puts the report rendered for the schedules in the outbox, and records that the
+    /// schedules ran today in local time, in one transaction. It returns the id of the entry.
+    pub fn enqueue(&self, schedule_ids: &[i64], delivery: &Delivery, payload: &str) -> Result<i64> {
+        let (kind, target) = delivery.to_columns();
+        let id = self.conn.run(|conn| {
+            let tx = conn.unchecked_transaction()?;
+            tx.execute(
+                "INSERT INTO outbox (delivery, target, payload) VALUES (?1, ?2, ?3)",
+                params![kind, target, payload],
+            )?;
+            let id = tx.last_insert_rowid();
+            for schedule_id in schedule_ids {
+                tx.execute(
+                    "UPDATE report_schedules SET last_run_on = date('now', 'localtime') WHERE id = ?",
+                    [schedule_id],
+                )?;
+            }
+            tx.commit()?;
+            Ok(id)
+        })?;
+
+        Ok(id)
+    }
+
+    /// outbox returns reports waiting to be delivered in the order they were put.
+    pub fn outbox(&self) -> Result<Vec<OutboxEntry>> {
+        let rows = self.conn.run(|conn| {
+            let mut stmt = conn.prepare(
+                "SELECT id, delivery, target, payload, attempts FROM outbox ORDER BY id ASC",
+            )?;
+            let iter = stmt.query_map([], |row| {
+                Ok((
+                    row.get::<_, i64>(0)?,
+                    row.get::<_, String>(1)?,
+                    row.get::<_, Option<String>>(2)?,
+                    row.get::<_, String>(3)?,
+                    row.get::<_, u32>(4)?,
+                ))
+            })?;
+            iter.collect::<rusqlite::Result<Vec<_>>>()
+        })?;
+
+        let mut entries = Vec::new();
+        for (id, kind, target, payload, attempts) in rows {
+            entries.push(OutboxEntry {
+                id,
+                delivery: Delivery::from_columns(&kind, target)?,
+                payload,
+                attempts,
+            });
+        }
+
+        Ok(entries)
+    }
+
+    /// mark_delivered removes the delivered report from the outbox.
+    pub fn mark_delivered(&self, id: i64) -> Result<()> {
+        self.conn
+            .run(|conn| conn.execute("DELETE FROM outbox WHERE id = ?", [id]))?;
+
+        Ok(())
+    }
+
+    /// mark_failed counts a failed delivery of the report, which stays in the outbox.
+    pub fn mark_failed(&self, id: i64, error: &str) -> Result<()> {
+        self.conn.run(|conn| {
+            conn.execute(
+                "UPDATE outbox SET attempts = attempts + 1, last_error = ?1 WHERE id = ?2",
+                params![error, id],
+            )
+        })?;
+
+        Ok(())
+    }
+
+    /// query reads schedules selected by the SQL.
+    fn query(&self, sql: &str) -> Result<Vec<Schedule>> {
+        let rows = self.conn.run(|conn| {
+            let mut stmt = conn.prepare(sql)?;
+            let iter = stmt.query_map([], |row| {
+                Ok((
+                    row.get::<_, i64>(0)?,
+                    row.get::<_, String>(1)?,
+                    row.get::<_, String>(2)?,
+                    row.get::<_, String>(3)?,
+                    row.get::<_, Option<String>>(4)?,
+                    row.get::<_, Option<String>>(5)?,
+                ))
+            })?;
+            iter.collect::<rusqlite::Result<Vec<_>>>()
+        })?;
+
+        let mut schedules = Vec::new();
+        for (id, report, period, kind, target, last_run_on) in rows {
+            schedules.push(Schedule {
+                id,
+                report,
+                period: period.parse()?,
+                delivery: Delivery::from_columns(&kind, target)?,
+                last_run_on,
+            });
+        }
+
+        Ok(schedules)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_due() {
+        let store = ScheduleStore::new(Connection::open_in_memory().unwrap());
+        store.create_table_if_not_exists().unwrap();
+
+        let daily = store
+            .add("stats", Period::Daily, &Delivery::Stdout)
+            .unwrap();
+        let weekly = store
+            .add(
+                "standup",
+                Period::Weekly,
+                &Delivery::File(PathBuf::from("/tmp/standup.txt")),
+            )
+            .unwrap();
+        assert_eq!(store.due().unwrap().len(), 2);
+
+        store
+            .enqueue(&[daily, weekly], &Delivery::Stdout, "report")
+            .unwrap();
+        assert_eq!(store.due().unwrap(), vec![]);
+
+        let set_last_run = |id: i64, days_ago: i64| {
+            store
+                .conn
+                .run(|conn| {
+                    conn.execute(
+                        "UPDATE report_schedules
+                         SET last_run_on = date('now', 'localtime', ?1)
+                         WHERE id = ?2",
+                        params![format!("-{} days", days_ago), id],
+                    )
+                })
+                .unwrap();
+        };
+        set_last_run(daily, 1);
+        set_last_run(weekly, 6);
+        let due: Vec<i64> = store.due().unwrap().iter().map(|s| s.id).collect();
+        assert_eq!(due, vec![daily]);
+
+        set_last_run(weekly, 7);
+        let due: Vec<i64> = store.due().unwrap().iter().map(|s| s.id).collect();
+        assert_eq!(due, vec![daily, weekly]);
+
+        assert!(store.remove(daily).unwrap());
+        assert!(!store.remove(daily).unwrap());
+        assert_eq!(store.list().unwrap().len(), 1);
+        assert_eq!(
+            store.list().unwrap()[0].delivery,
+            Delivery::File(PathBuf::from("/tmp/standup.txt"))
+        );
+    }
+
+    #[test]
+    fn test_outbox() {
+        let store = ScheduleStore::new(Connection::open_in_memory().unwrap());
+        store.create_table_if_not_exists().unwrap();
+
+        let slack = Delivery::Webhook(String::from("https://hooks.slack.com/services/a"));
+        let daily = store.add("stats", Period::Daily, &slack).unwrap();
+        let first = store.enqueue(&[daily], &slack, "first").unwrap();
+        let second = store.enqueue(&[], &Delivery::Stdout, "second").unwrap();
+        assert_eq!(store.due().unwrap(), vec![]);
+
+        store.mark_failed(first, "timed out").unwrap();
+        store.mark_delivered(second).unwrap();
+        assert_eq!(
+            store.outbox().unwrap(),
+            vec![OutboxEntry {
+                id: first,
+                delivery: slack,
+                payload: String::from("first"),
+                attempts: 1,
+            }]
+        );
+    }
+
+    #[test]
+    fn test_group_by_delivery() {
+        let schedule = |id, delivery: &Delivery| Schedule {
+            id,
+            report: String::from("stats"),
+            period: Period::Daily,
+            delivery: delivery.clone(),
+            last_run_on: None,
+        };
+        let slack = Delivery::Webhook(String::from("https://hooks.slack.com/services/a"));
+        let got = group_by_delivery(vec![
+            schedule(1, &slack),
+            schedule(2, &Delivery::Stdout),
+            schedule(3, &slack),
+        ]);
+        let got: Vec<(Delivery, Vec<i64>)> = got
+            .into_iter()
+            .map(|(d, g)| (d, g.iter().map(|s| s.id).collect()))
+            .collect();
+        assert_eq!(got, vec![(slack, vec![1, 3]), (Delivery::Stdout, vec![2])]);
+    }
+}
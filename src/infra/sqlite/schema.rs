@@ -0,0 +1,752 @@
+use std::fmt;
+
+use crate::Result;
+use rusqlite::Connection;
+use thiserror::Error;
+
+use crate::infra::sqlite::access::RetryingConnection;
+
+/// ColumnSpec is an expected column of a table.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ColumnSpec {
+    pub name: &'static str,
+    pub sql_type: &'static str,
+    /// definition is used as is by `ALTER TABLE ... ADD COLUMN`.
+    pub definition: &'static str,
+}
+
+impl ColumnSpec {
+    /// is_addable reports whether SQLite can add this column to an existing table.
+    /// SQLite refuses PRIMARY KEY, UNIQUE, NOT NULL without a default and non constant defaults.
+    pub fn is_addable(&self) -> bool {
+        let d = self.definition.to_uppercase();
+        if d.contains("PRIMARY KEY") || d.contains("UNIQUE") || d.contains("DEFAULT (") {
+            return false;
+        }
+        !d.contains("NOT NULL") || d.contains("DEFAULT")
+    }
+}
+
+/// TableSpec is an expected table.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TableSpec {
+    pub name: &'static str,
+    pub columns: &'static [ColumnSpec],
+}
+
+const fn col(name: &'static str, sql_type: &'static str, definition: &'static str) -> ColumnSpec {
+    ColumnSpec {
+        name,
+        sql_type,
+        definition,
+    }
+}
+
+/// EXPECTED_TABLES is the schema which this version of taskmr expects.
+/// Keep this in sync with `create_table_if_not_exists` of each repository.
+pub const EXPECTED_TABLES: &[TableSpec] = &[
+    #[cfg(feature = "legacy")]
+    TableSpec {
+        name: "tasks",
+        columns: &[
+            col("id", "INTEGER", "id INTEGER PRIMARY KEY"),
+            col("title", "TEXT", "title TEXT NOT NULL DEFAULT ''"),
+            col("is_closed", "INTEGER", "is_closed INTEGER DEFAULT 0"),
+            col(
+                "priority",
+                "INTEGER",
+                "priority INTEGER NOT NULL DEFAULT 10",
+            ),
+            col("cost", "INTEGER", "cost INTEGER NOT NULL DEFAULT 10"),
+            col(
+                "elapsed_time_sec",
+                "INTEGER",
+                "elapsed_time_sec INTEGER NOT NULL DEFAULT 0",
+            ),
+            col(
+                "created_at",
+                "TEXT",
+                "created_at TEXT NOT NULL DEFAULT (datetime(CURRENT_TIMESTAMP, 'localtime'))",
+            ),
+            col(
+                "updated_at",
+                "TEXT",
+                "updated_at TEXT NOT NULL DEFAULT (datetime(CURRENT_TIMESTAMP, 'localtime'))",
+            ),
+            col("deleted_at", "TEXT", "deleted_at TEXT"),
+        ],
+    },
+    #[cfg(feature = "legacy")]
+    TableSpec {
+        name: "task_journal",
+        columns: &[
+            col("id", "INTEGER", "id INTEGER PRIMARY KEY"),
+            col("task_id", "INTEGER", "task_id INTEGER NOT NULL"),
+            col("kind", "TEXT", "kind TEXT NOT NULL"),
+            col("title", "TEXT", "title TEXT"),
+            col("is_closed", "INTEGER", "is_closed INTEGER"),
+            col("priority", "INTEGER", "priority INTEGER"),
+            col("cost", "INTEGER", "cost INTEGER"),
+            col("elapsed_time_sec", "INTEGER", "elapsed_time_sec INTEGER"),
+            col(
+                "recorded_at",
+                "TEXT",
+                "recorded_at TEXT NOT NULL DEFAULT (datetime(CURRENT_TIMESTAMP, 'localtime'))",
+            ),
+            col("is_deleted", "INTEGER", "is_deleted INTEGER"),
+        ],
+    },
+    #[cfg(feature = "es")]
+    TableSpec {
+        name: "task_events",
+        columns: &[
+            col("aggregate_id", "TEXT", "aggregate_id TEXT NOT NULL"),
+            col(
+                "aggregate_version",
+                "INTEGER",
+                "aggregate_version INTEGER NOT NULL",
+            ),
+            col("event", "TEXT", "event TEXT NOT NULL"),
+            col("event_version", "INTEGER", "event_version INTEGER NOT NULL"),
+            col("occurred_on", "TEXT", "occurred_on TEXT NOT NULL"),
+        ],
+    },
+    #[cfg(feature = "es")]
+    TableSpec {
+        name: "task_sequential_ids",
+        columns: &[
+            col(
+                "sequential_id",
+                "INTEGER",
+                "sequential_id INTEGER PRIMARY KEY AUTOINCREMENT",
+            ),
+            col("task_id", "TEXT", "task_id TEXT NOT NULL UNIQUE"),
+            col("released", "INTEGER", "released INTEGER NOT NULL DEFAULT 0"),
+        ],
+    },
+    #[cfg(feature = "es")]
+    TableSpec {
+        name: "task_read_model",
+        columns: &[
+            col("task_id", "TEXT", "task_id TEXT PRIMARY KEY"),
+            col("version", "INTEGER", "version INTEGER NOT NULL"),
+            col("tag_version", "INTEGER", "tag_version INTEGER NOT NULL"),
+            col("task", "TEXT", "task TEXT NOT NULL"),
+        ],
+    },
+    #[cfg(feature = "es")]
+    TableSpec {
+        name: "event_chain",
+        columns: &[
+            col(
+                "position",
+                "INTEGER",
+                "position INTEGER PRIMARY KEY AUTOINCREMENT",
+            ),
+            col("aggregate_id", "TEXT", "aggregate_id TEXT NOT NULL"),
+            col(
+                "aggregate_version",
+                "INTEGER",
+                "aggregate_version INTEGER NOT NULL",
+            ),
+            col("prev_hash", "TEXT", "prev_hash TEXT NOT NULL"),
+            col("hash", "TEXT", "hash TEXT NOT NULL"),
+            col("signature", "TEXT", "signature TEXT"),
+        ],
+    },
+    #[cfg(feature = "es")]
+    TableSpec {
+        name: "tag_events",
+        columns: &[
+            col(
+                "aggregate_version",
+                "INTEGER",
+                "aggregate_version INTEGER PRIMARY KEY",
+            ),
+            col("event", "TEXT", "event TEXT NOT NULL"),
+            col("event_version", "INTEGER", "event_version INTEGER NOT NULL"),
+            col("occurred_on", "TEXT", "occurred_on TEXT NOT NULL"),
+        ],
+    },
+    #[cfg(feature = "es")]
+    TableSpec {
+        name: "sprints",
+        columns: &[
+            col("name", "TEXT", "name TEXT PRIMARY KEY"),
+            col("start_on", "TEXT", "start_on TEXT NOT NULL"),
+            col("length_days", "INTEGER", "length_days INTEGER NOT NULL"),
+            col("capacity", "INTEGER", "capacity INTEGER NOT NULL"),
+            col("closed_on", "TEXT", "closed_on TEXT"),
+        ],
+    },
+    #[cfg(feature = "es")]
+    TableSpec {
+        name: "sprint_tasks",
+        columns: &[
+            col("sprint_name", "TEXT", "sprint_name TEXT NOT NULL"),
+            col("sequential_id", "INTEGER", "sequential_id INTEGER NOT NULL"),
+            col("cost", "INTEGER", "cost INTEGER NOT NULL"),
+        ],
+    },
+];
+
+/// IndexSpec is an expected index of a table.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct IndexSpec {
+    pub name: &'static str,
+    pub table: &'static str,
+    /// columns is used as is by `CREATE INDEX ... ON table (columns)`.
+    pub columns: &'static str,
+}
+
+/// EXPECTED_INDEXES serve queries on hot paths, such as listing open tasks.
+/// They are created by `migrate_indexes`, so that existing databases get them too.
+/// Lookups of events by aggregate_id need none, since the primary key of task_events covers them.
+pub const EXPECTED_INDEXES: &[IndexSpec] = &[
+    #[cfg(feature = "legacy")]
+    IndexSpec {
+        name: "tasks_is_closed",
+        table: "tasks",
+        columns: "is_closed",
+    },
+    // NOTE: the expressions must be written as in the queries of the read model to be used.
+    #[cfg(feature = "es")]
+    IndexSpec {
+        name: "task_read_model_live",
+        table: "task_read_model",
+        columns: "COALESCE(json_extract(task, '$.is_deleted'), 0), \
+                  json_extract(task, '$.sequential_id')",
+    },
+];
+
+/// SchemaDrift is a difference between the expected schema and the actual database.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SchemaDrift {
+    MissingTable {
+        table: String,
+    },
+    MissingColumn {
+        table: String,
+        column: String,
+        expected_type: String,
+        addable: bool,
+    },
+    WrongType {
+        table: String,
+        column: String,
+        expected_type: String,
+        actual_type: String,
+    },
+    MissingIndex {
+        table: String,
+        index: String,
+    },
+}
+
+impl SchemaDrift {
+    /// is_auto_migratable reports whether taskmr can resolve this drift by itself.
+    pub fn is_auto_migratable(&self) -> bool {
+        match self {
+            SchemaDrift::MissingTable { .. } => true,
+            SchemaDrift::MissingColumn { addable, .. } => *addable,
+            SchemaDrift::WrongType { .. } => false,
+            SchemaDrift::MissingIndex { .. } => true,
+        }
+    }
+}
+
+impl fmt::Display for SchemaDrift {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SchemaDrift::MissingTable { table } => write!(f, "table `{}` is missing", table),
+            SchemaDrift::MissingColumn {
+                table,
+                column,
+                expected_type,
+                ..
+            } => write!(
+                f,
+                "column `{}.{}` ({}) is missing",
+                table, column, expected_type
+            ),
+            SchemaDrift::WrongType {
+                table,
+                column,
+                expected_type,
+                actual_type,
+            } => write!(
+                f,
+                "column `{}.{}` has type {} but {} is expected",
+                table, column, actual_type, expected_type
+            ),
+            SchemaDrift::MissingIndex { table, index } => {
+                write!(f, "index `{}` of `{}` is missing", index, table)
+            }
+        }
+    }
+}
+
+/// SchemaError is raised when the database cannot be used by this version of taskmr.
+#[derive(Error, Debug)]
+pub enum SchemaError {
+    #[error("the database schema is incompatible: {}. Back up the database and recreate the table, or run `taskmr doctor --schema` for details", .0.iter().map(|d| d.to_string()).collect::<Vec<_>>().join(", "))]
+    Incompatible(Vec<SchemaDrift>),
+    #[error("the database file is corrupted: {0}. Restore it from a backup")]
+    Corrupted(String),
+}
+
+/// SchemaInspector compares the database with EXPECTED_TABLES and migrates it if possible.
+pub struct SchemaInspector {
+    conn: RetryingConnection,
+}
+
+impl SchemaInspector {
+    /// Construct a SchemaInspector.
+    pub fn new(conn: Connection) -> SchemaInspector {
+        SchemaInspector {
+            conn: RetryingConnection::new(conn),
+        }
+    }
+
+    /// inspect returns all drifts against EXPECTED_TABLES and EXPECTED_INDEXES.
+    pub fn inspect(&self) -> Result<Vec<SchemaDrift>> {
+        let mut drifts = self.inspect_tables(EXPECTED_TABLES)?;
+        drifts.extend(self.inspect_indexes(EXPECTED_INDEXES)?);
+        Ok(drifts)
+    }
+
+    /// inspect_tables returns all drifts against the given tables.
+    pub fn inspect_tables(&self, tables: &[TableSpec]) -> Result<Vec<SchemaDrift>> {
+        let mut drifts = Vec::new();
+
+        for table in tables {
+            let actual = self.columns_of(table.name)?;
+            if actual.is_empty() {
+                drifts.push(SchemaDrift::MissingTable {
+                    table: table.name.to_owned(),
+                });
+                continue;
+            }
+
+            for column in table.columns {
+                match actual.iter().find(|(name, _)| name == column.name) {
+                    None => drifts.push(SchemaDrift::MissingColumn {
+                        table: table.name.to_owned(),
+                        column: column.name.to_owned(),
+                        expected_type: column.sql_type.to_owned(),
+                        addable: column.is_addable(),
+                    }),
+                    Some((_, actual_type)) => {
+                        if !actual_type.eq_ignore_ascii_case(column.sql_type) {
+                            drifts.push(SchemaDrift::WrongType {
+                                table: table.name.to_owned(),
+                                column: column.name.to_owned(),
+                                expected_type: column.sql_type.to_owned(),
+                                actual_type: actual_type.to_owned(),
+                            });
+                        }
+                    }
+                }
+            }
+        }
+
+        Ok(drifts)
+    }
+
+    /// inspect_indexes returns missing indexes of the given ones.
+    /// Indexes of missing tables are left out, since the missing tables are reported instead.
+    pub fn inspect_indexes(&self, indexes: &[IndexSpec]) -> Result<Vec<SchemaDrift>> {
+        let mut drifts = Vec::new();
+
+        for index in indexes {
+            if self.columns_of(index.table)?.is_empty() {
+                continue;
+            }
+            let exists = self.conn.run(|conn| {
+                conn.query_row(
+                    "SELECT EXISTS (SELECT 1 FROM sqlite_master WHERE type = 'index' AND name = ?1)",
+                    [index.name],
+                    |row| row.get::<_, bool>(0),
+                )
+            })?;
+            if !exists {
+                drifts.push(SchemaDrift::MissingIndex {
+                    table: index.table.to_owned(),
+                    index: index.name.to_owned(),
+                });
+            }
+        }
+
+        Ok(drifts)
+    }
+
+    /// check_integrity runs `PRAGMA quick_check` and returns an error if the file is corrupted.
+    pub fn check_integrity(&self) -> Result<()> {
+        let result = self
+            .conn
+            .run(|conn| conn.query_row("PRAGMA quick_check", [], |row| row.get::<_, String>(0)));
+
+        match result {
+            Ok(r) if r == "ok" => Ok(()),
+            Ok(r) => Err(SchemaError::Corrupted(r).into()),
+            Err(err) => Err(SchemaError::Corrupted(err.to_string()).into()),
+        }
+    }
+
+    /// user_version returns the schema version stored in `PRAGMA user_version`.
+    pub fn user_version(&self) -> Result<i64> {
+        let version = self
+            .conn
+            .run(|conn| conn.query_row("PRAGMA user_version", [], |row| row.get(0)))?;
+
+        Ok(version)
+    }
+
+    /// row_count returns the number of rows in the table, or None if the table does not exist.
+    pub fn row_count(&self, table: &str) -> Result<Option<i64>> {
+        if self.columns_of(table)?.is_empty() {
+            return Ok(None);
+        }
+
+        let sql = format!("SELECT COUNT(*) FROM \"{}\"", table);
+        let count = self
+            .conn
+            .run(|conn| conn.query_row(&sql, [], |row| row.get(0)))?;
+
+        Ok(Some(count))
+    }
+
+    /// migrate adds missing columns which SQLite can add and returns drifts left unresolved.
+    /// Missing tables are expected to be created by `create_table_if_not_exists` of repositories.
+    pub fn migrate(&self, tables: &[TableSpec]) -> Result<Vec<SchemaDrift>> {
+        let mut unresolved = Vec::new();
+
+        for drift in self.inspect_tables(tables)? {
+            match &drift {
+                SchemaDrift::MissingColumn {
+                    table,
+                    column,
+                    addable: true,
+                    ..
+                } => {
+                    let spec = tables
+                        .iter()
+                        .find(|t| t.name == table)
+                        .and_then(|t| t.columns.iter().find(|c| c.name == column))
+                        .expect("drift must come from the given tables");
+                    let sql = format!("ALTER TABLE {} ADD COLUMN {}", table, spec.definition);
+                    self.conn.run(|conn| conn.execute(&sql, []))?;
+                    tracing::info!(
+                        table = table.as_str(),
+                        column = column.as_str(),
+                        "added a missing column"
+                    );
+                }
+                _ => unresolved.push(drift),
+            }
+        }
+
+        Ok(unresolved)
+    }
+
+    /// migrate_indexes creates missing indexes of existing tables.
+    pub fn migrate_indexes(&self, indexes: &[IndexSpec]) -> Result<()> {
+        for drift in self.inspect_indexes(indexes)? {
+            let SchemaDrift::MissingIndex { table, index } = &drift else {
+                continue;
+            };
+            let spec = indexes
+                .iter()
+                .find(|i| i.name == index)
+                .expect("drift must come from the given indexes");
+            let sql = format!(
+                "CREATE INDEX IF NOT EXISTS {} ON {} ({})",
+                spec.name, spec.table, spec.columns
+            );
+            self.conn.run(|conn| conn.execute(&sql, []))?;
+            tracing::info!(
+                table = table.as_str(),
+                index = index.as_str(),
+                "created a missing index"
+            );
+        }
+
+        Ok(())
+    }
+
+    /// ensure adds missing columns which SQLite can add and fails with a precise message
+    /// if the schema is still incompatible. It runs before `setup::create_database`, so that no
+    /// DDL runs against an incompatible schema. Missing tables and indexes are left to it.
+    /// The integrity is left to `taskmr doctor`, since `PRAGMA quick_check` scans the whole file.
+    pub fn ensure(&self) -> Result<()> {
+        let unresolved: Vec<SchemaDrift> = self
+            .migrate(EXPECTED_TABLES)?
+            .into_iter()
+            .filter(|d| !matches!(d, SchemaDrift::MissingTable { .. }))
+            .collect();
+        if unresolved.is_empty() {
+            return Ok(());
+        }
+
+        Err(SchemaError::Incompatible(unresolved).into())
+    }
+
+    /// columns_of returns pairs of the name and the declared type of columns.
+    fn columns_of(&self, table: &str) -> Result<Vec<(String, String)>> {
+        let columns = self.conn.run(|conn| {
+            let mut stmt = conn.prepare("SELECT name, type FROM pragma_table_info(?1)")?;
+            let iter = stmt.query_map([table], |row| Ok((row.get(0)?, row.get(1)?)))?;
+            iter.collect::<rusqlite::Result<Vec<(String, String)>>>()
+        })?;
+
+        Ok(columns)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_db_path() -> std::path::PathBuf {
+        let mut path = std::env::temp_dir();
+        let nanos = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_nanos();
+        path.push(format!("taskmr-schema-{}-{}.db", std::process::id(), nanos));
+        path
+    }
+
+    #[test]
+    fn test_fresh_database_has_no_drift() {
+        let path = temp_db_path();
+        crate::infra::sqlite::setup::create_database(&path).unwrap();
+
+        let inspector = SchemaInspector::new(Connection::open(&path).unwrap());
+        assert_eq!(inspector.inspect().unwrap(), vec![]);
+        inspector.ensure().unwrap();
+        assert_eq!(inspector.user_version().unwrap(), 0);
+        assert_eq!(inspector.row_count("report_schedules").unwrap(), Some(0));
+        assert_eq!(inspector.row_count("no_such_table").unwrap(), None);
+
+        drop(inspector);
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[cfg(feature = "legacy")]
+    #[test]
+    fn test_migrate() {
+        #[derive(Debug)]
+        struct TestCase {
+            given: &'static str,
+            want_drifts: Vec<SchemaDrift>,
+            want_unresolved: Vec<SchemaDrift>,
+            name: String,
+        }
+
+        let tasks: Vec<TableSpec> = EXPECTED_TABLES
+            .iter()
+            .filter(|t| t.name == "tasks")
+            .copied()
+            .collect();
+        let tasks = tasks.as_slice();
+
+        let table = [
+            TestCase {
+                name: String::from("missing addable column"),
+                given: "CREATE TABLE tasks (
+                    id INTEGER PRIMARY KEY,
+                    title TEXT NOT NULL,
+                    is_closed INTEGER DEFAULT 0,
+                    cost INTEGER NOT NULL DEFAULT 10,
+                    elapsed_time_sec INTEGER NOT NULL DEFAULT 0,
+                    created_at TEXT NOT NULL DEFAULT '',
+                    updated_at TEXT NOT NULL DEFAULT '',
+                    deleted_at TEXT
+                )",
+                want_drifts: vec![SchemaDrift::MissingColumn {
+                    table: "tasks".to_owned(),
+                    column: "priority".to_owned(),
+                    expected_type: "INTEGER".to_owned(),
+                    addable: true,
+                }],
+                want_unresolved: vec![],
+            },
+            TestCase {
+                name: String::from("missing not addable column and wrong type"),
+                given: "CREATE TABLE tasks (
+                    id INTEGER PRIMARY KEY,
+                    title TEXT NOT NULL,
+                    is_closed INTEGER DEFAULT 0,
+                    priority TEXT NOT NULL DEFAULT '10',
+                    cost INTEGER NOT NULL DEFAULT 10,
+                    elapsed_time_sec INTEGER NOT NULL DEFAULT 0,
+                    updated_at TEXT NOT NULL DEFAULT '',
+                    deleted_at TEXT
+                )",
+                want_drifts: vec![
+                    SchemaDrift::WrongType {
+                        table: "tasks".to_owned(),
+                        column: "priority".to_owned(),
+                        expected_type: "INTEGER".to_owned(),
+                        actual_type: "TEXT".to_owned(),
+                    },
+                    SchemaDrift::MissingColumn {
+                        table: "tasks".to_owned(),
+                        column: "created_at".to_owned(),
+                        expected_type: "TEXT".to_owned(),
+                        addable: false,
+                    },
+                ],
+                want_unresolved: vec![
+                    SchemaDrift::WrongType {
+                        table: "tasks".to_owned(),
+                        column: "priority".to_owned(),
+                        expected_type: "INTEGER".to_owned(),
+                        actual_type: "TEXT".to_owned(),
+                    },
+                    SchemaDrift::MissingColumn {
+                        table: "tasks".to_owned(),
+                        column: "created_at".to_owned(),
+                        expected_type: "TEXT".to_owned(),
+                        addable: false,
+                    },
+                ],
+            },
+            TestCase {
+                name: String::from("missing table"),
+                given: "CREATE TABLE unrelated (id INTEGER)",
+                want_drifts: vec![SchemaDrift::MissingTable {
+                    table: "tasks".to_owned(),
+                }],
+                want_unresolved: vec![SchemaDrift::MissingTable {
+                    table: "tasks".to_owned(),
+                }],
+            },
+        ];
+
+        for test_case in table {
+            let conn = Connection::open_in_memory().unwrap();
+            conn.execute(test_case.given, []).unwrap();
+            let inspector = SchemaInspector::new(conn);
+
+            assert_eq!(
+                inspector.inspect_tables(tasks).unwrap(),
+                test_case.want_drifts,
+                "Failed in the \"{}\".",
+                test_case.name,
+            );
+            assert_eq!(
+                inspector.migrate(tasks).unwrap(),
+                test_case.want_unresolved,
+                "Failed in the \"{}\".",
+                test_case.name,
+            );
+            assert_eq!(
+                inspector.inspect_tables(tasks).unwrap(),
+                test_case.want_unresolved,
+                "Failed in the \"{}\".",
+                test_case.name,
+            );
+        }
+    }
+
+    #[cfg(feature = "legacy")]
+    #[test]
+    fn test_ensure() {
+        #[derive(Debug)]
+        struct TestCase {
+            given: &'static str,
+            want: Option<&'static str>,
+            name: String,
+        }
+
+        let table = [
+            TestCase {
+                name: String::from("normal: missing tables are left to setup"),
+                given: "CREATE TABLE unrelated (id INTEGER)",
+                want: None,
+            },
+            TestCase {
+                name: String::from("abnormal: drifted table"),
+                given: "CREATE TABLE tasks (id INTEGER PRIMARY KEY, title TEXT)",
+                want: Some(
+                    "column `tasks.created_at` (TEXT) is missing, column `tasks.updated_at` (TEXT) is missing",
+                ),
+            },
+        ];
+
+        for test_case in table {
+            let conn = Connection::open_in_memory().unwrap();
+            conn.execute(test_case.given, []).unwrap();
+            let inspector = SchemaInspector::new(conn);
+
+            let got = inspector.ensure().err().map(|err| err.to_string());
+            assert_eq!(
+                got.is_some(),
+                test_case.want.is_some(),
+                "Failed in the \"{}\".",
+                test_case.name,
+            );
+            if let (Some(got), Some(want)) = (got, test_case.want) {
+                assert!(got.contains(want), "Failed in the \"{}\".", test_case.name);
+                // NOTE: no DDL but adding columns runs on the drifted table.
+                assert!(
+                    inspector
+                        .inspect_indexes(EXPECTED_INDEXES)
+                        .unwrap()
+                        .contains(&SchemaDrift::MissingIndex {
+                            table: "tasks".to_owned(),
+                            index: "tasks_is_closed".to_owned(),
+                        }),
+                    "Failed in the \"{}\".",
+                    test_case.name,
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_migrate_indexes() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute("CREATE TABLE items (id INTEGER, kind TEXT)", [])
+            .unwrap();
+        let inspector = SchemaInspector::new(conn);
+        let indexes = &[
+            IndexSpec {
+                name: "items_kind",
+                table: "items",
+                columns: "kind",
+            },
+            IndexSpec {
+                name: "missing_table_id",
+                table: "missing_table",
+                columns: "id",
+            },
+        ];
+
+        let missing = SchemaDrift::MissingIndex {
+            table: "items".to_owned(),
+            index: "items_kind".to_owned(),
+        };
+        assert_eq!(inspector.inspect_indexes(indexes).unwrap(), vec![missing]);
+        inspector.migrate_indexes(indexes).unwrap();
+        inspector.migrate_indexes(indexes).unwrap();
+        assert_eq!(inspector.inspect_indexes(indexes).unwrap(), vec![]);
+    }
+
+    #[test]
+    fn test_incompatible_error_message() {
+        let err = SchemaError::Incompatible(vec![SchemaDrift::WrongType {
+            table: "tasks".to_owned(),
+            column: "priority".to_owned(),
+            expected_type: "INTEGER".to_owned(),
+            actual_type: "TEXT".to_owned(),
+        }]);
+
+        assert_eq!(
+            err.to_string(),
+            "the database schema is incompatible: column `tasks.priority` has type TEXT but INTEGER is expected. Back up the database and recreate the table, or run `taskmr doctor --schema` for details"
+        );
+    }
+}
@@ -0,0 +1,49 @@
+//! # setup
+//!
+//! setup creates the task database with all of its tables.
+
+use std::fs;
+use std::path::Path;
+
+use crate::{Error, Result};
+use rusqlite::Connection;
+
+use crate::infra::sqlite::command_history::CommandHistoryStore;
+#[cfg(feature = "es")]
+use crate::infra::sqlite::es_tag_repository::TagCatalogRepository;
+#[cfg(feature = "es")]
+use crate::infra::sqlite::es_task_repository::TaskRepository as ESTaskRepository;
+use crate::infra::sqlite::metrics::MetricsStore;
+use crate::infra::sqlite::schedule::ScheduleStore;
+use crate::infra::sqlite::schema::{SchemaInspector, EXPECTED_INDEXES};
+#[cfg(feature = "es")]
+use crate::infra::sqlite::sprint_repository::SprintRepository;
+#[cfg(feature = "legacy")]
+use crate::infra::sqlite::task_repository::TaskRepository;
+
+/// create_database creates the database file, its directory and the tables which do not exist yet.
+pub fn create_database(path: &Path) -> Result<()> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)
+            .map_err(|err| Error::storage(format!("failed to create {}", dir.display()), err))?;
+    }
+    let open = || {
+        Connection::open(path)
+            .map_err(|err| Error::storage(format!("failed to open {}", path.display()), err))
+    };
+
+    #[cfg(feature = "legacy")]
+    TaskRepository::new(open()?).create_table_if_not_exists()?;
+    #[cfg(feature = "es")]
+    ESTaskRepository::new(open()?).create_table_if_not_exists()?;
+    #[cfg(feature = "es")]
+    TagCatalogRepository::new(open()?).create_table_if_not_exists()?;
+    #[cfg(feature = "es")]
+    SprintRepository::new(open()?).create_table_if_not_exists()?;
+    MetricsStore::new(open()?).create_table_if_not_exists()?;
+    ScheduleStore::new(open()?).create_table_if_not_exists()?;
+    CommandHistoryStore::new(open()?).create_table_if_not_exists()?;
+    SchemaInspector::new(open()?).migrate_indexes(EXPECTED_INDEXES)?;
+
+    Ok(())
+}
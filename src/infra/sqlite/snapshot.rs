@@ -0,0 +1,214 @@
+//! # snapshot
+//!
+//! snapshot writes a compacted, read-only copy of the task database to hand over.
+
+use std::fs;
+use std::path::Path;
+
+use super::anonymize::{self, Anonymizer};
+use crate::{Error, Result};
+use rusqlite::Connection;
+
+/// LOCAL_TABLES hold state of this machine, such as metrics, delivery targets of schedules,
+/// invoked commands and checkpoints of imports.
+/// They are dropped from snapshots, since they are not tasks.
+const LOCAL_TABLES: &[&str] = &[
+    "repository_metrics",
+    "slow_queries",
+    "report_schedules",
+    "outbox",
+    "command_history",
+    "import_checkpoints",
+];
+
+/// write_snapshot copies the database into path by `VACUUM INTO`, then strips the copy.
+/// Tasks of `removed_ids` are deleted from the copy, so that private tasks can be left out.
+/// Texts of the copy are replaced with pseudonyms if the anonymizer is given.
+/// The copy is in the rollback journal mode, so that it is a single file, and is made read-only.
+pub fn write_snapshot(
+    conn: &Connection,
+    path: &Path,
+    removed_ids: &[i64],
+    anonymizer: Option<&Anonymizer>,
+) -> Result<()> {
+    if path.exists() {
+        return Err(Error::Storage(
+            format!("{} already exists", path.display()).into(),
+        ));
+    }
+    conn.execute("VACUUM INTO ?1", [path.to_string_lossy()])?;
+
+    let strip = || -> Result<()> {
+        let snapshot = Connection::open(path)?;
+        for table in LOCAL_TABLES {
+            snapshot.execute_batch(&format!("DROP TABLE IF EXISTS {}", table))?;
+        }
+        if has_table(&snapshot, "task_sequential_ids")? {
+            let tx = snapshot.unchecked_transaction()?;
+            let has_read_model = has_table(&tx, "task_read_model")?;
+            for id in removed_ids {
+                if has_read_model {
+                    tx.execute(
+                        "DELETE FROM task_read_model WHERE task_id IN
+                         (SELECT task_id FROM task_sequential_ids WHERE sequential_id = ?1)",
+                        [id],
+                    )?;
+                }
+                tx.execute(
+                    "DELETE FROM task_events WHERE aggregate_id IN
+                     (SELECT task_id FROM task_sequential_ids WHERE sequential_id = ?1)",
+                    [id],
+                )?;
+                tx.execute(
+                    "DELETE FROM task_sequential_ids WHERE sequential_id = ?1",
+                    [id],
+                )?;
+                if has_table(&tx, "sprint_tasks")? {
+                    tx.execute("DELETE FROM sprint_tasks WHERE sequential_id = ?1", [id])?;
+                }
+            }
+            // NOTE: the removed events break the chain of events, so that it is cleared
+            // instead of being reported as tampered.
+            if !removed_ids.is_empty() && has_table(&tx, "event_chain")? {
+                tx.execute("DELETE FROM event_chain", [])?;
+            }
+            tx.commit()?;
+        }
+        if let Some(anonymizer) = anonymizer {
+            anonymize::anonymize_database(&snapshot, anonymizer)?;
+        }
+        snapshot.pragma_update(None, "journal_mode", "DELETE")?;
+        snapshot.execute_batch("VACUUM")?;
+        drop(snapshot);
+
+        let mut permissions = fs::metadata(path)?.permissions();
+        permissions.set_readonly(true);
+        fs::set_permissions(path, permissions)?;
+        Ok(())
+    };
+    strip().inspect_err(|_| {
+        // NOTE: a half-stripped copy may still have private tasks or texts, so it is not left behind.
+        let _ = fs::remove_file(path);
+    })
+}
+
+/// has_table returns whether the database has the table.
+pub(super) fn has_table(conn: &Connection, table: &str) -> rusqlite::Result<bool> {
+    conn.query_row(
+        "SELECT EXISTS (SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = ?1)",
+        [table],
+        |row| row.get(0),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::es_task::{IESTaskRepository, IESTaskRepositoryComponent, SequentialID};
+    use crate::infra::sqlite::es_task_repository::TaskRepository;
+    use crate::infra::sqlite::event_chain::EventChain;
+    use crate::infra::sqlite::metrics::MetricsStore;
+    use crate::usecase::es_add_task_usecase::{AddTaskUseCase, AddTaskUseCaseInput};
+
+    struct SnapshotComponentImpl {
+        task_repository: TaskRepository,
+    }
+
+    impl IESTaskRepositoryComponent for SnapshotComponentImpl {
+        type Repository = TaskRepository;
+        fn repository(&self) -> &Self::Repository {
+            &self.task_repository
+        }
+    }
+
+    fn temp_path(name: &str) -> std::path::PathBuf {
+        let mut path = std::env::temp_dir();
+        let nanos = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_nanos();
+        path.push(format!(
+            "taskmr-snapshot-{}-{}-{}.db",
+            name,
+            std::process::id(),
+            nanos
+        ));
+        path
+    }
+
+    #[test]
+    fn test_write_snapshot() {
+        let source = temp_path("source");
+        let task_repository = TaskRepository::new(Connection::open(&source).unwrap())
+            .with_event_chain(Some(EventChain::default()));
+        task_repository.create_table_if_not_exists().unwrap();
+        MetricsStore::new(Connection::open(&source).unwrap())
+            .create_table_if_not_exists()
+            .unwrap();
+        let component = SnapshotComponentImpl { task_repository };
+        for (title, private) in [("public", false), ("secret", true)] {
+            <SnapshotComponentImpl as AddTaskUseCase>::execute(
+                &component,
+                AddTaskUseCaseInput {
+                    title: title.to_owned(),
+                    priority: None,
+                    urgency: None,
+                    cost: None,
+                    tags: vec![],
+                    private,
+                    habit: None,
+                    due: None,
+                    someday: false,
+                    parent: None,
+                    rules: Default::default(),
+                    description: None,
+                },
+            )
+            .unwrap();
+        }
+
+        let conn = Connection::open(&source).unwrap();
+        let path = temp_path("copy");
+        write_snapshot(&conn, &path, &[2], None).unwrap();
+        assert!(write_snapshot(&conn, &path, &[], None).is_err());
+
+        assert!(fs::metadata(&path).unwrap().permissions().readonly());
+        let snapshot = Connection::open(&path).unwrap();
+        let count = |sql: &str| -> i64 { snapshot.query_row(sql, [], |row| row.get(0)).unwrap() };
+        assert_eq!(count("SELECT COUNT(*) FROM task_sequential_ids"), 1);
+        assert_eq!(
+            count("SELECT COUNT(DISTINCT aggregate_id) FROM task_events"),
+            1
+        );
+        assert_eq!(count("SELECT COUNT(*) FROM task_read_model"), 1);
+        assert_eq!(count("SELECT COUNT(*) FROM event_chain"), 0);
+        assert!(!has_table(&snapshot, "slow_queries").unwrap());
+        let mode: String = snapshot
+            .query_row("PRAGMA journal_mode", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(mode, "delete");
+
+        let anonymized = temp_path("anonymized");
+        write_snapshot(&conn, &anonymized, &[], Some(&Anonymizer::new())).unwrap();
+        let snapshot = Connection::open(&anonymized).unwrap();
+        let count = |sql: &str| -> i64 { snapshot.query_row(sql, [], |row| row.get(0)).unwrap() };
+        assert_eq!(count("SELECT COUNT(*) FROM task_events"), 5);
+        assert_eq!(
+            count(
+                "SELECT COUNT(*) FROM task_events, task_read_model
+                 WHERE event LIKE '%public%' OR task LIKE '%public%'"
+            ),
+            0
+        );
+        let task_repository = TaskRepository::new(snapshot);
+        let task = task_repository
+            .load_by_sequential_id(SequentialID::new(1))
+            .unwrap()
+            .unwrap();
+        assert!(task.title().starts_with("task-"));
+
+        fs::remove_file(path).unwrap();
+        fs::remove_file(anonymized).unwrap();
+        fs::remove_file(source).unwrap();
+    }
+}
@@ -0,0 +1,198 @@
+use crate::Result;
+use chrono::NaiveDate;
+use rusqlite::{params, Connection, OptionalExtension};
+
+use crate::domain::es_task::{Cost, SequentialID};
+use crate::domain::sprint::{ISprintRepository, Sprint, SprintLength, SprintName, SprintTask};
+use crate::infra::sqlite::access::RetryingConnection;
+
+/// DATE_FORMAT is the format of dates stored in sprint tables.
+const DATE_FORMAT: &str = "%Y-%m-%d";
+
+/// Implementation of ISprintRepository.
+pub struct SprintRepository {
+    conn: RetryingConnection,
+}
+
+impl SprintRepository {
+    /// Construct a SprintRepository.
+    pub fn new(conn: Connection) -> SprintRepository {
+        SprintRepository {
+            conn: RetryingConnection::new(conn),
+        }
+    }
+
+    /// Create tables for sprints.
+    /// This function is to be called at first time.
+    pub fn create_table_if_not_exists(&self) -> Result<()> {
+        self.conn.run(|conn| {
+            conn.execute_batch(
+                "CREATE TABLE if not exists sprints (
+                    name TEXT PRIMARY KEY,
+                    start_on TEXT NOT NULL,
+                    length_days INTEGER NOT NULL,
+                    capacity INTEGER NOT NULL,
+                    closed_on TEXT
+                );
+                CREATE TABLE if not exists sprint_tasks (
+                    sprint_name TEXT NOT NULL,
+                    sequential_id INTEGER NOT NULL,
+                    cost INTEGER NOT NULL,
+                    PRIMARY KEY(sprint_name, sequential_id),
+                    FOREIGN KEY (sprint_name) REFERENCES sprints(name)
+                );",
+            )
+        })?;
+
+        Ok(())
+    }
+}
+
+impl ISprintRepository for SprintRepository {
+    /// save the sprint and its tasks in a transaction.
+    fn save(&self, sprint: &Sprint) -> Result<()> {
+        self.conn.run(|conn| {
+            let tx = conn.unchecked_transaction()?;
+            tx.execute(
+                "INSERT INTO sprints (name, start_on, length_days, capacity, closed_on)
+                 VALUES (?1, ?2, ?3, ?4, ?5)
+                 ON CONFLICT(name) DO UPDATE SET
+                    start_on = excluded.start_on,
+                    length_days = excluded.length_days,
+                    capacity = excluded.capacity,
+                    closed_on = excluded.closed_on",
+                params![
+                    sprint.name().as_str(),
+                    sprint.start_on().format(DATE_FORMAT).to_string(),
+                    sprint.length().days(),
+                    sprint.capacity().to_i32(),
+                    sprint
+                        .closed_on()
+                        .map(|d| d.format(DATE_FORMAT).to_string()),
+                ],
+            )?;
+            tx.execute(
+                "DELETE FROM sprint_tasks WHERE sprint_name = ?",
+                [sprint.name().as_str()],
+            )?;
+            for task in sprint.tasks() {
+                tx.execute(
+                    "INSERT INTO sprint_tasks (sprint_name, sequential_id, cost)
+                     VALUES (?1, ?2, ?3)",
+                    params![
+                        sprint.name().as_str(),
+                        task.sequential_id.to_i64(),
+                        task.cost.to_i32(),
+                    ],
+                )?;
+            }
+            tx.commit()
+        })?;
+
+        Ok(())
+    }
+
+    /// load the sprint and its tasks ordered by sequential_id.
+    fn load(&self, name: &SprintName) -> Result<Option<Sprint>> {
+        let row = self.conn.run(|conn| {
+            conn.query_row(
+                "SELECT start_on, length_days, capacity, closed_on
+                 FROM sprints
+                 WHERE name = ?",
+                [name.as_str()],
+                |row| {
+                    Ok((
+                        row.get::<_, String>(0)?,
+                        row.get::<_, u32>(1)?,
+                        row.get::<_, i32>(2)?,
+                        row.get::<_, Option<String>>(3)?,
+                    ))
+                },
+            )
+            .optional()
+        })?;
+
+        let (start_on, length_days, capacity, closed_on) = match row {
+            Some(row) => row,
+            None => return Ok(None),
+        };
+
+        let tasks = self.conn.run(|conn| {
+            let mut stmt = conn.prepare(
+                "SELECT sequential_id, cost
+                 FROM sprint_tasks
+                 WHERE sprint_name = ?
+                 ORDER BY sequential_id ASC",
+            )?;
+            let iter = stmt.query_map([name.as_str()], |row| {
+                Ok(SprintTask {
+                    sequential_id: SequentialID::new(row.get(0)?),
+                    cost: Cost::new(row.get(1)?),
+                })
+            })?;
+            iter.collect::<rusqlite::Result<Vec<_>>>()
+        })?;
+
+        Ok(Some(Sprint::recreate(
+            name.clone(),
+            NaiveDate::parse_from_str(&start_on, DATE_FORMAT)?,
+            SprintLength::from_days(length_days),
+            Cost::new(capacity),
+            tasks,
+            closed_on
+                .map(|d| NaiveDate::parse_from_str(&d, DATE_FORMAT))
+                .transpose()?,
+        )))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_save_and_load() {
+        let repository = SprintRepository::new(Connection::open_in_memory().unwrap());
+        repository.create_table_if_not_exists().unwrap();
+
+        let name = SprintName::new("s1").unwrap();
+        assert_eq!(repository.load(&name).unwrap(), None);
+
+        let mut sprint = Sprint::create(
+            name.clone(),
+            NaiveDate::from_ymd_opt(2026, 10, 1).unwrap(),
+            SprintLength::from_days(14),
+            Cost::new(40),
+            vec![
+                SprintTask {
+                    sequential_id: SequentialID::new(3),
+                    cost: Cost::new(5),
+                },
+                SprintTask {
+                    sequential_id: SequentialID::new(1),
+                    cost: Cost::new(8),
+                },
+            ],
+            false,
+        )
+        .unwrap();
+        repository.save(&sprint).unwrap();
+
+        let got = repository.load(&name).unwrap().unwrap();
+        assert_eq!(got.committed_cost(), Cost::new(13));
+        assert_eq!(got.tasks()[0].sequential_id, SequentialID::new(1));
+        assert_eq!(got.closed_on(), None);
+
+        sprint
+            .close(NaiveDate::from_ymd_opt(2026, 10, 14).unwrap())
+            .unwrap();
+        repository.save(&sprint).unwrap();
+
+        let got = repository.load(&name).unwrap().unwrap();
+        assert_eq!(
+            got.closed_on(),
+            Some(NaiveDate::from_ymd_opt(2026, 10, 14).unwrap())
+        );
+        assert_eq!(got.tasks().len(), 2);
+    }
+}
@@ -1,19 +1,41 @@
+use std::rc::Rc;
 use std::time::Duration;
 
-use anyhow::Result;
+use crate::Result;
 use rusqlite::Connection;
 
-use crate::domain::task::{Cost, ITaskRepository, Priority, Task, ID};
+use crate::domain::task::{
+    Cost, ITaskRepository, JournalChange, JournalEntry, Priority, Task, TaskOrder,
+    TaskRepositoryError, ID,
+};
+use crate::infra::sqlite::access::RetryingConnection;
+use crate::infra::sqlite::fts;
+use crate::infra::sqlite::metrics::RepositoryMetrics;
 
 /// Implementation of TaskRepository.
 pub struct TaskRepository {
-    conn: rusqlite::Connection,
+    conn: RetryingConnection,
+    metrics: Rc<RepositoryMetrics>,
 }
 
 impl TaskRepository {
     /// Construct a TaskRepository.
     pub fn new(conn: Connection) -> TaskRepository {
-        TaskRepository { conn }
+        TaskRepository {
+            conn: RetryingConnection::new(conn),
+            metrics: Rc::new(RepositoryMetrics::new()),
+        }
+    }
+
+    /// replace RepositoryMetrics to share it with other repositories.
+    pub fn with_metrics(mut self, metrics: Rc<RepositoryMetrics>) -> Self {
+        self.metrics = metrics;
+        self
+    }
+
+    /// get the access layer which holds metrics about the connection.
+    pub fn connection(&self) -> &RetryingConnection {
+        &self.conn
     }
 
     /// Create table tasks.
@@ -22,173 +44,454 @@ impl TaskRepository {
     /// FIXME: This function includes magic number about default values.
     /// These values should sync default values of task::Task::new.
     pub fn create_table_if_not_exists(&self) -> Result<()> {
-        self.conn.execute(
-            "CREATE TABLE if not exists tasks (
-                id INTEGER PRIMARY KEY,
-                title TEXT NOT NULL,
-                is_closed INTEGER DEFAULT 0,
-                priority INTEGER NOT NULL DEFAULT 10,
-                cost INTEGER NOT NULL DEFAULT 10,
-                elapsed_time_sec INTEGER NOT NULL DEFAULT 0,
-                created_at TEXT NOT NULL DEFAULT (datetime(CURRENT_TIMESTAMP, 'localtime')),
-                updated_at TEXT NOT NULL DEFAULT (datetime(CURRENT_TIMESTAMP, 'localtime'))
-            )",
-            [],
-        )?;
+        self.conn.run(|conn| {
+            conn.execute(
+                "CREATE TABLE if not exists tasks (
+                    id INTEGER PRIMARY KEY,
+                    title TEXT NOT NULL,
+                    is_closed INTEGER DEFAULT 0,
+                    priority INTEGER NOT NULL DEFAULT 10,
+                    cost INTEGER NOT NULL DEFAULT 10,
+                    elapsed_time_sec INTEGER NOT NULL DEFAULT 0,
+                    created_at TEXT NOT NULL DEFAULT (datetime(CURRENT_TIMESTAMP, 'localtime')),
+                    updated_at TEXT NOT NULL DEFAULT (datetime(CURRENT_TIMESTAMP, 'localtime')),
+                    deleted_at TEXT
+                )",
+                [],
+            )?;
+            // NOTE: columns of the task are the before-image, which are NULL for an added task.
+            conn.execute(
+                "CREATE TABLE if not exists task_journal (
+                    id INTEGER PRIMARY KEY,
+                    task_id INTEGER NOT NULL,
+                    kind TEXT NOT NULL,
+                    title TEXT,
+                    is_closed INTEGER,
+                    priority INTEGER,
+                    cost INTEGER,
+                    elapsed_time_sec INTEGER,
+                    recorded_at TEXT NOT NULL DEFAULT (datetime(CURRENT_TIMESTAMP, 'localtime')),
+                    is_deleted INTEGER
+                )",
+                [],
+            )?;
+            // NOTE: the index keeps no copy of titles, and triggers keep it in sync with tasks.
+            // It is filled from existing titles when it is created on an older database.
+            let has_index: bool = conn.query_row(
+                "SELECT EXISTS (SELECT 1 FROM sqlite_master WHERE name = 'tasks_fts')",
+                [],
+                |row| row.get(0),
+            )?;
+            conn.execute_batch(
+                "CREATE VIRTUAL TABLE if not exists tasks_fts
+                    USING fts5(title, content = 'tasks', content_rowid = 'id');
+                CREATE TRIGGER if not exists tasks_fts_insert AFTER INSERT ON tasks BEGIN
+                    INSERT INTO tasks_fts (rowid, title) VALUES (new.id, new.title);
+                END;
+                CREATE TRIGGER if not exists tasks_fts_delete AFTER DELETE ON tasks BEGIN
+                    INSERT INTO tasks_fts (tasks_fts, rowid, title)
+                        VALUES ('delete', old.id, old.title);
+                END;
+                CREATE TRIGGER if not exists tasks_fts_update AFTER UPDATE OF title ON tasks BEGIN
+                    INSERT INTO tasks_fts (tasks_fts, rowid, title)
+                        VALUES ('delete', old.id, old.title);
+                    INSERT INTO tasks_fts (rowid, title) VALUES (new.id, new.title);
+                END;",
+            )?;
+            if !has_index {
+                conn.execute("INSERT INTO tasks_fts (tasks_fts) VALUES ('rebuild')", [])?;
+            }
+            Ok(())
+        })?;
 
         Ok(())
     }
+
+    /// query tasks with the given sql which selects all columns of tasks, in the order.
+    fn query_tasks(&self, sql: &str, order: TaskOrder) -> Result<Vec<Task>> {
+        let sql = format!("{} {}", sql, order_by(order));
+        let tasks = self.conn.run(|conn| {
+            let mut stmt = conn.prepare(&sql)?;
+            let task_iter = stmt.query_map([], task_from_row)?;
+
+            task_iter.collect::<rusqlite::Result<Vec<Task>>>()
+        })?;
+
+        Ok(tasks)
+    }
 }
 
 impl ITaskRepository for TaskRepository {
     /// find a Task by id.
     fn find_by_id(&self, id: ID) -> Result<Option<Task>> {
-        let mut stmt = self.conn.prepare(
-            "SELECT id,
-                    title,
-                    is_closed,
-                    priority,
-                    cost,
-                    elapsed_time_sec,
-                    created_at,
-                    updated_at
-             FROM tasks where id = ?",
-        )?;
-
-        let mut rows = stmt.query([id.get()])?;
-
-        match rows.next()? {
-            Some(row) => Ok(Some(Task::from_repository(
-                ID::new(row.get(0)?),
-                row.get(1)?,
-                row.get(2)?,
-                Priority::new(row.get(3)?),
-                Cost::new(row.get(4)?),
-                Duration::from_secs(row.get(5)?),
-            ))),
-            None => Ok(None),
-        }
+        self.metrics.observe("tasks.find_by_id", || {
+            let task = self.conn.run(|conn| {
+                let mut stmt = conn.prepare(
+                    "SELECT id,
+                            title,
+                            is_closed,
+                            priority,
+                            cost,
+                            elapsed_time_sec,
+                            created_at,
+                            updated_at,
+                            deleted_at IS NOT NULL
+                     FROM tasks where id = ?1 AND deleted_at IS NULL",
+                )?;
+
+                let mut rows = stmt.query([id.get()])?;
+
+                match rows.next()? {
+                    Some(row) => Ok(Some(task_from_row(row)?)),
+                    None => Ok(None),
+                }
+            })?;
+
+            Ok(task)
+        })
     }
 
     /// find tasks that is not closed.
-    fn find_opening(&self) -> Result<Vec<Task>> {
-        let mut stmt = self.conn.prepare(
-            "SELECT id,
-                    title,
-                    is_closed,
-                    priority,
-                    cost,
-                    elapsed_time_sec,
-                    created_at,
-                    updated_at
-             FROM tasks where is_closed = 0",
-        )?;
-
-        let task_iter = stmt.query_map([], |row| {
-            Ok(Task::from_repository(
-                ID::new(row.get(0)?),
-                row.get(1)?,
-                row.get(2)?,
-                Priority::new(row.get(3)?),
-                Cost::new(row.get(4)?),
-                Duration::from_secs(row.get(5)?),
-            ))
-        })?;
-
-        let mut tv = Vec::new();
-        for t in task_iter {
-            tv.push(t?);
-        }
-
-        Ok(tv)
+    fn find_opening(&self, order: TaskOrder) -> Result<Vec<Task>> {
+        self.metrics.observe("tasks.find_opening", || {
+            self.query_tasks(SELECT_OPENING, order)
+        })
     }
 
     /// fetch all tasks regardless it is closed.
-    fn fetch_all(&self) -> Result<Vec<Task>> {
-        let mut stmt = self.conn.prepare(
-            "SELECT id,
-                    title,
-                    is_closed,
-                    priority,
-                    cost,
-                    elapsed_time_sec,
-                    created_at,
-                    updated_at
-             FROM tasks",
-        )?;
-
-        let task_iter = stmt.query_map([], |row| {
-            Ok(Task::from_repository(
-                ID::new(row.get(0)?),
-                row.get(1)?,
-                row.get(2)?,
-                Priority::new(row.get(3)?),
-                Cost::new(row.get(4)?),
-                Duration::from_secs(row.get(5)?),
-            ))
-        })?;
+    fn fetch_all(&self, order: TaskOrder) -> Result<Vec<Task>> {
+        self.metrics.observe("tasks.fetch_all", || {
+            self.query_tasks(
+                "SELECT id,
+                        title,
+                        is_closed,
+                        priority,
+                        cost,
+                        elapsed_time_sec,
+                        created_at,
+                        updated_at,
+                        deleted_at IS NOT NULL
+                 FROM tasks where deleted_at IS NULL",
+                order,
+            )
+        })
+    }
 
-        let mut tv = Vec::new();
-        for t in task_iter {
-            tv.push(t?);
-        }
+    /// find deleted tasks regardless whether it is closed.
+    fn find_deleted(&self, order: TaskOrder) -> Result<Vec<Task>> {
+        self.metrics.observe("tasks.find_deleted", || {
+            self.query_tasks(
+                "SELECT id,
+                        title,
+                        is_closed,
+                        priority,
+                        cost,
+                        elapsed_time_sec,
+                        created_at,
+                        updated_at,
+                        deleted_at IS NOT NULL
+                 FROM tasks where deleted_at IS NOT NULL",
+                order,
+            )
+        })
+    }
 
-        Ok(tv)
+    /// search tasks by the full-text index of titles, the best match first.
+    fn search(&self, query: &str) -> Result<Vec<Task>> {
+        let Some(query) = fts::prefix_query(query) else {
+            return Ok(vec![]);
+        };
+        self.metrics.observe("tasks.search", || {
+            let tasks = self.conn.run(|conn| {
+                let mut stmt = conn.prepare(SELECT_MATCHING)?;
+                let task_iter = stmt.query_map([&query], task_from_row)?;
+
+                task_iter.collect::<rusqlite::Result<Vec<Task>>>()
+            })?;
+
+            Ok(tasks)
+        })
     }
 
     /// add a Task.
     /// ID is auto incremented.
     /// It is client responsibility to set returned ID into the task.
     fn add(&self, a_task: Task) -> Result<ID> {
-        let mut stmt = self.conn.prepare(
-            "INSERT INTO tasks (
-                title,
-                is_closed,
-                priority,
-                cost,
-                elapsed_time_sec
-             ) VALUES (?1, ?2, ?3, ?4, ?5)",
-        )?;
-
-        let rowid = stmt.insert(rusqlite::params![
-            a_task.title(),
-            a_task.is_closed(),
-            a_task.priority().get(),
-            a_task.cost().get(),
-            a_task.elapsed_time().as_secs()
-        ])?;
-
-        Ok(ID::new(rowid))
+        self.metrics.observe("tasks.add", || {
+            let rowid = self.conn.run(|conn| {
+                let tx = conn.unchecked_transaction()?;
+                let rowid = tx
+                    .prepare(
+                        "INSERT INTO tasks (
+                            title,
+                            is_closed,
+                            priority,
+                            cost,
+                            elapsed_time_sec,
+                            deleted_at
+                         ) VALUES (
+                            ?1, ?2, ?3, ?4, ?5,
+                            CASE WHEN ?6 THEN datetime(CURRENT_TIMESTAMP, 'localtime') END
+                         )",
+                    )?
+                    .insert(rusqlite::params![
+                        a_task.title(),
+                        a_task.is_closed(),
+                        a_task.priority().get(),
+                        a_task.cost().get(),
+                        a_task.elapsed_time().as_secs(),
+                        a_task.is_deleted(),
+                    ])?;
+                tx.execute(
+                    "INSERT INTO task_journal (task_id, kind) VALUES (?1, ?2)",
+                    rusqlite::params![rowid, ADDED],
+                )?;
+                tx.commit()?;
+                Ok(rowid)
+            })?;
+
+            Ok(ID::new(rowid))
+        })
     }
 
     /// update a Task.
     fn update(&self, a_task: Task) -> Result<()> {
-        let mut stmt = self.conn.prepare(
-            "UPDATE tasks SET
-                title = ?1,
-                is_closed = ?2,
-                priority = ?3,
-                cost = ?4,
-                elapsed_time_sec = ?5
-             where id = ?6",
-        )?;
-
-        stmt.insert(rusqlite::params![
-            a_task.title(),
-            a_task.is_closed(),
-            a_task.priority().get(),
-            a_task.cost().get(),
-            a_task.elapsed_time().as_secs(),
-            a_task.id().get(),
-        ])?;
+        self.metrics.observe("tasks.update", || {
+            let changed = self.conn.run(|conn| {
+                let tx = conn.unchecked_transaction()?;
+                tx.execute(
+                    "INSERT INTO task_journal (
+                        task_id,
+                        kind,
+                        title,
+                        is_closed,
+                        priority,
+                        cost,
+                        elapsed_time_sec,
+                        is_deleted
+                     )
+                     SELECT id, ?1, title, is_closed, priority, cost, elapsed_time_sec,
+                            deleted_at IS NOT NULL
+                     FROM tasks where id = ?2",
+                    rusqlite::params![UPDATED, a_task.id().get()],
+                )?;
+                let changed = tx.execute(
+                    "UPDATE tasks SET
+                        title = ?1,
+                        is_closed = ?2,
+                        priority = ?3,
+                        cost = ?4,
+                        elapsed_time_sec = ?5,
+                        updated_at = datetime(CURRENT_TIMESTAMP, 'localtime'),
+                        deleted_at = CASE WHEN ?6
+                            THEN COALESCE(deleted_at, datetime(CURRENT_TIMESTAMP, 'localtime'))
+                        END
+                     where id = ?7",
+                    rusqlite::params![
+                        a_task.title(),
+                        a_task.is_closed(),
+                        a_task.priority().get(),
+                        a_task.cost().get(),
+                        a_task.elapsed_time().as_secs(),
+                        a_task.is_deleted(),
+                        a_task.id().get(),
+                    ],
+                )?;
+                // NOTE: nothing is journaled for a missing task either, since the INSERT selects no row.
+                tx.commit()?;
+                Ok(changed)
+            })?;
+
+            if changed == 0 {
+                return Err(TaskRepositoryError::NotFound(a_task.id().get()).into());
+            }
+            Ok(())
+        })
+    }
 
-        Ok(())
+    /// undo reverts the latest change in the journal and removes it from the journal.
+    fn undo(&self) -> Result<Option<JournalEntry>> {
+        self.metrics.observe("tasks.undo", || {
+            let entry = self.conn.run(|conn| {
+                let tx = conn.unchecked_transaction()?;
+                let (journal_id, entry) = {
+                    let mut stmt =
+                        tx.prepare(&format!("{} ORDER BY id DESC LIMIT 1", SELECT_JOURNAL))?;
+                    let mut rows = stmt.query([])?;
+                    match rows.next()? {
+                        Some(row) => (row.get::<_, i64>(0)?, journal_entry(row)?),
+                        None => return Ok(None),
+                    }
+                };
+                match &entry.before {
+                    None => tx.execute("DELETE FROM tasks where id = ?1", [entry.task_id.get()])?,
+                    Some(before) => tx.execute(
+                        "UPDATE tasks SET
+                            title = ?1,
+                            is_closed = ?2,
+                            priority = ?3,
+                            cost = ?4,
+                            elapsed_time_sec = ?5,
+                            updated_at = datetime(CURRENT_TIMESTAMP, 'localtime'),
+                            deleted_at = CASE WHEN ?6
+                                THEN COALESCE(deleted_at, datetime(CURRENT_TIMESTAMP, 'localtime'))
+                            END
+                         where id = ?7",
+                        rusqlite::params![
+                            before.title(),
+                            before.is_closed(),
+                            before.priority().get(),
+                            before.cost().get(),
+                            before.elapsed_time().as_secs(),
+                            before.is_deleted(),
+                            before.id().get(),
+                        ],
+                    )?,
+                };
+                tx.execute("DELETE FROM task_journal where id = ?1", [journal_id])?;
+                tx.commit()?;
+                Ok(Some(entry))
+            })?;
+
+            Ok(entry)
+        })
+    }
+
+    /// history reads the journal from the latest change.
+    fn history(&self, id: Option<ID>) -> Result<Vec<JournalEntry>> {
+        self.metrics.observe("tasks.history", || {
+            let entries = self.conn.run(|conn| {
+                let mut stmt = conn.prepare(&format!(
+                    "{} where ?1 IS NULL OR task_id = ?1 ORDER BY id DESC",
+                    SELECT_JOURNAL
+                ))?;
+                let entries = stmt.query_map([id.map(|id| id.get())], journal_entry)?;
+                entries.collect::<rusqlite::Result<Vec<JournalEntry>>>()
+            })?;
+
+            Ok(entries)
+        })
+    }
+}
+
+/// ADDED and UPDATED are kinds of changes stored in the journal.
+const ADDED: &str = "added";
+const UPDATED: &str = "updated";
+
+/// SELECT_OPENING selects open tasks, which the index `tasks_is_closed` serves.
+const SELECT_OPENING: &str = "SELECT id,
+        title,
+        is_closed,
+        priority,
+        cost,
+        elapsed_time_sec,
+        created_at,
+        updated_at,
+        deleted_at IS NOT NULL
+ FROM tasks where is_closed = 0 AND deleted_at IS NULL";
+
+/// SELECT_MATCHING selects tasks whose titles match the FTS5 query `?1`, ordered by relevance.
+const SELECT_MATCHING: &str = "SELECT t.id,
+        t.title,
+        t.is_closed,
+        t.priority,
+        t.cost,
+        t.elapsed_time_sec,
+        t.created_at,
+        t.updated_at,
+        t.deleted_at IS NOT NULL
+ FROM tasks_fts AS f JOIN tasks AS t ON t.id = f.rowid
+ where tasks_fts MATCH ?1 AND t.deleted_at IS NULL
+ ORDER BY f.rank, t.id ASC";
+
+/// SELECT_JOURNAL selects the columns which journal_entry reads.
+const SELECT_JOURNAL: &str = "SELECT id,
+        task_id,
+        kind,
+        title,
+        is_closed,
+        priority,
+        cost,
+        elapsed_time_sec,
+        recorded_at,
+        is_deleted
+ FROM task_journal";
+
+/// journal_entry reads a row selected by SELECT_JOURNAL.
+fn journal_entry(row: &rusqlite::Row) -> rusqlite::Result<JournalEntry> {
+    let task_id = ID::new(row.get(1)?);
+    let kind: String = row.get(2)?;
+    let (change, before) = match kind.as_str() {
+        ADDED => (JournalChange::Added, None),
+        _ => (
+            JournalChange::Updated,
+            Some(Task::from_repository(
+                task_id,
+                row.get(3)?,
+                row.get(4)?,
+                row.get::<_, Option<bool>>(9)?.unwrap_or(false),
+                Priority::new(row.get(5)?),
+                Cost::new(row.get(6)?),
+                Duration::from_secs(row.get(7)?),
+                // NOTE: the journal keeps no timestamps, and undoing updates updated_at anyway.
+                String::new(),
+                String::new(),
+            )),
+        ),
+    };
+    Ok(JournalEntry {
+        task_id,
+        change,
+        before,
+        recorded_at: row.get(8)?,
+    })
+}
+
+/// task_from_row reads a row selecting all columns of tasks in the order of the table.
+fn task_from_row(row: &rusqlite::Row) -> rusqlite::Result<Task> {
+    Ok(Task::from_repository(
+        ID::new(row.get(0)?),
+        row.get(1)?,
+        row.get(2)?,
+        row.get(8)?,
+        Priority::new(row.get(3)?),
+        Cost::new(row.get(4)?),
+        Duration::from_secs(row.get(5)?),
+        row.get(6)?,
+        row.get(7)?,
+    ))
+}
+
+/// order_by returns the ORDER BY clause of the order.
+/// NOTE: rows come in the order of the storage without it, which VACUUM may change.
+fn order_by(order: TaskOrder) -> &'static str {
+    match order {
+        TaskOrder::Id => "ORDER BY id ASC",
+        TaskOrder::Priority => "ORDER BY priority DESC, id ASC",
+        TaskOrder::CreatedAt => "ORDER BY created_at DESC, id ASC",
+        TaskOrder::UpdatedAt => "ORDER BY updated_at DESC, id ASC",
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::domain::task_builder::TaskBuilder;
+
+    /// unstamped clears timestamps, which are the time of the test.
+    fn unstamped(task: Task) -> Task {
+        Task::from_repository(
+            task.id(),
+            task.title().to_owned(),
+            task.is_closed(),
+            task.is_deleted(),
+            task.priority(),
+            task.cost(),
+            task.elapsed_time(),
+            String::new(),
+            String::new(),
+        )
+    }
 
     #[test]
     fn test_not_panic_create_table_twice() {
@@ -214,19 +517,18 @@ mod tests {
         let table = [TestCase {
             name: String::from("nominal"),
             args: Args {
-                task: Task::new(
-                    String::from("hoge"),
-                    Some(Priority::new(2)),
-                    Some(Cost::new(3)),
-                ),
+                task: Task::new(TaskBuilder::new("hoge").priority(2).cost(3)),
             },
             want: Some(Task::from_repository(
                 ID::new(1),
                 String::from("hoge"),
                 false,
+                false,
                 Priority::new(2),
                 Cost::new(3),
                 Duration::from_secs(0),
+                String::new(),
+                String::new(),
             )),
         }];
 
@@ -236,7 +538,7 @@ mod tests {
         for test_case in table {
             let id = task_repository.add(test_case.args.task).unwrap();
             assert_eq!(
-                task_repository.find_by_id(id).unwrap(),
+                task_repository.find_by_id(id).unwrap().map(unstamped),
                 test_case.want,
                 "Failed in the \"{}\".",
                 test_case.name,
@@ -261,28 +563,30 @@ mod tests {
 
         let table = [TestCase {
             name: String::from("normal: close"),
-            given: Task::new(
-                "hoge".to_owned(),
-                Some(Priority::new(2)),
-                Some(Cost::new(3)),
-            ),
+            given: Task::new(TaskBuilder::new("hoge").priority(2).cost(3)),
             args: Args {
                 task: Task::from_repository(
                     ID::new(1),
                     String::from("fuga"),
                     true,
+                    false,
                     Priority::new(3),
                     Cost::new(4),
                     Duration::from_secs(1),
+                    String::new(),
+                    String::new(),
                 ),
             },
             want: Some(Task::from_repository(
                 ID::new(1),
                 String::from("fuga"),
                 true,
+                false,
                 Priority::new(3),
                 Cost::new(4),
                 Duration::from_secs(1),
+                String::new(),
+                String::new(),
             )),
         }];
 
@@ -293,7 +597,7 @@ mod tests {
             let id = task_repository.add(test_case.given).unwrap();
             task_repository.update(test_case.args.task).unwrap();
             assert_eq!(
-                task_repository.find_by_id(id).unwrap(),
+                task_repository.find_by_id(id).unwrap().map(unstamped),
                 test_case.want,
                 "Failed in the \"{}\".",
                 test_case.name,
@@ -301,6 +605,66 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_update_not_stored() {
+        let task_repository = TaskRepository::new(rusqlite::Connection::open_in_memory().unwrap());
+        task_repository.create_table_if_not_exists().unwrap();
+
+        let err = task_repository.update(make_task(1, true)).unwrap_err();
+        assert_eq!(
+            err.downcast_ref::<TaskRepositoryError>(),
+            Some(&TaskRepositoryError::NotFound(1))
+        );
+        assert!(task_repository.history(None).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_undo() {
+        let task_repository = TaskRepository::new(rusqlite::Connection::open_in_memory().unwrap());
+        task_repository.create_table_if_not_exists().unwrap();
+
+        task_repository.add(make_task(1, false)).unwrap();
+        task_repository.add(make_task(2, false)).unwrap();
+        let mut task = task_repository.find_by_id(ID::new(1)).unwrap().unwrap();
+        task.edit_title("edited".to_owned());
+        task_repository.update(task).unwrap();
+        let mut task = task_repository.find_by_id(ID::new(1)).unwrap().unwrap();
+        task.close();
+        task_repository.update(task).unwrap();
+
+        let changes = |id: Option<i64>| -> Vec<(i64, JournalChange)> {
+            task_repository
+                .history(id.map(ID::new))
+                .unwrap()
+                .iter()
+                .map(|e| (e.task_id.get(), e.change))
+                .collect()
+        };
+        assert_eq!(
+            changes(Some(1)),
+            [
+                (1, JournalChange::Updated),
+                (1, JournalChange::Updated),
+                (1, JournalChange::Added)
+            ]
+        );
+        assert_eq!(changes(None).len(), 4);
+
+        let entry = task_repository.undo().unwrap().unwrap();
+        assert_eq!(entry.change, JournalChange::Updated);
+        let task = task_repository.find_by_id(ID::new(1)).unwrap().unwrap();
+        assert_eq!((task.title(), task.is_closed()), ("edited", false));
+        assert_eq!(Some(unstamped(task)), entry.before);
+
+        task_repository.undo().unwrap();
+        let entry = task_repository.undo().unwrap().unwrap();
+        assert_eq!((entry.task_id, entry.before), (ID::new(2), None));
+        assert_eq!(task_repository.find_by_id(ID::new(2)).unwrap(), None);
+        task_repository.undo().unwrap();
+        assert_eq!(task_repository.undo().unwrap(), None);
+        assert!(task_repository.fetch_all(TaskOrder::Id).unwrap().is_empty());
+    }
+
     #[test]
     fn test_find_by_id() {
         #[derive(Debug)]
@@ -324,9 +688,12 @@ mod tests {
                         id,
                         String::from("fuga"),
                         false,
+                        false,
                         Priority::new(10),
                         Cost::new(10),
                         Duration::from_secs(0),
+                        String::new(),
+                        String::new(),
                     ))
                 },
             },
@@ -342,14 +709,15 @@ mod tests {
         let task_repository = TaskRepository::new(rusqlite::Connection::open_in_memory().unwrap());
         task_repository.create_table_if_not_exists().unwrap();
         let inserted_id = task_repository
-            .add(Task::new(String::from("fuga"), None, None))
+            .add(Task::new(TaskBuilder::new("fuga")))
             .unwrap();
 
         for test_case in table {
             assert_eq!(
                 task_repository
                     .find_by_id((test_case.args.make_id)(inserted_id))
-                    .unwrap(),
+                    .unwrap()
+                    .map(unstamped),
                 (test_case.make_want)(inserted_id),
                 "Failed in the \"{}\".",
                 test_case.name,
@@ -362,17 +730,51 @@ mod tests {
             ID::new(seed as i64),
             seed.to_string(),
             is_closed,
+            false,
             Priority::new(seed as i32),
             Cost::new(seed as i32),
             Duration::from_secs(seed),
+            String::new(),
+            String::new(),
         )
     }
 
+    #[test]
+    fn test_find_opening_uses_index() {
+        let mut path = std::env::temp_dir();
+        path.push(format!("taskmr-plan-{}.db", std::process::id()));
+        crate::infra::sqlite::setup::create_database(&path).unwrap();
+
+        let conn = Connection::open(&path).unwrap();
+        let sql = format!(
+            "EXPLAIN QUERY PLAN {} {}",
+            SELECT_OPENING,
+            order_by(TaskOrder::Id)
+        );
+        let plan: Vec<String> = conn
+            .prepare(&sql)
+            .unwrap()
+            .query_map([], |row| row.get(3))
+            .unwrap()
+            .collect::<rusqlite::Result<_>>()
+            .unwrap();
+        assert!(
+            plan.iter()
+                .any(|p| p.contains("USING INDEX tasks_is_closed")),
+            "{:?}",
+            plan
+        );
+
+        drop(conn);
+        std::fs::remove_file(path).unwrap();
+    }
+
     #[test]
     fn test_find_opening() {
         #[derive(Debug)]
         struct TestCase {
             given: Vec<Task>,
+            args: TaskOrder,
             want: Vec<Task>,
             name: String,
         }
@@ -386,15 +788,23 @@ mod tests {
                     make_task(3, true),
                     make_task(4, false),
                 ],
+                args: TaskOrder::Id,
                 want: vec![
                     make_task(1, false),
                     make_task(2, false),
                     make_task(4, false),
                 ],
             },
+            TestCase {
+                name: String::from("normal: by priority"),
+                given: vec![make_task(1, false), make_task(2, true), make_task(3, false)],
+                args: TaskOrder::Priority,
+                want: vec![make_task(3, false), make_task(1, false)],
+            },
             TestCase {
                 name: String::from("normal: empty table"),
                 given: Vec::new(),
+                args: TaskOrder::Id,
                 want: Vec::new(),
             },
             TestCase {
@@ -405,6 +815,7 @@ mod tests {
                     make_task(3, true),
                     make_task(4, true),
                 ],
+                args: TaskOrder::Id,
                 want: Vec::new(),
             },
         ];
@@ -419,7 +830,12 @@ mod tests {
             }
 
             assert_eq!(
-                task_repository.find_opening().unwrap(),
+                task_repository
+                    .find_opening(test_case.args)
+                    .unwrap()
+                    .into_iter()
+                    .map(unstamped)
+                    .collect::<Vec<Task>>(),
                 test_case.want,
                 "Failed in the \"{}\".",
                 test_case.name,
@@ -427,15 +843,65 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_timestamps() {
+        let task_repository = TaskRepository::new(Connection::open_in_memory().unwrap());
+        task_repository.create_table_if_not_exists().unwrap();
+        for seed in 1..=3 {
+            task_repository.add(make_task(seed, false)).unwrap();
+        }
+        task_repository
+            .connection()
+            .run(|conn| {
+                conn.execute_batch(
+                    "UPDATE tasks SET created_at = '2024-05-0' || id || ' 09:00:00',
+                                      updated_at = '2024-05-0' || id || ' 09:00:00'",
+                )
+            })
+            .unwrap();
+
+        let task = task_repository.find_by_id(ID::new(1)).unwrap().unwrap();
+        assert_eq!(task.created_at(), "2024-05-01 09:00:00");
+        task_repository.update(task).unwrap();
+        let task = task_repository.find_by_id(ID::new(1)).unwrap().unwrap();
+        assert_eq!(task.created_at(), "2024-05-01 09:00:00");
+        assert!(task.updated_at() > "2024-05-03 09:00:00");
+
+        let ids = |order| -> Vec<i64> {
+            task_repository
+                .fetch_all(order)
+                .unwrap()
+                .iter()
+                .map(|t| t.id().get())
+                .collect()
+        };
+        assert_eq!(ids(TaskOrder::CreatedAt), [3, 2, 1]);
+        assert_eq!(ids(TaskOrder::UpdatedAt), [1, 3, 2]);
+    }
+
     #[test]
     fn test_fetch_all() {
         #[derive(Debug)]
         struct TestCase {
             given: Vec<Task>,
+            args: TaskOrder,
             want: Vec<Task>,
             name: String,
         }
 
+        let same_priority = |seed: u64| {
+            Task::from_repository(
+                ID::new(seed as i64),
+                seed.to_string(),
+                false,
+                false,
+                Priority::new(5),
+                Cost::new(seed as i32),
+                Duration::from_secs(seed),
+                String::new(),
+                String::new(),
+            )
+        };
         let table = [
             TestCase {
                 name: String::from("nominal"),
@@ -445,6 +911,7 @@ mod tests {
                     make_task(3, true),
                     make_task(4, false),
                 ],
+                args: TaskOrder::Id,
                 want: vec![
                     make_task(1, false),
                     make_task(2, false),
@@ -452,9 +919,22 @@ mod tests {
                     make_task(4, false),
                 ],
             },
+            TestCase {
+                name: String::from("normal: by priority"),
+                given: vec![make_task(1, false), make_task(2, true), make_task(3, false)],
+                args: TaskOrder::Priority,
+                want: vec![make_task(3, false), make_task(2, true), make_task(1, false)],
+            },
+            TestCase {
+                name: String::from("normal: ties by id"),
+                given: vec![same_priority(1), same_priority(2), same_priority(3)],
+                args: TaskOrder::Priority,
+                want: vec![same_priority(1), same_priority(2), same_priority(3)],
+            },
             TestCase {
                 name: String::from("normal: empty table"),
                 given: Vec::new(),
+                args: TaskOrder::Id,
                 want: Vec::new(),
             },
         ];
@@ -469,11 +949,70 @@ mod tests {
             }
 
             assert_eq!(
-                task_repository.fetch_all().unwrap(),
+                task_repository
+                    .fetch_all(test_case.args)
+                    .unwrap()
+                    .into_iter()
+                    .map(unstamped)
+                    .collect::<Vec<Task>>(),
                 test_case.want,
                 "Failed in the \"{}\".",
                 test_case.name,
             );
         }
     }
+
+    #[test]
+    fn test_search() {
+        let conn = rusqlite::Connection::open_in_memory().unwrap();
+        conn.execute(
+            "CREATE TABLE tasks (
+                id INTEGER PRIMARY KEY,
+                title TEXT NOT NULL,
+                is_closed INTEGER DEFAULT 0,
+                priority INTEGER NOT NULL DEFAULT 10,
+                cost INTEGER NOT NULL DEFAULT 10,
+                elapsed_time_sec INTEGER NOT NULL DEFAULT 0,
+                created_at TEXT NOT NULL DEFAULT '',
+                updated_at TEXT NOT NULL DEFAULT '',
+                deleted_at TEXT
+            )",
+            [],
+        )
+        .unwrap();
+        conn.execute(
+            "INSERT INTO tasks (title) VALUES ('fix the flaky test')",
+            [],
+        )
+        .unwrap();
+        let task_repository = TaskRepository::new(conn);
+        // NOTE: the index is filled from the task added before it was created.
+        task_repository.create_table_if_not_exists().unwrap();
+        for title in ["write tests", "fix the docs", "deploy \"fix\" OR"] {
+            task_repository
+                .add(Task::new(TaskBuilder::new(title)))
+                .unwrap();
+        }
+        let mut task = task_repository.find_by_id(ID::new(3)).unwrap().unwrap();
+        task.edit_title("review the docs".to_owned());
+        task_repository.update(task).unwrap();
+        let mut task = task_repository.find_by_id(ID::new(2)).unwrap().unwrap();
+        task.delete();
+        task_repository.update(task).unwrap();
+
+        let ids = |query: &str| -> Vec<i64> {
+            task_repository
+                .search(query)
+                .unwrap()
+                .iter()
+                .map(|t| t.id().get())
+                .collect()
+        };
+        assert_eq!(ids("TES"), [1]);
+        // NOTE: the shorter title is the better match.
+        assert_eq!(ids("fix"), [4, 1]);
+        assert_eq!(ids("the docs"), [3]);
+        assert_eq!(ids("\"fix\" OR"), [4]);
+        assert_eq!(ids(" "), Vec::<i64>::new());
+    }
 }
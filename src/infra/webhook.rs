@@ -0,0 +1,11 @@
+//! # webhook
+//!
+//! webhook posts texts to webhooks of chat services.
+
+use crate::Result;
+
+/// post posts the text as `{"text": ...}`, which Slack and compatible services accept.
+pub fn post(url: &str, text: &str) -> Result<()> {
+    ureq::post(url).send_json(serde_json::json!({ "text": text }))?;
+    Ok(())
+}
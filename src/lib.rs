@@ -5,13 +5,25 @@
 //!
 //! Bellow modules are layers based on Onion Architecture.
 
+#[cfg(not(any(feature = "es", feature = "legacy")))]
+compile_error!("enable at least one of the `es` or `legacy` features");
+
 /// ddd is a usefule module that provide traits about ddd.
+#[cfg(feature = "es")]
 pub mod ddd;
 /// domain is a layer which represent business rules.
 pub mod domain;
+/// error is the error of the library, classified by the layer which failed.
+pub mod error;
 /// infra is a infrastructure layer.
 pub mod infra;
+/// plugin defines contracts for external subcommands.
+#[cfg(feature = "plugin")]
+pub mod plugin;
 /// presentation is a layer which is transrate from/to any UI.
 pub mod presentation;
 /// usecase is a layer which represent use case.
 pub mod usecase;
+
+pub use domain::task_builder::TaskBuilder;
+pub use error::{Error, Result};
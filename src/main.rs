@@ -1,73 +1,248 @@
+use clap::Parser;
 use rusqlite::Connection;
-use std::fs;
-use std::io;
+use std::env;
+use std::io::{self, IsTerminal};
+use std::path::PathBuf;
 use std::process;
 use std::rc::Rc;
 
+#[cfg(feature = "legacy")]
 use taskmr::domain::task::ITaskRepository;
+use taskmr::infra::config::{Config, Engine, CONFIG_FILE_NAME};
+#[cfg(feature = "es")]
+use taskmr::infra::sqlite::es_tag_repository::TagCatalogRepository;
+#[cfg(feature = "es")]
 use taskmr::infra::sqlite::es_task_repository::TaskRepository as ESTaskRepository;
+#[cfg(feature = "es")]
+use taskmr::infra::sqlite::event_chain::EventChain;
+use taskmr::infra::sqlite::metrics::RepositoryMetrics;
+use taskmr::infra::sqlite::schema::SchemaInspector;
+use taskmr::infra::sqlite::setup;
+#[cfg(feature = "es")]
+use taskmr::infra::sqlite::sprint_repository::SprintRepository;
+#[cfg(feature = "legacy")]
 use taskmr::infra::sqlite::task_repository::TaskRepository;
-use taskmr::presentation::command::cli::Cli;
+use taskmr::presentation::command::cli::{Cli, Command};
+use taskmr::presentation::command::init::Wizard;
+use taskmr::presentation::printer::json::JsonPrinter;
 use taskmr::presentation::printer::table::TablePrinter;
+#[cfg(feature = "legacy")]
 use taskmr::usecase::add_task_usecase::AddTaskUseCase;
+#[cfg(feature = "legacy")]
 use taskmr::usecase::close_task_usecase::CloseTaskUseCase;
+#[cfg(feature = "legacy")]
+use taskmr::usecase::delete_task_usecase::{ArchiveTaskUseCase, DeleteTaskUseCase};
+#[cfg(feature = "legacy")]
 use taskmr::usecase::edit_task_usecase::EditTaskUseCase;
+#[cfg(feature = "legacy")]
 use taskmr::usecase::list_task_usecase::ListTaskUseCase;
+#[cfg(feature = "legacy")]
+use taskmr::usecase::search_task_usecase::SearchTaskUseCase;
+#[cfg(feature = "legacy")]
+use taskmr::usecase::undo_usecase::{HistoryUseCase, UndoUseCase};
+use tracing_subscriber::filter::LevelFilter;
+use tracing_subscriber::EnvFilter;
 
+/// DEMO_DATABASE is the URI of the in-memory database of `taskmr demo`, shared by its connections.
+const DEMO_DATABASE: &str = "file:taskmr-demo?mode=memory&cache=shared";
+
+// NOTE: errors are converted into anyhow::Error to print them with their causes by `{:#}`.
 fn main() {
-    let mut db_file_path = dirs::config_dir().unwrap_or_else(|| {
+    // NOTE: logs such as retries on a busy database are printed to stderr only if TASKMR_LOG is
+    // set, such as `TASKMR_LOG=debug`.
+    tracing_subscriber::fmt()
+        .with_env_filter(
+            EnvFilter::builder()
+                .with_default_directive(LevelFilter::OFF.into())
+                .with_env_var("TASKMR_LOG")
+                .from_env_lossy(),
+        )
+        .with_writer(io::stderr)
+        .with_ansi(io::stderr().is_terminal())
+        .init();
+
+    let args = Command::parse();
+
+    let mut config_dir = dirs::config_dir().unwrap_or_else(|| {
         eprintln!("Couldn't find out config directory.");
         process::exit(1)
     });
-    db_file_path.push("taskmr");
-    fs::create_dir_all(&db_file_path).unwrap_or_else(|err| {
-        eprintln!(
-            "Couldn't create taskmr directory in your config directory: {}",
-            err
-        );
-        process::exit(1)
-    });
-    db_file_path.push("taskmr.db");
+    config_dir.push("taskmr");
 
-    let task_repository =
-        TaskRepository::new(Connection::open(&db_file_path).unwrap_or_else(|err| {
-            eprintln!("Couldn't connect your task database: {}", err);
+    if let Some(options) = args.init_options() {
+        Wizard::new(io::stdin().lock(), io::stdout())
+            .run(&config_dir, options)
+            .unwrap_or_else(|err| {
+                eprintln!("Failed to set up taskmr: {:#}", anyhow::Error::from(err));
+                process::exit(1)
+            });
+        return;
+    }
+
+    // NOTE: the demo runs on a shared in-memory database, which lives while a connection is open,
+    // with the default config and a temporary config directory, so that nothing of the user is
+    // read or written.
+    let demo = args.is_demo();
+    let mut _demo_holder = None;
+    if demo {
+        config_dir = env::temp_dir().join(format!("taskmr-demo-{}", process::id()));
+        if let Err(err) = std::fs::create_dir_all(&config_dir) {
+            eprintln!("Couldn't create {}: {}", config_dir.display(), err);
+            process::exit(1)
+        }
+        _demo_holder = Some(Connection::open(DEMO_DATABASE).unwrap_or_else(|err| {
+            eprintln!("Couldn't open the demo database: {}", err);
             process::exit(1)
         }));
+    }
 
-    task_repository
-        .create_table_if_not_exists()
-        .unwrap_or_else(|err| {
-            eprintln!("Failed to create tables on your database: {}", err);
-            process::exit(1)
-        });
+    let config = if demo {
+        Config {
+            engine: Some(Engine::Es),
+            ..Default::default()
+        }
+    } else {
+        Config::load(&config_dir.join(CONFIG_FILE_NAME))
+            .and_then(|config| match env::current_dir() {
+                Ok(cwd) => config.with_scope_from(&cwd),
+                Err(_) => Ok(config),
+            })
+            .unwrap_or_else(|err| {
+                eprintln!("Couldn't load your config: {:#}", anyhow::Error::from(err));
+                process::exit(1)
+            })
+    };
+    let db_file_path = if demo {
+        PathBuf::from(DEMO_DATABASE)
+    } else {
+        config.database_path(&config_dir)
+    };
 
-    let es_task_repository =
-        ESTaskRepository::new(Connection::open(&db_file_path).unwrap_or_else(|err| {
+    // NOTE: only `taskmr init` creates the database, so that a mistyped `database` in the config
+    // is reported instead of silently starting an empty database.
+    let has_database = demo || db_file_path.is_file();
+    if !has_database && args.uses_database() {
+        eprintln!("No task database found at {}.", db_file_path.display());
+        eprintln!("Run `taskmr init` to set up taskmr.");
+        process::exit(1)
+    }
+    if has_database && args.sets_up_database() {
+        // NOTE: the schema is checked before any DDL, so that a drifted table is reported as is
+        // instead of failing in the middle of creating tables and indexes.
+        let conn = Connection::open(&db_file_path).unwrap_or_else(|err| {
             eprintln!("Couldn't connect your task database: {}", err);
             process::exit(1)
-        }));
-
-    es_task_repository
-        .create_table_if_not_exists()
-        .unwrap_or_else(|err| {
-            eprintln!("Failed to create tables on your database: {}", err);
+        });
+        SchemaInspector::new(conn).ensure().unwrap_or_else(|err| {
+            eprintln!("Your task database cannot be used: {}", err);
+            process::exit(1)
+        });
+        // NOTE: tables added by newer versions are created on existing databases.
+        setup::create_database(&db_file_path).unwrap_or_else(|err| {
+            eprintln!(
+                "Failed to create tables on your database: {:#}",
+                anyhow::Error::from(err)
+            );
             process::exit(1)
         });
+    }
+    let connect = || {
+        let conn = if has_database {
+            Connection::open(&db_file_path)
+        } else {
+            Connection::open_in_memory()
+        };
+        conn.unwrap_or_else(|err| {
+            eprintln!("Couldn't connect your task database: {}", err);
+            process::exit(1)
+        })
+    };
+
+    let metrics = Rc::new(RepositoryMetrics::new());
 
+    #[cfg(feature = "legacy")]
+    let task_repository = TaskRepository::new(connect()).with_metrics(Rc::clone(&metrics));
+
+    #[cfg(feature = "es")]
+    let event_chain = if config.event_log.chain {
+        Some(EventChain::new(
+            config.event_log_key(&config_dir).unwrap_or_else(|err| {
+                eprintln!("Couldn't load your config: {:#}", anyhow::Error::from(err));
+                process::exit(1)
+            }),
+        ))
+    } else {
+        None
+    };
+    #[cfg(feature = "es")]
+    let es_task_repository = ESTaskRepository::new(connect())
+        .with_metrics(Rc::clone(&metrics))
+        .with_id_policy(config.ids.policy)
+        .with_event_chain(event_chain);
+
+    #[cfg(feature = "es")]
+    let sprint_repository = SprintRepository::new(connect());
+    #[cfg(feature = "es")]
+    let tag_catalog_repository = TagCatalogRepository::new(connect());
+
+    #[cfg(feature = "legacy")]
     let rc_tr: Rc<dyn ITaskRepository> = Rc::new(task_repository);
-    let add_task_usecase = AddTaskUseCase::new(Rc::clone(&rc_tr));
-    let close_task_usecase = CloseTaskUseCase::new(Rc::clone(&rc_tr));
-    let edit_task_usecase = EditTaskUseCase::new(Rc::clone(&rc_tr));
-    let list_task_usecase = ListTaskUseCase::new(rc_tr);
+    #[cfg(not(feature = "es"))]
+    let es_task_repository = ();
+
+    let record_metrics = config.metrics.record;
+    let demo_dir = config_dir.clone();
     let table_printer = TablePrinter::new(io::stdout());
+    let json_printer = JsonPrinter::new(io::stdout());
     let mut cli = Cli::new(
-        add_task_usecase,
-        close_task_usecase,
-        edit_task_usecase,
-        list_task_usecase,
+        #[cfg(feature = "legacy")]
+        AddTaskUseCase::new(Rc::clone(&rc_tr)),
+        #[cfg(feature = "legacy")]
+        CloseTaskUseCase::new(Rc::clone(&rc_tr)),
+        #[cfg(feature = "legacy")]
+        DeleteTaskUseCase::new(Rc::clone(&rc_tr)),
+        #[cfg(feature = "legacy")]
+        ArchiveTaskUseCase::new(Rc::clone(&rc_tr)),
+        #[cfg(feature = "legacy")]
+        EditTaskUseCase::new(Rc::clone(&rc_tr)),
+        #[cfg(feature = "legacy")]
+        ListTaskUseCase::new(Rc::clone(&rc_tr)),
+        #[cfg(feature = "legacy")]
+        SearchTaskUseCase::new(Rc::clone(&rc_tr)),
+        #[cfg(feature = "legacy")]
+        UndoUseCase::new(Rc::clone(&rc_tr)),
+        #[cfg(feature = "legacy")]
+        HistoryUseCase::new(rc_tr),
         table_printer,
+        json_printer,
         es_task_repository,
+        #[cfg(feature = "es")]
+        sprint_repository,
+        #[cfg(feature = "es")]
+        tag_catalog_repository,
+        db_file_path,
+        config_dir,
+        config,
     );
-    cli.handle();
+    // NOTE: commands are recorded before they run, so that a mistyped one can be looked up too.
+    if has_database && args.is_recorded() {
+        let typed: Vec<String> = env::args_os()
+            .skip(1)
+            .map(|a| a.to_string_lossy().into_owned())
+            .collect();
+        if let Err(err) = cli.record_command(&typed, &args) {
+            eprintln!("Failed to record the command: {}", err);
+        }
+    }
+    cli.handle(args);
+
+    if demo {
+        let _ = std::fs::remove_dir_all(&demo_dir);
+    }
+
+    if has_database && record_metrics {
+        if let Err(err) = cli.flush_metrics(&metrics) {
+            eprintln!("Failed to record repository metrics: {}", err);
+        }
+    }
 }
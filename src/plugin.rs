@@ -0,0 +1,180 @@
+//! # Plugin
+//!
+//! plugin defines stable contracts between taskmr and external subcommands.
+//!
+//! `taskmr foo --bar` executes `taskmr-foo --bar` found on PATH, like git and cargo do.
+//! The plugin receives its context through environment variables.
+//! [`ENV_CONTEXT`] holds the whole [`PluginContext`] as JSON, and the other variables hold
+//! the frequently used fields as plain strings for shell script plugins.
+
+use std::collections::HashMap;
+use std::env;
+use std::path::PathBuf;
+
+use crate::Result;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+/// API_VERSION is bumped when the contract changes incompatibly.
+pub const API_VERSION: u32 = 1;
+
+/// PLUGIN_PREFIX is the prefix of executables recognized as plugins.
+pub const PLUGIN_PREFIX: &str = "taskmr-";
+
+/// ENV_CONTEXT is the environment variable holding PluginContext as JSON.
+pub const ENV_CONTEXT: &str = "TASKMR_PLUGIN_CONTEXT";
+/// ENV_API_VERSION is the environment variable holding API_VERSION.
+pub const ENV_API_VERSION: &str = "TASKMR_PLUGIN_API_VERSION";
+/// ENV_DB_PATH is the environment variable holding the path of the task database.
+pub const ENV_DB_PATH: &str = "TASKMR_DB_PATH";
+/// ENV_BIN is the environment variable holding the path of the taskmr executable.
+pub const ENV_BIN: &str = "TASKMR_BIN";
+
+/// PluginError is raised when a plugin cannot be used.
+#[derive(Error, Debug)]
+pub enum PluginError {
+    #[error("`{0}` is not a taskmr command and no plugin `{PLUGIN_PREFIX}{0}` is found on PATH")]
+    NotFound(String),
+    #[error("the plugin context is not given, `{ENV_CONTEXT}` is not set")]
+    MissingContext,
+    #[error("the plugin API version {0} is not supported, {API_VERSION} is expected")]
+    UnsupportedVersion(u32),
+}
+
+/// PluginContext is what taskmr tells to a plugin.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PluginContext {
+    pub api_version: u32,
+    pub taskmr_version: String,
+    pub db_path: PathBuf,
+    pub taskmr_bin: Option<PathBuf>,
+    /// extra is a free form map for context which is not formalized yet.
+    #[serde(default)]
+    pub extra: HashMap<String, String>,
+}
+
+impl PluginContext {
+    /// construct PluginContext of this taskmr.
+    pub fn new(db_path: PathBuf) -> Self {
+        PluginContext {
+            api_version: API_VERSION,
+            taskmr_version: env!("CARGO_PKG_VERSION").to_owned(),
+            db_path,
+            taskmr_bin: env::current_exe().ok(),
+            extra: HashMap::new(),
+        }
+    }
+
+    /// to_env returns pairs of environment variables passed to a plugin.
+    pub fn to_env(&self) -> Result<Vec<(&'static str, String)>> {
+        let mut vars = vec![
+            (ENV_CONTEXT, serde_json::to_string(self)?),
+            (ENV_API_VERSION, self.api_version.to_string()),
+            (ENV_DB_PATH, self.db_path.display().to_string()),
+        ];
+
+        if let Some(bin) = &self.taskmr_bin {
+            vars.push((ENV_BIN, bin.display().to_string()));
+        }
+
+        Ok(vars)
+    }
+
+    /// from_env reads PluginContext in a plugin written in Rust.
+    pub fn from_env() -> Result<Self> {
+        let json = env::var(ENV_CONTEXT).map_err(|_| PluginError::MissingContext)?;
+        Self::from_json(&json)
+    }
+
+    /// from_json parses PluginContext and checks its API version.
+    pub fn from_json(json: &str) -> Result<Self> {
+        let context: PluginContext = serde_json::from_str(json)?;
+        if context.api_version != API_VERSION {
+            return Err(PluginError::UnsupportedVersion(context.api_version).into());
+        }
+
+        Ok(context)
+    }
+}
+
+/// find_plugin searches `taskmr-<name>` in the directories of PATH.
+pub fn find_plugin(name: &str) -> Option<PathBuf> {
+    let paths = env::var_os("PATH")?;
+    find_plugin_in(name, env::split_paths(&paths))
+}
+
+/// find_plugin_in searches `taskmr-<name>` in the given directories.
+pub fn find_plugin_in<I: IntoIterator<Item = PathBuf>>(name: &str, dirs: I) -> Option<PathBuf> {
+    let file_name = format!("{}{}{}", PLUGIN_PREFIX, name, env::consts::EXE_SUFFIX);
+    dirs.into_iter()
+        .map(|dir| dir.join(&file_name))
+        .find(|candidate| candidate.is_file())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_context_round_trip() {
+        let mut context = PluginContext::new(PathBuf::from("/tmp/taskmr.db"));
+        context.extra.insert("engine".to_owned(), "es".to_owned());
+
+        let vars = context.to_env().unwrap();
+        let json = &vars.iter().find(|(k, _)| *k == ENV_CONTEXT).unwrap().1;
+        assert_eq!(PluginContext::from_json(json).unwrap(), context);
+
+        let db_path = &vars.iter().find(|(k, _)| *k == ENV_DB_PATH).unwrap().1;
+        assert_eq!(db_path, "/tmp/taskmr.db");
+    }
+
+    #[test]
+    fn test_from_json() {
+        #[derive(Debug)]
+        struct TestCase {
+            args: &'static str,
+            want_error: Option<String>,
+            name: String,
+        }
+
+        let table = [
+            TestCase {
+                name: String::from("normal: without extra"),
+                args: r#"{"api_version":1,"taskmr_version":"0.2.0","db_path":"/db","taskmr_bin":null}"#,
+                want_error: None,
+            },
+            TestCase {
+                name: String::from("abnormal: unsupported version"),
+                args: r#"{"api_version":2,"taskmr_version":"9.0.0","db_path":"/db","taskmr_bin":null}"#,
+                want_error: Some(PluginError::UnsupportedVersion(2).to_string()),
+            },
+        ];
+
+        for test_case in table {
+            let got = PluginContext::from_json(test_case.args);
+            assert_eq!(
+                got.err().map(|e| e.to_string()),
+                test_case.want_error,
+                "Failed in the \"{}\".",
+                test_case.name,
+            );
+        }
+    }
+
+    #[test]
+    fn test_find_plugin_in() {
+        let mut dir = env::temp_dir();
+        dir.push(format!("taskmr-plugin-test-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let plugin = dir.join(format!("taskmr-hello{}", env::consts::EXE_SUFFIX));
+        std::fs::write(&plugin, "").unwrap();
+
+        assert_eq!(
+            find_plugin_in("hello", vec![PathBuf::from("/nonexistent"), dir.clone()]),
+            Some(plugin)
+        );
+        assert_eq!(find_plugin_in("bye", vec![dir.clone()]), None);
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -1,117 +1,1670 @@
+#[cfg(feature = "es")]
+use chrono::{Datelike, Local, NaiveDate, TimeZone};
 use clap::{Parser, Subcommand};
-use std::{io, process};
+use rusqlite::Connection;
+use std::io::Write;
+#[cfg(feature = "es")]
+use std::path::Path;
+use std::path::PathBuf;
+#[cfg(feature = "es")]
+use std::rc::Rc;
+use std::{env, fs, io, process};
 
-use crate::domain::es_task::{IESTaskRepository, IESTaskRepositoryComponent, SequentialID};
-use crate::presentation::printer::table::TablePrinter;
+#[cfg(feature = "es")]
+use crate::domain::es_tag::ITagCatalogRepositoryComponent;
+#[cfg(feature = "es")]
+use crate::domain::es_task::{
+    DueDate, Energy, HabitPeriod, HabitTarget, IESTaskRepository, IESTaskRepositoryComponent,
+    Priority, ScoreWeights, SearchField, SequentialID, Tag,
+};
+#[cfg(feature = "es")]
+use crate::domain::scoring::{
+    DeadlineAware, PriorityPerCost, ScoringStrategy, WeightedShortestJobFirst,
+};
+#[cfg(feature = "es")]
+use crate::domain::sprint::{ISprintRepositoryComponent, SprintLength};
+#[cfg(all(feature = "es", feature = "legacy"))]
+use crate::domain::task::ITaskRepository;
+#[cfg(feature = "legacy")]
+use crate::domain::task::TaskOrder;
+use crate::infra::bundle::{Bundle, BundleFormat};
+#[cfg(feature = "es")]
+use crate::infra::config::{self, QuotaPeriod, ScoreConfig, ScoringStrategyKind};
+use crate::infra::config::{Config, Engine, FirstLetter, LintLevel, CONFIG_FILE_NAME};
+#[cfg(feature = "scripting")]
+use crate::infra::script::{ScriptEngine, ScriptTask};
+use crate::infra::selection::SelectionStore;
+#[cfg(feature = "es")]
+use crate::infra::sqlite::anonymize::Anonymizer;
+use crate::infra::sqlite::command_history::CommandHistoryStore;
+#[cfg(feature = "es")]
+use crate::infra::sqlite::es_tag_repository::TagCatalogRepository;
+#[cfg(feature = "es")]
+use crate::infra::sqlite::es_task_repository::TaskRepository as ESTaskRepository;
+#[cfg(feature = "es")]
+use crate::infra::sqlite::event_chain;
+use crate::infra::sqlite::metrics::{render_prometheus, MetricsStore, RepositoryMetrics};
+use crate::infra::sqlite::schedule::{self, Delivery, Period, ScheduleStore};
+use crate::infra::sqlite::schema::SchemaInspector;
+#[cfg(feature = "es")]
+use crate::infra::sqlite::snapshot;
+#[cfg(feature = "es")]
+use crate::infra::sqlite::sprint_repository::SprintRepository;
+#[cfg(all(feature = "es", feature = "legacy"))]
+use crate::infra::sqlite::task_repository::TaskRepository;
+#[cfg(feature = "webhook")]
+use crate::infra::webhook;
+#[cfg(feature = "plugin")]
+use crate::plugin::{self, PluginContext, PluginError};
+use crate::presentation::command::command_line::{quote_args, redact};
+use crate::presentation::command::git;
+#[cfg(feature = "es")]
+use crate::presentation::command::import;
+use crate::presentation::command::init::InitOptions;
+#[cfg(feature = "es")]
+use crate::presentation::command::quick_add::QuickAdd;
+use crate::presentation::command::span::Span;
+use crate::presentation::command::task_ref::TaskRef;
+use crate::presentation::command::title_pattern::TitlePattern;
+#[cfg(feature = "es")]
+use crate::presentation::context;
+#[cfg(feature = "es")]
+use crate::presentation::printer::chart;
+use crate::presentation::printer::env as env_printer;
+#[cfg(feature = "es")]
+use crate::presentation::printer::export::{ExportFormat, Exporter};
+#[cfg(feature = "pdf")]
+use crate::presentation::printer::export::{PdfLayout, PdfOptions};
+use crate::presentation::printer::json::JsonPrinter;
+#[cfg(feature = "es")]
+use crate::presentation::printer::report::{self, ReportFormat};
+#[cfg(feature = "es")]
+use crate::presentation::printer::share;
+use crate::presentation::printer::style::{self, TagStyles};
+use crate::presentation::printer::table::{self, TablePrinter};
+use crate::presentation::printer::widget::{self, WidgetData, WidgetFormat};
+use crate::presentation::printer::width;
+use crate::presentation::printer::Printer;
+#[cfg(feature = "legacy")]
 use crate::usecase::add_task_usecase::{AddTaskUseCase, AddTaskUseCaseInput};
+#[cfg(feature = "legacy")]
 use crate::usecase::close_task_usecase::{CloseTaskUseCase, CloseTaskUseCaseInput};
+#[cfg(feature = "legacy")]
+use crate::usecase::delete_task_usecase::{
+    ArchiveTaskUseCase, ArchiveTaskUseCaseInput, DeleteTaskUseCase, DeleteTaskUseCaseInput,
+};
+#[cfg(feature = "legacy")]
 use crate::usecase::edit_task_usecase::{EditTaskUseCase, EditTaskUseCaseInput};
+#[cfg(feature = "es")]
 use crate::usecase::es_add_task_usecase::AddTaskUseCase as ESAddTaskUseCase;
+#[cfg(feature = "es")]
 use crate::usecase::es_add_task_usecase::AddTaskUseCaseComponent;
+#[cfg(feature = "es")]
 use crate::usecase::es_add_task_usecase::AddTaskUseCaseInput as ESAddTaskUseCaseInput;
+#[cfg(feature = "es")]
 use crate::usecase::es_close_task_usecase::CloseTaskUseCase as ESCloseTaskUseCase;
+#[cfg(feature = "es")]
 use crate::usecase::es_close_task_usecase::CloseTaskUseCaseComponent;
+#[cfg(feature = "es")]
 use crate::usecase::es_close_task_usecase::CloseTaskUseCaseInput as ESCloseTaskUseCaseInput;
+#[cfg(feature = "es")]
+use crate::usecase::es_close_task_usecase::{
+    FollowUpUseCase, FollowUpUseCaseComponent, FollowUpUseCaseInput,
+};
+#[cfg(feature = "es")]
+use crate::usecase::es_cycle_time_usecase::{
+    CycleTimeUseCase, CycleTimeUseCaseComponent, CycleTimeUseCaseInput,
+};
+#[cfg(feature = "es")]
+use crate::usecase::es_delete_task_usecase::DeleteTaskUseCase as ESDeleteTaskUseCase;
+#[cfg(feature = "es")]
+use crate::usecase::es_delete_task_usecase::{
+    ArchiveUseCase, ArchiveUseCaseComponent, ArchiveUseCaseInput, DeleteTaskUseCaseComponent,
+    DeleteTaskUseCaseInput as ESDeleteTaskUseCaseInput,
+};
+#[cfg(feature = "es")]
+use crate::usecase::es_demo_usecase::{
+    SeedDemoUseCase, SeedDemoUseCaseComponent, SeedDemoUseCaseInput,
+};
+#[cfg(feature = "es")]
 use crate::usecase::es_edit_task_usecase::EditTaskUseCase as ESEditTaskUseCase;
+#[cfg(feature = "es")]
 use crate::usecase::es_edit_task_usecase::EditTaskUseCaseComponent;
+#[cfg(feature = "es")]
 use crate::usecase::es_edit_task_usecase::EditTaskUseCaseInput as ESEditTaskUseCaseInput;
+#[cfg(feature = "es")]
+use crate::usecase::es_forecast_usecase::{
+    ForecastUseCase, ForecastUseCaseComponent, ForecastUseCaseInput,
+};
+#[cfg(feature = "devtools")]
+use crate::usecase::es_generate_usecase::{
+    GenerateUseCase, GenerateUseCaseComponent, GenerateUseCaseInput,
+};
+#[cfg(feature = "es")]
+use crate::usecase::es_habit_usecase::{
+    CheckInUseCase, CheckInUseCaseComponent, CheckInUseCaseInput,
+};
+#[cfg(feature = "es")]
+use crate::usecase::es_import_usecase::{
+    DuplicateOfDTO, ImportUseCase, ImportUseCaseComponent, ImportUseCaseInput,
+};
+#[cfg(feature = "es")]
+use crate::usecase::es_inbox_usecase::{
+    CaptureUseCase, CaptureUseCaseComponent, CaptureUseCaseInput, ListInboxUseCase,
+    ListInboxUseCaseComponent, TriageAction, TriageUseCase, TriageUseCaseComponent,
+    TriageUseCaseInput,
+};
+#[cfg(feature = "es")]
+use crate::usecase::es_list_task_usecase;
+#[cfg(feature = "es")]
 use crate::usecase::es_list_task_usecase::ListTaskUseCase as ESListTaskUseCase;
+#[cfg(feature = "es")]
 use crate::usecase::es_list_task_usecase::ListTaskUseCaseComponent;
+#[cfg(feature = "es")]
 use crate::usecase::es_list_task_usecase::ListTaskUseCaseInput as ESListTaskUseCaseInput;
-use crate::usecase::list_task_usecase::{ListTaskUseCase, ListTaskUseCaseInput};
+#[cfg(feature = "es")]
+use crate::usecase::es_lock_task_usecase::{
+    LockTaskUseCase, LockTaskUseCaseComponent, LockTaskUseCaseInput,
+};
+#[cfg(feature = "es")]
+use crate::usecase::es_migrate_legacy_usecase::MigrateLegacyUseCaseComponent;
+#[cfg(all(feature = "es", feature = "legacy"))]
+use crate::usecase::es_migrate_legacy_usecase::{
+    LegacyTaskDTO, MigrateLegacyUseCase, MigrateLegacyUseCaseInput,
+};
+#[cfg(feature = "es")]
+use crate::usecase::es_next_usecase::{
+    NextTaskUseCase, NextTaskUseCaseComponent, NextTaskUseCaseInput,
+};
+#[cfg(feature = "es")]
+use crate::usecase::es_review_usecase::{
+    MonthReviewUseCase, MonthReviewUseCaseComponent, MonthReviewUseCaseInput,
+};
+#[cfg(feature = "es")]
+use crate::usecase::es_rules_usecase::{
+    ApplyRulesUseCase, ApplyRulesUseCaseComponent, ApplyRulesUseCaseInput, Rule, Rules,
+};
+#[cfg(feature = "es")]
+use crate::usecase::es_search_task_usecase::{
+    SearchTaskUseCase as ESSearchTaskUseCase, SearchTaskUseCaseComponent,
+    SearchTaskUseCaseInput as ESSearchTaskUseCaseInput,
+};
+#[cfg(feature = "es")]
+use crate::usecase::es_show_task_usecase::{
+    ShowTaskUseCase, ShowTaskUseCaseComponent, ShowTaskUseCaseInput,
+};
+#[cfg(feature = "es")]
+use crate::usecase::es_simulate_usecase::{
+    SimulateUseCase, SimulateUseCaseComponent, SimulateUseCaseInput,
+};
+#[cfg(feature = "es")]
+use crate::usecase::es_someday_usecase::{
+    PromoteUseCase, PromoteUseCaseComponent, PromoteUseCaseInput,
+};
+#[cfg(feature = "es")]
+use crate::usecase::es_sprint_usecase::{
+    BurndownSprintUseCase, BurndownSprintUseCaseComponent, BurndownSprintUseCaseInput,
+    CloseSprintUseCase, CloseSprintUseCaseComponent, CloseSprintUseCaseInput, CreateSprintUseCase,
+    CreateSprintUseCaseComponent, CreateSprintUseCaseInput, ShowSprintUseCase,
+    ShowSprintUseCaseComponent, ShowSprintUseCaseInput,
+};
+#[cfg(feature = "es")]
+use crate::usecase::es_tag_usecase::{
+    ListTagsUseCase, ListTagsUseCaseComponent, RenameTagUseCase, RenameTagUseCaseComponent,
+    RenameTagUseCaseInput, RetagUseCase, RetagUseCaseComponent, RetagUseCaseInput, TagTaskUseCase,
+    TagTaskUseCaseComponent, TagTaskUseCaseInput,
+};
+#[cfg(feature = "es")]
+use crate::usecase::es_tidy_usecase::{
+    TidyFinding, TidyUseCase, TidyUseCaseComponent, TidyUseCaseInput,
+};
+#[cfg(feature = "es")]
+use crate::usecase::es_time_usecase::{
+    CompletePomodoroUseCase, CompletePomodoroUseCaseComponent, CompletePomodoroUseCaseInput,
+    LogTimeUseCase, LogTimeUseCaseComponent, LogTimeUseCaseInput, Quota, QuotaUsageDTO,
+    QuotaUsageUseCase, QuotaUsageUseCaseComponent, QuotaUsageUseCaseInput, StartTimerUseCase,
+    StartTimerUseCaseComponent, StartTimerUseCaseInput, StopTimerUseCase,
+    StopTimerUseCaseComponent, StopTimerUseCaseInput, TimeByTagUseCase, TimeByTagUseCaseComponent,
+    TimeByTagUseCaseInput,
+};
+#[cfg(feature = "es")]
+use crate::usecase::es_waiting_usecase::{
+    NudgeUseCase, NudgeUseCaseComponent, NudgeUseCaseInput, WaitOnUseCase, WaitOnUseCaseComponent,
+    WaitOnUseCaseInput,
+};
+use crate::usecase::lint::{LetterCase, LintRules};
+use crate::usecase::list_summary::ListSummary;
+#[cfg(feature = "legacy")]
+use crate::usecase::list_task_usecase::{self, ListTaskUseCase, ListTaskUseCaseInput};
+use crate::usecase::outstanding::Outstanding;
+use crate::usecase::pick::{Candidate, Pick};
+#[cfg(feature = "legacy")]
+use crate::usecase::search_task_usecase::{SearchTaskUseCase, SearchTaskUseCaseInput};
+#[cfg(feature = "legacy")]
+use crate::usecase::undo_usecase::{HistoryUseCase, HistoryUseCaseInput, UndoUseCase};
 
 /// Task ManageR.
 #[derive(Parser)]
-struct Command {
+pub struct Command {
+    /// Storage which add, close, edit and list use. Defaults to `engine` of the config.
+    #[clap(long, global = true, value_enum)]
+    engine: Option<EngineArg>,
+    /// Format of results of list, show and commands changing tasks, given before the subcommand.
+    /// `json` prints them in a line of JSON for scripts.
+    #[clap(long, value_enum, default_value_t = OutputFormatArg::Table)]
+    format: OutputFormatArg,
     #[clap(subcommand)]
     command: SubCommands,
 }
 
+/// AddArgs is arguments of `add`.
+#[derive(clap::Args)]
+struct AddArgs {
+    /// Title of a task.
+    title: String,
+    /// Description of a task, the detail which may span lines. Only the es engine supports it.
+    #[cfg(feature = "es")]
+    #[clap(long)]
+    description: Option<String>,
+    /// Priority of a task, which is its importance.
+    #[clap(short, long, alias = "importance")]
+    priority: Option<i32>,
+    /// Urgency of a task. Only the es engine supports it.
+    #[cfg(feature = "es")]
+    #[clap(short, long)]
+    urgency: Option<i32>,
+    /// Cost of a task.
+    #[clap(short, long)]
+    cost: Option<i32>,
+    /// Tags of a task. This option can be repeated. Only the es engine supports it.
+    #[cfg(feature = "es")]
+    #[clap(long = "tag")]
+    tags: Vec<String>,
+    /// Don't tag the task with tags pinned by `.taskmr.toml` nor detected by `auto_context`.
+    #[cfg(feature = "es")]
+    #[clap(long)]
+    no_context: bool,
+    /// Hide the task from list, export and reports unless --show-private is given.
+    /// Only the es engine supports it.
+    #[cfg(feature = "es")]
+    #[clap(long)]
+    private: bool,
+    /// Energy which the task takes, kept as the tag `energy:<energy>`. Only the es engine supports it.
+    #[cfg(feature = "es")]
+    #[clap(long, value_enum)]
+    energy: Option<EnergyArg>,
+    /// Kind of a task. A habit is never closed but checked in toward `--target`.
+    /// Only the es engine supports habits.
+    #[cfg(feature = "es")]
+    #[clap(long, value_enum, default_value = "task")]
+    kind: TaskKindArg,
+    /// Check-ins a habit aims at in a period such as `5/week`. The period is day, week or month.
+    #[cfg(feature = "es")]
+    #[clap(long, required_if_eq("kind", "habit"))]
+    target: Option<HabitTarget>,
+    /// Day by which the task should be closed in `YYYY-MM-DD`. Only the es engine supports it.
+    #[cfg(feature = "es")]
+    #[clap(long)]
+    due: Option<DueDate>,
+    /// Defer the task to someday, out of list, next and plans until it is promoted.
+    /// Only the es engine supports it.
+    #[cfg(feature = "es")]
+    #[clap(long)]
+    someday: bool,
+    /// Add the task as a subtask of the open task of the id, or a row number such as `%2`.
+    /// Only the es engine supports it.
+    #[cfg(feature = "es")]
+    #[clap(long, value_name = "ID")]
+    parent: Option<TaskRef>,
+    /// Print the added task as `show` does, with defaults given to it. Only the es engine supports it.
+    #[cfg(feature = "es")]
+    #[clap(long)]
+    echo: bool,
+}
+
+/// TaskKindArg is a kind given to `add --kind`.
+#[cfg(feature = "es")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+enum TaskKindArg {
+    Task,
+    Habit,
+}
+
+/// EnergyArg is an energy given to `--energy`.
+#[cfg(feature = "es")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+enum EnergyArg {
+    /// Routine work done with little focus.
+    Shallow,
+    /// Focused work which needs an uninterrupted stretch.
+    Deep,
+}
+
+#[cfg(feature = "es")]
+impl From<EnergyArg> for Energy {
+    fn from(arg: EnergyArg) -> Self {
+        match arg {
+            EnergyArg::Shallow => Energy::Shallow,
+            EnergyArg::Deep => Energy::Deep,
+        }
+    }
+}
+
+/// CloseArgs is arguments of `close`.
+#[derive(clap::Args)]
+struct CloseArgs {
+    /// ids of the tasks, or row numbers such as `%2` of the last numbered list.
+    ids: Vec<TaskRef>,
+    /// Unlock locked tasks to close them. Only the es engine locks tasks.
+    #[cfg(feature = "es")]
+    #[clap(long)]
+    unlock: bool,
+    /// Add a task with the title following up the closed one. Only the es engine supports it.
+    #[cfg(feature = "es")]
+    #[clap(long, value_name = "TITLE", conflicts_with = "cascade")]
+    follow_up: Option<String>,
+    /// Close open subtasks together, otherwise a task with them is not closed.
+    /// Only the es engine supports it.
+    #[cfg(feature = "es")]
+    #[clap(long)]
+    cascade: bool,
+}
+
+/// DeleteArgs is arguments of `delete`.
+#[derive(clap::Args)]
+struct DeleteArgs {
+    /// ids of the tasks, or row numbers such as `%2` of the last numbered list.
+    ids: Vec<TaskRef>,
+}
+
+/// ArchiveArgs is arguments of `archive`.
+#[derive(clap::Args)]
+struct ArchiveArgs {
+    /// Archive tasks closed for longer than the span such as `90d`.
+    #[clap(long, default_value = "90d")]
+    older_than: Span,
+}
+
+/// EditArgs is arguments of `edit`.
+#[derive(clap::Args)]
+struct EditArgs {
+    /// id of the task, or a row number such as `%2` of the last numbered list.
+    id: TaskRef,
+    /// Title of the task.
+    #[clap(short, long)]
+    title: Option<String>,
+    /// Description of the task, the detail which may span lines. Only the es engine supports it.
+    #[cfg(feature = "es")]
+    #[clap(long)]
+    description: Option<String>,
+    /// Clear the description. Only the es engine supports it.
+    #[cfg(feature = "es")]
+    #[clap(long, conflicts_with = "description")]
+    no_description: bool,
+    /// Priority of the task, which is its importance.
+    #[clap(short, long, alias = "importance")]
+    priority: Option<i32>,
+    /// Urgency of the task. Only the es engine supports it.
+    #[cfg(feature = "es")]
+    #[clap(short, long)]
+    urgency: Option<i32>,
+    /// Cost of the task.
+    #[clap(short, long)]
+    cost: Option<i32>,
+    /// Unlock the task if it is locked. Only the es engine locks tasks.
+    #[cfg(feature = "es")]
+    #[clap(long)]
+    unlock: bool,
+    /// Make the task private. Only the es engine supports it.
+    #[cfg(feature = "es")]
+    #[clap(long)]
+    private: bool,
+    /// Make the task public. Only the es engine supports it.
+    #[cfg(feature = "es")]
+    #[clap(long, conflicts_with = "private")]
+    public: bool,
+    /// Day by which the task should be closed in `YYYY-MM-DD`. Only the es engine supports it.
+    #[cfg(feature = "es")]
+    #[clap(long)]
+    due: Option<DueDate>,
+    /// Clear the due date. Only the es engine supports it.
+    #[cfg(feature = "es")]
+    #[clap(long, conflicts_with = "due")]
+    no_due: bool,
+    /// Print the edited task as `show` does. Only the es engine supports it.
+    #[cfg(feature = "es")]
+    #[clap(long)]
+    echo: bool,
+}
+
+/// ListArgs is arguments of `list`.
+#[derive(clap::Args)]
+struct ListArgs {
+    /// List only tasks having the tags. This option can be repeated. Only the es engine supports it.
+    #[cfg(feature = "es")]
+    #[clap(long = "tag")]
+    tags: Vec<String>,
+    /// Ignore tags pinned by `.taskmr.toml`.
+    #[cfg(feature = "es")]
+    #[clap(long)]
+    no_context: bool,
+    /// Number rows so that the next command can refer to them as `%1`, `%2` and so on.
+    #[clap(short, long)]
+    number: bool,
+    /// Show a summary footer. `summary` in `[list]` of the config enables it by default.
+    #[clap(long)]
+    summary: bool,
+    /// Truncate titles to fit the table in the width. Defaults to the terminal width.
+    #[clap(long)]
+    max_width: Option<usize>,
+    /// Never truncate titles.
+    #[clap(long, conflicts_with = "max_width")]
+    full: bool,
+    /// Name of a script whose `filter(task)` selects tasks to list.
+    #[cfg(feature = "scripting")]
+    #[clap(long)]
+    filter: Option<String>,
+    /// List only tasks whose titles match the regular expression, such as `(?i)^fix`.
+    #[clap(long)]
+    regex: Option<TitlePattern>,
+    /// Split the list into sections with subtotals. Only the es engine supports it.
+    #[cfg(feature = "es")]
+    #[clap(long, value_enum)]
+    group_by: Option<GroupByArg>,
+    /// List only tasks waiting on someone, grouped by the person.
+    #[cfg(feature = "es")]
+    #[clap(long, conflicts_with = "group_by")]
+    waiting_on: bool,
+    /// List private tasks too.
+    #[cfg(feature = "es")]
+    #[clap(long)]
+    show_private: bool,
+    /// Order of tasks. Only the legacy engine supports it.
+    #[cfg(feature = "legacy")]
+    #[clap(long, value_enum)]
+    sort: Option<SortArg>,
+    /// List only tasks updated on or after the day, such as `2024-05-01`. Only the legacy engine supports it.
+    #[cfg(feature = "legacy")]
+    #[clap(long, value_parser = parse_day)]
+    updated_since: Option<String>,
+    /// List deleted and archived tasks too.
+    #[clap(long)]
+    include_deleted: bool,
+}
+
+/// SearchArgs is arguments of `search`.
+#[derive(clap::Args)]
+struct SearchArgs {
+    /// Words which tasks have, such as `fix test`. A word matches words it begins.
+    #[clap(required = true)]
+    query: Vec<String>,
+    /// Search only titles or only notes, which are descriptions. Both are searched by default.
+    /// Only the es engine has notes.
+    #[clap(long = "in", value_enum)]
+    field: Option<SearchFieldArg>,
+    /// Search closed tasks too.
+    #[clap(long)]
+    closed: bool,
+    /// Search private tasks too.
+    #[cfg(feature = "es")]
+    #[clap(long)]
+    show_private: bool,
+}
+
 /// Subcommands define cli subcommands.
 #[derive(Subcommand)]
 enum SubCommands {
     /// Add a task.
     #[clap(arg_required_else_help = true)]
-    Add {
-        /// Title of a task.
-        title: String,
-        /// Priority of a task.
-        #[clap(short, long)]
-        priority: Option<i32>,
-        /// Cost of a task.
-        #[clap(short, long)]
-        cost: Option<i32>,
+    Add(AddArgs),
+    /// Add a task written in one string, such as `"Fix login bug !80 ~3 #bug @web due:fri"`.
+    /// `!` gives the priority, `~` the cost, `#` a tag, `@` the tag `project:<name>` and `due:`
+    /// the due date. `\` keeps a word starting with them in the title.
+    #[cfg(feature = "es")]
+    #[clap(arg_required_else_help = true)]
+    Q {
+        /// Title with tokens. Its words are joined, so that quoting is optional.
+        #[clap(required = true)]
+        text: Vec<String>,
+        /// Print the added task as `show` does, to check how the string was read.
+        #[clap(long)]
+        echo: bool,
+    },
+    /// Close tasks.
+    #[clap(arg_required_else_help = true)]
+    Close(CloseArgs),
+    /// Edit the task.
+    #[clap(arg_required_else_help = true)]
+    Edit(EditArgs),
+    /// List tasks.
+    List(ListArgs),
+    /// Show the full detail of a task of the es engine, with the events which changed it.
+    #[cfg(feature = "es")]
+    #[clap(arg_required_else_help = true)]
+    Show {
+        /// id of the task, or a row number such as `%2` of the last numbered list.
+        id: TaskRef,
+    },
+    /// Delete tasks. They are hidden, but `list --include-deleted` still shows them.
+    #[clap(arg_required_else_help = true)]
+    Delete(DeleteArgs),
+    /// Archive closed tasks so that they are hidden like deleted ones.
+    Archive(ArchiveArgs),
+    /// Lock tasks so that they can not be edited or closed without --unlock.
+    #[cfg(feature = "es")]
+    #[clap(arg_required_else_help = true)]
+    Lock {
+        /// ids of the tasks, or row numbers such as `%2` of the last numbered list.
+        ids: Vec<TaskRef>,
+    },
+    /// Unlock locked tasks.
+    #[cfg(feature = "es")]
+    #[clap(arg_required_else_help = true)]
+    Unlock {
+        /// ids of the tasks, or row numbers such as `%2` of the last numbered list.
+        ids: Vec<TaskRef>,
+    },
+    /// Log time spent on the task.
+    #[cfg(feature = "es")]
+    #[clap(arg_required_else_help = true)]
+    Log {
+        /// id of the task, or a row number such as `%2` of the last numbered list.
+        id: TaskRef,
+        /// Time spent such as `1h30m` or `45m`.
+        duration: Span,
+    },
+    /// Start the timer of the task. Stopping it logs the time since it started.
+    #[cfg(feature = "es")]
+    #[clap(arg_required_else_help = true)]
+    Start {
+        /// id of the task, or a row number such as `%2` of the last numbered list.
+        id: TaskRef,
+    },
+    /// Stop the timer of the task and log the time since it started.
+    #[cfg(feature = "es")]
+    #[clap(arg_required_else_help = true)]
+    Stop {
+        /// id of the task, or a row number such as `%2` of the last numbered list.
+        id: TaskRef,
+    },
+    /// Work on the task in pomodoros, cycles of work timed by the timer and a break.
+    #[cfg(feature = "es")]
+    #[clap(arg_required_else_help = true)]
+    Pomodoro {
+        /// id of the task, or a row number such as `%2` of the last numbered list.
+        id: TaskRef,
+        /// Minutes of work in a pomodoro.
+        #[clap(long, default_value_t = 25, value_parser = clap::value_parser!(u64).range(1..))]
+        work: u64,
+        /// Minutes of a break after a pomodoro.
+        #[clap(long = "break", default_value_t = 5)]
+        rest: u64,
+        /// The number of pomodoros to run.
+        #[clap(long, default_value_t = 4, value_parser = clap::value_parser!(u32).range(1..))]
+        cycles: u32,
+    },
+    /// Explore taskmr on sample tasks in a throwaway database. Your tasks and config are not touched.
+    #[cfg(feature = "es")]
+    Demo,
+    /// Suggest the open task which fits the energy and the time best, and refer to it as `%1`.
+    #[cfg(feature = "es")]
+    Next {
+        /// Energy available now. Tasks taking more energy are left out.
+        #[clap(long, value_enum)]
+        energy: Option<EnergyArg>,
+        /// Time available now such as `20m`. Tasks which would take longer are left out.
+        #[clap(long)]
+        time: Option<Span>,
+    },
+    /// Project which open tasks will be done by a day on the throughput of past weeks, and which will slip.
+    #[cfg(feature = "es")]
+    #[clap(arg_required_else_help = true)]
+    Simulate {
+        /// Last day of the simulation in `YYYY-MM-DD`.
+        #[clap(long)]
+        until: NaiveDate,
+        /// Capacity added on top of the throughput, such as `2h/day`.
+        #[clap(long, value_parser = parse_capacity)]
+        add_capacity: Option<config::Quota>,
+    },
+    /// Forecast completion dates of open tasks by a Monte Carlo simulation over past cycle times.
+    #[cfg(feature = "es")]
+    #[clap(arg_required_else_help = true)]
+    Forecast {
+        /// ids of the tasks, or row numbers such as `%2` of the last numbered list.
+        #[clap(required_unless_present = "query")]
+        ids: Vec<TaskRef>,
+        /// Forecast open tasks whose titles match the regular expression, as `list --regex`.
+        #[clap(long, conflicts_with = "ids")]
+        query: Option<TitlePattern>,
+        /// Number of trials for each task.
+        #[clap(long, default_value_t = 10000)]
+        trials: usize,
+    },
+    /// Print the task as JSON to hand it off to another device.
+    #[cfg(feature = "es")]
+    #[clap(arg_required_else_help = true)]
+    Share {
+        /// id of the task, or a row number such as `%2` of the last numbered list.
+        id: TaskRef,
+        /// Render the JSON as a QR code in the terminal to scan it with a phone.
+        #[cfg(feature = "qr")]
+        #[clap(long)]
+        qr: bool,
+    },
+    /// Add tasks from a file of JSON Lines, a task of the JSON which `share` prints in each line,
+    /// or of CSV if the file ends with `.csv`. Nothing is added if a row is invalid.
+    #[cfg(feature = "es")]
+    #[clap(arg_required_else_help = true)]
+    Import {
+        /// Path of the file.
+        file: PathBuf,
+        /// Report invalid rows without adding anything.
+        #[clap(long)]
+        validate_only: bool,
+        /// Number of tasks added in a transaction, after which the import can be resumed.
+        /// 0 adds all of them in one.
+        #[clap(long, default_value_t = 500)]
+        chunk_size: usize,
+        /// Continue the interrupted import of the same file after its last added chunk.
+        #[clap(long, conflicts_with = "validate_only")]
+        resume: bool,
+    },
+    /// Check in a habit once, counting toward its target.
+    #[cfg(feature = "es")]
+    #[clap(arg_required_else_help = true)]
+    CheckIn {
+        /// id of the habit, or a row number such as `%2` of the last numbered list.
+        id: TaskRef,
     },
-    /// ESAdd add a task with event sourcing.
+    /// Mark a task as waiting on an external person, such as an answer from a colleague.
+    #[cfg(feature = "es")]
     #[clap(arg_required_else_help = true)]
-    ESAdd {
-        /// Title of a task.
+    WaitingOn {
+        /// id of the task, or a row number such as `%2` of the last numbered list.
+        id: TaskRef,
+        /// Person whom the task waits on.
+        #[clap(required_unless_present = "clear")]
+        person: Option<String>,
+        /// Day to follow up with the person in `YYYY-MM-DD`. The `nudges` report lists it from then.
+        #[clap(long, conflicts_with = "clear")]
+        follow_up: Option<NaiveDate>,
+        /// Stop waiting.
+        #[clap(long, conflicts_with = "person")]
+        clear: bool,
+    },
+    /// Capture a thought into the inbox with only its title, to triage it later.
+    #[cfg(feature = "es")]
+    #[clap(name = "in", arg_required_else_help = true)]
+    In {
+        /// Title of the item.
         title: String,
-        /// Priority of a task.
-        #[clap(short, long)]
-        priority: Option<i32>,
-        /// Cost of a task.
-        #[clap(short, long)]
-        cost: Option<i32>,
     },
-    /// Close tasks.
+    /// Walk through the inbox, keeping, delegating or dropping each item.
+    #[cfg(feature = "es")]
+    TriageInbox,
+    /// Look through tasks deferred to someday.
+    #[cfg(feature = "es")]
+    Someday {
+        #[clap(subcommand)]
+        command: SomedayCommands,
+    },
+    /// Move a task deferred to someday into the active backlog.
+    #[cfg(feature = "es")]
     #[clap(arg_required_else_help = true)]
-    Close {
-        /// ids of the tasks.
-        ids: Vec<i64>,
+    Promote {
+        /// id of the task, or a row number such as `%2` of the last numbered list.
+        id: TaskRef,
     },
-    /// Close tasks.
+    /// Search titles and notes of tasks, the best match first.
     #[clap(arg_required_else_help = true)]
-    ESClose {
-        /// ids of the tasks.
-        ids: Vec<i64>,
+    Search(SearchArgs),
+    /// Run the latest command which changed tasks again, as it was typed.
+    Last {
+        /// Only print the command without running it.
+        #[clap(long)]
+        dry_run: bool,
     },
-    /// Edit the task.
+    /// List invoked commands from the latest, with secrets such as `--url` redacted.
+    HistoryCmd {
+        /// The number of commands listed.
+        #[clap(short = 'n', long, default_value_t = 20)]
+        limit: usize,
+    },
+    /// Undo the latest add, close, edit or delete of the legacy engine.
+    #[cfg(feature = "legacy")]
+    Undo,
+    /// List changes of the legacy engine which `undo` reverts, from the latest.
+    #[cfg(feature = "legacy")]
+    History {
+        /// id of the task, or a row number such as `%2` of the last numbered list.
+        id: Option<TaskRef>,
+    },
+    /// Find likely duplicates, stale and zero-cost tasks, and offer to close them one by one.
+    #[cfg(feature = "es")]
+    Tidy {
+        /// Tasks open for longer than the span such as `90d` are stale.
+        #[clap(long, default_value = "90d")]
+        stale: Span,
+        /// Only print the findings without asking.
+        #[clap(long)]
+        dry_run: bool,
+    },
+    /// Deprecated alias of `--engine es add`.
+    #[cfg(feature = "es")]
+    #[clap(hide = true, arg_required_else_help = true)]
+    ESAdd(AddArgs),
+    /// Deprecated alias of `--engine es close`.
+    #[cfg(feature = "es")]
+    #[clap(hide = true, arg_required_else_help = true)]
+    ESClose(CloseArgs),
+    /// Deprecated alias of `--engine es edit`.
+    #[cfg(feature = "es")]
+    #[clap(hide = true, arg_required_else_help = true)]
+    ESEdit(EditArgs),
+    /// Deprecated alias of `--engine es list`.
+    #[cfg(feature = "es")]
+    #[clap(hide = true)]
+    ESList(ListArgs),
+    /// Show statistics of tasks.
+    Stats {
+        /// Show call counts and latencies of repositories instead.
+        #[clap(long)]
+        internal: bool,
+        /// Print internal metrics in the Prometheus text format.
+        #[clap(long, requires = "internal")]
+        prometheus: bool,
+    },
+    /// Diagnose the database. All checks run if no check is specified.
+    Doctor {
+        /// Report differences between the database schema and the expected one.
+        #[clap(long)]
+        schema: bool,
+        /// Report sequential ids left without events by an interrupted addition.
+        #[cfg(feature = "es")]
+        #[clap(long)]
+        orphans: bool,
+        /// Remove the orphan sequential ids.
+        #[cfg(feature = "es")]
+        #[clap(long)]
+        repair: bool,
+        /// Verify that no two tasks share a sequential id.
+        #[cfg(feature = "es")]
+        #[clap(long)]
+        ids: bool,
+        /// Verify that events linked by `event_log.chain` are neither altered nor removed.
+        #[cfg(feature = "es")]
+        #[clap(long)]
+        verify_chain: bool,
+    },
+    /// Print paths, versions and compiled features to paste into bug reports.
+    Env,
+    /// Print a built-in report, or a custom report defined by `report(tasks)` of the script.
+    Report {
+        /// Name of the report or the script. Available reports are listed if omitted.
+        name: Option<String>,
+        /// Sum only time logged within the span such as `30d`. Used by `time-by-tag`.
+        /// `cycle-time` measures only tasks closed within it.
+        #[cfg(feature = "es")]
+        #[clap(long)]
+        since: Option<Span>,
+        /// Month to review as `YYYY-MM`. Used by `month`. Defaults to this month.
+        #[cfg(feature = "es")]
+        #[clap(long, value_parser = parse_month)]
+        month: Option<NaiveDate>,
+        /// Format of a built-in report.
+        #[cfg(feature = "es")]
+        #[clap(long, value_enum, default_value = "table")]
+        format: ReportFormat,
+    },
+    /// Schedule a report delivered by `taskmr cron`. Schedules are listed without options.
+    ScheduleReport {
+        /// Deliver the report every day. `stats` or the name of a script.
+        #[clap(long, value_name = "REPORT", conflicts_with = "weekly")]
+        daily: Option<String>,
+        /// Deliver the report every week. `stats` or the name of a script.
+        #[clap(long, value_name = "REPORT")]
+        weekly: Option<String>,
+        /// Destination of the report.
+        #[clap(long, value_enum, default_value = "stdout")]
+        to: DeliveryArg,
+        /// File to append the report to.
+        #[clap(long, required_if_eq("to", "file"))]
+        path: Option<PathBuf>,
+        /// URL to post the report to.
+        #[clap(long, required_if_eq("to", "webhook"))]
+        url: Option<String>,
+        /// Remove the schedule of the id instead.
+        #[clap(long, conflicts_with_all = ["daily", "weekly"])]
+        remove: Option<i64>,
+    },
+    /// Deliver scheduled reports which are due. Run it from cron or a systemd timer.
+    Cron,
+    /// Retry delivering reports which `taskmr cron` failed to deliver.
+    FlushOutbox,
+    /// Check titles of open tasks against `[lint]` of the config. It fails if any title breaks a rule.
+    Lint,
+    /// Pick a random open task and focus on it. It is shown by `widget` and referred as `%1`.
+    Random {
+        /// Pick only tasks costing at most this.
+        #[clap(long)]
+        max_cost: Option<i32>,
+        /// Make a task as likely as its priority.
+        #[clap(long)]
+        weighted: bool,
+    },
+    /// Print a compact status segment of the focused task for status bars.
+    Widget {
+        /// Markup of the segment.
+        #[clap(long, value_enum, default_value = "plain")]
+        format: WidgetFormat,
+        /// Maximum number of visible characters.
+        #[clap(long, default_value_t = 40)]
+        max_width: usize,
+    },
+    /// Export all tasks including closed ones.
+    #[cfg(feature = "es")]
+    Export {
+        /// Format of the output.
+        #[clap(long, value_enum)]
+        format: ExportFormat,
+        /// Path of the copy. Required by the sqlite-snapshot format.
+        path: Option<PathBuf>,
+        /// Write into the file instead of the standard output.
+        #[clap(short, long, conflicts_with = "path")]
+        output: Option<PathBuf>,
+        /// Layout of the pdf format. Redirect the output into a file.
+        #[cfg(feature = "pdf")]
+        #[clap(long, value_enum, default_value = "agenda")]
+        layout: PdfLayout,
+        /// Ignore tags pinned by `.taskmr.toml`.
+        #[clap(long)]
+        no_context: bool,
+        /// Export private tasks too.
+        #[clap(long)]
+        show_private: bool,
+        /// Replace titles, tags, names of people and sprints with pseudonyms,
+        /// so that the export can be attached to a bug report.
+        #[clap(long)]
+        anonymize: bool,
+    },
+    /// Set up taskmr by answering a few questions. The global `--engine` is written to the config.
+    Init {
+        /// Accept all defaults without asking.
+        #[clap(long)]
+        defaults: bool,
+        /// Overwrite the existing config file.
+        #[clap(long)]
+        force: bool,
+    },
+    /// Move tasks between the simple table and the event store.
+    #[cfg(all(feature = "es", feature = "legacy"))]
+    Migrate {
+        #[clap(subcommand)]
+        command: MigrateCommands,
+    },
+    /// Move settings between machines.
+    Config {
+        #[clap(subcommand)]
+        command: ConfigCommands,
+    },
+    /// Tie git commits to tasks.
+    Git {
+        #[clap(subcommand)]
+        command: GitCommands,
+    },
+    /// Commit tasks to time-boxed sprints and track them.
+    #[cfg(feature = "es")]
+    Sprint {
+        #[clap(subcommand)]
+        command: SprintCommands,
+    },
+    /// Tag the task, or rename and list tags of es engine tasks.
+    #[cfg(feature = "es")]
+    #[clap(
+        arg_required_else_help = true,
+        args_conflicts_with_subcommands = true,
+        subcommand_negates_reqs = true
+    )]
+    Tag {
+        #[clap(subcommand)]
+        command: Option<TagCommands>,
+        /// id of the task, or a row number such as `%2` of the last numbered list.
+        #[clap(required = true)]
+        id: Option<TaskRef>,
+        /// Tags to add to the task.
+        #[clap(required = true)]
+        tags: Vec<String>,
+    },
+    /// Remove tags from the task.
+    #[cfg(feature = "es")]
     #[clap(arg_required_else_help = true)]
-    Edit {
-        /// id of the task.
-        id: i64,
-        /// Title of the task.
-        #[clap(short, long)]
-        title: Option<String>,
-        /// Priority of the task.
-        #[clap(short, long)]
-        priority: Option<i32>,
-        /// Cost of the task.
-        #[clap(short, long)]
-        cost: Option<i32>,
+    Untag {
+        /// id of the task, or a row number such as `%2` of the last numbered list.
+        id: TaskRef,
+        /// Tags to remove from the task.
+        #[clap(required = true)]
+        tags: Vec<String>,
     },
-    /// Edit the task.
+    /// Move a tag of open tasks to another tag in one transaction. Closed tasks keep it.
+    #[cfg(feature = "es")]
     #[clap(arg_required_else_help = true)]
-    ESEdit {
-        /// id of the task.
-        id: i64,
-        /// Title of the task.
-        #[clap(short, long)]
-        title: Option<String>,
-        /// Priority of the task.
+    Retag {
+        /// Tag to take off.
+        #[clap(long)]
+        from: String,
+        /// Tag to put on instead.
+        #[clap(long)]
+        to: String,
+        /// Retag only tasks whose titles match the regular expression, as `list --regex`.
+        #[clap(long)]
+        query: Option<TitlePattern>,
+    },
+    /// Apply `[[rules]]` of the config to open tasks added before the rules, in one transaction.
+    #[cfg(feature = "es")]
+    ApplyRules,
+    /// Tools for contributors, such as generating large databases.
+    #[cfg(feature = "devtools")]
+    Devtools {
+        #[clap(subcommand)]
+        command: DevtoolsCommands,
+    },
+    /// External runs `taskmr-<name>` on PATH as a plugin.
+    #[cfg(feature = "plugin")]
+    #[clap(external_subcommand)]
+    External(Vec<String>),
+}
+
+/// ConfigCommands define subcommands of `taskmr config`.
+#[derive(Subcommand)]
+enum ConfigCommands {
+    /// Bundle the config and saved filters into one file.
+    Export {
+        /// Format of the bundle.
+        #[clap(long, value_enum, default_value = "toml")]
+        format: BundleFormatArg,
+        /// File to write the bundle to. Defaults to stdout.
         #[clap(short, long)]
-        priority: Option<i32>,
-        /// Cost of the task.
+        output: Option<PathBuf>,
+    },
+    /// Install the config and saved filters from a bundle. `.json` files are read as JSON.
+    #[clap(arg_required_else_help = true)]
+    Import {
+        /// Path of the bundle.
+        file: PathBuf,
+        /// Overwrite the existing config and scripts.
+        #[clap(long)]
+        force: bool,
+    },
+}
+
+/// GitCommands define subcommands of `taskmr git`.
+#[derive(Subcommand)]
+enum GitCommands {
+    /// Install the commit-msg hook closing tasks referred as `closes task#<id>`.
+    InstallHook {
+        /// Overwrite the existing hook which is not installed by taskmr.
+        #[clap(long)]
+        force: bool,
+    },
+    /// Close tasks referred as `closes task#<id>` in the commit message file.
+    #[clap(arg_required_else_help = true)]
+    ApplyMessage {
+        /// Path of the commit message file given to the hook.
+        file: PathBuf,
+    },
+}
+
+/// MigrateCommands define subcommands of `taskmr migrate`.
+#[cfg(all(feature = "es", feature = "legacy"))]
+#[derive(Subcommand)]
+enum MigrateCommands {
+    /// Import every task of the simple table into the event store with its original timestamps.
+    /// Tasks migrated by an earlier run are skipped.
+    LegacyToEs {
+        /// Close the migrated tasks in the simple table, so that they leave `list`.
+        #[clap(long)]
+        close_source: bool,
+    },
+}
+
+/// DevtoolsCommands define subcommands of `taskmr devtools`.
+#[cfg(feature = "devtools")]
+#[derive(Subcommand)]
+enum DevtoolsCommands {
+    /// Fill the event store with synthetic tasks created in the last two years,
+    /// so that performance issues can be reproduced. Use a database made for it.
+    Generate {
+        /// Number of tasks to generate.
+        #[clap(long, default_value_t = 1000)]
+        tasks: usize,
+        /// Number of events of each task, counting the creation with the title.
+        #[clap(long, default_value_t = 20, value_parser = clap::value_parser!(u16).range(2..))]
+        events_per_task: u16,
+        /// Seed of the random content. The same seed generates the same tasks.
+        #[clap(long, default_value_t = 0)]
+        seed: u64,
+    },
+}
+
+/// GENERATE_CHUNK is the number of tasks which `devtools generate` saves in one transaction.
+#[cfg(feature = "devtools")]
+const GENERATE_CHUNK: usize = 10_000;
+
+/// TagCommands define subcommands of `taskmr tag`.
+#[cfg(feature = "es")]
+#[derive(Subcommand)]
+enum TagCommands {
+    /// Rename the tag on every task, merging it into the new name if it is already used.
+    #[clap(arg_required_else_help = true)]
+    Rename {
+        /// Current name of the tag.
+        from: String,
+        /// New name of the tag.
+        to: String,
+    },
+    /// List tags in use. Tags only on private tasks are left out.
+    List {
+        /// Show the numbers of open and all tasks having each tag.
+        #[clap(long)]
+        counts: bool,
+    },
+}
+
+/// SomedayCommands define subcommands of `taskmr someday`.
+#[cfg(feature = "es")]
+#[derive(Subcommand)]
+enum SomedayCommands {
+    /// List open tasks deferred to someday.
+    List {
+        /// Number rows so that `promote` can refer to them as `%1`, `%2` and so on.
         #[clap(short, long)]
-        cost: Option<i32>,
+        number: bool,
     },
-    /// List tasks.
-    List {},
-    /// ESList tasks.
-    ESList {},
+}
+
+/// SprintCommands define subcommands of `taskmr sprint`.
+#[cfg(feature = "es")]
+#[derive(Subcommand)]
+enum SprintCommands {
+    /// Create a sprint committing the tasks with their current costs.
+    #[clap(arg_required_else_help = true)]
+    Create {
+        /// Name of the sprint.
+        name: String,
+        /// Ids of tasks to commit. Row numbers such as `%1` of `list -n` on the es engine are accepted.
+        #[clap(required = true)]
+        ids: Vec<TaskRef>,
+        /// Length of the sprint such as `10d` or `2w`.
+        #[clap(long, default_value = "2w")]
+        length: SprintLength,
+        /// Total cost which the sprint can take.
+        #[clap(long)]
+        capacity: i32,
+        /// First day of the sprint in `YYYY-MM-DD`. Defaults to today.
+        #[clap(long)]
+        start: Option<NaiveDate>,
+        /// Create the sprint even if the committed cost exceeds the capacity.
+        #[clap(long)]
+        force: bool,
+    },
+    /// Report completed and committed cost of the sprint.
+    #[clap(arg_required_else_help = true)]
+    Status {
+        /// Name of the sprint.
+        name: String,
+    },
+    /// Close the sprint and report its result.
+    #[clap(arg_required_else_help = true)]
+    Close {
+        /// Name of the sprint.
+        name: String,
+    },
+    /// Chart the remaining cost of the sprint day by day.
+    #[clap(arg_required_else_help = true)]
+    Burndown {
+        /// Name of the sprint.
+        name: String,
+        /// Width of the longest bar.
+        #[clap(long, default_value_t = 40)]
+        width: usize,
+    },
+}
+
+/// OpenTask is an opening task read from either model.
+struct OpenTask {
+    id: i64,
+    title: String,
+    priority: i32,
+    cost: i32,
+    #[cfg_attr(not(feature = "scripting"), allow(dead_code))]
+    tags: Vec<String>,
+    #[cfg_attr(not(feature = "scripting"), allow(dead_code))]
+    waiting_on: Option<String>,
+    #[cfg_attr(not(feature = "scripting"), allow(dead_code))]
+    nudge: bool,
+    /// is_overdue is whether the task is open past the due date. Legacy tasks have no due dates.
+    is_overdue: bool,
+}
+
+/// EngineArg is a storage given to `--engine`.
+#[derive(Debug, Clone, Copy, clap::ValueEnum)]
+enum EngineArg {
+    /// Event Sourcing.
+    Es,
+    /// The simple table.
+    Legacy,
+}
+
+impl From<EngineArg> for Engine {
+    fn from(arg: EngineArg) -> Self {
+        match arg {
+            EngineArg::Es => Engine::Es,
+            EngineArg::Legacy => Engine::Legacy,
+        }
+    }
+}
+
+/// OutputFormatArg is a format given to `--format` before the subcommand.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+enum OutputFormatArg {
+    /// Tables for people.
+    Table,
+    /// JSON for scripts.
+    Json,
+}
+
+/// EngineHandlers is the dispatch table of subcommands which both engines implement.
+struct EngineHandlers<C> {
+    add: fn(&mut C, &AddArgs),
+    close: fn(&mut C, &CloseArgs),
+    delete: fn(&mut C, &DeleteArgs),
+    archive: fn(&mut C, &ArchiveArgs),
+    edit: fn(&mut C, &EditArgs),
+    list: fn(&mut C, &ListArgs),
+    search: fn(&mut C, &SearchArgs),
+    /// summarize_open summarizes open tasks, which the outstanding advice is made from.
+    summarize_open: fn(&C) -> crate::Result<ListSummary>,
+}
+
+/// GroupByArg is a key given to `--group-by`.
+#[cfg(feature = "es")]
+#[derive(Debug, Clone, Copy, clap::ValueEnum)]
+enum GroupByArg {
+    /// Group by tags. A task having several tags appears in each group.
+    Tag,
+    /// Group into high (>= 20), normal (>= 10) and low priority.
+    PriorityBand,
+    /// Group into quadrants of importance (priority) and urgency ordered by the score.
+    /// `[score]` of the config weighs the dimensions and sets the threshold of high.
+    Matrix,
+}
+
+#[cfg(feature = "es")]
+impl GroupByArg {
+    /// to_group_by converts the argument into GroupBy with the score settings.
+    fn to_group_by(self, score: &ScoreConfig) -> es_list_task_usecase::GroupBy {
+        match self {
+            GroupByArg::Tag => es_list_task_usecase::GroupBy::Tag,
+            GroupByArg::PriorityBand => es_list_task_usecase::GroupBy::PriorityBand,
+            GroupByArg::Matrix => es_list_task_usecase::GroupBy::Matrix {
+                strategy: scoring_strategy(score),
+                threshold: score.threshold,
+            },
+        }
+    }
+}
+
+/// scoring_strategy builds the strategy of the score settings.
+#[cfg(feature = "es")]
+fn scoring_strategy(score: &ScoreConfig) -> Rc<dyn ScoringStrategy> {
+    let weights = ScoreWeights {
+        importance: score.importance,
+        urgency: score.urgency,
+    };
+    match score.strategy {
+        ScoringStrategyKind::Weighted => Rc::new(weights),
+        ScoringStrategyKind::PriorityPerCost => Rc::new(PriorityPerCost),
+        ScoringStrategyKind::Wsjf => Rc::new(WeightedShortestJobFirst),
+        ScoringStrategyKind::Deadline => Rc::new(DeadlineAware {
+            weights,
+            today: Local::now().date_naive(),
+        }),
+    }
+}
+
+/// DeliveryArg is a destination given to `--to`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+enum DeliveryArg {
+    Stdout,
+    /// Append to the file given by `--path`.
+    File,
+    /// Post to the URL given by `--url`. Needs the `webhook` feature.
+    Webhook,
+}
+
+/// BundleFormatArg is a format given to `config export --format`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+enum BundleFormatArg {
+    Toml,
+    Json,
+}
+
+impl From<BundleFormatArg> for BundleFormat {
+    fn from(arg: BundleFormatArg) -> Self {
+        match arg {
+            BundleFormatArg::Toml => BundleFormat::Toml,
+            BundleFormatArg::Json => BundleFormat::Json,
+        }
+    }
+}
+
+/// STATS_REPORT is the built-in report which `stats` prints.
+const STATS_REPORT: &str = "stats";
+
+/// TIME_BY_TAG_REPORT is the built-in report of logged time per tag.
+#[cfg(feature = "es")]
+const TIME_BY_TAG_REPORT: &str = "time-by-tag";
+
+/// MONTH_REPORT is the built-in review of a month for a retrospective.
+#[cfg(feature = "es")]
+const MONTH_REPORT: &str = "month";
+
+/// QUOTAS_REPORT is the built-in report of logged time against quotas of the config.
+#[cfg(feature = "es")]
+const QUOTAS_REPORT: &str = "quotas";
+
+/// NUDGES_REPORT is the built-in report of people to follow up with about waiting tasks.
+#[cfg(feature = "es")]
+const NUDGES_REPORT: &str = "nudges";
+
+/// CYCLE_TIME_REPORT is the built-in report of lead time and cycle time of closed tasks.
+#[cfg(feature = "es")]
+const CYCLE_TIME_REPORT: &str = "cycle-time";
+
+/// SearchFieldArg is a field given to `--in`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+enum SearchFieldArg {
+    /// Titles.
+    Title,
+    /// Notes, which are descriptions of tasks.
+    Notes,
+}
+
+#[cfg(feature = "es")]
+impl From<SearchFieldArg> for SearchField {
+    fn from(arg: SearchFieldArg) -> Self {
+        match arg {
+            SearchFieldArg::Title => SearchField::Title,
+            SearchFieldArg::Notes => SearchField::Description,
+        }
+    }
+}
+
+/// SortArg is an order given to `--sort`.
+#[cfg(feature = "legacy")]
+#[derive(Debug, Clone, Copy, clap::ValueEnum)]
+enum SortArg {
+    /// In the order of ids.
+    Id,
+    /// From the highest priority.
+    Priority,
+    /// From the latest created.
+    Created,
+    /// From the latest updated.
+    Updated,
+}
+
+#[cfg(feature = "legacy")]
+impl From<SortArg> for TaskOrder {
+    fn from(arg: SortArg) -> Self {
+        match arg {
+            SortArg::Id => TaskOrder::Id,
+            SortArg::Priority => TaskOrder::Priority,
+            SortArg::Created => TaskOrder::CreatedAt,
+            SortArg::Updated => TaskOrder::UpdatedAt,
+        }
+    }
+}
+
+/// DEMO_STEPS are commands which `demo` runs on the sample tasks, as typed after `taskmr`.
+#[cfg(feature = "es")]
+const DEMO_STEPS: &[&str] = &[
+    "list",
+    "list --group-by matrix",
+    "list --waiting-on",
+    "someday list",
+    "next --time 30m",
+    "close 2",
+    "list --summary",
+];
+
+/// print_archived prints ids of archived tasks.
+fn print_archived(ids: impl Iterator<Item = i64>) {
+    let ids: Vec<String> = ids.map(|id| format!("`{}`", id)).collect();
+    match ids.len() {
+        0 => println!("No closed task is old enough to archive."),
+        _ => println!("Archive the tasks for ids {}.", ids.join(", ")),
+    }
+}
+
+/// parse_day checks `YYYY-MM-DD` without chrono, which only the es engine depends on.
+#[cfg(feature = "legacy")]
+fn parse_day(s: &str) -> Result<String, String> {
+    let numbers: Vec<Option<u32>> = s
+        .split('-')
+        .map(|p| {
+            p.bytes()
+                .all(|b| b.is_ascii_digit())
+                .then(|| p.parse().ok())?
+        })
+        .collect();
+    let valid = s.len() == 10
+        && matches!(numbers.as_slice(), [Some(_), Some(m), Some(d)]
+            if (1..=12).contains(m) && (1..=31).contains(d));
+    if valid {
+        Ok(s.to_owned())
+    } else {
+        Err(format!("`{}` is not a day such as 2024-05-01", s))
+    }
+}
+
+/// parse_month parses `YYYY-MM` into the first day of the month.
+#[cfg(feature = "es")]
+fn parse_month(s: &str) -> Result<NaiveDate, String> {
+    NaiveDate::parse_from_str(&format!("{}-01", s), "%Y-%m-%d")
+        .map_err(|_| format!("`{}` is not a month such as 2024-05", s))
+}
+
+/// parse_capacity parses time in a period such as `2h/day`.
+#[cfg(feature = "es")]
+fn parse_capacity(s: &str) -> Result<config::Quota, String> {
+    config::Quota::try_from(s.to_owned())
+        .map_err(|_| format!("`{}` is not a capacity such as 2h/day", s))
+}
+
+/// local_to_utc converts `YYYY-MM-DD HH:MM:SS` in local time into UTC.
+#[cfg(all(feature = "es", feature = "legacy"))]
+fn local_to_utc(s: &str) -> Option<chrono::NaiveDateTime> {
+    let local = chrono::NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M:%S").ok()?;
+    Local
+        .from_local_datetime(&local)
+        .earliest()
+        .map(|t| t.naive_utc())
+}
+
+/// warn_deprecated tells that the es- prefixed subcommand is going away.
+#[cfg(feature = "es")]
+fn warn_deprecated(old: &str, new: &str) {
+    eprintln!(
+        "warning: `{}` is deprecated, use `taskmr --engine es {}` or set `engine = \"es\"` in the config.",
+        old, new
+    );
+}
+
+/// ask prints the prompt and reads a trimmed answer. None means stdin has been closed.
+#[cfg(feature = "es")]
+fn ask(lines: &mut impl Iterator<Item = io::Result<String>>, prompt: &str) -> Option<String> {
+    print!("{}", prompt);
+    io::stdout().flush().ok()?;
+    match lines.next() {
+        Some(Ok(line)) => Some(line.trim().to_owned()),
+        _ => None,
+    }
+}
+
+/// require_legacy exits unless the engine is legacy, since only the simple table keeps a journal.
+/// The es engine records every change as an event instead.
+#[cfg(feature = "legacy")]
+fn require_legacy(engine: Engine, subcommand: &str) {
+    if engine != Engine::Legacy {
+        eprintln!(
+            "`{}` works on the legacy engine, pass `--engine legacy` to use it.",
+            subcommand
+        );
+        process::exit(1);
+    }
+}
+
+/// deliver sends the rendered report to the destination.
+fn deliver(delivery: &Delivery, text: &str) -> crate::Result<()> {
+    match delivery {
+        Delivery::Stdout => println!("{}", text),
+        Delivery::File(path) => {
+            let mut file = fs::OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(path)?;
+            writeln!(file, "{}", text)?;
+        }
+        #[cfg(feature = "webhook")]
+        Delivery::Webhook(url) => webhook::post(url, text)?,
+        #[cfg(not(feature = "webhook"))]
+        Delivery::Webhook(_) => {
+            return Err(crate::Error::Presentation(
+                "webhooks need the `webhook` feature".into(),
+            ))
+        }
+    }
+    Ok(())
+}
+
+/// flush_outbox delivers reports in the outbox, and returns the numbers of delivered and
+/// undelivered reports. An undelivered report stays in the outbox to be retried.
+fn flush_outbox(store: &ScheduleStore) -> (usize, usize) {
+    let entries = store.outbox().unwrap_or_else(|err| {
+        eprintln!("Failed to load the outbox: {}.", err);
+        process::exit(1);
+    });
+
+    let (mut delivered, mut undelivered) = (0, 0);
+    for entry in entries {
+        let result = deliver(&entry.delivery, &entry.payload);
+        let marked = match &result {
+            Ok(_) => store.mark_delivered(entry.id),
+            Err(err) => store.mark_failed(entry.id, &err.to_string()),
+        };
+        match (result, marked) {
+            (Ok(_), Ok(_)) => delivered += 1,
+            (Err(err), _) | (_, Err(err)) => {
+                undelivered += 1;
+                eprintln!(
+                    "Failed to deliver a report to {} after {} attempts, it is kept to retry: {}.",
+                    entry.delivery,
+                    entry.attempts + 1,
+                    err
+                );
+            }
+        }
+    }
+    (delivered, undelivered)
+}
+
+/// table_width decides the maximum width of task tables from `--max-width` and `--full`.
+/// The terminal width is used unless specified, and no limit applies when not on a terminal.
+fn table_width(max_width: Option<usize>, full: bool) -> Option<usize> {
+    if full {
+        return None;
+    }
+    max_width.or_else(width::terminal_width)
+}
+
+/// LEGACY_SELECTION and ES_SELECTION name row number files of each model,
+/// because ids of the models are different.
+#[cfg(feature = "legacy")]
+const LEGACY_SELECTION: &str = "legacy";
+#[cfg(feature = "es")]
+const ES_SELECTION: &str = "es";
+
+/// OPEN_TASKS_SELECTION is the model which `open_tasks` reads.
+#[cfg(feature = "es")]
+const OPEN_TASKS_SELECTION: &str = ES_SELECTION;
+#[cfg(not(feature = "es"))]
+const OPEN_TASKS_SELECTION: &str = LEGACY_SELECTION;
+
+/// The number of slow queries shown by `stats --internal`.
+const SLOW_QUERY_DISPLAY_LIMIT: usize = 10;
+
+/// ESRepository is the repository which Cli requires for the es engine.
+/// Any type satisfies it when the `es` feature is disabled.
+#[cfg(feature = "es")]
+pub trait ESRepository: IESTaskRepository {}
+
+#[cfg(feature = "es")]
+impl<T: IESTaskRepository> ESRepository for T {}
+
+/// ESRepository is the repository which Cli requires for the es engine.
+/// Any type satisfies it when the `es` feature is disabled.
+#[cfg(not(feature = "es"))]
+pub trait ESRepository {}
+
+#[cfg(not(feature = "es"))]
+impl<T> ESRepository for T {}
+
+impl Command {
+    /// init_options returns the options if the command is `init`.
+    pub fn init_options(&self) -> Option<InitOptions> {
+        match self.command {
+            SubCommands::Init { defaults, force } => Some(InitOptions {
+                defaults,
+                force,
+                engine: self.engine.map(Engine::from),
+            }),
+            _ => None,
+        }
+    }
+
+    /// is_demo reports whether the command runs on the demo database instead of the user's.
+    pub fn is_demo(&self) -> bool {
+        #[cfg(feature = "es")]
+        if matches!(self.command, SubCommands::Demo) {
+            return true;
+        }
+        false
+    }
+
+    /// is_recorded reports whether the command is kept in the command history.
+    /// Commands run by status bars, schedulers and hooks are left out, and so are the ones
+    /// reading the history, so that `last` repeats what the user typed.
+    pub fn is_recorded(&self) -> bool {
+        match &self.command {
+            SubCommands::Widget { .. }
+            | SubCommands::Cron
+            | SubCommands::FlushOutbox
+            | SubCommands::Init { .. }
+            | SubCommands::Last { .. }
+            | SubCommands::HistoryCmd { .. }
+            | SubCommands::Git {
+                command: GitCommands::ApplyMessage { .. },
+            } => false,
+            #[cfg(feature = "es")]
+            SubCommands::Demo => false,
+            _ => true,
+        }
+    }
+
+    /// is_mutating reports whether the command may change tasks, so that `last` runs it again.
+    /// Commands are taken as mutating unless they are known to only read.
+    pub fn is_mutating(&self) -> bool {
+        match &self.command {
+            SubCommands::List(_)
+            | SubCommands::Stats { .. }
+            | SubCommands::Doctor { .. }
+            | SubCommands::Env
+            | SubCommands::Report { .. }
+            | SubCommands::Lint
+            | SubCommands::Random { .. }
+            | SubCommands::Widget { .. }
+            | SubCommands::Last { .. }
+            | SubCommands::HistoryCmd { .. }
+            | SubCommands::Config {
+                command: ConfigCommands::Export { .. },
+            } => false,
+            #[cfg(feature = "es")]
+            SubCommands::ESList(_)
+            | SubCommands::Show { .. }
+            | SubCommands::Next { .. }
+            | SubCommands::Simulate { .. }
+            | SubCommands::Forecast { .. }
+            | SubCommands::Share { .. }
+            | SubCommands::Export { .. }
+            | SubCommands::Demo
+            | SubCommands::Someday {
+                command: SomedayCommands::List { .. },
+            }
+            | SubCommands::Sprint {
+                command: SprintCommands::Status { .. } | SprintCommands::Burndown { .. },
+            }
+            | SubCommands::Tag {
+                command: Some(TagCommands::List { .. }),
+                ..
+            } => false,
+            #[cfg(feature = "es")]
+            SubCommands::Tidy { dry_run, .. } => !dry_run,
+            #[cfg(feature = "es")]
+            SubCommands::Import { validate_only, .. } => !validate_only,
+            SubCommands::Search(_) => false,
+            #[cfg(feature = "legacy")]
+            SubCommands::History { .. } => false,
+            _ => true,
+        }
+    }
+
+    /// uses_database reports whether the command needs the task database.
+    pub fn uses_database(&self) -> bool {
+        !matches!(
+            self.command,
+            SubCommands::Init { .. } | SubCommands::Env | SubCommands::Config { .. }
+        )
+    }
+
+    /// sets_up_database reports whether the schema is checked and migrated before the command runs.
+    /// doctor must work even if the schema is broken, so that it inspects the database untouched,
+    /// and widget must not wait for migrations because status bars poll it frequently.
+    pub fn sets_up_database(&self) -> bool {
+        self.uses_database()
+            && !matches!(
+                self.command,
+                SubCommands::Doctor { .. } | SubCommands::Widget { .. }
+            )
+    }
 }
 
 /// Cli has structs to execute usecases.
-pub struct Cli<TR: IESTaskRepository> {
+pub struct Cli<TR: ESRepository> {
+    #[cfg(feature = "legacy")]
     add_task_usecase: AddTaskUseCase,
+    #[cfg(feature = "legacy")]
     close_task_usecase: CloseTaskUseCase,
+    #[cfg(feature = "legacy")]
+    delete_task_usecase: DeleteTaskUseCase,
+    #[cfg(feature = "legacy")]
+    archive_task_usecase: ArchiveTaskUseCase,
+    #[cfg(feature = "legacy")]
     edit_task_usecase: EditTaskUseCase,
+    #[cfg(feature = "legacy")]
     list_task_usecase: ListTaskUseCase,
+    #[cfg(feature = "legacy")]
+    search_task_usecase: SearchTaskUseCase,
+    #[cfg(feature = "legacy")]
+    undo_usecase: UndoUseCase,
+    #[cfg(feature = "legacy")]
+    history_usecase: HistoryUseCase,
     table_printer: TablePrinter<io::Stdout>,
+    json_printer: JsonPrinter<io::Stdout>,
+    output_format: OutputFormatArg,
+    #[cfg_attr(not(feature = "es"), allow(dead_code))]
     es_task_repository: TR,
+    #[cfg(feature = "es")]
+    sprint_repository: SprintRepository,
+    #[cfg(feature = "es")]
+    tag_catalog_repository: TagCatalogRepository,
+    db_file_path: PathBuf,
+    config_dir: PathBuf,
+    config: Config,
 }
 
+#[cfg(feature = "es")]
 impl<TR: IESTaskRepository> IESTaskRepositoryComponent for Cli<TR> {
     type Repository = TR;
     fn repository(&self) -> &Self::Repository {
@@ -119,6 +1672,23 @@ impl<TR: IESTaskRepository> IESTaskRepositoryComponent for Cli<TR> {
     }
 }
 
+#[cfg(feature = "es")]
+impl<TR: IESTaskRepository> ISprintRepositoryComponent for Cli<TR> {
+    type SprintRepository = SprintRepository;
+    fn sprint_repository(&self) -> &Self::SprintRepository {
+        &self.sprint_repository
+    }
+}
+
+#[cfg(feature = "es")]
+impl<TR: IESTaskRepository> ITagCatalogRepositoryComponent for Cli<TR> {
+    type TagCatalogRepository = TagCatalogRepository;
+    fn tag_catalog_repository(&self) -> &Self::TagCatalogRepository {
+        &self.tag_catalog_repository
+    }
+}
+
+#[cfg(feature = "es")]
 impl<TR: IESTaskRepository> AddTaskUseCaseComponent for Cli<TR> {
     type AddTaskUseCase = Self;
     fn add_task_usecase(&self) -> &Self::AddTaskUseCase {
@@ -126,6 +1696,7 @@ impl<TR: IESTaskRepository> AddTaskUseCaseComponent for Cli<TR> {
     }
 }
 
+#[cfg(feature = "es")]
 impl<TR: IESTaskRepository> CloseTaskUseCaseComponent for Cli<TR> {
     type CloseTaskUseCase = Self;
     fn close_task_usecase(&self) -> &Self::CloseTaskUseCase {
@@ -133,159 +1704,3246 @@ impl<TR: IESTaskRepository> CloseTaskUseCaseComponent for Cli<TR> {
     }
 }
 
-impl<TR: IESTaskRepository> EditTaskUseCaseComponent for Cli<TR> {
-    type EditTaskUseCase = Self;
-    fn edit_task_usecase(&self) -> &Self::EditTaskUseCase {
+#[cfg(feature = "es")]
+impl<TR: IESTaskRepository> DeleteTaskUseCaseComponent for Cli<TR> {
+    type DeleteTaskUseCase = Self;
+    fn delete_task_usecase(&self) -> &Self::DeleteTaskUseCase {
         self
     }
 }
 
-impl<TR: IESTaskRepository> ListTaskUseCaseComponent for Cli<TR> {
-    type ListTaskUseCase = Self;
-    fn list_task_usecase(&self) -> &Self::ListTaskUseCase {
+#[cfg(feature = "es")]
+impl<TR: IESTaskRepository> ArchiveUseCaseComponent for Cli<TR> {
+    type ArchiveUseCase = Self;
+    fn archive_usecase(&self) -> &Self::ArchiveUseCase {
         self
     }
 }
 
-impl<TR: IESTaskRepository> Cli<TR> {
-    /// construct Cli.
-    pub fn new(
-        add_task_usecase: AddTaskUseCase,
-        close_task_usecase: CloseTaskUseCase,
-        edit_task_usecase: EditTaskUseCase,
-        list_task_usecase: ListTaskUseCase,
-        table_printer: TablePrinter<io::Stdout>,
-        es_task_repository: TR,
-    ) -> Self {
-        Cli {
+#[cfg(feature = "es")]
+impl<TR: IESTaskRepository> FollowUpUseCaseComponent for Cli<TR> {
+    type FollowUpUseCase = Self;
+    fn follow_up_usecase(&self) -> &Self::FollowUpUseCase {
+        self
+    }
+}
+
+#[cfg(feature = "es")]
+impl<TR: IESTaskRepository> EditTaskUseCaseComponent for Cli<TR> {
+    type EditTaskUseCase = Self;
+    fn edit_task_usecase(&self) -> &Self::EditTaskUseCase {
+        self
+    }
+}
+
+#[cfg(feature = "es")]
+impl<TR: IESTaskRepository> ListTaskUseCaseComponent for Cli<TR> {
+    type ListTaskUseCase = Self;
+    fn list_task_usecase(&self) -> &Self::ListTaskUseCase {
+        self
+    }
+}
+
+#[cfg(feature = "es")]
+impl<TR: IESTaskRepository> CreateSprintUseCaseComponent for Cli<TR> {
+    type CreateSprintUseCase = Self;
+    fn create_sprint_usecase(&self) -> &Self::CreateSprintUseCase {
+        self
+    }
+}
+
+#[cfg(feature = "es")]
+impl<TR: IESTaskRepository> SearchTaskUseCaseComponent for Cli<TR> {
+    type SearchTaskUseCase = Self;
+    fn search_task_usecase(&self) -> &Self::SearchTaskUseCase {
+        self
+    }
+}
+
+#[cfg(feature = "es")]
+impl<TR: IESTaskRepository> ShowTaskUseCaseComponent for Cli<TR> {
+    type ShowTaskUseCase = Self;
+    fn show_task_usecase(&self) -> &Self::ShowTaskUseCase {
+        self
+    }
+}
+
+#[cfg(feature = "es")]
+impl<TR: IESTaskRepository> ShowSprintUseCaseComponent for Cli<TR> {
+    type ShowSprintUseCase = Self;
+    fn show_sprint_usecase(&self) -> &Self::ShowSprintUseCase {
+        self
+    }
+}
+
+#[cfg(feature = "es")]
+impl<TR: IESTaskRepository> CloseSprintUseCaseComponent for Cli<TR> {
+    type CloseSprintUseCase = Self;
+    fn close_sprint_usecase(&self) -> &Self::CloseSprintUseCase {
+        self
+    }
+}
+
+#[cfg(feature = "es")]
+impl<TR: IESTaskRepository> BurndownSprintUseCaseComponent for Cli<TR> {
+    type BurndownSprintUseCase = Self;
+    fn burndown_sprint_usecase(&self) -> &Self::BurndownSprintUseCase {
+        self
+    }
+}
+
+#[cfg(feature = "es")]
+impl<TR: IESTaskRepository> LogTimeUseCaseComponent for Cli<TR> {
+    type LogTimeUseCase = Self;
+    fn log_time_usecase(&self) -> &Self::LogTimeUseCase {
+        self
+    }
+}
+
+#[cfg(feature = "es")]
+impl<TR: IESTaskRepository> StartTimerUseCaseComponent for Cli<TR> {
+    type StartTimerUseCase = Self;
+    fn start_timer_usecase(&self) -> &Self::StartTimerUseCase {
+        self
+    }
+}
+
+#[cfg(feature = "es")]
+impl<TR: IESTaskRepository> StopTimerUseCaseComponent for Cli<TR> {
+    type StopTimerUseCase = Self;
+    fn stop_timer_usecase(&self) -> &Self::StopTimerUseCase {
+        self
+    }
+}
+
+#[cfg(feature = "es")]
+impl<TR: IESTaskRepository> CompletePomodoroUseCaseComponent for Cli<TR> {
+    type CompletePomodoroUseCase = Self;
+    fn complete_pomodoro_usecase(&self) -> &Self::CompletePomodoroUseCase {
+        self
+    }
+}
+
+#[cfg(feature = "es")]
+impl<TR: IESTaskRepository> NextTaskUseCaseComponent for Cli<TR> {
+    type NextTaskUseCase = Self;
+    fn next_task_usecase(&self) -> &Self::NextTaskUseCase {
+        self
+    }
+}
+
+#[cfg(feature = "es")]
+impl<TR: IESTaskRepository> CycleTimeUseCaseComponent for Cli<TR> {
+    type CycleTimeUseCase = Self;
+    fn cycle_time_usecase(&self) -> &Self::CycleTimeUseCase {
+        self
+    }
+}
+
+#[cfg(feature = "es")]
+impl<TR: IESTaskRepository> ForecastUseCaseComponent for Cli<TR> {
+    type ForecastUseCase = Self;
+    fn forecast_usecase(&self) -> &Self::ForecastUseCase {
+        self
+    }
+}
+
+#[cfg(feature = "es")]
+impl<TR: IESTaskRepository> SimulateUseCaseComponent for Cli<TR> {
+    type SimulateUseCase = Self;
+    fn simulate_usecase(&self) -> &Self::SimulateUseCase {
+        self
+    }
+}
+
+#[cfg(feature = "es")]
+impl<TR: IESTaskRepository> CheckInUseCaseComponent for Cli<TR> {
+    type CheckInUseCase = Self;
+    fn check_in_usecase(&self) -> &Self::CheckInUseCase {
+        self
+    }
+}
+
+#[cfg(feature = "es")]
+impl<TR: IESTaskRepository> TimeByTagUseCaseComponent for Cli<TR> {
+    type TimeByTagUseCase = Self;
+    fn time_by_tag_usecase(&self) -> &Self::TimeByTagUseCase {
+        self
+    }
+}
+
+#[cfg(feature = "es")]
+impl<TR: IESTaskRepository> CaptureUseCaseComponent for Cli<TR> {
+    type CaptureUseCase = Self;
+    fn capture_usecase(&self) -> &Self::CaptureUseCase {
+        self
+    }
+}
+
+#[cfg(feature = "es")]
+impl<TR: IESTaskRepository> ListInboxUseCaseComponent for Cli<TR> {
+    type ListInboxUseCase = Self;
+    fn list_inbox_usecase(&self) -> &Self::ListInboxUseCase {
+        self
+    }
+}
+
+#[cfg(feature = "es")]
+impl<TR: IESTaskRepository> SeedDemoUseCaseComponent for Cli<TR> {
+    type SeedDemoUseCase = Self;
+    fn seed_demo_usecase(&self) -> &Self::SeedDemoUseCase {
+        self
+    }
+}
+
+#[cfg(feature = "devtools")]
+impl<TR: IESTaskRepository> GenerateUseCaseComponent for Cli<TR> {
+    type GenerateUseCase = Self;
+    fn generate_usecase(&self) -> &Self::GenerateUseCase {
+        self
+    }
+}
+
+#[cfg(feature = "es")]
+impl<TR: IESTaskRepository> PromoteUseCaseComponent for Cli<TR> {
+    type PromoteUseCase = Self;
+    fn promote_usecase(&self) -> &Self::PromoteUseCase {
+        self
+    }
+}
+
+#[cfg(feature = "es")]
+impl<TR: IESTaskRepository> TriageUseCaseComponent for Cli<TR> {
+    type TriageUseCase = Self;
+    fn triage_usecase(&self) -> &Self::TriageUseCase {
+        self
+    }
+}
+
+#[cfg(feature = "es")]
+impl<TR: IESTaskRepository> WaitOnUseCaseComponent for Cli<TR> {
+    type WaitOnUseCase = Self;
+    fn wait_on_usecase(&self) -> &Self::WaitOnUseCase {
+        self
+    }
+}
+
+#[cfg(feature = "es")]
+impl<TR: IESTaskRepository> NudgeUseCaseComponent for Cli<TR> {
+    type NudgeUseCase = Self;
+    fn nudge_usecase(&self) -> &Self::NudgeUseCase {
+        self
+    }
+}
+
+#[cfg(feature = "es")]
+impl<TR: IESTaskRepository> QuotaUsageUseCaseComponent for Cli<TR> {
+    type QuotaUsageUseCase = Self;
+    fn quota_usage_usecase(&self) -> &Self::QuotaUsageUseCase {
+        self
+    }
+}
+
+#[cfg(feature = "es")]
+impl<TR: IESTaskRepository> TidyUseCaseComponent for Cli<TR> {
+    type TidyUseCase = Self;
+    fn tidy_usecase(&self) -> &Self::TidyUseCase {
+        self
+    }
+}
+
+#[cfg(feature = "es")]
+impl<TR: IESTaskRepository> RenameTagUseCaseComponent for Cli<TR> {
+    type RenameTagUseCase = Self;
+    fn rename_tag_usecase(&self) -> &Self::RenameTagUseCase {
+        self
+    }
+}
+
+#[cfg(feature = "es")]
+impl<TR: IESTaskRepository> RetagUseCaseComponent for Cli<TR> {
+    type RetagUseCase = Self;
+    fn retag_usecase(&self) -> &Self::RetagUseCase {
+        self
+    }
+}
+
+#[cfg(feature = "es")]
+impl<TR: IESTaskRepository> TagTaskUseCaseComponent for Cli<TR> {
+    type TagTaskUseCase = Self;
+    fn tag_task_usecase(&self) -> &Self::TagTaskUseCase {
+        self
+    }
+}
+
+#[cfg(feature = "es")]
+impl<TR: IESTaskRepository> ApplyRulesUseCaseComponent for Cli<TR> {
+    type ApplyRulesUseCase = Self;
+    fn apply_rules_usecase(&self) -> &Self::ApplyRulesUseCase {
+        self
+    }
+}
+
+#[cfg(feature = "es")]
+impl<TR: IESTaskRepository> ListTagsUseCaseComponent for Cli<TR> {
+    type ListTagsUseCase = Self;
+    fn list_tags_usecase(&self) -> &Self::ListTagsUseCase {
+        self
+    }
+}
+
+#[cfg(feature = "es")]
+impl<TR: IESTaskRepository> MonthReviewUseCaseComponent for Cli<TR> {
+    type MonthReviewUseCase = Self;
+    fn month_review_usecase(&self) -> &Self::MonthReviewUseCase {
+        self
+    }
+}
+
+#[cfg(feature = "es")]
+impl<TR: IESTaskRepository> LockTaskUseCaseComponent for Cli<TR> {
+    type LockTaskUseCase = Self;
+    fn lock_task_usecase(&self) -> &Self::LockTaskUseCase {
+        self
+    }
+}
+
+#[cfg(feature = "es")]
+impl<TR: IESTaskRepository> ImportUseCaseComponent for Cli<TR> {
+    type ImportUseCase = Self;
+    fn import_usecase(&self) -> &Self::ImportUseCase {
+        self
+    }
+}
+
+#[cfg(feature = "es")]
+impl<TR: IESTaskRepository> MigrateLegacyUseCaseComponent for Cli<TR> {
+    type MigrateLegacyUseCase = Self;
+    fn migrate_legacy_usecase(&self) -> &Self::MigrateLegacyUseCase {
+        self
+    }
+}
+
+impl<TR: ESRepository> Cli<TR> {
+    /// construct Cli.
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        #[cfg(feature = "legacy")] add_task_usecase: AddTaskUseCase,
+        #[cfg(feature = "legacy")] close_task_usecase: CloseTaskUseCase,
+        #[cfg(feature = "legacy")] delete_task_usecase: DeleteTaskUseCase,
+        #[cfg(feature = "legacy")] archive_task_usecase: ArchiveTaskUseCase,
+        #[cfg(feature = "legacy")] edit_task_usecase: EditTaskUseCase,
+        #[cfg(feature = "legacy")] list_task_usecase: ListTaskUseCase,
+        #[cfg(feature = "legacy")] search_task_usecase: SearchTaskUseCase,
+        #[cfg(feature = "legacy")] undo_usecase: UndoUseCase,
+        #[cfg(feature = "legacy")] history_usecase: HistoryUseCase,
+        mut table_printer: TablePrinter<io::Stdout>,
+        json_printer: JsonPrinter<io::Stdout>,
+        es_task_repository: TR,
+        #[cfg(feature = "es")] sprint_repository: SprintRepository,
+        #[cfg(feature = "es")] tag_catalog_repository: TagCatalogRepository,
+        db_file_path: PathBuf,
+        config_dir: PathBuf,
+        config: Config,
+    ) -> Self {
+        table_printer.set_tag_styles(TagStyles::new(
+            config.tags.clone(),
+            style::should_colorize(config.color),
+        ));
+        Cli {
+            #[cfg(feature = "legacy")]
             add_task_usecase,
+            #[cfg(feature = "legacy")]
             close_task_usecase,
+            #[cfg(feature = "legacy")]
+            delete_task_usecase,
+            #[cfg(feature = "legacy")]
+            archive_task_usecase,
+            #[cfg(feature = "legacy")]
             edit_task_usecase,
+            #[cfg(feature = "legacy")]
             list_task_usecase,
+            #[cfg(feature = "legacy")]
+            search_task_usecase,
+            #[cfg(feature = "legacy")]
+            undo_usecase,
+            #[cfg(feature = "legacy")]
+            history_usecase,
             table_printer,
+            json_printer,
+            output_format: OutputFormatArg::Table,
             es_task_repository,
+            #[cfg(feature = "es")]
+            sprint_repository,
+            #[cfg(feature = "es")]
+            tag_catalog_repository,
+            db_file_path,
+            config_dir,
+            config,
+        }
+    }
+
+    /// open_connection opens another connection to the database for maintenance commands.
+    fn open_connection(&self) -> Connection {
+        Connection::open(&self.db_file_path).unwrap_or_else(|err| {
+            eprintln!("Couldn't connect your task database: {}", err);
+            process::exit(1)
+        })
+    }
+
+    /// selection_store returns SelectionStore of row numbers of the model.
+    fn selection_store(&self, model: &str) -> SelectionStore {
+        SelectionStore::new(self.config_dir.join(format!("selection-{}", model)))
+    }
+
+    /// resolve returns the id referred by the TaskRef, or exits on failure.
+    fn resolve(&self, task_ref: &TaskRef, model: &str) -> i64 {
+        task_ref
+            .resolve(&self.selection_store(model))
+            .unwrap_or_else(|err| {
+                eprintln!("Failed to find the task: {}.", err);
+                process::exit(1);
+            })
+    }
+
+    /// focus_store returns the store of the focused task of the model.
+    /// It holds a single id in the same format as the row numbers.
+    fn focus_store(&self, model: &str) -> SelectionStore {
+        SelectionStore::new(self.config_dir.join(format!("focus-{}", model)))
+    }
+
+    /// save_selection saves row numbers of the listed tasks, or exits on failure.
+    fn save_selection(&self, model: &str, ids: &[i64]) {
+        self.selection_store(model).save(ids).unwrap_or_else(|err| {
+            eprintln!("Failed to save row numbers: {}.", err);
+            process::exit(1);
+        });
+    }
+
+    /// record_command keeps the command typed as `args` after `taskmr` in the command history.
+    pub fn record_command(&self, args: &[String], command: &Command) -> crate::Result<()> {
+        let (args, is_redacted) = redact(args);
+        CommandHistoryStore::new(self.open_connection()).record(
+            &args,
+            command.is_mutating(),
+            is_redacted,
+        )
+    }
+
+    /// last runs the latest command which changed tasks again.
+    fn last(&mut self, dry_run: bool) {
+        let entry = CommandHistoryStore::new(self.open_connection())
+            .last_mutating()
+            .unwrap_or_else(|err| {
+                eprintln!("Failed to read the command history: {}.", err);
+                process::exit(1);
+            });
+        let Some(entry) = entry else {
+            println!("No command to run again.");
+            return;
+        };
+        if entry.is_redacted {
+            eprintln!(
+                "`taskmr {}` had secrets, which are not kept, type it again.",
+                quote_args(&entry.args)
+            );
+            process::exit(1);
+        }
+
+        println!("$ taskmr {}", quote_args(&entry.args));
+        if dry_run {
+            return;
+        }
+        let args = std::iter::once("taskmr".to_owned()).chain(entry.args);
+        match Command::try_parse_from(args) {
+            Ok(command) => self.handle(command),
+            Err(err) => {
+                eprintln!("Failed to parse the command: {}.", err.kind());
+                process::exit(1);
+            }
+        }
+    }
+
+    /// flush_metrics records metrics of this invocation into the metrics store.
+    pub fn flush_metrics(&self, metrics: &RepositoryMetrics) -> crate::Result<()> {
+        let metrics_store = MetricsStore::new(self.open_connection());
+        metrics_store.create_table_if_not_exists()?;
+        metrics_store.flush(metrics)
+    }
+
+    /// context_tags returns tags of the context detected as configured by `auto_context`.
+    #[cfg(feature = "es")]
+    fn context_tags(&self) -> Vec<String> {
+        let detector = match context::detector(self.config.auto_context) {
+            Some(detector) => detector,
+            None => return vec![],
+        };
+
+        std::env::current_dir()
+            .ok()
+            .and_then(|cwd| detector.detect(&cwd))
+            .map(|c| c.tags())
+            .unwrap_or_default()
+    }
+
+    /// close_task closes the task and returns its id.
+    /// The Event Sourcing model is preferred when both models are compiled in.
+    #[cfg(feature = "es")]
+    fn close_task(&self, id: i64) -> crate::Result<i64> {
+        let input = ESCloseTaskUseCaseInput {
+            sequential_id: SequentialID::new(id),
+            unlock: false,
+            cascade: false,
+        };
+        Ok(<Cli<TR> as ESCloseTaskUseCase>::execute(self, input)?.to_i64())
+    }
+
+    /// close_task closes the task and returns its id.
+    #[cfg(not(feature = "es"))]
+    fn close_task(&self, id: i64) -> crate::Result<i64> {
+        Ok(self
+            .close_task_usecase
+            .execute(CloseTaskUseCaseInput { id })?
+            .get())
+    }
+
+    /// open_tasks returns opening tasks in the current scope.
+    /// The Event Sourcing model is preferred when both models are compiled in.
+    /// Private tasks are left out, since reports may leave the machine via webhooks.
+    #[cfg(feature = "es")]
+    fn open_tasks(&self) -> crate::Result<Vec<OpenTask>> {
+        let input = ESListTaskUseCaseInput {
+            tags: self.config.scope_tags().to_vec(),
+            ..Default::default()
+        };
+        let today = Local::now().date_naive();
+        Ok(<Cli<TR> as ESListTaskUseCase>::execute(self, input)?
+            .into_iter()
+            .map(|t| OpenTask {
+                id: t.id,
+                title: t.title,
+                priority: t.priority,
+                cost: t.cost,
+                tags: t.tags,
+                nudge: t.waiting_on.as_ref().is_some_and(|w| w.is_due(today)),
+                waiting_on: t.waiting_on.map(|w| w.person),
+                is_overdue: t.is_overdue,
+            })
+            .collect())
+    }
+
+    /// export_snapshot writes a read-only copy of the database, leaving out private tasks unless asked.
+    /// Texts in the copy are replaced with pseudonyms if anonymize is given.
+    #[cfg(feature = "es")]
+    fn export_snapshot(&self, path: &std::path::Path, show_private: bool, anonymize: bool) {
+        let ids = |include_private| -> crate::Result<std::collections::BTreeSet<i64>> {
+            let input = ESListTaskUseCaseInput {
+                include_closed: true,
+                include_private,
+                include_inbox: true,
+                include_someday: true,
+                include_deleted: true,
+                ..Default::default()
+            };
+            Ok(<Cli<TR> as ESListTaskUseCase>::execute(self, input)?
+                .into_iter()
+                .map(|t| t.id)
+                .collect())
+        };
+        let export = || -> crate::Result<()> {
+            let private: Vec<i64> = if show_private {
+                vec![]
+            } else {
+                ids(true)?.difference(&ids(false)?).copied().collect()
+            };
+            let anonymizer = anonymize.then(Anonymizer::new);
+            snapshot::write_snapshot(&self.open_connection(), path, &private, anonymizer.as_ref())
+        };
+        export().unwrap_or_else(|err| {
+            eprintln!("Failed to export the snapshot: {}.", err);
+            process::exit(1);
+        });
+        println!("Wrote a read-only snapshot to {}.", path.display());
+    }
+
+    /// open_tasks returns opening tasks.
+    #[cfg(not(feature = "es"))]
+    fn open_tasks(&self) -> crate::Result<Vec<OpenTask>> {
+        Ok(self
+            .list_task_usecase
+            .execute(ListTaskUseCaseInput::default())?
+            .into_iter()
+            .map(|t| OpenTask {
+                id: t.id,
+                title: t.title,
+                priority: t.priority,
+                cost: t.cost,
+                tags: vec![],
+                waiting_on: None,
+                nudge: false,
+                is_overdue: false,
+            })
+            .collect())
+    }
+
+    /// script_engine returns ScriptEngine loading scripts next to the database.
+    #[cfg(feature = "scripting")]
+    fn script_engine(&self) -> ScriptEngine {
+        ScriptEngine::new(self.scripts_dir())
+    }
+
+    /// env_report collects the environment. It works without the database.
+    fn env_report(&self) -> String {
+        let describe = |path: &std::path::Path| match fs::metadata(path) {
+            Ok(meta) => format!(
+                "{} ({})",
+                path.display(),
+                env_printer::format_bytes(meta.len())
+            ),
+            Err(_) => format!("{} (not found)", path.display()),
+        };
+
+        let mut entries = vec![
+            ("taskmr", env!("CARGO_PKG_VERSION").to_owned()),
+            ("sqlite", rusqlite::version().to_owned()),
+            ("os", format!("{} {}", env::consts::OS, env::consts::ARCH)),
+            ("features", env_printer::compiled_features().join(", ")),
+            ("config", describe(&self.config_dir.join(CONFIG_FILE_NAME))),
+            (
+                "scope",
+                match &self.config.scope {
+                    Some(scope) => scope.path.display().to_string(),
+                    None => String::from("none"),
+                },
+            ),
+            ("database", describe(&self.db_file_path)),
+        ];
+        if !self.db_file_path.is_file() {
+            return env_printer::render(&entries);
+        }
+
+        let inspector = SchemaInspector::new(self.open_connection());
+        let or_error = |r: crate::Result<String>| r.unwrap_or_else(|err| format!("error: {}", err));
+        entries.push((
+            "schema",
+            or_error(inspector.user_version().and_then(|version| {
+                let drifts = inspector.inspect()?.len();
+                Ok(match drifts {
+                    0 => format!("version {}, ok", version),
+                    n => format!("version {}, {} drifts (see `taskmr doctor`)", version, n),
+                })
+            })),
+        ));
+        #[cfg(feature = "es")]
+        entries.push((
+            "events",
+            or_error(
+                inspector
+                    .row_count("task_events")
+                    .map(|n| n.unwrap_or(0).to_string()),
+            ),
+        ));
+        #[cfg(feature = "legacy")]
+        entries.push((
+            "tasks",
+            or_error(
+                inspector
+                    .row_count("tasks")
+                    .map(|n| n.unwrap_or(0).to_string()),
+            ),
+        ));
+
+        env_printer::render(&entries)
+    }
+
+    /// scripts_dir returns the directory of user scripts next to the database.
+    fn scripts_dir(&self) -> PathBuf {
+        self.config_dir.join("scripts")
+    }
+
+    /// apply_script_filter keeps tasks selected by `filter(task)` of the script.
+    #[cfg(feature = "scripting")]
+    fn apply_script_filter<T, F>(
+        &self,
+        name: &Option<String>,
+        tasks: Vec<T>,
+        to_script_task: F,
+    ) -> Vec<T>
+    where
+        F: Fn(&T) -> ScriptTask,
+    {
+        let name = match name {
+            Some(name) => name,
+            None => return tasks,
+        };
+
+        let engine = self.script_engine();
+        let selected = engine
+            .load(name)
+            .and_then(|script| {
+                Ok(engine.filter(&script, tasks.iter().map(&to_script_task).collect())?)
+            })
+            .unwrap_or_else(|err| {
+                eprintln!("Failed to filter tasks: {}.", err);
+                process::exit(1);
+            });
+
+        tasks
+            .into_iter()
+            .filter(|t| selected.iter().any(|s| s.id == to_script_task(t).id))
+            .collect()
+    }
+
+    /// render_report renders the built-in report or the report defined by the script.
+    fn render_report(&self, name: &str) -> crate::Result<String> {
+        let tasks = self.open_tasks()?;
+        if name == STATS_REPORT {
+            let summary = ListSummary::from_costs(tasks.iter().map(|t| t.cost));
+            return Ok(format!(
+                "Open tasks: {}\nTotal cost: {}",
+                summary.open, summary.total_cost
+            ));
+        }
+
+        #[cfg(feature = "scripting")]
+        {
+            let tasks: Vec<ScriptTask> = tasks
+                .into_iter()
+                .map(|t| ScriptTask {
+                    id: t.id,
+                    title: t.title,
+                    priority: t.priority,
+                    cost: t.cost,
+                    tags: t.tags,
+                    waiting_on: t.waiting_on,
+                    nudge: t.nudge,
+                })
+                .collect();
+            let engine = self.script_engine();
+            let script = engine.load(name)?;
+            Ok(engine.report(&script, &tasks)?)
+        }
+
+        #[cfg(not(feature = "scripting"))]
+        Err(crate::Error::Presentation(
+            format!(
+                "unknown report `{}`, script reports need the `scripting` feature",
+                name
+            )
+            .into(),
+        ))
+    }
+
+    /// schedule_store returns ScheduleStore, or exits on failure.
+    fn schedule_store(&self) -> ScheduleStore {
+        let store = ScheduleStore::new(self.open_connection());
+        store.create_table_if_not_exists().unwrap_or_else(|err| {
+            eprintln!("Failed to create tables on your database: {}.", err);
+            process::exit(1);
+        });
+        store
+    }
+
+    /// run_plugin executes the plugin `taskmr-<name>` and returns its exit code.
+    #[cfg(feature = "plugin")]
+    fn run_plugin(&self, name: &str, args: &[String]) -> crate::Result<i32> {
+        let path =
+            plugin::find_plugin(name).ok_or_else(|| PluginError::NotFound(name.to_owned()))?;
+        let context = PluginContext::new(self.db_file_path.clone());
+
+        let status = process::Command::new(path)
+            .args(args)
+            .envs(context.to_env()?)
+            .status()?;
+
+        // NOTE: a plugin killed by a signal has no exit code.
+        Ok(status.code().unwrap_or(1))
+    }
+
+    /// engine_handlers returns the dispatch table of the engine, or exits if it is not compiled in.
+    fn engine_handlers(&self, engine: Engine) -> EngineHandlers<Self> {
+        match engine {
+            #[cfg(feature = "es")]
+            Engine::Es => EngineHandlers {
+                add: Self::es_add,
+                close: Self::es_close,
+                delete: Self::es_delete,
+                archive: Self::es_archive,
+                edit: Self::es_edit,
+                list: Self::es_list,
+                search: Self::es_search,
+                summarize_open: Self::es_summarize_open,
+            },
+            #[cfg(feature = "legacy")]
+            Engine::Legacy => EngineHandlers {
+                add: Self::legacy_add,
+                close: Self::legacy_close,
+                delete: Self::legacy_delete,
+                archive: Self::legacy_archive,
+                edit: Self::legacy_edit,
+                list: Self::legacy_list,
+                search: Self::legacy_search,
+                summarize_open: Self::legacy_summarize_open,
+            },
+            #[allow(unreachable_patterns)]
+            _ => {
+                eprintln!("This taskmr is built without the {} engine.", engine);
+                process::exit(1);
+            }
+        }
+    }
+
+    /// print_outstanding prints the open cost if `[outstanding]` of the config turns it on.
+    /// After tasks are added, it also warns if more than `max_weeks` of work is queued.
+    fn print_outstanding(&self, engine: Engine, added: bool) {
+        let config = &self.config.outstanding;
+        let max_weeks = config.max_weeks.filter(|_| added);
+        if !config.show && max_weeks.is_none() {
+            return;
+        }
+
+        // NOTE: the advice is secondary to the change, so a failure is reported but not fatal.
+        match (self.engine_handlers(engine).summarize_open)(self) {
+            Ok(summary) => {
+                let outstanding = Outstanding::new(summary, config.weekly_capacity);
+                if config.show {
+                    println!("{}", table::format_outstanding(&outstanding));
+                }
+                if let Some(max_weeks) = max_weeks.filter(|w| outstanding.exceeds(*w)) {
+                    eprintln!("warning: {}.", table::format_capacity_warning(max_weeks));
+                }
+            }
+            Err(err) => eprintln!("Failed to summarize open tasks: {}.", err),
+        }
+    }
+
+    /// legacy_summarize_open summarizes open tasks in the simple table.
+    #[cfg(feature = "legacy")]
+    fn legacy_summarize_open(&self) -> crate::Result<ListSummary> {
+        let tasks = self
+            .list_task_usecase
+            .execute(ListTaskUseCaseInput::default())?;
+        Ok(list_task_usecase::summarize(&tasks))
+    }
+
+    /// es_summarize_open summarizes open tasks in the event store, private ones included.
+    #[cfg(feature = "es")]
+    fn es_summarize_open(&self) -> crate::Result<ListSummary> {
+        let input = ESListTaskUseCaseInput {
+            tags: self.config.scope_tags().to_vec(),
+            include_private: true,
+            ..Default::default()
+        };
+        let tasks = <Cli<TR> as ESListTaskUseCase>::execute(self, input)?;
+        Ok(es_list_task_usecase::summarize(&tasks))
+    }
+
+    /// rules returns `[[rules]]` of the config, exiting if a pattern or a tag is invalid.
+    #[cfg(feature = "es")]
+    fn rules(&self) -> Rules {
+        let invalid = |err: String| -> ! {
+            eprintln!("Failed to read rules of the config: {}.", err);
+            process::exit(1);
+        };
+        let tags = |tags: &[String]| -> Vec<Tag> {
+            tags.iter()
+                .map(|t| Tag::new(t).unwrap_or_else(|err| invalid(err.to_string())))
+                .collect()
+        };
+        Rules(
+            self.config
+                .rules
+                .iter()
+                .map(|r| Rule {
+                    title: r.when_title.as_ref().map(|t| {
+                        t.parse::<TitlePattern>()
+                            .unwrap_or_else(|err| invalid(err))
+                            .into()
+                    }),
+                    tags: tags(&r.when_tags),
+                    add_tags: tags(&r.add_tags),
+                    min_priority: r.min_priority.map(Priority::new),
+                })
+                .collect(),
+        )
+    }
+
+    /// apply_rules applies rules of the config to open tasks, and prints the changed ids.
+    #[cfg(feature = "es")]
+    fn apply_rules(&self) {
+        let input = ApplyRulesUseCaseInput {
+            rules: self.rules(),
+        };
+        let changed = <Cli<TR> as ApplyRulesUseCase>::execute(self, input).unwrap_or_else(|err| {
+            eprintln!("Failed to apply rules: {}.", err);
+            process::exit(1);
+        });
+
+        if changed.is_empty() {
+            println!("No open task is changed by the rules.");
+        } else {
+            println!(
+                "Applied rules to {} tasks: {}.",
+                changed.len(),
+                changed
+                    .iter()
+                    .map(|id| id.to_string())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            );
+        }
+    }
+
+    /// lint_rules returns the conventions of titles in the config.
+    fn lint_rules(&self) -> LintRules {
+        let lint = &self.config.lint;
+        LintRules {
+            max_length: lint.max_length,
+            no_trailing_period: lint.no_trailing_period,
+            first_words: lint.first_words.clone(),
+            first_letter: lint.first_letter.map(|f| match f {
+                FirstLetter::Upper => LetterCase::Upper,
+                FirstLetter::Lower => LetterCase::Lower,
+            }),
+        }
+    }
+
+    /// quota_usage sums logged time against quotas of the config, limited to tags of the task if given.
+    #[cfg(feature = "es")]
+    fn quota_usage(
+        &self,
+        sequential_id: Option<SequentialID>,
+    ) -> crate::Result<Vec<QuotaUsageDTO>> {
+        let quotas = self
+            .config
+            .quota
+            .iter()
+            .map(|(tag, quota)| Quota {
+                tag: tag.to_owned(),
+                limit: std::time::Duration::from_secs(u64::from(quota.minutes) * 60),
+                period: match quota.period {
+                    QuotaPeriod::Day => HabitPeriod::Day,
+                    QuotaPeriod::Week => HabitPeriod::Week,
+                    QuotaPeriod::Month => HabitPeriod::Month,
+                },
+            })
+            .collect();
+        let input = QuotaUsageUseCaseInput {
+            quotas,
+            now: Local::now().naive_local(),
+            sequential_id,
+        };
+        <Cli<TR> as QuotaUsageUseCase>::execute(self, input)
+    }
+
+    /// warn_quotas warns of quotas which tags of the task are past after time is logged on it.
+    #[cfg(feature = "es")]
+    fn warn_quotas(&self, id: SequentialID) {
+        // NOTE: the time is logged already, so a failure is reported but not fatal.
+        match self.quota_usage(Some(id)) {
+            Ok(rows) => {
+                for row in rows.iter().filter(|r| r.is_over()) {
+                    eprintln!(
+                        "warning: {} hours are logged on `{}` this {}, past the quota of {}.",
+                        report::hours(row.used),
+                        row.tag,
+                        row.period,
+                        report::hours(row.limit)
+                    );
+                }
+            }
+            Err(err) => eprintln!("Failed to check quotas: {}.", err),
+        }
+    }
+
+    /// start_timer starts the timer of the task.
+    #[cfg(feature = "es")]
+    fn start_timer(&self, id: &TaskRef) {
+        let input = StartTimerUseCaseInput {
+            sequential_id: SequentialID::new(self.resolve(id, ES_SELECTION)),
+        };
+        let id = <Cli<TR> as StartTimerUseCase>::execute(self, input).unwrap_or_else(|err| {
+            eprintln!("Failed to start the timer: {}.", err);
+            process::exit(1);
+        });
+        println!("Start the timer of the task for id `{}`.", id);
+    }
+
+    /// stop_timer stops the timer of the task, logging the time since it started.
+    #[cfg(feature = "es")]
+    fn stop_timer(&self, id: &TaskRef) {
+        let sequential_id = SequentialID::new(self.resolve(id, ES_SELECTION));
+        let input = StopTimerUseCaseInput { sequential_id };
+        let elapsed = <Cli<TR> as StopTimerUseCase>::execute(self, input).unwrap_or_else(|err| {
+            eprintln!("Failed to stop the timer: {}.", err);
+            process::exit(1);
+        });
+        println!(
+            "Stop the timer of the task for id `{}` after {}.",
+            sequential_id,
+            table::format_elapsed(elapsed)
+        );
+        self.warn_quotas(sequential_id);
+    }
+
+    /// pomodoro runs cycles of work and a break in the foreground. Work of each cycle is timed
+    /// by the timer of the task, so that an interrupted pomodoro can still be logged by `stop`.
+    #[cfg(feature = "es")]
+    fn pomodoro(&self, id: &TaskRef, work: u64, rest: u64, cycles: u32) {
+        let sequential_id = SequentialID::new(self.resolve(id, ES_SELECTION));
+        eprintln!(
+            "Press Ctrl-C to quit. `taskmr stop {}` logs the work of an interrupted pomodoro.",
+            sequential_id
+        );
+        for cycle in 1..=cycles {
+            let input = StartTimerUseCaseInput { sequential_id };
+            <Cli<TR> as StartTimerUseCase>::execute(self, input).unwrap_or_else(|err| {
+                eprintln!("Failed to start the pomodoro: {}.", err);
+                process::exit(1);
+            });
+            println!("Work on the task for id `{}` for {}m.", sequential_id, work);
+            std::thread::sleep(std::time::Duration::from_secs(work * 60));
+
+            let input = CompletePomodoroUseCaseInput { sequential_id };
+            let count =
+                <Cli<TR> as CompletePomodoroUseCase>::execute(self, input).unwrap_or_else(|err| {
+                    eprintln!("Failed to complete the pomodoro: {}.", err);
+                    process::exit(1);
+                });
+            println!(
+                "Complete the pomodoro {} of the task for id `{}`.",
+                count, sequential_id
+            );
+            self.warn_quotas(sequential_id);
+
+            if cycle < cycles && rest > 0 {
+                println!("Take a break for {}m.", rest);
+                std::thread::sleep(std::time::Duration::from_secs(rest * 60));
+            }
+        }
+    }
+
+    /// lint_title warns of the rules which the title breaks, or exits if `level` is `error`.
+    fn lint_title(&self, title: &str) {
+        let violations = self.lint_rules().check(title);
+        for violation in &violations {
+            match self.config.lint.level {
+                LintLevel::Warn => eprintln!("warning: {}.", violation),
+                LintLevel::Error => eprintln!("Failed to accept the title: {}.", violation),
+            }
+        }
+        if !violations.is_empty() && self.config.lint.level == LintLevel::Error {
+            process::exit(1);
+        }
+    }
+
+    /// lint prints the rules which titles of open tasks break, and exits with 1 if any.
+    fn lint(&self) {
+        let tasks = self.open_tasks().unwrap_or_else(|err| {
+            eprintln!("Failed to load tasks: {}.", err);
+            process::exit(1);
+        });
+        let rules = self.lint_rules();
+        let mut broken = 0;
+        for task in &tasks {
+            let violations = rules.check(&task.title);
+            if !violations.is_empty() {
+                broken += 1;
+            }
+            for violation in violations {
+                println!("#{} {}: {}.", task.id, task.title, violation);
+            }
+        }
+
+        if broken > 0 {
+            println!("{} of {} open titles break the rules.", broken, tasks.len());
+            process::exit(1);
+        }
+        println!("All {} open titles follow the rules.", tasks.len());
+    }
+
+    /// legacy_add adds a task to the simple table.
+    #[cfg(feature = "legacy")]
+    fn legacy_add(&mut self, a: &AddArgs) {
+        #[cfg(feature = "es")]
+        if !a.tags.is_empty()
+            || a.description.is_some()
+            || a.urgency.is_some()
+            || a.private
+            || a.energy.is_some()
+            || a.kind == TaskKindArg::Habit
+            || a.target.is_some()
+            || a.due.is_some()
+            || a.someday
+            || a.parent.is_some()
+            || a.echo
+        {
+            eprintln!(
+                "--tag, --description, --urgency, --private, --energy, --kind habit, --target, --due, --someday, --parent and --echo are supported only by the es engine."
+            );
+            process::exit(1);
+        }
+        self.lint_title(&a.title);
+
+        let input = AddTaskUseCaseInput {
+            title: a.title.to_owned(),
+            priority: a.priority.or(self.config.defaults.priority),
+            cost: a.cost.or(self.config.defaults.cost),
+        };
+        let id = self.add_task_usecase.execute(input).unwrap();
+        self.printer().print_saved("Add", id.get()).unwrap();
+    }
+
+    /// es_add adds a task to the event store.
+    #[cfg(feature = "es")]
+    fn es_add(&mut self, a: &AddArgs) {
+        if a.kind == TaskKindArg::Task && a.target.is_some() {
+            eprintln!("--target is given only to a habit added with --kind habit.");
+            process::exit(1);
+        }
+        self.lint_title(&a.title);
+
+        let mut tags = a.tags.to_owned();
+        if let Some(energy) = a.energy {
+            tags.push(Energy::from(energy).tag().as_str().to_owned());
+        }
+        if !a.no_context {
+            tags.extend(self.config.scope_tags().iter().cloned());
+            tags.extend(self.context_tags());
+        }
+
+        let input = ESAddTaskUseCaseInput {
+            title: a.title.to_owned(),
+            description: a.description.to_owned(),
+            priority: a.priority.or(self.config.defaults.priority),
+            urgency: a.urgency,
+            cost: a.cost.or(self.config.defaults.cost),
+            tags,
+            private: a.private,
+            habit: match a.kind {
+                TaskKindArg::Task => None,
+                TaskKindArg::Habit => a.target,
+            },
+            due: a.due,
+            someday: a.someday,
+            parent: a
+                .parent
+                .as_ref()
+                .map(|r| SequentialID::new(self.resolve(r, ES_SELECTION))),
+            rules: self.rules(),
+        };
+        let id = <Cli<TR> as ESAddTaskUseCase>::execute(self, input).unwrap_or_else(|err| {
+            eprintln!("Failed to add the task: {}.", err);
+            process::exit(1);
+        });
+        if a.echo {
+            self.show(id, false);
+        } else {
+            self.printer().print_saved("Add", id.to_i64()).unwrap();
+        }
+    }
+
+    /// legacy_close closes tasks in the simple table.
+    #[cfg(feature = "legacy")]
+    fn legacy_close(&mut self, a: &CloseArgs) {
+        #[cfg(feature = "es")]
+        if a.follow_up.is_some() || a.cascade {
+            eprintln!("--follow-up and --cascade are supported only by the es engine.");
+            process::exit(1);
+        }
+
+        let ids: Vec<i64> = a
+            .ids
+            .iter()
+            .map(|r| self.resolve(r, LEGACY_SELECTION))
+            .collect();
+        let mut is_all_success = true;
+        for id in ids {
+            match self
+                .close_task_usecase
+                .execute(CloseTaskUseCaseInput { id })
+            {
+                Ok(r_id) => self.printer().print_done("Close", r_id.get()).unwrap(),
+                Err(err) => {
+                    is_all_success = false;
+                    eprintln!("Failed to close the task: {}.", err)
+                }
+            }
+        }
+
+        if !is_all_success {
+            process::exit(1);
+        }
+    }
+
+    /// es_close closes tasks in the event store.
+    #[cfg(feature = "es")]
+    fn es_close(&mut self, a: &CloseArgs) {
+        let ids: Vec<i64> = a
+            .ids
+            .iter()
+            .map(|r| self.resolve(r, ES_SELECTION))
+            .collect();
+        if let Some(title) = &a.follow_up {
+            if ids.len() != 1 {
+                eprintln!("--follow-up needs exactly one task to close.");
+                process::exit(1);
+            }
+            let input = FollowUpUseCaseInput {
+                sequential_id: SequentialID::new(ids[0]),
+                unlock: a.unlock,
+                title: title.clone(),
+            };
+            match <Cli<TR> as FollowUpUseCase>::execute(self, input) {
+                Ok(dto) => {
+                    self.printer()
+                        .print_done("Close", dto.closed.to_i64())
+                        .unwrap();
+                    self.printer()
+                        .print_done("Add", dto.follow_up.to_i64())
+                        .unwrap();
+                }
+                Err(err) => {
+                    eprintln!("Failed to close the task: {}.", err);
+                    process::exit(1);
+                }
+            }
+            return;
+        }
+
+        let mut is_all_success = true;
+        for id in ids {
+            match <Cli<TR> as ESCloseTaskUseCase>::execute(
+                self,
+                ESCloseTaskUseCaseInput {
+                    sequential_id: SequentialID::new(id),
+                    unlock: a.unlock,
+                    cascade: a.cascade,
+                },
+            ) {
+                Ok(r_id) => self.printer().print_done("Close", r_id.to_i64()).unwrap(),
+                Err(err) => {
+                    is_all_success = false;
+                    eprintln!("Failed to close the task: {}.", err)
+                }
+            }
+        }
+
+        if !is_all_success {
+            process::exit(1);
+        }
+    }
+
+    /// legacy_delete deletes tasks in the simple table.
+    #[cfg(feature = "legacy")]
+    fn legacy_delete(&mut self, a: &DeleteArgs) {
+        let ids: Vec<i64> = a
+            .ids
+            .iter()
+            .map(|r| self.resolve(r, LEGACY_SELECTION))
+            .collect();
+        let mut is_all_success = true;
+        for id in ids {
+            match self
+                .delete_task_usecase
+                .execute(DeleteTaskUseCaseInput { id })
+            {
+                Ok(r_id) => self.printer().print_done("Delete", r_id.get()).unwrap(),
+                Err(err) => {
+                    is_all_success = false;
+                    eprintln!("Failed to delete the task: {}.", err)
+                }
+            }
+        }
+
+        if !is_all_success {
+            process::exit(1);
+        }
+    }
+
+    /// es_delete deletes tasks in the event store.
+    #[cfg(feature = "es")]
+    fn es_delete(&mut self, a: &DeleteArgs) {
+        let ids: Vec<i64> = a
+            .ids
+            .iter()
+            .map(|r| self.resolve(r, ES_SELECTION))
+            .collect();
+        let mut is_all_success = true;
+        for id in ids {
+            match <Cli<TR> as ESDeleteTaskUseCase>::execute(
+                self,
+                ESDeleteTaskUseCaseInput {
+                    sequential_id: SequentialID::new(id),
+                },
+            ) {
+                Ok(r_id) => self.printer().print_done("Delete", r_id.to_i64()).unwrap(),
+                Err(err) => {
+                    is_all_success = false;
+                    eprintln!("Failed to delete the task: {}.", err)
+                }
+            }
+        }
+
+        if !is_all_success {
+            process::exit(1);
+        }
+    }
+
+    /// legacy_archive archives closed tasks in the simple table, which deletes them.
+    /// The cutoff is computed by SQLite, since timestamps of the table are its local time.
+    #[cfg(feature = "legacy")]
+    fn legacy_archive(&mut self, a: &ArchiveArgs) {
+        let updated_before: String = self
+            .open_connection()
+            .query_row(
+                "SELECT datetime(CURRENT_TIMESTAMP, 'localtime', ?1)",
+                [format!("-{} seconds", a.older_than.duration().as_secs())],
+                |row| row.get(0),
+            )
+            .unwrap_or_else(|err| {
+                eprintln!("Failed to archive tasks: {}.", err);
+                process::exit(1);
+            });
+        let ids = self
+            .archive_task_usecase
+            .execute(ArchiveTaskUseCaseInput { updated_before })
+            .unwrap_or_else(|err| {
+                eprintln!("Failed to archive tasks: {}.", err);
+                process::exit(1);
+            });
+        print_archived(ids.iter().map(|id| id.get()));
+    }
+
+    /// es_archive archives closed tasks in the event store.
+    #[cfg(feature = "es")]
+    fn es_archive(&mut self, a: &ArchiveArgs) {
+        let input = ArchiveUseCaseInput {
+            closed_before: chrono::Utc::now().naive_utc()
+                - chrono::Duration::from_std(a.older_than.duration())
+                    .unwrap_or(chrono::Duration::MAX),
+        };
+        let ids = <Cli<TR> as ArchiveUseCase>::execute(self, input).unwrap_or_else(|err| {
+            eprintln!("Failed to archive tasks: {}.", err);
+            process::exit(1);
+        });
+        print_archived(ids.iter().map(|id| id.to_i64()));
+    }
+
+    /// demo fills the demo database with sample tasks and runs DEMO_STEPS on them.
+    #[cfg(feature = "es")]
+    fn demo(&mut self) {
+        let input = SeedDemoUseCaseInput {
+            today: Local::now().date_naive(),
+        };
+        <Cli<TR> as SeedDemoUseCase>::execute(self, input).unwrap_or_else(|err| {
+            eprintln!("Failed to add sample tasks: {}.", err);
+            process::exit(1);
+        });
+        println!("This is a throwaway database of sample tasks, which is gone when the demo ends.");
+        for step in DEMO_STEPS {
+            println!();
+            println!("$ taskmr {}", step);
+            let args = std::iter::once("taskmr").chain(step.split_whitespace());
+            self.handle(Command::parse_from(args));
+        }
+        println!();
+        println!(
+            "Run `taskmr init` to start your own list, and `taskmr help` to see every command."
+        );
+    }
+
+    /// random picks an eligible open task at random and focuses on it.
+    fn random(&self, pick: Pick) {
+        let tasks = self.open_tasks().unwrap_or_else(|err| {
+            eprintln!("Failed to load tasks: {}.", err);
+            process::exit(1);
+        });
+        let candidates: Vec<Candidate> = tasks
+            .iter()
+            .map(|t| Candidate {
+                id: t.id,
+                priority: t.priority,
+                cost: t.cost,
+            })
+            .collect();
+
+        let Some(picked) = pick.choose(&pick.eligible(&candidates), fastrand::u64(..)) else {
+            println!("No open task is eligible.");
+            return;
+        };
+        self.focus_store(OPEN_TASKS_SELECTION)
+            .save(&[picked.id])
+            .unwrap_or_else(|err| {
+                eprintln!("Failed to focus on the task: {}.", err);
+                process::exit(1);
+            });
+        self.save_selection(OPEN_TASKS_SELECTION, &[picked.id]);
+
+        let title = tasks
+            .iter()
+            .find(|t| t.id == picked.id)
+            .map_or("", |t| t.title.as_str());
+        println!(
+            "Picked #{} {} (cost {}). It is %1 until the next numbered list.",
+            picked.id, title, picked.cost
+        );
+    }
+
+    /// next prints the task which fits the energy and the time best, and saves it as the selection.
+    #[cfg(feature = "es")]
+    fn next(&self, energy: Option<EnergyArg>, time: Option<Span>) {
+        let input = NextTaskUseCaseInput {
+            energy: energy.map(Energy::from),
+            time: time.map(|t| t.duration()),
+            minutes_per_cost: self.config.next.minutes_per_cost,
+            strategy: scoring_strategy(&self.config.score),
+        };
+        let suggestions =
+            <Cli<TR> as NextTaskUseCase>::execute(self, input).unwrap_or_else(|err| {
+                eprintln!("Failed to suggest a task: {}.", err);
+                process::exit(1);
+            });
+        let Some(best) = suggestions.first() else {
+            println!("No open task fits now.");
+            return;
+        };
+        self.save_selection(ES_SELECTION, &[best.id]);
+
+        println!(
+            "Next: #{} {} (cost {}, about {}m left, score {}). It is %1 until the next numbered list.",
+            best.id,
+            best.title,
+            best.cost,
+            best.remaining.as_secs().div_ceil(60),
+            best.score
+        );
+        if suggestions.len() > 1 {
+            println!("{} other tasks fit too.", suggestions.len() - 1);
+        }
+    }
+
+    /// simulate prints which open tasks will be done by the day with the capacity added.
+    #[cfg(feature = "es")]
+    fn simulate(&mut self, until: NaiveDate, add_capacity: Option<config::Quota>) {
+        let minutes_per_cost = self.config.next.minutes_per_cost;
+        let added_cost_per_day = add_capacity.map_or(0.0, |capacity| {
+            let days = match capacity.period {
+                QuotaPeriod::Day => 1.0,
+                QuotaPeriod::Week => 7.0,
+                QuotaPeriod::Month => 30.0,
+            };
+            f64::from(capacity.minutes) / f64::from(minutes_per_cost.max(1)) / days
+        });
+        let input = SimulateUseCaseInput {
+            today: Local::now().date_naive(),
+            until,
+            added_cost_per_day,
+            minutes_per_cost,
+            strategy: scoring_strategy(&self.config.score),
+        };
+        let simulation = <Cli<TR> as SimulateUseCase>::execute(self, input).unwrap_or_else(|err| {
+            eprintln!("Failed to simulate: {}.", err);
+            process::exit(1);
+        });
+        self.table_printer.print_simulation(&simulation).unwrap();
+    }
+
+    /// forecast prints completion dates of the tasks, or open tasks matching the query.
+    #[cfg(feature = "es")]
+    fn forecast(&mut self, ids: &[TaskRef], query: &Option<TitlePattern>, trials: usize) {
+        let sequential_ids = match query {
+            Some(query) => {
+                let tasks = <Cli<TR> as ESListTaskUseCase>::execute(self, Default::default())
+                    .unwrap_or_else(|err| {
+                        eprintln!("Failed to list tasks: {}.", err);
+                        process::exit(1);
+                    });
+                tasks
+                    .into_iter()
+                    .filter(|t| t.habit.is_none() && query.is_match(&t.title))
+                    .map(|t| SequentialID::new(t.id))
+                    .collect()
+            }
+            None => ids
+                .iter()
+                .map(|r| SequentialID::new(self.resolve(r, ES_SELECTION)))
+                .collect(),
+        };
+        let input = ForecastUseCaseInput {
+            sequential_ids,
+            now: chrono::Utc::now().naive_utc(),
+            trials,
+            seed: fastrand::u64(..),
+        };
+        let forecast = <Cli<TR> as ForecastUseCase>::execute(self, input).unwrap_or_else(|err| {
+            eprintln!("Failed to forecast: {}.", err);
+            process::exit(1);
+        });
+        self.table_printer.print_forecast(&forecast).unwrap();
+    }
+
+    /// tag tags or untags the task in the event store.
+    #[cfg(feature = "es")]
+    fn tag(&mut self, id: &TaskRef, tags: &[String], tagged: bool) {
+        let verb = if tagged { "Tag" } else { "Untag" };
+        let input = TagTaskUseCaseInput {
+            sequential_id: SequentialID::new(self.resolve(id, ES_SELECTION)),
+            tags: tags.to_vec(),
+            tagged,
+        };
+        let id = <Cli<TR> as TagTaskUseCase>::execute(self, input).unwrap_or_else(|err| {
+            eprintln!("Failed to {} the task: {}.", verb.to_lowercase(), err);
+            process::exit(1);
+        });
+        self.printer().print_done(verb, id.to_i64()).unwrap();
+    }
+
+    /// retag moves the tag of open tasks matching the query, and prints the affected ids.
+    #[cfg(feature = "es")]
+    fn retag(&self, from: &str, to: &str, query: &Option<TitlePattern>) {
+        let sequential_ids = query.as_ref().map(|query| {
+            let input = ESListTaskUseCaseInput {
+                tags: vec![from.to_owned()],
+                include_private: true,
+                ..Default::default()
+            };
+            let tasks =
+                <Cli<TR> as ESListTaskUseCase>::execute(self, input).unwrap_or_else(|err| {
+                    eprintln!("Failed to list tasks: {}.", err);
+                    process::exit(1);
+                });
+            tasks
+                .into_iter()
+                .filter(|t| query.is_match(&t.title))
+                .map(|t| SequentialID::new(t.id))
+                .collect()
+        });
+
+        let input = RetagUseCaseInput {
+            from: from.to_owned(),
+            to: to.to_owned(),
+            sequential_ids,
+        };
+        let retagged = <Cli<TR> as RetagUseCase>::execute(self, input).unwrap_or_else(|err| {
+            eprintln!("Failed to retag tasks: {}.", err);
+            process::exit(1);
+        });
+
+        let join = |ids: &[i64]| {
+            ids.iter()
+                .map(|id| id.to_string())
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+        if retagged.retagged.is_empty() {
+            println!("No open task has the tag `{}`.", from);
+        } else {
+            println!(
+                "Retagged {} tasks from `{}` to `{}`: {}.",
+                retagged.retagged.len(),
+                from,
+                to,
+                join(&retagged.retagged)
+            );
+        }
+        if !retagged.locked.is_empty() {
+            println!("Skipped locked tasks: {}.", join(&retagged.locked));
+        }
+    }
+
+    /// tidy prints tasks to tidy up and asks whether to close each of them.
+    #[cfg(feature = "es")]
+    fn tidy(&mut self, stale: Span, dry_run: bool) {
+        let input = TidyUseCaseInput {
+            stale_before: chrono::Utc::now().naive_utc()
+                - chrono::Duration::from_std(stale.duration()).unwrap_or(chrono::Duration::MAX),
+        };
+        let findings = <Cli<TR> as TidyUseCase>::execute(self, input).unwrap_or_else(|err| {
+            eprintln!("Failed to find tasks to tidy up: {}.", err);
+            process::exit(1);
+        });
+        if findings.is_empty() {
+            println!("Nothing to tidy up.");
+            return;
+        }
+
+        let mut lines = std::io::stdin().lines();
+        for finding in findings {
+            let task = match &finding {
+                TidyFinding::Duplicate {
+                    original,
+                    duplicate,
+                } => {
+                    println!(
+                        "#{} `{}` looks like a duplicate of #{} `{}`.",
+                        duplicate.id, duplicate.title, original.id, original.title
+                    );
+                    duplicate
+                }
+                TidyFinding::Stale { task, created_at } => {
+                    println!(
+                        "#{} `{}` has been open since {}.",
+                        task.id,
+                        task.title,
+                        Local.from_utc_datetime(created_at).date_naive()
+                    );
+                    task
+                }
+                TidyFinding::ZeroCost { task } => {
+                    println!("#{} `{}` costs nothing.", task.id, task.title);
+                    task
+                }
+            };
+            if dry_run {
+                continue;
+            }
+
+            print!("Close #{}? [y/N/q] ", task.id);
+            std::io::stdout().flush().unwrap();
+            let answer = match lines.next() {
+                Some(Ok(line)) => line.trim().to_lowercase(),
+                _ => return,
+            };
+            match answer.as_str() {
+                "y" | "yes" => match self.close_task(task.id) {
+                    Ok(id) => self.printer().print_done("Close", id).unwrap(),
+                    Err(err) => eprintln!("Failed to close the task: {}.", err),
+                },
+                "q" | "quit" => return,
+                _ => {}
+            }
+        }
+    }
+
+    /// someday_list lists open tasks deferred to someday.
+    #[cfg(feature = "es")]
+    fn someday_list(&mut self, number: bool) {
+        let input = ESListTaskUseCaseInput {
+            include_someday: true,
+            ..Default::default()
+        };
+        let mut tasks =
+            <Cli<TR> as ESListTaskUseCase>::execute(self, input).unwrap_or_else(|err| {
+                eprintln!("Failed to list tasks: {}.", err);
+                process::exit(1);
+            });
+        tasks.retain(|t| t.is_someday);
+        if tasks.is_empty() {
+            println!("Nothing is deferred to someday.");
+            return;
+        }
+
+        if number {
+            let ids: Vec<i64> = tasks.iter().map(|t| t.id).collect();
+            self.save_selection(ES_SELECTION, &ids);
+        }
+        self.printer().set_numbered(number);
+        self.printer().print_es(tasks, None).unwrap();
+    }
+
+    /// promote moves the task deferred to someday into the active backlog.
+    #[cfg(feature = "es")]
+    fn promote(&mut self, id: &TaskRef) {
+        let input = PromoteUseCaseInput {
+            sequential_id: SequentialID::new(self.resolve(id, ES_SELECTION)),
+        };
+        let id = <Cli<TR> as PromoteUseCase>::execute(self, input).unwrap_or_else(|err| {
+            eprintln!("Failed to promote the task: {}.", err);
+            process::exit(1);
+        });
+        self.printer().print_done("Promote", id.to_i64()).unwrap();
+    }
+
+    /// triage_inbox asks what to do with each item in the inbox, from the oldest.
+    #[cfg(feature = "es")]
+    fn triage_inbox(&mut self) {
+        let items = <Cli<TR> as ListInboxUseCase>::execute(self).unwrap_or_else(|err| {
+            eprintln!("Failed to load the inbox: {}.", err);
+            process::exit(1);
+        });
+        if items.is_empty() {
+            println!("The inbox is empty.");
+            return;
+        }
+
+        let mut lines = std::io::stdin().lines();
+        for item in items {
+            println!(
+                "#{} `{}` captured on {}.",
+                item.id,
+                item.title,
+                Local.from_utc_datetime(&item.captured_at).date_naive()
+            );
+            let Some(answer) = ask(&mut lines, "[k]eep, [d]elegate, d[r]op, [s]kip or [q]uit? ")
+            else {
+                return;
+            };
+            let action = match answer.to_lowercase().as_str() {
+                "k" | "keep" => {
+                    let (Some(priority), Some(cost), Some(tags)) = (
+                        ask(&mut lines, "Priority: "),
+                        ask(&mut lines, "Cost: "),
+                        ask(&mut lines, "Tags: "),
+                    ) else {
+                        return;
+                    };
+                    let score = |s: String, default: Option<i32>| {
+                        if s.is_empty() {
+                            Ok(default)
+                        } else {
+                            s.parse().map(Some)
+                        }
+                    };
+                    match (
+                        score(priority, self.config.defaults.priority),
+                        score(cost, self.config.defaults.cost),
+                    ) {
+                        (Ok(priority), Ok(cost)) => TriageAction::Keep {
+                            priority,
+                            urgency: None,
+                            cost,
+                            tags: tags.split_whitespace().map(String::from).collect(),
+                        },
+                        _ => {
+                            eprintln!("Priority and cost must be integers, skip the item.");
+                            continue;
+                        }
+                    }
+                }
+                "d" | "delegate" => {
+                    let (Some(person), Some(follow_up_on)) = (
+                        ask(&mut lines, "Person: "),
+                        ask(&mut lines, "Follow up on (YYYY-MM-DD): "),
+                    ) else {
+                        return;
+                    };
+                    let follow_up_on = if follow_up_on.is_empty() {
+                        None
+                    } else {
+                        match follow_up_on.parse() {
+                            Ok(day) => Some(day),
+                            Err(err) => {
+                                eprintln!("Failed to read the day: {}, skip the item.", err);
+                                continue;
+                            }
+                        }
+                    };
+                    TriageAction::Delegate {
+                        person,
+                        follow_up_on,
+                    }
+                }
+                "r" | "drop" => TriageAction::Drop,
+                "q" | "quit" => return,
+                _ => continue,
+            };
+
+            let input = TriageUseCaseInput {
+                sequential_id: SequentialID::new(item.id),
+                action,
+            };
+            match <Cli<TR> as TriageUseCase>::execute(self, input) {
+                Ok(id) => println!("Triage the item for id `{}`.", id),
+                Err(err) => eprintln!("Failed to triage the item: {}.", err),
+            }
+        }
+    }
+
+    /// lock locks or unlocks tasks in the event store.
+    #[cfg(feature = "es")]
+    fn lock(&mut self, ids: &[TaskRef], locked: bool) {
+        let ids: Vec<i64> = ids.iter().map(|r| self.resolve(r, ES_SELECTION)).collect();
+        let verb = if locked { "Lock" } else { "Unlock" };
+        let mut is_all_success = true;
+        for id in ids {
+            match <Cli<TR> as LockTaskUseCase>::execute(
+                self,
+                LockTaskUseCaseInput {
+                    sequential_id: SequentialID::new(id),
+                    locked,
+                },
+            ) {
+                Ok(r_id) => self.printer().print_done(verb, r_id.to_i64()).unwrap(),
+                Err(err) => {
+                    is_all_success = false;
+                    eprintln!("Failed to {} the task: {}.", verb.to_lowercase(), err)
+                }
+            }
+        }
+
+        if !is_all_success {
+            process::exit(1);
+        }
+    }
+
+    /// legacy_edit edits a task in the simple table.
+    #[cfg(feature = "legacy")]
+    fn legacy_edit(&mut self, a: &EditArgs) {
+        #[cfg(feature = "es")]
+        if a.description.is_some()
+            || a.no_description
+            || a.urgency.is_some()
+            || a.private
+            || a.public
+            || a.due.is_some()
+            || a.no_due
+            || a.echo
+        {
+            eprintln!(
+                "--description, --no-description, --urgency, --private, --public, --due, --no-due and --echo are supported only by the es engine."
+            );
+            process::exit(1);
+        }
+        if let Some(title) = &a.title {
+            self.lint_title(title);
+        }
+
+        let input = EditTaskUseCaseInput {
+            id: self.resolve(&a.id, LEGACY_SELECTION),
+            title: a.title.to_owned(),
+            priority: a.priority.to_owned(),
+            cost: a.cost.to_owned(),
+        };
+        let id = self.edit_task_usecase.execute(input).unwrap_or_else(|err| {
+            eprintln!("Failed to edit the task: {}.", err);
+            process::exit(1);
+        });
+        self.printer().print_saved("Edit", id.get()).unwrap();
+    }
+
+    /// es_edit edits a task in the event store.
+    #[cfg(feature = "es")]
+    fn es_edit(&mut self, a: &EditArgs) {
+        if let Some(title) = &a.title {
+            self.lint_title(title);
+        }
+        let input = ESEditTaskUseCaseInput {
+            sequential_id: SequentialID::new(self.resolve(&a.id, ES_SELECTION)),
+            title: a.title.to_owned(),
+            description: match (&a.description, a.no_description) {
+                (Some(description), _) => Some(Some(description.to_owned())),
+                (None, true) => Some(None),
+                (None, false) => None,
+            },
+            priority: a.priority.to_owned(),
+            urgency: a.urgency.to_owned(),
+            cost: a.cost.to_owned(),
+            unlock: a.unlock,
+            private: (a.private || a.public).then_some(a.private),
+            due: match (a.due, a.no_due) {
+                (Some(due), _) => Some(Some(due)),
+                (None, true) => Some(None),
+                (None, false) => None,
+            },
+        };
+        let id = <Cli<TR> as ESEditTaskUseCase>::execute(self, input).unwrap_or_else(|err| {
+            eprintln!("Failed to edit the task: {}.", err);
+            process::exit(1);
+        });
+        if a.echo {
+            self.show(id, false);
+        } else {
+            self.printer().print_saved("Edit", id.to_i64()).unwrap();
+        }
+    }
+
+    /// import adds tasks from the file to the event store, or only validates them.
+    /// All invalid lines are reported, and nothing is added if there is one.
+    #[cfg(feature = "es")]
+    fn import(&mut self, file: &Path, validate_only: bool, chunk_size: usize, resume: bool) {
+        let text = fs::read_to_string(file).unwrap_or_else(|err| {
+            eprintln!("Failed to read {}: {}.", file.display(), err);
+            process::exit(1);
+        });
+        let is_csv = file
+            .extension()
+            .is_some_and(|e| e.eq_ignore_ascii_case("csv"));
+        let (rows, mut errors) = if is_csv {
+            import::read_csv_rows(&text)
+        } else {
+            import::read_rows(&text)
+        };
+        let count = rows.len() + errors.len();
+        let input = ImportUseCaseInput {
+            rows,
+            validate_only: validate_only || !errors.is_empty(),
+            chunk_size,
+            digest: Some(import::digest(&text)),
+            resume,
+        };
+        let shows_progress = io::IsTerminal::is_terminal(&io::stderr());
+        let mut has_added = false;
+        let result = <Cli<TR> as ImportUseCase>::execute_in_chunks(self, input, &mut |progress| {
+            has_added = true;
+            if shows_progress {
+                eprint!("\r{}", import::progress_bar(&progress));
+                if progress.imported == progress.total {
+                    eprintln!();
+                }
+            }
+            Ok(())
+        });
+        let output = result.unwrap_or_else(|err| {
+            if shows_progress && has_added {
+                eprintln!();
+            }
+            eprintln!("Failed to import tasks: {}.", err);
+            if has_added {
+                eprintln!("Run it again with --resume to continue after the last added chunk.");
+            }
+            process::exit(1);
+        });
+        errors.extend(output.errors);
+        errors.sort_by_key(|e| e.line);
+
+        if !errors.is_empty() {
+            for e in &errors {
+                eprintln!("line {}: {}.", e.line, e.message);
+            }
+            eprintln!(
+                "Failed to import tasks: {} of {} rows are invalid, so that nothing is added.",
+                errors.len(),
+                count
+            );
+            process::exit(1);
+        }
+        // NOTE: a file imported again skips all of its rows, so that only the first ones are told.
+        const SHOWN_DUPLICATES: usize = 10;
+        for d in output.duplicates.iter().take(SHOWN_DUPLICATES) {
+            match d.of {
+                DuplicateOfDTO::Task(id) => println!(
+                    "Skip line {}: the task for id `{}` has the same title.",
+                    d.line, id
+                ),
+                DuplicateOfDTO::Line(line) => {
+                    println!("Skip line {}: line {} has the same title.", d.line, line)
+                }
+            }
+        }
+        if output.duplicates.len() > SHOWN_DUPLICATES {
+            println!(
+                "Skip {} more lines with the same titles.",
+                output.duplicates.len() - SHOWN_DUPLICATES
+            );
+        }
+        if validate_only {
+            println!("All {} rows are valid.", count);
+        } else if let Some(line) = output.resumed_after {
+            println!("Import {} tasks after line {}.", output.imported, line);
+        } else {
+            println!("Import {} tasks.", output.imported);
+        }
+    }
+
+    /// show prints the full detail of a task in the event store.
+    /// The events are left out when echoing a saved task, which only needs its fields.
+    #[cfg(feature = "es")]
+    fn show(&mut self, sequential_id: SequentialID, with_events: bool) {
+        let mut task =
+            <Cli<TR> as ShowTaskUseCase>::execute(self, ShowTaskUseCaseInput { sequential_id })
+                .unwrap_or_else(|err| {
+                    eprintln!("Failed to show the task: {}.", err);
+                    process::exit(1);
+                });
+        if !with_events {
+            task.events.clear();
+        }
+        self.printer().print_task_detail(&task).unwrap();
+    }
+
+    /// legacy_list lists tasks in the simple table.
+    #[cfg(feature = "legacy")]
+    fn legacy_list(&mut self, a: &ListArgs) {
+        #[cfg(feature = "es")]
+        if !a.tags.is_empty() || a.group_by.is_some() || a.waiting_on {
+            eprintln!("--tag, --group-by and --waiting-on are supported only by the es engine.");
+            process::exit(1);
+        }
+
+        let mut task_dto = self
+            .list_task_usecase
+            .execute(ListTaskUseCaseInput {
+                order: a.sort.map(TaskOrder::from).unwrap_or_default(),
+                updated_since: a.updated_since.clone(),
+                include_deleted: a.include_deleted,
+            })
+            .unwrap();
+        if let Some(pattern) = &a.regex {
+            task_dto.retain(|t| pattern.is_match(&t.title));
+        }
+        #[cfg(feature = "scripting")]
+        let task_dto = self.apply_script_filter(&a.filter, task_dto, |t| ScriptTask {
+            id: t.id,
+            title: t.title.clone(),
+            priority: t.priority,
+            cost: t.cost,
+            tags: vec![],
+            waiting_on: None,
+            nudge: false,
+        });
+        if a.number {
+            let ids: Vec<i64> = task_dto.iter().map(|t| t.id).collect();
+            self.save_selection(LEGACY_SELECTION, &ids);
+        }
+        self.printer().set_numbered(a.number);
+        self.printer()
+            .set_max_width(table_width(a.max_width, a.full));
+        let summary = (a.summary || self.config.list.summary)
+            .then(|| list_task_usecase::summarize(&task_dto));
+        self.printer().print(task_dto, summary.as_ref()).unwrap();
+    }
+
+    /// legacy_search searches titles of tasks in the simple table, which has no notes.
+    #[cfg(feature = "legacy")]
+    fn legacy_search(&mut self, a: &SearchArgs) {
+        if a.field == Some(SearchFieldArg::Notes) {
+            eprintln!("--in notes is supported only by the es engine.");
+            process::exit(1);
+        }
+
+        let task_dto = match self.search_task_usecase.execute(SearchTaskUseCaseInput {
+            query: a.query.join(" "),
+            include_closed: a.closed,
+        }) {
+            Ok(task_dto) => task_dto,
+            Err(err) => {
+                eprintln!("Failed to search tasks: {}.", err);
+                process::exit(1);
+            }
+        };
+        self.printer().print(task_dto, None).unwrap();
+    }
+
+    /// es_search searches titles and descriptions of tasks in the event store.
+    #[cfg(feature = "es")]
+    fn es_search(&mut self, a: &SearchArgs) {
+        let input = ESSearchTaskUseCaseInput {
+            query: a.query.join(" "),
+            field: a.field.map(SearchField::from).unwrap_or_default(),
+            include_closed: a.closed,
+            include_private: a.show_private,
+        };
+        let matches =
+            <Cli<TR> as ESSearchTaskUseCase>::execute(self, input).unwrap_or_else(|err| {
+                eprintln!("Failed to search tasks: {}.", err);
+                process::exit(1);
+            });
+        self.printer().print_search_matches(matches).unwrap();
+    }
+
+    /// es_list lists tasks in the event store.
+    #[cfg(feature = "es")]
+    fn es_list(&mut self, a: &ListArgs) {
+        #[cfg(feature = "legacy")]
+        if a.sort.is_some() || a.updated_since.is_some() {
+            eprintln!("--sort and --updated-since are supported only by the legacy engine.");
+            process::exit(1);
+        }
+
+        let mut tags = a.tags.to_owned();
+        if !a.no_context {
+            tags.extend(self.config.scope_tags().iter().cloned());
+        }
+
+        let mut task_dto_vec = <Cli<TR> as ESListTaskUseCase>::execute(
+            self,
+            ESListTaskUseCaseInput {
+                tags,
+                include_private: a.show_private,
+                include_deleted: a.include_deleted,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        if let Some(pattern) = &a.regex {
+            task_dto_vec.retain(|t| pattern.is_match(&t.title));
+        }
+        if a.waiting_on {
+            task_dto_vec.retain(|t| t.waiting_on.is_some());
+        }
+        #[cfg(feature = "scripting")]
+        let today = Local::now().date_naive();
+        #[cfg(feature = "scripting")]
+        let task_dto_vec = self.apply_script_filter(&a.filter, task_dto_vec, |t| ScriptTask {
+            id: t.id,
+            title: t.title.clone(),
+            priority: t.priority,
+            cost: t.cost,
+            tags: t.tags.clone(),
+            waiting_on: t.waiting_on.as_ref().map(|w| w.person.clone()),
+            nudge: t.waiting_on.as_ref().is_some_and(|w| w.is_due(today)),
+        });
+        self.printer().set_numbered(a.number);
+        self.printer()
+            .set_max_width(table_width(a.max_width, a.full));
+        let summary = (a.summary || self.config.list.summary)
+            .then(|| es_list_task_usecase::summarize(&task_dto_vec));
+
+        let group_by = if a.waiting_on {
+            Some(es_list_task_usecase::GroupBy::WaitingOn)
+        } else {
+            a.group_by.map(|g| g.to_group_by(&self.config.score))
+        };
+        let Some(group_by) = group_by else {
+            let tree = es_list_task_usecase::nest(task_dto_vec);
+            if a.number {
+                let ids: Vec<i64> = tree.iter().map(|n| n.task.id).collect();
+                self.save_selection(ES_SELECTION, &ids);
+            }
+            self.printer()
+                .print_es_tree(tree, summary.as_ref())
+                .unwrap();
+            return;
+        };
+
+        let groups = es_list_task_usecase::group(task_dto_vec, group_by);
+        if a.number {
+            let ids: Vec<i64> = groups
+                .iter()
+                .flat_map(|g| g.tasks.iter().map(|t| t.id))
+                .collect();
+            self.save_selection(ES_SELECTION, &ids);
+        }
+        self.printer()
+            .print_es_groups(groups, summary.as_ref())
+            .unwrap();
+    }
+
+    /// printer returns the printer of `--format` for results which scripts may read.
+    fn printer(&mut self) -> &mut dyn Printer {
+        match self.output_format {
+            OutputFormatArg::Table => &mut self.table_printer,
+            OutputFormatArg::Json => &mut self.json_printer,
         }
     }
 
     /// handle user input.
-    pub fn handle(&mut self) {
-        let args = Command::parse();
+    pub fn handle(&mut self, args: Command) {
+        self.output_format = args.format;
+
+        let engine = args
+            .engine
+            .map(Engine::from)
+            .or(self.config.engine)
+            .unwrap_or_default();
 
         match &args.command {
-            SubCommands::Add {
-                title,
-                priority,
-                cost,
+            SubCommands::Add(a) => {
+                (self.engine_handlers(engine).add)(self, a);
+                self.print_outstanding(engine, true);
+            }
+            #[cfg(feature = "es")]
+            SubCommands::Q { text, echo } => {
+                let quick_add = match QuickAdd::parse(&text.join(" "), Local::now().date_naive()) {
+                    Ok(quick_add) => quick_add,
+                    Err(err) => {
+                        eprintln!("Failed to parse the task: {}.", err);
+                        process::exit(1);
+                    }
+                };
+                let a = AddArgs {
+                    title: quick_add.title,
+                    description: None,
+                    priority: quick_add.priority,
+                    urgency: None,
+                    cost: quick_add.cost,
+                    tags: quick_add.tags,
+                    no_context: false,
+                    private: false,
+                    energy: None,
+                    kind: TaskKindArg::Task,
+                    target: None,
+                    due: quick_add.due,
+                    someday: false,
+                    parent: None,
+                    echo: *echo,
+                };
+                (self.engine_handlers(engine).add)(self, &a);
+                self.print_outstanding(engine, true);
+            }
+            SubCommands::Close(a) => {
+                (self.engine_handlers(engine).close)(self, a);
+                self.print_outstanding(engine, false);
+            }
+            SubCommands::Delete(a) => {
+                (self.engine_handlers(engine).delete)(self, a);
+                self.print_outstanding(engine, false);
+            }
+            SubCommands::Archive(a) => (self.engine_handlers(engine).archive)(self, a),
+            SubCommands::Edit(a) => (self.engine_handlers(engine).edit)(self, a),
+            SubCommands::List(a) => (self.engine_handlers(engine).list)(self, a),
+            #[cfg(feature = "es")]
+            SubCommands::ESAdd(a) => {
+                warn_deprecated("es-add", "add");
+                (self.engine_handlers(Engine::Es).add)(self, a);
+                self.print_outstanding(Engine::Es, true);
+            }
+            #[cfg(feature = "es")]
+            SubCommands::ESClose(a) => {
+                warn_deprecated("es-close", "close");
+                (self.engine_handlers(Engine::Es).close)(self, a);
+                self.print_outstanding(Engine::Es, false);
+            }
+            #[cfg(feature = "es")]
+            SubCommands::ESEdit(a) => {
+                warn_deprecated("es-edit", "edit");
+                (self.engine_handlers(Engine::Es).edit)(self, a)
+            }
+            #[cfg(feature = "es")]
+            SubCommands::ESList(a) => {
+                warn_deprecated("es-list", "list");
+                (self.engine_handlers(Engine::Es).list)(self, a)
+            }
+            #[cfg(feature = "es")]
+            SubCommands::Show { id } => {
+                let sequential_id = SequentialID::new(self.resolve(id, ES_SELECTION));
+                self.show(sequential_id, true);
+            }
+            #[cfg(feature = "es")]
+            SubCommands::Import {
+                file,
+                validate_only,
+                chunk_size,
+                resume,
+            } => self.import(file, *validate_only, *chunk_size, *resume),
+            #[cfg(feature = "es")]
+            SubCommands::Lock { ids } => self.lock(ids, true),
+            #[cfg(feature = "es")]
+            SubCommands::Unlock { ids } => self.lock(ids, false),
+            #[cfg(feature = "es")]
+            SubCommands::Log { id, duration } => {
+                let input = LogTimeUseCaseInput {
+                    sequential_id: SequentialID::new(self.resolve(id, ES_SELECTION)),
+                    duration: duration.duration(),
+                };
+                let id = <Cli<TR> as LogTimeUseCase>::execute(self, input).unwrap_or_else(|err| {
+                    eprintln!("Failed to log time: {}.", err);
+                    process::exit(1);
+                });
+                println!("Log {} on the task for id `{}`.", duration, id);
+                self.warn_quotas(id);
+            }
+            #[cfg(feature = "es")]
+            SubCommands::Start { id } => self.start_timer(id),
+            #[cfg(feature = "es")]
+            SubCommands::Stop { id } => self.stop_timer(id),
+            #[cfg(feature = "es")]
+            SubCommands::Pomodoro {
+                id,
+                work,
+                rest,
+                cycles,
+            } => self.pomodoro(id, *work, *rest, *cycles),
+            #[cfg(feature = "es")]
+            #[cfg(feature = "es")]
+            SubCommands::Demo => self.demo(),
+            #[cfg(feature = "es")]
+            SubCommands::Next { energy, time } => self.next(*energy, *time),
+            #[cfg(feature = "es")]
+            SubCommands::Simulate {
+                until,
+                add_capacity,
+            } => self.simulate(*until, *add_capacity),
+            #[cfg(feature = "es")]
+            SubCommands::Forecast { ids, query, trials } => self.forecast(ids, query, *trials),
+            #[cfg(feature = "es")]
+            SubCommands::Share {
+                id,
+                #[cfg(feature = "qr")]
+                qr,
             } => {
-                let input = AddTaskUseCaseInput {
-                    title: title.to_owned(),
-                    priority: priority.to_owned(),
-                    cost: cost.to_owned(),
+                let id = self.resolve(id, ES_SELECTION);
+                let input = ESListTaskUseCaseInput {
+                    include_closed: true,
+                    include_private: true,
+                    include_inbox: true,
+                    include_someday: true,
+                    ..Default::default()
+                };
+                let task = <Cli<TR> as ESListTaskUseCase>::execute(self, input)
+                    .unwrap_or_else(|err| {
+                        eprintln!("Failed to load the task: {}.", err);
+                        process::exit(1);
+                    })
+                    .into_iter()
+                    .find(|t| t.id == id)
+                    .unwrap_or_else(|| {
+                        eprintln!(
+                            "Failed to share the task: the task for id `{}` is not found.",
+                            id
+                        );
+                        process::exit(1);
+                    });
+                let json = share::task_json(&task);
+
+                #[cfg(feature = "qr")]
+                if *qr {
+                    let code = share::qr(&json).unwrap_or_else(|err| {
+                        eprintln!("Failed to render the QR code: {}.", err);
+                        process::exit(1);
+                    });
+                    println!("{}", code);
+                    return;
+                }
+                println!("{}", json);
+            }
+            #[cfg(feature = "es")]
+            SubCommands::CheckIn { id } => {
+                let input = CheckInUseCaseInput {
+                    sequential_id: SequentialID::new(self.resolve(id, ES_SELECTION)),
                 };
-                self.add_task_usecase.execute(input).unwrap();
+                let id = <Cli<TR> as CheckInUseCase>::execute(self, input).unwrap_or_else(|err| {
+                    eprintln!("Failed to check in: {}.", err);
+                    process::exit(1);
+                });
+                println!("Check in the habit for id `{}`.", id);
             }
-            SubCommands::ESAdd {
-                title,
-                priority,
-                cost,
+            #[cfg(feature = "es")]
+            SubCommands::WaitingOn {
+                id,
+                person,
+                follow_up,
+                ..
             } => {
-                let input = ESAddTaskUseCaseInput {
+                let input = WaitOnUseCaseInput {
+                    sequential_id: SequentialID::new(self.resolve(id, ES_SELECTION)),
+                    person: person.to_owned(),
+                    follow_up_on: *follow_up,
+                };
+                let id = <Cli<TR> as WaitOnUseCase>::execute(self, input).unwrap_or_else(|err| {
+                    eprintln!("Failed to mark the task: {}.", err);
+                    process::exit(1);
+                });
+                match person {
+                    Some(person) => {
+                        println!(
+                            "The task for id `{}` waits on {}.",
+                            id.to_i64(),
+                            person.trim()
+                        )
+                    }
+                    None => println!("The task for id `{}` waits on no one.", id),
+                }
+            }
+            #[cfg(feature = "es")]
+            SubCommands::In { title } => {
+                let input = CaptureUseCaseInput {
                     title: title.to_owned(),
-                    priority: priority.to_owned(),
-                    cost: cost.to_owned(),
                 };
-                <Cli<TR> as ESAddTaskUseCase>::execute(self, input).unwrap();
-            }
-            SubCommands::Close { ids } => {
-                let mut is_all_success = true;
-                for id in ids {
-                    match self
-                        .close_task_usecase
-                        .execute(CloseTaskUseCaseInput { id: id.to_owned() })
+                let id = <Cli<TR> as CaptureUseCase>::execute(self, input).unwrap_or_else(|err| {
+                    eprintln!("Failed to capture the item: {}.", err);
+                    process::exit(1);
+                });
+                println!("Capture the item for id `{}` in the inbox.", id);
+            }
+            #[cfg(feature = "es")]
+            SubCommands::TriageInbox => self.triage_inbox(),
+            #[cfg(feature = "es")]
+            SubCommands::Someday {
+                command: SomedayCommands::List { number },
+            } => self.someday_list(*number),
+            #[cfg(feature = "es")]
+            SubCommands::Promote { id } => self.promote(id),
+            SubCommands::Search(a) => (self.engine_handlers(engine).search)(self, a),
+            SubCommands::Last { dry_run } => self.last(*dry_run),
+            SubCommands::HistoryCmd { limit } => {
+                let entries = CommandHistoryStore::new(self.open_connection())
+                    .recent(*limit)
+                    .unwrap_or_else(|err| {
+                        eprintln!("Failed to read the command history: {}.", err);
+                        process::exit(1);
+                    });
+                let commands: Vec<(String, String)> = entries
+                    .into_iter()
+                    .map(|e| (e.recorded_at, format!("taskmr {}", quote_args(&e.args))))
+                    .collect();
+                self.table_printer.print_commands(&commands).unwrap();
+            }
+            #[cfg(feature = "legacy")]
+            SubCommands::Undo => {
+                require_legacy(engine, "undo");
+                match self.undo_usecase.execute() {
+                    Ok(Some(change)) => match change.before {
+                        Some(_) => println!("Undo the change of the task for id `{}`.", change.id),
+                        None => println!("Undo adding the task for id `{}`.", change.id),
+                    },
+                    Ok(None) => println!("Nothing to undo."),
+                    Err(err) => {
+                        eprintln!("Failed to undo: {}.", err);
+                        process::exit(1);
+                    }
+                }
+            }
+            #[cfg(feature = "legacy")]
+            SubCommands::History { id } => {
+                require_legacy(engine, "history");
+                let id = id.as_ref().map(|r| self.resolve(r, LEGACY_SELECTION));
+                let changes = self
+                    .history_usecase
+                    .execute(HistoryUseCaseInput { id })
+                    .unwrap_or_else(|err| {
+                        eprintln!("Failed to load the history: {}.", err);
+                        process::exit(1);
+                    });
+                self.table_printer.print_history(&changes).unwrap();
+            }
+            #[cfg(feature = "es")]
+            SubCommands::Tidy { stale, dry_run } => self.tidy(*stale, *dry_run),
+            SubCommands::Stats {
+                internal,
+                prometheus,
+            } => {
+                if !internal {
+                    println!("{}", self.render_report(STATS_REPORT).unwrap());
+                    return;
+                }
+
+                let metrics_store = MetricsStore::new(self.open_connection());
+                let stats = metrics_store.load().unwrap_or_else(|err| {
+                    eprintln!("Failed to load metrics: {}.", err);
+                    process::exit(1);
+                });
+                if !self.config.metrics.record {
+                    eprintln!(
+                        "Metrics are not being recorded. Set `record = true` under `[metrics]` in the config to record them."
+                    );
+                }
+
+                if *prometheus {
+                    print!("{}", render_prometheus(&stats));
+                    return;
+                }
+
+                let slow_queries = metrics_store
+                    .load_slow_queries(SLOW_QUERY_DISPLAY_LIMIT)
+                    .unwrap_or_else(|err| {
+                        eprintln!("Failed to load slow queries: {}.", err);
+                        process::exit(1);
+                    });
+                self.table_printer
+                    .print_metrics(&stats, &slow_queries)
+                    .unwrap();
+            }
+            SubCommands::Doctor {
+                schema,
+                #[cfg(feature = "es")]
+                orphans,
+                #[cfg(feature = "es")]
+                repair,
+                #[cfg(feature = "es")]
+                ids,
+                #[cfg(feature = "es")]
+                verify_chain,
+            } => {
+                #[cfg(feature = "es")]
+                let all = !schema && !orphans && !ids && !verify_chain;
+                #[cfg(not(feature = "es"))]
+                let all = !schema;
+                let mut is_healthy = true;
+
+                if all || *schema {
+                    let inspector = SchemaInspector::new(self.open_connection());
+                    if let Err(err) = inspector.check_integrity() {
+                        is_healthy = false;
+                        eprintln!("{}", err);
+                    }
+
+                    let drifts = inspector.inspect().unwrap_or_else(|err| {
+                        eprintln!("Failed to inspect the schema: {}.", err);
+                        process::exit(1);
+                    });
+                    for drift in &drifts {
+                        is_healthy = false;
+                        let remedy = if drift.is_auto_migratable() {
+                            "fixed automatically on the next run"
+                        } else {
+                            "needs manual migration"
+                        };
+                        println!("schema: {} ({})", drift, remedy);
+                    }
+                    if drifts.is_empty() {
+                        println!("schema: ok");
+                    }
+                }
+
+                #[cfg(feature = "es")]
+                if all || *orphans {
+                    let repository = ESTaskRepository::new(self.open_connection());
+                    let found = if *repair {
+                        repository.remove_orphans()
+                    } else {
+                        repository.find_orphans()
+                    };
+                    let found = found.unwrap_or_else(|err| {
+                        eprintln!("Failed to inspect sequential ids: {}.", err);
+                        process::exit(1);
+                    });
+                    let ids: Vec<String> = found.iter().map(|id| format!("#{}", id)).collect();
+                    match (found.is_empty(), repair) {
+                        (true, _) => println!("orphans: ok"),
+                        (false, true) => println!("orphans: removed {}", ids.join(", ")),
+                        (false, false) => {
+                            is_healthy = false;
+                            println!(
+                                "orphans: {} have no events (pass --repair to remove them)",
+                                ids.join(", ")
+                            );
+                        }
+                    }
+                }
+
+                #[cfg(feature = "es")]
+                if all || *ids {
+                    let duplicates = ESTaskRepository::new(self.open_connection())
+                        .find_duplicates()
+                        .unwrap_or_else(|err| {
+                            eprintln!("Failed to inspect sequential ids: {}.", err);
+                            process::exit(1);
+                        });
+                    if duplicates.is_empty() {
+                        println!("ids: ok");
+                    } else {
+                        is_healthy = false;
+                        println!("ids: {} are shared by several tasks", duplicates.join(", "));
+                    }
+                }
+
+                #[cfg(feature = "es")]
+                if (all && self.config.event_log.chain) || *verify_chain {
+                    let key = self
+                        .config
+                        .event_log_key(&self.config_dir)
+                        .unwrap_or_else(|err| {
+                            eprintln!("Failed to load the key of the chain: {}.", err);
+                            process::exit(1);
+                        });
+                    let report = event_chain::verify(&self.open_connection(), key.as_deref())
+                        .unwrap_or_else(|err| {
+                            eprintln!("Failed to verify the chain of events: {}.", err);
+                            process::exit(1);
+                        });
+                    for problem in &report.problems {
+                        is_healthy = false;
+                        println!("chain: {}", problem);
+                    }
+                    if report.problems.is_empty() {
+                        println!("chain: ok ({} events verified)", report.verified);
+                    }
+                    if report.uncovered > 0 {
+                        println!(
+                            "chain: {} events stored before the chain are not covered",
+                            report.uncovered
+                        );
+                    }
+                    if report.unchecked_signatures > 0 {
+                        println!(
+                            "chain: {} signatures are not checked without `event_log.key_file`",
+                            report.unchecked_signatures
+                        );
+                    }
+                }
+
+                if !is_healthy {
+                    process::exit(1);
+                }
+            }
+            SubCommands::Env => {
+                print!("{}", self.env_report());
+            }
+            #[cfg(feature = "es")]
+            SubCommands::Report {
+                name: Some(name),
+                since,
+                format,
+                ..
+            } if name == TIME_BY_TAG_REPORT => {
+                let input = TimeByTagUseCaseInput {
+                    since: since.map(|span| {
+                        chrono::Utc::now().naive_utc()
+                            - chrono::Duration::from_std(span.duration())
+                                .unwrap_or(chrono::Duration::MAX)
+                    }),
+                };
+                let rows =
+                    <Cli<TR> as TimeByTagUseCase>::execute(self, input).unwrap_or_else(|err| {
+                        eprintln!("Failed to make the report: {}.", err);
+                        process::exit(1);
+                    });
+                print!("{}", report::render_time_by_tag(*format, &rows));
+            }
+            #[cfg(feature = "es")]
+            SubCommands::Report {
+                name: Some(name),
+                month,
+                format,
+                ..
+            } if name == MONTH_REPORT => {
+                let input = MonthReviewUseCaseInput {
+                    month: month.unwrap_or_else(|| {
+                        Local::now().date_naive().with_day(1).unwrap_or_default()
+                    }),
+                };
+                let review =
+                    <Cli<TR> as MonthReviewUseCase>::execute(self, input).unwrap_or_else(|err| {
+                        eprintln!("Failed to make the report: {}.", err);
+                        process::exit(1);
+                    });
+                let output = report::render_month(*format, &review).unwrap_or_else(|| {
+                    eprintln!(
+                        "The {} report is printed as a table or markdown.",
+                        MONTH_REPORT
+                    );
+                    process::exit(1);
+                });
+                print!("{}", output);
+            }
+            #[cfg(feature = "es")]
+            SubCommands::Report {
+                name: Some(name),
+                format,
+                ..
+            } if name == QUOTAS_REPORT => {
+                let rows = self.quota_usage(None).unwrap_or_else(|err| {
+                    eprintln!("Failed to make the report: {}.", err);
+                    process::exit(1);
+                });
+                if rows.is_empty() {
+                    println!("No quotas in the config.");
+                    return;
+                }
+                print!("{}", report::render_quotas(*format, &rows));
+            }
+            #[cfg(feature = "es")]
+            SubCommands::Report {
+                name: Some(name),
+                format,
+                ..
+            } if name == NUDGES_REPORT => {
+                let input = NudgeUseCaseInput {
+                    today: Local::now().date_naive(),
+                };
+                let nudges =
+                    <Cli<TR> as NudgeUseCase>::execute(self, input).unwrap_or_else(|err| {
+                        eprintln!("Failed to make the report: {}.", err);
+                        process::exit(1);
+                    });
+                if nudges.is_empty() {
+                    println!("Nobody to follow up with.");
+                    return;
+                }
+                print!("{}", report::render_nudges(*format, &nudges));
+            }
+            #[cfg(feature = "es")]
+            SubCommands::Report {
+                name: Some(name),
+                since,
+                format,
+                ..
+            } if name == CYCLE_TIME_REPORT => {
+                let input = CycleTimeUseCaseInput {
+                    since: since.map(|span| {
+                        chrono::Utc::now().naive_utc()
+                            - chrono::Duration::from_std(span.duration())
+                                .unwrap_or(chrono::Duration::MAX)
+                    }),
+                };
+                let rows =
+                    <Cli<TR> as CycleTimeUseCase>::execute(self, input).unwrap_or_else(|err| {
+                        eprintln!("Failed to make the report: {}.", err);
+                        process::exit(1);
+                    });
+                print!("{}", report::render_cycle_time(*format, &rows));
+            }
+            SubCommands::Report { name, .. } => {
+                let Some(name) = name else {
+                    println!("{}", STATS_REPORT);
+                    #[cfg(feature = "es")]
+                    println!("{}", TIME_BY_TAG_REPORT);
+                    #[cfg(feature = "es")]
+                    println!("{}", MONTH_REPORT);
+                    #[cfg(feature = "es")]
+                    println!("{}", QUOTAS_REPORT);
+                    #[cfg(feature = "es")]
+                    println!("{}", NUDGES_REPORT);
+                    #[cfg(feature = "es")]
+                    println!("{}", CYCLE_TIME_REPORT);
+                    #[cfg(feature = "scripting")]
                     {
-                        Ok(r_id) => {
-                            println!("Close the task for id `{}`.", r_id.get())
+                        let engine = self.script_engine();
+                        let names = engine.list_scripts().unwrap_or_else(|err| {
+                            eprintln!("Failed to list scripts: {}.", err);
+                            process::exit(1);
+                        });
+                        if names.is_empty() {
+                            println!("No scripts in {}.", engine.scripts_dir().display());
+                        }
+                        for name in names {
+                            println!("{}", name);
+                        }
+                    }
+                    return;
+                };
+
+                let output = self.render_report(name).unwrap_or_else(|err| {
+                    eprintln!("Failed to make the report: {}.", err);
+                    process::exit(1);
+                });
+                println!("{}", output);
+            }
+            SubCommands::ScheduleReport {
+                daily,
+                weekly,
+                to,
+                path,
+                url,
+                remove,
+            } => {
+                let store = self.schedule_store();
+                if let Some(id) = remove {
+                    match store.remove(*id) {
+                        Ok(true) => println!("Removed the schedule {}.", id),
+                        Ok(false) => {
+                            eprintln!("The schedule {} is not found.", id);
+                            process::exit(1);
                         }
                         Err(err) => {
-                            is_all_success = false;
-                            eprintln!("Failed to close the task: {}.", err)
+                            eprintln!("Failed to remove the schedule: {}.", err);
+                            process::exit(1);
                         }
                     }
+                    return;
                 }
 
-                if !is_all_success {
+                let (report, period) = match (daily, weekly) {
+                    (Some(report), _) => (report, Period::Daily),
+                    (_, Some(report)) => (report, Period::Weekly),
+                    (None, None) => {
+                        let schedules = store.list().unwrap_or_else(|err| {
+                            eprintln!("Failed to list schedules: {}.", err);
+                            process::exit(1);
+                        });
+                        if schedules.is_empty() {
+                            println!("No reports are scheduled.");
+                        }
+                        for s in schedules {
+                            println!(
+                                "{}: {} {} to {} (last run: {})",
+                                s.id,
+                                s.period,
+                                s.report,
+                                s.delivery,
+                                s.last_run_on.as_deref().unwrap_or("never")
+                            );
+                        }
+                        return;
+                    }
+                };
+
+                let delivery = match to {
+                    DeliveryArg::Stdout => Delivery::Stdout,
+                    DeliveryArg::File => Delivery::File(path.clone().unwrap()),
+                    DeliveryArg::Webhook => Delivery::Webhook(url.clone().unwrap()),
+                };
+                if matches!(delivery, Delivery::Webhook(_)) && !cfg!(feature = "webhook") {
+                    eprintln!(
+                        "Webhooks are not available, build taskmr with the `webhook` feature."
+                    );
                     process::exit(1);
                 }
+                match store.add(report, period, &delivery) {
+                    Ok(id) => println!(
+                        "Scheduled {} {} to {} as {}. Run `taskmr cron` from your scheduler.",
+                        period, report, delivery, id
+                    ),
+                    Err(err) => {
+                        eprintln!("Failed to schedule the report: {}.", err);
+                        process::exit(1);
+                    }
+                }
             }
-            SubCommands::ESClose { ids } => {
-                let mut is_all_success = true;
-                for id in ids {
-                    match <Cli<TR> as ESCloseTaskUseCase>::execute(
-                        self,
-                        ESCloseTaskUseCaseInput {
-                            sequential_id: SequentialID::new(id.to_owned()),
-                        },
-                    ) {
-                        Ok(r_id) => {
-                            println!("Close the task for id `{}`.", r_id.to_i64())
+            SubCommands::Cron => {
+                let store = self.schedule_store();
+                let schedules = store.due().unwrap_or_else(|err| {
+                    eprintln!("Failed to load schedules: {}.", err);
+                    process::exit(1);
+                });
+
+                // NOTE: a digest joins reports due to the same destination, so that a chat
+                // channel gets one post a run instead of one post for each report.
+                let groups = if self.config.cron.digest {
+                    schedule::group_by_delivery(schedules)
+                } else {
+                    schedules
+                        .into_iter()
+                        .map(|s| (s.delivery.clone(), vec![s]))
+                        .collect()
+                };
+
+                let mut failed = false;
+                for (delivery, group) in groups {
+                    let mut texts = vec![];
+                    let mut rendered = vec![];
+                    for schedule in group {
+                        match self.render_report(&schedule.report) {
+                            Ok(text) => {
+                                texts.push(text);
+                                rendered.push(schedule);
+                            }
+                            Err(err) => {
+                                failed = true;
+                                eprintln!(
+                                    "Failed to deliver the report {} of the schedule {}: {}.",
+                                    schedule.report, schedule.id, err
+                                );
+                            }
                         }
-                        Err(err) => {
-                            is_all_success = false;
-                            eprintln!("Failed to close the task: {}.", err)
+                    }
+                    if rendered.is_empty() {
+                        continue;
+                    }
+
+                    let ids: Vec<i64> = rendered.iter().map(|s| s.id).collect();
+                    if let Err(err) = store.enqueue(&ids, &delivery, &texts.join("\n\n")) {
+                        failed = true;
+                        for schedule in rendered {
+                            eprintln!(
+                                "Failed to deliver the report {} of the schedule {}: {}.",
+                                schedule.report, schedule.id, err
+                            );
                         }
                     }
                 }
 
-                if !is_all_success {
+                // NOTE: reports left by failures of earlier runs are retried with new ones.
+                let (_, undelivered) = flush_outbox(&store);
+                if undelivered > 0 {
+                    failed = true;
+                }
+
+                if failed {
                     process::exit(1);
                 }
             }
-            SubCommands::Edit {
-                id,
-                title,
-                priority,
-                cost,
+            SubCommands::FlushOutbox => {
+                let store = self.schedule_store();
+                let (delivered, undelivered) = flush_outbox(&store);
+                match (delivered, undelivered) {
+                    (0, 0) => println!("The outbox is empty."),
+                    (n, _) => println!("Delivered {} reports from the outbox.", n),
+                }
+                if undelivered > 0 {
+                    process::exit(1);
+                }
+            }
+            SubCommands::Lint => self.lint(),
+            SubCommands::Random { max_cost, weighted } => self.random(Pick {
+                max_cost: *max_cost,
+                weighted: *weighted,
+            }),
+            SubCommands::Widget { format, max_width } => {
+                // NOTE: print nothing on errors not to break the status bar.
+                let tasks = match self.open_tasks() {
+                    Ok(tasks) => tasks,
+                    Err(err) => {
+                        tracing::warn!(%err, "failed to load tasks for the widget");
+                        return;
+                    }
+                };
+
+                // NOTE: the task focused by `random` wins while it is open.
+                let focus = self
+                    .focus_store(OPEN_TASKS_SELECTION)
+                    .load()
+                    .unwrap_or_default();
+                let focused = tasks
+                    .iter()
+                    .find(|t| focus.contains(&t.id))
+                    .or_else(|| tasks.iter().max_by_key(|t| (t.priority, -t.id)))
+                    .map(|t| t.title.clone());
+                let data = WidgetData {
+                    focused,
+                    overdue: tasks.iter().filter(|t| t.is_overdue).count(),
+                };
+                println!("{}", widget::render(*format, &data, *max_width));
+            }
+            #[cfg(feature = "es")]
+            SubCommands::Export {
+                format,
+                path,
+                output,
+                #[cfg(feature = "pdf")]
+                layout,
+                no_context,
+                show_private,
+                anonymize,
             } => {
-                let input = EditTaskUseCaseInput {
-                    id: id.to_owned(),
-                    title: title.to_owned(),
-                    priority: priority.to_owned(),
-                    cost: cost.to_owned(),
+                if *format == ExportFormat::SqliteSnapshot {
+                    let Some(path) = path.as_ref().or(output.as_ref()) else {
+                        eprintln!("Give the path of the snapshot.");
+                        process::exit(1);
+                    };
+                    self.export_snapshot(path, *show_private, *anonymize);
+                    return;
+                }
+                if path.is_some() {
+                    eprintln!(
+                        "Only the sqlite-snapshot format is written to a path. Give --output for the others."
+                    );
+                    process::exit(1);
+                }
+
+                #[cfg(feature = "pdf")]
+                if *format == ExportFormat::Pdf
+                    && output.is_none()
+                    && io::IsTerminal::is_terminal(&io::stdout())
+                {
+                    eprintln!(
+                        "PDF is not printed to the terminal. Redirect the output into a file."
+                    );
+                    process::exit(1);
+                }
+
+                let tags = if *no_context {
+                    vec![]
+                } else {
+                    self.config.scope_tags().to_vec()
                 };
-                self.edit_task_usecase.execute(input).unwrap_or_else(|err| {
-                    eprintln!("Failed to edit the task: {}.", err);
+                let input = ESListTaskUseCaseInput {
+                    tags,
+                    include_closed: true,
+                    include_private: *show_private,
+                    include_inbox: true,
+                    include_someday: true,
+                    include_deleted: false,
+                };
+                let export = || -> crate::Result<()> {
+                    let out: Box<dyn Write> = match output {
+                        Some(output) => Box::new(io::BufWriter::new(fs::File::create(output)?)),
+                        None => Box::new(io::stdout().lock()),
+                    };
+                    let mut exporter = Exporter::begin(*format, out)?;
+                    if *anonymize {
+                        exporter.set_anonymizer(Anonymizer::new());
+                    }
+                    #[cfg(feature = "pdf")]
+                    exporter.set_pdf_options(PdfOptions {
+                        layout: *layout,
+                        strategy: scoring_strategy(&self.config.score),
+                        ..Default::default()
+                    });
+                    <Cli<TR> as ESListTaskUseCase>::for_each(self, input, &mut |task| {
+                        Ok(exporter.write_task(&task)?)
+                    })?;
+                    exporter.finish()?;
+                    Ok(())
+                };
+                export().unwrap_or_else(|err| {
+                    eprintln!("Failed to export tasks: {}.", err);
                     process::exit(1);
                 });
             }
-            SubCommands::ESEdit {
-                id,
-                title,
-                priority,
-                cost,
+            #[cfg(feature = "es")]
+            SubCommands::Sprint {
+                command:
+                    SprintCommands::Create {
+                        name,
+                        ids,
+                        length,
+                        capacity,
+                        start,
+                        force,
+                    },
             } => {
-                let input = ESEditTaskUseCaseInput {
-                    sequential_id: SequentialID::new(id.to_owned()),
-                    title: title.to_owned(),
-                    priority: priority.to_owned(),
-                    cost: cost.to_owned(),
+                let input = CreateSprintUseCaseInput {
+                    name: name.to_owned(),
+                    start_on: start.unwrap_or_else(|| Local::now().date_naive()),
+                    length: *length,
+                    capacity: *capacity,
+                    sequential_ids: ids
+                        .iter()
+                        .map(|id| SequentialID::new(self.resolve(id, ES_SELECTION)))
+                        .collect(),
+                    allow_over_capacity: *force,
+                };
+                let sprint =
+                    <Cli<TR> as CreateSprintUseCase>::execute(self, input).unwrap_or_else(|err| {
+                        eprintln!("Failed to create the sprint: {}.", err);
+                        process::exit(1);
+                    });
+                self.table_printer.print_sprint(&sprint).unwrap();
+            }
+            #[cfg(feature = "es")]
+            SubCommands::Sprint {
+                command: SprintCommands::Status { name },
+            } => {
+                let input = ShowSprintUseCaseInput {
+                    name: name.to_owned(),
+                };
+                let sprint =
+                    <Cli<TR> as ShowSprintUseCase>::execute(self, input).unwrap_or_else(|err| {
+                        eprintln!("Failed to show the sprint: {}.", err);
+                        process::exit(1);
+                    });
+                self.table_printer.print_sprint(&sprint).unwrap();
+            }
+            #[cfg(feature = "es")]
+            SubCommands::Sprint {
+                command: SprintCommands::Close { name },
+            } => {
+                let input = CloseSprintUseCaseInput {
+                    name: name.to_owned(),
+                    closed_on: Local::now().date_naive(),
+                };
+                let sprint =
+                    <Cli<TR> as CloseSprintUseCase>::execute(self, input).unwrap_or_else(|err| {
+                        eprintln!("Failed to close the sprint: {}.", err);
+                        process::exit(1);
+                    });
+                self.table_printer.print_sprint(&sprint).unwrap();
+            }
+            #[cfg(feature = "es")]
+            SubCommands::Sprint {
+                command: SprintCommands::Burndown { name, width },
+            } => {
+                let input = BurndownSprintUseCaseInput {
+                    name: name.to_owned(),
+                    today: Local::now().date_naive(),
+                };
+                let burndown = <Cli<TR> as BurndownSprintUseCase>::execute(self, input)
+                    .unwrap_or_else(|err| {
+                        eprintln!("Failed to chart the sprint: {}.", err);
+                        process::exit(1);
+                    });
+                print!("{}", chart::render_burndown(&burndown, *width));
+            }
+            #[cfg(feature = "es")]
+            SubCommands::Tag {
+                command: None,
+                id: Some(id),
+                tags,
+            } => self.tag(id, tags, true),
+            #[cfg(feature = "es")]
+            SubCommands::Tag {
+                command: None,
+                id: None,
+                ..
+            } => unreachable!("clap requires the id without a subcommand"),
+            #[cfg(feature = "es")]
+            SubCommands::Untag { id, tags } => self.tag(id, tags, false),
+            #[cfg(feature = "es")]
+            SubCommands::Tag {
+                command: Some(TagCommands::Rename { from, to }),
+                ..
+            } => {
+                let input = RenameTagUseCaseInput {
+                    from: from.to_owned(),
+                    to: to.to_owned(),
                 };
-                <Cli<TR> as ESEditTaskUseCase>::execute(self, input).unwrap_or_else(|err| {
-                    eprintln!("Failed to edit the task: {}.", err);
+                <Cli<TR> as RenameTagUseCase>::execute(self, input).unwrap_or_else(|err| {
+                    eprintln!("Failed to rename the tag: {}.", err);
                     process::exit(1);
                 });
+                println!("Renamed the tag `{}` to `{}`.", from, to);
             }
-            SubCommands::List {} => {
-                let task_dto = self
-                    .list_task_usecase
-                    .execute(ListTaskUseCaseInput {})
-                    .unwrap();
-                self.table_printer.print(task_dto).unwrap();
+            #[cfg(feature = "es")]
+            SubCommands::Tag {
+                command: Some(TagCommands::List { counts }),
+                ..
+            } => {
+                let tags = <Cli<TR> as ListTagsUseCase>::execute(self).unwrap_or_else(|err| {
+                    eprintln!("Failed to list tags: {}.", err);
+                    process::exit(1);
+                });
+                self.table_printer.print_tags(&tags, *counts).unwrap();
             }
-            SubCommands::ESList {} => {
-                let task_dto_vec =
-                    <Cli<TR> as ESListTaskUseCase>::execute(self, ESListTaskUseCaseInput {})
-                        .unwrap();
-                self.table_printer.print_es(task_dto_vec).unwrap();
+            #[cfg(feature = "es")]
+            SubCommands::Retag { from, to, query } => self.retag(from, to, query),
+            #[cfg(feature = "es")]
+            SubCommands::ApplyRules => self.apply_rules(),
+            #[cfg(all(feature = "es", feature = "legacy"))]
+            SubCommands::Migrate {
+                command: MigrateCommands::LegacyToEs { close_source },
+            } => {
+                let legacy_tasks = TaskRepository::new(self.open_connection())
+                    .fetch_all(TaskOrder::Id)
+                    .unwrap_or_else(|err| {
+                        eprintln!("Failed to read legacy tasks: {}.", err);
+                        process::exit(1);
+                    });
+
+                let mut tasks = Vec::new();
+                for task in &legacy_tasks {
+                    // NOTE: the table does not record when a task was closed.
+                    // updated_at is the best guess.
+                    let (Some(created_at), Some(updated_at)) = (
+                        local_to_utc(task.created_at()),
+                        local_to_utc(task.updated_at()),
+                    ) else {
+                        eprintln!(
+                            "Failed to read timestamps of the legacy task `{}`.",
+                            task.id().get()
+                        );
+                        process::exit(1);
+                    };
+                    tasks.push(LegacyTaskDTO {
+                        id: task.id().get(),
+                        title: task.title().to_owned(),
+                        priority: task.priority().get(),
+                        cost: task.cost().get(),
+                        created_at,
+                        closed_at: task.is_closed().then_some(updated_at),
+                    });
+                }
+
+                let output = <Cli<TR> as MigrateLegacyUseCase>::execute(
+                    self,
+                    MigrateLegacyUseCaseInput { tasks },
+                )
+                .unwrap_or_else(|err| {
+                    eprintln!("Failed to migrate legacy tasks: {}.", err);
+                    process::exit(1);
+                });
+                for m in &output.migrated {
+                    println!("Migrate the legacy task `{}` to `{}`.", m.legacy_id, m.id);
+                }
+                println!(
+                    "{} migrated, {} already migrated.",
+                    output.migrated.len(),
+                    output.skipped.len()
+                );
+
+                if *close_source {
+                    let open_ids = legacy_tasks
+                        .iter()
+                        .filter(|task| !task.is_closed())
+                        .map(|task| task.id().get());
+                    for id in open_ids {
+                        if let Err(err) = self
+                            .close_task_usecase
+                            .execute(CloseTaskUseCaseInput { id })
+                        {
+                            eprintln!("Failed to close the legacy task `{}`: {}.", id, err);
+                            process::exit(1);
+                        }
+                    }
+                }
+            }
+            SubCommands::Init { .. } => {
+                unreachable!("init is run before the database is opened")
+            }
+            SubCommands::Config {
+                command: ConfigCommands::Export { format, output },
+            } => {
+                let bundle =
+                    Bundle::collect(&self.config_dir.join(CONFIG_FILE_NAME), &self.scripts_dir())
+                        .and_then(|bundle| bundle.render((*format).into()))
+                        .unwrap_or_else(|err| {
+                            eprintln!("Failed to export the config: {:#}.", err);
+                            process::exit(1);
+                        });
+                match output {
+                    Some(path) => fs::write(path, bundle).unwrap_or_else(|err| {
+                        eprintln!("Failed to write {}: {}.", path.display(), err);
+                        process::exit(1);
+                    }),
+                    None => print!("{}", bundle),
+                }
+            }
+            SubCommands::Config {
+                command: ConfigCommands::Import { file, force },
+            } => {
+                let written = fs::read_to_string(file)
+                    .map_err(crate::Error::from)
+                    .and_then(|source| Bundle::parse(&source, BundleFormat::from_path(file)))
+                    .and_then(|bundle| {
+                        bundle.install(
+                            &self.config_dir.join(CONFIG_FILE_NAME),
+                            &self.scripts_dir(),
+                            *force,
+                        )
+                    })
+                    .unwrap_or_else(|err| {
+                        eprintln!("Failed to import the config: {:#}.", err);
+                        process::exit(1);
+                    });
+                for path in written {
+                    println!("Wrote {}.", path.display());
+                }
+            }
+            SubCommands::Git {
+                command: GitCommands::InstallHook { force },
+            } => {
+                let cwd = env::current_dir().unwrap_or_else(|err| {
+                    eprintln!("Couldn't find out the current directory: {}.", err);
+                    process::exit(1);
+                });
+                match git::install_hook(&cwd, *force) {
+                    Ok(path) => println!("Installed {}.", path.display()),
+                    Err(err) => {
+                        eprintln!("Failed to install the hook: {}.", err);
+                        process::exit(1);
+                    }
+                }
+            }
+            SubCommands::Git {
+                command: GitCommands::ApplyMessage { file },
+            } => {
+                // NOTE: never fail, a broken task database must not block commits.
+                let message = match fs::read_to_string(file) {
+                    Ok(message) => message,
+                    Err(err) => {
+                        eprintln!("taskmr: failed to read the commit message: {}.", err);
+                        return;
+                    }
+                };
+
+                for id in git::parse_closing_references(&message) {
+                    match self.close_task(id) {
+                        Ok(id) => println!("taskmr: close the task for id `{}`.", id),
+                        Err(err) => eprintln!("taskmr: failed to close the task: {}.", err),
+                    }
+                }
+            }
+            #[cfg(feature = "devtools")]
+            SubCommands::Devtools {
+                command:
+                    DevtoolsCommands::Generate {
+                        tasks,
+                        events_per_task,
+                        seed,
+                    },
+            } => {
+                let now = chrono::Utc::now().naive_utc();
+                let mut generated = 0;
+                let mut events = 0;
+                while generated < *tasks {
+                    let chunk = GENERATE_CHUNK.min(tasks - generated);
+                    let input = GenerateUseCaseInput {
+                        tasks: chunk,
+                        events_per_task: usize::from(*events_per_task),
+                        seed: *seed,
+                        now,
+                    };
+                    events +=
+                        <Cli<TR> as GenerateUseCase>::execute(self, input).unwrap_or_else(|err| {
+                            eprintln!("Failed to generate tasks: {}.", err);
+                            process::exit(1);
+                        });
+                    generated += chunk;
+                    eprintln!("Generated {} of {} tasks.", generated, tasks);
+                }
+                println!("Generate {} tasks with {} events.", generated, events);
+            }
+            #[cfg(feature = "plugin")]
+            SubCommands::External(args) => {
+                let code = self.run_plugin(&args[0], &args[1..]).unwrap_or_else(|err| {
+                    eprintln!("Failed to run the plugin: {}.", err);
+                    process::exit(1);
+                });
+                if code != 0 {
+                    process::exit(code);
+                }
             }
         }
     }
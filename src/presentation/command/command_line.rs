@@ -0,0 +1,128 @@
+//! # command_line
+//!
+//! command_line handles command lines kept in the command history, which are typed after
+//! `taskmr`.
+
+/// SECRET_OPTIONS are options whose values are redacted from the command history.
+const SECRET_OPTIONS: &[&str] = &["--url"];
+
+/// REDACTED replaces a redacted value in the command history.
+const REDACTED: &str = "***";
+
+/// redact replaces values of SECRET_OPTIONS, given as `--url <value>` or `--url=<value>`,
+/// and reports whether any was replaced.
+pub fn redact(args: &[String]) -> (Vec<String>, bool) {
+    let mut redacted = false;
+    let mut out = Vec::with_capacity(args.len());
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        if SECRET_OPTIONS.contains(&arg.as_str()) {
+            out.push(arg.to_owned());
+            if args.next().is_some() {
+                out.push(REDACTED.to_owned());
+                redacted = true;
+            }
+            continue;
+        }
+        match arg
+            .split_once('=')
+            .filter(|(option, _)| SECRET_OPTIONS.contains(option))
+        {
+            Some((option, _)) => {
+                out.push(format!("{}={}", option, REDACTED));
+                redacted = true;
+            }
+            None => out.push(arg.to_owned()),
+        }
+    }
+    (out, redacted)
+}
+
+/// quote_args joins arguments as they are typed in a shell, quoting the ones which need it.
+pub fn quote_args(args: &[String]) -> String {
+    args.iter()
+        .map(|arg| {
+            let plain = !arg.is_empty()
+                && arg.chars().all(|c| {
+                    c.is_alphanumeric()
+                        || matches!(c, '-' | '_' | '.' | '/' | ':' | '=' | '%' | ',')
+                });
+            if plain {
+                arg.to_owned()
+            } else {
+                format!("'{}'", arg.replace('\'', "'\\''"))
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(s: &str) -> Vec<String> {
+        s.split(' ').map(String::from).collect()
+    }
+
+    #[test]
+    fn test_redact() {
+        #[derive(Debug)]
+        struct TestCase {
+            args: Vec<String>,
+            want: (Vec<String>, bool),
+            name: String,
+        }
+
+        let table = [
+            TestCase {
+                name: String::from("nominal"),
+                args: args("add fix -p 3"),
+                want: (args("add fix -p 3"), false),
+            },
+            TestCase {
+                name: String::from("normal: separated value"),
+                args: args("schedule-report --daily stats --to webhook --url https://x/secret"),
+                want: (
+                    args("schedule-report --daily stats --to webhook --url ***"),
+                    true,
+                ),
+            },
+            TestCase {
+                name: String::from("normal: joined value"),
+                args: args("schedule-report --url=https://x/secret --weekly stats"),
+                want: (args("schedule-report --url=*** --weekly stats"), true),
+            },
+            TestCase {
+                name: String::from("normal: no value"),
+                args: args("schedule-report --url"),
+                want: (args("schedule-report --url"), false),
+            },
+        ];
+
+        for test_case in table {
+            assert_eq!(
+                redact(&test_case.args),
+                test_case.want,
+                "Failed in the \"{}\".",
+                test_case.name,
+            );
+        }
+    }
+
+    #[test]
+    fn test_quote_args() {
+        let args = vec![
+            "list".to_owned(),
+            "--regex".to_owned(),
+            "^fix (a|b)".to_owned(),
+            "--tag=repo:x".to_owned(),
+            "it's".to_owned(),
+            String::new(),
+        ];
+        assert_eq!(
+            quote_args(&args),
+            "list --regex '^fix (a|b)' --tag=repo:x 'it'\\''s' ''"
+        );
+    }
+}
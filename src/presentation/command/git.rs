@@ -0,0 +1,160 @@
+//! # git
+//!
+//! git ties commits to tasks.
+//! A commit message containing `closes task#12` closes the task 12 through the commit-msg hook.
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::{Error, Result};
+
+use crate::presentation::context;
+
+/// HOOK_NAME is the git hook which taskmr installs.
+pub const HOOK_NAME: &str = "commit-msg";
+
+/// HOOK_MARKER marks hooks installed by taskmr, so that they can be overwritten safely.
+const HOOK_MARKER: &str = "# installed by taskmr";
+
+/// CLOSING_KEYWORDS are verbs which close the following task reference.
+const CLOSING_KEYWORDS: [&str; 3] = ["close", "closes", "closed"];
+
+/// TASK_REFERENCE_PREFIX is the prefix of a task reference such as `task#12`.
+const TASK_REFERENCE_PREFIX: &str = "task#";
+
+/// parse_closing_references returns ids of tasks referred as `closes task#<id>` in the message.
+/// Comment lines of git, starting with `#`, are ignored.
+pub fn parse_closing_references(message: &str) -> Vec<i64> {
+    let mut ids = Vec::new();
+
+    for line in message.lines().filter(|l| !l.trim_start().starts_with('#')) {
+        let words: Vec<&str> = line.split_whitespace().collect();
+        for pair in words.windows(2) {
+            if !CLOSING_KEYWORDS.contains(&pair[0].to_lowercase().as_str()) {
+                continue;
+            }
+
+            let id = pair[1]
+                .strip_prefix(TASK_REFERENCE_PREFIX)
+                .map(|r| r.trim_end_matches(|c: char| !c.is_ascii_digit()))
+                .and_then(|r| r.parse::<i64>().ok());
+            if let Some(id) = id {
+                if !ids.contains(&id) {
+                    ids.push(id);
+                }
+            }
+        }
+    }
+
+    ids
+}
+
+/// hook_script returns the content of the commit-msg hook.
+pub fn hook_script() -> String {
+    format!(
+        "#!/bin/sh\n{}\n# close tasks referred as `closes task#<id>`.\ntaskmr git apply-message \"$1\" || true\n",
+        HOOK_MARKER
+    )
+}
+
+/// install_hook writes the commit-msg hook into the git repository containing `dir`.
+/// A hook which is not installed by taskmr is kept unless `force` is true.
+pub fn install_hook(dir: &Path, force: bool) -> Result<PathBuf> {
+    let hooks_dir = match context::git(dir, &["rev-parse", "--git-path", "hooks"]) {
+        Some(hooks_dir) => dir.join(hooks_dir),
+        None => {
+            return Err(Error::Presentation(
+                format!("{} is not in a git repository", dir.display()).into(),
+            ))
+        }
+    };
+    fs::create_dir_all(&hooks_dir)?;
+
+    let path = hooks_dir.join(HOOK_NAME);
+    match fs::read_to_string(&path) {
+        Ok(current) if !force && !current.contains(HOOK_MARKER) => {
+            return Err(Error::Presentation(
+                format!(
+                    "{} already exists, pass --force to overwrite it",
+                    path.display()
+                )
+                .into(),
+            ))
+        }
+        Err(err) if err.kind() != io::ErrorKind::NotFound => return Err(err.into()),
+        _ => {}
+    }
+
+    fs::write(&path, hook_script())?;
+    make_executable(&path)?;
+
+    Ok(path)
+}
+
+#[cfg(unix)]
+fn make_executable(path: &Path) -> Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+
+    let mut permissions = fs::metadata(path)?.permissions();
+    permissions.set_mode(0o755);
+    fs::set_permissions(path, permissions)?;
+    Ok(())
+}
+
+#[cfg(not(unix))]
+fn make_executable(_: &Path) -> Result<()> {
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_closing_references() {
+        #[derive(Debug)]
+        struct TestCase {
+            args: &'static str,
+            want: Vec<i64>,
+            name: String,
+        }
+
+        let table = [
+            TestCase {
+                name: String::from("normal: one reference"),
+                args: "Fix the parser\n\ncloses task#12",
+                want: vec![12],
+            },
+            TestCase {
+                name: String::from("normal: several references with punctuation"),
+                args: "Closes task#1, closed task#3. close task#1",
+                want: vec![1, 3],
+            },
+            TestCase {
+                name: String::from("normal: mention without keyword"),
+                args: "see task#4",
+                want: vec![],
+            },
+            TestCase {
+                name: String::from("normal: git comment"),
+                args: "Refactor\n# closes task#5",
+                want: vec![],
+            },
+            TestCase {
+                name: String::from("abnormal: not a number"),
+                args: "closes task#abc",
+                want: vec![],
+            },
+        ];
+
+        for test_case in table {
+            assert_eq!(
+                parse_closing_references(test_case.args),
+                test_case.want,
+                "Failed in the \"{}\".",
+                test_case.name,
+            );
+        }
+    }
+}
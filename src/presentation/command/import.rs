@@ -0,0 +1,350 @@
+//! # import
+//!
+//! import reads tasks to import from JSON Lines, a task of the JSON which `share` prints in each line,
+//! or from CSV which `export --format csv` writes.
+
+use std::mem;
+
+use sha2::{Digest, Sha256};
+
+use crate::domain::es_task::{DueDate, TaskError};
+use crate::presentation::dto::TaskV1;
+use crate::presentation::printer::export::CSV_COLUMNS;
+use crate::usecase::es_import_usecase::{
+    ImportProgressDTO, ImportRowDTO, ImportTaskDTO, RowErrorDTO,
+};
+use crate::usecase::es_list_task_usecase::TaskDTO;
+
+/// read_rows parses each line which is not blank into a row.
+/// Lines which are not a task are returned as errors, so that all of them are reported at once.
+pub fn read_rows(text: &str) -> (Vec<ImportRowDTO>, Vec<RowErrorDTO>) {
+    let mut rows = vec![];
+    let mut errors = vec![];
+    for (i, line) in text.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        match serde_json::from_str::<TaskV1>(line) {
+            Ok(task) => rows.push(ImportRowDTO {
+                line: i + 1,
+                task: TaskDTO::from(task).into(),
+            }),
+            Err(err) => errors.push(RowErrorDTO {
+                line: i + 1,
+                message: message(&err),
+            }),
+        }
+    }
+
+    (rows, errors)
+}
+
+/// read_csv_rows parses CSV whose header names the columns of CSV_COLUMNS in any order.
+/// Only `title` is required, and `id` is ignored as it is of another database.
+/// Empty values are left to the defaults of a new task.
+pub fn read_csv_rows(text: &str) -> (Vec<ImportRowDTO>, Vec<RowErrorDTO>) {
+    let records = match parse_csv(text) {
+        Ok(records) => records,
+        Err(err) => return (vec![], vec![err]),
+    };
+    let mut records = records
+        .into_iter()
+        .filter(|(_, fields)| fields.iter().any(|f| !f.trim().is_empty()));
+    let Some((header_line, header)) = records.next() else {
+        return (vec![], vec![]);
+    };
+    let header_error = |message| {
+        vec![RowErrorDTO {
+            line: header_line,
+            message,
+        }]
+    };
+
+    let mut columns = Vec::with_capacity(header.len());
+    for name in &header {
+        let name = name.trim().to_lowercase();
+        if !CSV_COLUMNS.contains(&name.as_str()) {
+            return (
+                vec![],
+                header_error(format!(
+                    "`{}` is not a column, columns are {}",
+                    name,
+                    CSV_COLUMNS.join(", ")
+                )),
+            );
+        }
+        columns.push(name);
+    }
+    if !columns.iter().any(|c| c == "title") {
+        return (
+            vec![],
+            header_error("the header has no title column".to_owned()),
+        );
+    }
+
+    let mut rows = vec![];
+    let mut errors = vec![];
+    for (line, fields) in records {
+        match csv_task(&columns, &fields) {
+            Ok(task) => rows.push(ImportRowDTO { line, task }),
+            Err(message) => errors.push(RowErrorDTO { line, message }),
+        }
+    }
+
+    (rows, errors)
+}
+
+/// csv_task reads the fields of a record in the columns.
+fn csv_task(columns: &[String], fields: &[String]) -> Result<ImportTaskDTO, String> {
+    if fields.len() != columns.len() {
+        return Err(format!(
+            "the row has {} fields, but the header has {}",
+            fields.len(),
+            columns.len()
+        ));
+    }
+
+    let mut task = ImportTaskDTO::default();
+    for (column, field) in columns.iter().zip(fields) {
+        let value = field.trim();
+        if value.is_empty() && column != "title" {
+            continue;
+        }
+        let score = |name| {
+            value
+                .parse::<i32>()
+                .map_err(|_| TaskError::InvalidScore(name, value.to_owned()).to_string())
+        };
+        match column.as_str() {
+            "title" => task.title = field.to_owned(),
+            "priority" => task.priority = Some(score("priority")?),
+            "urgency" => task.urgency = Some(score("urgency")?),
+            "cost" => task.cost = Some(score("cost")?),
+            "tags" => task.tags = value.split_whitespace().map(str::to_owned).collect(),
+            "due" => {
+                let due = value.parse::<DueDate>().map_err(|err| err.to_string())?;
+                task.due_on = Some(due.to_naive_date());
+            }
+            "status" => {
+                task.is_closed = match value {
+                    "open" => false,
+                    "closed" => true,
+                    _ => {
+                        return Err(format!(
+                            "`{}` is not a status, it must be open or closed",
+                            value
+                        ))
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    Ok(task)
+}
+
+/// parse_csv splits CSV into records with the lines which they begin at.
+/// A quoted field may have commas and newlines, and quotes doubled as `""`.
+fn parse_csv(text: &str) -> Result<Vec<(usize, Vec<String>)>, RowErrorDTO> {
+    let mut records = vec![];
+    let mut record = vec![];
+    let mut field = String::new();
+    let (mut line, mut start) = (1, 1);
+    let mut in_quotes = false;
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        match (in_quotes, c) {
+            (true, '"') if chars.peek() == Some(&'"') => {
+                chars.next();
+                field.push('"');
+            }
+            (true, '"') => in_quotes = false,
+            (true, c) => {
+                if c == '\n' {
+                    line += 1;
+                }
+                field.push(c);
+            }
+            (false, '"') if field.is_empty() => in_quotes = true,
+            (false, ',') => record.push(mem::take(&mut field)),
+            (false, '\r') if chars.peek() == Some(&'\n') => {}
+            (false, '\n') => {
+                record.push(mem::take(&mut field));
+                records.push((start, mem::take(&mut record)));
+                line += 1;
+                start = line;
+            }
+            (false, c) => field.push(c),
+        }
+    }
+    if in_quotes {
+        return Err(RowErrorDTO {
+            line: start,
+            message: "a quoted field is not closed".to_owned(),
+        });
+    }
+    if !field.is_empty() || !record.is_empty() {
+        record.push(field);
+        records.push((start, record));
+    }
+
+    Ok(records)
+}
+
+/// PROGRESS_WIDTH is the number of cells of the bar of progress_bar.
+const PROGRESS_WIDTH: usize = 30;
+
+/// digest tells files apart to keep the checkpoint of each import.
+pub fn digest(text: &str) -> String {
+    format!("{:x}", Sha256::digest(text.as_bytes()))
+}
+
+/// progress_bar renders the progress such as `[#######.......]  50% 250/500 tasks`.
+pub fn progress_bar(progress: &ImportProgressDTO) -> String {
+    let total = progress.total.max(1);
+    let filled = PROGRESS_WIDTH * progress.imported / total;
+    format!(
+        "[{}{}] {:>3}% {}/{} tasks",
+        "#".repeat(filled),
+        ".".repeat(PROGRESS_WIDTH - filled),
+        100 * progress.imported / total,
+        progress.imported,
+        progress.total
+    )
+}
+
+/// message is the error without its line, as a row is always the first line of its JSON.
+/// The column is kept to find the value in a long line.
+fn message(err: &serde_json::Error) -> String {
+    let err_string = err.to_string();
+    let position = format!(" at line {} column {}", err.line(), err.column());
+    match err_string.strip_suffix(&position) {
+        Some(message) => format!("{} at column {}", message, err.column()),
+        None => err_string,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+
+    #[test]
+    fn test_read_rows() {
+        let text = concat!(
+            r#"{"id":3,"title":"buy milk","priority":10,"urgency":20,"cost":1,"tags":["home"],"closed":false}"#,
+            "\n\n",
+            r#"{"id":4,"priority":10,"urgency":20,"cost":1,"tags":[],"closed":false}"#,
+            "\n",
+            r#"{"id":5,"title":"pay rent","priority":"high","urgency":20,"cost":1,"tags":[],"closed":false}"#,
+            "\n",
+            r#"{"id":6,"title":"renew","priority":1,"urgency":1,"cost":1,"tags":[],"closed":false,"due":"2024-13-01"}"#,
+            "\n",
+        );
+
+        let (rows, errors) = read_rows(text);
+        assert_eq!(rows.len(), 1);
+        assert_eq!((rows[0].line, rows[0].task.title.as_str()), (1, "buy milk"));
+        assert_eq!(
+            errors,
+            vec![
+                RowErrorDTO {
+                    line: 3,
+                    message: String::from("missing field `title` at column 69"),
+                },
+                RowErrorDTO {
+                    line: 4,
+                    message: String::from(
+                        "invalid type: string \"high\", expected i32 at column 44"
+                    ),
+                },
+                RowErrorDTO {
+                    line: 5,
+                    message: String::from("input is out of range at column 101"),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_read_csv_rows() {
+        let text = concat!(
+            "Title,Priority,tags,due,status,id\r\n",
+            "\"buy \"\"oat\"\" milk, 2l\",20,home errand,2024-05-01,closed,3\r\n",
+            "\n",
+            "\"a title\nin two lines\",,,,,\n",
+            "pay rent,high,,,,\n",
+            "renew,,,2024-13-01,,\n",
+            "walk,,,,done,\n",
+            "call mom,10\n",
+        );
+
+        let (rows, errors) = read_csv_rows(text);
+        assert_eq!(
+            rows.iter()
+                .map(|r| (r.line, r.task.title.as_str()))
+                .collect::<Vec<_>>(),
+            vec![(2, "buy \"oat\" milk, 2l"), (4, "a title\nin two lines")]
+        );
+        let task = &rows[0].task;
+        assert_eq!((task.priority, task.cost), (Some(20), None));
+        assert_eq!(task.tags, vec!["home", "errand"]);
+        assert_eq!(task.due_on, NaiveDate::from_ymd_opt(2024, 5, 1));
+        assert!(task.is_closed);
+        assert_eq!(
+            errors,
+            vec![
+                RowErrorDTO {
+                    line: 6,
+                    message: String::from("`high` is not a valid priority, it must be an integer"),
+                },
+                RowErrorDTO {
+                    line: 7,
+                    message: String::from(
+                        "`2024-13-01` is not a valid due date, it must be YYYY-MM-DD"
+                    ),
+                },
+                RowErrorDTO {
+                    line: 8,
+                    message: String::from("`done` is not a status, it must be open or closed"),
+                },
+                RowErrorDTO {
+                    line: 9,
+                    message: String::from("the row has 2 fields, but the header has 6"),
+                },
+            ]
+        );
+
+        let (_, errors) = read_csv_rows("title,prio\nfix,1\n");
+        assert_eq!(
+            errors[0].message,
+            "`prio` is not a column, columns are id, title, priority, urgency, cost, tags, due, status"
+        );
+        let (_, errors) = read_csv_rows("title\n\"fix\n");
+        assert_eq!(
+            errors,
+            vec![RowErrorDTO {
+                line: 2,
+                message: String::from("a quoted field is not closed"),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_progress_bar() {
+        let progress = |imported, total| ImportProgressDTO {
+            imported,
+            total,
+            last_line: 0,
+        };
+        assert_eq!(
+            progress_bar(&progress(250, 1000)),
+            "[#######.......................]  25% 250/1000 tasks"
+        );
+        assert_eq!(
+            progress_bar(&progress(3, 3)),
+            "[##############################] 100% 3/3 tasks"
+        );
+    }
+}
@@ -0,0 +1,321 @@
+//! # init
+//!
+//! init sets up taskmr on the first run.
+//! It asks a few questions, writes the config file and creates the task database.
+
+use std::fs;
+use std::io::{BufRead, Write};
+use std::path::{Path, PathBuf};
+
+use crate::{Error, Result};
+use thiserror::Error;
+
+use crate::infra::config::{
+    AutoContext, ColorChoice, Config, DefaultsConfig, Engine, CONFIG_FILE_NAME,
+};
+use crate::infra::sqlite::setup;
+
+/// DEFAULT_PRIORITY and DEFAULT_COST are offered when no default is configured.
+const DEFAULT_PRIORITY: i32 = 10;
+const DEFAULT_COST: i32 = 10;
+
+/// TUTORIAL is printed after the setup.
+const TUTORIAL: &str = "
+Try these to get started:
+
+  taskmr add \"write the report\" --priority 20 --cost 3
+  taskmr list
+  taskmr close 1
+
+Run `taskmr help` to see all commands.
+";
+
+/// InitError is raised when taskmr can not be set up.
+#[derive(Error, Debug, PartialEq, Eq)]
+pub enum InitError {
+    #[error("{0} already exists, pass --force to overwrite it")]
+    AlreadyInitialized(PathBuf),
+}
+
+/// InitOptions is options of `taskmr init`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct InitOptions {
+    /// defaults accepts all defaults without asking.
+    pub defaults: bool,
+    /// force overwrites the existing config file.
+    pub force: bool,
+    /// engine is written to the config, as given by the global `--engine`.
+    pub engine: Option<Engine>,
+}
+
+/// Wizard asks questions on `input` and writes prompts to `output`.
+pub struct Wizard<R: BufRead, W: Write> {
+    input: R,
+    output: W,
+}
+
+impl<R: BufRead, W: Write> Wizard<R, W> {
+    /// construct a Wizard.
+    pub fn new(input: R, output: W) -> Self {
+        Wizard { input, output }
+    }
+
+    /// run sets up taskmr in `config_dir` and returns the written config.
+    pub fn run(&mut self, config_dir: &Path, options: InitOptions) -> Result<Config> {
+        let config_path = config_dir.join(CONFIG_FILE_NAME);
+        if config_path.exists() && !options.force {
+            return Err(InitError::AlreadyInitialized(config_path).into());
+        }
+
+        let mut config = if options.defaults {
+            Config::default()
+        } else {
+            self.ask_config(config_dir)?
+        };
+        config.engine = options.engine;
+
+        fs::create_dir_all(config_dir).map_err(|err| {
+            Error::config(format!("failed to create {}", config_dir.display()), err)
+        })?;
+        fs::write(&config_path, toml::to_string_pretty(&config)?).map_err(|err| {
+            Error::config(format!("failed to write {}", config_path.display()), err)
+        })?;
+        writeln!(self.output, "Wrote {}", config_path.display())?;
+
+        let db_path = config.database_path(config_dir);
+        setup::create_database(&db_path)?;
+        writeln!(self.output, "Created {}", db_path.display())?;
+
+        write!(self.output, "{}", TUTORIAL)?;
+
+        Ok(config)
+    }
+
+    /// ask_config asks the settings. An empty answer takes the default in brackets.
+    fn ask_config(&mut self, config_dir: &Path) -> Result<Config> {
+        let default_db = Config::default().database_path(config_dir);
+        let db_path = self.ask(
+            "Where should the task database be stored?",
+            &default_db.display().to_string(),
+            |s| Some(PathBuf::from(s)),
+        )?;
+        let priority = self.ask(
+            "Default priority of new tasks (higher is more important)",
+            &DEFAULT_PRIORITY.to_string(),
+            |s| s.parse::<i32>().ok(),
+        )?;
+        let cost = self.ask(
+            "Default cost of new tasks",
+            &DEFAULT_COST.to_string(),
+            |s| s.parse::<i32>().ok().filter(|c| *c >= 0),
+        )?;
+        let color = self.ask(
+            "Colorize the output? (auto/always/never)",
+            "auto",
+            |s| match s {
+                "auto" => Some(ColorChoice::Auto),
+                "always" => Some(ColorChoice::Always),
+                "never" => Some(ColorChoice::Never),
+                _ => None,
+            },
+        )?;
+        let auto_context = self.ask(
+            "Context to tag new tasks with (none/git)",
+            "none",
+            |s| match s {
+                "none" => Some(AutoContext::None),
+                "git" => Some(AutoContext::Git),
+                _ => None,
+            },
+        )?;
+
+        Ok(Config {
+            auto_context,
+            color,
+            // NOTE: the default location is left out, so that the config can move to another machine.
+            database: (db_path != default_db).then_some(db_path),
+            defaults: DefaultsConfig {
+                priority: (priority != DEFAULT_PRIORITY).then_some(priority),
+                cost: (cost != DEFAULT_COST).then_some(cost),
+            },
+            ..Config::default()
+        })
+    }
+
+    /// ask asks the question until the answer is parsed. The end of input takes the default.
+    fn ask<T>(
+        &mut self,
+        question: &str,
+        default: &str,
+        parse: impl Fn(&str) -> Option<T>,
+    ) -> Result<T> {
+        loop {
+            write!(self.output, "{} [{}]: ", question, default)?;
+            self.output.flush()?;
+
+            let mut line = String::new();
+            let answer = match self.input.read_line(&mut line)? {
+                0 => default,
+                _ if line.trim().is_empty() => default,
+                _ => line.trim(),
+            };
+            if let Some(value) = parse(answer) {
+                return Ok(value);
+            }
+            writeln!(self.output, "`{}` is not a valid answer.", answer)?;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    fn temp_dir() -> PathBuf {
+        let nanos = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_nanos();
+        std::env::temp_dir().join(format!("taskmr-init-{}-{}", std::process::id(), nanos))
+    }
+
+    #[test]
+    fn test_run() {
+        #[derive(Debug)]
+        struct TestCase {
+            args: &'static str,
+            want: Config,
+            name: String,
+        }
+
+        let table = [
+            TestCase {
+                name: String::from("normal: all defaults"),
+                args: "\n\n\n\n\n",
+                want: Config::default(),
+            },
+            TestCase {
+                name: String::from("normal: end of input"),
+                args: "",
+                want: Config::default(),
+            },
+            TestCase {
+                name: String::from("normal: answered after a retry"),
+                args: "tasks.db\n20\n-1\n5\nnever\ngit\n",
+                want: Config {
+                    auto_context: AutoContext::Git,
+                    color: ColorChoice::Never,
+                    database: Some(PathBuf::from("tasks.db")),
+                    defaults: DefaultsConfig {
+                        priority: Some(20),
+                        cost: Some(5),
+                    },
+                    ..Config::default()
+                },
+            },
+        ];
+
+        for test_case in table {
+            let dir = temp_dir();
+            let mut output = Vec::new();
+            let got = Wizard::new(Cursor::new(test_case.args), &mut output)
+                .run(&dir, InitOptions::default())
+                .unwrap();
+            assert_eq!(got, test_case.want, "Failed in the \"{}\".", test_case.name);
+            assert_eq!(
+                Config::load(&dir.join(CONFIG_FILE_NAME)).unwrap(),
+                test_case.want,
+                "Failed in the \"{}\".",
+                test_case.name
+            );
+            assert!(
+                got.database_path(&dir).is_file(),
+                "Failed in the \"{}\".",
+                test_case.name
+            );
+            fs::remove_dir_all(dir).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_run_twice() {
+        let dir = temp_dir();
+        let options = InitOptions {
+            defaults: true,
+            ..InitOptions::default()
+        };
+        Wizard::new(Cursor::new(""), Vec::new())
+            .run(&dir, options)
+            .unwrap();
+
+        let err = Wizard::new(Cursor::new(""), Vec::new())
+            .run(&dir, options)
+            .unwrap_err();
+        assert_eq!(
+            err.downcast_ref::<InitError>(),
+            Some(&InitError::AlreadyInitialized(dir.join(CONFIG_FILE_NAME)))
+        );
+
+        let options = InitOptions {
+            force: true,
+            ..options
+        };
+        Wizard::new(Cursor::new(""), Vec::new())
+            .run(&dir, options)
+            .unwrap();
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_run_with_engine() {
+        #[derive(Debug)]
+        struct TestCase {
+            args: InitOptions,
+            want: Option<Engine>,
+            name: String,
+        }
+
+        let table = [
+            TestCase {
+                name: String::from("normal: no engine"),
+                args: InitOptions {
+                    defaults: true,
+                    ..InitOptions::default()
+                },
+                want: None,
+            },
+            TestCase {
+                name: String::from("normal: es with defaults"),
+                args: InitOptions {
+                    defaults: true,
+                    engine: Some(Engine::Es),
+                    ..InitOptions::default()
+                },
+                want: Some(Engine::Es),
+            },
+            TestCase {
+                name: String::from("normal: legacy with questions"),
+                args: InitOptions {
+                    engine: Some(Engine::Legacy),
+                    ..InitOptions::default()
+                },
+                want: Some(Engine::Legacy),
+            },
+        ];
+
+        for test_case in table {
+            let dir = temp_dir();
+            Wizard::new(Cursor::new(""), Vec::new())
+                .run(&dir, test_case.args)
+                .unwrap();
+            assert_eq!(
+                Config::load(&dir.join(CONFIG_FILE_NAME)).unwrap().engine,
+                test_case.want,
+                "Failed in the \"{}\".",
+                test_case.name
+            );
+            fs::remove_dir_all(dir).unwrap();
+        }
+    }
+}
@@ -3,3 +3,13 @@
 //! Handle CLI with clap.
 
 pub mod cli;
+pub mod command_line;
+pub mod git;
+#[cfg(feature = "es")]
+pub mod import;
+pub mod init;
+#[cfg(feature = "es")]
+pub mod quick_add;
+pub mod span;
+pub mod task_ref;
+pub mod title_pattern;
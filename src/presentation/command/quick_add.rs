@@ -0,0 +1,220 @@
+//! # quick_add
+//!
+//! quick_add parses a task written in one string, such as
+//! `Fix login bug !80 ~3 #bug @web due:fri`, which `taskmr q` adds.
+
+use chrono::{Datelike, Days, NaiveDate, Weekday};
+
+use crate::domain::es_task::{DueDate, Tag};
+
+/// PROJECT_NAMESPACE is the namespace of tags which `@project` gives.
+const PROJECT_NAMESPACE: &str = "project";
+
+/// ESCAPE keeps a word starting with a marker in the title, such as `\#1`.
+const ESCAPE: char = '\\';
+
+/// QuickAdd is a task parsed from a quick-add string.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct QuickAdd {
+    /// title is the words which are not tokens, joined by a space.
+    pub title: String,
+    /// priority is given by `!80`.
+    pub priority: Option<i32>,
+    /// cost is given by `~3`.
+    pub cost: Option<i32>,
+    /// tags are given by `#bug`, and by `@web` as `project:web`, in their order.
+    pub tags: Vec<String>,
+    /// due is given by `due:fri`, `due:tomorrow` or `due:2024-05-01`.
+    pub due: Option<DueDate>,
+}
+
+impl QuickAdd {
+    /// parse the words of a quick-add string. Relative due dates are counted from `today`.
+    pub fn parse(input: &str, today: NaiveDate) -> Result<Self, String> {
+        let mut quick_add = QuickAdd::default();
+        let mut title = vec![];
+        for word in input.split_whitespace() {
+            if let Some(rest) = word.strip_prefix(ESCAPE) {
+                title.push(rest);
+                continue;
+            }
+            if let Some(due) = word.strip_prefix("due:") {
+                let due = parse_due(due, today).ok_or_else(|| {
+                    format!(
+                        "`{}` is not a due date such as due:fri, due:tomorrow or due:2024-05-01",
+                        word
+                    )
+                })?;
+                set_once(&mut quick_add.due, due, "due date", word)?;
+                continue;
+            }
+            match word.chars().next() {
+                Some('!') => {
+                    let priority = parse_number(word, "priority", "!80")?;
+                    set_once(&mut quick_add.priority, priority, "priority", word)?;
+                }
+                Some('~') => {
+                    let cost = parse_number(word, "cost", "~3")?;
+                    set_once(&mut quick_add.cost, cost, "cost", word)?;
+                }
+                Some('#') => quick_add.tags.push(parse_tag(word, "#bug")?.to_owned()),
+                Some('@') => quick_add.tags.push(format!(
+                    "{}:{}",
+                    PROJECT_NAMESPACE,
+                    parse_tag(word, "@web")?
+                )),
+                _ => title.push(word),
+            }
+        }
+        if title.is_empty() {
+            return Err(format!(
+                "`{}` has no title, write the title besides tokens such as !80",
+                input.trim()
+            ));
+        }
+        quick_add.title = title.join(" ");
+
+        Ok(quick_add)
+    }
+}
+
+/// parse_number parses the number after the marker of the word.
+fn parse_number(word: &str, name: &str, example: &str) -> Result<i32, String> {
+    word[1..]
+        .parse()
+        .map_err(|_| format!("`{}` is not a {} such as {}", word, name, example))
+}
+
+/// parse_tag returns the name after the marker of the word, which must be a valid tag.
+fn parse_tag<'a>(word: &'a str, example: &str) -> Result<&'a str, String> {
+    let name = &word[1..];
+    Tag::new(name).map_err(|_| format!("`{}` is not a tag such as {}", word, example))?;
+    Ok(name)
+}
+
+/// set_once sets the value, which can be given only once.
+fn set_once<T>(slot: &mut Option<T>, value: T, name: &str, word: &str) -> Result<(), String> {
+    if slot.is_some() {
+        return Err(format!("`{}` gives the {} twice", word, name));
+    }
+    *slot = Some(value);
+    Ok(())
+}
+
+/// parse_due parses `today`, `tomorrow`, a weekday such as `fri` or `friday`, or `YYYY-MM-DD`.
+/// A weekday is the next one after today, so that `due:fri` on a Friday is a week later.
+fn parse_due(s: &str, today: NaiveDate) -> Option<DueDate> {
+    let day = match s.to_lowercase().as_str() {
+        "today" => today,
+        "tomorrow" => today + Days::new(1),
+        s => match s.parse::<Weekday>() {
+            Ok(weekday) => {
+                let ahead = (weekday.num_days_from_monday() + 7
+                    - today.weekday().num_days_from_monday()
+                    - 1)
+                    % 7
+                    + 1;
+                today + Days::new(ahead.into())
+            }
+            Err(_) => return s.parse::<DueDate>().ok(),
+        },
+    };
+
+    Some(DueDate::new(day))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        #[derive(Debug)]
+        struct TestCase {
+            args: &'static str,
+            want: Result<QuickAdd, &'static str>,
+            name: String,
+        }
+
+        // NOTE: 2024-05-01 is a Wednesday.
+        let today = NaiveDate::from_ymd_opt(2024, 5, 1).unwrap();
+        let due = |day| Some(DueDate::new(NaiveDate::from_ymd_opt(2024, 5, day).unwrap()));
+        let table = [
+            TestCase {
+                name: String::from("nominal"),
+                args: "Fix login bug !80 ~3 #bug @web due:fri",
+                want: Ok(QuickAdd {
+                    title: String::from("Fix login bug"),
+                    priority: Some(80),
+                    cost: Some(3),
+                    tags: vec![String::from("bug"), String::from("project:web")],
+                    due: due(3),
+                }),
+            },
+            TestCase {
+                name: String::from("normal: tokens between words"),
+                args: "#home pay  the !20 bill",
+                want: Ok(QuickAdd {
+                    title: String::from("pay the bill"),
+                    priority: Some(20),
+                    tags: vec![String::from("home")],
+                    ..Default::default()
+                }),
+            },
+            TestCase {
+                name: String::from("normal: the same weekday is a week later"),
+                args: "water plants due:Wednesday",
+                want: Ok(QuickAdd {
+                    title: String::from("water plants"),
+                    due: due(8),
+                    ..Default::default()
+                }),
+            },
+            TestCase {
+                name: String::from("normal: tomorrow and escaped markers"),
+                args: "close \\#12 due:tomorrow",
+                want: Ok(QuickAdd {
+                    title: String::from("close #12"),
+                    due: due(2),
+                    ..Default::default()
+                }),
+            },
+            TestCase {
+                name: String::from("abnormal: priority is not a number"),
+                args: "fix !high",
+                want: Err("`!high` is not a priority such as !80"),
+            },
+            TestCase {
+                name: String::from("abnormal: empty tag"),
+                args: "fix #",
+                want: Err("`#` is not a tag such as #bug"),
+            },
+            TestCase {
+                name: String::from("abnormal: unknown due date"),
+                args: "fix due:someday",
+                want: Err(
+                    "`due:someday` is not a due date such as due:fri, due:tomorrow or due:2024-05-01",
+                ),
+            },
+            TestCase {
+                name: String::from("abnormal: cost twice"),
+                args: "fix ~3 ~5",
+                want: Err("`~5` gives the cost twice"),
+            },
+            TestCase {
+                name: String::from("abnormal: no title"),
+                args: "!80 #bug",
+                want: Err("`!80 #bug` has no title, write the title besides tokens such as !80"),
+            },
+        ];
+
+        for test_case in table {
+            assert_eq!(
+                QuickAdd::parse(test_case.args, today),
+                test_case.want.map_err(String::from),
+                "Failed in the \"{}\".",
+                test_case.name,
+            );
+        }
+    }
+}
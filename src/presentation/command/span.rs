@@ -0,0 +1,129 @@
+//! # span
+//!
+//! span parses lengths of time given on the command line, such as `1h30m` or `30d`.
+
+use std::fmt;
+use std::str::FromStr;
+use std::time::Duration;
+
+/// UNITS are suffixes of a span and their lengths in seconds.
+const UNITS: [(char, u64); 4] = [('w', 7 * 86400), ('d', 86400), ('h', 3600), ('m', 60)];
+
+/// Span is a positive length of time made of weeks, days, hours and minutes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Span(Duration);
+
+impl Span {
+    /// get the span as Duration.
+    pub fn duration(&self) -> Duration {
+        self.0
+    }
+}
+
+impl FromStr for Span {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("`{}` is not a span such as 1h30m or 30d", s);
+
+        let mut seconds: u64 = 0;
+        let mut digits = String::new();
+        for c in s.chars() {
+            if c.is_ascii_digit() {
+                digits.push(c);
+                continue;
+            }
+            let (_, unit) = UNITS.iter().find(|(u, _)| *u == c).ok_or_else(invalid)?;
+            let n: u64 = digits.parse().map_err(|_| invalid())?;
+            seconds = n
+                .checked_mul(*unit)
+                .and_then(|s| seconds.checked_add(s))
+                .ok_or_else(invalid)?;
+            digits.clear();
+        }
+        if !digits.is_empty() || seconds == 0 {
+            return Err(invalid());
+        }
+
+        Ok(Span(Duration::from_secs(seconds)))
+    }
+}
+
+impl fmt::Display for Span {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let minutes = self.0.as_secs() / 60;
+        match (minutes / 60, minutes % 60) {
+            (0, m) => write!(f, "{}m", m),
+            (h, 0) => write!(f, "{}h", h),
+            (h, m) => write!(f, "{}h{}m", h, m),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_str() {
+        #[derive(Debug)]
+        struct TestCase {
+            args: &'static str,
+            want: Option<u64>,
+            name: String,
+        }
+
+        let table = [
+            TestCase {
+                name: String::from("normal: minutes"),
+                args: "45m",
+                want: Some(45 * 60),
+            },
+            TestCase {
+                name: String::from("normal: hours and minutes"),
+                args: "1h30m",
+                want: Some(90 * 60),
+            },
+            TestCase {
+                name: String::from("normal: weeks and days"),
+                args: "1w2d",
+                want: Some(9 * 86400),
+            },
+            TestCase {
+                name: String::from("abnormal: no unit"),
+                args: "30",
+                want: None,
+            },
+            TestCase {
+                name: String::from("abnormal: zero"),
+                args: "0m",
+                want: None,
+            },
+            TestCase {
+                name: String::from("abnormal: unknown unit"),
+                args: "3s",
+                want: None,
+            },
+        ];
+
+        for test_case in table {
+            assert_eq!(
+                test_case
+                    .args
+                    .parse::<Span>()
+                    .ok()
+                    .map(|s| s.duration().as_secs()),
+                test_case.want,
+                "Failed in the \"{}\".",
+                test_case.name,
+            );
+        }
+    }
+
+    #[test]
+    fn test_display() {
+        assert_eq!("90m".parse::<Span>().unwrap().to_string(), "1h30m");
+        assert_eq!("2h".parse::<Span>().unwrap().to_string(), "2h");
+        assert_eq!("45m".parse::<Span>().unwrap().to_string(), "45m");
+    }
+}
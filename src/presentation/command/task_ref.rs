@@ -0,0 +1,93 @@
+//! # task_ref
+//!
+//! task_ref parses references to tasks given on the command line.
+
+use std::str::FromStr;
+
+use crate::Result;
+
+use crate::infra::selection::SelectionStore;
+
+/// ROW_PREFIX is the prefix of row numbers such as `%2`.
+const ROW_PREFIX: char = '%';
+
+/// TaskRef refers to a task by its id or by a row number of the last numbered list.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TaskRef {
+    Id(i64),
+    Row(usize),
+}
+
+impl TaskRef {
+    /// resolve returns the id of the task.
+    pub fn resolve(&self, selection: &SelectionStore) -> Result<i64> {
+        match self {
+            TaskRef::Id(id) => Ok(*id),
+            TaskRef::Row(row) => selection.resolve(*row),
+        }
+    }
+}
+
+impl FromStr for TaskRef {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.strip_prefix(ROW_PREFIX) {
+            Some(row) => row
+                .parse::<usize>()
+                .map(TaskRef::Row)
+                .map_err(|_| format!("`{}` is not a row number such as %2", s)),
+            None => s
+                .parse::<i64>()
+                .map(TaskRef::Id)
+                .map_err(|_| format!("`{}` is neither an id nor a row number such as %2", s)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_str() {
+        #[derive(Debug)]
+        struct TestCase {
+            args: &'static str,
+            want: Option<TaskRef>,
+            name: String,
+        }
+
+        let table = [
+            TestCase {
+                name: String::from("normal: id"),
+                args: "12",
+                want: Some(TaskRef::Id(12)),
+            },
+            TestCase {
+                name: String::from("normal: row"),
+                args: "%2",
+                want: Some(TaskRef::Row(2)),
+            },
+            TestCase {
+                name: String::from("abnormal: empty row"),
+                args: "%",
+                want: None,
+            },
+            TestCase {
+                name: String::from("abnormal: word"),
+                args: "abc",
+                want: None,
+            },
+        ];
+
+        for test_case in table {
+            assert_eq!(
+                test_case.args.parse::<TaskRef>().ok(),
+                test_case.want,
+                "Failed in the \"{}\".",
+                test_case.name,
+            );
+        }
+    }
+}
@@ -0,0 +1,113 @@
+//! # title_pattern
+//!
+//! title_pattern parses regular expressions which `list --regex` matches titles with.
+
+use std::str::FromStr;
+
+use regex::{Regex, RegexBuilder};
+
+/// MAX_PATTERN_LEN is the longest pattern accepted, in bytes.
+const MAX_PATTERN_LEN: usize = 1024;
+
+/// MAX_COMPILED_SIZE bounds the memory of the compiled pattern and its lazy DFA, in bytes.
+/// Patterns such as `\w{1000}{1000}` exceed it instead of exhausting memory.
+const MAX_COMPILED_SIZE: usize = 1 << 20;
+
+/// TitlePattern is a regular expression matched against titles.
+/// The regex crate matches in linear time of the title, so no pattern can take exponential time.
+#[derive(Debug, Clone)]
+pub struct TitlePattern(Regex);
+
+impl TitlePattern {
+    /// is_match returns true if the pattern matches anywhere in the title.
+    pub fn is_match(&self, title: &str) -> bool {
+        self.0.is_match(title)
+    }
+}
+
+impl From<TitlePattern> for Regex {
+    fn from(pattern: TitlePattern) -> Self {
+        pattern.0
+    }
+}
+
+impl FromStr for TitlePattern {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.len() > MAX_PATTERN_LEN {
+            return Err(format!(
+                "the pattern is longer than {} bytes",
+                MAX_PATTERN_LEN
+            ));
+        }
+
+        RegexBuilder::new(s)
+            .size_limit(MAX_COMPILED_SIZE)
+            .dfa_size_limit(MAX_COMPILED_SIZE)
+            .build()
+            .map(TitlePattern)
+            .map_err(|err| err.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_str() {
+        #[derive(Debug)]
+        struct TestCase {
+            args: String,
+            want: Option<(&'static str, bool)>,
+            name: String,
+        }
+
+        let table = [
+            TestCase {
+                name: String::from("normal: match"),
+                args: String::from(r"^fix #\d+"),
+                want: Some(("fix #12 in the parser", true)),
+            },
+            TestCase {
+                name: String::from("normal: case insensitive flag"),
+                args: String::from("(?i)report"),
+                want: Some(("Write the REPORT", true)),
+            },
+            TestCase {
+                name: String::from("normal: no match"),
+                args: String::from("^report"),
+                want: Some(("Write the report", false)),
+            },
+            TestCase {
+                name: String::from("abnormal: invalid"),
+                args: String::from("(unclosed"),
+                want: None,
+            },
+            TestCase {
+                name: String::from("abnormal: too large when compiled"),
+                args: String::from(r"\w{1000}{1000}"),
+                want: None,
+            },
+            TestCase {
+                name: String::from("abnormal: too long"),
+                args: "a".repeat(MAX_PATTERN_LEN + 1),
+                want: None,
+            },
+        ];
+
+        for test_case in table {
+            let got = TitlePattern::from_str(&test_case.args).ok();
+            match test_case.want {
+                Some((title, matched)) => assert_eq!(
+                    got.map(|p| p.is_match(title)),
+                    Some(matched),
+                    "Failed in the \"{}\".",
+                    test_case.name
+                ),
+                None => assert!(got.is_none(), "Failed in the \"{}\".", test_case.name),
+            }
+        }
+    }
+}
@@ -0,0 +1,123 @@
+//! # context
+//!
+//! context detects where taskmr is invoked from, so that added tasks can be tagged automatically.
+
+use std::path::Path;
+use std::process::Command;
+
+use crate::infra::config::AutoContext;
+
+/// WorkContext is what the user is working on.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WorkContext {
+    pub repository: String,
+    pub branch: Option<String>,
+}
+
+impl WorkContext {
+    /// tags returns tags representing the context, such as `repo:taskmr` and `branch:main`.
+    pub fn tags(&self) -> Vec<String> {
+        let mut tags = vec![format!("repo:{}", self.repository)];
+        if let Some(branch) = &self.branch {
+            tags.push(format!("branch:{}", branch));
+        }
+        tags
+    }
+}
+
+/// ContextDetector detects WorkContext of a directory.
+pub trait ContextDetector {
+    /// detect returns None if the directory has no context.
+    fn detect(&self, dir: &Path) -> Option<WorkContext>;
+}
+
+/// GitContextDetector detects the git repository and the branch by the git command.
+pub struct GitContextDetector;
+
+impl ContextDetector for GitContextDetector {
+    fn detect(&self, dir: &Path) -> Option<WorkContext> {
+        let toplevel = git(dir, &["rev-parse", "--show-toplevel"])?;
+        let branch = git(dir, &["rev-parse", "--abbrev-ref", "HEAD"]);
+        parse_git_context(&toplevel, branch.as_deref())
+    }
+}
+
+/// detector returns ContextDetector for the setting.
+pub fn detector(auto_context: AutoContext) -> Option<Box<dyn ContextDetector>> {
+    match auto_context {
+        AutoContext::None => None,
+        AutoContext::Git => Some(Box::new(GitContextDetector)),
+    }
+}
+
+/// git runs the git command in the directory and returns its trimmed stdout on success.
+pub(crate) fn git(dir: &Path, args: &[&str]) -> Option<String> {
+    let output = Command::new("git")
+        .args(args)
+        .current_dir(dir)
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+
+    let stdout = String::from_utf8(output.stdout).ok()?;
+    Some(stdout.trim().to_owned())
+}
+
+/// parse_git_context makes WorkContext from outputs of git.
+/// The branch is omitted on a detached HEAD.
+fn parse_git_context(toplevel: &str, branch: Option<&str>) -> Option<WorkContext> {
+    let repository = sanitize(Path::new(toplevel).file_name()?.to_str()?);
+    let branch = branch
+        .filter(|b| !b.is_empty() && *b != "HEAD")
+        .map(sanitize);
+
+    Some(WorkContext { repository, branch })
+}
+
+/// sanitize replaces characters which can not be used in tags.
+fn sanitize(s: &str) -> String {
+    s.replace(|c: char| c.is_whitespace() || c == ',', "-")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_git_context() {
+        #[derive(Debug)]
+        struct TestCase {
+            args: (&'static str, Option<&'static str>),
+            want: Option<Vec<String>>,
+            name: String,
+        }
+
+        let table = [
+            TestCase {
+                name: String::from("normal: with branch"),
+                args: ("/home/me/src/taskmr", Some("feature/tags")),
+                want: Some(vec![
+                    String::from("repo:taskmr"),
+                    String::from("branch:feature/tags"),
+                ]),
+            },
+            TestCase {
+                name: String::from("normal: detached head"),
+                args: ("/home/me/src/taskmr", Some("HEAD")),
+                want: Some(vec![String::from("repo:taskmr")]),
+            },
+            TestCase {
+                name: String::from("abnormal: root"),
+                args: ("/", None),
+                want: None,
+            },
+        ];
+
+        for test_case in table {
+            let got = parse_git_context(test_case.args.0, test_case.args.1).map(|c| c.tags());
+            assert_eq!(got, test_case.want, "Failed in the \"{}\".", test_case.name);
+        }
+    }
+}
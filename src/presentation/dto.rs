@@ -0,0 +1,426 @@
+//! # dto
+//!
+//! dto defines wire DTOs, which are the stable representation of use case DTOs in JSON outputs.
+//! A version only gains optional fields. Renaming or removing a field makes a new version,
+//! so that readers of an older version keep reading outputs of newer taskmr.
+
+#[cfg(feature = "es")]
+use chrono::{NaiveDate, NaiveDateTime, SubsecRound};
+use serde::{Deserialize, Serialize};
+
+#[cfg(feature = "es")]
+use crate::usecase::es_list_task_usecase::{HabitProgressDTO, TaskDTO, WaitingOnDTO};
+#[cfg(feature = "es")]
+use crate::usecase::es_search_task_usecase::SearchMatchDTO;
+#[cfg(feature = "es")]
+use crate::usecase::es_show_task_usecase::TaskDetailDTO;
+use crate::usecase::list_summary::ListSummary;
+#[cfg(feature = "legacy")]
+use crate::usecase::list_task_usecase::TaskDTO as LegacyTaskDTO;
+
+/// TaskV1 is the version 1 of a task on the wire.
+/// Optional fields are left out if empty, so that simple tasks are short enough for a QR code.
+#[cfg(feature = "es")]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TaskV1 {
+    pub id: i64,
+    pub title: String,
+    pub priority: i32,
+    pub urgency: i32,
+    pub cost: i32,
+    pub tags: Vec<String>,
+    pub closed: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub habit: Option<HabitV1>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub waiting_on: Option<WaitingOnV1>,
+    /// follows is the id of the task which this task follows up.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub follows: Option<i64>,
+    /// parent is the id of the task which this task is a subtask of.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub parent: Option<i64>,
+    /// due is `YYYY-MM-DD`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub due: Option<NaiveDate>,
+    /// someday is whether the task is deferred out of the active backlog.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub someday: bool,
+    /// deleted is whether the task is deleted, which only `list --include-deleted` lists.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub deleted: bool,
+}
+
+/// HabitV1 is the version 1 of check-ins of a habit in the current period.
+#[cfg(feature = "es")]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HabitV1 {
+    pub done: usize,
+    pub times: u32,
+    pub period: String,
+}
+
+/// WaitingOnV1 is the version 1 of a person whom a task waits on.
+#[cfg(feature = "es")]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WaitingOnV1 {
+    pub person: String,
+    /// follow_up_on is `YYYY-MM-DD`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub follow_up_on: Option<NaiveDate>,
+}
+
+#[cfg(feature = "es")]
+impl From<&TaskDTO> for TaskV1 {
+    fn from(task: &TaskDTO) -> Self {
+        TaskV1 {
+            id: task.id,
+            title: task.title.clone(),
+            priority: task.priority,
+            urgency: task.urgency,
+            cost: task.cost,
+            tags: task.tags.clone(),
+            closed: task.is_closed,
+            habit: task.habit.as_ref().map(|h| HabitV1 {
+                done: h.done,
+                times: h.times,
+                period: h.period.clone(),
+            }),
+            waiting_on: task.waiting_on.as_ref().map(|w| WaitingOnV1 {
+                person: w.person.clone(),
+                follow_up_on: w.follow_up_on,
+            }),
+            follows: task.follows,
+            parent: task.parent,
+            due: task.due_on,
+            someday: task.is_someday,
+            deleted: task.is_deleted,
+        }
+    }
+}
+
+#[cfg(feature = "es")]
+impl From<TaskV1> for TaskDTO {
+    fn from(task: TaskV1) -> Self {
+        TaskDTO {
+            id: task.id,
+            title: task.title,
+            priority: task.priority,
+            urgency: task.urgency,
+            cost: task.cost,
+            tags: task.tags,
+            is_closed: task.closed,
+            is_deleted: task.deleted,
+            is_archived: false,
+            habit: task.habit.map(|h| HabitProgressDTO {
+                done: h.done,
+                times: h.times,
+                period: h.period,
+            }),
+            waiting_on: task.waiting_on.map(|w| WaitingOnDTO {
+                person: w.person,
+                follow_up_on: w.follow_up_on,
+            }),
+            follows: task.follows,
+            parent: task.parent,
+            due_on: task.due,
+            // NOTE: whether it is overdue depends on the day of reading.
+            is_overdue: false,
+            is_someday: task.someday,
+            elapsed: Default::default(),
+            is_timer_running: false,
+            pomodoros: 0,
+        }
+    }
+}
+
+/// TaskDetailV1 is the version 1 of the full detail of a task, which `show` prints.
+#[cfg(feature = "es")]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TaskDetailV1 {
+    pub id: i64,
+    pub title: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    pub priority: i32,
+    pub urgency: i32,
+    pub cost: i32,
+    pub tags: Vec<String>,
+    pub closed: bool,
+    pub deleted: bool,
+    pub archived: bool,
+    pub locked: bool,
+    pub private: bool,
+    /// inbox is whether the task is captured and not triaged yet.
+    pub inbox: bool,
+    pub someday: bool,
+    /// due is `YYYY-MM-DD`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub due: Option<NaiveDate>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub parent: Option<i64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub follows: Option<i64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub waiting_on: Option<WaitingOnV1>,
+    /// elapsed_seconds is the time spent on the task.
+    pub elapsed_seconds: u64,
+    pub pomodoros: u32,
+    /// created_at is `YYYY-MM-DDTHH:MM:SS` in local time.
+    pub created_at: NaiveDateTime,
+    /// closed_at is `YYYY-MM-DDTHH:MM:SS` in local time.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub closed_at: Option<NaiveDateTime>,
+    /// events are what changed the task, in the order they occurred.
+    pub events: Vec<EventV1>,
+}
+
+/// EventV1 is the version 1 of an event of a task.
+#[cfg(feature = "es")]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EventV1 {
+    /// occurred_at is `YYYY-MM-DDTHH:MM:SS` in local time.
+    pub occurred_at: NaiveDateTime,
+    /// change describes the event for people, such as `priority set to 80`.
+    pub change: String,
+}
+
+#[cfg(feature = "es")]
+impl From<&TaskDetailDTO> for TaskDetailV1 {
+    fn from(task: &TaskDetailDTO) -> Self {
+        TaskDetailV1 {
+            id: task.id,
+            title: task.title.clone(),
+            description: task.description.clone(),
+            priority: task.priority,
+            urgency: task.urgency,
+            cost: task.cost,
+            tags: task.tags.clone(),
+            closed: task.is_closed,
+            deleted: task.is_deleted,
+            archived: task.is_archived,
+            locked: task.is_locked,
+            private: task.is_private,
+            inbox: task.in_inbox,
+            someday: task.is_someday,
+            due: task.due_on,
+            parent: task.parent,
+            follows: task.follows,
+            waiting_on: task.waiting_on.as_ref().map(|w| WaitingOnV1 {
+                person: w.person.clone(),
+                follow_up_on: w.follow_up_on,
+            }),
+            elapsed_seconds: task.elapsed.as_secs(),
+            pomodoros: task.pomodoros,
+            created_at: task.created_at.trunc_subsecs(0),
+            closed_at: task.closed_at.map(|c| c.trunc_subsecs(0)),
+            events: task
+                .events
+                .iter()
+                .map(|e| EventV1 {
+                    occurred_at: e.occurred_at.trunc_subsecs(0),
+                    change: e.change.clone(),
+                })
+                .collect(),
+        }
+    }
+}
+
+/// SearchMatchV1 is the version 1 of a task found by `search`.
+#[cfg(feature = "es")]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SearchMatchV1 {
+    pub id: i64,
+    pub title: String,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub closed: bool,
+    /// snippet is the text around the matches, split into matched words and the rest.
+    pub snippet: Vec<SnippetPartV1>,
+}
+
+/// SnippetPartV1 is the version 1 of a piece of a snippet.
+#[cfg(feature = "es")]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SnippetPartV1 {
+    pub text: String,
+    #[serde(default, rename = "match", skip_serializing_if = "std::ops::Not::not")]
+    pub is_match: bool,
+}
+
+#[cfg(feature = "es")]
+impl From<&SearchMatchDTO> for SearchMatchV1 {
+    fn from(m: &SearchMatchDTO) -> Self {
+        SearchMatchV1 {
+            id: m.id,
+            title: m.title.clone(),
+            closed: m.is_closed,
+            snippet: m
+                .snippet
+                .iter()
+                .map(|p| SnippetPartV1 {
+                    text: p.text.clone(),
+                    is_match: p.is_match,
+                })
+                .collect(),
+        }
+    }
+}
+
+/// LegacyTaskV1 is the version 1 of a task of the legacy engine on the wire.
+#[cfg(feature = "legacy")]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LegacyTaskV1 {
+    pub id: i64,
+    pub title: String,
+    pub priority: i32,
+    pub cost: i32,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub deleted: bool,
+    /// created_at is `YYYY-MM-DD HH:MM:SS` in local time.
+    pub created_at: String,
+    /// updated_at is `YYYY-MM-DD HH:MM:SS` in local time.
+    pub updated_at: String,
+}
+
+#[cfg(feature = "legacy")]
+impl From<&LegacyTaskDTO> for LegacyTaskV1 {
+    fn from(task: &LegacyTaskDTO) -> Self {
+        LegacyTaskV1 {
+            id: task.id,
+            title: task.title.clone(),
+            priority: task.priority,
+            cost: task.cost,
+            deleted: task.is_deleted,
+            created_at: task.created_at.clone(),
+            updated_at: task.updated_at.clone(),
+        }
+    }
+}
+
+/// ListV1 is the version 1 of listed tasks. The summary is there only if asked for.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ListV1<T> {
+    pub tasks: Vec<T>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub summary: Option<SummaryV1>,
+}
+
+/// GroupsV1 is the version 1 of listed tasks in groups.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GroupsV1<T> {
+    pub groups: Vec<GroupV1<T>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub summary: Option<SummaryV1>,
+}
+
+/// GroupV1 is the version 1 of a group of listed tasks, such as tasks having a tag.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GroupV1<T> {
+    pub key: String,
+    pub tasks: Vec<T>,
+    pub summary: SummaryV1,
+}
+
+/// SummaryV1 is the version 1 of the summary of listed tasks.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SummaryV1 {
+    pub open: usize,
+    pub total_cost: i64,
+    #[serde(default)]
+    pub overdue: usize,
+}
+
+impl From<&ListSummary> for SummaryV1 {
+    fn from(summary: &ListSummary) -> Self {
+        SummaryV1 {
+            open: summary.open,
+            total_cost: summary.total_cost,
+            overdue: summary.overdue,
+        }
+    }
+}
+
+/// DoneV1 is the version 1 of a change which a command made to a task, such as closing it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DoneV1 {
+    /// action is the verb of the change in lowercase, such as `close`.
+    pub action: String,
+    pub id: i64,
+}
+
+#[cfg(all(test, feature = "es"))]
+mod tests {
+    use super::*;
+
+    // NOTE: these are outputs of released versions. Do not edit them, but add a new version.
+    const TASK_V1_SIMPLE: &str = r#"{"id":3,"title":"buy milk","priority":10,"urgency":20,"cost":1,"tags":["home"],"closed":false}"#;
+    const TASK_V1_FULL: &str = r#"{"id":4,"title":"ask for review","priority":10,"urgency":10,"cost":2,"tags":[],"closed":true,"habit":{"done":1,"times":3,"period":"week"},"waiting_on":{"person":"alice","follow_up_on":"2024-05-01"},"follows":3}"#;
+
+    #[test]
+    fn test_task_v1_compatibility() {
+        let simple = TaskDTO {
+            id: 3,
+            title: String::from("buy milk"),
+            priority: 10,
+            urgency: 20,
+            cost: 1,
+            tags: vec![String::from("home")],
+            is_closed: false,
+            habit: None,
+            waiting_on: None,
+            follows: None,
+            parent: None,
+            due_on: None,
+            is_overdue: false,
+            is_someday: false,
+            elapsed: Default::default(),
+            is_timer_running: false,
+            pomodoros: 0,
+            is_archived: false,
+            is_deleted: false,
+        };
+        let full = TaskDTO {
+            id: 4,
+            title: String::from("ask for review"),
+            priority: 10,
+            urgency: 10,
+            cost: 2,
+            tags: vec![],
+            is_closed: true,
+            habit: Some(HabitProgressDTO {
+                done: 1,
+                times: 3,
+                period: String::from("week"),
+            }),
+            waiting_on: Some(WaitingOnDTO {
+                person: String::from("alice"),
+                follow_up_on: NaiveDate::from_ymd_opt(2024, 5, 1),
+            }),
+            follows: Some(3),
+            parent: None,
+            due_on: None,
+            is_overdue: false,
+            is_someday: false,
+            elapsed: Default::default(),
+            is_timer_running: false,
+            pomodoros: 0,
+            is_archived: false,
+            is_deleted: false,
+        };
+
+        for (json, task) in [(TASK_V1_SIMPLE, simple), (TASK_V1_FULL, full)] {
+            assert_eq!(serde_json::to_string(&TaskV1::from(&task)).unwrap(), json);
+            let got: TaskV1 = serde_json::from_str(json).unwrap();
+            assert_eq!(TaskDTO::from(got), task);
+        }
+    }
+
+    #[test]
+    fn test_task_v1_ignores_unknown_fields() {
+        // NOTE: readers of version 1 keep reading fields added to it later.
+        let json = r#"{"id":3,"title":"buy milk","priority":10,"urgency":20,"cost":1,"tags":[],"closed":false,"estimate":"2h"}"#;
+        let got: TaskV1 = serde_json::from_str(json).unwrap();
+        assert_eq!(got.id, 3);
+        assert_eq!(got.habit, None);
+    }
+}
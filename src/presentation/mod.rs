@@ -3,4 +3,6 @@
 //! presentation is a layer which has responsibility to communicate UI.
 
 pub mod command;
+pub mod context;
+pub mod dto;
 pub mod printer;
@@ -0,0 +1,82 @@
+//! # chart
+//!
+//! chart renders series as bar charts made of characters for terminals.
+
+use crate::usecase::es_sprint_usecase::BurndownDTO;
+
+/// BAR is a filled cell of bars.
+const BAR: char = '█';
+
+/// IDEAL is the mark of the ideal remaining cost.
+const IDEAL: char = '│';
+
+/// render_burndown renders a row per day whose bar is the remaining cost.
+/// The ideal remaining cost is marked where the bar does not cover it.
+pub fn render_burndown(burndown: &BurndownDTO, bar_width: usize) -> String {
+    let mut out = format!(
+        "Burndown of {} (committed {}, {} marks the ideal)\n",
+        burndown.name, burndown.committed_cost, IDEAL
+    );
+
+    for point in &burndown.points {
+        let filled = scale(
+            f64::from(point.remaining),
+            burndown.committed_cost,
+            bar_width,
+        );
+        let ideal = scale(point.ideal, burndown.committed_cost, bar_width);
+        let bar: String = (0..bar_width)
+            .map(|i| match i {
+                i if i < filled => BAR,
+                i if i == ideal => IDEAL,
+                _ => ' ',
+            })
+            .collect();
+        out.push_str(&format!(
+            "{}  {}  {}\n",
+            point.date.format("%m-%d"),
+            bar,
+            point.remaining
+        ));
+    }
+
+    out
+}
+
+/// scale maps value in [0, max] to a cell in [0, width].
+fn scale(value: f64, max: i32, width: usize) -> usize {
+    if max <= 0 {
+        return 0;
+    }
+    let ratio = (value / f64::from(max)).clamp(0.0, 1.0);
+    (ratio * width as f64).round() as usize
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::usecase::es_sprint_usecase::BurndownPointDTO;
+    use chrono::NaiveDate;
+
+    #[test]
+    fn test_render_burndown() {
+        let point = |day: u32, remaining: i32, ideal: f64| BurndownPointDTO {
+            date: NaiveDate::from_ymd_opt(2026, 10, day).unwrap(),
+            remaining,
+            ideal,
+        };
+        let burndown = BurndownDTO {
+            name: String::from("s1"),
+            committed_cost: 40,
+            points: vec![point(1, 40, 30.0), point(2, 20, 20.0), point(3, 0, 10.0)],
+        };
+
+        assert_eq!(
+            render_burndown(&burndown, 8),
+            "Burndown of s1 (committed 40, │ marks the ideal)\n\
+             10-01  ████████  40\n\
+             10-02  ████│     20\n\
+             10-03    │       0\n"
+        );
+    }
+}
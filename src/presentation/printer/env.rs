@@ -0,0 +1,97 @@
+//! # env
+//!
+//! env renders the environment report of `taskmr env`, which is meant to be pasted into bug reports.
+
+/// FEATURES are cargo features and whether they are compiled in.
+const FEATURES: &[(&str, bool)] = &[
+    ("es", cfg!(feature = "es")),
+    ("legacy", cfg!(feature = "legacy")),
+    ("plugin", cfg!(feature = "plugin")),
+    ("scripting", cfg!(feature = "scripting")),
+    ("webhook", cfg!(feature = "webhook")),
+];
+
+/// compiled_features returns names of cargo features compiled in.
+pub fn compiled_features() -> Vec<&'static str> {
+    FEATURES
+        .iter()
+        .filter(|(_, enabled)| *enabled)
+        .map(|(name, _)| *name)
+        .collect()
+}
+
+/// format_bytes formats a file size such as `12.5 KiB`.
+pub fn format_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["KiB", "MiB", "GiB", "TiB"];
+
+    if bytes < 1024 {
+        return format!("{} B", bytes);
+    }
+    let mut size = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    format!("{:.1} {}", size, UNITS[unit])
+}
+
+/// render renders pairs of a key and a value with aligned values.
+pub fn render(entries: &[(&str, String)]) -> String {
+    let width = entries.iter().map(|(k, _)| k.len()).max().unwrap_or(0);
+    entries
+        .iter()
+        .map(|(k, v)| format!("{:width$}  {}\n", k, v, width = width))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_bytes() {
+        #[derive(Debug)]
+        struct TestCase {
+            args: u64,
+            want: &'static str,
+            name: String,
+        }
+
+        let table = [
+            TestCase {
+                name: String::from("normal: bytes"),
+                args: 1023,
+                want: "1023 B",
+            },
+            TestCase {
+                name: String::from("normal: kibibytes"),
+                args: 12 * 1024 + 512,
+                want: "12.5 KiB",
+            },
+            TestCase {
+                name: String::from("normal: mebibytes"),
+                args: 3 * 1024 * 1024,
+                want: "3.0 MiB",
+            },
+        ];
+
+        for test_case in table {
+            assert_eq!(
+                format_bytes(test_case.args),
+                test_case.want,
+                "Failed in the \"{}\".",
+                test_case.name
+            );
+        }
+    }
+
+    #[test]
+    fn test_render() {
+        let got = render(&[
+            ("taskmr", String::from("0.2.0")),
+            ("features", String::from("es, legacy")),
+        ]);
+        assert_eq!(got, "taskmr    0.2.0\nfeatures  es, legacy\n");
+    }
+}
@@ -0,0 +1,426 @@
+//! # export
+//!
+//! export renders all tasks into formats read by other tools.
+
+use std::collections::HashSet;
+use std::io::{self, Write};
+#[cfg(feature = "pdf")]
+use std::rc::Rc;
+
+#[cfg(feature = "pdf")]
+use chrono::{Local, NaiveDate};
+use clap::ValueEnum;
+
+#[cfg(feature = "pdf")]
+use crate::domain::es_task::ScoreWeights;
+#[cfg(feature = "pdf")]
+use crate::domain::scoring::ScoringStrategy;
+use crate::infra::sqlite::anonymize::Anonymizer;
+#[cfg(feature = "pdf")]
+use crate::presentation::printer::pdf;
+use crate::usecase::es_list_task_usecase::TaskDTO;
+
+/// ExportFormat is the format of exported tasks.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ExportFormat {
+    /// Comma-separated values in the columns of CSV_COLUMNS, which `import` reads back.
+    Csv,
+    /// Graphviz digraph with edges from parents to subtasks. Render it with `dot -Tsvg`.
+    Dot,
+    /// Markdown checklist to paste into pull requests and documents.
+    #[value(alias = "markdown")]
+    Md,
+    /// Printable PDF in the layout given by `--layout`.
+    #[cfg(feature = "pdf")]
+    Pdf,
+    /// Compacted, read-only copy of the database written to the path given.
+    SqliteSnapshot,
+}
+
+/// PdfLayout is the layout of the pdf format.
+#[cfg(feature = "pdf")]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum PdfLayout {
+    /// One-page daily agenda of the top open tasks with checkboxes and time blocks.
+    #[default]
+    Agenda,
+}
+
+/// PdfOptions are settings of the pdf format.
+#[cfg(feature = "pdf")]
+#[derive(Debug, Clone)]
+pub struct PdfOptions {
+    pub layout: PdfLayout,
+    /// strategy orders tasks to pick the top ones.
+    pub strategy: Rc<dyn ScoringStrategy>,
+    /// date is the day of the agenda.
+    pub date: NaiveDate,
+}
+
+#[cfg(feature = "pdf")]
+impl Default for PdfOptions {
+    fn default() -> Self {
+        PdfOptions {
+            layout: PdfLayout::default(),
+            strategy: Rc::new(ScoreWeights::default()),
+            date: Local::now().date_naive(),
+        }
+    }
+}
+
+/// OPEN_COLOR fills nodes of opening tasks.
+const OPEN_COLOR: &str = "lightskyblue";
+
+/// CLOSED_COLOR fills nodes of closed tasks.
+const CLOSED_COLOR: &str = "gray85";
+
+/// CSV_COLUMNS are the columns of the csv format. Tags are separated by spaces,
+/// and the status is `open` or `closed`.
+pub const CSV_COLUMNS: [&str; 8] = [
+    "id", "title", "priority", "urgency", "cost", "tags", "due", "status",
+];
+
+/// Exporter writes tasks in the format one by one, so that a large export is not held in memory.
+pub struct Exporter<W: Write> {
+    format: ExportFormat,
+    out: W,
+    /// held keeps tasks until finish for formats which lay out all tasks at once, such as pdf.
+    #[cfg(feature = "pdf")]
+    held: Vec<TaskDTO>,
+    #[cfg(feature = "pdf")]
+    pdf_options: PdfOptions,
+    /// anonymizer replaces texts of tasks with pseudonyms if it is set.
+    anonymizer: Option<Anonymizer>,
+    /// written and edges keep ids of written tasks and `(parent, child)` pairs of the dot format,
+    /// so that edges to parents left out of the export are dropped at finish.
+    written: HashSet<i64>,
+    edges: Vec<(i64, i64)>,
+}
+
+impl<W: Write> Exporter<W> {
+    /// begin writes the header of the format.
+    pub fn begin(format: ExportFormat, mut out: W) -> io::Result<Self> {
+        match format {
+            ExportFormat::Csv => writeln!(out, "{}", CSV_COLUMNS.join(","))?,
+            ExportFormat::Md => {}
+            ExportFormat::Dot => {
+                out.write_all(b"digraph taskmr {\n    node [shape=box, style=filled];\n")?
+            }
+            #[cfg(feature = "pdf")]
+            ExportFormat::Pdf => {}
+            ExportFormat::SqliteSnapshot => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    "sqlite-snapshot copies the database instead of writing tasks",
+                ))
+            }
+        }
+        Ok(Exporter {
+            format,
+            out,
+            #[cfg(feature = "pdf")]
+            held: vec![],
+            #[cfg(feature = "pdf")]
+            pdf_options: PdfOptions::default(),
+            anonymizer: None,
+            written: HashSet::new(),
+            edges: vec![],
+        })
+    }
+
+    /// set_pdf_options sets the settings of the pdf format.
+    #[cfg(feature = "pdf")]
+    pub fn set_pdf_options(&mut self, options: PdfOptions) {
+        self.pdf_options = options;
+    }
+
+    /// set_anonymizer makes the exporter write pseudonyms instead of texts of tasks.
+    pub fn set_anonymizer(&mut self, anonymizer: Anonymizer) {
+        self.anonymizer = Some(anonymizer);
+    }
+
+    /// write_task writes a task.
+    pub fn write_task(&mut self, task: &TaskDTO) -> io::Result<()> {
+        let anonymized;
+        let task = match &self.anonymizer {
+            Some(anonymizer) => {
+                anonymized = anonymize(anonymizer, task);
+                &anonymized
+            }
+            None => task,
+        };
+        match self.format {
+            ExportFormat::Csv => write_csv_row(&mut self.out, task),
+            ExportFormat::Dot => {
+                self.written.insert(task.id);
+                if let Some(parent) = task.parent {
+                    self.edges.push((parent, task.id));
+                }
+                write_dot_node(&mut self.out, task)
+            }
+            ExportFormat::Md => write_checklist_item(&mut self.out, task),
+            #[cfg(feature = "pdf")]
+            ExportFormat::Pdf => {
+                // NOTE: the agenda shows only open tasks, so closed ones are not held.
+                if !task.is_closed {
+                    self.held.push(task.clone());
+                }
+                Ok(())
+            }
+            ExportFormat::SqliteSnapshot => unreachable!("refused by begin"),
+        }
+    }
+
+    /// finish writes the footer of the format.
+    pub fn finish(mut self) -> io::Result<()> {
+        match self.format {
+            ExportFormat::Csv | ExportFormat::Md => {}
+            ExportFormat::Dot => {
+                for (parent, child) in &self.edges {
+                    if self.written.contains(parent) {
+                        writeln!(self.out, "    task{} -> task{};", parent, child)?;
+                    }
+                }
+                self.out.write_all(b"}\n")?
+            }
+            #[cfg(feature = "pdf")]
+            ExportFormat::Pdf => {
+                let options = &self.pdf_options;
+                let document = match options.layout {
+                    PdfLayout::Agenda => {
+                        pdf::agenda(&self.held, options.date, options.strategy.as_ref())
+                    }
+                };
+                self.out.write_all(&document)?
+            }
+            ExportFormat::SqliteSnapshot => unreachable!("refused by begin"),
+        }
+        self.out.flush()
+    }
+}
+
+/// render renders the tasks in the format.
+pub fn render(format: ExportFormat, tasks: &[TaskDTO]) -> String {
+    let mut out = Vec::new();
+    let render = |out| -> io::Result<()> {
+        let mut exporter = Exporter::begin(format, out)?;
+        for task in tasks {
+            exporter.write_task(task)?;
+        }
+        exporter.finish()
+    };
+    // NOTE: writing into Vec<u8> never fails, and only UTF-8 is written.
+    render(&mut out).unwrap_or_default();
+    String::from_utf8(out).unwrap_or_default()
+}
+
+/// write_dot_node writes the task as a node of a Graphviz digraph colored by status.
+fn write_dot_node<W: Write>(out: &mut W, task: &TaskDTO) -> io::Result<()> {
+    let (color, status) = if task.is_closed {
+        (CLOSED_COLOR, "closed")
+    } else {
+        (OPEN_COLOR, "open")
+    };
+    writeln!(
+        out,
+        "    task{} [label=\"#{} {}\\ncost {} · {}\", fillcolor=\"{}\"];",
+        task.id,
+        task.id,
+        escape(&task.title),
+        task.cost,
+        status,
+        color
+    )
+}
+
+/// write_csv_row writes the task as a row in the columns of CSV_COLUMNS.
+fn write_csv_row<W: Write>(out: &mut W, task: &TaskDTO) -> io::Result<()> {
+    let status = if task.is_closed { "closed" } else { "open" };
+    writeln!(
+        out,
+        "{},{},{},{},{},{},{},{}",
+        task.id,
+        quote(&task.title),
+        task.priority,
+        task.urgency,
+        task.cost,
+        quote(&task.tags.join(" ")),
+        task.due_on.map(|d| d.to_string()).unwrap_or_default(),
+        status
+    )
+}
+
+/// write_checklist_item writes the task as an item of a Markdown checklist, checked if it is closed.
+fn write_checklist_item<W: Write>(out: &mut W, task: &TaskDTO) -> io::Result<()> {
+    let check = if task.is_closed { 'x' } else { ' ' };
+    writeln!(
+        out,
+        "- [{}] {} (P:{} C:{})",
+        check,
+        escape_markdown(&task.title),
+        task.priority,
+        task.cost
+    )
+}
+
+/// anonymize returns the task whose title, tags and person are replaced with pseudonyms.
+fn anonymize(anonymizer: &Anonymizer, task: &TaskDTO) -> TaskDTO {
+    let mut task = task.clone();
+    task.title = anonymizer.title(&task.title);
+    task.tags = task.tags.iter().map(|t| anonymizer.tag(t)).collect();
+    if let Some(w) = task.waiting_on.as_mut() {
+        w.person = anonymizer.person(&w.person);
+    }
+    task
+}
+
+/// quote quotes a CSV field if it has a comma, a quote or a newline, doubling quotes in it.
+fn quote(s: &str) -> String {
+    if s.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", s.replace('"', "\"\""))
+    } else {
+        s.to_owned()
+    }
+}
+
+/// escape_markdown escapes characters which Markdown reads as emphasis, code, links or HTML.
+fn escape_markdown(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        if matches!(c, '\\' | '`' | '*' | '_' | '[' | ']' | '<') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+/// escape escapes a string to be put in a double-quoted ID of the DOT language.
+fn escape(s: &str) -> String {
+    s.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+
+    #[test]
+    fn test_render() {
+        #[derive(Debug)]
+        struct TestCase {
+            args: (ExportFormat, Vec<TaskDTO>),
+            want: &'static str,
+            name: String,
+        }
+
+        let tasks = || {
+            vec![
+                TaskDTO {
+                    id: 1,
+                    title: String::from("write \"docs\""),
+                    priority: 10,
+                    urgency: 10,
+                    cost: 3,
+                    tags: vec![],
+                    is_closed: false,
+                    habit: None,
+                    waiting_on: None,
+                    follows: None,
+                    parent: None,
+                    due_on: None,
+                    is_overdue: false,
+                    is_someday: false,
+                    elapsed: Default::default(),
+                    is_timer_running: false,
+                    pomodoros: 0,
+                    is_archived: false,
+                    is_deleted: false,
+                },
+                TaskDTO {
+                    id: 2,
+                    title: String::from("fix bug"),
+                    priority: 10,
+                    urgency: 10,
+                    cost: 5,
+                    tags: vec![String::from("bug"), String::from("ui")],
+                    is_closed: true,
+                    habit: None,
+                    waiting_on: None,
+                    follows: None,
+                    parent: None,
+                    due_on: NaiveDate::from_ymd_opt(2024, 5, 1),
+                    is_overdue: false,
+                    is_someday: false,
+                    elapsed: Default::default(),
+                    is_timer_running: false,
+                    pomodoros: 0,
+                    is_archived: false,
+                    is_deleted: false,
+                },
+            ]
+        };
+
+        let table = [
+            TestCase {
+                name: String::from("normal: empty"),
+                args: (ExportFormat::Dot, vec![]),
+                want: "digraph taskmr {\n    node [shape=box, style=filled];\n}\n",
+            },
+            TestCase {
+                name: String::from("normal: colored by status"),
+                args: (ExportFormat::Dot, tasks()),
+                want: "digraph taskmr {\n    node [shape=box, style=filled];\n    task1 [label=\"#1 write \\\"docs\\\"\\ncost 3 · open\", fillcolor=\"lightskyblue\"];\n    task2 [label=\"#2 fix bug\\ncost 5 · closed\", fillcolor=\"gray85\"];\n}\n",
+            },
+            TestCase {
+                name: String::from("normal: edges from parents"),
+                args: (
+                    ExportFormat::Dot,
+                    vec![
+                        TaskDTO {
+                            parent: Some(2),
+                            ..tasks()[0].clone()
+                        },
+                        tasks()[1].clone(),
+                        TaskDTO {
+                            id: 3,
+                            parent: Some(9),
+                            ..tasks()[0].clone()
+                        },
+                    ],
+                ),
+                want: "digraph taskmr {\n    node [shape=box, style=filled];\n    task1 [label=\"#1 write \\\"docs\\\"\\ncost 3 · open\", fillcolor=\"lightskyblue\"];\n    task2 [label=\"#2 fix bug\\ncost 5 · closed\", fillcolor=\"gray85\"];\n    task3 [label=\"#3 write \\\"docs\\\"\\ncost 3 · open\", fillcolor=\"lightskyblue\"];\n    task2 -> task1;\n}\n",
+            },
+            TestCase {
+                name: String::from("normal: csv quoting titles and tags"),
+                args: (ExportFormat::Csv, tasks()),
+                want: "id,title,priority,urgency,cost,tags,due,status\n1,\"write \"\"docs\"\"\",10,10,3,,,open\n2,fix bug,10,10,5,bug ui,2024-05-01,closed\n",
+            },
+            TestCase {
+                name: String::from("normal: md checking closed tasks"),
+                args: (ExportFormat::Md, tasks()),
+                want: "- [ ] write \"docs\" (P:10 C:3)\n- [x] fix bug (P:10 C:5)\n",
+            },
+            TestCase {
+                name: String::from("normal: md escaping markup"),
+                args: (
+                    ExportFormat::Md,
+                    vec![TaskDTO {
+                        title: String::from("fix *bold* [link] in `README_md`"),
+                        ..tasks()[0].clone()
+                    }],
+                ),
+                want: "- [ ] fix \\*bold\\* \\[link\\] in \\`README\\_md\\` (P:10 C:3)\n",
+            },
+        ];
+
+        for test_case in table {
+            assert_eq!(
+                render(test_case.args.0, &test_case.args.1),
+                test_case.want,
+                "Failed in the \"{}\".",
+                test_case.name
+            );
+        }
+    }
+}
@@ -0,0 +1,217 @@
+//! # json
+//!
+//! json prints results of commands as JSON of wire DTOs, one document in a line,
+//! so that scripts read them with tools such as `jq`.
+
+use crate::Result;
+use serde::Serialize;
+use std::io::Write;
+
+#[cfg(feature = "legacy")]
+use crate::presentation::dto::LegacyTaskV1;
+use crate::presentation::dto::{DoneV1, ListV1, SummaryV1};
+#[cfg(feature = "es")]
+use crate::presentation::dto::{GroupV1, GroupsV1, SearchMatchV1, TaskDetailV1, TaskV1};
+use crate::presentation::printer::Printer;
+#[cfg(feature = "es")]
+use crate::usecase::es_list_task_usecase::{
+    NestedTaskDTO, TaskDTO as ESTaskDTO, TaskGroupDTO as ESTaskGroupDTO,
+};
+#[cfg(feature = "es")]
+use crate::usecase::es_search_task_usecase::SearchMatchDTO;
+#[cfg(feature = "es")]
+use crate::usecase::es_show_task_usecase::TaskDetailDTO;
+use crate::usecase::list_summary::ListSummary;
+#[cfg(feature = "legacy")]
+use crate::usecase::list_task_usecase::TaskDTO;
+
+/// Printer to translate results into JSON.
+pub struct JsonPrinter<W: Write> {
+    writer: W,
+}
+
+impl<W: Write> JsonPrinter<W> {
+    /// construct JsonPrinter.
+    pub fn new(w: W) -> Self {
+        JsonPrinter { writer: w }
+    }
+
+    /// write the value as JSON in a line.
+    fn write<T: Serialize>(&mut self, value: &T) -> Result<()> {
+        serde_json::to_writer(&mut self.writer, value)?;
+        self.writer.write_all(b"\n")?;
+        Ok(())
+    }
+}
+
+impl<W: Write> Printer for JsonPrinter<W> {
+    #[cfg(feature = "legacy")]
+    fn print(&mut self, tasks: Vec<TaskDTO>, summary: Option<&ListSummary>) -> Result<()> {
+        self.write(&ListV1 {
+            tasks: tasks.iter().map(LegacyTaskV1::from).collect(),
+            summary: summary.map(SummaryV1::from),
+        })
+    }
+
+    #[cfg(feature = "es")]
+    fn print_es(&mut self, tasks: Vec<ESTaskDTO>, summary: Option<&ListSummary>) -> Result<()> {
+        self.write(&ListV1 {
+            tasks: tasks.iter().map(TaskV1::from).collect(),
+            summary: summary.map(SummaryV1::from),
+        })
+    }
+
+    /// print_es_tree prints tasks in the order of the tree. Their `parent` tells the tree.
+    #[cfg(feature = "es")]
+    fn print_es_tree(
+        &mut self,
+        tasks: Vec<NestedTaskDTO>,
+        summary: Option<&ListSummary>,
+    ) -> Result<()> {
+        self.write(&ListV1 {
+            tasks: tasks.iter().map(|n| TaskV1::from(&n.task)).collect(),
+            summary: summary.map(SummaryV1::from),
+        })
+    }
+
+    #[cfg(feature = "es")]
+    fn print_es_groups(
+        &mut self,
+        groups: Vec<ESTaskGroupDTO>,
+        summary: Option<&ListSummary>,
+    ) -> Result<()> {
+        self.write(&GroupsV1 {
+            groups: groups
+                .iter()
+                .map(|g| GroupV1 {
+                    key: g.key.clone(),
+                    tasks: g.tasks.iter().map(TaskV1::from).collect(),
+                    summary: SummaryV1::from(&g.summary),
+                })
+                .collect(),
+            summary: summary.map(SummaryV1::from),
+        })
+    }
+
+    #[cfg(feature = "es")]
+    fn print_task_detail(&mut self, task: &TaskDetailDTO) -> Result<()> {
+        self.write(&TaskDetailV1::from(task))
+    }
+
+    #[cfg(feature = "es")]
+    fn print_search_matches(&mut self, matches: Vec<SearchMatchDTO>) -> Result<()> {
+        self.write(&ListV1 {
+            tasks: matches.iter().map(SearchMatchV1::from).collect(),
+            summary: None,
+        })
+    }
+
+    fn print_done(&mut self, verb: &str, id: i64) -> Result<()> {
+        self.write(&DoneV1 {
+            action: verb.to_lowercase(),
+            id,
+        })
+    }
+
+    fn print_saved(&mut self, verb: &str, id: i64) -> Result<()> {
+        self.print_done(verb, id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// printed runs the print with a JsonPrinter and returns what it printed.
+    fn printed(print: impl FnOnce(&mut JsonPrinter<Vec<u8>>) -> Result<()>) -> String {
+        let mut printer = JsonPrinter::new(Vec::new());
+        print(&mut printer).unwrap();
+        String::from_utf8(printer.writer).unwrap()
+    }
+
+    #[test]
+    fn test_print_done() {
+        assert_eq!(
+            printed(|p| p.print_done("Close", 3)),
+            "{\"action\":\"close\",\"id\":3}\n"
+        );
+        assert_eq!(
+            printed(|p| p.print_saved("Add", 4)),
+            "{\"action\":\"add\",\"id\":4}\n"
+        );
+    }
+
+    #[cfg(feature = "legacy")]
+    #[test]
+    fn test_print() {
+        let tasks = vec![TaskDTO {
+            id: 1,
+            title: String::from("buy \"milk\""),
+            priority: 10,
+            cost: 2,
+            is_deleted: false,
+            created_at: String::from("2024-05-01 10:00:00"),
+            updated_at: String::from("2024-05-02 10:00:00"),
+        }];
+        assert_eq!(
+            printed(|p| p.print(tasks, Some(&ListSummary::from_costs([2])))),
+            concat!(
+                r#"{"tasks":[{"id":1,"title":"buy \"milk\"","priority":10,"cost":2,"#,
+                r#""created_at":"2024-05-01 10:00:00","updated_at":"2024-05-02 10:00:00"}],"#,
+                r#""summary":{"open":1,"total_cost":2,"overdue":0}}"#,
+                "\n"
+            )
+        );
+    }
+
+    #[cfg(feature = "es")]
+    #[test]
+    fn test_print_task_detail() {
+        use crate::usecase::es_show_task_usecase::EventDTO;
+        use chrono::NaiveDate;
+
+        let created_at = NaiveDate::from_ymd_opt(2024, 5, 1)
+            .unwrap()
+            .and_hms_opt(9, 30, 0)
+            .unwrap();
+        let task = TaskDetailDTO {
+            id: 7,
+            title: String::from("fix login bug"),
+            description: None,
+            priority: 80,
+            urgency: 10,
+            cost: 3,
+            tags: vec![String::from("bug")],
+            is_closed: false,
+            is_locked: false,
+            is_private: false,
+            in_inbox: false,
+            is_someday: false,
+            is_deleted: false,
+            is_archived: false,
+            due_on: None,
+            parent: None,
+            follows: None,
+            waiting_on: None,
+            elapsed: std::time::Duration::from_secs(90),
+            pomodoros: 0,
+            created_at,
+            closed_at: None,
+            events: vec![EventDTO {
+                occurred_at: created_at,
+                change: String::from("created"),
+            }],
+        };
+        assert_eq!(
+            printed(|p| p.print_task_detail(&task)),
+            concat!(
+                r#"{"id":7,"title":"fix login bug","priority":80,"urgency":10,"cost":3,"tags":["bug"],"#,
+                r#""closed":false,"deleted":false,"archived":false,"locked":false,"private":false,"#,
+                r#""inbox":false,"someday":false,"elapsed_seconds":90,"pomodoros":0,"#,
+                r#""created_at":"2024-05-01T09:30:00","#,
+                r#""events":[{"occurred_at":"2024-05-01T09:30:00","change":"created"}]}"#,
+                "\n"
+            )
+        );
+    }
+}
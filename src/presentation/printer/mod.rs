@@ -3,4 +3,81 @@
 //! Translate structures written in Rust into some prittify string.
 //!
 
+#[cfg(feature = "es")]
+pub mod chart;
+pub mod env;
+#[cfg(feature = "es")]
+pub mod export;
+pub mod json;
+#[cfg(feature = "pdf")]
+pub mod pdf;
+#[cfg(feature = "es")]
+pub mod report;
+#[cfg(feature = "es")]
+pub mod share;
+pub mod style;
 pub mod table;
+pub mod widget;
+pub mod width;
+
+#[cfg(feature = "es")]
+use crate::usecase::es_list_task_usecase::{
+    NestedTaskDTO, TaskDTO as ESTaskDTO, TaskGroupDTO as ESTaskGroupDTO,
+};
+#[cfg(feature = "es")]
+use crate::usecase::es_search_task_usecase::SearchMatchDTO;
+#[cfg(feature = "es")]
+use crate::usecase::es_show_task_usecase::TaskDetailDTO;
+use crate::usecase::list_summary::ListSummary;
+#[cfg(feature = "legacy")]
+use crate::usecase::list_task_usecase::TaskDTO;
+use crate::Result;
+
+/// Printer prints results of commands which scripts may read too, such as listed tasks.
+/// TablePrinter prints them for people and JsonPrinter for scripts.
+pub trait Printer {
+    /// set the maximum width of task tables. None means no limit.
+    fn set_max_width(&mut self, _max_width: Option<usize>) {}
+
+    /// set whether task tables have row numbers such as `%1` at the head of rows.
+    fn set_numbered(&mut self, _numbered: bool) {}
+
+    /// print out tasks of the legacy engine.
+    #[cfg(feature = "legacy")]
+    fn print(&mut self, tasks: Vec<TaskDTO>, summary: Option<&ListSummary>) -> Result<()>;
+
+    /// print out tasks of the es engine.
+    #[cfg(feature = "es")]
+    fn print_es(&mut self, tasks: Vec<ESTaskDTO>, summary: Option<&ListSummary>) -> Result<()>;
+
+    /// print out a tree of tasks, subtasks following their parents.
+    #[cfg(feature = "es")]
+    fn print_es_tree(
+        &mut self,
+        tasks: Vec<NestedTaskDTO>,
+        summary: Option<&ListSummary>,
+    ) -> Result<()>;
+
+    /// print out groups of tasks with their keys and subtotals.
+    #[cfg(feature = "es")]
+    fn print_es_groups(
+        &mut self,
+        groups: Vec<ESTaskGroupDTO>,
+        summary: Option<&ListSummary>,
+    ) -> Result<()>;
+
+    /// print out the full detail of a task with the events which changed it.
+    #[cfg(feature = "es")]
+    fn print_task_detail(&mut self, task: &TaskDetailDTO) -> Result<()>;
+
+    /// print out tasks found by a search with snippets of the matches, the best match first.
+    #[cfg(feature = "es")]
+    fn print_search_matches(&mut self, matches: Vec<SearchMatchDTO>) -> Result<()>;
+
+    /// print out that the verb was done to the task for the id, such as closing it.
+    fn print_done(&mut self, verb: &str, id: i64) -> Result<()>;
+
+    /// print out the id of the task which add or edit saved.
+    /// Tables print nothing, as add and edit are quiet on success.
+    fn print_saved(&mut self, verb: &str, id: i64) -> Result<()>;
+}
@@ -0,0 +1,285 @@
+//! # pdf
+//!
+//! pdf writes a printable one-page agenda as PDF without any external library.
+//! Only the standard font Helvetica is used, so that characters out of Latin-1 become `?`.
+
+use chrono::NaiveDate;
+
+use crate::domain::scoring::{ScoringStrategy, TaskView};
+use crate::usecase::es_list_task_usecase::TaskDTO;
+
+/// PAGE_WIDTH and PAGE_HEIGHT are the size of A4 in points.
+const PAGE_WIDTH: f32 = 595.0;
+const PAGE_HEIGHT: f32 = 842.0;
+
+/// MARGIN is the blank around the page.
+const MARGIN: f32 = 48.0;
+
+/// AGENDA_TASKS is the number of tasks put on the agenda.
+const AGENDA_TASKS: usize = 12;
+
+/// AGENDA_HOURS are the hours of the time blocks.
+const AGENDA_HOURS: std::ops::RangeInclusive<u32> = 8..=19;
+
+/// TITLE_CHARS is the number of characters of a title fitting the task column.
+const TITLE_CHARS: usize = 40;
+
+/// Page collects drawing operators of a page.
+#[derive(Debug, Default)]
+struct Page {
+    content: String,
+}
+
+impl Page {
+    /// text draws the text with the baseline at (x, y).
+    fn text(&mut self, x: f32, y: f32, size: f32, text: &str) {
+        self.content.push_str(&format!(
+            "BT /F1 {} Tf {} {} Td ({}) Tj ET\n",
+            size,
+            x,
+            y,
+            escape(text)
+        ));
+    }
+
+    /// rect strokes the rectangle whose bottom left is (x, y).
+    fn rect(&mut self, x: f32, y: f32, width: f32, height: f32) {
+        self.content
+            .push_str(&format!("{} {} {} {} re S\n", x, y, width, height));
+    }
+
+    /// line strokes the line from (x1, y1) to (x2, y2).
+    fn line(&mut self, x1: f32, y1: f32, x2: f32, y2: f32) {
+        self.content
+            .push_str(&format!("{} {} m {} {} l S\n", x1, y1, x2, y2));
+    }
+
+    /// into_document wraps the page into a PDF document.
+    fn into_document(self) -> Vec<u8> {
+        let objects = [
+            String::from("<< /Type /Catalog /Pages 2 0 R >>"),
+            String::from("<< /Type /Pages /Kids [3 0 R] /Count 1 >>"),
+            format!(
+                "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 {} {}] \
+                 /Resources << /Font << /F1 4 0 R >> >> /Contents 5 0 R >>",
+                PAGE_WIDTH, PAGE_HEIGHT
+            ),
+            String::from(
+                "<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica /Encoding /WinAnsiEncoding >>",
+            ),
+            format!(
+                "<< /Length {} >>\nstream\n{}endstream",
+                self.content.len(),
+                self.content
+            ),
+        ];
+
+        let mut out = b"%PDF-1.4\n".to_vec();
+        let mut offsets = vec![];
+        for (i, object) in objects.iter().enumerate() {
+            offsets.push(out.len());
+            out.extend(format!("{} 0 obj\n{}\nendobj\n", i + 1, object).bytes());
+        }
+
+        let xref = out.len();
+        out.extend(format!("xref\n0 {}\n0000000000 65535 f \n", objects.len() + 1).bytes());
+        for offset in offsets {
+            out.extend(format!("{:010} 00000 n \n", offset).bytes());
+        }
+        out.extend(
+            format!(
+                "trailer\n<< /Size {} /Root 1 0 R >>\nstartxref\n{}\n%%EOF\n",
+                objects.len() + 1,
+                xref
+            )
+            .bytes(),
+        );
+        out
+    }
+}
+
+/// agenda renders the open tasks with the highest scores as a daily agenda of the date,
+/// with a checkbox for each task and blank time blocks to plan them in.
+pub fn agenda(tasks: &[TaskDTO], date: NaiveDate, strategy: &dyn ScoringStrategy) -> Vec<u8> {
+    let mut top: Vec<&TaskDTO> = tasks.iter().filter(|t| !t.is_closed).collect();
+    top.sort_by(|a, b| {
+        let score = |t: &TaskDTO| strategy.score(&TaskView::from(t));
+        score(b).total_cmp(&score(a)).then(a.id.cmp(&b.id))
+    });
+    top.truncate(AGENDA_TASKS);
+
+    let mut page = Page::default();
+    let top_y = PAGE_HEIGHT - MARGIN;
+    page.text(MARGIN, top_y - 20.0, 22.0, "Agenda");
+    page.text(
+        PAGE_WIDTH - MARGIN - 150.0,
+        top_y - 20.0,
+        14.0,
+        &date.format("%Y-%m-%d (%a)").to_string(),
+    );
+    page.line(MARGIN, top_y - 32.0, PAGE_WIDTH - MARGIN, top_y - 32.0);
+
+    let tasks_x = MARGIN;
+    let blocks_x = PAGE_WIDTH / 2.0 + 40.0;
+    let heading_y = top_y - 60.0;
+    page.text(tasks_x, heading_y, 14.0, "Top tasks");
+    page.text(blocks_x, heading_y, 14.0, "Time blocks");
+
+    let row_height = 28.0;
+    for (i, task) in top.iter().enumerate() {
+        let y = heading_y - 30.0 - row_height * i as f32;
+        page.rect(tasks_x, y - 2.0, 10.0, 10.0);
+        page.text(
+            tasks_x + 18.0,
+            y,
+            11.0,
+            &format!("#{} {}", task.id, truncate(&task.title, TITLE_CHARS)),
+        );
+        page.text(
+            tasks_x + 18.0,
+            y - 11.0,
+            8.0,
+            &format!("cost {}", task.cost),
+        );
+    }
+    if top.is_empty() {
+        page.text(tasks_x, heading_y - 30.0, 11.0, "No open task.");
+    }
+
+    let block_height = 52.0;
+    for (i, hour) in AGENDA_HOURS.enumerate() {
+        let y = heading_y - 14.0 - block_height * i as f32;
+        page.line(blocks_x, y, PAGE_WIDTH - MARGIN, y);
+        page.text(blocks_x, y - 12.0, 9.0, &format!("{:02}:00", hour));
+    }
+
+    page.into_document()
+}
+
+/// truncate shortens the text to the number of characters with an ellipsis.
+fn truncate(text: &str, chars: usize) -> String {
+    if text.chars().count() <= chars {
+        return text.to_owned();
+    }
+    let mut out: String = text.chars().take(chars - 3).collect();
+    out.push_str("...");
+    out
+}
+
+/// escape escapes a string into a literal string of PDF encoded in WinAnsi.
+/// Characters out of Latin-1 can not be shown by the standard font and become `?`.
+fn escape(text: &str) -> String {
+    let mut out = String::new();
+    for c in text.chars() {
+        match c {
+            '\\' | '(' | ')' => {
+                out.push('\\');
+                out.push(c);
+            }
+            ' '..='~' => out.push(c),
+            '\u{a0}'..='\u{ff}' => out.push_str(&format!("\\{:03o}", c as u32)),
+            _ => out.push('?'),
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::es_task::ScoreWeights;
+
+    #[test]
+    fn test_escape() {
+        #[derive(Debug)]
+        struct TestCase {
+            args: &'static str,
+            want: &'static str,
+            name: String,
+        }
+
+        let table = [
+            TestCase {
+                name: String::from("normal: parentheses and a backslash"),
+                args: r"call f(x) \ 2",
+                want: r"call f\(x\) \\ 2",
+            },
+            TestCase {
+                name: String::from("normal: latin-1"),
+                args: "café",
+                want: r"caf\351",
+            },
+            TestCase {
+                name: String::from("normal: out of latin-1"),
+                args: "日本 ok",
+                want: "?? ok",
+            },
+        ];
+
+        for test_case in table {
+            assert_eq!(
+                escape(test_case.args),
+                test_case.want,
+                "Failed in the \"{}\".",
+                test_case.name
+            );
+        }
+    }
+
+    #[test]
+    fn test_agenda() {
+        let task = |id: i64, priority: i32, is_closed: bool| TaskDTO {
+            id,
+            title: format!("title{}", id),
+            priority,
+            urgency: 10,
+            cost: 3,
+            tags: vec![],
+            is_closed,
+            habit: None,
+            waiting_on: None,
+            follows: None,
+            parent: None,
+            due_on: None,
+            is_overdue: false,
+            is_someday: false,
+            elapsed: Default::default(),
+            is_timer_running: false,
+            pomodoros: 0,
+            is_archived: false,
+            is_deleted: false,
+        };
+        let tasks = [task(1, 10, false), task(2, 30, false), task(3, 50, true)];
+        let date = NaiveDate::from_ymd_opt(2024, 5, 17).unwrap();
+
+        let got = agenda(&tasks, date, &ScoreWeights::default());
+        let text = String::from_utf8(got).unwrap();
+        assert!(text.starts_with("%PDF-1.4\n"));
+        assert!(text.ends_with("%%EOF\n"));
+        assert!(text.contains("(2024-05-17 \\(Fri\\)) Tj"));
+        let first = text.find("(#2 title2) Tj").unwrap();
+        let second = text.find("(#1 title1) Tj").unwrap();
+        assert!(first < second);
+        assert!(!text.contains("title3"));
+
+        let startxref: usize = text
+            .lines()
+            .skip_while(|l| *l != "startxref")
+            .nth(1)
+            .unwrap()
+            .parse()
+            .unwrap();
+        assert!(text[startxref..].starts_with("xref\n0 6\n"));
+        let stream = text.find("stream\n").unwrap() + "stream\n".len();
+        let length: usize = text[..stream]
+            .rsplit("/Length ")
+            .next()
+            .unwrap()
+            .split_whitespace()
+            .next()
+            .unwrap()
+            .parse()
+            .unwrap();
+        assert!(text[stream + length..].starts_with("endstream"));
+    }
+}
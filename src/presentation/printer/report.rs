@@ -0,0 +1,393 @@
+//! # report
+//!
+//! report renders built-in reports as a table, as CSV for spreadsheets or as Markdown for documents.
+
+use std::time::Duration;
+
+use clap::ValueEnum;
+
+use crate::presentation::printer::width;
+use crate::usecase::es_cycle_time_usecase::FlowTimeDTO;
+use crate::usecase::es_review_usecase::{MonthReviewDTO, ReviewTaskDTO};
+use crate::usecase::es_time_usecase::{QuotaUsageDTO, TimeByTagDTO};
+use crate::usecase::es_waiting_usecase::NudgeDTO;
+use crate::usecase::percentile::PERCENTILES;
+
+/// ReportFormat is the format of a built-in report.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum ReportFormat {
+    #[default]
+    Table,
+    /// Comma-separated values with a header row.
+    Csv,
+    /// Markdown to paste into documents.
+    Markdown,
+}
+
+/// render_time_by_tag renders time per tag. Time is in hours with two decimals.
+pub fn render_time_by_tag(format: ReportFormat, rows: &[TimeByTagDTO]) -> String {
+    let header = ["Tag", "Hours", "Tasks"];
+    let rows: Vec<Vec<String>> = rows
+        .iter()
+        .map(|r| vec![r.tag.to_owned(), hours(r.elapsed), r.tasks.to_string()])
+        .collect();
+    render_rows(format, &header, rows)
+}
+
+/// render_quotas renders time logged per tag in the current period against its quota.
+/// Time is in hours with two decimals, and tags past their quotas are marked as over.
+pub fn render_quotas(format: ReportFormat, rows: &[QuotaUsageDTO]) -> String {
+    let header = ["Tag", "Hours", "Quota", "Status"];
+    let rows: Vec<Vec<String>> = rows
+        .iter()
+        .map(|r| {
+            let status = if r.is_over() {
+                String::from("over")
+            } else {
+                String::from("ok")
+            };
+            vec![
+                r.tag.to_owned(),
+                hours(r.used),
+                format!("{}/{}", hours(r.limit), r.period),
+                status,
+            ]
+        })
+        .collect();
+    render_rows(format, &header, rows)
+}
+
+/// render_nudges renders people to follow up with about waiting tasks.
+pub fn render_nudges(format: ReportFormat, nudges: &[NudgeDTO]) -> String {
+    let header = ["Person", "ID", "Title", "Follow up on"];
+    let rows: Vec<Vec<String>> = nudges
+        .iter()
+        .map(|n| {
+            vec![
+                n.person.to_owned(),
+                format!("#{}", n.id),
+                n.title.to_owned(),
+                n.follow_up_on.to_string(),
+            ]
+        })
+        .collect();
+    render_rows(format, &header, rows)
+}
+
+/// render_cycle_time renders distributions of lead time and cycle time in days with one decimal.
+/// Percentiles of a metric without tasks are `-`.
+pub fn render_cycle_time(format: ReportFormat, rows: &[FlowTimeDTO]) -> String {
+    let percentiles: Vec<String> = PERCENTILES.iter().map(|p| format!("p{}", p)).collect();
+    let mut header = vec!["Metric", "Tasks"];
+    header.extend(percentiles.iter().map(String::as_str));
+    let rows: Vec<Vec<String>> = rows
+        .iter()
+        .map(|r| {
+            let mut row = vec![r.metric.to_owned(), r.tasks.to_string()];
+            row.extend(
+                (0..PERCENTILES.len())
+                    .map(|i| r.percentiles.get(i).map_or(String::from("-"), |d| days(*d))),
+            );
+            row
+        })
+        .collect();
+    render_rows(format, &header, rows)
+}
+
+/// render_rows renders rows under the header.
+fn render_rows(format: ReportFormat, header: &[&str], rows: Vec<Vec<String>>) -> String {
+    match format {
+        ReportFormat::Table => {
+            let mut table = vec![header.iter().map(|h| h.to_string()).collect()];
+            table.extend(rows);
+            width::align(&table)
+        }
+        ReportFormat::Csv => std::iter::once(header.iter().map(|h| h.to_string()).collect())
+            .chain(rows)
+            .map(|row| {
+                row.iter()
+                    .map(|f| csv_field(f))
+                    .collect::<Vec<_>>()
+                    .join(",")
+                    + "\n"
+            })
+            .collect(),
+        ReportFormat::Markdown => markdown_table(header, &rows),
+    }
+}
+
+/// render_month renders the review of a month as a narrative for a retrospective.
+/// It returns None for CSV, which does not fit a narrative.
+pub fn render_month(format: ReportFormat, review: &MonthReviewDTO) -> Option<String> {
+    let month = review.month.format("%B %Y");
+    let summary = format!(
+        "In {}, {} created and {} closed. {} hours were logged.",
+        month,
+        tasks(review.created),
+        tasks(review.closed),
+        hours(review.elapsed),
+    );
+    let biggest: Vec<Vec<String>> = review
+        .biggest
+        .iter()
+        .map(|t| review_row(t, format!("{} hours", hours(t.elapsed))))
+        .collect();
+    let carried_over: Vec<Vec<String>> = review
+        .carried_over
+        .iter()
+        .map(|t| review_row(t, format!("cost {}", t.cost)))
+        .collect();
+
+    let sections = [("Biggest tasks", biggest), ("Carried over", carried_over)];
+    match format {
+        ReportFormat::Table => {
+            let mut output = format!("Review of {}\n\n{}\n", month, summary);
+            for (title, rows) in sections {
+                output += &format!("\n{}:\n", title);
+                if rows.is_empty() {
+                    output += "None.\n";
+                } else {
+                    output += &width::align(&rows);
+                }
+            }
+            Some(output)
+        }
+        ReportFormat::Markdown => {
+            let mut output = format!("# Review of {}\n\n{}\n", month, summary);
+            for (title, rows) in sections {
+                output += &format!("\n## {}\n\n", title);
+                if rows.is_empty() {
+                    output += "None.\n";
+                }
+                for row in rows {
+                    output += &format!("- {} {} ({})\n", row[0], row[1], row[2]);
+                }
+            }
+            Some(output)
+        }
+        ReportFormat::Csv => None,
+    }
+}
+
+/// review_row is the id, the title and the detail of a task in the review.
+fn review_row(task: &ReviewTaskDTO, detail: String) -> Vec<String> {
+    vec![format!("#{}", task.id), task.title.to_owned(), detail]
+}
+
+/// hours renders the duration in hours with two decimals.
+pub fn hours(elapsed: Duration) -> String {
+    format!("{:.2}", elapsed.as_secs_f64() / 3600.0)
+}
+
+/// days renders the duration in days with one decimal.
+fn days(elapsed: Duration) -> String {
+    format!("{:.1}", elapsed.as_secs_f64() / 86400.0)
+}
+
+/// tasks renders the number of tasks as a subject, e.g. `1 task was` or `3 tasks were`.
+fn tasks(n: usize) -> String {
+    match n {
+        1 => String::from("1 task was"),
+        n => format!("{} tasks were", n),
+    }
+}
+
+/// markdown_table renders a table with a header row. Pipes in fields are escaped.
+fn markdown_table(header: &[&str], rows: &[Vec<String>]) -> String {
+    let line = |fields: Vec<String>| format!("| {} |\n", fields.join(" | "));
+    let mut output = line(header.iter().map(|h| h.to_string()).collect());
+    output += &line(header.iter().map(|_| String::from("---")).collect());
+    for row in rows {
+        output += &line(row.iter().map(|f| f.replace('|', "\\|")).collect());
+    }
+    output
+}
+
+/// csv_field quotes the field if it contains a comma, a quote or a line break.
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_owned()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::es_task::HabitPeriod;
+    use chrono::NaiveDate;
+
+    #[test]
+    fn test_render_time_by_tag() {
+        #[derive(Debug)]
+        struct TestCase {
+            args: ReportFormat,
+            want: &'static str,
+            name: String,
+        }
+
+        let rows = [
+            TimeByTagDTO {
+                tag: String::from("acme"),
+                elapsed: Duration::from_secs(5400),
+                tasks: 2,
+            },
+            TimeByTagDTO {
+                tag: String::from("say\"hi\""),
+                elapsed: Duration::from_secs(600),
+                tasks: 1,
+            },
+        ];
+
+        let table = [
+            TestCase {
+                name: String::from("normal: table"),
+                args: ReportFormat::Table,
+                want: "Tag      Hours  Tasks\nacme     1.50   2\nsay\"hi\"  0.17   1\n",
+            },
+            TestCase {
+                name: String::from("normal: csv"),
+                args: ReportFormat::Csv,
+                want: "Tag,Hours,Tasks\nacme,1.50,2\n\"say\"\"hi\"\"\",0.17,1\n",
+            },
+            TestCase {
+                name: String::from("normal: markdown"),
+                args: ReportFormat::Markdown,
+                want: "| Tag | Hours | Tasks |\n| --- | --- | --- |\n| acme | 1.50 | 2 |\n| say\"hi\" | 0.17 | 1 |\n",
+            },
+        ];
+
+        for test_case in table {
+            assert_eq!(
+                render_time_by_tag(test_case.args, &rows),
+                test_case.want,
+                "Failed in the \"{}\".",
+                test_case.name
+            );
+        }
+    }
+
+    #[test]
+    fn test_render_quotas() {
+        let rows = [
+            QuotaUsageDTO {
+                tag: String::from("meetings"),
+                used: Duration::from_secs(19800),
+                limit: Duration::from_secs(18000),
+                period: HabitPeriod::Week,
+            },
+            QuotaUsageDTO {
+                tag: String::from("email"),
+                used: Duration::from_secs(900),
+                limit: Duration::from_secs(1800),
+                period: HabitPeriod::Day,
+            },
+        ];
+        assert_eq!(
+            render_quotas(ReportFormat::Table, &rows),
+            "Tag       Hours  Quota      Status\nmeetings  5.50   5.00/week  over\nemail     0.25   0.50/day   ok\n"
+        );
+    }
+
+    #[test]
+    fn test_render_nudges() {
+        let nudges = [NudgeDTO {
+            id: 3,
+            title: String::from("contract, signed"),
+            person: String::from("Alice"),
+            follow_up_on: NaiveDate::from_ymd_opt(2024, 5, 10).unwrap(),
+        }];
+        assert_eq!(
+            render_nudges(ReportFormat::Csv, &nudges),
+            "Person,ID,Title,Follow up on\nAlice,#3,\"contract, signed\",2024-05-10\n"
+        );
+    }
+
+    #[test]
+    fn test_render_cycle_time() {
+        let day = Duration::from_secs(24 * 3600);
+        let rows = [
+            FlowTimeDTO {
+                metric: String::from("lead time"),
+                tasks: 3,
+                percentiles: vec![day * 4, day * 10, day * 21 / 2],
+            },
+            FlowTimeDTO {
+                metric: String::from("cycle time"),
+                tasks: 0,
+                percentiles: vec![],
+            },
+        ];
+        assert_eq!(
+            render_cycle_time(ReportFormat::Table, &rows),
+            "Metric      Tasks  p50  p85   p95
+\
+             lead time   3      4.0  10.0  10.5
+\
+             cycle time  0      -    -     -
+"
+        );
+    }
+
+    #[test]
+    fn test_render_month() {
+        #[derive(Debug)]
+        struct TestCase {
+            args: ReportFormat,
+            want: Option<&'static str>,
+            name: String,
+        }
+
+        let review = MonthReviewDTO {
+            month: NaiveDate::from_ymd_opt(2024, 5, 1).unwrap(),
+            created: 3,
+            closed: 1,
+            elapsed: Duration::from_secs(5400),
+            biggest: vec![ReviewTaskDTO {
+                id: 2,
+                title: String::from("write the report"),
+                cost: 3,
+                elapsed: Duration::from_secs(5400),
+            }],
+            carried_over: vec![],
+        };
+
+        let table = [
+            TestCase {
+                name: String::from("normal: table"),
+                args: ReportFormat::Table,
+                want: Some(
+                    "Review of May 2024\n\n\
+                     In May 2024, 3 tasks were created and 1 task was closed. 1.50 hours were logged.\n\n\
+                     Biggest tasks:\n#2  write the report  1.50 hours\n\n\
+                     Carried over:\nNone.\n",
+                ),
+            },
+            TestCase {
+                name: String::from("normal: markdown"),
+                args: ReportFormat::Markdown,
+                want: Some(
+                    "# Review of May 2024\n\n\
+                     In May 2024, 3 tasks were created and 1 task was closed. 1.50 hours were logged.\n\n\
+                     ## Biggest tasks\n\n- #2 write the report (1.50 hours)\n\n\
+                     ## Carried over\n\nNone.\n",
+                ),
+            },
+            TestCase {
+                name: String::from("abnormal: csv"),
+                args: ReportFormat::Csv,
+                want: None,
+            },
+        ];
+
+        for test_case in table {
+            assert_eq!(
+                render_month(test_case.args, &review).as_deref(),
+                test_case.want,
+                "Failed in the \"{}\".",
+                test_case.name
+            );
+        }
+    }
+}
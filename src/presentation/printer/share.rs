@@ -0,0 +1,72 @@
+//! # share
+//!
+//! share encodes a task to hand it off to another device.
+
+use crate::presentation::dto::TaskV1;
+use crate::usecase::es_list_task_usecase::TaskDTO;
+
+/// task_json encodes the task as compact JSON of TaskV1, which is short enough for a QR code.
+pub fn task_json(task: &TaskDTO) -> String {
+    serde_json::to_string(&TaskV1::from(task)).expect("TaskV1 is always encoded")
+}
+
+/// qr renders the data as a QR code with half blocks, two modules per character.
+/// Light modules are drawn as blocks, so that the code is scanned from terminals with a dark background.
+#[cfg(feature = "qr")]
+pub fn qr(data: &str) -> Result<String, qrcode::types::QrError> {
+    use qrcode::render::unicode::Dense1x2;
+
+    let code = qrcode::QrCode::new(data.as_bytes())?;
+    Ok(code
+        .render::<Dense1x2>()
+        .dark_color(Dense1x2::Light)
+        .light_color(Dense1x2::Dark)
+        .quiet_zone(true)
+        .build())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_task_json() {
+        let task = TaskDTO {
+            id: 3,
+            title: String::from("buy \"milk\""),
+            priority: 10,
+            urgency: 20,
+            cost: 1,
+            tags: vec![String::from("home")],
+            is_closed: false,
+            habit: None,
+            waiting_on: None,
+            follows: None,
+            parent: None,
+            due_on: None,
+            is_overdue: false,
+            is_someday: false,
+            elapsed: Default::default(),
+            is_timer_running: false,
+            pomodoros: 0,
+            is_archived: false,
+            is_deleted: false,
+        };
+        assert_eq!(
+            task_json(&task),
+            r#"{"id":3,"title":"buy \"milk\"","priority":10,"urgency":20,"cost":1,"tags":["home"],"closed":false}"#
+        );
+    }
+
+    #[cfg(feature = "qr")]
+    #[test]
+    fn test_qr() {
+        let got = qr(r#"{"id":3}"#).unwrap();
+        let lines: Vec<&str> = got.lines().collect();
+        // version 1 is 21 modules with a quiet zone of 4 on each side, 2 modules per line.
+        assert_eq!(lines.len(), 15);
+        assert!(lines.iter().all(|l| l.chars().count() == 29));
+
+        assert!(qr(&"x".repeat(8000)).is_err());
+    }
+}
@@ -0,0 +1,147 @@
+//! # style
+//!
+//! style decorates tags with the colors and the icons of the config, and alerts in red.
+
+use std::collections::BTreeMap;
+use std::io::IsTerminal;
+
+use crate::infra::config::{ColorChoice, TagColor, TagStyle};
+
+/// RESET ends a colored span.
+const RESET: &str = "\x1b[0m";
+
+/// TagStyles renders tags as configured.
+#[derive(Debug, Clone, Default)]
+pub struct TagStyles {
+    styles: BTreeMap<String, TagStyle>,
+    colorize: bool,
+}
+
+impl TagStyles {
+    /// construct TagStyles. Colors are left out unless colorize is set.
+    pub fn new(styles: BTreeMap<String, TagStyle>, colorize: bool) -> Self {
+        TagStyles { styles, colorize }
+    }
+
+    /// render decorates the tag. A tag without a style is returned as it is.
+    pub fn render(&self, tag: &str) -> String {
+        let Some(style) = self.styles.get(tag) else {
+            return tag.to_owned();
+        };
+
+        let mut out = match &style.icon {
+            Some(icon) => format!("{}{}", icon, tag),
+            None => tag.to_owned(),
+        };
+        if let (true, Some(color)) = (self.colorize, style.color) {
+            out = format!("\x1b[{}m{}{}", ansi_code(color), out, RESET);
+        }
+        out
+    }
+
+    /// alert renders the text in red to draw attention, such as ids of overdue tasks.
+    pub fn alert(&self, text: &str) -> String {
+        if self.colorize {
+            format!("\x1b[{}m{}{}", ansi_code(TagColor::Red), text, RESET)
+        } else {
+            text.to_owned()
+        }
+    }
+
+    /// highlight renders a matched word in bold, or in brackets if colors are left out.
+    pub fn highlight(&self, text: &str) -> String {
+        if self.colorize {
+            format!("\x1b[1m{}{}", text, RESET)
+        } else {
+            format!("[{}]", text)
+        }
+    }
+}
+
+/// should_colorize resolves the choice, where `auto` colorizes only when stdout is a terminal.
+pub fn should_colorize(choice: ColorChoice) -> bool {
+    match choice {
+        ColorChoice::Auto => std::io::stdout().is_terminal(),
+        ColorChoice::Always => true,
+        ColorChoice::Never => false,
+    }
+}
+
+/// ansi_code returns the SGR code of the foreground color.
+fn ansi_code(color: TagColor) -> u8 {
+    match color {
+        TagColor::Black => 30,
+        TagColor::Red => 31,
+        TagColor::Green => 32,
+        TagColor::Yellow => 33,
+        TagColor::Blue => 34,
+        TagColor::Magenta => 35,
+        TagColor::Cyan => 36,
+        TagColor::White => 37,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render() {
+        #[derive(Debug)]
+        struct TestCase {
+            args: (&'static str, bool),
+            want: &'static str,
+            name: String,
+        }
+
+        let styles = BTreeMap::from([
+            (
+                String::from("bug"),
+                TagStyle {
+                    color: Some(TagColor::Red),
+                    icon: Some(String::from("🐛")),
+                },
+            ),
+            (
+                String::from("idea"),
+                TagStyle {
+                    color: None,
+                    icon: Some(String::from("💡")),
+                },
+            ),
+        ]);
+
+        let table = [
+            TestCase {
+                name: String::from("normal: colorized"),
+                args: ("bug", true),
+                want: "\x1b[31m🐛bug\x1b[0m",
+            },
+            TestCase {
+                name: String::from("normal: not colorized"),
+                args: ("bug", false),
+                want: "🐛bug",
+            },
+            TestCase {
+                name: String::from("normal: icon only"),
+                args: ("idea", true),
+                want: "💡idea",
+            },
+            TestCase {
+                name: String::from("normal: no style"),
+                args: ("docs", true),
+                want: "docs",
+            },
+        ];
+
+        for test_case in table {
+            let (tag, colorize) = test_case.args;
+            assert_eq!(
+                TagStyles::new(styles.clone(), colorize).render(tag),
+                test_case.want,
+                "Failed in the \"{}\".",
+                test_case.name
+            );
+        }
+    }
+}
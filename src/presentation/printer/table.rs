@@ -1,61 +1,749 @@
-use anyhow::Result;
+use crate::Result;
 use std::io::Write;
-use tabwriter::TabWriter;
+#[cfg(feature = "es")]
+use std::time::Duration;
 
-use crate::usecase::es_list_task_usecase::TaskDTO as ESTaskDTO;
+use crate::infra::sqlite::metrics::{MethodStats, SlowQuery};
+use crate::presentation::printer::style::TagStyles;
+use crate::presentation::printer::width;
+use crate::presentation::printer::Printer;
+#[cfg(feature = "es")]
+use crate::usecase::es_forecast_usecase::ForecastDTO;
+#[cfg(feature = "es")]
+use crate::usecase::es_list_task_usecase::{
+    NestedTaskDTO, TaskDTO as ESTaskDTO, TaskGroupDTO as ESTaskGroupDTO, WaitingOnDTO,
+};
+#[cfg(feature = "es")]
+use crate::usecase::es_search_task_usecase::SearchMatchDTO;
+#[cfg(feature = "es")]
+use crate::usecase::es_show_task_usecase::TaskDetailDTO;
+#[cfg(feature = "es")]
+use crate::usecase::es_simulate_usecase::{Outlook, SimulationDTO};
+#[cfg(feature = "es")]
+use crate::usecase::es_sprint_usecase::SprintDTO;
+#[cfg(feature = "es")]
+use crate::usecase::es_tag_usecase::TagCountDTO;
+use crate::usecase::list_summary::ListSummary;
+#[cfg(feature = "legacy")]
 use crate::usecase::list_task_usecase::TaskDTO;
+use crate::usecase::outstanding::Outstanding;
+#[cfg(feature = "es")]
+use crate::usecase::percentile::PERCENTILES;
+#[cfg(feature = "legacy")]
+use crate::usecase::undo_usecase::ChangeDTO;
+
+/// TITLE_COLUMN is the index of the title column in task tables.
+const TITLE_COLUMN: usize = 1;
+
+/// MIN_TITLE_WIDTH keeps titles readable even on a narrow terminal.
+const MIN_TITLE_WIDTH: usize = 10;
 
 /// Printer to transrate tasks into table style string.
 pub struct TablePrinter<W: Write> {
-    tab_writer: TabWriter<W>,
+    writer: W,
+    max_width: Option<usize>,
+    numbered: bool,
+    tag_styles: TagStyles,
 }
 
 impl<W: Write> TablePrinter<W> {
     /// construct TablePrinter.
     pub fn new(w: W) -> Self {
         TablePrinter {
-            tab_writer: TabWriter::new(w),
+            writer: w,
+            max_width: None,
+            numbered: false,
+            tag_styles: TagStyles::default(),
         }
     }
 
-    /// print out with given writer.
-    pub fn print(&mut self, tasks: Vec<TaskDTO>) -> Result<()> {
-        writeln!(&mut self.tab_writer, "ID\tTitle\tPriority\tCost")?;
+    /// set the styles which tags are decorated with.
+    pub fn set_tag_styles(&mut self, tag_styles: TagStyles) {
+        self.tag_styles = tag_styles;
+    }
+
+    /// print_tasks prints a task table, truncating titles to fit in max_width.
+    /// The summary is printed as a footer if given.
+    fn print_tasks(
+        &mut self,
+        header: &[&str],
+        rows: Vec<Vec<String>>,
+        summary: Option<&ListSummary>,
+    ) -> Result<()> {
+        self.print_sections(header, vec![(None, rows)], summary)
+    }
+
+    /// print_sections prints a task table split into sections with optional headings.
+    /// Columns are aligned across all sections and rows are numbered through them.
+    fn print_sections(
+        &mut self,
+        header: &[&str],
+        sections: Vec<(Option<String>, Vec<Vec<String>>)>,
+        summary: Option<&ListSummary>,
+    ) -> Result<()> {
+        let mut headings = Vec::new();
+        let mut rows = Vec::new();
+        for (heading, section_rows) in sections {
+            headings.push((rows.len(), heading));
+            rows.extend(section_rows);
+        }
+
+        if let Some(max_width) = self.max_width {
+            let max_width = if self.numbered {
+                max_width.saturating_sub(number_width(rows.len()))
+            } else {
+                max_width
+            };
+            let title_width = title_width(header, &rows, max_width);
+            for row in rows.iter_mut() {
+                row[TITLE_COLUMN] = width::truncate_middle(&row[TITLE_COLUMN], title_width);
+            }
+        }
+
+        if self.numbered {
+            for (i, row) in rows.iter_mut().enumerate() {
+                row.insert(0, format!("%{}", i + 1));
+            }
+        }
+
+        let mut header: Vec<String> = header.iter().map(|h| h.to_string()).collect();
+        if self.numbered {
+            header.insert(0, "#".to_owned());
+        }
+        rows.insert(0, header);
+
+        let aligned = width::align(&rows);
+        let mut lines = aligned.lines();
+        let mut out = String::new();
+        if let Some(header) = lines.next() {
+            out.push_str(header);
+            out.push('\n');
+        }
+        let mut headings = headings.into_iter().peekable();
+        for (i, line) in lines.enumerate() {
+            while let Some((_, heading)) = headings.next_if(|(start, _)| *start <= i) {
+                push_heading(&mut out, heading);
+            }
+            out.push_str(line);
+            out.push('\n');
+        }
+        for (_, heading) in headings {
+            push_heading(&mut out, heading);
+        }
+
+        if let Some(summary) = summary {
+            out.push_str(&format_summary(summary));
+            out.push('\n');
+        }
+        self.write(&out)
+    }
+
+    /// print out the sprint and its committed tasks.
+    #[cfg(feature = "es")]
+    pub fn print_sprint(&mut self, sprint: &SprintDTO) -> Result<()> {
+        let status = match sprint.closed_on {
+            Some(closed_on) => format!("closed on {}", closed_on),
+            None => "active".to_owned(),
+        };
+        let mut out = format!(
+            "Sprint {}: {} - {} ({})\nCapacity: {}\nCommitted: {}\nCompleted: {} ({}%)\n\n",
+            sprint.name,
+            sprint.start_on,
+            sprint.end_on,
+            status,
+            sprint.capacity,
+            sprint.committed_cost,
+            sprint.completed_cost,
+            percentage(sprint.completed_cost, sprint.committed_cost),
+        );
+
+        let mut rows = vec![vec![
+            "ID".to_owned(),
+            "Title".to_owned(),
+            "Cost".to_owned(),
+            "Status".to_owned(),
+        ]];
+        for t in &sprint.tasks {
+            rows.push(vec![
+                t.id.to_string(),
+                t.title.to_owned(),
+                t.cost.to_string(),
+                if t.is_closed {
+                    "closed".to_owned()
+                } else {
+                    "open".to_owned()
+                },
+            ]);
+        }
+        out.push_str(&width::align(&rows));
+
+        self.write(&out)
+    }
+
+    /// print out the forecast, with completion dates of each task by percentile.
+    #[cfg(feature = "es")]
+    pub fn print_forecast(&mut self, forecast: &ForecastDTO) -> Result<()> {
+        if forecast.history == 0 {
+            return self.write("No task has been closed yet, so that nothing can be forecast.\n");
+        }
+        let mut out = format!(
+            "Based on cycle times of {} closed tasks, {} trials each\n\n",
+            forecast.history, forecast.trials,
+        );
+
+        let mut header = vec!["ID".to_owned(), "Title".to_owned(), "Age".to_owned()];
+        header.extend(PERCENTILES.iter().map(|p| format!("{}%", p)));
+        let mut rows = vec![header];
+        for t in &forecast.tasks {
+            let mut row = vec![
+                t.id.to_string(),
+                t.title.to_owned(),
+                format!("{}d", t.age_days),
+            ];
+            row.extend(t.percentiles.iter().map(|p| p.finish_on.to_string()));
+            rows.push(row);
+        }
+        out.push_str(&width::align(&rows));
+
+        self.write(&out)
+    }
+
+    /// print out the simulation, with the throughput band and the outlook of each task.
+    #[cfg(feature = "es")]
+    pub fn print_simulation(&mut self, simulation: &SimulationDTO) -> Result<()> {
+        let throughput = &simulation.throughput;
+        let mut out = format!(
+            "Until {}\nThroughput: {:.1} / {:.1} / {:.1} cost per day (low / median / high)\n\n",
+            simulation.until, throughput.low, throughput.median, throughput.high,
+        );
+
+        let mut rows = vec![vec![
+            "ID".to_owned(),
+            "Title".to_owned(),
+            "Left".to_owned(),
+            "Cumulative".to_owned(),
+            "Finish".to_owned(),
+            "Outlook".to_owned(),
+        ]];
+        for t in &simulation.tasks {
+            rows.push(vec![
+                t.id.to_string(),
+                t.title.to_owned(),
+                t.remaining.to_string(),
+                t.cumulative.to_string(),
+                t.finish_on.map_or("-".to_owned(), |d| d.to_string()),
+                match t.outlook {
+                    Outlook::Done => "done".to_owned(),
+                    Outlook::AtRisk => "at risk".to_owned(),
+                    Outlook::Slip => "slip".to_owned(),
+                },
+            ]);
+        }
+        out.push_str(&width::align(&rows));
+
+        let count = |outlook| {
+            simulation
+                .tasks
+                .iter()
+                .filter(|t| t.outlook == outlook)
+                .count()
+        };
+        out.push_str(&format!(
+            "\n{} done, {} at risk, {} slip by {}.\n",
+            count(Outlook::Done),
+            count(Outlook::AtRisk),
+            count(Outlook::Slip),
+            simulation.until,
+        ));
+
+        self.write(&out)
+    }
+
+    /// print out tags, one per line, with the numbers of open and all tasks if counts is set.
+    #[cfg(feature = "es")]
+    pub fn print_tags(&mut self, tags: &[TagCountDTO], counts: bool) -> Result<()> {
+        if !counts {
+            let out: String = tags
+                .iter()
+                .map(|t| format!("{}\n", self.tag_styles.render(&t.tag)))
+                .collect();
+            return self.write(&out);
+        }
+
+        let mut rows = vec![vec![
+            "Tag".to_owned(),
+            "Open".to_owned(),
+            "Total".to_owned(),
+        ]];
+        for t in tags {
+            rows.push(vec![
+                self.tag_styles.render(&t.tag),
+                t.open.to_string(),
+                t.total.to_string(),
+            ]);
+        }
+        self.write(&width::align(&rows))
+    }
+
+    /// print out changes of the legacy engine with the task before each change.
+    #[cfg(feature = "legacy")]
+    pub fn print_history(&mut self, changes: &[ChangeDTO]) -> Result<()> {
+        let mut rows = vec![vec![
+            "Recorded at".to_owned(),
+            "ID".to_owned(),
+            "Change".to_owned(),
+            "Before".to_owned(),
+        ]];
+        for c in changes {
+            let (change, before) = match &c.before {
+                Some(b) => (
+                    "update",
+                    format!(
+                        "{} (priority {}, cost {}, {})",
+                        b.title,
+                        b.priority,
+                        b.cost,
+                        if b.is_closed { "closed" } else { "open" }
+                    ),
+                ),
+                None => ("add", String::new()),
+            };
+            rows.push(vec![
+                c.recorded_at.clone(),
+                c.id.to_string(),
+                change.to_owned(),
+                before,
+            ]);
+        }
+        self.write(&width::align(&rows))
+    }
+
+    /// print out invoked commands, each a pair of when it was recorded and the command line.
+    pub fn print_commands(&mut self, commands: &[(String, String)]) -> Result<()> {
+        let mut rows = vec![vec!["Recorded at".to_owned(), "Command".to_owned()]];
+        for (recorded_at, command) in commands {
+            rows.push(vec![recorded_at.to_owned(), command.to_owned()]);
+        }
+        self.write(&width::align(&rows))
+    }
+
+    /// print out repository metrics and slow queries.
+    pub fn print_metrics(
+        &mut self,
+        stats: &[(String, MethodStats)],
+        slow_queries: &[SlowQuery],
+    ) -> Result<()> {
+        let mut rows = vec![vec![
+            "Method".to_owned(),
+            "Calls".to_owned(),
+            "Avg(ms)".to_owned(),
+            "Max(ms)".to_owned(),
+            "Total(ms)".to_owned(),
+        ]];
+        for (method, s) in stats {
+            rows.push(vec![
+                method.to_owned(),
+                s.calls.to_string(),
+                format!("{:.3}", s.average().as_secs_f64() * 1000.0),
+                format!("{:.3}", s.max.as_secs_f64() * 1000.0),
+                format!("{:.3}", s.total.as_secs_f64() * 1000.0),
+            ]);
+        }
+        let mut out = width::align(&rows);
 
-        for t in tasks {
-            writeln!(
-                &mut self.tab_writer,
-                "{}\t{}\t{}\t{}",
-                t.id, t.title, t.priority, t.cost
-            )?;
+        if !slow_queries.is_empty() {
+            let mut rows = vec![vec![
+                "Slow query".to_owned(),
+                "Elapsed(ms)".to_owned(),
+                "Occurred on".to_owned(),
+            ]];
+            for sq in slow_queries {
+                rows.push(vec![
+                    sq.method.to_owned(),
+                    format!("{:.3}", sq.elapsed.as_secs_f64() * 1000.0),
+                    sq.occurred_on.to_owned(),
+                ]);
+            }
+            out.push('\n');
+            out.push_str(&width::align(&rows));
         }
 
-        self.tab_writer.flush()?;
+        self.write(&out)
+    }
+
+    /// write writes the rendered table and flushes it.
+    fn write(&mut self, table: &str) -> Result<()> {
+        self.writer.write_all(table.as_bytes())?;
+        self.writer.flush()?;
 
         Ok(())
     }
+}
+
+impl<W: Write> Printer for TablePrinter<W> {
+    /// set the maximum width of task tables. Titles are truncated to fit in it.
+    /// None means no limit.
+    fn set_max_width(&mut self, max_width: Option<usize>) {
+        self.max_width = max_width;
+    }
+
+    /// set whether task tables have row numbers such as `%1` at the head of rows.
+    fn set_numbered(&mut self, numbered: bool) {
+        self.numbered = numbered;
+    }
+
+    /// print out with given writer.
+    #[cfg(feature = "legacy")]
+    fn print(&mut self, tasks: Vec<TaskDTO>, summary: Option<&ListSummary>) -> Result<()> {
+        let rows = tasks
+            .into_iter()
+            .map(|t| {
+                let title = if t.is_deleted {
+                    format!("{} (deleted)", t.title)
+                } else {
+                    t.title
+                };
+                vec![
+                    t.id.to_string(),
+                    title,
+                    t.priority.to_string(),
+                    t.cost.to_string(),
+                ]
+            })
+            .collect();
+
+        self.print_tasks(&["ID", "Title", "Priority", "Cost"], rows, summary)
+    }
 
     /// print out with given writer.
-    pub fn print_es(&mut self, tasks: Vec<ESTaskDTO>) -> Result<()> {
-        writeln!(&mut self.tab_writer, "ID\tTitle\tPriority\tCost")?;
+    #[cfg(feature = "es")]
+    fn print_es(&mut self, tasks: Vec<ESTaskDTO>, summary: Option<&ListSummary>) -> Result<()> {
+        let rows = tasks
+            .into_iter()
+            .map(|t| es_row(t, &self.tag_styles))
+            .collect();
+
+        self.print_tasks(&ES_HEADER, rows, summary)
+    }
+
+    /// print out a tree of tasks, indenting subtasks under their parents.
+    #[cfg(feature = "es")]
+    fn print_es_tree(
+        &mut self,
+        tasks: Vec<NestedTaskDTO>,
+        summary: Option<&ListSummary>,
+    ) -> Result<()> {
+        let rows = tasks
+            .into_iter()
+            .map(|n| {
+                let mut row = es_row(n.task, &self.tag_styles);
+                if n.depth > 0 {
+                    row[1] = format!("{}└ {}", "  ".repeat(n.depth - 1), row[1]);
+                }
+                row
+            })
+            .collect();
+
+        self.print_tasks(&ES_HEADER, rows, summary)
+    }
+
+    /// print out groups of tasks as sections headed by their keys and subtotals.
+    #[cfg(feature = "es")]
+    fn print_es_groups(
+        &mut self,
+        groups: Vec<ESTaskGroupDTO>,
+        summary: Option<&ListSummary>,
+    ) -> Result<()> {
+        let sections = groups
+            .into_iter()
+            .map(|g| {
+                (
+                    Some(format!("[{}] {}", g.key, format_summary(&g.summary))),
+                    g.tasks
+                        .into_iter()
+                        .map(|t| es_row(t, &self.tag_styles))
+                        .collect(),
+                )
+            })
+            .collect();
+
+        self.print_sections(&ES_HEADER, sections, summary)
+    }
 
-        for t in tasks {
-            writeln!(
-                &mut self.tab_writer,
-                "{}\t{}\t{}\t{}",
-                t.id, t.title, t.priority, t.cost
-            )?;
+    /// print out the full detail of a task, fields which are not set left out, the
+    /// description below them, and the events which changed the task at the bottom.
+    #[cfg(feature = "es")]
+    fn print_task_detail(&mut self, task: &TaskDetailDTO) -> Result<()> {
+        let mut status = vec![match (task.is_archived, task.is_deleted, task.is_closed) {
+            (true, _, _) => "archived",
+            (false, true, _) => "deleted",
+            (false, false, true) => "closed",
+            (false, false, false) => "open",
+        }];
+        for (flag, name) in [
+            (task.is_locked, "locked"),
+            (task.is_private, "private"),
+            (task.in_inbox, "in the inbox"),
+            (task.is_someday, "someday"),
+        ] {
+            if flag {
+                status.push(name);
+            }
         }
 
-        self.tab_writer.flush()?;
+        let mut rows = vec![
+            vec!["ID".to_owned(), task.id.to_string()],
+            vec!["Title".to_owned(), task.title.to_owned()],
+            vec!["Status".to_owned(), status.join(", ")],
+            vec!["Priority".to_owned(), task.priority.to_string()],
+            vec!["Urgency".to_owned(), task.urgency.to_string()],
+            vec!["Cost".to_owned(), task.cost.to_string()],
+        ];
+        let mut push = |name: &str, value: Option<String>| {
+            if let Some(value) = value {
+                rows.push(vec![name.to_owned(), value]);
+            }
+        };
+        push("Tags", (!task.tags.is_empty()).then(|| task.tags.join(",")));
+        push("Due", task.due_on.map(|d| d.to_string()));
+        push("Parent", task.parent.map(|id| format!("#{}", id)));
+        push("Follows", task.follows.map(|id| format!("#{}", id)));
+        push(
+            "Waiting on",
+            task.waiting_on.as_ref().map(|w| match w.follow_up_on {
+                Some(day) => format!("{}, follow up on {}", w.person, day),
+                None => w.person.to_owned(),
+            }),
+        );
+        push(
+            "Spent",
+            (task.elapsed.as_secs() >= 60).then(|| format_elapsed(task.elapsed)),
+        );
+        push(
+            "Pomodoros",
+            (task.pomodoros > 0).then(|| task.pomodoros.to_string()),
+        );
+        push(
+            "Created",
+            Some(task.created_at.format("%Y-%m-%d %H:%M").to_string()),
+        );
+        push(
+            "Closed",
+            task.closed_at
+                .map(|c| c.format("%Y-%m-%d %H:%M").to_string()),
+        );
+
+        let mut out = width::align(&rows);
+        if let Some(description) = &task.description {
+            out.push('\n');
+            out.push_str(description);
+            if !description.ends_with('\n') {
+                out.push('\n');
+            }
+        }
+        if !task.events.is_empty() {
+            let mut rows = vec![vec!["Occurred at".to_owned(), "Change".to_owned()]];
+            for e in &task.events {
+                rows.push(vec![
+                    e.occurred_at.format("%Y-%m-%d %H:%M").to_string(),
+                    e.change.to_owned(),
+                ]);
+            }
+            out.push('\n');
+            out.push_str(&width::align(&rows));
+        }
+
+        self.write(&out)
+    }
+
+    /// print out found tasks with snippets on a line each, matched words highlighted.
+    #[cfg(feature = "es")]
+    fn print_search_matches(&mut self, matches: Vec<SearchMatchDTO>) -> Result<()> {
+        let rows = matches
+            .iter()
+            .map(|m| {
+                let snippet: String = m
+                    .snippet
+                    .iter()
+                    .map(|p| {
+                        let text = p.text.replace('\n', " ");
+                        if p.is_match {
+                            self.tag_styles.highlight(&text)
+                        } else {
+                            text
+                        }
+                    })
+                    .collect();
+                vec![m.id.to_string(), m.title.to_owned(), snippet]
+            })
+            .collect();
+        self.print_tasks(&["ID", "Title", "Match"], rows, None)
+    }
+
+    fn print_done(&mut self, verb: &str, id: i64) -> Result<()> {
+        self.write(&format!("{} the task for id `{}`.\n", verb, id))
+    }
 
+    fn print_saved(&mut self, _verb: &str, _id: i64) -> Result<()> {
         Ok(())
     }
 }
 
-#[cfg(test)]
+/// ES_HEADER is the header of ES task tables.
+#[cfg(feature = "es")]
+const ES_HEADER: [&str; 6] = ["ID", "Title", "Priority", "Urgency", "Cost", "Tags"];
+
+/// es_row converts a task into a row of ES task tables.
+#[cfg(feature = "es")]
+fn es_row(t: ESTaskDTO, tag_styles: &TagStyles) -> Vec<String> {
+    let title = match &t.habit {
+        Some(h) => format!("{} ({}/{} this {})", t.title, h.done, h.times, h.period),
+        None => t.title,
+    };
+    let title = match &t.waiting_on {
+        Some(WaitingOnDTO {
+            person,
+            follow_up_on: Some(day),
+        }) => format!("{} (waiting on {}, follow up on {})", title, person, day),
+        Some(WaitingOnDTO { person, .. }) => format!("{} (waiting on {})", title, person),
+        None => title,
+    };
+    let title = match t.follows {
+        Some(id) => format!("{} (follows #{})", title, id),
+        None => title,
+    };
+    let title = match t.due_on {
+        Some(day) if t.is_overdue => format!("{} (overdue since {})", title, day),
+        Some(day) => format!("{} (due on {})", title, day),
+        None => title,
+    };
+    let title = match (t.elapsed.as_secs() >= 60, t.is_timer_running) {
+        (true, true) => format!(
+            "{} ({} spent, timer running)",
+            title,
+            format_elapsed(t.elapsed)
+        ),
+        (true, false) => format!("{} ({} spent)", title, format_elapsed(t.elapsed)),
+        (false, true) => format!("{} (timer running)", title),
+        (false, false) => title,
+    };
+    let title = match t.pomodoros {
+        0 => title,
+        1 => format!("{} (1 pomodoro)", title),
+        n => format!("{} ({} pomodoros)", title, n),
+    };
+    let title = match (t.is_archived, t.is_deleted) {
+        (true, _) => format!("{} (archived)", title),
+        (false, true) => format!("{} (deleted)", title),
+        (false, false) => title,
+    };
+    let id = if t.is_overdue {
+        tag_styles.alert(&t.id.to_string())
+    } else {
+        t.id.to_string()
+    };
+    vec![
+        id,
+        title,
+        t.priority.to_string(),
+        t.urgency.to_string(),
+        t.cost.to_string(),
+        t.tags
+            .iter()
+            .map(|tag| tag_styles.render(tag))
+            .collect::<Vec<_>>()
+            .join(","),
+    ]
+}
+
+/// format_elapsed formats time spent in hours and minutes, e.g. `1h30m`.
+#[cfg(feature = "es")]
+pub fn format_elapsed(elapsed: Duration) -> String {
+    let minutes = elapsed.as_secs() / 60;
+    match (minutes / 60, minutes % 60) {
+        (0, m) => format!("{}m", m),
+        (h, 0) => format!("{}h", h),
+        (h, m) => format!("{}h{}m", h, m),
+    }
+}
+
+/// percentage returns part / whole in percent rounded down, or 0 if whole is 0.
+#[cfg(feature = "es")]
+fn percentage(part: i32, whole: i32) -> i64 {
+    match whole {
+        0 => 0,
+        _ => i64::from(part) * 100 / i64::from(whole),
+    }
+}
+
+/// format_summary formats the summary as a single line. Overdue tasks are shown only if any.
+fn format_summary(summary: &ListSummary) -> String {
+    let line = format!("{} open · total cost {}", summary.open, summary.total_cost);
+    if summary.overdue == 0 {
+        return line;
+    }
+    format!("{} · {} overdue", line, summary.overdue)
+}
+
+/// format_outstanding formats the open work as a sentence, e.g. `You now have 3.2 weeks of work queued`.
+pub fn format_outstanding(outstanding: &Outstanding) -> String {
+    let summary = format_summary(&outstanding.summary);
+    match outstanding.weeks {
+        Some(weeks) => format!(
+            "You now have {:.1} weeks of work queued ({}).",
+            weeks, summary
+        ),
+        None => format!("You now have {}.", summary),
+    }
+}
+
+/// format_capacity_warning advises to make room in the backlog queued beyond max_weeks.
+pub fn format_capacity_warning(max_weeks: u32) -> String {
+    format!(
+        "backlog now exceeds {} weeks; consider moving something to someday",
+        max_weeks
+    )
+}
+
+/// push_heading appends the heading of a section as a line.
+fn push_heading(out: &mut String, heading: Option<String>) {
+    if let Some(heading) = heading {
+        out.push_str(&heading);
+        out.push('\n');
+    }
+}
+
+/// number_width returns the width which the row number column occupies including its padding.
+fn number_width(rows: usize) -> usize {
+    (format!("%{}", rows).len()).max(width::display_width("#")) + width::COLUMN_PADDING
+}
+
+/// title_width returns the width left for the title column after the other columns.
+fn title_width(header: &[&str], rows: &[Vec<String>], max_width: usize) -> usize {
+    let others: usize = (0..header.len())
+        .filter(|c| *c != TITLE_COLUMN)
+        .map(|c| {
+            rows.iter()
+                .map(|r| width::display_width(&r[c]))
+                .chain(std::iter::once(width::display_width(header[c])))
+                .max()
+                .unwrap_or(0)
+        })
+        .sum();
+    let paddings = width::COLUMN_PADDING * header.len().saturating_sub(1);
+
+    max_width
+        .saturating_sub(others + paddings)
+        .max(MIN_TITLE_WIDTH)
+}
+
+#[cfg(all(test, feature = "legacy"))]
 mod tests {
     use super::*;
+    #[cfg(feature = "es")]
+    use crate::usecase::es_list_task_usecase::HabitProgressDTO;
+    #[cfg(feature = "es")]
+    use crate::usecase::es_show_task_usecase::EventDTO;
 
     #[test]
     fn test_execute() {
@@ -86,29 +774,64 @@ mod tests {
                             title: "title1".to_owned(),
                             priority: 1,
                             cost: 1,
+                            created_at: String::new(),
+                            updated_at: String::new(),
+                            is_deleted: false,
                         },
                         TaskDTO {
                             id: 2,
                             title: "title2".to_owned(),
                             priority: 2,
                             cost: 2,
+                            created_at: String::new(),
+                            updated_at: String::new(),
+                            is_deleted: false,
                         },
                         TaskDTO {
                             id: 3,
                             title: "title3".to_owned(),
                             priority: 3,
                             cost: 3,
+                            created_at: String::new(),
+                            updated_at: String::new(),
+                            is_deleted: false,
                         },
                     ],
                 },
                 want: String::from("ID  Title   Priority  Cost\n1   title1  1         1\n2   title2  2         2\n3   title3  3         3\n"),
             },
+            TestCase {
+                name: String::from("normal: japanese titles"),
+                args: Args {
+                    tasks: vec![
+                        TaskDTO {
+                            id: 1,
+                            title: "日本語のタイトル".to_owned(),
+                            priority: 1,
+                            cost: 1,
+                            created_at: String::new(),
+                            updated_at: String::new(),
+                            is_deleted: false,
+                        },
+                        TaskDTO {
+                            id: 2,
+                            title: "mixed 日本語".to_owned(),
+                            priority: 2,
+                            cost: 2,
+                            created_at: String::new(),
+                            updated_at: String::new(),
+                            is_deleted: false,
+                        },
+                    ],
+                },
+                want: String::from("ID  Title             Priority  Cost\n1   日本語のタイトル  1         1\n2   mixed 日本語      2         2\n"),
+            },
         ];
 
         for test_case in table {
             let mut table_printer = TablePrinter::new(vec![]);
-            table_printer.print(test_case.args.tasks).unwrap();
-            let got = String::from_utf8(table_printer.tab_writer.into_inner().unwrap()).unwrap();
+            table_printer.print(test_case.args.tasks, None).unwrap();
+            let got = String::from_utf8(table_printer.writer).unwrap();
 
             assert_eq!(
                 &*got, test_case.want,
@@ -117,4 +840,586 @@ mod tests {
             );
         }
     }
+
+    #[test]
+    fn test_print_with_max_width() {
+        let mut table_printer = TablePrinter::new(vec![]);
+        table_printer.set_max_width(Some(36));
+        table_printer
+            .print(
+                vec![TaskDTO {
+                    id: 1,
+                    title: "write documents of taskmr".to_owned(),
+                    priority: 1,
+                    cost: 1,
+                    created_at: String::new(),
+                    updated_at: String::new(),
+                    is_deleted: false,
+                }],
+                None,
+            )
+            .unwrap();
+        let got = String::from_utf8(table_printer.writer).unwrap();
+
+        assert_eq!(
+            got,
+            "ID  Title             Priority  Cost\n1   write do… taskmr  1         1\n"
+        );
+    }
+
+    #[test]
+    fn test_print_numbered_with_summary() {
+        let mut table_printer = TablePrinter::new(vec![]);
+        table_printer.set_numbered(true);
+        table_printer
+            .print(
+                vec![
+                    TaskDTO {
+                        id: 5,
+                        title: "title5".to_owned(),
+                        priority: 1,
+                        cost: 1,
+                        created_at: String::new(),
+                        updated_at: String::new(),
+                        is_deleted: false,
+                    },
+                    TaskDTO {
+                        id: 8,
+                        title: "title8".to_owned(),
+                        priority: 1,
+                        cost: 1,
+                        created_at: String::new(),
+                        updated_at: String::new(),
+                        is_deleted: false,
+                    },
+                ],
+                Some(&ListSummary {
+                    open: 2,
+                    total_cost: 2,
+                    overdue: 0,
+                }),
+            )
+            .unwrap();
+        let got = String::from_utf8(table_printer.writer).unwrap();
+
+        assert_eq!(
+            got,
+            "#   ID  Title   Priority  Cost\n%1  5   title5  1         1\n%2  8   title8  1         1\n2 open · total cost 2\n"
+        );
+    }
+
+    #[cfg(feature = "es")]
+    #[test]
+    fn test_print_es_groups() {
+        let task = |id: i64, tags: &[&str]| ESTaskDTO {
+            id,
+            title: format!("title{}", id),
+            priority: 10,
+            urgency: 10,
+            cost: id as i32,
+            tags: tags.iter().map(|t| t.to_string()).collect(),
+            is_closed: false,
+            habit: None,
+            waiting_on: None,
+            follows: None,
+            parent: None,
+            due_on: None,
+            is_overdue: false,
+            is_someday: false,
+            elapsed: Default::default(),
+            is_timer_running: false,
+            pomodoros: 0,
+            is_archived: false,
+            is_deleted: false,
+        };
+        let groups = vec![
+            ESTaskGroupDTO {
+                key: "docs".to_owned(),
+                tasks: vec![task(1, &["docs"])],
+                summary: ListSummary {
+                    open: 1,
+                    total_cost: 1,
+                    overdue: 0,
+                },
+            },
+            ESTaskGroupDTO {
+                key: "work".to_owned(),
+                tasks: vec![task(2, &["work"]), task(12, &["work"])],
+                summary: ListSummary {
+                    open: 2,
+                    total_cost: 14,
+                    overdue: 1,
+                },
+            },
+        ];
+
+        let mut table_printer = TablePrinter::new(vec![]);
+        table_printer.print_es_groups(groups, None).unwrap();
+        let got = String::from_utf8(table_printer.writer).unwrap();
+
+        assert_eq!(
+            got,
+            "ID  Title    Priority  Urgency  Cost  Tags\n\
+             [docs] 1 open · total cost 1\n\
+             1   title1   10        10       1     docs\n\
+             [work] 2 open · total cost 14 · 1 overdue\n\
+             2   title2   10        10       2     work\n\
+             12  title12  10        10       12    work\n"
+        );
+    }
+
+    #[cfg(feature = "es")]
+    #[test]
+    fn test_print_es_habit() {
+        let habit = ESTaskDTO {
+            id: 1,
+            title: "stretch".to_owned(),
+            priority: 10,
+            urgency: 10,
+            cost: 1,
+            tags: vec![],
+            is_closed: false,
+            habit: Some(HabitProgressDTO {
+                done: 3,
+                times: 5,
+                period: "week".to_owned(),
+            }),
+            waiting_on: None,
+            follows: None,
+            parent: None,
+            due_on: None,
+            is_overdue: false,
+            is_someday: false,
+            elapsed: Default::default(),
+            is_timer_running: false,
+            pomodoros: 0,
+            is_archived: false,
+            is_deleted: false,
+        };
+
+        let mut table_printer = TablePrinter::new(vec![]);
+        table_printer.print_es(vec![habit], None).unwrap();
+        let got = String::from_utf8(table_printer.writer).unwrap();
+
+        assert_eq!(
+            got,
+            "ID  Title                    Priority  Urgency  Cost  Tags\n\
+             1   stretch (3/5 this week)  10        10       1     \n"
+        );
+    }
+
+    #[cfg(feature = "es")]
+    #[test]
+    fn test_print_es_waiting_on() {
+        let task = |id: i64, follow_up_on| ESTaskDTO {
+            id,
+            title: "contract".to_owned(),
+            priority: 10,
+            urgency: 10,
+            cost: 1,
+            tags: vec![],
+            is_closed: false,
+            habit: None,
+            waiting_on: Some(WaitingOnDTO {
+                person: "Alice".to_owned(),
+                follow_up_on,
+            }),
+            follows: None,
+            parent: None,
+            due_on: None,
+            is_overdue: false,
+            is_someday: false,
+            elapsed: Default::default(),
+            is_timer_running: false,
+            pomodoros: 0,
+            is_archived: false,
+            is_deleted: false,
+        };
+
+        let mut table_printer = TablePrinter::new(vec![]);
+        let follow_up_on = chrono::NaiveDate::from_ymd_opt(2024, 5, 10);
+        table_printer
+            .print_es(vec![task(1, follow_up_on), task(2, None)], None)
+            .unwrap();
+        let got = String::from_utf8(table_printer.writer).unwrap();
+
+        assert_eq!(
+            got,
+            "ID  Title                                                 Priority  Urgency  Cost  Tags\n\
+             1   contract (waiting on Alice, follow up on 2024-05-10)  10        10       1     \n\
+             2   contract (waiting on Alice)                           10        10       1     \n"
+        );
+    }
+
+    #[cfg(feature = "es")]
+    #[test]
+    fn test_print_es_due() {
+        let task = |id: i64, is_overdue| ESTaskDTO {
+            id,
+            title: "tax".to_owned(),
+            priority: 10,
+            urgency: 10,
+            cost: 1,
+            tags: vec![],
+            is_closed: false,
+            habit: None,
+            waiting_on: None,
+            follows: None,
+            parent: None,
+            due_on: chrono::NaiveDate::from_ymd_opt(2024, 5, 10),
+            is_overdue,
+            is_someday: false,
+            elapsed: Default::default(),
+            is_timer_running: false,
+            pomodoros: 0,
+            is_archived: false,
+            is_deleted: false,
+        };
+
+        let mut table_printer = TablePrinter::new(vec![]);
+        table_printer.set_tag_styles(TagStyles::new(Default::default(), true));
+        table_printer
+            .print_es(vec![task(1, true), task(2, false)], None)
+            .unwrap();
+        let got = String::from_utf8(table_printer.writer).unwrap();
+
+        assert_eq!(
+            got,
+            "ID  Title                           Priority  Urgency  Cost  Tags\n\
+             \x1b[31m1\x1b[0m   tax (overdue since 2024-05-10)  10        10       1     \n\
+             2   tax (due on 2024-05-10)         10        10       1     \n"
+        );
+    }
+
+    #[cfg(feature = "es")]
+    #[test]
+    fn test_print_search_matches() {
+        use crate::usecase::es_search_task_usecase::SnippetPartDTO;
+
+        let part = |text: &str, is_match| SnippetPartDTO {
+            text: text.to_owned(),
+            is_match,
+        };
+        let matches = vec![SearchMatchDTO {
+            id: 2,
+            title: "write docs".to_owned(),
+            is_closed: false,
+            snippet: vec![
+                part("a ", false),
+                part("flaky", true),
+                part("\ntest", false),
+            ],
+        }];
+
+        let mut table_printer = TablePrinter::new(vec![]);
+        table_printer.print_search_matches(matches.clone()).unwrap();
+        let got = String::from_utf8(table_printer.writer).unwrap();
+        assert_eq!(
+            got,
+            "ID  Title       Match\n\
+             2   write docs  a [flaky] test\n"
+        );
+
+        let mut table_printer = TablePrinter::new(vec![]);
+        table_printer.set_tag_styles(TagStyles::new(Default::default(), true));
+        table_printer.print_search_matches(matches).unwrap();
+        let got = String::from_utf8(table_printer.writer).unwrap();
+        assert_eq!(
+            got,
+            "ID  Title       Match\n\
+             2   write docs  a \x1b[1mflaky\x1b[0m test\n"
+        );
+    }
+
+    #[cfg(feature = "es")]
+    #[test]
+    fn test_print_task_detail() {
+        let created_at = chrono::NaiveDate::from_ymd_opt(2024, 5, 1)
+            .unwrap()
+            .and_hms_opt(9, 30, 0)
+            .unwrap();
+        let task = TaskDetailDTO {
+            id: 3,
+            title: "fix login bug".to_owned(),
+            description: Some("sign in twice\nto see it".to_owned()),
+            priority: 80,
+            urgency: 10,
+            cost: 3,
+            tags: vec!["bug".to_owned(), "project:web".to_owned()],
+            is_closed: false,
+            is_locked: true,
+            is_private: false,
+            in_inbox: false,
+            is_someday: false,
+            is_deleted: false,
+            is_archived: false,
+            due_on: chrono::NaiveDate::from_ymd_opt(2024, 5, 3),
+            parent: None,
+            follows: None,
+            waiting_on: None,
+            elapsed: Duration::from_secs(45 * 60),
+            pomodoros: 0,
+            created_at,
+            closed_at: None,
+            events: vec![
+                EventDTO {
+                    occurred_at: created_at,
+                    change: "created".to_owned(),
+                },
+                EventDTO {
+                    occurred_at: created_at + chrono::Duration::hours(1),
+                    change: "locked".to_owned(),
+                },
+            ],
+        };
+
+        let mut table_printer = TablePrinter::new(vec![]);
+        table_printer.print_task_detail(&task).unwrap();
+        let got = String::from_utf8(table_printer.writer).unwrap();
+
+        assert_eq!(
+            got,
+            "ID        3\n\
+             Title     fix login bug\n\
+             Status    open, locked\n\
+             Priority  80\n\
+             Urgency   10\n\
+             Cost      3\n\
+             Tags      bug,project:web\n\
+             Due       2024-05-03\n\
+             Spent     45m\n\
+             Created   2024-05-01 09:30\n\
+             \n\
+             sign in twice\n\
+             to see it\n\
+             \n\
+             Occurred at       Change\n\
+             2024-05-01 09:30  created\n\
+             2024-05-01 10:30  locked\n"
+        );
+    }
+
+    #[cfg(feature = "es")]
+    #[test]
+    fn test_print_es_time_spent() {
+        let task = |id: i64, minutes: u64, is_timer_running| ESTaskDTO {
+            id,
+            title: "draft".to_owned(),
+            priority: 10,
+            urgency: 10,
+            cost: 1,
+            tags: vec![],
+            is_closed: false,
+            habit: None,
+            waiting_on: None,
+            follows: None,
+            parent: None,
+            due_on: None,
+            is_overdue: false,
+            is_someday: false,
+            elapsed: Duration::from_secs(minutes * 60),
+            is_timer_running,
+            pomodoros: 0,
+            is_archived: false,
+            is_deleted: false,
+        };
+
+        let mut table_printer = TablePrinter::new(vec![]);
+        table_printer
+            .print_es(
+                vec![
+                    task(1, 90, true),
+                    ESTaskDTO {
+                        pomodoros: 4,
+                        ..task(2, 120, false)
+                    },
+                    task(3, 0, true),
+                    task(4, 0, false),
+                ],
+                None,
+            )
+            .unwrap();
+        let got = String::from_utf8(table_printer.writer).unwrap();
+
+        assert_eq!(
+            got,
+            "ID  Title                               Priority  Urgency  Cost  Tags\n\
+             1   draft (1h30m spent, timer running)  10        10       1     \n\
+             2   draft (2h spent) (4 pomodoros)      10        10       1     \n\
+             3   draft (timer running)               10        10       1     \n\
+             4   draft                               10        10       1     \n"
+        );
+    }
+
+    #[cfg(feature = "es")]
+    #[test]
+    fn test_print_es_tree() {
+        let task = |id: i64, title: &str, parent| ESTaskDTO {
+            id,
+            title: title.to_owned(),
+            priority: 0,
+            urgency: 0,
+            cost: 0,
+            tags: vec![],
+            is_closed: false,
+            habit: None,
+            waiting_on: None,
+            follows: None,
+            parent,
+            due_on: None,
+            is_overdue: false,
+            is_someday: false,
+            elapsed: Default::default(),
+            is_timer_running: false,
+            pomodoros: 0,
+            is_archived: false,
+            is_deleted: false,
+        };
+        let tree = vec![
+            NestedTaskDTO {
+                depth: 0,
+                task: task(1, "release", None),
+            },
+            NestedTaskDTO {
+                depth: 1,
+                task: task(2, "write notes", Some(1)),
+            },
+            NestedTaskDTO {
+                depth: 2,
+                task: task(3, "list fixes", Some(2)),
+            },
+        ];
+
+        let mut table_printer = TablePrinter::new(vec![]);
+        table_printer.print_es_tree(tree, None).unwrap();
+        let got = String::from_utf8(table_printer.writer).unwrap();
+
+        assert_eq!(
+            got,
+            "ID  Title           Priority  Urgency  Cost  Tags\n\
+             1   release         0         0        0     \n\
+             2   └ write notes   0         0        0     \n\
+             3     └ list fixes  0         0        0     \n"
+        );
+    }
+
+    #[cfg(feature = "es")]
+    #[test]
+    fn test_print_es_follows() {
+        let task = ESTaskDTO {
+            id: 2,
+            title: "send summary".to_owned(),
+            priority: 0,
+            urgency: 0,
+            cost: 0,
+            tags: vec![],
+            is_closed: false,
+            habit: None,
+            waiting_on: None,
+            follows: Some(1),
+            parent: None,
+            due_on: None,
+            is_overdue: false,
+            is_someday: false,
+            elapsed: Default::default(),
+            is_timer_running: false,
+            pomodoros: 0,
+            is_archived: false,
+            is_deleted: false,
+        };
+
+        let mut table_printer = TablePrinter::new(vec![]);
+        table_printer.print_es(vec![task], None).unwrap();
+        let got = String::from_utf8(table_printer.writer).unwrap();
+
+        assert_eq!(
+            got,
+            "ID  Title                      Priority  Urgency  Cost  Tags\n\
+             2   send summary (follows #1)  0         0        0     \n"
+        );
+    }
+
+    #[cfg(feature = "es")]
+    #[test]
+    fn test_print_tags() {
+        let tags = [
+            TagCountDTO {
+                tag: "docs".to_owned(),
+                open: 1,
+                total: 1,
+            },
+            TagCountDTO {
+                tag: "job".to_owned(),
+                open: 2,
+                total: 12,
+            },
+        ];
+
+        let mut table_printer = TablePrinter::new(vec![]);
+        table_printer.print_tags(&tags, false).unwrap();
+        table_printer.print_tags(&tags, true).unwrap();
+        let got = String::from_utf8(table_printer.writer).unwrap();
+
+        assert_eq!(
+            got,
+            "docs\njob\n\
+             Tag   Open  Total\n\
+             docs  1     1\n\
+             job   2     12\n"
+        );
+    }
+
+    #[test]
+    fn test_print_history() {
+        let changes = [
+            ChangeDTO {
+                id: 1,
+                before: Some(crate::usecase::undo_usecase::BeforeImageDTO {
+                    title: "title".to_owned(),
+                    is_closed: false,
+                    priority: 20,
+                    cost: 3,
+                    is_deleted: false,
+                }),
+                recorded_at: "2024-05-02 10:00:00".to_owned(),
+            },
+            ChangeDTO {
+                id: 1,
+                before: None,
+                recorded_at: "2024-05-01 09:00:00".to_owned(),
+            },
+        ];
+
+        let mut table_printer = TablePrinter::new(vec![]);
+        table_printer.print_history(&changes).unwrap();
+        let got = String::from_utf8(table_printer.writer).unwrap();
+
+        assert_eq!(
+            got,
+            "Recorded at          ID  Change  Before\n\
+             2024-05-02 10:00:00  1   update  title (priority 20, cost 3, open)\n\
+             2024-05-01 09:00:00  1   add     \n"
+        );
+    }
+
+    #[test]
+    fn test_format_outstanding() {
+        let summary = ListSummary {
+            open: 12,
+            total_cost: 64,
+            overdue: 0,
+        };
+        assert_eq!(
+            format_outstanding(&Outstanding::new(summary, Some(20))),
+            "You now have 3.2 weeks of work queued (12 open · total cost 64)."
+        );
+        assert_eq!(
+            format_outstanding(&Outstanding::new(summary, None)),
+            "You now have 12 open · total cost 64."
+        );
+        assert_eq!(
+            format_capacity_warning(4),
+            "backlog now exceeds 4 weeks; consider moving something to someday"
+        );
+    }
 }
@@ -0,0 +1,146 @@
+//! # widget
+//!
+//! widget renders a compact one-line status segment for status bars such as tmux.
+
+use clap::ValueEnum;
+
+use crate::presentation::printer::width;
+
+/// WidgetFormat is the markup of the segment.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum WidgetFormat {
+    /// plain text.
+    Plain,
+    /// tmux status line with `#[fg=...]` styles.
+    Tmux,
+}
+
+/// WidgetData is what the widget shows.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WidgetData {
+    /// focused is the title of the task to be done next.
+    pub focused: Option<String>,
+    /// overdue is the number of tasks open past the due date.
+    pub overdue: usize,
+}
+
+/// render renders the segment whose display width never exceeds `max_width` columns.
+pub fn render(format: WidgetFormat, data: &WidgetData, max_width: usize) -> String {
+    let count = format!(" [!{}]", data.overdue);
+    let title_width = max_width.saturating_sub(width::display_width(&count));
+    let title = width::truncate_end(data.focused.as_deref().unwrap_or("-"), title_width);
+
+    if title.is_empty() {
+        return String::new();
+    }
+
+    match format {
+        WidgetFormat::Plain => format!("{}{}", title, count),
+        WidgetFormat::Tmux => format!(
+            "#[fg=green]{}#[fg=yellow]{}#[default]",
+            // NOTE: `#` starts a style in tmux, `##` is a literal `#`.
+            title.replace('#', "##"),
+            count
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render() {
+        #[derive(Debug)]
+        struct TestCase {
+            args: (WidgetFormat, WidgetData, usize),
+            want: &'static str,
+            name: String,
+        }
+
+        let table = [
+            TestCase {
+                name: String::from("plain"),
+                args: (
+                    WidgetFormat::Plain,
+                    WidgetData {
+                        focused: Some(String::from("write docs")),
+                        overdue: 3,
+                    },
+                    40,
+                ),
+                want: "write docs [!3]",
+            },
+            TestCase {
+                name: String::from("truncated"),
+                args: (
+                    WidgetFormat::Plain,
+                    WidgetData {
+                        focused: Some(String::from("write documents")),
+                        overdue: 12,
+                    },
+                    11,
+                ),
+                want: "writ… [!12]",
+            },
+            TestCase {
+                name: String::from("japanese"),
+                args: (
+                    WidgetFormat::Plain,
+                    WidgetData {
+                        focused: Some(String::from("ドキュメントを書く")),
+                        overdue: 1,
+                    },
+                    11,
+                ),
+                want: "ドキ… [!1]",
+            },
+            TestCase {
+                name: String::from("tmux escapes sharp"),
+                args: (
+                    WidgetFormat::Tmux,
+                    WidgetData {
+                        focused: Some(String::from("fix #3")),
+                        overdue: 1,
+                    },
+                    40,
+                ),
+                want: "#[fg=green]fix ##3#[fg=yellow] [!1]#[default]",
+            },
+            TestCase {
+                name: String::from("nothing to do"),
+                args: (
+                    WidgetFormat::Plain,
+                    WidgetData {
+                        focused: None,
+                        overdue: 0,
+                    },
+                    40,
+                ),
+                want: "- [!0]",
+            },
+            TestCase {
+                name: String::from("too narrow"),
+                args: (
+                    WidgetFormat::Plain,
+                    WidgetData {
+                        focused: None,
+                        overdue: 0,
+                    },
+                    4,
+                ),
+                want: "",
+            },
+        ];
+
+        for test_case in table {
+            let (format, data, max_width) = test_case.args;
+            assert_eq!(
+                render(format, &data, max_width),
+                test_case.want,
+                "Failed in the \"{}\".",
+                test_case.name,
+            );
+        }
+    }
+}
@@ -0,0 +1,245 @@
+//! # width
+//!
+//! width measures strings by their display width on terminals.
+//! CJK characters and most emoji occupy two columns, and ANSI color sequences occupy none.
+
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
+
+/// ELLIPSIS replaces the omitted part of truncated strings.
+const ELLIPSIS: &str = "…";
+
+/// COLUMN_PADDING is the number of spaces between columns.
+pub const COLUMN_PADDING: usize = 2;
+
+/// MIN_COLUMN_WIDTH is the minimum width of aligned columns.
+const MIN_COLUMN_WIDTH: usize = 2;
+
+/// display_width returns the number of columns `s` occupies.
+pub fn display_width(s: &str) -> usize {
+    if !s.contains('\x1b') {
+        return UnicodeWidthStr::width(s);
+    }
+    UnicodeWidthStr::width(strip_ansi(s).as_str())
+}
+
+/// strip_ansi removes ANSI escape sequences such as `\x1b[31m`.
+fn strip_ansi(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    let mut chars = s.chars();
+    while let Some(c) = chars.next() {
+        if c != '\x1b' {
+            out.push(c);
+            continue;
+        }
+        if chars.next() == Some('[') {
+            // NOTE: a control sequence ends with a byte from `@` to `~`.
+            for c in chars.by_ref() {
+                if ('@'..='~').contains(&c) {
+                    break;
+                }
+            }
+        }
+    }
+    out
+}
+
+/// terminal_width returns the width of the terminal attached to stdout.
+pub fn terminal_width() -> Option<usize> {
+    terminal_size::terminal_size().map(|(w, _)| w.0 as usize)
+}
+
+/// align renders rows as left aligned columns measured by display width.
+/// Every cell except the last one of a row is padded, so rows may have different lengths.
+pub fn align(rows: &[Vec<String>]) -> String {
+    let columns = rows.iter().map(|r| r.len()).max().unwrap_or(0);
+    let widths: Vec<usize> = (0..columns)
+        .map(|c| {
+            rows.iter()
+                .filter_map(|r| r.get(c))
+                .map(|cell| display_width(cell))
+                .max()
+                .unwrap_or(0)
+                .max(MIN_COLUMN_WIDTH)
+        })
+        .collect();
+
+    let mut out = String::new();
+    for row in rows {
+        for (c, cell) in row.iter().enumerate() {
+            out.push_str(cell);
+            if c + 1 < row.len() {
+                let padding = widths[c] - display_width(cell) + COLUMN_PADDING;
+                out.extend(std::iter::repeat_n(' ', padding));
+            }
+        }
+        out.push('\n');
+    }
+    out
+}
+
+/// truncate_middle shortens `s` to at most `width` columns by replacing its middle with an ellipsis.
+/// The head and the tail are kept because both tend to be meaningful in titles.
+pub fn truncate_middle(s: &str, width: usize) -> String {
+    if display_width(s) <= width {
+        return s.to_owned();
+    }
+    if width <= display_width(ELLIPSIS) {
+        return take_width(s.graphemes(true), width).concat();
+    }
+
+    let budget = width - display_width(ELLIPSIS);
+    let head = take_width(s.graphemes(true), budget - budget / 2);
+    let mut tail = take_width(s.graphemes(true).rev(), budget / 2);
+    tail.reverse();
+
+    let mut truncated = head.concat();
+    truncated.push_str(ELLIPSIS);
+    truncated.push_str(&tail.concat());
+    truncated
+}
+
+/// truncate_end shortens `s` to at most `width` columns by replacing its end with an ellipsis.
+pub fn truncate_end(s: &str, width: usize) -> String {
+    if display_width(s) <= width {
+        return s.to_owned();
+    }
+    if width <= display_width(ELLIPSIS) {
+        return take_width(s.graphemes(true), width).concat();
+    }
+
+    let mut truncated = take_width(s.graphemes(true), width - display_width(ELLIPSIS)).concat();
+    truncated.push_str(ELLIPSIS);
+    truncated
+}
+
+/// take_width takes grapheme clusters while they fit in `width` columns, so that emoji sequences
+/// joined by ZWJ or followed by a variation selector are never split.
+fn take_width<'a, I: Iterator<Item = &'a str>>(graphemes: I, width: usize) -> Vec<&'a str> {
+    let mut taken = Vec::new();
+    let mut used = 0;
+    for g in graphemes {
+        let w = UnicodeWidthStr::width(g);
+        if used + w > width {
+            break;
+        }
+        used += w;
+        taken.push(g);
+    }
+    taken
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_truncate_middle() {
+        #[derive(Debug)]
+        struct TestCase {
+            args: (&'static str, usize),
+            want: &'static str,
+            name: String,
+        }
+
+        let table = [
+            TestCase {
+                name: String::from("fits"),
+                args: ("write docs", 10),
+                want: "write docs",
+            },
+            TestCase {
+                name: String::from("ascii"),
+                args: ("write documents of taskmr", 11),
+                want: "write…askmr",
+            },
+            TestCase {
+                name: String::from("japanese"),
+                args: ("ドキュメントを書く", 9),
+                want: "ドキ…書く",
+            },
+            TestCase {
+                name: String::from("too narrow"),
+                args: ("write", 1),
+                want: "w",
+            },
+            TestCase {
+                name: String::from("zwj sequence at the cut"),
+                args: ("emoji 👨\u{200d}👩\u{200d}👧 family tree check", 15),
+                want: "emoji …e check",
+            },
+            TestCase {
+                name: String::from("zwj sequence kept whole"),
+                args: ("👨\u{200d}👩\u{200d}👧 family tree", 8),
+                want: "👨\u{200d}👩\u{200d}👧 f…ree",
+            },
+            TestCase {
+                name: String::from("vs16 emoji at the cut"),
+                args: ("check ✔\u{fe0f} done and more", 14),
+                want: "check …d more",
+            },
+            TestCase {
+                name: String::from("vs16 emoji kept whole"),
+                args: ("✔\u{fe0f} done and more", 8),
+                want: "✔\u{fe0f} d…ore",
+            },
+        ];
+
+        for test_case in table {
+            let got = truncate_middle(test_case.args.0, test_case.args.1);
+            assert_eq!(got, test_case.want, "Failed in the \"{}\".", test_case.name);
+            assert!(display_width(&got) <= test_case.args.1);
+        }
+    }
+
+    #[test]
+    fn test_align() {
+        #[derive(Debug)]
+        struct TestCase {
+            args: Vec<Vec<&'static str>>,
+            want: &'static str,
+            name: String,
+        }
+
+        let table = [
+            TestCase {
+                name: String::from("ascii"),
+                args: vec![vec!["ID", "Title", "Cost"], vec!["10", "write", "1"]],
+                want: "ID  Title  Cost\n10  write  1\n",
+            },
+            TestCase {
+                name: String::from("japanese"),
+                args: vec![vec!["ID", "Title", "Cost"], vec!["1", "日本語", "1"]],
+                want: "ID  Title   Cost\n1   日本語  1\n",
+            },
+            TestCase {
+                name: String::from("colored"),
+                args: vec![vec!["Tag", "ID"], vec!["\x1b[31mbug\x1b[0m", "1"]],
+                want: "Tag  ID\n\x1b[31mbug\x1b[0m  1\n",
+            },
+            TestCase {
+                name: String::from("emoji sequences"),
+                args: vec![
+                    vec!["1", "❤️", "x"],
+                    vec!["2", "👨\u{200d}👩\u{200d}👧", "x"],
+                    vec!["3", "ab", "x"],
+                ],
+                want: "1   ❤️  x\n2   👨\u{200d}👩\u{200d}👧  x\n3   ab  x\n",
+            },
+        ];
+
+        for test_case in table {
+            let rows: Vec<Vec<String>> = test_case
+                .args
+                .iter()
+                .map(|r| r.iter().map(|c| c.to_string()).collect())
+                .collect();
+            assert_eq!(
+                align(&rows),
+                test_case.want,
+                "Failed in the \"{}\".",
+                test_case.name,
+            );
+        }
+    }
+}
@@ -1,7 +1,8 @@
-use anyhow::Result;
+use crate::Result;
 use std::rc::Rc;
 
-use crate::domain::task::{Cost, ITaskRepository, Priority, Task, ID};
+use crate::domain::task::{ITaskRepository, Task, ID};
+use crate::domain::task_builder::TaskBuilder;
 
 /// DTO for input of AddTaskUseCase.
 #[derive(Debug)]
@@ -11,6 +12,16 @@ pub struct AddTaskUseCaseInput {
     pub cost: Option<i32>,
 }
 
+impl From<TaskBuilder> for AddTaskUseCaseInput {
+    fn from(builder: TaskBuilder) -> Self {
+        AddTaskUseCaseInput {
+            title: builder.title,
+            priority: builder.priority,
+            cost: builder.cost,
+        }
+    }
+}
+
 /// Usecase to add a task.
 pub struct AddTaskUseCase {
     task_repository: Rc<dyn ITaskRepository>,
@@ -24,9 +35,11 @@ impl AddTaskUseCase {
 
     /// execute addition a task.
     pub fn execute(&self, input: AddTaskUseCaseInput) -> Result<ID> {
-        let p: Option<Priority> = input.priority.map(Priority::new);
-        let c: Option<Cost> = input.cost.map(Cost::new);
-        let t = Task::new(input.title, p, c);
+        let t = Task::new(TaskBuilder {
+            priority: input.priority,
+            cost: input.cost,
+            ..TaskBuilder::new(input.title)
+        });
         self.task_repository.add(t)
     }
 }
@@ -61,11 +74,7 @@ mod tests {
                         cost: Some(200),
                     },
                 },
-                want: Task::new(
-                    "title1".to_owned(),
-                    Some(Priority::new(100)),
-                    Some(Cost::new(200)),
-                ),
+                want: Task::new(TaskBuilder::new("title1").priority(100).cost(200)),
             },
             TestCase {
                 name: String::from("normal: without priority and cost"),
@@ -76,11 +85,7 @@ mod tests {
                         cost: None,
                     },
                 },
-                want: Task::new(
-                    "title2".to_owned(),
-                    Some(Priority::new(10)),
-                    Some(Cost::new(10)),
-                ),
+                want: Task::new(TaskBuilder::new("title2").priority(10).cost(10)),
             },
         ];
 
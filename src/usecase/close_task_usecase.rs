@@ -1,4 +1,4 @@
-use anyhow::Result;
+use crate::Result;
 use std::rc::Rc;
 
 use crate::domain::task::{ITaskRepository, ID};
@@ -44,6 +44,7 @@ impl CloseTaskUseCase {
 mod tests {
     use super::*;
     use crate::domain::task::Task;
+    use crate::domain::task_builder::TaskBuilder;
     use crate::infra::sqlite::task_repository::TaskRepository;
     use rusqlite::Connection;
 
@@ -68,7 +69,7 @@ mod tests {
             name: String,
         }
 
-        let given = Task::new("title".to_owned(), None, None);
+        let given = Task::new(TaskBuilder::new("title"));
 
         let table = [
             TestCase {
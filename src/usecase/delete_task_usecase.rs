@@ -0,0 +1,125 @@
+use crate::Result;
+use std::rc::Rc;
+
+use crate::domain::task::{ITaskRepository, TaskOrder, ID};
+use crate::usecase::error::UseCaseError;
+
+/// DTO for input of DeleteTaskUseCase.
+#[derive(Debug)]
+pub struct DeleteTaskUseCaseInput {
+    pub id: i64,
+}
+
+/// Usecase to delete a task.
+pub struct DeleteTaskUseCase {
+    task_repository: Rc<dyn ITaskRepository>,
+}
+
+impl DeleteTaskUseCase {
+    /// construct DeleteTaskUseCase with ITaskRepository.
+    pub fn new(task_repository: Rc<dyn ITaskRepository>) -> Self {
+        DeleteTaskUseCase { task_repository }
+    }
+
+    /// execute deleting a task. The task is kept, so that `undo` restores it.
+    pub fn execute(&self, input: DeleteTaskUseCaseInput) -> Result<ID> {
+        let mut t = self
+            .task_repository
+            .find_by_id(ID::new(input.id))?
+            .ok_or(UseCaseError::NotFound(input.id))?;
+        let id = t.id();
+
+        t.delete();
+        self.task_repository.update(t)?;
+
+        Ok(id)
+    }
+}
+
+/// DTO for input of ArchiveTaskUseCase.
+#[derive(Debug)]
+pub struct ArchiveTaskUseCaseInput {
+    /// updated_before is `YYYY-MM-DD HH:MM:SS` in local time.
+    pub updated_before: String,
+}
+
+/// Usecase to archive old closed tasks.
+pub struct ArchiveTaskUseCase {
+    task_repository: Rc<dyn ITaskRepository>,
+}
+
+impl ArchiveTaskUseCase {
+    /// construct ArchiveTaskUseCase with ITaskRepository.
+    pub fn new(task_repository: Rc<dyn ITaskRepository>) -> Self {
+        ArchiveTaskUseCase { task_repository }
+    }
+
+    /// execute deleting closed tasks last updated before the time, and returns their ids.
+    /// This model does not keep when a task was closed, so that the last update stands for it.
+    pub fn execute(&self, input: ArchiveTaskUseCaseInput) -> Result<Vec<ID>> {
+        let mut ids = vec![];
+        for mut t in self.task_repository.fetch_all(TaskOrder::Id)? {
+            // NOTE: timestamps are compared as strings, which sort in time order in their format.
+            if !t.is_closed() || t.updated_at() >= input.updated_before.as_str() {
+                continue;
+            }
+            ids.push(t.id());
+            t.delete();
+            self.task_repository.update(t)?;
+        }
+
+        Ok(ids)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::task::Task;
+    use crate::domain::task_builder::TaskBuilder;
+    use crate::infra::memory::task_repository::TaskRepository;
+
+    #[test]
+    fn test_execute() {
+        let task_repository = Rc::new(TaskRepository::new());
+        for title in ["open", "closed"] {
+            task_repository
+                .add(Task::new(TaskBuilder::new(title)))
+                .unwrap();
+        }
+        let mut closed = task_repository.find_by_id(ID::new(2)).unwrap().unwrap();
+        closed.close();
+        task_repository.update(closed).unwrap();
+
+        let delete_task_usecase = DeleteTaskUseCase::new(Rc::clone(&task_repository) as _);
+        let archive_task_usecase = ArchiveTaskUseCase::new(Rc::clone(&task_repository) as _);
+
+        // NOTE: the memory repository keeps timestamps empty, which are before any time.
+        let archived = archive_task_usecase
+            .execute(ArchiveTaskUseCaseInput {
+                updated_before: "2024-05-01 00:00:00".to_owned(),
+            })
+            .unwrap();
+        assert_eq!(archived, vec![ID::new(2)]);
+
+        delete_task_usecase
+            .execute(DeleteTaskUseCaseInput { id: 1 })
+            .unwrap();
+        assert!(delete_task_usecase
+            .execute(DeleteTaskUseCaseInput { id: 1 })
+            .is_err());
+        assert!(task_repository.fetch_all(TaskOrder::Id).unwrap().is_empty());
+        assert_eq!(
+            task_repository
+                .find_deleted(TaskOrder::Id)
+                .unwrap()
+                .iter()
+                .map(|t| t.title().to_owned())
+                .collect::<Vec<_>>(),
+            vec!["open", "closed"]
+        );
+
+        task_repository.undo().unwrap();
+        assert_eq!(task_repository.fetch_all(TaskOrder::Id).unwrap().len(), 1);
+    }
+}
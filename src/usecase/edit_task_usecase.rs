@@ -1,4 +1,4 @@
-use anyhow::Result;
+use crate::Result;
 use std::rc::Rc;
 
 use crate::domain::task::{Cost, ITaskRepository, Priority, ID};
@@ -59,6 +59,7 @@ mod tests {
 
     use super::*;
     use crate::domain::task::Task;
+    use crate::domain::task_builder::TaskBuilder;
     use crate::infra::sqlite::task_repository::TaskRepository;
     use rusqlite::Connection;
 
@@ -78,14 +79,17 @@ mod tests {
         }
 
         let given = vec![
-            Task::new("title".to_owned(), None, None),
+            Task::new(TaskBuilder::new("title")),
             Task::from_repository(
                 ID::new(2),
                 "closed".to_owned(),
                 true,
+                false,
                 Priority::new(10),
                 Cost::new(10),
                 Duration::from_secs(0),
+                String::new(),
+                String::new(),
             ),
         ];
 
@@ -101,9 +105,7 @@ mod tests {
                     },
                 },
                 want: Some(Task::new(
-                    "title1".to_owned(),
-                    Some(Priority::new(100)),
-                    Some(Cost::new(200)),
+                    TaskBuilder::new("title1").priority(100).cost(200),
                 )),
                 want_error: None,
             },
@@ -118,9 +120,7 @@ mod tests {
                     },
                 },
                 want: Some(Task::new(
-                    "title1".to_owned(),
-                    Some(Priority::new(100)),
-                    Some(Cost::new(200)),
+                    TaskBuilder::new("title1").priority(100).cost(200),
                 )),
                 want_error: None,
             },
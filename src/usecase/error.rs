@@ -11,6 +11,22 @@ pub enum UseCaseError {
     NotFound(i64),
     #[error("the task for id `{0}` has already been closed")]
     AlreadyClosed(i64),
+    #[error("the task for id `{0}` is locked, unlock it to change it")]
+    Locked(i64),
+    #[error("the task for id `{0}` is not in the inbox")]
+    NotInInbox(i64),
+    #[error("the task for id `{0}` is not deferred to someday")]
+    NotSomeday(i64),
+    #[error("the task for id `{0}` has open subtasks, close them first or together")]
+    OpenChildren(i64),
+    #[error("the sprint `{0}` is not found")]
+    SprintNotFound(String),
+    #[error("the sprint `{0}` already exists")]
+    SprintAlreadyExists(String),
+    #[error("no interrupted import of the file to resume")]
+    NothingToResume,
+    #[error("the day `{0}` has already passed, simulate until today or later")]
+    PastDay(String),
 }
 
 #[cfg(test)]
@@ -1,37 +1,123 @@
-use anyhow::Result;
+use crate::Result;
 
-use crate::ddd::component::{AggregateID, Repository};
+use crate::ddd::component::{AggregateID, AggregateRoot};
 use crate::domain::es_task::{
-    Cost, IESTaskRepository, IESTaskRepositoryComponent, Priority, SequentialID, Task, TaskSource,
+    Cost, DueDate, HabitTarget, IESTaskRepository, IESTaskRepositoryComponent, Priority,
+    SequentialID, Tag, Task, TaskCommand, TaskSource, Urgency,
 };
+use crate::domain::task_builder::TaskBuilder;
+use crate::usecase::error::UseCaseError;
+use crate::usecase::es_rules_usecase::Rules;
 
 /// DTO for input of AddTaskUseCase.
 #[derive(Debug)]
 pub struct AddTaskUseCaseInput {
     pub title: String,
+    /// description is the detail of the task, which may span lines.
+    pub description: Option<String>,
     pub priority: Option<i32>,
+    pub urgency: Option<i32>,
     pub cost: Option<i32>,
+    pub tags: Vec<String>,
+    /// private hides the task unless it is asked for explicitly.
+    pub private: bool,
+    /// habit makes the task a habit aiming at the target.
+    pub habit: Option<HabitTarget>,
+    pub due: Option<DueDate>,
+    /// someday defers the task out of the active backlog.
+    pub someday: bool,
+    /// parent makes the task a subtask of the open task.
+    pub parent: Option<SequentialID>,
+    /// rules tag and raise the priority of the task before it is saved.
+    pub rules: Rules,
+}
+
+impl From<TaskBuilder> for AddTaskUseCaseInput {
+    fn from(builder: TaskBuilder) -> Self {
+        AddTaskUseCaseInput {
+            title: builder.title,
+            description: None,
+            priority: builder.priority,
+            urgency: builder.urgency,
+            cost: builder.cost,
+            tags: builder.tags,
+            private: builder.private,
+            habit: None,
+            due: builder.due.map(DueDate::new),
+            someday: false,
+            parent: None,
+            rules: Rules::default(),
+        }
+    }
 }
 
 /// Usecase to add a task.
 pub trait AddTaskUseCase: IESTaskRepositoryComponent {
-    /// execute addition a task.
+    /// execute addition a task. A subtask can not be added to a closed parent.
     fn execute(&self, input: AddTaskUseCaseInput) -> Result<SequentialID> {
         let p: Option<Priority> = input.priority.map(Priority::new);
         let c: Option<Cost> = input.cost.map(Cost::new);
+        let tags = input
+            .tags
+            .iter()
+            .map(|t| Tag::new(t))
+            .collect::<Result<Vec<Tag>, _>>()?;
+        if let Some(parent) = input.parent {
+            let parent = self
+                .repository()
+                .load_by_sequential_id(parent)?
+                .ok_or(UseCaseError::NotFound(parent.to_i64()))?;
+            if parent.is_closed() {
+                return Err(UseCaseError::AlreadyClosed(parent.sequential_id().to_i64()).into());
+            }
+        }
 
         let aggregate_id = AggregateID::new();
-        let sequential_id = self.repository().issue_sequential_id(aggregate_id)?;
-
-        let mut t = Task::create(TaskSource {
-            aggregate_id,
-            sequential_id,
-            title: input.title,
-            priority: p,
-            cost: c,
-        });
-
-        self.repository().save(&mut t)?;
+        let t = self.repository().save_new(aggregate_id, &|sequential_id| {
+            let mut task = Task::create(TaskSource {
+                aggregate_id,
+                sequential_id,
+                title: input.title.clone(),
+                priority: p,
+                cost: c,
+                tags: tags.clone(),
+            });
+            if let Some(urgency) = input.urgency {
+                task.rescore_urgency(Urgency::new(urgency));
+            }
+            if input.description.is_some() {
+                task.execute(TaskCommand::EditDescription {
+                    description: input.description.clone(),
+                })
+                .expect("a new task can have a description");
+            }
+            if input.private {
+                task.make_private();
+            }
+            if let Some(target) = input.habit {
+                // NOTE: a new task is open, so that it can always be a habit.
+                task.execute(TaskCommand::MakeHabit { target })
+                    .expect("an open task can be a habit");
+            }
+            if let Some(due) = input.due {
+                task.execute(TaskCommand::SetDueDate { due: Some(due) })
+                    .expect("an open task can have a due date");
+            }
+            if input.someday {
+                task.execute(TaskCommand::Defer)
+                    .expect("an open task can be deferred");
+            }
+            if let Some(parent) = input.parent {
+                task.execute(TaskCommand::AssignParent { parent })
+                    .expect("a new task can be a subtask");
+            }
+            // NOTE: rules only tag and rescore, which never fail on an open task.
+            input
+                .rules
+                .apply(&mut task)
+                .expect("rules can apply to an open task");
+            task
+        })?;
 
         Ok(t.sequential_id())
     }
@@ -90,7 +176,16 @@ mod tests {
                     input: AddTaskUseCaseInput {
                         title: String::from("title1"),
                         priority: Some(100),
+                        urgency: None,
                         cost: Some(200),
+                        tags: vec![String::from("work")],
+                        private: false,
+                        habit: None,
+                        due: None,
+                        someday: false,
+                        parent: None,
+                        rules: Default::default(),
+                        description: None,
                     },
                 },
                 want: Task::create(TaskSource {
@@ -99,6 +194,7 @@ mod tests {
                     title: "title1".to_owned(),
                     priority: Some(Priority::new(100)),
                     cost: Some(Cost::new(200)),
+                    tags: vec![Tag::new("work").unwrap()],
                 }),
             },
             TestCase {
@@ -107,7 +203,16 @@ mod tests {
                     input: AddTaskUseCaseInput {
                         title: String::from("title2"),
                         priority: None,
+                        urgency: None,
                         cost: None,
+                        tags: vec![],
+                        private: false,
+                        habit: None,
+                        due: None,
+                        someday: false,
+                        parent: None,
+                        rules: Default::default(),
+                        description: None,
                     },
                 },
                 want: Task::create(TaskSource {
@@ -116,6 +221,25 @@ mod tests {
                     title: "title2".to_owned(),
                     priority: Some(Priority::new(10)),
                     cost: Some(Cost::new(10)),
+                    tags: vec![],
+                }),
+            },
+            TestCase {
+                name: String::from("normal: from the builder"),
+                args: Args {
+                    input: TaskBuilder::new("title3")
+                        .cost(3)
+                        .tag("home")
+                        .private()
+                        .into(),
+                },
+                want: Task::create(TaskSource {
+                    aggregate_id: AggregateID::new(),
+                    sequential_id: SequentialID::new(10),
+                    title: "title3".to_owned(),
+                    priority: Some(Priority::new(10)),
+                    cost: Some(Cost::new(3)),
+                    tags: vec![Tag::new("home").unwrap()],
                 }),
             },
         ];
@@ -155,6 +279,13 @@ mod tests {
                 "Failed in the \"{}\".",
                 test_case.name,
             );
+
+            assert_eq!(
+                got.tags(),
+                test_case.want.tags(),
+                "Failed in the \"{}\".",
+                test_case.name,
+            );
         }
     }
 }
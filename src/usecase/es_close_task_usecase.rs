@@ -1,8 +1,9 @@
-use anyhow::Result;
+use crate::Result;
+use serde::{Deserialize, Serialize};
 
-use crate::ddd::component::{AggregateRoot, Repository};
+use crate::ddd::component::{AggregateID, AggregateRoot};
 use crate::domain::es_task::{
-    IESTaskRepository, IESTaskRepositoryComponent, SequentialID, TaskCommand,
+    IESTaskRepository, IESTaskRepositoryComponent, SequentialID, Task, TaskCommand, TaskSource,
 };
 use crate::usecase::error::UseCaseError;
 
@@ -10,25 +11,49 @@ use crate::usecase::error::UseCaseError;
 #[derive(Debug)]
 pub struct CloseTaskUseCaseInput {
     pub sequential_id: SequentialID,
+    /// unlock unlocks the task if it is locked, otherwise a locked task is not closed.
+    pub unlock: bool,
+    /// cascade closes open subtasks together, otherwise a task with them is not closed.
+    pub cascade: bool,
+}
+
+/// DTO for input of FollowUpUseCase.
+#[derive(Debug)]
+pub struct FollowUpUseCaseInput {
+    pub sequential_id: SequentialID,
+    pub unlock: bool,
+    /// title of the follow-up task.
+    pub title: String,
+}
+
+/// DTO of a task closed with its follow-up.
+#[derive(Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FollowUpDTO {
+    pub closed: SequentialID,
+    pub follow_up: SequentialID,
 }
 
 /// Usecase to close a task.
 pub trait CloseTaskUseCase: IESTaskRepositoryComponent {
-    /// execute closing a task.
+    /// execute closing a task, and its open subtasks at any depth in one transaction if cascade.
     fn execute(&self, input: CloseTaskUseCaseInput) -> Result<SequentialID> {
         let mut task = self
             .repository()
             .load_by_sequential_id(input.sequential_id)?
             .ok_or(UseCaseError::NotFound(input.sequential_id.to_i64()))?;
 
-        if task.is_closed() {
-            return Err(UseCaseError::AlreadyClosed(task.sequential_id().to_i64()).into());
-        }
-
-        task.execute(TaskCommand::Close)?;
+        close(&mut task, input.unlock)?;
+        let sequential_id = task.sequential_id();
+        let mut tasks = close_children(
+            self.repository(),
+            sequential_id,
+            input.unlock,
+            input.cascade,
+        )?;
+        tasks.push(task);
 
-        self.repository().save(&mut task)?;
-        Ok(task.sequential_id())
+        self.repository().save_all(&mut tasks)?;
+        Ok(sequential_id)
     }
 }
 
@@ -40,6 +65,110 @@ pub trait CloseTaskUseCaseComponent {
     fn close_task_usecase(&self) -> &Self::CloseTaskUseCase;
 }
 
+/// Usecase to close a task and add the next step following it up.
+pub trait FollowUpUseCase: IESTaskRepositoryComponent {
+    /// execute closing the task and adding the follow-up in one transaction.
+    /// The follow-up takes over the tags and the privacy of the closed task.
+    fn execute(&self, input: FollowUpUseCaseInput) -> Result<FollowUpDTO> {
+        let mut task = self
+            .repository()
+            .load_by_sequential_id(input.sequential_id)?
+            .ok_or(UseCaseError::NotFound(input.sequential_id.to_i64()))?;
+
+        close(&mut task, input.unlock)?;
+        close_children(self.repository(), task.sequential_id(), input.unlock, false)?;
+
+        let predecessor = task.sequential_id();
+        let tags = task.tags().to_vec();
+        let private = task.is_private();
+        let aggregate_id = AggregateID::new();
+        let follow_up = self.repository().save_new_with(
+            aggregate_id,
+            &|sequential_id| {
+                let mut follow_up = Task::create(TaskSource {
+                    aggregate_id,
+                    sequential_id,
+                    title: input.title.clone(),
+                    priority: None,
+                    cost: None,
+                    tags: tags.clone(),
+                });
+                if private {
+                    follow_up.make_private();
+                }
+                // NOTE: FollowUp never fails, it only records the link.
+                follow_up
+                    .execute(TaskCommand::FollowUp { predecessor })
+                    .expect("a new task can follow up another");
+                follow_up
+            },
+            std::slice::from_mut(&mut task),
+        )?;
+
+        Ok(FollowUpDTO {
+            closed: predecessor,
+            follow_up: follow_up.sequential_id(),
+        })
+    }
+}
+
+impl<T: IESTaskRepositoryComponent> FollowUpUseCase for T {}
+
+/// FollowUpUseCaseComponent returns FollowUpUseCase.
+pub trait FollowUpUseCaseComponent {
+    type FollowUpUseCase: FollowUpUseCase;
+    fn follow_up_usecase(&self) -> &Self::FollowUpUseCase;
+}
+
+/// close closes the task, unlocking it if `unlock` is given.
+fn close(task: &mut Task, unlock: bool) -> Result<()> {
+    if task.is_closed() {
+        return Err(UseCaseError::AlreadyClosed(task.sequential_id().to_i64()).into());
+    }
+
+    if task.is_locked() {
+        if !unlock {
+            return Err(UseCaseError::Locked(task.sequential_id().to_i64()).into());
+        }
+        task.execute(TaskCommand::Unlock)?;
+    }
+
+    task.execute(TaskCommand::Close)
+}
+
+/// close_children closes open descendants of the parent to be saved with it if cascade is given.
+/// Otherwise an open child refuses closing the parent.
+fn close_children<R: IESTaskRepository>(
+    repository: &R,
+    parent: SequentialID,
+    unlock: bool,
+    cascade: bool,
+) -> Result<Vec<Task>> {
+    if !cascade {
+        return match repository
+            .load_children(parent)?
+            .iter()
+            .any(|c| !c.is_closed())
+        {
+            true => Err(UseCaseError::OpenChildren(parent.to_i64()).into()),
+            false => Ok(vec![]),
+        };
+    }
+
+    let mut closed = vec![];
+    let mut parents = vec![parent];
+    while let Some(parent) = parents.pop() {
+        for mut child in repository.load_children(parent)? {
+            parents.push(child.sequential_id());
+            if !child.is_closed() {
+                close(&mut child, unlock)?;
+                closed.push(child);
+            }
+        }
+    }
+    Ok(closed)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -102,6 +231,8 @@ mod tests {
                 args: Args {
                     input: CloseTaskUseCaseInput {
                         sequential_id: SequentialID::new(1),
+                        unlock: false,
+                        cascade: false,
                     },
                 },
                 want: Some(Want {
@@ -115,6 +246,8 @@ mod tests {
                 args: Args {
                     input: CloseTaskUseCaseInput {
                         sequential_id: SequentialID::new(1),
+                        unlock: false,
+                        cascade: false,
                     },
                 },
                 want: None,
@@ -125,6 +258,8 @@ mod tests {
                 args: Args {
                     input: CloseTaskUseCaseInput {
                         sequential_id: SequentialID::new(2),
+                        unlock: false,
+                        cascade: false,
                     },
                 },
                 want: None,
@@ -139,11 +274,20 @@ mod tests {
         let add_task_usecase = close_task_usecase_component_impl.add_task_usecase();
 
         <CloseTaskUseCaseComponentImpl as AddTaskUseCase>::execute(
-            &add_task_usecase,
+            add_task_usecase,
             AddTaskUseCaseInput {
                 title: "title".to_owned(),
                 priority: None,
+                urgency: None,
                 cost: None,
+                tags: vec![],
+                private: false,
+                habit: None,
+                due: None,
+                someday: false,
+                parent: None,
+                rules: Default::default(),
+                description: None,
             },
         )
         .unwrap();
@@ -151,7 +295,7 @@ mod tests {
         let close_task_usecase = close_task_usecase_component_impl.close_task_usecase();
         for test_case in table {
             match <CloseTaskUseCaseComponentImpl as CloseTaskUseCase>::execute(
-                &close_task_usecase,
+                close_task_usecase,
                 test_case.args.input,
             ) {
                 Ok(sequential_id) => {
@@ -188,4 +332,148 @@ mod tests {
             };
         }
     }
+
+    #[test]
+    fn test_follow_up() {
+        struct FollowUpUseCaseComponentImpl {
+            task_repository: TaskRepository,
+        }
+
+        impl IESTaskRepositoryComponent for FollowUpUseCaseComponentImpl {
+            type Repository = TaskRepository;
+            fn repository(&self) -> &Self::Repository {
+                &self.task_repository
+            }
+        }
+
+        let task_repository = TaskRepository::new(Connection::open_in_memory().unwrap());
+        task_repository.create_table_if_not_exists().unwrap();
+        let component = FollowUpUseCaseComponentImpl { task_repository };
+
+        <FollowUpUseCaseComponentImpl as AddTaskUseCase>::execute(
+            &component,
+            AddTaskUseCaseInput {
+                title: "meet the client".to_owned(),
+                priority: None,
+                urgency: None,
+                cost: None,
+                tags: vec!["work".to_owned()],
+                private: true,
+                habit: None,
+                due: None,
+                someday: false,
+                parent: None,
+                rules: Default::default(),
+                description: None,
+            },
+        )
+        .unwrap();
+
+        let follow_up = |id: i64| {
+            <FollowUpUseCaseComponentImpl as FollowUpUseCase>::execute(
+                &component,
+                FollowUpUseCaseInput {
+                    sequential_id: SequentialID::new(id),
+                    unlock: false,
+                    title: "send summary".to_owned(),
+                },
+            )
+        };
+        assert_eq!(
+            follow_up(1).unwrap(),
+            FollowUpDTO {
+                closed: SequentialID::new(1),
+                follow_up: SequentialID::new(2),
+            }
+        );
+        assert_eq!(
+            follow_up(1).unwrap_err().to_string(),
+            UseCaseError::AlreadyClosed(1).to_string()
+        );
+
+        let load = |id: i64| {
+            component
+                .task_repository
+                .load_by_sequential_id(SequentialID::new(id))
+                .unwrap()
+                .unwrap()
+        };
+        assert!(load(1).is_closed());
+        let got = load(2);
+        assert_eq!(got.title(), "send summary");
+        assert_eq!(got.follows(), Some(SequentialID::new(1)));
+        assert_eq!(got.tags(), load(1).tags());
+        assert!(got.is_private());
+        assert!(!got.is_closed());
+    }
+
+    #[test]
+    fn test_cascade() {
+        struct CascadeUseCaseComponentImpl {
+            task_repository: TaskRepository,
+        }
+
+        impl IESTaskRepositoryComponent for CascadeUseCaseComponentImpl {
+            type Repository = TaskRepository;
+            fn repository(&self) -> &Self::Repository {
+                &self.task_repository
+            }
+        }
+
+        let task_repository = TaskRepository::new(Connection::open_in_memory().unwrap());
+        task_repository.create_table_if_not_exists().unwrap();
+        let component = CascadeUseCaseComponentImpl { task_repository };
+
+        // NOTE: 1 has 2, which has 3. 4 is not related.
+        for parent in [None, Some(1), Some(2), None] {
+            <CascadeUseCaseComponentImpl as AddTaskUseCase>::execute(
+                &component,
+                AddTaskUseCaseInput {
+                    title: "title".to_owned(),
+                    priority: None,
+                    urgency: None,
+                    cost: None,
+                    tags: vec![],
+                    private: false,
+                    habit: None,
+                    due: None,
+                    someday: false,
+                    parent: parent.map(SequentialID::new),
+                    rules: Default::default(),
+                    description: None,
+                },
+            )
+            .unwrap();
+        }
+
+        let close = |id, cascade| {
+            <CascadeUseCaseComponentImpl as CloseTaskUseCase>::execute(
+                &component,
+                CloseTaskUseCaseInput {
+                    sequential_id: SequentialID::new(id),
+                    unlock: false,
+                    cascade,
+                },
+            )
+        };
+        let is_closed = |id| {
+            component
+                .task_repository
+                .load_by_sequential_id(SequentialID::new(id))
+                .unwrap()
+                .unwrap()
+                .is_closed()
+        };
+
+        assert_eq!(
+            close(1, false).unwrap_err().to_string(),
+            UseCaseError::OpenChildren(1).to_string()
+        );
+        assert!(!is_closed(1));
+        close(1, true).unwrap();
+        assert_eq!(
+            (1..=4).map(is_closed).collect::<Vec<_>>(),
+            vec![true, true, true, false]
+        );
+    }
 }
@@ -0,0 +1,196 @@
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+use crate::Result;
+use chrono::NaiveDateTime;
+
+use crate::domain::es_task::{IESTaskRepository, IESTaskRepositoryComponent, Task};
+use crate::usecase::percentile::{nearest_rank, PERCENTILES};
+
+/// LEAD_TIME is the metric from Created to Closed.
+pub const LEAD_TIME: &str = "lead time";
+
+/// CYCLE_TIME is the metric from the start of work to Closed.
+pub const CYCLE_TIME: &str = "cycle time";
+
+/// DTO for input of CycleTimeUseCase.
+#[derive(Debug, Default)]
+pub struct CycleTimeUseCaseInput {
+    /// since in UTC. Tasks closed before it are left out. All closed tasks count if None.
+    pub since: Option<NaiveDateTime>,
+}
+
+/// DTO of the distribution of a metric over closed tasks.
+#[derive(Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FlowTimeDTO {
+    pub metric: String,
+    /// tasks is the number of tasks measured.
+    pub tasks: usize,
+    /// percentiles are durations at PERCENTILES in order, or empty if no task is measured.
+    pub percentiles: Vec<Duration>,
+}
+
+/// Usecase to measure lead time and cycle time of closed tasks.
+pub trait CycleTimeUseCase: IESTaskRepositoryComponent {
+    /// execute measuring lead time and cycle time, in this order.
+    /// Tasks have no statuses yet, so that work is taken to start when the first logged time
+    /// began. Tasks without logged time have only lead time.
+    /// Habits, which are never closed, and private tasks are left out.
+    fn execute(&self, input: CycleTimeUseCaseInput) -> Result<Vec<FlowTimeDTO>> {
+        let mut lead_times = vec![];
+        let mut cycle_times = vec![];
+        self.repository().for_each_task(&mut |task| {
+            let Some(closed_at) = task.closed_at() else {
+                return Ok(());
+            };
+            if task.is_private() || input.since.is_some_and(|since| closed_at < since) {
+                return Ok(());
+            }
+            lead_times.push(elapsed(task.created_at(), closed_at));
+            if let Some(started_at) = started_at(&task) {
+                cycle_times.push(elapsed(started_at, closed_at));
+            }
+            Ok(())
+        })?;
+
+        Ok([(LEAD_TIME, lead_times), (CYCLE_TIME, cycle_times)]
+            .into_iter()
+            .map(|(metric, mut times)| {
+                times.sort();
+                FlowTimeDTO {
+                    metric: metric.to_owned(),
+                    tasks: times.len(),
+                    percentiles: PERCENTILES
+                        .iter()
+                        .filter_map(|&percent| nearest_rank(&times, percent))
+                        .collect(),
+                }
+            })
+            .collect())
+    }
+}
+
+impl<T: IESTaskRepositoryComponent> CycleTimeUseCase for T {}
+
+/// CycleTimeUseCaseComponent returns CycleTimeUseCase.
+pub trait CycleTimeUseCaseComponent {
+    type CycleTimeUseCase: CycleTimeUseCase;
+    fn cycle_time_usecase(&self) -> &Self::CycleTimeUseCase;
+}
+
+/// started_at is when the first logged time began, but not before the task was created.
+fn started_at(task: &Task) -> Option<NaiveDateTime> {
+    let log = task.time_logs().first()?;
+    let duration = chrono::Duration::from_std(log.duration).unwrap_or(chrono::Duration::MAX);
+    let began = log.logged_on.checked_sub_signed(duration)?;
+    Some(began.max(task.created_at()))
+}
+
+/// elapsed is the duration from start to end, or zero if end is before start.
+fn elapsed(start: NaiveDateTime, end: NaiveDateTime) -> Duration {
+    (end - start).to_std().unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ddd::component::{AggregateID, AggregateRoot};
+    use crate::domain::es_task::{SequentialID, TaskCommand, TaskSource};
+    use crate::infra::sqlite::es_task_repository::TaskRepository;
+    use rusqlite::Connection;
+
+    struct CycleTimeUseCaseComponentImpl {
+        task_repository: TaskRepository,
+    }
+
+    impl IESTaskRepositoryComponent for CycleTimeUseCaseComponentImpl {
+        type Repository = TaskRepository;
+        fn repository(&self) -> &Self::Repository {
+            &self.task_repository
+        }
+    }
+
+    fn utc(s: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M:%S").unwrap()
+    }
+
+    #[test]
+    fn test_execute() {
+        let task_repository = TaskRepository::new(Connection::open_in_memory().unwrap());
+        task_repository.create_table_if_not_exists().unwrap();
+        let component = CycleTimeUseCaseComponentImpl { task_repository };
+
+        // NOTE: (created at, hours logged at the day after, closed at)
+        let tasks = [
+            ("2024-05-01 09:00:00", Some(2), Some("2024-05-03 09:00:00")),
+            ("2024-05-01 09:00:00", None, Some("2024-05-05 09:00:00")),
+            ("2024-05-01 09:00:00", Some(1), Some("2024-05-11 09:00:00")),
+            ("2024-05-01 09:00:00", Some(1), None),
+        ];
+        for (created_at, hours, closed_at) in tasks {
+            let aggregate_id = AggregateID::new();
+            component
+                .task_repository
+                .save_new(aggregate_id, &|sequential_id: SequentialID| {
+                    let mut task = Task::import(
+                        TaskSource {
+                            aggregate_id,
+                            sequential_id,
+                            title: String::from("task"),
+                            priority: None,
+                            cost: None,
+                            tags: vec![],
+                        },
+                        utc(created_at),
+                        None,
+                    );
+                    if let Some(hours) = hours {
+                        task.execute(TaskCommand::LogTime {
+                            duration: Duration::from_secs(hours * 3600),
+                        })
+                        .unwrap();
+                        task.backdate_last_event(utc("2024-05-02 12:00:00"));
+                    }
+                    if let Some(closed_at) = closed_at {
+                        task.execute(TaskCommand::Close).unwrap();
+                        task.backdate_last_event(utc(closed_at));
+                    }
+                    task
+                })
+                .unwrap();
+        }
+
+        let days = |d: u64| Duration::from_secs(d * 24 * 3600);
+        let hours = |h: u64| Duration::from_secs(h * 3600);
+        let got = <CycleTimeUseCaseComponentImpl as CycleTimeUseCase>::execute(
+            &component,
+            Default::default(),
+        )
+        .unwrap();
+        assert_eq!(
+            got,
+            vec![
+                FlowTimeDTO {
+                    metric: LEAD_TIME.to_owned(),
+                    tasks: 3,
+                    percentiles: vec![days(4), days(10), days(10)],
+                },
+                FlowTimeDTO {
+                    metric: CYCLE_TIME.to_owned(),
+                    tasks: 2,
+                    percentiles: vec![hours(23), days(9) - hours(2), days(9) - hours(2)],
+                },
+            ]
+        );
+
+        let got = <CycleTimeUseCaseComponentImpl as CycleTimeUseCase>::execute(
+            &component,
+            CycleTimeUseCaseInput {
+                since: Some(utc("2024-05-04 00:00:00")),
+            },
+        )
+        .unwrap();
+        assert_eq!(got[0].tasks, 2);
+        assert_eq!(got[1].tasks, 1);
+    }
+}
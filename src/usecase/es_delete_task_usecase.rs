@@ -0,0 +1,263 @@
+use crate::Result;
+use chrono::NaiveDateTime;
+
+use crate::ddd::component::{AggregateRoot, Repository};
+use crate::domain::es_task::{
+    IESTaskRepository, IESTaskRepositoryComponent, SequentialID, TaskCommand,
+};
+use crate::usecase::error::UseCaseError;
+
+/// DTO for input of DeleteTaskUseCase.
+#[derive(Debug)]
+pub struct DeleteTaskUseCaseInput {
+    pub sequential_id: SequentialID,
+}
+
+/// DTO for input of ArchiveUseCase.
+#[derive(Debug)]
+pub struct ArchiveUseCaseInput {
+    /// closed_before in UTC. Tasks closed before it are archived.
+    pub closed_before: NaiveDateTime,
+}
+
+/// Usecase to delete a task.
+pub trait DeleteTaskUseCase: IESTaskRepositoryComponent {
+    /// execute deleting a task. Its events are kept, so that `list --include-deleted` shows it.
+    fn execute(&self, input: DeleteTaskUseCaseInput) -> Result<SequentialID> {
+        let mut task = self
+            .repository()
+            .load_by_sequential_id(input.sequential_id)?
+            .ok_or(UseCaseError::NotFound(input.sequential_id.to_i64()))?;
+        if task.is_locked() {
+            return Err(UseCaseError::Locked(input.sequential_id.to_i64()).into());
+        }
+
+        task.execute(TaskCommand::Delete)?;
+        self.repository().save(&mut task)?;
+        // NOTE: the id is released after the deletion is saved, so that a failure only leaves
+        // the id held, which is not given out again under any policy.
+        self.repository()
+            .release_sequential_ids(&[input.sequential_id])?;
+        Ok(input.sequential_id)
+    }
+}
+
+impl<T: IESTaskRepositoryComponent> DeleteTaskUseCase for T {}
+
+/// DeleteTaskUseCaseComponent returns DeleteTaskUseCase.
+pub trait DeleteTaskUseCaseComponent {
+    type DeleteTaskUseCase: DeleteTaskUseCase;
+    fn delete_task_usecase(&self) -> &Self::DeleteTaskUseCase;
+}
+
+/// Usecase to archive old closed tasks.
+pub trait ArchiveUseCase: IESTaskRepositoryComponent {
+    /// execute archiving tasks closed before the time in one transaction, and returns their ids.
+    /// Locked tasks are left out, since they are kept as they are on purpose.
+    fn execute(&self, input: ArchiveUseCaseInput) -> Result<Vec<SequentialID>> {
+        let mut tasks = vec![];
+        self.repository().for_each_task(&mut |mut task| {
+            if task.is_locked() || task.closed_at().is_none_or(|c| c >= input.closed_before) {
+                return Ok(());
+            }
+            task.execute(TaskCommand::Archive)?;
+            tasks.push(task);
+            Ok(())
+        })?;
+
+        self.repository().save_all(&mut tasks)?;
+        let ids: Vec<SequentialID> = tasks.iter().map(|t| t.sequential_id()).collect();
+        self.repository().release_sequential_ids(&ids)?;
+        Ok(ids)
+    }
+}
+
+impl<T: IESTaskRepositoryComponent> ArchiveUseCase for T {}
+
+/// ArchiveUseCaseComponent returns ArchiveUseCase.
+pub trait ArchiveUseCaseComponent {
+    type ArchiveUseCase: ArchiveUseCase;
+    fn archive_usecase(&self) -> &Self::ArchiveUseCase;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::infra::config::IdPolicy;
+    use crate::infra::sqlite::es_task_repository::TaskRepository;
+    use crate::usecase::es_add_task_usecase::{AddTaskUseCase, AddTaskUseCaseInput};
+    use chrono::{Duration, Utc};
+    use rusqlite::Connection;
+
+    struct DeleteTaskUseCaseComponentImpl {
+        task_repository: TaskRepository,
+    }
+
+    impl IESTaskRepositoryComponent for DeleteTaskUseCaseComponentImpl {
+        type Repository = TaskRepository;
+        fn repository(&self) -> &Self::Repository {
+            &self.task_repository
+        }
+    }
+
+    #[test]
+    fn test_execute() {
+        let task_repository = TaskRepository::new(Connection::open_in_memory().unwrap());
+        task_repository.create_table_if_not_exists().unwrap();
+        let component = DeleteTaskUseCaseComponentImpl { task_repository };
+
+        for title in ["open", "closed", "locked", "deleted"] {
+            <DeleteTaskUseCaseComponentImpl as AddTaskUseCase>::execute(
+                &component,
+                AddTaskUseCaseInput {
+                    title: title.to_owned(),
+                    priority: None,
+                    urgency: None,
+                    cost: None,
+                    tags: vec![],
+                    private: false,
+                    habit: None,
+                    due: None,
+                    someday: false,
+                    parent: None,
+                    rules: Default::default(),
+                    description: None,
+                },
+            )
+            .unwrap();
+        }
+        for (id, command) in [
+            (2, TaskCommand::Close),
+            (3, TaskCommand::Close),
+            (3, TaskCommand::Lock),
+        ] {
+            let mut task = component
+                .repository()
+                .load_by_sequential_id(SequentialID::new(id))
+                .unwrap()
+                .unwrap();
+            task.execute(command).unwrap();
+            component.repository().save(&mut task).unwrap();
+        }
+
+        <DeleteTaskUseCaseComponentImpl as DeleteTaskUseCase>::execute(
+            &component,
+            DeleteTaskUseCaseInput {
+                sequential_id: SequentialID::new(4),
+            },
+        )
+        .unwrap();
+        for id in [3, 4] {
+            assert!(
+                <DeleteTaskUseCaseComponentImpl as DeleteTaskUseCase>::execute(
+                    &component,
+                    DeleteTaskUseCaseInput {
+                        sequential_id: SequentialID::new(id),
+                    },
+                )
+                .is_err(),
+                "the task {} is locked or deleted",
+                id
+            );
+        }
+
+        let archived = <DeleteTaskUseCaseComponentImpl as ArchiveUseCase>::execute(
+            &component,
+            ArchiveUseCaseInput {
+                closed_before: Utc::now().naive_utc() + Duration::minutes(1),
+            },
+        )
+        .unwrap();
+        assert_eq!(archived, vec![SequentialID::new(2)]);
+
+        let mut ids = vec![];
+        component
+            .repository()
+            .for_each_task(&mut |t| {
+                ids.push(t.sequential_id().to_i64());
+                Ok(())
+            })
+            .unwrap();
+        assert_eq!(ids, vec![1, 3]);
+        let archived = component
+            .repository()
+            .load_any_by_sequential_id(SequentialID::new(2))
+            .unwrap()
+            .unwrap();
+        assert!(archived.is_archived() && archived.is_deleted());
+    }
+
+    #[test]
+    fn test_execute_with_reuse() {
+        let task_repository = TaskRepository::new(Connection::open_in_memory().unwrap())
+            .with_id_policy(IdPolicy::Reuse);
+        task_repository.create_table_if_not_exists().unwrap();
+        let component = DeleteTaskUseCaseComponentImpl { task_repository };
+        let add = |title: &str| {
+            <DeleteTaskUseCaseComponentImpl as AddTaskUseCase>::execute(
+                &component,
+                AddTaskUseCaseInput {
+                    title: title.to_owned(),
+                    priority: None,
+                    urgency: None,
+                    cost: None,
+                    tags: vec![],
+                    private: false,
+                    habit: None,
+                    due: None,
+                    someday: false,
+                    parent: None,
+                    rules: Default::default(),
+                    description: None,
+                },
+            )
+            .unwrap()
+        };
+
+        for title in ["closed", "deleted", "open"] {
+            add(title);
+        }
+        let mut task = component
+            .repository()
+            .load_by_sequential_id(SequentialID::new(1))
+            .unwrap()
+            .unwrap();
+        task.execute(TaskCommand::Close).unwrap();
+        component.repository().save(&mut task).unwrap();
+
+        <DeleteTaskUseCaseComponentImpl as DeleteTaskUseCase>::execute(
+            &component,
+            DeleteTaskUseCaseInput {
+                sequential_id: SequentialID::new(2),
+            },
+        )
+        .unwrap();
+        assert_eq!(add("after the deletion"), SequentialID::new(2));
+
+        <DeleteTaskUseCaseComponentImpl as ArchiveUseCase>::execute(
+            &component,
+            ArchiveUseCaseInput {
+                closed_before: Utc::now().naive_utc() + Duration::minutes(1),
+            },
+        )
+        .unwrap();
+        assert_eq!(add("after the archive"), SequentialID::new(1));
+
+        let mut titles = vec![];
+        component
+            .repository()
+            .for_each_task(&mut |t| {
+                titles.push((t.sequential_id().to_i64(), t.title().to_owned()));
+                Ok(())
+            })
+            .unwrap();
+        assert_eq!(
+            titles,
+            vec![
+                (1, String::from("after the archive")),
+                (2, String::from("after the deletion")),
+                (3, String::from("open")),
+            ]
+        );
+    }
+}
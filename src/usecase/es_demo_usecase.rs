@@ -0,0 +1,156 @@
+use crate::Result;
+use chrono::{Days, NaiveDate};
+use std::time::Duration;
+
+use crate::ddd::component::AggregateRoot;
+use crate::domain::es_task::{
+    Cost, DueDate, HabitTarget, IESTaskRepository, IESTaskRepositoryComponent, Priority,
+    SequentialID, Tag, Task, TaskCommand, TaskSource, Urgency, WaitingOn,
+};
+
+/// SAMPLES are title, priority, urgency, cost and tags of sample tasks in the order of ids.
+/// Each shows a feature, which sample_commands gives to the task.
+const SAMPLES: &[(&str, i32, i32, i32, &[&str])] = &[
+    ("fix the flaky test", 25, 20, 2, &["work"]),
+    ("write the release notes", 20, 10, 3, &["work", "docs"]),
+    ("get the budget approved", 20, 15, 1, &["work"]),
+    ("plan the trip", 10, 5, 5, &["home"]),
+    ("book the flights", 15, 10, 1, &["home"]),
+    ("reserve the hotel", 10, 10, 1, &["home"]),
+    ("go running", 10, 10, 1, &["health", "energy:deep"]),
+    ("renew the passport", 15, 10, 2, &["errand"]),
+    ("learn to play the piano", 5, 0, 10, &[]),
+    ("call the dentist?", 10, 10, 10, &[]),
+    ("pay the electricity bill", 15, 20, 1, &["home"]),
+];
+
+/// DTO for input of SeedDemoUseCase.
+#[derive(Debug)]
+pub struct SeedDemoUseCaseInput {
+    /// today is the day on which due dates and follow-ups of samples are set.
+    pub today: NaiveDate,
+}
+
+/// Usecase to fill an empty event store with sample tasks showing features of taskmr.
+pub trait SeedDemoUseCase: IESTaskRepositoryComponent {
+    /// execute adding the sample tasks in one transaction and returns the number of them.
+    fn execute(&self, input: SeedDemoUseCaseInput) -> Result<usize> {
+        self.repository()
+            .save_new_all(SAMPLES.len(), &|index, aggregate_id, sequential_id| {
+                let (title, priority, urgency, cost, tags) = SAMPLES[index];
+                let mut task = Task::create(TaskSource {
+                    aggregate_id,
+                    sequential_id,
+                    title: title.to_owned(),
+                    priority: Some(Priority::new(priority)),
+                    cost: Some(Cost::new(cost)),
+                    tags: tags
+                        .iter()
+                        .map(|t| Tag::new(t).expect("sample tags are valid"))
+                        .collect(),
+                });
+                let commands = std::iter::once(TaskCommand::RescoreUrgency {
+                    urgency: Urgency::new(urgency),
+                })
+                .chain(sample_commands(index, input.today));
+                for command in commands {
+                    task.execute(command).expect("samples are valid");
+                }
+                task
+            })?;
+
+        Ok(SAMPLES.len())
+    }
+}
+
+impl<T: IESTaskRepositoryComponent> SeedDemoUseCase for T {}
+
+/// SeedDemoUseCaseComponent returns SeedDemoUseCase.
+pub trait SeedDemoUseCaseComponent {
+    type SeedDemoUseCase: SeedDemoUseCase;
+    fn seed_demo_usecase(&self) -> &Self::SeedDemoUseCase;
+}
+
+/// sample_commands returns commands which give the sample of the index its feature.
+fn sample_commands(index: usize, today: NaiveDate) -> Vec<TaskCommand> {
+    match SAMPLES[index].0 {
+        "fix the flaky test" => vec![TaskCommand::LogTime {
+            duration: Duration::from_secs(45 * 60),
+        }],
+        "get the budget approved" => vec![TaskCommand::WaitOn {
+            waiting_on: WaitingOn::new("bob", Some(today + Days::new(3)))
+                .expect("the person is not empty"),
+        }],
+        "book the flights" | "reserve the hotel" => vec![TaskCommand::AssignParent {
+            parent: SequentialID::new(4),
+        }],
+        "go running" => vec![
+            TaskCommand::MakeHabit {
+                target: "3/week"
+                    .parse::<HabitTarget>()
+                    .expect("the target is valid"),
+            },
+            TaskCommand::CheckIn,
+        ],
+        "renew the passport" => vec![TaskCommand::SetDueDate {
+            due: Some(DueDate::new(today + Days::new(7))),
+        }],
+        "learn to play the piano" => vec![TaskCommand::Defer],
+        "call the dentist?" => vec![TaskCommand::Capture],
+        "pay the electricity bill" => vec![
+            TaskCommand::LogTime {
+                duration: Duration::from_secs(10 * 60),
+            },
+            TaskCommand::Close,
+        ],
+        _ => vec![],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::infra::sqlite::es_task_repository::TaskRepository;
+    use rusqlite::Connection;
+
+    struct SeedDemoUseCaseComponentImpl {
+        task_repository: TaskRepository,
+    }
+
+    impl IESTaskRepositoryComponent for SeedDemoUseCaseComponentImpl {
+        type Repository = TaskRepository;
+        fn repository(&self) -> &Self::Repository {
+            &self.task_repository
+        }
+    }
+
+    #[test]
+    fn test_execute() {
+        let task_repository = TaskRepository::new(Connection::open_in_memory().unwrap());
+        task_repository.create_table_if_not_exists().unwrap();
+        let component = SeedDemoUseCaseComponentImpl { task_repository };
+
+        let today = NaiveDate::from_ymd_opt(2024, 5, 1).unwrap();
+        let count = <SeedDemoUseCaseComponentImpl as SeedDemoUseCase>::execute(
+            &component,
+            SeedDemoUseCaseInput { today },
+        )
+        .unwrap();
+        assert_eq!(count, SAMPLES.len());
+
+        let mut tasks = vec![];
+        component
+            .repository()
+            .for_each_task(&mut |t| {
+                tasks.push(t);
+                Ok(())
+            })
+            .unwrap();
+        assert_eq!(tasks.len(), SAMPLES.len());
+        for (task, sample) in tasks.iter().zip(SAMPLES) {
+            assert_eq!(task.title(), sample.0);
+        }
+        assert_eq!(tasks[4].parent(), Some(SequentialID::new(4)));
+        assert!(tasks[10].is_closed());
+    }
+}
@@ -1,8 +1,9 @@
-use anyhow::Result;
+use crate::Result;
 
 use crate::ddd::component::{AggregateRoot, Repository};
 use crate::domain::es_task::{
-    Cost, IESTaskRepository, IESTaskRepositoryComponent, Priority, SequentialID, TaskCommand,
+    Cost, DueDate, IESTaskRepository, IESTaskRepositoryComponent, Priority, SequentialID,
+    TaskCommand, Urgency,
 };
 use crate::usecase::error::UseCaseError;
 
@@ -11,8 +12,17 @@ use crate::usecase::error::UseCaseError;
 pub struct EditTaskUseCaseInput {
     pub sequential_id: SequentialID,
     pub title: Option<String>,
+    /// description sets the description if it is Some, and clears it if it is Some(None).
+    pub description: Option<Option<String>>,
     pub priority: Option<i32>,
+    pub urgency: Option<i32>,
     pub cost: Option<i32>,
+    /// unlock unlocks the task if it is locked, otherwise a locked task is not edited.
+    pub unlock: bool,
+    /// private makes the task private if true and public if false.
+    pub private: Option<bool>,
+    /// due sets the due date if it is Some, and clears it if it is Some(None).
+    pub due: Option<Option<DueDate>>,
 }
 
 /// Usecase to edit a task.
@@ -30,22 +40,49 @@ pub trait EditTaskUseCase: IESTaskRepositoryComponent {
             );
         }
 
+        if task.is_locked() {
+            if !input.unlock {
+                return Err(UseCaseError::Locked(task.sequential_id().to_i64()).into());
+            }
+            task.execute(TaskCommand::Unlock)?;
+        }
+
         if let Some(title) = input.title {
             task.execute(TaskCommand::EditTitle { title })?;
         }
 
+        if let Some(description) = input.description {
+            task.execute(TaskCommand::EditDescription { description })?;
+        }
+
         if let Some(priority) = input.priority {
             task.execute(TaskCommand::RescorePriority {
                 priority: Priority::new(priority),
             })?;
         }
 
+        if let Some(urgency) = input.urgency {
+            task.execute(TaskCommand::RescoreUrgency {
+                urgency: Urgency::new(urgency),
+            })?;
+        }
+
         if let Some(cost) = input.cost {
             task.execute(TaskCommand::RescoreCost {
                 cost: Cost::new(cost),
             })?;
         }
 
+        match input.private {
+            Some(true) => task.execute(TaskCommand::MakePrivate)?,
+            Some(false) => task.execute(TaskCommand::MakePublic)?,
+            None => {}
+        }
+
+        if let Some(due) = input.due {
+            task.execute(TaskCommand::SetDueDate { due })?;
+        }
+
         self.repository().save(&mut task)?;
         Ok(task.sequential_id())
     }
@@ -129,21 +166,39 @@ mod tests {
         let add_task_usecase = edit_task_usecase_component_impl.add_task_usecase();
 
         <EditTaskUseCaseComponentImpl as AddTaskUseCase>::execute(
-            &add_task_usecase,
+            add_task_usecase,
             AddTaskUseCaseInput {
                 title: "title".to_owned(),
                 priority: None,
+                urgency: None,
                 cost: None,
+                tags: vec![],
+                private: false,
+                habit: None,
+                due: None,
+                someday: false,
+                parent: None,
+                rules: Default::default(),
+                description: None,
             },
         )
         .unwrap();
 
         <EditTaskUseCaseComponentImpl as AddTaskUseCase>::execute(
-            &add_task_usecase,
+            add_task_usecase,
             AddTaskUseCaseInput {
                 title: "closed".to_owned(),
                 priority: None,
+                urgency: None,
                 cost: None,
+                tags: vec![],
+                private: false,
+                habit: None,
+                due: None,
+                someday: false,
+                parent: None,
+                rules: Default::default(),
+                description: None,
             },
         )
         .unwrap();
@@ -151,9 +206,11 @@ mod tests {
         let close_task_usecase = edit_task_usecase_component_impl.close_task_usecase();
 
         <EditTaskUseCaseComponentImpl as CloseTaskUseCase>::execute(
-            &close_task_usecase,
+            close_task_usecase,
             CloseTaskUseCaseInput {
                 sequential_id: SequentialID::new(2),
+                unlock: false,
+                cascade: false,
             },
         )
         .unwrap();
@@ -166,7 +223,12 @@ mod tests {
                         sequential_id: SequentialID::new(1),
                         title: Some(String::from("title1")),
                         priority: Some(100),
+                        urgency: None,
                         cost: Some(200),
+                        unlock: false,
+                        private: None,
+                        due: None,
+                        description: None,
                     },
                 },
                 want: Some(Task::create(TaskSource {
@@ -175,6 +237,7 @@ mod tests {
                     title: "title1".to_owned(),
                     priority: Some(Priority::new(100)),
                     cost: Some(Cost::new(200)),
+                    tags: vec![],
                 })),
                 want_error: None,
             },
@@ -185,7 +248,12 @@ mod tests {
                         sequential_id: SequentialID::new(1),
                         title: None,
                         priority: None,
+                        urgency: None,
                         cost: None,
+                        unlock: false,
+                        private: None,
+                        due: None,
+                        description: None,
                     },
                 },
                 want: Some(Task::create(TaskSource {
@@ -194,6 +262,7 @@ mod tests {
                     title: "title1".to_owned(),
                     priority: Some(Priority::new(100)),
                     cost: Some(Cost::new(200)),
+                    tags: vec![],
                 })),
                 want_error: None,
             },
@@ -204,7 +273,12 @@ mod tests {
                         sequential_id: SequentialID::new(3),
                         title: None,
                         priority: None,
+                        urgency: None,
                         cost: None,
+                        unlock: false,
+                        private: None,
+                        due: None,
+                        description: None,
                     },
                 },
                 want: None,
@@ -217,7 +291,12 @@ mod tests {
                         sequential_id: SequentialID::new(2),
                         title: None,
                         priority: None,
+                        urgency: None,
                         cost: None,
+                        unlock: false,
+                        private: None,
+                        due: None,
+                        description: None,
                     },
                 },
                 want: None,
@@ -228,7 +307,7 @@ mod tests {
         for test_case in table {
             let edit_task_usecase = edit_task_usecase_component_impl.edit_task_usecase();
             match <EditTaskUseCaseComponentImpl as EditTaskUseCase>::execute(
-                &edit_task_usecase,
+                edit_task_usecase,
                 test_case.args.input,
             ) {
                 Ok(id) => {
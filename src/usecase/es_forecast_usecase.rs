@@ -0,0 +1,225 @@
+use serde::{Deserialize, Serialize};
+
+use crate::Result;
+use chrono::{Duration, Local, NaiveDate, NaiveDateTime, TimeZone};
+
+use crate::domain::es_task::{IESTaskRepository, IESTaskRepositoryComponent, SequentialID};
+use crate::usecase::error::UseCaseError;
+use crate::usecase::percentile::{nearest_rank, PERCENTILES};
+
+/// DTO for input of ForecastUseCase.
+#[derive(Debug)]
+pub struct ForecastUseCaseInput {
+    pub sequential_ids: Vec<SequentialID>,
+    /// now in UTC. Tasks are forecast from now.
+    pub now: NaiveDateTime,
+    /// trials is the number of samples drawn for each task.
+    pub trials: usize,
+    /// seed makes the samples reproducible.
+    pub seed: u64,
+}
+
+/// DTO of a completion date which the percent of trials finish by.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PercentileDTO {
+    pub percent: u32,
+    pub finish_on: NaiveDate,
+}
+
+/// DTO of a forecast task.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ForecastTaskDTO {
+    pub id: i64,
+    pub title: String,
+    /// age_days is how many days the task has been open.
+    pub age_days: i64,
+    /// percentiles are empty if no task has been closed yet.
+    pub percentiles: Vec<PercentileDTO>,
+}
+
+/// DTO of the forecast.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ForecastDTO {
+    /// history is the number of closed tasks whose cycle times are sampled.
+    pub history: usize,
+    pub trials: usize,
+    pub tasks: Vec<ForecastTaskDTO>,
+}
+
+/// Usecase to forecast completion dates of open tasks by a Monte Carlo simulation.
+pub trait ForecastUseCase: IESTaskRepositoryComponent {
+    /// execute sampling cycle times, from Created to Closed, of closed tasks for each task.
+    /// A task open for some days is sampled among cycle times longer than that, so that old tasks
+    /// are not forecast to be done at once. If there is none, all cycle times count from now.
+    /// Habits are left out of the history since they are never closed once.
+    fn execute(&self, input: ForecastUseCaseInput) -> Result<ForecastDTO> {
+        let mut cycle_times = vec![];
+        let mut selected: Vec<Option<_>> = input.sequential_ids.iter().map(|_| None).collect();
+        self.repository().for_each_task(&mut |task| {
+            if let (Some(closed_at), None) = (task.closed_at(), task.habit()) {
+                cycle_times.push((closed_at - task.created_at()).max(Duration::zero()));
+            }
+            if let Some(i) = input
+                .sequential_ids
+                .iter()
+                .position(|id| *id == task.sequential_id())
+            {
+                selected[i] = Some(task);
+            }
+            Ok(())
+        })?;
+        cycle_times.sort();
+
+        let mut rng = fastrand::Rng::with_seed(input.seed);
+        let mut tasks = vec![];
+        for (sequential_id, task) in input.sequential_ids.iter().zip(selected) {
+            let task = task.ok_or(UseCaseError::NotFound(sequential_id.to_i64()))?;
+            if task.is_closed() {
+                return Err(UseCaseError::AlreadyClosed(sequential_id.to_i64()).into());
+            }
+
+            let age = (input.now - task.created_at()).max(Duration::zero());
+            let longer = cycle_times.partition_point(|c| *c <= age);
+            let (samples, elapsed) = match &cycle_times[longer..] {
+                [] => (&cycle_times[..], Duration::zero()),
+                samples => (samples, age),
+            };
+
+            let mut finishes: Vec<NaiveDate> = match samples {
+                [] => vec![],
+                _ => (0..input.trials)
+                    .map(|_| {
+                        let remaining = samples[rng.usize(..samples.len())] - elapsed;
+                        local_date(input.now + remaining)
+                    })
+                    .collect(),
+            };
+            finishes.sort();
+            let percentiles = PERCENTILES
+                .iter()
+                .filter_map(|&percent| {
+                    nearest_rank(&finishes, percent)
+                        .map(|finish_on| PercentileDTO { percent, finish_on })
+                })
+                .collect();
+
+            tasks.push(ForecastTaskDTO {
+                id: sequential_id.to_i64(),
+                title: task.title().to_owned(),
+                age_days: age.num_days(),
+                percentiles,
+            });
+        }
+
+        Ok(ForecastDTO {
+            history: cycle_times.len(),
+            trials: input.trials,
+            tasks,
+        })
+    }
+}
+
+impl<T: IESTaskRepositoryComponent> ForecastUseCase for T {}
+
+/// ForecastUseCaseComponent returns ForecastUseCase.
+pub trait ForecastUseCaseComponent {
+    type ForecastUseCase: ForecastUseCase;
+    fn forecast_usecase(&self) -> &Self::ForecastUseCase;
+}
+
+/// local_date converts the time in UTC into the day in local time.
+fn local_date(utc: NaiveDateTime) -> NaiveDate {
+    Local.from_utc_datetime(&utc).date_naive()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ddd::component::AggregateID;
+    use crate::domain::es_task::{Task, TaskSource};
+    use crate::infra::sqlite::es_task_repository::TaskRepository;
+    use rusqlite::Connection;
+
+    struct ForecastUseCaseComponentImpl {
+        task_repository: TaskRepository,
+    }
+
+    impl IESTaskRepositoryComponent for ForecastUseCaseComponentImpl {
+        type Repository = TaskRepository;
+        fn repository(&self) -> &Self::Repository {
+            &self.task_repository
+        }
+    }
+
+    fn local(s: &str) -> NaiveDateTime {
+        let local = NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M:%S").unwrap();
+        Local.from_local_datetime(&local).unwrap().naive_utc()
+    }
+
+    #[test]
+    fn test_execute() {
+        let task_repository = TaskRepository::new(Connection::open_in_memory().unwrap());
+        task_repository.create_table_if_not_exists().unwrap();
+        let component = ForecastUseCaseComponentImpl { task_repository };
+
+        // NOTE: closed tasks took 2 days and 10 days. The open tasks are 1 and 5 days old.
+        let tasks = [
+            ("2 days", "2024-05-01 09:00:00", Some("2024-05-03 09:00:00")),
+            (
+                "10 days",
+                "2024-05-01 09:00:00",
+                Some("2024-05-11 09:00:00"),
+            ),
+            ("new", "2024-05-31 09:00:00", None),
+            ("old", "2024-05-27 09:00:00", None),
+        ];
+        for (title, created_at, closed_at) in tasks {
+            let aggregate_id = AggregateID::new();
+            component
+                .task_repository
+                .save_new(aggregate_id, &|sequential_id| {
+                    Task::import(
+                        TaskSource {
+                            aggregate_id,
+                            sequential_id,
+                            title: title.to_owned(),
+                            priority: None,
+                            cost: None,
+                            tags: vec![],
+                        },
+                        local(created_at),
+                        closed_at.map(local),
+                    )
+                })
+                .unwrap();
+        }
+
+        let forecast = |ids: Vec<i64>| {
+            <ForecastUseCaseComponentImpl as ForecastUseCase>::execute(
+                &component,
+                ForecastUseCaseInput {
+                    sequential_ids: ids.into_iter().map(SequentialID::new).collect(),
+                    now: local("2024-06-01 09:00:00"),
+                    trials: 1000,
+                    seed: 1,
+                },
+            )
+        };
+
+        let got = forecast(vec![3, 4]).unwrap();
+        assert_eq!(got.history, 2);
+        let on = |day| NaiveDate::from_ymd_opt(2024, 6, day).unwrap();
+        // NOTE: the new task is either done in 1 day or 9 days, but the old one only in 5 days.
+        let new = &got.tasks[0];
+        assert_eq!(new.age_days, 1);
+        assert!([on(2), on(10)].contains(&new.percentiles[0].finish_on));
+        assert_eq!(new.percentiles[2].finish_on, on(10));
+        assert!(got.tasks[1]
+            .percentiles
+            .iter()
+            .all(|p| p.finish_on == on(6)));
+
+        assert!(forecast(vec![1]).is_err());
+        assert!(forecast(vec![9]).is_err());
+    }
+}
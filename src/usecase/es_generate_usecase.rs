@@ -0,0 +1,218 @@
+use crate::Result;
+use chrono::{Duration, NaiveDateTime};
+
+use crate::ddd::component::{AggregateID, AggregateRoot};
+use crate::domain::es_task::{
+    Cost, IESTaskRepository, IESTaskRepositoryComponent, Priority, SequentialID, Tag, Task,
+    TaskCommand, TaskSource, Urgency,
+};
+
+/// VERBS and NOUNS make titles of generated tasks, such as `review the invoice`.
+const VERBS: &[&str] = &[
+    "write",
+    "review",
+    "fix",
+    "plan",
+    "call about",
+    "buy",
+    "clean",
+    "update",
+    "draft",
+    "book",
+];
+const NOUNS: &[&str] = &[
+    "the invoice",
+    "the release notes",
+    "the flaky test",
+    "the trip",
+    "the dentist",
+    "groceries",
+    "the garage",
+    "the roadmap",
+    "the budget",
+    "the onboarding guide",
+];
+
+/// TAGS are given to generated tasks at random.
+const TAGS: &[&str] = &[
+    "work", "home", "docs", "ops", "errand", "review", "proj-x", "proj-y",
+];
+
+/// HISTORY_DAYS is how far back generated tasks are created.
+const HISTORY_DAYS: i64 = 730;
+
+/// DTO for input of GenerateUseCase.
+#[derive(Debug)]
+pub struct GenerateUseCaseInput {
+    pub tasks: usize,
+    /// events_per_task counts the creation with the title too, so that it is at least 2.
+    pub events_per_task: usize,
+    /// seed makes the content reproducible. Aggregate ids are random anyway.
+    pub seed: u64,
+    /// now in UTC. No event occurs after it.
+    pub now: NaiveDateTime,
+}
+
+/// Usecase to fill the event store with synthetic tasks to reproduce performance issues.
+pub trait GenerateUseCase: IESTaskRepositoryComponent {
+    /// execute generating tasks in one transaction and returns the number of events.
+    /// Tasks are created in the last two years, rescored, tagged and worked on, and most are closed.
+    fn execute(&self, input: GenerateUseCaseInput) -> Result<usize> {
+        let events_per_task = input.events_per_task.max(2);
+        self.repository()
+            .save_new_all(input.tasks, &|_, aggregate_id, sequential_id| {
+                generate_task(aggregate_id, sequential_id, events_per_task, &input)
+            })?;
+
+        Ok(input.tasks * events_per_task)
+    }
+}
+
+impl<T: IESTaskRepositoryComponent> GenerateUseCase for T {}
+
+/// GenerateUseCaseComponent returns GenerateUseCase.
+pub trait GenerateUseCaseComponent {
+    type GenerateUseCase: GenerateUseCase;
+    fn generate_usecase(&self) -> &Self::GenerateUseCase;
+}
+
+/// generate_task builds a task with exactly `events` events occurring in the order of versions.
+/// The random numbers depend only on the seed and the sequential id, so that retries build the same task.
+fn generate_task(
+    aggregate_id: AggregateID,
+    sequential_id: SequentialID,
+    events: usize,
+    input: &GenerateUseCaseInput,
+) -> Task {
+    let mut rng = fastrand::Rng::with_seed(input.seed ^ (sequential_id.to_i64() as u64));
+    let pick = |rng: &mut fastrand::Rng, words: &[&str]| words[rng.usize(..words.len())].to_owned();
+    let title = |rng: &mut fastrand::Rng| format!("{} {}", pick(rng, VERBS), pick(rng, NOUNS));
+
+    let mut occurred_on = input.now - Duration::minutes(rng.i64(0..HISTORY_DAYS * 24 * 60));
+    let mut task = Task::import(
+        TaskSource {
+            aggregate_id,
+            sequential_id,
+            title: title(&mut rng),
+            priority: None,
+            cost: None,
+            tags: vec![],
+        },
+        occurred_on,
+        None,
+    );
+
+    let closes = events > 2 && rng.u8(0..10) < 6;
+    // NOTE: tagging a tag which the task has, or untagging one which it lacks, records no event.
+    while task.events().len() < events - usize::from(closes) {
+        let command = match rng.u8(0..20) {
+            0..=1 => TaskCommand::EditTitle {
+                title: title(&mut rng),
+            },
+            2..=5 => TaskCommand::RescorePriority {
+                priority: Priority::new(rng.i32(1..=30)),
+            },
+            6..=8 => TaskCommand::RescoreUrgency {
+                urgency: Urgency::new(rng.i32(1..=30)),
+            },
+            9..=11 => TaskCommand::RescoreCost {
+                cost: Cost::new(rng.i32(1..=20)),
+            },
+            12..=14 => TaskCommand::Tag {
+                tag: Tag::new(&pick(&mut rng, TAGS)).expect("TAGS are valid"),
+            },
+            15 => TaskCommand::Untag {
+                tag: Tag::new(&pick(&mut rng, TAGS)).expect("TAGS are valid"),
+            },
+            _ => TaskCommand::LogTime {
+                duration: std::time::Duration::from_secs(rng.u64(5..=120) * 60),
+            },
+        };
+        let recorded = task.events().len();
+        task.execute(command)
+            .expect("the commands never fail on an open task");
+        if task.events().len() == recorded {
+            continue;
+        }
+        occurred_on = next_time(&mut rng, occurred_on, input.now);
+        task.backdate_last_event(occurred_on);
+    }
+    if closes {
+        task.execute(TaskCommand::Close)
+            .expect("a task which is not a habit can be closed");
+        occurred_on = next_time(&mut rng, occurred_on, input.now);
+        task.backdate_last_event(occurred_on);
+    }
+
+    task
+}
+
+/// next_time returns a time up to three days after `at`, but not after `now`.
+fn next_time(rng: &mut fastrand::Rng, at: NaiveDateTime, now: NaiveDateTime) -> NaiveDateTime {
+    (at + Duration::minutes(rng.i64(1..=3 * 24 * 60))).min(now)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::infra::sqlite::es_task_repository::TaskRepository;
+    use chrono::NaiveDate;
+    use rusqlite::Connection;
+
+    struct GenerateUseCaseComponentImpl {
+        task_repository: TaskRepository,
+    }
+
+    impl IESTaskRepositoryComponent for GenerateUseCaseComponentImpl {
+        type Repository = TaskRepository;
+        fn repository(&self) -> &Self::Repository {
+            &self.task_repository
+        }
+    }
+
+    #[test]
+    fn test_execute() {
+        let task_repository = TaskRepository::new(Connection::open_in_memory().unwrap());
+        task_repository.create_table_if_not_exists().unwrap();
+        let component = GenerateUseCaseComponentImpl { task_repository };
+        let now = NaiveDate::from_ymd_opt(2024, 5, 1)
+            .unwrap()
+            .and_hms_opt(12, 0, 0)
+            .unwrap();
+
+        let events = <GenerateUseCaseComponentImpl as GenerateUseCase>::execute(
+            &component,
+            GenerateUseCaseInput {
+                tasks: 30,
+                events_per_task: 6,
+                seed: 42,
+                now,
+            },
+        )
+        .unwrap();
+        assert_eq!(events, 180);
+        let stored: i64 = component
+            .repository()
+            .connection()
+            .run(|conn| conn.query_row("SELECT COUNT(*) FROM task_events", [], |row| row.get(0)))
+            .unwrap();
+        assert_eq!(stored, 180);
+
+        let mut tasks = 0;
+        let mut closed = 0;
+        component
+            .repository()
+            .for_each_task(&mut |task| {
+                tasks += 1;
+                assert!(task.created_at() <= now);
+                if let Some(closed_at) = task.closed_at() {
+                    closed += 1;
+                    assert!(task.created_at() <= closed_at && closed_at <= now);
+                }
+                Ok(())
+            })
+            .unwrap();
+        assert_eq!(tasks, 30);
+        assert!(0 < closed && closed < 30);
+    }
+}
@@ -0,0 +1,114 @@
+use crate::Result;
+
+use crate::ddd::component::{AggregateRoot, Repository};
+use crate::domain::es_task::{
+    IESTaskRepository, IESTaskRepositoryComponent, SequentialID, TaskCommand,
+};
+use crate::usecase::error::UseCaseError;
+
+/// DTO for input of CheckInUseCase.
+#[derive(Debug)]
+pub struct CheckInUseCaseInput {
+    pub sequential_id: SequentialID,
+}
+
+/// Usecase to check in a habit.
+pub trait CheckInUseCase: IESTaskRepositoryComponent {
+    /// execute checking in. A locked habit refuses it, and a task which is not a habit fails.
+    fn execute(&self, input: CheckInUseCaseInput) -> Result<SequentialID> {
+        let mut task = self
+            .repository()
+            .load_by_sequential_id(input.sequential_id)?
+            .ok_or(UseCaseError::NotFound(input.sequential_id.to_i64()))?;
+
+        if task.is_locked() {
+            return Err(UseCaseError::Locked(task.sequential_id().to_i64()).into());
+        }
+
+        task.execute(TaskCommand::CheckIn)?;
+
+        self.repository().save(&mut task)?;
+        Ok(task.sequential_id())
+    }
+}
+
+impl<T: IESTaskRepositoryComponent> CheckInUseCase for T {}
+
+/// CheckInUseCaseComponent returns CheckInUseCase.
+pub trait CheckInUseCaseComponent {
+    type CheckInUseCase: CheckInUseCase;
+    fn check_in_usecase(&self) -> &Self::CheckInUseCase;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::es_task::{HabitPeriod, HabitTarget, TaskError};
+    use crate::infra::sqlite::es_task_repository::TaskRepository;
+    use crate::usecase::es_add_task_usecase::{AddTaskUseCase, AddTaskUseCaseInput};
+    use rusqlite::Connection;
+
+    struct CheckInUseCaseComponentImpl {
+        task_repository: TaskRepository,
+    }
+
+    impl IESTaskRepositoryComponent for CheckInUseCaseComponentImpl {
+        type Repository = TaskRepository;
+        fn repository(&self) -> &Self::Repository {
+            &self.task_repository
+        }
+    }
+
+    #[test]
+    fn test_execute() {
+        let task_repository = TaskRepository::new(Connection::open_in_memory().unwrap());
+        task_repository.create_table_if_not_exists().unwrap();
+        let component = CheckInUseCaseComponentImpl { task_repository };
+
+        let habits = [None, HabitTarget::new(3, HabitPeriod::Week).ok()];
+        for habit in habits {
+            <CheckInUseCaseComponentImpl as AddTaskUseCase>::execute(
+                &component,
+                AddTaskUseCaseInput {
+                    title: "stretch".to_owned(),
+                    priority: None,
+                    urgency: None,
+                    cost: None,
+                    tags: vec![],
+                    private: false,
+                    habit,
+                    due: None,
+                    someday: false,
+                    parent: None,
+                    rules: Default::default(),
+                    description: None,
+                },
+            )
+            .unwrap();
+        }
+
+        let check_in = |id: i64| {
+            <CheckInUseCaseComponentImpl as CheckInUseCase>::execute(
+                &component,
+                CheckInUseCaseInput {
+                    sequential_id: SequentialID::new(id),
+                },
+            )
+        };
+        check_in(2).unwrap();
+        check_in(2).unwrap();
+        let err = check_in(1).unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<TaskError>(),
+            Some(TaskError::NotHabit)
+        ));
+        assert!(check_in(3).is_err());
+
+        let task = component
+            .repository()
+            .load_by_sequential_id(SequentialID::new(2))
+            .unwrap()
+            .unwrap();
+        assert_eq!(task.check_ins().len(), 2);
+    }
+}
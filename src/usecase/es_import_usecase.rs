@@ -0,0 +1,537 @@
+use std::collections::HashMap;
+
+use chrono::NaiveDate;
+
+use crate::Result;
+
+use crate::ddd::component::{AggregateID, AggregateRoot};
+use crate::domain::es_task::{
+    Cost, DueDate, HabitTarget, IESTaskRepository, IESTaskRepositoryComponent, ImportCheckpoint,
+    Priority, SequentialID, Tag, Task, TaskCommand, TaskError, TaskSource, Urgency, WaitingOn,
+};
+use crate::usecase::error::UseCaseError;
+use crate::usecase::es_list_task_usecase::{HabitProgressDTO, TaskDTO, WaitingOnDTO};
+
+/// DTO of a row read from a file to import.
+#[derive(Debug)]
+pub struct ImportRowDTO {
+    /// line is where the row is in the file, counted from 1.
+    pub line: usize,
+    pub task: ImportTaskDTO,
+}
+
+/// DTO of a task to import. Scores which are not given are the defaults of a new task.
+#[derive(Debug, Clone, Default)]
+pub struct ImportTaskDTO {
+    pub title: String,
+    pub priority: Option<i32>,
+    pub urgency: Option<i32>,
+    pub cost: Option<i32>,
+    pub tags: Vec<String>,
+    pub habit: Option<HabitProgressDTO>,
+    pub waiting_on: Option<WaitingOnDTO>,
+    pub due_on: Option<NaiveDate>,
+    pub is_someday: bool,
+    pub is_closed: bool,
+}
+
+/// A task read from a file has its id, parent and follow-up of another database, so that they are dropped.
+impl From<TaskDTO> for ImportTaskDTO {
+    fn from(task: TaskDTO) -> Self {
+        ImportTaskDTO {
+            title: task.title,
+            priority: Some(task.priority),
+            urgency: Some(task.urgency),
+            cost: Some(task.cost),
+            tags: task.tags,
+            habit: task.habit,
+            waiting_on: task.waiting_on,
+            due_on: task.due_on,
+            is_someday: task.is_someday,
+            is_closed: task.is_closed,
+        }
+    }
+}
+
+/// DTO for input of ImportUseCase.
+#[derive(Debug)]
+pub struct ImportUseCaseInput {
+    pub rows: Vec<ImportRowDTO>,
+    /// validate_only checks the rows without adding them.
+    pub validate_only: bool,
+    /// chunk_size is the number of tasks added in a transaction. 0 adds all of them in one.
+    pub chunk_size: usize,
+    /// digest tells the file apart to record a checkpoint after each chunk. None records nothing.
+    pub digest: Option<String>,
+    /// resume skips rows up to the checkpoint of the digest, which an interrupted import has added.
+    pub resume: bool,
+}
+
+/// DTO of the progress told after each chunk is added.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ImportProgressDTO {
+    /// imported is the number of tasks added so far.
+    pub imported: usize,
+    /// total is the number of tasks to add.
+    pub total: usize,
+    /// last_line is the line of the last added row, to resume after it.
+    pub last_line: usize,
+}
+
+/// DTO of a row which can not be imported.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RowErrorDTO {
+    pub line: usize,
+    pub message: String,
+}
+
+/// DTO of a row skipped as a task or an earlier row has the same title.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DuplicateDTO {
+    pub line: usize,
+    pub of: DuplicateOfDTO,
+}
+
+/// DTO of what a skipped row duplicates.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DuplicateOfDTO {
+    /// Task is the id of a stored task.
+    Task(i64),
+    /// Line is the line of an earlier row.
+    Line(usize),
+}
+
+/// DTO for output of ImportUseCase.
+#[derive(Debug, PartialEq, Eq)]
+pub struct ImportUseCaseOutput {
+    /// errors of invalid rows in the order of lines. Nothing is added if there is one.
+    pub errors: Vec<RowErrorDTO>,
+    /// duplicates are rows which are skipped, in the order of lines.
+    pub duplicates: Vec<DuplicateDTO>,
+    /// imported is the number of added tasks.
+    pub imported: usize,
+    /// resumed_after is the line of the checkpoint which the import resumed after.
+    pub resumed_after: Option<usize>,
+}
+
+/// Usecase to add tasks read from a file.
+pub trait ImportUseCase: IESTaskRepositoryComponent {
+    /// execute validating all rows, and then adding them in chunks if all are valid.
+    fn execute(&self, input: ImportUseCaseInput) -> Result<ImportUseCaseOutput> {
+        self.execute_in_chunks(input, &mut |_| Ok(()))
+    }
+
+    /// execute_in_chunks is execute telling the progress after each chunk is committed.
+    /// Rows with the title of a task, open, closed or added by an earlier row, are skipped,
+    /// so that a file can be imported again. Titles are compared ignoring the case.
+    /// NOTE: chunks added before a failure or an interruption are kept with the checkpoint,
+    /// and resume skips them.
+    fn execute_in_chunks(
+        &self,
+        input: ImportUseCaseInput,
+        on_chunk: &mut dyn FnMut(ImportProgressDTO) -> Result<()>,
+    ) -> Result<ImportUseCaseOutput> {
+        let resumed_after = match (&input.digest, input.resume) {
+            (Some(digest), true) => Some(
+                self.repository()
+                    .load_import_checkpoint(digest)?
+                    .ok_or(UseCaseError::NothingToResume)?,
+            ),
+            _ => None,
+        };
+        let mut titles = HashMap::new();
+        self.repository().for_each_task(&mut |task| {
+            titles
+                .entry(title_key(task.title()))
+                .or_insert(DuplicateOfDTO::Task(task.sequential_id().to_i64()));
+            Ok(())
+        })?;
+
+        let mut errors = vec![];
+        let mut duplicates = vec![];
+        let mut tasks = vec![];
+        let resume_after = resumed_after.unwrap_or(0);
+        for row in input.rows.iter().filter(|r| r.line > resume_after) {
+            let task = match ImportedTask::validate(&row.task) {
+                Ok(task) => task,
+                Err(message) => {
+                    errors.push(RowErrorDTO {
+                        line: row.line,
+                        message,
+                    });
+                    continue;
+                }
+            };
+            match titles.get(&title_key(&task.title)) {
+                Some(&of) => duplicates.push(DuplicateDTO { line: row.line, of }),
+                None => {
+                    titles.insert(title_key(&task.title), DuplicateOfDTO::Line(row.line));
+                    tasks.push((row.line, task));
+                }
+            }
+        }
+        if !errors.is_empty() || input.validate_only {
+            return Ok(ImportUseCaseOutput {
+                errors,
+                duplicates,
+                imported: 0,
+                resumed_after,
+            });
+        }
+
+        let chunk_size = match input.chunk_size {
+            0 => tasks.len().max(1),
+            n => n,
+        };
+        let mut imported = 0;
+        for chunk in tasks.chunks(chunk_size) {
+            let create = |i: usize, aggregate_id, sequential_id| {
+                chunk[i].1.create(aggregate_id, sequential_id)
+            };
+            let last_line = chunk[chunk.len() - 1].0;
+            match &input.digest {
+                Some(digest) => self.repository().save_imported(
+                    chunk.len(),
+                    &create,
+                    &ImportCheckpoint {
+                        digest: digest.clone(),
+                        last_line,
+                    },
+                )?,
+                None => self.repository().save_new_all(chunk.len(), &create)?,
+            }
+            imported += chunk.len();
+            on_chunk(ImportProgressDTO {
+                imported,
+                total: tasks.len(),
+                last_line,
+            })?;
+        }
+        if let Some(digest) = &input.digest {
+            self.repository().clear_import_checkpoint(digest)?;
+        }
+
+        Ok(ImportUseCaseOutput {
+            errors,
+            duplicates,
+            imported,
+            resumed_after,
+        })
+    }
+}
+
+impl<T: IESTaskRepositoryComponent> ImportUseCase for T {}
+
+/// ImportUseCaseComponent returns ImportUseCase.
+pub trait ImportUseCaseComponent {
+    type ImportUseCase: ImportUseCase;
+    fn import_usecase(&self) -> &Self::ImportUseCase;
+}
+
+/// title_key is the title compared to find duplicates.
+fn title_key(title: &str) -> String {
+    title.trim().to_lowercase()
+}
+
+/// ImportedTask is a row which is valid to be a task.
+struct ImportedTask {
+    title: String,
+    priority: Option<Priority>,
+    urgency: Option<Urgency>,
+    cost: Option<Cost>,
+    tags: Vec<Tag>,
+    habit: Option<HabitTarget>,
+    waiting_on: Option<WaitingOn>,
+    due: Option<DueDate>,
+    someday: bool,
+    closed: bool,
+}
+
+impl ImportedTask {
+    /// validate the row, and tell what is wrong in words if it is invalid.
+    fn validate(task: &ImportTaskDTO) -> std::result::Result<Self, String> {
+        let title = task.title.trim();
+        if title.is_empty() {
+            return Err("the title must not be empty".to_owned());
+        }
+        let tags = task
+            .tags
+            .iter()
+            .map(|t| Tag::new(t))
+            .collect::<std::result::Result<Vec<_>, _>>()
+            .map_err(|err| err.to_string())?;
+        let habit = task
+            .habit
+            .as_ref()
+            .map(|h| format!("{}/{}", h.times, h.period).parse::<HabitTarget>())
+            .transpose()
+            .map_err(|err| err.to_string())?;
+        if habit.is_some() && task.is_closed {
+            return Err(TaskError::ClosedHabit.to_string());
+        }
+        let waiting_on = task
+            .waiting_on
+            .as_ref()
+            .map(|w| WaitingOn::new(&w.person, w.follow_up_on))
+            .transpose()
+            .map_err(|err| err.to_string())?;
+
+        Ok(ImportedTask {
+            title: title.to_owned(),
+            priority: task.priority.map(Priority::new),
+            urgency: task.urgency.map(Urgency::new),
+            cost: task.cost.map(Cost::new),
+            tags,
+            habit,
+            waiting_on,
+            due: task.due_on.map(DueDate::new),
+            someday: task.is_someday,
+            closed: task.is_closed,
+        })
+    }
+
+    /// create the task. The commands never fail, as the row is validated.
+    fn create(&self, aggregate_id: AggregateID, sequential_id: SequentialID) -> Task {
+        let mut task = Task::create(TaskSource {
+            aggregate_id,
+            sequential_id,
+            title: self.title.clone(),
+            priority: self.priority,
+            cost: self.cost,
+            tags: self.tags.clone(),
+        });
+        let mut commands = vec![];
+        if let Some(urgency) = self.urgency {
+            commands.push(TaskCommand::RescoreUrgency { urgency });
+        }
+        if let Some(target) = self.habit {
+            commands.push(TaskCommand::MakeHabit { target });
+        }
+        if let Some(waiting_on) = &self.waiting_on {
+            commands.push(TaskCommand::WaitOn {
+                waiting_on: waiting_on.clone(),
+            });
+        }
+        if self.due.is_some() {
+            commands.push(TaskCommand::SetDueDate { due: self.due });
+        }
+        if self.someday {
+            commands.push(TaskCommand::Defer);
+        }
+        if self.closed {
+            commands.push(TaskCommand::Close);
+        }
+        for command in commands {
+            task.execute(command).expect("the row is validated");
+        }
+        task
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::infra::sqlite::es_task_repository::TaskRepository;
+    use rusqlite::Connection;
+
+    struct ImportUseCaseComponentImpl {
+        task_repository: TaskRepository,
+    }
+
+    impl IESTaskRepositoryComponent for ImportUseCaseComponentImpl {
+        type Repository = TaskRepository;
+        fn repository(&self) -> &Self::Repository {
+            &self.task_repository
+        }
+    }
+
+    fn row(line: usize, title: &str, tags: &[&str], is_closed: bool) -> ImportRowDTO {
+        ImportRowDTO {
+            line,
+            task: ImportTaskDTO {
+                title: title.to_owned(),
+                priority: Some(20),
+                cost: Some(3),
+                tags: tags.iter().map(|t| t.to_string()).collect(),
+                is_closed,
+                ..Default::default()
+            },
+        }
+    }
+
+    #[test]
+    fn test_execute() {
+        let task_repository = TaskRepository::new(Connection::open_in_memory().unwrap());
+        task_repository.create_table_if_not_exists().unwrap();
+        let component = ImportUseCaseComponentImpl { task_repository };
+        let import = |rows, validate_only| {
+            <ImportUseCaseComponentImpl as ImportUseCase>::execute(
+                &component,
+                ImportUseCaseInput {
+                    rows,
+                    validate_only,
+                    chunk_size: 0,
+                    digest: None,
+                    resume: false,
+                },
+            )
+            .unwrap()
+        };
+        let count = || {
+            let mut count = 0;
+            component
+                .repository()
+                .for_each_task(&mut |_| {
+                    count += 1;
+                    Ok(())
+                })
+                .unwrap();
+            count
+        };
+
+        let output = import(
+            vec![
+                row(1, "buy milk", &["home"], false),
+                row(2, "  ", &[], false),
+                row(4, "pay rent", &["a b"], false),
+            ],
+            false,
+        );
+        assert_eq!(
+            output.errors,
+            vec![
+                RowErrorDTO {
+                    line: 2,
+                    message: String::from("the title must not be empty"),
+                },
+                RowErrorDTO {
+                    line: 4,
+                    message: String::from(
+                        "`a b` is not a valid tag, a tag must be non-empty without whitespace and commas"
+                    ),
+                },
+            ]
+        );
+        assert_eq!(count(), 0);
+
+        let rows = || {
+            vec![
+                row(1, "buy milk", &["home"], false),
+                row(2, "pay rent", &[], true),
+            ]
+        };
+        assert_eq!(
+            import(rows(), true),
+            ImportUseCaseOutput {
+                errors: vec![],
+                duplicates: vec![],
+                imported: 0,
+                resumed_after: None,
+            }
+        );
+        assert_eq!(count(), 0);
+
+        assert_eq!(import(rows(), false).imported, 2);
+        let mut tasks = vec![];
+        component
+            .repository()
+            .for_each_task(&mut |t| {
+                tasks.push(t);
+                Ok(())
+            })
+            .unwrap();
+        assert_eq!(tasks[0].title(), "buy milk");
+        assert!(!tasks[0].is_closed());
+        assert!(tasks[1].is_closed());
+
+        let output = import(
+            vec![
+                row(1, "Buy Milk ", &[], false),
+                row(2, "walk the dog", &[], false),
+                row(3, "walk the dog", &[], false),
+            ],
+            false,
+        );
+        assert_eq!(
+            output.duplicates,
+            vec![
+                DuplicateDTO {
+                    line: 1,
+                    of: DuplicateOfDTO::Task(1),
+                },
+                DuplicateDTO {
+                    line: 3,
+                    of: DuplicateOfDTO::Line(2),
+                },
+            ]
+        );
+        assert_eq!(output.imported, 1);
+        assert_eq!(count(), 3);
+
+        let rows = || {
+            (10..15)
+                .map(|i| row(i, &format!("read chapter {}", i), &[], false))
+                .collect::<Vec<_>>()
+        };
+        let import_in_chunks =
+            |resume, on_chunk: &mut dyn FnMut(ImportProgressDTO) -> Result<()>| {
+                <ImportUseCaseComponentImpl as ImportUseCase>::execute_in_chunks(
+                    &component,
+                    ImportUseCaseInput {
+                        rows: rows(),
+                        validate_only: false,
+                        chunk_size: 2,
+                        digest: Some("abc".to_owned()),
+                        resume,
+                    },
+                    on_chunk,
+                )
+            };
+
+        // NOTE: the interruption after the first chunk keeps it with the checkpoint.
+        assert!(
+            import_in_chunks(false, &mut |_| Err(UseCaseError::NothingToResume.into())).is_err()
+        );
+        assert_eq!(count(), 5);
+        assert_eq!(
+            component
+                .repository()
+                .load_import_checkpoint("abc")
+                .unwrap(),
+            Some(11)
+        );
+
+        let mut progress = vec![];
+        let output = import_in_chunks(true, &mut |p| {
+            progress.push(p);
+            Ok(())
+        })
+        .unwrap();
+        assert_eq!((output.imported, output.resumed_after), (3, Some(11)));
+        assert_eq!(output.duplicates, vec![]);
+        assert_eq!(
+            progress,
+            vec![
+                ImportProgressDTO {
+                    imported: 2,
+                    total: 3,
+                    last_line: 13,
+                },
+                ImportProgressDTO {
+                    imported: 3,
+                    total: 3,
+                    last_line: 14,
+                },
+            ]
+        );
+        assert_eq!(count(), 8);
+        assert_eq!(
+            component
+                .repository()
+                .load_import_checkpoint("abc")
+                .unwrap(),
+            None
+        );
+        assert!(import_in_chunks(true, &mut |_| Ok(())).is_err());
+    }
+}
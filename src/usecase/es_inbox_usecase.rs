@@ -0,0 +1,281 @@
+use crate::Result;
+use chrono::{NaiveDate, NaiveDateTime};
+use serde::{Deserialize, Serialize};
+
+use crate::ddd::component::{AggregateID, AggregateRoot, Repository};
+use crate::domain::es_task::{
+    Cost, IESTaskRepository, IESTaskRepositoryComponent, Priority, SequentialID, Tag, Task,
+    TaskCommand, TaskSource, Urgency, WaitingOn,
+};
+use crate::usecase::error::UseCaseError;
+
+/// DTO for input of CaptureUseCase.
+#[derive(Debug)]
+pub struct CaptureUseCaseInput {
+    pub title: String,
+}
+
+/// DTO of an item in the inbox.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct InboxItemDTO {
+    pub id: i64,
+    pub title: String,
+    /// captured_at in UTC.
+    pub captured_at: NaiveDateTime,
+}
+
+/// TriageAction is what an item in the inbox turns into.
+#[derive(Debug)]
+pub enum TriageAction {
+    /// keep the item as a task with the scores and the tags. Scores not given stay default.
+    Keep {
+        priority: Option<i32>,
+        urgency: Option<i32>,
+        cost: Option<i32>,
+        tags: Vec<String>,
+    },
+    /// keep the item as a task waiting on the person.
+    Delegate {
+        person: String,
+        follow_up_on: Option<NaiveDate>,
+    },
+    /// close the item since it is not worth doing.
+    Drop,
+}
+
+/// DTO for input of TriageUseCase.
+#[derive(Debug)]
+pub struct TriageUseCaseInput {
+    pub sequential_id: SequentialID,
+    pub action: TriageAction,
+}
+
+/// Usecase to capture a thought into the inbox with only its title.
+pub trait CaptureUseCase: IESTaskRepositoryComponent {
+    /// execute capturing. The item is left out of listings until it is triaged.
+    fn execute(&self, input: CaptureUseCaseInput) -> Result<SequentialID> {
+        let aggregate_id = AggregateID::new();
+        let t = self.repository().save_new(aggregate_id, &|sequential_id| {
+            let mut task = Task::create(TaskSource {
+                aggregate_id,
+                sequential_id,
+                title: input.title.clone(),
+                priority: None,
+                cost: None,
+                tags: vec![],
+            });
+            // NOTE: a new task is open, so that it can always be captured.
+            task.execute(TaskCommand::Capture)
+                .expect("an open task can be captured");
+            task
+        })?;
+
+        Ok(t.sequential_id())
+    }
+}
+
+impl<T: IESTaskRepositoryComponent> CaptureUseCase for T {}
+
+/// CaptureUseCaseComponent returns CaptureUseCase.
+pub trait CaptureUseCaseComponent {
+    type CaptureUseCase: CaptureUseCase;
+    fn capture_usecase(&self) -> &Self::CaptureUseCase;
+}
+
+/// Usecase to list items in the inbox.
+pub trait ListInboxUseCase: IESTaskRepositoryComponent {
+    /// execute listing open items in the inbox from the oldest.
+    fn execute(&self) -> Result<Vec<InboxItemDTO>> {
+        let mut items = vec![];
+        self.repository().for_each_task(&mut |task| {
+            if task.is_in_inbox() && !task.is_closed() {
+                items.push(InboxItemDTO {
+                    id: task.sequential_id().to_i64(),
+                    title: task.title().to_owned(),
+                    captured_at: task.created_at(),
+                });
+            }
+            Ok(())
+        })?;
+
+        Ok(items)
+    }
+}
+
+impl<T: IESTaskRepositoryComponent> ListInboxUseCase for T {}
+
+/// ListInboxUseCaseComponent returns ListInboxUseCase.
+pub trait ListInboxUseCaseComponent {
+    type ListInboxUseCase: ListInboxUseCase;
+    fn list_inbox_usecase(&self) -> &Self::ListInboxUseCase;
+}
+
+/// Usecase to clarify an item in the inbox.
+pub trait TriageUseCase: IESTaskRepositoryComponent {
+    /// execute turning the item into a task, a delegated task, or a closed one.
+    fn execute(&self, input: TriageUseCaseInput) -> Result<SequentialID> {
+        let mut task = self
+            .repository()
+            .load_by_sequential_id(input.sequential_id)?
+            .ok_or(UseCaseError::NotFound(input.sequential_id.to_i64()))?;
+
+        if task.is_closed() {
+            return Err(UseCaseError::AlreadyClosed(task.sequential_id().to_i64()).into());
+        }
+        if !task.is_in_inbox() {
+            return Err(UseCaseError::NotInInbox(task.sequential_id().to_i64()).into());
+        }
+        if task.is_locked() {
+            return Err(UseCaseError::Locked(task.sequential_id().to_i64()).into());
+        }
+
+        task.execute(TaskCommand::Triage)?;
+        match input.action {
+            TriageAction::Keep {
+                priority,
+                urgency,
+                cost,
+                tags,
+            } => {
+                if let Some(priority) = priority {
+                    task.execute(TaskCommand::RescorePriority {
+                        priority: Priority::new(priority),
+                    })?;
+                }
+                if let Some(urgency) = urgency {
+                    task.execute(TaskCommand::RescoreUrgency {
+                        urgency: Urgency::new(urgency),
+                    })?;
+                }
+                if let Some(cost) = cost {
+                    task.execute(TaskCommand::RescoreCost {
+                        cost: Cost::new(cost),
+                    })?;
+                }
+                for tag in tags {
+                    task.execute(TaskCommand::Tag {
+                        tag: Tag::new(&tag)?,
+                    })?;
+                }
+            }
+            TriageAction::Delegate {
+                person,
+                follow_up_on,
+            } => task.execute(TaskCommand::WaitOn {
+                waiting_on: WaitingOn::new(&person, follow_up_on)?,
+            })?,
+            TriageAction::Drop => task.execute(TaskCommand::Close)?,
+        }
+
+        self.repository().save(&mut task)?;
+        Ok(task.sequential_id())
+    }
+}
+
+impl<T: IESTaskRepositoryComponent> TriageUseCase for T {}
+
+/// TriageUseCaseComponent returns TriageUseCase.
+pub trait TriageUseCaseComponent {
+    type TriageUseCase: TriageUseCase;
+    fn triage_usecase(&self) -> &Self::TriageUseCase;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::infra::sqlite::es_task_repository::TaskRepository;
+    use crate::usecase::es_list_task_usecase::{ListTaskUseCase, ListTaskUseCaseInput};
+    use rusqlite::Connection;
+
+    struct InboxUseCaseComponentImpl {
+        task_repository: TaskRepository,
+    }
+
+    impl IESTaskRepositoryComponent for InboxUseCaseComponentImpl {
+        type Repository = TaskRepository;
+        fn repository(&self) -> &Self::Repository {
+            &self.task_repository
+        }
+    }
+
+    #[test]
+    fn test_execute() {
+        let task_repository = TaskRepository::new(Connection::open_in_memory().unwrap());
+        task_repository.create_table_if_not_exists().unwrap();
+        let component = InboxUseCaseComponentImpl { task_repository };
+
+        for title in [
+            "renew the passport",
+            "ask about the budget",
+            "learn the harp",
+        ] {
+            <InboxUseCaseComponentImpl as CaptureUseCase>::execute(
+                &component,
+                CaptureUseCaseInput {
+                    title: title.to_owned(),
+                },
+            )
+            .unwrap();
+        }
+        let inbox = || {
+            <InboxUseCaseComponentImpl as ListInboxUseCase>::execute(&component)
+                .unwrap()
+                .into_iter()
+                .map(|i| i.id)
+                .collect::<Vec<i64>>()
+        };
+        let list = || {
+            <InboxUseCaseComponentImpl as ListTaskUseCase>::execute(
+                &component,
+                ListTaskUseCaseInput::default(),
+            )
+            .unwrap()
+        };
+        assert_eq!(inbox(), vec![1, 2, 3]);
+        assert!(list().is_empty());
+
+        let triage = |id: i64, action| {
+            <InboxUseCaseComponentImpl as TriageUseCase>::execute(
+                &component,
+                TriageUseCaseInput {
+                    sequential_id: SequentialID::new(id),
+                    action,
+                },
+            )
+        };
+        triage(
+            1,
+            TriageAction::Keep {
+                priority: Some(20),
+                urgency: None,
+                cost: Some(3),
+                tags: vec!["home".to_owned()],
+            },
+        )
+        .unwrap();
+        triage(
+            2,
+            TriageAction::Delegate {
+                person: "Bob".to_owned(),
+                follow_up_on: None,
+            },
+        )
+        .unwrap();
+        triage(3, TriageAction::Drop).unwrap();
+        assert_eq!(
+            triage(1, TriageAction::Drop).unwrap_err().to_string(),
+            UseCaseError::NotInInbox(1).to_string()
+        );
+        assert_eq!(
+            triage(3, TriageAction::Drop).unwrap_err().to_string(),
+            UseCaseError::AlreadyClosed(3).to_string()
+        );
+
+        assert!(inbox().is_empty());
+        let got = list();
+        assert_eq!(got.len(), 2);
+        assert_eq!((got[0].priority, got[0].cost), (20, 3));
+        assert_eq!(got[0].tags, vec!["home".to_owned()]);
+        assert_eq!(got[1].waiting_on.as_ref().unwrap().person, "Bob");
+    }
+}
@@ -1,55 +1,345 @@
-use anyhow::Result;
+use std::collections::{HashMap, HashSet};
+use std::rc::Rc;
+use std::time::Duration;
 
-use crate::domain::es_task::{IESTaskRepository, IESTaskRepositoryComponent};
+use crate::Result;
+use chrono::{Local, NaiveDate, NaiveDateTime, TimeZone};
+use serde::{Deserialize, Serialize};
 
-use super::error::UseCaseError;
+use crate::domain::es_task::{
+    Cost, DueDate, IESTaskRepository, IESTaskRepositoryComponent, Priority, TaskReadModel, Urgency,
+};
+use crate::domain::scoring::{ScoringStrategy, TaskView};
 
-/// DTO for input of AddTaskUseCase.
-#[derive(Debug)]
-pub struct ListTaskUseCaseInput {}
+use super::list_summary::ListSummary;
+
+/// DTO for input of ListTaskUseCase.
+#[derive(Debug, Default)]
+pub struct ListTaskUseCaseInput {
+    /// only tasks having all of the tags are listed.
+    pub tags: Vec<String>,
+    /// closed tasks are listed too if true.
+    pub include_closed: bool,
+    /// private tasks are listed too if true.
+    pub include_private: bool,
+    /// items in the inbox, which are not triaged yet, are listed too if true.
+    pub include_inbox: bool,
+    /// tasks deferred to someday are listed too if true.
+    pub include_someday: bool,
+    /// deleted and archived tasks are listed too if true, even if they are closed.
+    pub include_deleted: bool,
+}
 
 /// DTO of task
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct TaskDTO {
     pub id: i64,
     pub title: String,
     pub priority: i32,
+    pub urgency: i32,
     pub cost: i32,
+    pub tags: Vec<String>,
+    pub is_closed: bool,
+    pub is_deleted: bool,
+    pub is_archived: bool,
+    /// habit is the progress in the current period if the task is a habit.
+    pub habit: Option<HabitProgressDTO>,
+    pub waiting_on: Option<WaitingOnDTO>,
+    /// follows is the id of the task which this task follows up.
+    pub follows: Option<i64>,
+    /// parent is the id of the task which this task is a subtask of.
+    pub parent: Option<i64>,
+    pub due_on: Option<NaiveDate>,
+    /// is_overdue is whether the task is open past the due date at the time of listing.
+    pub is_overdue: bool,
+    pub is_someday: bool,
+    /// elapsed is the time logged on the task, including stopped timers.
+    pub elapsed: Duration,
+    pub is_timer_running: bool,
+    pub pomodoros: u32,
 }
 
-/// Usecase to list tasks.
-pub trait ListTaskUseCase: IESTaskRepositoryComponent {
-    /// execute listing tasks.
-    /// TODO: CQRS accelerates performance.
-    fn execute(&self, _: ListTaskUseCaseInput) -> Result<Vec<TaskDTO>> {
-        let sequential_ids = self.repository().load_all_sequential_ids()?;
-
-        let mut tasks = Vec::new();
-        for sequential_id in sequential_ids {
-            let task = self
-                .repository()
-                .load_by_sequential_id(sequential_id)?
-                .ok_or(UseCaseError::NotFound(sequential_id.to_i64()))?;
-
-            if task.is_closed() {
-                continue;
+/// DTO of an external person whom a task waits on.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WaitingOnDTO {
+    pub person: String,
+    pub follow_up_on: Option<NaiveDate>,
+}
+
+impl WaitingOnDTO {
+    /// is_due returns whether the day to follow up has come on the day.
+    pub fn is_due(&self, today: NaiveDate) -> bool {
+        self.follow_up_on.is_some_and(|d| d <= today)
+    }
+}
+
+/// DTO of check-ins of a habit in the current period.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HabitProgressDTO {
+    pub done: usize,
+    pub times: u32,
+    pub period: String,
+}
+
+/// habit_progress counts check-ins since the local beginning of the current period.
+fn habit_progress(task: &TaskReadModel, now: NaiveDateTime) -> Option<HabitProgressDTO> {
+    let target = task.habit?;
+    let start = target.period().start(now);
+    let start = Local
+        .from_local_datetime(&start)
+        .earliest()
+        .map_or(start, |s| s.naive_utc());
+    Some(HabitProgressDTO {
+        done: task.check_ins_since(start),
+        times: target.times(),
+        period: target.period().to_string(),
+    })
+}
+
+impl From<&TaskDTO> for TaskView {
+    fn from(task: &TaskDTO) -> Self {
+        TaskView {
+            priority: Priority::new(task.priority),
+            urgency: Urgency::new(task.urgency),
+            cost: Cost::new(task.cost),
+            due_on: task.due_on,
+        }
+    }
+}
+
+/// summarize computes ListSummary of the listed tasks.
+pub fn summarize(tasks: &[TaskDTO]) -> ListSummary {
+    ListSummary {
+        overdue: tasks.iter().filter(|t| t.is_overdue).count(),
+        ..ListSummary::from_costs(tasks.iter().map(|t| t.cost))
+    }
+}
+
+/// GroupBy is a key to group listed tasks.
+#[derive(Debug, Clone)]
+pub enum GroupBy {
+    /// a task appears in every group of its tags.
+    Tag,
+    /// high (>= 20), normal (>= 10) and low.
+    PriorityBand,
+    /// quadrants of importance (priority) and urgency. A dimension at or above the threshold is high.
+    /// Tasks in a quadrant are ordered by the score.
+    Matrix {
+        strategy: Rc<dyn ScoringStrategy>,
+        threshold: i32,
+    },
+    /// people whom tasks wait on. Tasks waiting on no one are left out.
+    WaitingOn,
+}
+
+/// Quadrants of the matrix from the most to the least pressing.
+pub const MATRIX_QUADRANTS: [&str; 4] = ["do first", "schedule", "delegate", "eliminate"];
+
+/// The group of tasks without tags.
+pub const NO_TAG_GROUP: &str = "(no tag)";
+
+/// DTO of grouped tasks
+#[derive(Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TaskGroupDTO {
+    pub key: String,
+    pub tasks: Vec<TaskDTO>,
+    pub summary: ListSummary,
+}
+
+/// group groups the listed tasks keeping their order in each group, except matrix quadrants.
+/// Tag and person groups are ordered by name, priority bands from high to low and quadrants from do first.
+pub fn group(tasks: Vec<TaskDTO>, by: GroupBy) -> Vec<TaskGroupDTO> {
+    let mut groups: Vec<(String, Vec<TaskDTO>)> = match &by {
+        GroupBy::Tag => {
+            let mut keys: Vec<String> = tasks.iter().flat_map(|t| t.tags.clone()).collect();
+            keys.sort();
+            keys.dedup();
+            keys.push(NO_TAG_GROUP.to_owned());
+            keys.into_iter().map(|k| (k, vec![])).collect()
+        }
+        GroupBy::PriorityBand => ["high", "normal", "low"]
+            .iter()
+            .map(|k| (k.to_string(), vec![]))
+            .collect(),
+        GroupBy::Matrix { .. } => MATRIX_QUADRANTS
+            .iter()
+            .map(|k| (k.to_string(), vec![]))
+            .collect(),
+        GroupBy::WaitingOn => {
+            let mut keys: Vec<String> = tasks
+                .iter()
+                .filter_map(|t| t.waiting_on.as_ref().map(|w| w.person.clone()))
+                .collect();
+            keys.sort();
+            keys.dedup();
+            keys.into_iter().map(|k| (k, vec![])).collect()
+        }
+    };
+
+    for task in tasks {
+        let keys = match &by {
+            GroupBy::Tag if task.tags.is_empty() => vec![NO_TAG_GROUP.to_owned()],
+            GroupBy::Tag => task.tags.clone(),
+            GroupBy::PriorityBand => vec![priority_band(task.priority).to_owned()],
+            GroupBy::Matrix { threshold, .. } => vec![quadrant(&task, *threshold).to_owned()],
+            GroupBy::WaitingOn => task.waiting_on.iter().map(|w| w.person.clone()).collect(),
+        };
+        for (key, grouped) in groups.iter_mut() {
+            if keys.contains(key) {
+                grouped.push(task.clone());
             }
+        }
+    }
 
-            tasks.push(task);
+    if let GroupBy::Matrix { strategy, .. } = &by {
+        for (_, tasks) in groups.iter_mut() {
+            tasks.sort_by(|a, b| {
+                let score = |t: &TaskDTO| strategy.score(&TaskView::from(t));
+                score(b).total_cmp(&score(a))
+            });
         }
+    }
 
-        let mut dto_tasks: Vec<TaskDTO> = Vec::new();
-        for task in tasks {
-            dto_tasks.push(TaskDTO {
-                id: task.sequential_id().to_i64(),
-                title: task.title().to_owned(),
-                priority: task.priority().to_i32(),
-                cost: task.cost().to_i32(),
-            })
+    groups
+        .into_iter()
+        .filter(|(_, tasks)| !tasks.is_empty())
+        .map(|(key, tasks)| TaskGroupDTO {
+            key,
+            summary: summarize(&tasks),
+            tasks,
+        })
+        .collect()
+}
+
+/// DTO of a task in a tree of subtasks
+#[derive(Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NestedTaskDTO {
+    /// depth is the number of ancestors listed above the task.
+    pub depth: usize,
+    pub task: TaskDTO,
+}
+
+/// nest orders the listed tasks depth first, so that subtasks follow their parents.
+/// Siblings keep their order, and tasks whose parents are not listed are at the top level.
+pub fn nest(tasks: Vec<TaskDTO>) -> Vec<NestedTaskDTO> {
+    let ids: HashSet<i64> = tasks.iter().map(|t| t.id).collect();
+    let mut children: HashMap<Option<i64>, Vec<TaskDTO>> = HashMap::new();
+    for task in tasks {
+        let parent = task.parent.filter(|p| ids.contains(p));
+        children.entry(parent).or_default().push(task);
+    }
+
+    let mut nested = vec![];
+    let mut stack: Vec<(usize, TaskDTO)> = children
+        .remove(&None)
+        .unwrap_or_default()
+        .into_iter()
+        .rev()
+        .map(|t| (0, t))
+        .collect();
+    while let Some((depth, task)) = stack.pop() {
+        if let Some(subtasks) = children.remove(&Some(task.id)) {
+            stack.extend(subtasks.into_iter().rev().map(|t| (depth + 1, t)));
         }
+        nested.push(NestedTaskDTO { depth, task });
+    }
+    nested
+}
+
+/// quadrant classifies the task by its importance (priority) and urgency.
+/// A dimension at or above the threshold counts as high.
+fn quadrant(task: &TaskDTO, threshold: i32) -> &'static str {
+    match (task.priority >= threshold, task.urgency >= threshold) {
+        (true, true) => MATRIX_QUADRANTS[0],
+        (true, false) => MATRIX_QUADRANTS[1],
+        (false, true) => MATRIX_QUADRANTS[2],
+        (false, false) => MATRIX_QUADRANTS[3],
+    }
+}
+
+/// priority_band classifies the priority.
+fn priority_band(priority: i32) -> &'static str {
+    match priority {
+        p if p >= 20 => "high",
+        p if p >= 10 => "normal",
+        _ => "low",
+    }
+}
+
+/// Usecase to list tasks.
+pub trait ListTaskUseCase: IESTaskRepositoryComponent {
+    /// execute listing tasks. Tasks are read from the read model, not replayed from events.
+    fn execute(&self, input: ListTaskUseCaseInput) -> Result<Vec<TaskDTO>> {
+        let mut dto_tasks: Vec<TaskDTO> = Vec::new();
+        self.for_each(input, &mut |task| {
+            dto_tasks.push(task);
+            Ok(())
+        })?;
 
         Ok(dto_tasks)
     }
+
+    /// for_each passes listed tasks to `f` one by one in the order of ids,
+    /// so that large listings such as exports are not held in memory.
+    fn for_each(
+        &self,
+        input: ListTaskUseCaseInput,
+        f: &mut dyn FnMut(TaskDTO) -> Result<()>,
+    ) -> Result<()> {
+        let now = Local::now().naive_local();
+        self.repository()
+            .for_each_read_model(input.include_deleted, &mut |task| {
+                let is_deleted = task.is_deleted || task.is_archived;
+                if task.is_closed && !input.include_closed && !is_deleted {
+                    return Ok(());
+                }
+
+                if task.is_private && !input.include_private {
+                    return Ok(());
+                }
+
+                if task.in_inbox && !input.include_inbox {
+                    return Ok(());
+                }
+
+                if task.is_someday && !input.include_someday {
+                    return Ok(());
+                }
+
+                if !input
+                    .tags
+                    .iter()
+                    .all(|want| task.tags.iter().any(|t| t.as_str() == want))
+                {
+                    return Ok(());
+                }
+
+                f(TaskDTO {
+                    id: task.sequential_id.to_i64(),
+                    title: task.title.clone(),
+                    priority: task.priority.to_i32(),
+                    urgency: task.urgency.to_i32(),
+                    cost: task.cost.to_i32(),
+                    tags: task.tags.iter().map(|t| t.as_str().to_owned()).collect(),
+                    is_closed: task.is_closed,
+                    is_deleted: task.is_deleted,
+                    is_archived: task.is_archived,
+                    habit: habit_progress(&task, now),
+                    waiting_on: task.waiting_on.as_ref().map(|w| WaitingOnDTO {
+                        person: w.person().to_owned(),
+                        follow_up_on: w.follow_up_on(),
+                    }),
+                    follows: task.follows.map(|id| id.to_i64()),
+                    parent: task.parent.map(|id| id.to_i64()),
+                    due_on: task.due.map(DueDate::to_naive_date),
+                    is_overdue: task.is_overdue(now.date()),
+                    is_someday: task.is_someday,
+                    elapsed: task.elapsed_time,
+                    is_timer_running: task.timer_started_at.is_some(),
+                    pomodoros: task.pomodoros,
+                })
+            })
+    }
 }
 
 impl<T: IESTaskRepositoryComponent> ListTaskUseCase for T {}
@@ -63,6 +353,7 @@ pub trait ListTaskUseCaseComponent {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::domain::es_task::ScoreWeights;
     use crate::infra::sqlite::es_task_repository::TaskRepository;
     use crate::usecase::es_add_task_usecase::{
         AddTaskUseCase, AddTaskUseCaseComponent, AddTaskUseCaseInput,
@@ -77,7 +368,22 @@ mod tests {
             id: seed as i64,
             title: seed.to_string(),
             priority: 10,
+            urgency: 10,
             cost: 10,
+            tags: vec![],
+            is_closed: false,
+            habit: None,
+            waiting_on: None,
+            follows: None,
+            parent: None,
+            due_on: None,
+            is_overdue: false,
+            is_someday: false,
+            elapsed: Default::default(),
+            is_timer_running: false,
+            pomodoros: 0,
+            is_archived: false,
+            is_deleted: false,
         }
     }
 
@@ -92,6 +398,8 @@ mod tests {
         struct TaskSource {
             seed: u64,
             is_closed: bool,
+            is_private: bool,
+            tags: Vec<String>,
         }
 
         #[derive(Debug)]
@@ -143,47 +451,167 @@ mod tests {
                     TaskSource {
                         seed: 1,
                         is_closed: false,
+                        is_private: false,
+                        tags: vec![],
                     },
                     TaskSource {
                         seed: 2,
                         is_closed: false,
+                        is_private: false,
+                        tags: vec![],
                     },
                     TaskSource {
                         seed: 3,
                         is_closed: true,
+                        is_private: false,
+                        tags: vec![],
                     },
                     TaskSource {
                         seed: 4,
                         is_closed: false,
+                        is_private: false,
+                        tags: vec![],
                     },
                 ],
                 args: Args {
-                    input: ListTaskUseCaseInput {},
+                    input: ListTaskUseCaseInput::default(),
                 },
                 want: vec![make_task_dto(1), make_task_dto(2), make_task_dto(4)],
             },
+            TestCase {
+                name: String::from("normal: filter by tags"),
+                given: vec![
+                    TaskSource {
+                        seed: 1,
+                        is_closed: false,
+                        is_private: false,
+                        tags: vec![String::from("a")],
+                    },
+                    TaskSource {
+                        seed: 2,
+                        is_closed: false,
+                        is_private: false,
+                        tags: vec![String::from("a"), String::from("b")],
+                    },
+                    TaskSource {
+                        seed: 3,
+                        is_closed: true,
+                        is_private: false,
+                        tags: vec![String::from("a"), String::from("b")],
+                    },
+                ],
+                args: Args {
+                    input: ListTaskUseCaseInput {
+                        tags: vec![String::from("b"), String::from("a")],
+                        ..Default::default()
+                    },
+                },
+                want: vec![TaskDTO {
+                    tags: vec![String::from("a"), String::from("b")],
+                    ..make_task_dto(2)
+                }],
+            },
             TestCase {
                 name: String::from("normal: empty"),
                 given: vec![
                     TaskSource {
                         seed: 1,
                         is_closed: true,
+                        is_private: false,
+                        tags: vec![],
                     },
                     TaskSource {
                         seed: 2,
                         is_closed: true,
+                        is_private: false,
+                        tags: vec![],
                     },
                 ],
                 args: Args {
-                    input: ListTaskUseCaseInput {},
+                    input: ListTaskUseCaseInput::default(),
                 },
                 want: vec![],
             },
+            TestCase {
+                name: String::from("normal: include closed"),
+                given: vec![
+                    TaskSource {
+                        seed: 1,
+                        is_closed: true,
+                        is_private: false,
+                        tags: vec![],
+                    },
+                    TaskSource {
+                        seed: 2,
+                        is_closed: false,
+                        is_private: false,
+                        tags: vec![],
+                    },
+                ],
+                args: Args {
+                    input: ListTaskUseCaseInput {
+                        include_closed: true,
+                        ..Default::default()
+                    },
+                },
+                want: vec![
+                    TaskDTO {
+                        is_closed: true,
+                        ..make_task_dto(1)
+                    },
+                    make_task_dto(2),
+                ],
+            },
+            TestCase {
+                name: String::from("normal: hide private"),
+                given: vec![
+                    TaskSource {
+                        seed: 1,
+                        is_closed: false,
+                        is_private: true,
+                        tags: vec![],
+                    },
+                    TaskSource {
+                        seed: 2,
+                        is_closed: false,
+                        is_private: false,
+                        tags: vec![],
+                    },
+                ],
+                args: Args {
+                    input: ListTaskUseCaseInput::default(),
+                },
+                want: vec![make_task_dto(2)],
+            },
+            TestCase {
+                name: String::from("normal: include private"),
+                given: vec![
+                    TaskSource {
+                        seed: 1,
+                        is_closed: false,
+                        is_private: true,
+                        tags: vec![],
+                    },
+                    TaskSource {
+                        seed: 2,
+                        is_closed: false,
+                        is_private: false,
+                        tags: vec![],
+                    },
+                ],
+                args: Args {
+                    input: ListTaskUseCaseInput {
+                        include_private: true,
+                        ..Default::default()
+                    },
+                },
+                want: vec![make_task_dto(1), make_task_dto(2)],
+            },
             TestCase {
                 name: String::from("normal: empty2"),
                 given: vec![],
                 args: Args {
-                    input: ListTaskUseCaseInput {},
+                    input: ListTaskUseCaseInput::default(),
                 },
                 want: vec![],
             },
@@ -197,11 +625,20 @@ mod tests {
             for gt in test_case.given {
                 let add_task_usecase = list_task_usecase_component_impl.add_task_usecase();
                 let sequential_id = <ListTaskUseCaseComponentImpl as AddTaskUseCase>::execute(
-                    &add_task_usecase,
+                    add_task_usecase,
                     AddTaskUseCaseInput {
                         title: gt.seed.to_string(),
                         priority: None,
+                        urgency: None,
                         cost: None,
+                        tags: gt.tags,
+                        private: gt.is_private,
+                        habit: None,
+                        due: None,
+                        someday: false,
+                        parent: None,
+                        rules: Default::default(),
+                        description: None,
                     },
                 )
                 .unwrap();
@@ -209,8 +646,12 @@ mod tests {
                 if gt.is_closed {
                     let close_task_usecase = list_task_usecase_component_impl.close_task_usecase();
                     <ListTaskUseCaseComponentImpl as CloseTaskUseCase>::execute(
-                        &close_task_usecase,
-                        CloseTaskUseCaseInput { sequential_id },
+                        close_task_usecase,
+                        CloseTaskUseCaseInput {
+                            sequential_id,
+                            unlock: false,
+                            cascade: false,
+                        },
                     )
                     .unwrap();
                 }
@@ -218,7 +659,7 @@ mod tests {
 
             let list_task_usecase = list_task_usecase_component_impl.list_task_usecase();
             let got = <ListTaskUseCaseComponentImpl as ListTaskUseCase>::execute(
-                &list_task_usecase,
+                list_task_usecase,
                 test_case.args.input,
             )
             .unwrap();
@@ -226,4 +667,134 @@ mod tests {
             assert_eq!(got, test_case.want, "Failed in the \"{}\".", test_case.name,);
         }
     }
+
+    #[test]
+    fn test_group() {
+        #[derive(Debug)]
+        struct TestCase {
+            args: GroupBy,
+            want: Vec<(&'static str, Vec<i64>)>,
+            name: String,
+        }
+
+        let tasks = vec![
+            TaskDTO {
+                priority: 30,
+                tags: vec![String::from("work"), String::from("docs")],
+                ..make_task_dto(1)
+            },
+            TaskDTO {
+                priority: 1,
+                ..make_task_dto(2)
+            },
+            TaskDTO {
+                tags: vec![String::from("work")],
+                ..make_task_dto(3)
+            },
+        ];
+
+        let table = [
+            TestCase {
+                name: String::from("normal: tag"),
+                args: GroupBy::Tag,
+                want: vec![
+                    ("docs", vec![1]),
+                    ("work", vec![1, 3]),
+                    (NO_TAG_GROUP, vec![2]),
+                ],
+            },
+            TestCase {
+                name: String::from("normal: priority band"),
+                args: GroupBy::PriorityBand,
+                want: vec![("high", vec![1]), ("normal", vec![3]), ("low", vec![2])],
+            },
+        ];
+
+        for test_case in table {
+            let got: Vec<(String, Vec<i64>)> = group(tasks.clone(), test_case.args)
+                .into_iter()
+                .map(|g| (g.key, g.tasks.iter().map(|t| t.id).collect()))
+                .collect();
+            let want: Vec<(String, Vec<i64>)> = test_case
+                .want
+                .into_iter()
+                .map(|(k, ids)| (k.to_owned(), ids))
+                .collect();
+            assert_eq!(got, want, "Failed in the \"{}\".", test_case.name);
+        }
+    }
+
+    #[test]
+    fn test_summarize() {
+        let overdue = |seed| TaskDTO {
+            is_overdue: true,
+            ..make_task_dto(seed)
+        };
+        let tasks = vec![make_task_dto(1), overdue(2), overdue(3)];
+
+        assert_eq!(
+            summarize(&tasks),
+            ListSummary {
+                open: 3,
+                total_cost: 30,
+                overdue: 2,
+            }
+        );
+    }
+
+    #[test]
+    fn test_nest() {
+        let with_parent = |seed, parent| TaskDTO {
+            parent,
+            ..make_task_dto(seed)
+        };
+        // NOTE: the parent of 4 is not listed, so that it is at the top level.
+        let tasks = vec![
+            with_parent(1, None),
+            with_parent(2, Some(1)),
+            with_parent(3, None),
+            with_parent(4, Some(9)),
+            with_parent(5, Some(2)),
+            with_parent(6, Some(1)),
+        ];
+
+        let got: Vec<(usize, i64)> = nest(tasks)
+            .into_iter()
+            .map(|n| (n.depth, n.task.id))
+            .collect();
+        assert_eq!(got, vec![(0, 1), (1, 2), (2, 5), (1, 6), (0, 3), (0, 4)]);
+    }
+
+    #[test]
+    fn test_matrix() {
+        let task = |seed: u64, priority: i32, urgency: i32| TaskDTO {
+            priority,
+            urgency,
+            ..make_task_dto(seed)
+        };
+        let tasks = vec![
+            task(1, 20, 20),
+            task(2, 20, 1),
+            task(3, 1, 1),
+            task(4, 30, 25),
+            task(5, 20, 30),
+        ];
+        let by = GroupBy::Matrix {
+            strategy: Rc::new(ScoreWeights::default()),
+            threshold: 20,
+        };
+
+        let got: Vec<(String, Vec<i64>)> = group(tasks, by)
+            .into_iter()
+            .map(|g| (g.key, g.tasks.iter().map(|t| t.id).collect()))
+            .collect();
+        assert_eq!(
+            got,
+            vec![
+                (String::from("do first"), vec![4, 5, 1]),
+                (String::from("schedule"), vec![2]),
+                (String::from("eliminate"), vec![3]),
+            ]
+        );
+    }
 }
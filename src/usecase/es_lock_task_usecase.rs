@@ -0,0 +1,170 @@
+use crate::Result;
+
+use crate::ddd::component::{AggregateRoot, Repository};
+use crate::domain::es_task::{
+    IESTaskRepository, IESTaskRepositoryComponent, SequentialID, TaskCommand,
+};
+use crate::usecase::error::UseCaseError;
+
+/// DTO for input of LockTaskUseCase.
+#[derive(Debug)]
+pub struct LockTaskUseCaseInput {
+    pub sequential_id: SequentialID,
+    /// locked is true to lock the task and false to unlock it.
+    pub locked: bool,
+}
+
+/// Usecase to lock a task against edits, e.g. after it has been reported externally.
+pub trait LockTaskUseCase: IESTaskRepositoryComponent {
+    /// execute locking or unlocking a task.
+    /// Locking a locked task and unlocking an unlocked task record nothing.
+    fn execute(&self, input: LockTaskUseCaseInput) -> Result<SequentialID> {
+        let mut task = self
+            .repository()
+            .load_by_sequential_id(input.sequential_id)?
+            .ok_or(UseCaseError::NotFound(input.sequential_id.to_i64()))?;
+
+        let command = if input.locked {
+            TaskCommand::Lock
+        } else {
+            TaskCommand::Unlock
+        };
+        task.execute(command)?;
+
+        self.repository().save(&mut task)?;
+        Ok(task.sequential_id())
+    }
+}
+
+impl<T: IESTaskRepositoryComponent> LockTaskUseCase for T {}
+
+/// LockTaskUseCaseComponent returns LockTaskUseCase.
+pub trait LockTaskUseCaseComponent {
+    type LockTaskUseCase: LockTaskUseCase;
+    fn lock_task_usecase(&self) -> &Self::LockTaskUseCase;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::infra::sqlite::es_task_repository::TaskRepository;
+    use crate::usecase::es_add_task_usecase::{AddTaskUseCase, AddTaskUseCaseInput};
+    use crate::usecase::es_close_task_usecase::{CloseTaskUseCase, CloseTaskUseCaseInput};
+    use crate::usecase::es_edit_task_usecase::{EditTaskUseCase, EditTaskUseCaseInput};
+    use rusqlite::Connection;
+
+    struct LockTaskUseCaseComponentImpl {
+        task_repository: TaskRepository,
+    }
+
+    impl IESTaskRepositoryComponent for LockTaskUseCaseComponentImpl {
+        type Repository = TaskRepository;
+        fn repository(&self) -> &Self::Repository {
+            &self.task_repository
+        }
+    }
+
+    #[test]
+    fn test_execute() {
+        let task_repository = TaskRepository::new(Connection::open_in_memory().unwrap());
+        task_repository.create_table_if_not_exists().unwrap();
+        let component = LockTaskUseCaseComponentImpl { task_repository };
+
+        <LockTaskUseCaseComponentImpl as AddTaskUseCase>::execute(
+            &component,
+            AddTaskUseCaseInput {
+                title: "reported".to_owned(),
+                priority: None,
+                urgency: None,
+                cost: None,
+                tags: vec![],
+                private: false,
+                habit: None,
+                due: None,
+                someday: false,
+                parent: None,
+                rules: Default::default(),
+                description: None,
+            },
+        )
+        .unwrap();
+
+        let lock = |locked| {
+            <LockTaskUseCaseComponentImpl as LockTaskUseCase>::execute(
+                &component,
+                LockTaskUseCaseInput {
+                    sequential_id: SequentialID::new(1),
+                    locked,
+                },
+            )
+        };
+        let edit = |unlock| {
+            <LockTaskUseCaseComponentImpl as EditTaskUseCase>::execute(
+                &component,
+                EditTaskUseCaseInput {
+                    sequential_id: SequentialID::new(1),
+                    title: Some("edited".to_owned()),
+                    priority: None,
+                    urgency: None,
+                    cost: None,
+                    unlock,
+                    private: None,
+                    due: None,
+                    description: None,
+                },
+            )
+        };
+        let close = |unlock| {
+            <LockTaskUseCaseComponentImpl as CloseTaskUseCase>::execute(
+                &component,
+                CloseTaskUseCaseInput {
+                    sequential_id: SequentialID::new(1),
+                    unlock,
+                    cascade: false,
+                },
+            )
+        };
+        let load = || {
+            component
+                .task_repository
+                .load_by_sequential_id(SequentialID::new(1))
+                .unwrap()
+                .unwrap()
+        };
+
+        lock(true).unwrap();
+        assert!(load().is_locked());
+        assert_eq!(
+            edit(false).unwrap_err().to_string(),
+            UseCaseError::Locked(1).to_string()
+        );
+        assert_eq!(
+            close(false).unwrap_err().to_string(),
+            UseCaseError::Locked(1).to_string()
+        );
+        assert_eq!(load().title(), "reported");
+
+        lock(false).unwrap();
+        edit(false).unwrap();
+        assert_eq!(load().title(), "edited");
+
+        lock(true).unwrap();
+        close(true).unwrap();
+        let task = load();
+        assert!(task.is_closed());
+        assert!(!task.is_locked());
+
+        assert_eq!(
+            <LockTaskUseCaseComponentImpl as LockTaskUseCase>::execute(
+                &component,
+                LockTaskUseCaseInput {
+                    sequential_id: SequentialID::new(2),
+                    locked: true,
+                },
+            )
+            .unwrap_err()
+            .to_string(),
+            UseCaseError::NotFound(2).to_string()
+        );
+    }
+}
@@ -0,0 +1,239 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+use crate::Result;
+use chrono::NaiveDateTime;
+
+use crate::ddd::component::AggregateID;
+use crate::domain::es_task::{
+    Cost, IESTaskRepository, IESTaskRepositoryComponent, Priority, Tag, Task, TaskSource,
+};
+
+/// LEGACY_TAG_PREFIX prefixes the tag recording the id of the legacy task, such as `legacy:12`.
+/// It keeps the migration idempotent.
+pub const LEGACY_TAG_PREFIX: &str = "legacy:";
+
+/// DTO of a task of the legacy `tasks` table.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LegacyTaskDTO {
+    pub id: i64,
+    pub title: String,
+    pub priority: i32,
+    pub cost: i32,
+    /// created_at in UTC.
+    pub created_at: NaiveDateTime,
+    /// closed_at in UTC. It is None while the task is open.
+    pub closed_at: Option<NaiveDateTime>,
+}
+
+/// DTO for input of MigrateLegacyUseCase.
+#[derive(Debug)]
+pub struct MigrateLegacyUseCaseInput {
+    pub tasks: Vec<LegacyTaskDTO>,
+}
+
+/// DTO of a migrated task.
+#[derive(Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MigratedTaskDTO {
+    pub legacy_id: i64,
+    pub id: i64,
+}
+
+/// DTO for output of MigrateLegacyUseCase.
+#[derive(Debug, PartialEq, Eq)]
+pub struct MigrateLegacyUseCaseOutput {
+    pub migrated: Vec<MigratedTaskDTO>,
+    /// ids of legacy tasks migrated by an earlier run.
+    pub skipped: Vec<i64>,
+}
+
+/// Usecase to import legacy tasks into the event store.
+pub trait MigrateLegacyUseCase: IESTaskRepositoryComponent {
+    /// execute the migration. Tasks already migrated are skipped, even if they are deleted since.
+    fn execute(&self, input: MigrateLegacyUseCaseInput) -> Result<MigrateLegacyUseCaseOutput> {
+        let mut migrated_ids = HashSet::new();
+        for sequential_id in self.repository().load_all_sequential_ids()? {
+            let Some(task) = self.repository().load_any_by_sequential_id(sequential_id)? else {
+                continue;
+            };
+            migrated_ids.extend(task.tags().iter().filter_map(|tag| {
+                tag.as_str()
+                    .strip_prefix(LEGACY_TAG_PREFIX)
+                    .and_then(|id| id.parse::<i64>().ok())
+            }));
+        }
+
+        let mut output = MigrateLegacyUseCaseOutput {
+            migrated: vec![],
+            skipped: vec![],
+        };
+        for legacy in input.tasks {
+            if migrated_ids.contains(&legacy.id) {
+                output.skipped.push(legacy.id);
+                continue;
+            }
+
+            let tag = Tag::new(&format!("{}{}", LEGACY_TAG_PREFIX, legacy.id))?;
+            let aggregate_id = AggregateID::new();
+            let task = self.repository().save_new(aggregate_id, &|sequential_id| {
+                Task::import(
+                    TaskSource {
+                        aggregate_id,
+                        sequential_id,
+                        title: legacy.title.clone(),
+                        priority: Some(Priority::new(legacy.priority)),
+                        cost: Some(Cost::new(legacy.cost)),
+                        tags: vec![tag.clone()],
+                    },
+                    legacy.created_at,
+                    legacy.closed_at,
+                )
+            })?;
+            output.migrated.push(MigratedTaskDTO {
+                legacy_id: legacy.id,
+                id: task.sequential_id().to_i64(),
+            });
+        }
+
+        Ok(output)
+    }
+}
+
+impl<T: IESTaskRepositoryComponent> MigrateLegacyUseCase for T {}
+
+/// MigrateLegacyUseCaseComponent returns MigrateLegacyUseCase.
+pub trait MigrateLegacyUseCaseComponent {
+    type MigrateLegacyUseCase: MigrateLegacyUseCase;
+    fn migrate_legacy_usecase(&self) -> &Self::MigrateLegacyUseCase;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ddd::component::{AggregateRoot, Repository};
+    use crate::domain::es_task::{SequentialID, TaskCommand};
+    use crate::infra::sqlite::es_task_repository::TaskRepository;
+    use rusqlite::Connection;
+
+    struct MigrateLegacyUseCaseComponentImpl {
+        task_repository: TaskRepository,
+    }
+
+    impl IESTaskRepositoryComponent for MigrateLegacyUseCaseComponentImpl {
+        type Repository = TaskRepository;
+        fn repository(&self) -> &Self::Repository {
+            &self.task_repository
+        }
+    }
+
+    fn legacy_task(id: i64, is_closed: bool) -> LegacyTaskDTO {
+        let created_at =
+            NaiveDateTime::parse_from_str("2020-01-02 03:04:05", "%Y-%m-%d %H:%M:%S").unwrap();
+        LegacyTaskDTO {
+            id,
+            title: format!("task {}", id),
+            priority: 20,
+            cost: 3,
+            created_at,
+            closed_at: is_closed.then_some(created_at),
+        }
+    }
+
+    #[test]
+    fn test_execute() {
+        let task_repository = TaskRepository::new(Connection::open_in_memory().unwrap());
+        task_repository.create_table_if_not_exists().unwrap();
+        let component = MigrateLegacyUseCaseComponentImpl { task_repository };
+
+        let got = <MigrateLegacyUseCaseComponentImpl as MigrateLegacyUseCase>::execute(
+            &component,
+            MigrateLegacyUseCaseInput {
+                tasks: vec![legacy_task(3, false), legacy_task(7, true)],
+            },
+        )
+        .unwrap();
+        assert_eq!(
+            got,
+            MigrateLegacyUseCaseOutput {
+                migrated: vec![
+                    MigratedTaskDTO {
+                        legacy_id: 3,
+                        id: 1
+                    },
+                    MigratedTaskDTO {
+                        legacy_id: 7,
+                        id: 2
+                    },
+                ],
+                skipped: vec![],
+            }
+        );
+
+        let task = component
+            .task_repository
+            .load_by_sequential_id(SequentialID::new(2))
+            .unwrap()
+            .unwrap();
+        assert_eq!(task.title(), "task 7");
+        assert_eq!(task.priority(), Priority::new(20));
+        assert_eq!(task.cost(), Cost::new(3));
+        assert!(task.is_closed());
+        assert_eq!(task.closed_at(), legacy_task(7, true).closed_at);
+        assert_eq!(task.tags(), &[Tag::new("legacy:7").unwrap()]);
+
+        let got = <MigrateLegacyUseCaseComponentImpl as MigrateLegacyUseCase>::execute(
+            &component,
+            MigrateLegacyUseCaseInput {
+                tasks: vec![
+                    legacy_task(3, false),
+                    legacy_task(7, true),
+                    legacy_task(8, false),
+                ],
+            },
+        )
+        .unwrap();
+        assert_eq!(
+            got,
+            MigrateLegacyUseCaseOutput {
+                migrated: vec![MigratedTaskDTO {
+                    legacy_id: 8,
+                    id: 3
+                }],
+                skipped: vec![3, 7],
+            }
+        );
+    }
+
+    #[test]
+    fn test_execute_skips_deleted_tasks() {
+        let task_repository = TaskRepository::new(Connection::open_in_memory().unwrap());
+        task_repository.create_table_if_not_exists().unwrap();
+        let component = MigrateLegacyUseCaseComponentImpl { task_repository };
+        let migrate = || {
+            <MigrateLegacyUseCaseComponentImpl as MigrateLegacyUseCase>::execute(
+                &component,
+                MigrateLegacyUseCaseInput {
+                    tasks: vec![legacy_task(1, false), legacy_task(2, false)],
+                },
+            )
+            .unwrap()
+        };
+        migrate();
+
+        let mut task = component
+            .task_repository
+            .load_by_sequential_id(SequentialID::new(1))
+            .unwrap()
+            .unwrap();
+        task.execute(TaskCommand::Delete).unwrap();
+        component.task_repository.save(&mut task).unwrap();
+
+        assert_eq!(
+            migrate(),
+            MigrateLegacyUseCaseOutput {
+                migrated: vec![],
+                skipped: vec![1, 2],
+            }
+        );
+    }
+}
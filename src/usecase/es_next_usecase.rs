@@ -0,0 +1,199 @@
+use serde::{Deserialize, Serialize};
+use std::rc::Rc;
+use std::time::Duration;
+
+use crate::Result;
+
+use crate::domain::es_task::{Energy, IESTaskRepository, IESTaskRepositoryComponent};
+use crate::domain::scoring::{ScoringStrategy, TaskView};
+
+/// DTO for input of NextTaskUseCase.
+#[derive(Debug)]
+pub struct NextTaskUseCaseInput {
+    /// energy available now. A task fits if it takes at most this, or is not marked.
+    pub energy: Option<Energy>,
+    /// time available now. The remaining time of a task must fit into it.
+    pub time: Option<Duration>,
+    /// minutes_per_cost converts a cost into time.
+    pub minutes_per_cost: u32,
+    pub strategy: Rc<dyn ScoringStrategy>,
+}
+
+/// DTO of a suggested task.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NextTaskDTO {
+    pub id: i64,
+    pub title: String,
+    pub cost: i32,
+    pub score: f64,
+    /// remaining is the estimated time left, the cost in time minus time already logged.
+    pub remaining: Duration,
+}
+
+/// Usecase to suggest tasks to do right now.
+pub trait NextTaskUseCase: IESTaskRepositoryComponent {
+    /// execute suggesting open tasks which fit the energy and the time, the best fit first.
+    /// The best fit has the highest score, then fills the most of the time.
+    /// Private tasks, habits and tasks deferred to someday are left out.
+    fn execute(&self, input: NextTaskUseCaseInput) -> Result<Vec<NextTaskDTO>> {
+        let per_cost = Duration::from_secs(u64::from(input.minutes_per_cost) * 60);
+        let mut suggestions = vec![];
+        self.repository().for_each_task(&mut |task| {
+            if task.is_closed() || task.is_private() || task.habit().is_some() || task.is_someday()
+            {
+                return Ok(());
+            }
+            if let (Some(available), Some(taken)) = (input.energy, Energy::of(task.tags())) {
+                if taken > available {
+                    return Ok(());
+                }
+            }
+
+            let cost = task.cost().to_i32();
+            let remaining = per_cost
+                .saturating_mul(cost.max(0) as u32)
+                .saturating_sub(task.elapsed_time());
+            if input.time.is_some_and(|time| remaining > time) {
+                return Ok(());
+            }
+
+            suggestions.push(NextTaskDTO {
+                id: task.sequential_id().to_i64(),
+                title: task.title().to_owned(),
+                cost,
+                score: input.strategy.score(&TaskView::from(&task)),
+                remaining,
+            });
+            Ok(())
+        })?;
+
+        suggestions.sort_by(|a, b| {
+            b.score
+                .total_cmp(&a.score)
+                .then(b.remaining.cmp(&a.remaining))
+                .then(a.id.cmp(&b.id))
+        });
+        Ok(suggestions)
+    }
+}
+
+impl<T: IESTaskRepositoryComponent> NextTaskUseCase for T {}
+
+/// NextTaskUseCaseComponent returns NextTaskUseCase.
+pub trait NextTaskUseCaseComponent {
+    type NextTaskUseCase: NextTaskUseCase;
+    fn next_task_usecase(&self) -> &Self::NextTaskUseCase;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::es_task::{ScoreWeights, SequentialID};
+    use crate::infra::sqlite::es_task_repository::TaskRepository;
+    use crate::usecase::es_add_task_usecase::{AddTaskUseCase, AddTaskUseCaseInput};
+    use crate::usecase::es_time_usecase::{LogTimeUseCase, LogTimeUseCaseInput};
+    use rusqlite::Connection;
+
+    struct NextTaskUseCaseComponentImpl {
+        task_repository: TaskRepository,
+    }
+
+    impl IESTaskRepositoryComponent for NextTaskUseCaseComponentImpl {
+        type Repository = TaskRepository;
+        fn repository(&self) -> &Self::Repository {
+            &self.task_repository
+        }
+    }
+
+    #[test]
+    fn test_execute() {
+        #[derive(Debug)]
+        struct TestCase {
+            args: (Option<Energy>, Option<u64>),
+            want: Vec<i64>,
+            name: String,
+        }
+
+        let task_repository = TaskRepository::new(Connection::open_in_memory().unwrap());
+        task_repository.create_table_if_not_exists().unwrap();
+        let component = NextTaskUseCaseComponentImpl { task_repository };
+
+        let tasks: [(i32, i32, &[&str]); 4] = [
+            (10, 2, &["energy:deep"]),
+            (10, 1, &["energy:shallow"]),
+            (20, 4, &[]),
+            (10, 2, &[]),
+        ];
+        for (priority, cost, tags) in tasks {
+            <NextTaskUseCaseComponentImpl as AddTaskUseCase>::execute(
+                &component,
+                AddTaskUseCaseInput {
+                    title: "title".to_owned(),
+                    priority: Some(priority),
+                    urgency: None,
+                    cost: Some(cost),
+                    tags: tags.iter().map(|t| t.to_string()).collect(),
+                    private: false,
+                    habit: None,
+                    due: None,
+                    someday: false,
+                    parent: None,
+                    rules: Default::default(),
+                    description: None,
+                },
+            )
+            .unwrap();
+        }
+        <NextTaskUseCaseComponentImpl as LogTimeUseCase>::execute(
+            &component,
+            LogTimeUseCaseInput {
+                sequential_id: SequentialID::new(3),
+                duration: Duration::from_secs(30 * 60),
+            },
+        )
+        .unwrap();
+
+        let table = [
+            TestCase {
+                name: String::from("normal: anything"),
+                args: (None, None),
+                want: vec![3, 1, 4, 2],
+            },
+            TestCase {
+                name: String::from("normal: shallow energy"),
+                args: (Some(Energy::Shallow), None),
+                want: vec![3, 4, 2],
+            },
+            TestCase {
+                name: String::from("normal: 15 minutes counting logged time"),
+                args: (Some(Energy::Deep), Some(15)),
+                want: vec![3, 2],
+            },
+            TestCase {
+                name: String::from("normal: no time"),
+                args: (None, Some(5)),
+                want: vec![],
+            },
+        ];
+
+        for test_case in table {
+            let (energy, minutes) = test_case.args;
+            let got = <NextTaskUseCaseComponentImpl as NextTaskUseCase>::execute(
+                &component,
+                NextTaskUseCaseInput {
+                    energy,
+                    time: minutes.map(|m| Duration::from_secs(m * 60)),
+                    minutes_per_cost: 10,
+                    strategy: Rc::new(ScoreWeights::default()),
+                },
+            )
+            .unwrap();
+            assert_eq!(
+                got.iter().map(|s| s.id).collect::<Vec<_>>(),
+                test_case.want,
+                "Failed in the \"{}\".",
+                test_case.name
+            );
+        }
+    }
+}
@@ -0,0 +1,250 @@
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+use crate::Result;
+use chrono::{Local, Months, NaiveDate, NaiveDateTime, TimeZone};
+
+use crate::domain::es_task::{IESTaskRepository, IESTaskRepositoryComponent};
+
+/// BIGGEST_LIMIT is the number of tasks listed as the biggest of the month.
+pub const BIGGEST_LIMIT: usize = 5;
+
+/// DTO for input of MonthReviewUseCase.
+#[derive(Debug)]
+pub struct MonthReviewUseCaseInput {
+    /// month is the first day of the month in local time.
+    pub month: NaiveDate,
+}
+
+/// DTO of a task mentioned in the review.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReviewTaskDTO {
+    pub id: i64,
+    pub title: String,
+    pub cost: i32,
+    /// elapsed is time logged on the task within the month.
+    pub elapsed: Duration,
+}
+
+/// DTO of the review of a month.
+#[derive(Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MonthReviewDTO {
+    pub month: NaiveDate,
+    pub created: usize,
+    pub closed: usize,
+    /// elapsed is time logged on all tasks within the month.
+    pub elapsed: Duration,
+    /// biggest is tasks with the most time logged within the month, from the longest.
+    pub biggest: Vec<ReviewTaskDTO>,
+    /// carried_over is tasks created by the end of the month and still open at the end.
+    pub carried_over: Vec<ReviewTaskDTO>,
+}
+
+/// Usecase to review a month for a retrospective.
+pub trait MonthReviewUseCase: IESTaskRepositoryComponent {
+    /// execute reviewing the month. Days are in local time and private tasks are left out.
+    fn execute(&self, input: MonthReviewUseCaseInput) -> Result<MonthReviewDTO> {
+        let first = input.month;
+        let next = first
+            .checked_add_months(Months::new(1))
+            .unwrap_or(NaiveDate::MAX);
+        let within = |utc: NaiveDateTime| (first..next).contains(&local_date(utc));
+
+        let mut review = MonthReviewDTO {
+            month: first,
+            created: 0,
+            closed: 0,
+            elapsed: Duration::ZERO,
+            biggest: vec![],
+            carried_over: vec![],
+        };
+        for sequential_id in self.repository().load_all_sequential_ids()? {
+            // NOTE: deleted and archived tasks are not found, and are not reviewed.
+            let Some(task) = self.repository().load_by_sequential_id(sequential_id)? else {
+                continue;
+            };
+            if task.is_private() {
+                continue;
+            }
+
+            let elapsed: Duration = task
+                .time_logs()
+                .iter()
+                .filter(|log| within(log.logged_on))
+                .map(|log| log.duration)
+                .sum();
+            let dto = ReviewTaskDTO {
+                id: task.sequential_id().to_i64(),
+                title: task.title().to_owned(),
+                cost: task.cost().to_i32(),
+                elapsed,
+            };
+            review.elapsed += elapsed;
+
+            if within(task.created_at()) {
+                review.created += 1;
+            }
+            if task.closed_at().is_some_and(within) {
+                review.closed += 1;
+            }
+            let closed_by_end = task.closed_at().is_some_and(|c| local_date(c) < next);
+            if local_date(task.created_at()) < next && !closed_by_end {
+                review.carried_over.push(dto.clone());
+            }
+            if !elapsed.is_zero() {
+                review.biggest.push(dto);
+            }
+        }
+
+        review.biggest.sort_by_key(|t| std::cmp::Reverse(t.elapsed));
+        review.biggest.truncate(BIGGEST_LIMIT);
+        Ok(review)
+    }
+}
+
+impl<T: IESTaskRepositoryComponent> MonthReviewUseCase for T {}
+
+/// MonthReviewUseCaseComponent returns MonthReviewUseCase.
+pub trait MonthReviewUseCaseComponent {
+    type MonthReviewUseCase: MonthReviewUseCase;
+    fn month_review_usecase(&self) -> &Self::MonthReviewUseCase;
+}
+
+/// local_date converts the time in UTC into the local date.
+fn local_date(utc: NaiveDateTime) -> NaiveDate {
+    Local.from_utc_datetime(&utc).date_naive()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ddd::component::{AggregateID, AggregateRoot, Repository};
+    use crate::domain::es_task::{SequentialID, Task, TaskCommand, TaskSource};
+    use crate::infra::sqlite::es_task_repository::TaskRepository;
+    use crate::usecase::es_time_usecase::{LogTimeUseCase, LogTimeUseCaseInput};
+    use chrono::Datelike;
+    use rusqlite::Connection;
+
+    struct MonthReviewUseCaseComponentImpl {
+        task_repository: TaskRepository,
+    }
+
+    impl IESTaskRepositoryComponent for MonthReviewUseCaseComponentImpl {
+        type Repository = TaskRepository;
+        fn repository(&self) -> &Self::Repository {
+            &self.task_repository
+        }
+    }
+
+    fn local(s: &str) -> NaiveDateTime {
+        let local = NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M:%S").unwrap();
+        Local.from_local_datetime(&local).unwrap().naive_utc()
+    }
+
+    #[test]
+    fn test_execute() {
+        let task_repository = TaskRepository::new(Connection::open_in_memory().unwrap());
+        task_repository.create_table_if_not_exists().unwrap();
+        let component = MonthReviewUseCaseComponentImpl { task_repository };
+
+        let tasks = [
+            (
+                "closed in the month",
+                "2024-04-10 12:00:00",
+                Some("2024-05-20 12:00:00"),
+                false,
+            ),
+            ("created in the month", "2024-05-02 12:00:00", None, false),
+            (
+                "closed after the month",
+                "2024-05-03 12:00:00",
+                Some("2024-06-04 12:00:00"),
+                false,
+            ),
+            (
+                "created after the month",
+                "2024-06-01 12:00:00",
+                None,
+                false,
+            ),
+            ("private", "2024-05-05 12:00:00", None, true),
+            ("deleted", "2024-05-06 12:00:00", None, false),
+        ];
+        for (title, created_at, closed_at, private) in tasks {
+            let aggregate_id = AggregateID::new();
+            component
+                .task_repository
+                .save_new(aggregate_id, &|sequential_id| {
+                    let mut task = Task::import(
+                        TaskSource {
+                            aggregate_id,
+                            sequential_id,
+                            title: title.to_owned(),
+                            priority: None,
+                            cost: None,
+                            tags: vec![],
+                        },
+                        local(created_at),
+                        closed_at.map(local),
+                    );
+                    if private {
+                        task.make_private();
+                    }
+                    task
+                })
+                .unwrap();
+        }
+
+        let mut deleted = component
+            .task_repository
+            .load_by_sequential_id(SequentialID::new(6))
+            .unwrap()
+            .unwrap();
+        deleted.execute(TaskCommand::Delete).unwrap();
+        component.task_repository.save(&mut deleted).unwrap();
+
+        let review = |month| {
+            <MonthReviewUseCaseComponentImpl as MonthReviewUseCase>::execute(
+                &component,
+                MonthReviewUseCaseInput { month },
+            )
+            .unwrap()
+        };
+        let task = |id: i64, title: &str, elapsed: u64| ReviewTaskDTO {
+            id,
+            title: title.to_owned(),
+            cost: 10,
+            elapsed: Duration::from_secs(elapsed),
+        };
+
+        let may = NaiveDate::from_ymd_opt(2024, 5, 1).unwrap();
+        assert_eq!(
+            review(may),
+            MonthReviewDTO {
+                month: may,
+                created: 2,
+                closed: 1,
+                elapsed: Duration::ZERO,
+                biggest: vec![],
+                carried_over: vec![
+                    task(2, "created in the month", 0),
+                    task(3, "closed after the month", 0),
+                ],
+            }
+        );
+
+        <MonthReviewUseCaseComponentImpl as LogTimeUseCase>::execute(
+            &component,
+            LogTimeUseCaseInput {
+                sequential_id: SequentialID::new(2),
+                duration: Duration::from_secs(3600),
+            },
+        )
+        .unwrap();
+        let this_month = Local::now().date_naive().with_day(1).unwrap();
+        let got = review(this_month);
+        assert_eq!(got.elapsed, Duration::from_secs(3600));
+        assert_eq!(got.biggest, vec![task(2, "created in the month", 3600)]);
+        assert_eq!(review(may).elapsed, Duration::ZERO);
+    }
+}
@@ -0,0 +1,188 @@
+use regex::Regex;
+
+use crate::Result;
+
+use crate::ddd::component::AggregateRoot;
+use crate::domain::es_task::{
+    IESTaskRepository, IESTaskRepositoryComponent, Priority, Tag, Task, TaskCommand,
+};
+
+/// Rule tags and raises the priority of tasks meeting its conditions.
+#[derive(Debug, Clone, Default)]
+pub struct Rule {
+    /// title matches somewhere in titles of tasks. Any title meets it if None.
+    pub title: Option<Regex>,
+    /// tags are had by tasks. Any task meets it if empty.
+    pub tags: Vec<Tag>,
+    /// add_tags are added to the task.
+    pub add_tags: Vec<Tag>,
+    /// min_priority raises the priority of the task to at least it.
+    pub min_priority: Option<Priority>,
+}
+
+impl Rule {
+    /// is_met returns whether the task meets all of the conditions.
+    fn is_met(&self, task: &Task) -> bool {
+        self.title.as_ref().is_none_or(|t| t.is_match(task.title()))
+            && self.tags.iter().all(|t| task.tags().contains(t))
+    }
+}
+
+/// Rules are rules of the config in order. No rule applies by default.
+#[derive(Debug, Clone, Default)]
+pub struct Rules(pub Vec<Rule>);
+
+impl Rules {
+    /// apply tags the task and raises its priority by rules which the task meets, and returns
+    /// whether anything changes. Conditions see the task before any rule is applied, so that
+    /// the order of rules does not matter.
+    pub fn apply(&self, task: &mut Task) -> Result<bool> {
+        let met: Vec<&Rule> = self.0.iter().filter(|r| r.is_met(task)).collect();
+        let mut changed = false;
+        for rule in met {
+            for tag in &rule.add_tags {
+                if !task.tags().contains(tag) {
+                    task.execute(TaskCommand::Tag { tag: tag.clone() })?;
+                    changed = true;
+                }
+            }
+            if let Some(priority) = rule.min_priority.filter(|p| task.priority() < *p) {
+                task.execute(TaskCommand::RescorePriority { priority })?;
+                changed = true;
+            }
+        }
+        Ok(changed)
+    }
+}
+
+/// DTO for input of ApplyRulesUseCase.
+#[derive(Debug)]
+pub struct ApplyRulesUseCaseInput {
+    pub rules: Rules,
+}
+
+/// Usecase to apply rules to tasks added before the rules.
+pub trait ApplyRulesUseCase: IESTaskRepositoryComponent {
+    /// execute applying the rules to open tasks in one transaction, and returns the changed ids.
+    /// Locked tasks are skipped.
+    fn execute(&self, input: ApplyRulesUseCaseInput) -> Result<Vec<i64>> {
+        let mut tasks = vec![];
+        self.repository().for_each_task(&mut |mut task| {
+            if task.is_closed() || task.is_locked() {
+                return Ok(());
+            }
+            if input.rules.apply(&mut task)? {
+                tasks.push(task);
+            }
+            Ok(())
+        })?;
+
+        self.repository().save_all(&mut tasks)?;
+        Ok(tasks.iter().map(|t| t.sequential_id().to_i64()).collect())
+    }
+}
+
+impl<T: IESTaskRepositoryComponent> ApplyRulesUseCase for T {}
+
+/// ApplyRulesUseCaseComponent returns ApplyRulesUseCase.
+pub trait ApplyRulesUseCaseComponent {
+    type ApplyRulesUseCase: ApplyRulesUseCase;
+    fn apply_rules_usecase(&self) -> &Self::ApplyRulesUseCase;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::es_task::SequentialID;
+    use crate::infra::sqlite::es_task_repository::TaskRepository;
+    use crate::usecase::es_add_task_usecase::{AddTaskUseCase, AddTaskUseCaseInput};
+    use rusqlite::Connection;
+
+    struct ApplyRulesUseCaseComponentImpl {
+        task_repository: TaskRepository,
+    }
+
+    impl IESTaskRepositoryComponent for ApplyRulesUseCaseComponentImpl {
+        type Repository = TaskRepository;
+        fn repository(&self) -> &Self::Repository {
+            &self.task_repository
+        }
+    }
+
+    fn rules() -> Rules {
+        let tag = |t| Tag::new(t).unwrap();
+        Rules(vec![
+            Rule {
+                title: Some(Regex::new("bug|fix").unwrap()),
+                add_tags: vec![tag("bug")],
+                min_priority: Some(Priority::new(60)),
+                ..Default::default()
+            },
+            Rule {
+                tags: vec![tag("bug")],
+                add_tags: vec![tag("triage")],
+                ..Default::default()
+            },
+        ])
+    }
+
+    #[test]
+    fn test_execute() {
+        let task_repository = TaskRepository::new(Connection::open_in_memory().unwrap());
+        task_repository.create_table_if_not_exists().unwrap();
+        let component = ApplyRulesUseCaseComponentImpl { task_repository };
+
+        // NOTE: (title, tags, priority, rules at add-time)
+        let tasks = [
+            ("fix the login", vec![], 10, true),
+            ("fix the logout", vec!["bug".to_owned()], 80, false),
+            ("write docs", vec![], 10, false),
+        ];
+        for (title, tags, priority, with_rules) in tasks {
+            <ApplyRulesUseCaseComponentImpl as AddTaskUseCase>::execute(
+                &component,
+                AddTaskUseCaseInput {
+                    title: title.to_owned(),
+                    priority: Some(priority),
+                    urgency: None,
+                    cost: None,
+                    tags,
+                    private: false,
+                    habit: None,
+                    due: None,
+                    someday: false,
+                    parent: None,
+                    rules: if with_rules {
+                        rules()
+                    } else {
+                        Rules::default()
+                    },
+                    description: None,
+                },
+            )
+            .unwrap();
+        }
+
+        let load = |id| {
+            let task = component
+                .task_repository
+                .load_by_sequential_id(SequentialID::new(id))
+                .unwrap()
+                .unwrap();
+            let tags: Vec<String> = task.tags().iter().map(|t| t.as_str().to_owned()).collect();
+            (tags, task.priority().to_i32())
+        };
+        // NOTE: the triage rule sees the task before the bug rule tags it.
+        assert_eq!(load(1), (vec!["bug".to_owned()], 60));
+
+        let got = <ApplyRulesUseCaseComponentImpl as ApplyRulesUseCase>::execute(
+            &component,
+            ApplyRulesUseCaseInput { rules: rules() },
+        )
+        .unwrap();
+        assert_eq!(got, vec![1, 2]);
+        assert_eq!(load(1), (vec!["bug".to_owned(), "triage".to_owned()], 60));
+        assert_eq!(load(2), (vec!["bug".to_owned(), "triage".to_owned()], 80));
+        assert_eq!(load(3), (vec![], 10));
+    }
+}
@@ -0,0 +1,178 @@
+use crate::Result;
+
+use crate::domain::es_task::{IESTaskRepository, IESTaskRepositoryComponent, SearchField};
+
+/// DTO for input of SearchTaskUseCase.
+#[derive(Debug, Default)]
+pub struct SearchTaskUseCaseInput {
+    /// query is words which the field has, such as `fix test`. A word matches words it begins.
+    pub query: String,
+    pub field: SearchField,
+    /// closed tasks are found too if true.
+    pub include_closed: bool,
+    /// private tasks are found too if true.
+    pub include_private: bool,
+}
+
+/// DTO of a task found by a search.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SearchMatchDTO {
+    pub id: i64,
+    pub title: String,
+    pub is_closed: bool,
+    /// snippet is the text around the matches of the field matching best.
+    pub snippet: Vec<SnippetPartDTO>,
+}
+
+/// DTO of a piece of a snippet, which is a matched word or not.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SnippetPartDTO {
+    pub text: String,
+    pub is_match: bool,
+}
+
+/// Usecase to search tasks by their titles and descriptions.
+pub trait SearchTaskUseCase: IESTaskRepositoryComponent {
+    /// execute searching tasks, the best match first.
+    fn execute(&self, input: SearchTaskUseCaseInput) -> Result<Vec<SearchMatchDTO>> {
+        Ok(self
+            .repository()
+            .search(&input.query, input.field)?
+            .into_iter()
+            .filter(|m| input.include_closed || !m.task.is_closed)
+            .filter(|m| input.include_private || !m.task.is_private)
+            .map(|m| SearchMatchDTO {
+                id: m.task.sequential_id.to_i64(),
+                title: m.task.title,
+                is_closed: m.task.is_closed,
+                snippet: m
+                    .snippet
+                    .into_iter()
+                    .map(|p| SnippetPartDTO {
+                        text: p.text,
+                        is_match: p.is_match,
+                    })
+                    .collect(),
+            })
+            .collect())
+    }
+}
+
+impl<T: IESTaskRepositoryComponent> SearchTaskUseCase for T {}
+
+/// SearchTaskUseCaseComponent returns SearchTaskUseCase.
+pub trait SearchTaskUseCaseComponent {
+    type SearchTaskUseCase: SearchTaskUseCase;
+    fn search_task_usecase(&self) -> &Self::SearchTaskUseCase;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::es_task::SequentialID;
+    use crate::infra::sqlite::es_task_repository::TaskRepository;
+    use crate::usecase::es_add_task_usecase::{AddTaskUseCase, AddTaskUseCaseInput};
+    use crate::usecase::es_close_task_usecase::{CloseTaskUseCase, CloseTaskUseCaseInput};
+    use rusqlite::Connection;
+
+    struct SearchTaskUseCaseComponentImpl {
+        task_repository: TaskRepository,
+    }
+
+    impl IESTaskRepositoryComponent for SearchTaskUseCaseComponentImpl {
+        type Repository = TaskRepository;
+        fn repository(&self) -> &Self::Repository {
+            &self.task_repository
+        }
+    }
+
+    #[test]
+    fn test_execute() {
+        #[derive(Debug)]
+        struct TestCase {
+            args: SearchTaskUseCaseInput,
+            want: Vec<i64>,
+            name: String,
+        }
+
+        let task_repository = TaskRepository::new(Connection::open_in_memory().unwrap());
+        task_repository.create_table_if_not_exists().unwrap();
+        let component = SearchTaskUseCaseComponentImpl { task_repository };
+        for (title, description, private) in [
+            ("fix the test", None, false),
+            ("write docs", Some("a test of the docs"), false),
+            ("closed test", None, false),
+            ("private test", None, true),
+        ] {
+            <SearchTaskUseCaseComponentImpl as AddTaskUseCase>::execute(
+                &component,
+                AddTaskUseCaseInput {
+                    title: title.to_owned(),
+                    priority: None,
+                    urgency: None,
+                    cost: None,
+                    tags: vec![],
+                    private,
+                    habit: None,
+                    due: None,
+                    someday: false,
+                    parent: None,
+                    rules: Default::default(),
+                    description: description.map(str::to_owned),
+                },
+            )
+            .unwrap();
+        }
+        <SearchTaskUseCaseComponentImpl as CloseTaskUseCase>::execute(
+            &component,
+            CloseTaskUseCaseInput {
+                sequential_id: SequentialID::new(3),
+                unlock: false,
+                cascade: false,
+            },
+        )
+        .unwrap();
+
+        let table = [
+            TestCase {
+                name: String::from("normal: open public tasks"),
+                args: SearchTaskUseCaseInput {
+                    query: String::from("test"),
+                    ..Default::default()
+                },
+                want: vec![1, 2],
+            },
+            TestCase {
+                name: String::from("normal: descriptions only"),
+                args: SearchTaskUseCaseInput {
+                    query: String::from("test"),
+                    field: SearchField::Description,
+                    ..Default::default()
+                },
+                want: vec![2],
+            },
+            TestCase {
+                name: String::from("normal: closed and private tasks too"),
+                args: SearchTaskUseCaseInput {
+                    query: String::from("test"),
+                    field: SearchField::Title,
+                    include_closed: true,
+                    include_private: true,
+                },
+                want: vec![3, 4, 1],
+            },
+        ];
+
+        for test_case in table {
+            let got: Vec<i64> = <SearchTaskUseCaseComponentImpl as SearchTaskUseCase>::execute(
+                &component,
+                test_case.args,
+            )
+            .unwrap()
+            .iter()
+            .map(|m| m.id)
+            .collect();
+            assert_eq!(got, test_case.want, "Failed in the \"{}\".", test_case.name);
+        }
+    }
+}
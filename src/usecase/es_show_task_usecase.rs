@@ -0,0 +1,278 @@
+use std::time::Duration;
+
+use crate::Result;
+use chrono::{Local, NaiveDate, NaiveDateTime, TimeZone};
+
+use crate::domain::es_task::{
+    IESTaskRepository, IESTaskRepositoryComponent, SequentialID, TaskDomainEvent,
+};
+use crate::usecase::error::UseCaseError;
+use crate::usecase::es_list_task_usecase::WaitingOnDTO;
+
+/// DTO for input of ShowTaskUseCase.
+#[derive(Debug)]
+pub struct ShowTaskUseCaseInput {
+    pub sequential_id: SequentialID,
+}
+
+/// DTO of the full detail of a task.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TaskDetailDTO {
+    pub id: i64,
+    pub title: String,
+    /// description may span lines.
+    pub description: Option<String>,
+    pub priority: i32,
+    pub urgency: i32,
+    pub cost: i32,
+    pub tags: Vec<String>,
+    pub is_closed: bool,
+    pub is_locked: bool,
+    pub is_private: bool,
+    pub in_inbox: bool,
+    pub is_someday: bool,
+    pub is_deleted: bool,
+    pub is_archived: bool,
+    pub due_on: Option<NaiveDate>,
+    pub parent: Option<i64>,
+    pub follows: Option<i64>,
+    pub waiting_on: Option<WaitingOnDTO>,
+    pub elapsed: Duration,
+    pub pomodoros: u32,
+    /// created_at in local time.
+    pub created_at: NaiveDateTime,
+    /// closed_at in local time.
+    pub closed_at: Option<NaiveDateTime>,
+    /// events are what changed the task, in the order they occurred.
+    pub events: Vec<EventDTO>,
+}
+
+/// DTO of an event of a task.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EventDTO {
+    /// occurred_at in local time.
+    pub occurred_at: NaiveDateTime,
+    /// change describes the event, such as "priority set to 80".
+    pub change: String,
+}
+
+/// Usecase to show the full detail of a task.
+pub trait ShowTaskUseCase: IESTaskRepositoryComponent {
+    /// execute loading a task with its events. A deleted task is shown too, so that its record can
+    /// still be audited.
+    fn execute(&self, input: ShowTaskUseCaseInput) -> Result<TaskDetailDTO> {
+        let task = self
+            .repository()
+            .load_any_by_sequential_id(input.sequential_id)?
+            .ok_or(UseCaseError::NotFound(input.sequential_id.to_i64()))?;
+        let local = |utc: NaiveDateTime| Local.from_utc_datetime(&utc).naive_local();
+        let events = self
+            .repository()
+            .load_events(task.aggregate_id())?
+            .iter()
+            .map(|e| EventDTO {
+                occurred_at: local(e.occurred_on()),
+                change: describe(e.event()),
+            })
+            .collect();
+
+        Ok(TaskDetailDTO {
+            id: task.sequential_id().to_i64(),
+            title: task.title().to_owned(),
+            description: task.description().map(str::to_owned),
+            priority: task.priority().to_i32(),
+            urgency: task.urgency().to_i32(),
+            cost: task.cost().to_i32(),
+            tags: task.tags().iter().map(|t| t.as_str().to_owned()).collect(),
+            is_closed: task.is_closed(),
+            is_locked: task.is_locked(),
+            is_private: task.is_private(),
+            in_inbox: task.is_in_inbox(),
+            is_someday: task.is_someday(),
+            is_deleted: task.is_deleted(),
+            is_archived: task.is_archived(),
+            due_on: task.due().map(|d| d.to_naive_date()),
+            parent: task.parent().map(|p| p.to_i64()),
+            follows: task.follows().map(|f| f.to_i64()),
+            waiting_on: task.waiting_on().map(|w| WaitingOnDTO {
+                person: w.person().to_owned(),
+                follow_up_on: w.follow_up_on(),
+            }),
+            elapsed: task.elapsed_time(),
+            pomodoros: task.pomodoros(),
+            created_at: local(task.created_at()),
+            closed_at: task.closed_at().map(local),
+            events,
+        })
+    }
+}
+
+impl<T: IESTaskRepositoryComponent> ShowTaskUseCase for T {}
+
+/// ShowTaskUseCaseComponent returns ShowTaskUseCase.
+pub trait ShowTaskUseCaseComponent {
+    type ShowTaskUseCase: ShowTaskUseCase;
+    fn show_task_usecase(&self) -> &Self::ShowTaskUseCase;
+}
+
+/// describe tells what the event changed in words.
+fn describe(event: &TaskDomainEvent) -> String {
+    match event {
+        TaskDomainEvent::Created { .. } => "created".to_owned(),
+        TaskDomainEvent::Closed => "closed".to_owned(),
+        TaskDomainEvent::TitleEdited { title } => format!("title set to `{}`", title),
+        TaskDomainEvent::DescriptionEdited {
+            description: Some(_),
+        } => "description edited".to_owned(),
+        TaskDomainEvent::DescriptionEdited { description: None } => {
+            "description cleared".to_owned()
+        }
+        TaskDomainEvent::CostRescored { cost } => format!("cost set to {}", cost),
+        TaskDomainEvent::PriorityRescored { priority } => format!("priority set to {}", priority),
+        TaskDomainEvent::UrgencyRescored { urgency } => format!("urgency set to {}", urgency),
+        TaskDomainEvent::Tagged { tag } => format!("tagged `{}`", tag.as_str()),
+        TaskDomainEvent::Untagged { tag } => format!("untagged `{}`", tag.as_str()),
+        TaskDomainEvent::Locked => "locked".to_owned(),
+        TaskDomainEvent::Unlocked => "unlocked".to_owned(),
+        TaskDomainEvent::MadePrivate => "made private".to_owned(),
+        TaskDomainEvent::MadePublic => "made public".to_owned(),
+        TaskDomainEvent::TimeLogged { seconds } => format!("{} logged", minutes(*seconds)),
+        TaskDomainEvent::TimerStarted => "timer started".to_owned(),
+        TaskDomainEvent::TimerStopped { seconds } => {
+            format!("timer stopped after {}", minutes(*seconds))
+        }
+        TaskDomainEvent::PomodoroCompleted => "pomodoro completed".to_owned(),
+        TaskDomainEvent::MadeHabit { target } => {
+            format!("made a habit of {}/{}", target.times(), target.period())
+        }
+        TaskDomainEvent::CheckedIn => "checked in".to_owned(),
+        TaskDomainEvent::WaitedOn { waiting_on } => match waiting_on.follow_up_on() {
+            Some(day) => format!("waiting on {}, follow up on {}", waiting_on.person(), day),
+            None => format!("waiting on {}", waiting_on.person()),
+        },
+        TaskDomainEvent::StoppedWaiting => "stopped waiting".to_owned(),
+        TaskDomainEvent::FollowedUp { predecessor } => format!("follows up #{}", predecessor),
+        TaskDomainEvent::ParentAssigned { parent } => format!("made a subtask of #{}", parent),
+        TaskDomainEvent::Captured => "captured into the inbox".to_owned(),
+        TaskDomainEvent::Triaged => "triaged".to_owned(),
+        TaskDomainEvent::DueDateSet { due: Some(due) } => format!("due on {}", due),
+        TaskDomainEvent::DueDateSet { due: None } => "due date cleared".to_owned(),
+        TaskDomainEvent::Deferred => "deferred to someday".to_owned(),
+        TaskDomainEvent::Promoted => "promoted".to_owned(),
+        TaskDomainEvent::Deleted => "deleted".to_owned(),
+        TaskDomainEvent::Archived => "archived".to_owned(),
+    }
+}
+
+/// minutes formats seconds in hours and minutes, e.g. `1h30m`.
+fn minutes(seconds: u64) -> String {
+    match (seconds / 3600, seconds / 60 % 60) {
+        (0, m) => format!("{}m", m),
+        (h, 0) => format!("{}h", h),
+        (h, m) => format!("{}h{}m", h, m),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::infra::sqlite::es_task_repository::TaskRepository;
+    use crate::usecase::es_add_task_usecase::{AddTaskUseCase, AddTaskUseCaseInput};
+    use crate::usecase::es_edit_task_usecase::{EditTaskUseCase, EditTaskUseCaseInput};
+    use rusqlite::Connection;
+
+    struct ShowTaskUseCaseComponentImpl {
+        task_repository: TaskRepository,
+    }
+
+    impl IESTaskRepositoryComponent for ShowTaskUseCaseComponentImpl {
+        type Repository = TaskRepository;
+        fn repository(&self) -> &Self::Repository {
+            &self.task_repository
+        }
+    }
+
+    #[test]
+    fn test_execute() {
+        let task_repository = TaskRepository::new(Connection::open_in_memory().unwrap());
+        task_repository.create_table_if_not_exists().unwrap();
+        let component = ShowTaskUseCaseComponentImpl { task_repository };
+
+        let sequential_id = <ShowTaskUseCaseComponentImpl as AddTaskUseCase>::execute(
+            &component,
+            AddTaskUseCaseInput {
+                title: "fix login bug".to_owned(),
+                description: Some("sign in twice\nto see it".to_owned()),
+                priority: Some(80),
+                urgency: None,
+                cost: Some(3),
+                tags: vec!["bug".to_owned()],
+                private: false,
+                habit: None,
+                due: None,
+                someday: false,
+                parent: None,
+                rules: Default::default(),
+            },
+        )
+        .unwrap();
+
+        let show = || {
+            <ShowTaskUseCaseComponentImpl as ShowTaskUseCase>::execute(
+                &component,
+                ShowTaskUseCaseInput { sequential_id },
+            )
+            .unwrap()
+        };
+        let detail = show();
+        assert_eq!(detail.title, "fix login bug");
+        assert_eq!(
+            detail.description.as_deref(),
+            Some("sign in twice\nto see it")
+        );
+        assert_eq!((detail.priority, detail.cost), (80, 3));
+        assert_eq!(detail.tags, vec!["bug"]);
+
+        <ShowTaskUseCaseComponentImpl as EditTaskUseCase>::execute(
+            &component,
+            EditTaskUseCaseInput {
+                sequential_id,
+                title: None,
+                description: Some(None),
+                priority: None,
+                urgency: None,
+                cost: None,
+                unlock: false,
+                private: None,
+                due: None,
+            },
+        )
+        .unwrap();
+        let detail = show();
+        assert_eq!(detail.description, None);
+        assert_eq!(
+            detail
+                .events
+                .iter()
+                .map(|e| e.change.as_str())
+                .collect::<Vec<_>>(),
+            vec![
+                "created",
+                "title set to `fix login bug`",
+                "priority set to 80",
+                "cost set to 3",
+                "tagged `bug`",
+                "description edited",
+                "description cleared",
+            ]
+        );
+
+        assert!(<ShowTaskUseCaseComponentImpl as ShowTaskUseCase>::execute(
+            &component,
+            ShowTaskUseCaseInput {
+                sequential_id: SequentialID::new(2),
+            },
+        )
+        .is_err());
+    }
+}
@@ -0,0 +1,296 @@
+use serde::{Deserialize, Serialize};
+use std::rc::Rc;
+
+use crate::Result;
+use chrono::{Days, Local, NaiveDate, NaiveDateTime, TimeZone};
+
+use crate::domain::es_task::{IESTaskRepository, IESTaskRepositoryComponent};
+use crate::domain::scoring::{ScoringStrategy, TaskView};
+use crate::usecase::error::UseCaseError;
+
+/// HISTORY_WEEKS is the number of weeks before today whose closed costs make the throughput.
+pub const HISTORY_WEEKS: u64 = 8;
+
+/// DTO for input of SimulateUseCase.
+#[derive(Debug)]
+pub struct SimulateUseCaseInput {
+    /// today in local time. The simulation starts today, and the history ends yesterday.
+    pub today: NaiveDate,
+    /// until is the last day of the simulation.
+    pub until: NaiveDate,
+    /// added_cost_per_day is capacity added on top of the throughput, such as more hours a day.
+    pub added_cost_per_day: f64,
+    /// minutes_per_cost converts time logged on a task into cost already spent.
+    pub minutes_per_cost: u32,
+    /// strategy orders open tasks, the highest score to be done first.
+    pub strategy: Rc<dyn ScoringStrategy>,
+}
+
+/// Outlook is whether a task will be closed by the last day.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Outlook {
+    /// closed even at the low throughput.
+    Done,
+    /// closed at the high throughput, but not at the low.
+    AtRisk,
+    /// not closed even at the high throughput.
+    Slip,
+}
+
+/// DTO of throughput in cost per day, the low and high quartiles and the median of weeks.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ThroughputDTO {
+    pub low: f64,
+    pub median: f64,
+    pub high: f64,
+}
+
+/// DTO of an open task in the simulation.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SimulatedTaskDTO {
+    pub id: i64,
+    pub title: String,
+    /// remaining is the cost minus time already logged on the task.
+    pub remaining: i32,
+    /// cumulative is the remaining cost of this task and all tasks before it.
+    pub cumulative: i64,
+    /// finish_on is the projected day to close the task at the median throughput.
+    /// It is None if nothing has been closed in the history and no capacity is added.
+    pub finish_on: Option<NaiveDate>,
+    pub outlook: Outlook,
+}
+
+/// DTO of the simulation.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SimulationDTO {
+    pub until: NaiveDate,
+    /// throughput includes the added capacity.
+    pub throughput: ThroughputDTO,
+    pub tasks: Vec<SimulatedTaskDTO>,
+}
+
+/// Usecase to project which open tasks will be closed by a day.
+pub trait SimulateUseCase: IESTaskRepositoryComponent {
+    /// execute projecting open tasks in the order of the score on the throughput of past weeks.
+    /// Private tasks, habits, items in the inbox and tasks deferred to someday are left out.
+    /// Nothing is written, so that it is a what-if of the capacity.
+    /// It fails if until is before today, since every task would slip.
+    fn execute(&self, input: SimulateUseCaseInput) -> Result<SimulationDTO> {
+        if input.until < input.today {
+            return Err(UseCaseError::PastDay(input.until.to_string()).into());
+        }
+        let history_start = input
+            .today
+            .checked_sub_days(Days::new(HISTORY_WEEKS * 7))
+            .unwrap_or(NaiveDate::MIN);
+        let mut weekly = vec![0_i64; HISTORY_WEEKS as usize];
+        let mut open = vec![];
+        self.repository().for_each_task(&mut |task| {
+            if task.is_private()
+                || task.habit().is_some()
+                || task.is_in_inbox()
+                || task.is_someday()
+            {
+                return Ok(());
+            }
+            if let Some(closed_at) = task.closed_at() {
+                let closed_on = local_date(closed_at);
+                if (history_start..input.today).contains(&closed_on) {
+                    let week = (closed_on - history_start).num_days() / 7;
+                    weekly[week as usize] += i64::from(task.cost().to_i32().max(0));
+                }
+                return Ok(());
+            }
+
+            let spent =
+                task.elapsed_time().as_secs() / 60 / u64::from(input.minutes_per_cost.max(1));
+            let remaining = i64::from(task.cost().to_i32()) - spent as i64;
+            open.push((
+                input.strategy.score(&TaskView::from(&task)),
+                task.sequential_id().to_i64(),
+                task.title().to_owned(),
+                remaining.max(0) as i32,
+            ));
+            Ok(())
+        })?;
+
+        weekly.sort();
+        let per_day = |q: f64| {
+            let weeks = weekly[((weekly.len() - 1) as f64 * q).round() as usize];
+            weeks as f64 / 7.0 + input.added_cost_per_day
+        };
+        let throughput = ThroughputDTO {
+            low: per_day(0.25),
+            median: per_day(0.5),
+            high: per_day(0.75),
+        };
+
+        open.sort_by(|a, b| b.0.total_cmp(&a.0).then(a.1.cmp(&b.1)));
+        let days = (input.until - input.today).num_days().max(0) as f64;
+        let mut cumulative = 0;
+        let tasks = open
+            .into_iter()
+            .map(|(_, id, title, remaining)| {
+                cumulative += i64::from(remaining);
+                let cost = cumulative as f64;
+                let outlook = match cost {
+                    c if c <= throughput.low * days => Outlook::Done,
+                    c if c <= throughput.high * days => Outlook::AtRisk,
+                    _ => Outlook::Slip,
+                };
+                let finish_on = match throughput.median {
+                    rate if rate > 0.0 => input
+                        .today
+                        .checked_add_days(Days::new((cost / rate).ceil() as u64)),
+                    _ => None,
+                };
+                SimulatedTaskDTO {
+                    id,
+                    title,
+                    remaining,
+                    cumulative,
+                    finish_on,
+                    outlook,
+                }
+            })
+            .collect();
+
+        Ok(SimulationDTO {
+            until: input.until,
+            throughput,
+            tasks,
+        })
+    }
+}
+
+impl<T: IESTaskRepositoryComponent> SimulateUseCase for T {}
+
+/// SimulateUseCaseComponent returns SimulateUseCase.
+pub trait SimulateUseCaseComponent {
+    type SimulateUseCase: SimulateUseCase;
+    fn simulate_usecase(&self) -> &Self::SimulateUseCase;
+}
+
+/// local_date converts the time in UTC into the day in local time.
+fn local_date(utc: NaiveDateTime) -> NaiveDate {
+    Local.from_utc_datetime(&utc).date_naive()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ddd::component::AggregateID;
+    use crate::domain::es_task::{Cost, Priority, ScoreWeights, Task, TaskSource};
+    use crate::infra::sqlite::es_task_repository::TaskRepository;
+    use rusqlite::Connection;
+
+    struct SimulateUseCaseComponentImpl {
+        task_repository: TaskRepository,
+    }
+
+    impl IESTaskRepositoryComponent for SimulateUseCaseComponentImpl {
+        type Repository = TaskRepository;
+        fn repository(&self) -> &Self::Repository {
+            &self.task_repository
+        }
+    }
+
+    fn local(s: &str) -> NaiveDateTime {
+        let local = NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M:%S").unwrap();
+        Local.from_local_datetime(&local).unwrap().naive_utc()
+    }
+
+    #[test]
+    fn test_execute() {
+        let task_repository = TaskRepository::new(Connection::open_in_memory().unwrap());
+        task_repository.create_table_if_not_exists().unwrap();
+        let component = SimulateUseCaseComponentImpl { task_repository };
+
+        // NOTE: 14 cost is closed in each of the 8 weeks before 2024-06-01, 2 a day.
+        let first = NaiveDate::from_ymd_opt(2024, 4, 9).unwrap();
+        let mut tasks: Vec<(i32, i32, Option<String>)> = (0..8)
+            .map(|w| {
+                let closed_on = first + Days::new(w * 7);
+                (10, 14, Some(format!("{} 12:00:00", closed_on)))
+            })
+            .collect();
+        // NOTE: 2024-04-01 is before the history, so that it is not counted.
+        tasks.push((10, 100, Some("2024-04-01 12:00:00".to_owned())));
+        tasks.extend([(30, 10, None), (20, 10, None), (10, 30, None)]);
+        for (priority, cost, closed_at) in tasks {
+            let aggregate_id = AggregateID::new();
+            component
+                .task_repository
+                .save_new(aggregate_id, &|sequential_id| {
+                    Task::import(
+                        TaskSource {
+                            aggregate_id,
+                            sequential_id,
+                            title: format!("p{}", priority),
+                            priority: Some(Priority::new(priority)),
+                            cost: Some(Cost::new(cost)),
+                            tags: vec![],
+                        },
+                        local("2024-03-01 12:00:00"),
+                        closed_at.as_deref().map(local),
+                    )
+                })
+                .unwrap();
+        }
+
+        let simulate = |until, added_cost_per_day| {
+            <SimulateUseCaseComponentImpl as SimulateUseCase>::execute(
+                &component,
+                SimulateUseCaseInput {
+                    today: NaiveDate::from_ymd_opt(2024, 6, 1).unwrap(),
+                    until,
+                    added_cost_per_day,
+                    minutes_per_cost: 15,
+                    strategy: Rc::new(ScoreWeights::default()),
+                },
+            )
+        };
+        let until = NaiveDate::from_ymd_opt(2024, 6, 11).unwrap();
+
+        let got = simulate(until, 0.0).unwrap();
+        assert_eq!(got.throughput.median, 2.0);
+        assert_eq!(
+            got.tasks
+                .iter()
+                .map(|t| (t.title.as_str(), t.cumulative, t.finish_on, t.outlook))
+                .collect::<Vec<_>>(),
+            vec![
+                (
+                    "p30",
+                    10,
+                    NaiveDate::from_ymd_opt(2024, 6, 6),
+                    Outlook::Done
+                ),
+                (
+                    "p20",
+                    20,
+                    NaiveDate::from_ymd_opt(2024, 6, 11),
+                    Outlook::Done
+                ),
+                (
+                    "p10",
+                    50,
+                    NaiveDate::from_ymd_opt(2024, 6, 26),
+                    Outlook::Slip
+                ),
+            ]
+        );
+
+        let got = simulate(until, 3.0).unwrap();
+        assert_eq!(got.throughput.median, 5.0);
+        assert_eq!(got.tasks[2].outlook, Outlook::Done);
+
+        let today = NaiveDate::from_ymd_opt(2024, 6, 1).unwrap();
+        assert!(simulate(today, 0.0).is_ok());
+        let past = NaiveDate::from_ymd_opt(2020, 1, 1).unwrap();
+        assert_eq!(
+            simulate(past, 0.0).unwrap_err().to_string(),
+            UseCaseError::PastDay("2020-01-01".to_owned()).to_string()
+        );
+    }
+}
@@ -0,0 +1,119 @@
+use crate::Result;
+
+use crate::ddd::component::{AggregateRoot, Repository};
+use crate::domain::es_task::{
+    IESTaskRepository, IESTaskRepositoryComponent, SequentialID, TaskCommand,
+};
+use crate::usecase::error::UseCaseError;
+
+/// DTO for input of PromoteUseCase.
+#[derive(Debug)]
+pub struct PromoteUseCaseInput {
+    pub sequential_id: SequentialID,
+}
+
+/// Usecase to move a task deferred to someday into the active backlog.
+pub trait PromoteUseCase: IESTaskRepositoryComponent {
+    /// execute promoting the task. A locked task refuses it.
+    fn execute(&self, input: PromoteUseCaseInput) -> Result<SequentialID> {
+        let mut task = self
+            .repository()
+            .load_by_sequential_id(input.sequential_id)?
+            .ok_or(UseCaseError::NotFound(input.sequential_id.to_i64()))?;
+
+        if task.is_closed() {
+            return Err(UseCaseError::AlreadyClosed(task.sequential_id().to_i64()).into());
+        }
+        if !task.is_someday() {
+            return Err(UseCaseError::NotSomeday(task.sequential_id().to_i64()).into());
+        }
+        if task.is_locked() {
+            return Err(UseCaseError::Locked(task.sequential_id().to_i64()).into());
+        }
+
+        task.execute(TaskCommand::Promote)?;
+
+        self.repository().save(&mut task)?;
+        Ok(task.sequential_id())
+    }
+}
+
+impl<T: IESTaskRepositoryComponent> PromoteUseCase for T {}
+
+/// PromoteUseCaseComponent returns PromoteUseCase.
+pub trait PromoteUseCaseComponent {
+    type PromoteUseCase: PromoteUseCase;
+    fn promote_usecase(&self) -> &Self::PromoteUseCase;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::infra::sqlite::es_task_repository::TaskRepository;
+    use crate::usecase::es_add_task_usecase::{AddTaskUseCase, AddTaskUseCaseInput};
+    use crate::usecase::es_list_task_usecase::{ListTaskUseCase, ListTaskUseCaseInput};
+    use rusqlite::Connection;
+
+    struct PromoteUseCaseComponentImpl {
+        task_repository: TaskRepository,
+    }
+
+    impl IESTaskRepositoryComponent for PromoteUseCaseComponentImpl {
+        type Repository = TaskRepository;
+        fn repository(&self) -> &Self::Repository {
+            &self.task_repository
+        }
+    }
+
+    #[test]
+    fn test_execute() {
+        let task_repository = TaskRepository::new(Connection::open_in_memory().unwrap());
+        task_repository.create_table_if_not_exists().unwrap();
+        let component = PromoteUseCaseComponentImpl { task_repository };
+
+        for (title, someday) in [("learn the cello", true), ("pay the rent", false)] {
+            <PromoteUseCaseComponentImpl as AddTaskUseCase>::execute(
+                &component,
+                AddTaskUseCaseInput {
+                    title: title.to_owned(),
+                    priority: None,
+                    urgency: None,
+                    cost: None,
+                    tags: vec![],
+                    private: false,
+                    habit: None,
+                    due: None,
+                    someday,
+                    parent: None,
+                    rules: Default::default(),
+                    description: None,
+                },
+            )
+            .unwrap();
+        }
+        let titles = || {
+            <PromoteUseCaseComponentImpl as ListTaskUseCase>::execute(
+                &component,
+                ListTaskUseCaseInput::default(),
+            )
+            .unwrap()
+            .into_iter()
+            .map(|t| t.title)
+            .collect::<Vec<_>>()
+        };
+        assert_eq!(titles(), vec!["pay the rent"]);
+
+        let promote = |id| {
+            <PromoteUseCaseComponentImpl as PromoteUseCase>::execute(
+                &component,
+                PromoteUseCaseInput {
+                    sequential_id: SequentialID::new(id),
+                },
+            )
+        };
+        promote(1).unwrap();
+        assert_eq!(titles(), vec!["learn the cello", "pay the rent"]);
+        assert!(promote(1).is_err());
+        assert!(promote(3).is_err());
+    }
+}
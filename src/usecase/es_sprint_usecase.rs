@@ -0,0 +1,504 @@
+use crate::Result;
+use chrono::{Duration, Local, NaiveDate, TimeZone};
+use serde::{Deserialize, Serialize};
+
+use crate::domain::es_task::{Cost, IESTaskRepository, IESTaskRepositoryComponent, SequentialID};
+use crate::domain::sprint::{
+    ISprintRepository, ISprintRepositoryComponent, Sprint, SprintLength, SprintName, SprintTask,
+};
+use crate::usecase::error::UseCaseError;
+
+/// DTO for input of CreateSprintUseCase.
+#[derive(Debug)]
+pub struct CreateSprintUseCaseInput {
+    pub name: String,
+    pub start_on: NaiveDate,
+    pub length: SprintLength,
+    pub capacity: i32,
+    pub sequential_ids: Vec<SequentialID>,
+    /// the sprint is created even if the committed cost exceeds the capacity.
+    pub allow_over_capacity: bool,
+}
+
+/// DTO for input of ShowSprintUseCase.
+#[derive(Debug)]
+pub struct ShowSprintUseCaseInput {
+    pub name: String,
+}
+
+/// DTO for input of CloseSprintUseCase.
+#[derive(Debug)]
+pub struct CloseSprintUseCaseInput {
+    pub name: String,
+    pub closed_on: NaiveDate,
+}
+
+/// DTO for input of BurndownSprintUseCase.
+#[derive(Debug)]
+pub struct BurndownSprintUseCaseInput {
+    pub name: String,
+    /// the series stops at today if the sprint is still going.
+    pub today: NaiveDate,
+}
+
+/// DTO of a task committed to the sprint.
+#[derive(Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SprintTaskDTO {
+    pub id: i64,
+    pub title: String,
+    /// cost at the time of commitment.
+    pub cost: i32,
+    pub is_closed: bool,
+}
+
+/// DTO of sprint
+#[derive(Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SprintDTO {
+    pub name: String,
+    pub start_on: NaiveDate,
+    pub end_on: NaiveDate,
+    pub capacity: i32,
+    pub committed_cost: i32,
+    /// sum of costs of the committed tasks which have been closed.
+    pub completed_cost: i32,
+    pub closed_on: Option<NaiveDate>,
+    pub tasks: Vec<SprintTaskDTO>,
+}
+
+/// DTO of a day of the burndown.
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct BurndownPointDTO {
+    pub date: NaiveDate,
+    /// committed cost left open at the end of the day.
+    pub remaining: i32,
+    /// remaining cost if the committed cost were burned evenly through the sprint.
+    pub ideal: f64,
+}
+
+/// DTO of burndown of a sprint
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct BurndownDTO {
+    pub name: String,
+    pub committed_cost: i32,
+    pub points: Vec<BurndownPointDTO>,
+}
+
+/// Usecase to snapshot tasks into a new sprint.
+pub trait CreateSprintUseCase: IESTaskRepositoryComponent + ISprintRepositoryComponent {
+    /// execute creating a sprint.
+    fn execute(&self, input: CreateSprintUseCaseInput) -> Result<SprintDTO> {
+        let name = SprintName::new(&input.name)?;
+        if self.sprint_repository().exists(&name)? {
+            return Err(UseCaseError::SprintAlreadyExists(input.name).into());
+        }
+
+        let mut tasks = Vec::new();
+        for sequential_id in input.sequential_ids {
+            let task = self
+                .repository()
+                .load_by_sequential_id(sequential_id)?
+                .ok_or(UseCaseError::NotFound(sequential_id.to_i64()))?;
+
+            if task.is_closed() {
+                return Err(UseCaseError::AlreadyClosed(sequential_id.to_i64()).into());
+            }
+
+            tasks.push(SprintTask {
+                sequential_id,
+                cost: task.cost(),
+            });
+        }
+
+        let sprint = Sprint::create(
+            name,
+            input.start_on,
+            input.length,
+            Cost::new(input.capacity),
+            tasks,
+            input.allow_over_capacity,
+        )?;
+        self.sprint_repository().save(&sprint)?;
+
+        to_dto(self.repository(), &sprint)
+    }
+}
+
+impl<T: IESTaskRepositoryComponent + ISprintRepositoryComponent> CreateSprintUseCase for T {}
+
+/// CreateSprintUseCaseComponent returns CreateSprintUseCase.
+pub trait CreateSprintUseCaseComponent {
+    type CreateSprintUseCase: CreateSprintUseCase;
+    fn create_sprint_usecase(&self) -> &Self::CreateSprintUseCase;
+}
+
+/// Usecase to report progress of a sprint.
+pub trait ShowSprintUseCase: IESTaskRepositoryComponent + ISprintRepositoryComponent {
+    /// execute showing a sprint.
+    fn execute(&self, input: ShowSprintUseCaseInput) -> Result<SprintDTO> {
+        let sprint = self
+            .sprint_repository()
+            .load(&SprintName::new(&input.name)?)?
+            .ok_or(UseCaseError::SprintNotFound(input.name))?;
+
+        to_dto(self.repository(), &sprint)
+    }
+}
+
+impl<T: IESTaskRepositoryComponent + ISprintRepositoryComponent> ShowSprintUseCase for T {}
+
+/// ShowSprintUseCaseComponent returns ShowSprintUseCase.
+pub trait ShowSprintUseCaseComponent {
+    type ShowSprintUseCase: ShowSprintUseCase;
+    fn show_sprint_usecase(&self) -> &Self::ShowSprintUseCase;
+}
+
+/// Usecase to close a sprint.
+pub trait CloseSprintUseCase: IESTaskRepositoryComponent + ISprintRepositoryComponent {
+    /// execute closing a sprint.
+    fn execute(&self, input: CloseSprintUseCaseInput) -> Result<SprintDTO> {
+        let mut sprint = self
+            .sprint_repository()
+            .load(&SprintName::new(&input.name)?)?
+            .ok_or(UseCaseError::SprintNotFound(input.name))?;
+
+        sprint.close(input.closed_on)?;
+        self.sprint_repository().save(&sprint)?;
+
+        to_dto(self.repository(), &sprint)
+    }
+}
+
+impl<T: IESTaskRepositoryComponent + ISprintRepositoryComponent> CloseSprintUseCase for T {}
+
+/// CloseSprintUseCaseComponent returns CloseSprintUseCase.
+pub trait CloseSprintUseCaseComponent {
+    type CloseSprintUseCase: CloseSprintUseCase;
+    fn close_sprint_usecase(&self) -> &Self::CloseSprintUseCase;
+}
+
+/// Usecase to trace the remaining cost of a sprint day by day.
+pub trait BurndownSprintUseCase: IESTaskRepositoryComponent + ISprintRepositoryComponent {
+    /// execute replaying close events of the committed tasks within the sprint.
+    fn execute(&self, input: BurndownSprintUseCaseInput) -> Result<BurndownDTO> {
+        let sprint = self
+            .sprint_repository()
+            .load(&SprintName::new(&input.name)?)?
+            .ok_or(UseCaseError::SprintNotFound(input.name))?;
+
+        let mut closes = Vec::new();
+        for sprint_task in sprint.tasks() {
+            let task = self
+                .repository()
+                .load_by_sequential_id(sprint_task.sequential_id)?
+                .ok_or(UseCaseError::NotFound(sprint_task.sequential_id.to_i64()))?;
+
+            if let Some(closed_at) = task.closed_at() {
+                let closed_on = Local.from_utc_datetime(&closed_at).date_naive();
+                closes.push((closed_on, sprint_task.cost.to_i32()));
+            }
+        }
+
+        let last = [Some(sprint.end_on()), sprint.closed_on(), Some(input.today)]
+            .into_iter()
+            .flatten()
+            .min()
+            .unwrap_or(sprint.end_on());
+        let committed = sprint.committed_cost().to_i32();
+        let length = f64::from(sprint.length().days());
+
+        let mut points = Vec::new();
+        let mut date = sprint.start_on();
+        let mut elapsed = 1.0;
+        while date <= last {
+            let burned: i32 = closes
+                .iter()
+                .filter(|(closed_on, _)| *closed_on <= date)
+                .map(|(_, cost)| cost)
+                .sum();
+            points.push(BurndownPointDTO {
+                date,
+                remaining: committed - burned,
+                ideal: f64::from(committed) * (1.0 - elapsed / length),
+            });
+            date += Duration::days(1);
+            elapsed += 1.0;
+        }
+
+        Ok(BurndownDTO {
+            name: sprint.name().as_str().to_owned(),
+            committed_cost: committed,
+            points,
+        })
+    }
+}
+
+impl<T: IESTaskRepositoryComponent + ISprintRepositoryComponent> BurndownSprintUseCase for T {}
+
+/// BurndownSprintUseCaseComponent returns BurndownSprintUseCase.
+pub trait BurndownSprintUseCaseComponent {
+    type BurndownSprintUseCase: BurndownSprintUseCase;
+    fn burndown_sprint_usecase(&self) -> &Self::BurndownSprintUseCase;
+}
+
+/// to_dto converts the sprint into SprintDTO with the current state of its tasks.
+fn to_dto<R: IESTaskRepository>(repository: &R, sprint: &Sprint) -> Result<SprintDTO> {
+    let mut tasks = Vec::new();
+    for sprint_task in sprint.tasks() {
+        let task = repository
+            .load_by_sequential_id(sprint_task.sequential_id)?
+            .ok_or(UseCaseError::NotFound(sprint_task.sequential_id.to_i64()))?;
+
+        tasks.push(SprintTaskDTO {
+            id: sprint_task.sequential_id.to_i64(),
+            title: task.title().to_owned(),
+            cost: sprint_task.cost.to_i32(),
+            is_closed: task.is_closed(),
+        });
+    }
+
+    Ok(SprintDTO {
+        name: sprint.name().as_str().to_owned(),
+        start_on: sprint.start_on(),
+        end_on: sprint.end_on(),
+        capacity: sprint.capacity().to_i32(),
+        committed_cost: sprint.committed_cost().to_i32(),
+        completed_cost: tasks.iter().filter(|t| t.is_closed).map(|t| t.cost).sum(),
+        closed_on: sprint.closed_on(),
+        tasks,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::sprint::SprintError;
+    use crate::infra::sqlite::es_task_repository::TaskRepository;
+    use crate::infra::sqlite::sprint_repository::SprintRepository;
+    use crate::usecase::es_add_task_usecase::{AddTaskUseCase, AddTaskUseCaseInput};
+    use crate::usecase::es_close_task_usecase::{CloseTaskUseCase, CloseTaskUseCaseInput};
+    use rusqlite::Connection;
+
+    struct SprintUseCaseComponentImpl {
+        task_repository: TaskRepository,
+        sprint_repository: SprintRepository,
+    }
+
+    impl IESTaskRepositoryComponent for SprintUseCaseComponentImpl {
+        type Repository = TaskRepository;
+        fn repository(&self) -> &Self::Repository {
+            &self.task_repository
+        }
+    }
+
+    impl ISprintRepositoryComponent for SprintUseCaseComponentImpl {
+        type SprintRepository = SprintRepository;
+        fn sprint_repository(&self) -> &Self::SprintRepository {
+            &self.sprint_repository
+        }
+    }
+
+    fn setup() -> SprintUseCaseComponentImpl {
+        let task_repository = TaskRepository::new(Connection::open_in_memory().unwrap());
+        task_repository.create_table_if_not_exists().unwrap();
+        let sprint_repository = SprintRepository::new(Connection::open_in_memory().unwrap());
+        sprint_repository.create_table_if_not_exists().unwrap();
+        let component = SprintUseCaseComponentImpl {
+            task_repository,
+            sprint_repository,
+        };
+
+        for (title, cost) in [("a", 10), ("b", 20), ("c", 30)] {
+            <SprintUseCaseComponentImpl as AddTaskUseCase>::execute(
+                &component,
+                AddTaskUseCaseInput {
+                    title: title.to_owned(),
+                    priority: None,
+                    urgency: None,
+                    cost: Some(cost),
+                    tags: vec![],
+                    private: false,
+                    habit: None,
+                    due: None,
+                    someday: false,
+                    parent: None,
+                    rules: Default::default(),
+                    description: None,
+                },
+            )
+            .unwrap();
+        }
+        component
+    }
+
+    fn create_input(ids: &[i64]) -> CreateSprintUseCaseInput {
+        CreateSprintUseCaseInput {
+            name: "s1".to_owned(),
+            start_on: NaiveDate::from_ymd_opt(2026, 10, 1).unwrap(),
+            length: SprintLength::from_days(14),
+            capacity: 40,
+            sequential_ids: ids.iter().map(|id| SequentialID::new(*id)).collect(),
+            allow_over_capacity: false,
+        }
+    }
+
+    #[test]
+    fn test_create() {
+        #[derive(Debug)]
+        struct TestCase {
+            args: CreateSprintUseCaseInput,
+            want: Result<i32, String>,
+            name: String,
+        }
+
+        let table = [
+            TestCase {
+                name: String::from("normal: within capacity"),
+                args: create_input(&[1, 3]),
+                want: Ok(40),
+            },
+            TestCase {
+                name: String::from("abnormal: over capacity"),
+                args: create_input(&[2, 3]),
+                want: Err(SprintError::OverCapacity {
+                    committed: 50,
+                    capacity: 40,
+                }
+                .to_string()),
+            },
+            TestCase {
+                name: String::from("abnormal: not found"),
+                args: create_input(&[4]),
+                want: Err(UseCaseError::NotFound(4).to_string()),
+            },
+        ];
+
+        for test_case in table {
+            let component = setup();
+            let got = <SprintUseCaseComponentImpl as CreateSprintUseCase>::execute(
+                &component,
+                test_case.args,
+            )
+            .map(|s| s.committed_cost)
+            .map_err(|e| e.to_string());
+            assert_eq!(got, test_case.want, "Failed in the \"{}\".", test_case.name);
+        }
+    }
+
+    #[test]
+    fn test_show_and_close() {
+        let component = setup();
+        <SprintUseCaseComponentImpl as CreateSprintUseCase>::execute(
+            &component,
+            create_input(&[1, 3]),
+        )
+        .unwrap();
+        assert_eq!(
+            <SprintUseCaseComponentImpl as CreateSprintUseCase>::execute(
+                &component,
+                create_input(&[2]),
+            )
+            .unwrap_err()
+            .to_string(),
+            UseCaseError::SprintAlreadyExists("s1".to_owned()).to_string()
+        );
+
+        <SprintUseCaseComponentImpl as CloseTaskUseCase>::execute(
+            &component,
+            CloseTaskUseCaseInput {
+                sequential_id: SequentialID::new(3),
+                unlock: false,
+                cascade: false,
+            },
+        )
+        .unwrap();
+
+        let got = <SprintUseCaseComponentImpl as ShowSprintUseCase>::execute(
+            &component,
+            ShowSprintUseCaseInput {
+                name: "s1".to_owned(),
+            },
+        )
+        .unwrap();
+        assert_eq!(got.committed_cost, 40);
+        assert_eq!(got.completed_cost, 30);
+        assert_eq!(got.end_on, NaiveDate::from_ymd_opt(2026, 10, 14).unwrap());
+
+        let closed_on = NaiveDate::from_ymd_opt(2026, 10, 14).unwrap();
+        let got = <SprintUseCaseComponentImpl as CloseSprintUseCase>::execute(
+            &component,
+            CloseSprintUseCaseInput {
+                name: "s1".to_owned(),
+                closed_on,
+            },
+        )
+        .unwrap();
+        assert_eq!(got.closed_on, Some(closed_on));
+
+        assert_eq!(
+            <SprintUseCaseComponentImpl as ShowSprintUseCase>::execute(
+                &component,
+                ShowSprintUseCaseInput {
+                    name: "s2".to_owned(),
+                },
+            )
+            .unwrap_err()
+            .to_string(),
+            UseCaseError::SprintNotFound("s2".to_owned()).to_string()
+        );
+    }
+
+    #[test]
+    fn test_burndown() {
+        let component = setup();
+        let today = Local::now().date_naive();
+        <SprintUseCaseComponentImpl as CreateSprintUseCase>::execute(
+            &component,
+            CreateSprintUseCaseInput {
+                start_on: today - Duration::days(2),
+                length: SprintLength::from_days(4),
+                ..create_input(&[1, 3])
+            },
+        )
+        .unwrap();
+        <SprintUseCaseComponentImpl as CloseTaskUseCase>::execute(
+            &component,
+            CloseTaskUseCaseInput {
+                sequential_id: SequentialID::new(3),
+                unlock: false,
+                cascade: false,
+            },
+        )
+        .unwrap();
+
+        let got = <SprintUseCaseComponentImpl as BurndownSprintUseCase>::execute(
+            &component,
+            BurndownSprintUseCaseInput {
+                name: "s1".to_owned(),
+                today,
+            },
+        )
+        .unwrap();
+
+        assert_eq!(got.committed_cost, 40);
+        assert_eq!(
+            got.points,
+            vec![
+                BurndownPointDTO {
+                    date: today - Duration::days(2),
+                    remaining: 40,
+                    ideal: 30.0,
+                },
+                BurndownPointDTO {
+                    date: today - Duration::days(1),
+                    remaining: 40,
+                    ideal: 20.0,
+                },
+                BurndownPointDTO {
+                    date: today,
+                    remaining: 10,
+                    ideal: 10.0,
+                },
+            ]
+        );
+    }
+}
@@ -0,0 +1,508 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+use crate::Result;
+
+use crate::ddd::component::{AggregateRoot, Repository};
+use crate::domain::es_tag::{ITagCatalogRepository, ITagCatalogRepositoryComponent, TagCommand};
+use crate::domain::es_task::{
+    IESTaskRepository, IESTaskRepositoryComponent, SequentialID, Tag, TaskCommand,
+};
+use crate::usecase::error::UseCaseError;
+
+/// DTO for input of RenameTagUseCase.
+#[derive(Debug)]
+pub struct RenameTagUseCaseInput {
+    pub from: String,
+    pub to: String,
+}
+
+/// DTO for input of RetagUseCase.
+#[derive(Debug)]
+pub struct RetagUseCaseInput {
+    pub from: String,
+    pub to: String,
+    /// sequential_ids limits the tasks to retag. All tasks are retagged if None.
+    pub sequential_ids: Option<Vec<SequentialID>>,
+}
+
+/// DTO for input of TagTaskUseCase.
+#[derive(Debug)]
+pub struct TagTaskUseCaseInput {
+    pub sequential_id: SequentialID,
+    pub tags: Vec<String>,
+    /// tagged is true to tag the task and false to untag it.
+    pub tagged: bool,
+}
+
+/// DTO of tasks affected by RetagUseCase.
+#[derive(Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RetagDTO {
+    pub retagged: Vec<i64>,
+    /// locked tasks have the tag but are left as they are.
+    pub locked: Vec<i64>,
+}
+
+/// DTO of a tag and the number of tasks having it.
+#[derive(Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TagCountDTO {
+    pub tag: String,
+    pub open: usize,
+    pub total: usize,
+}
+
+/// Usecase to rename a tag of all tasks.
+pub trait RenameTagUseCase: ITagCatalogRepositoryComponent {
+    /// execute renaming the tag. Tasks which already have the new name keep it once.
+    fn execute(&self, input: RenameTagUseCaseInput) -> Result<()> {
+        let mut catalog = self.tag_catalog_repository().load()?;
+        catalog.execute(TagCommand::Rename {
+            from: Tag::new(&input.from)?,
+            to: Tag::new(&input.to)?,
+        })?;
+        self.tag_catalog_repository().save(&mut catalog)
+    }
+}
+
+impl<T: ITagCatalogRepositoryComponent> RenameTagUseCase for T {}
+
+/// RenameTagUseCaseComponent returns RenameTagUseCase.
+pub trait RenameTagUseCaseComponent {
+    type RenameTagUseCase: RenameTagUseCase;
+    fn rename_tag_usecase(&self) -> &Self::RenameTagUseCase;
+}
+
+/// Usecase to move a tag from some tasks to another tag.
+pub trait RetagUseCase: IESTaskRepositoryComponent {
+    /// execute retagging open tasks having the tag in one transaction.
+    /// Closed tasks keep the tag as a record, unlike RenameTagUseCase renaming it everywhere.
+    fn execute(&self, input: RetagUseCaseInput) -> Result<RetagDTO> {
+        let from = Tag::new(&input.from)?;
+        let to = Tag::new(&input.to)?;
+        let sequential_ids = match input.sequential_ids {
+            Some(sequential_ids) => sequential_ids,
+            None => self.repository().load_all_sequential_ids()?,
+        };
+
+        let mut dto = RetagDTO::default();
+        let mut tasks = vec![];
+        for sequential_id in sequential_ids {
+            let mut task = self
+                .repository()
+                .load_by_sequential_id(sequential_id)?
+                .ok_or(UseCaseError::NotFound(sequential_id.to_i64()))?;
+            if task.is_closed() || !task.tags().contains(&from) {
+                continue;
+            }
+            if task.is_locked() {
+                dto.locked.push(sequential_id.to_i64());
+                continue;
+            }
+
+            task.execute(TaskCommand::Untag { tag: from.clone() })?;
+            task.execute(TaskCommand::Tag { tag: to.clone() })?;
+            dto.retagged.push(sequential_id.to_i64());
+            tasks.push(task);
+        }
+
+        self.repository().save_all(&mut tasks)?;
+        dto.retagged.sort();
+        dto.locked.sort();
+        Ok(dto)
+    }
+}
+
+impl<T: IESTaskRepositoryComponent> RetagUseCase for T {}
+
+/// RetagUseCaseComponent returns RetagUseCase.
+pub trait RetagUseCaseComponent {
+    type RetagUseCase: RetagUseCase;
+    fn retag_usecase(&self) -> &Self::RetagUseCase;
+}
+
+/// Usecase to tag or untag a task.
+pub trait TagTaskUseCase: IESTaskRepositoryComponent {
+    /// execute tagging or untagging the task with all of the tags, or none of them on failure.
+    /// Tags which the task already has, or does not have to untag, record nothing.
+    fn execute(&self, input: TagTaskUseCaseInput) -> Result<SequentialID> {
+        let tags = input
+            .tags
+            .iter()
+            .map(|t| Tag::new(t))
+            .collect::<Result<Vec<Tag>, _>>()?;
+        let mut task = self
+            .repository()
+            .load_by_sequential_id(input.sequential_id)?
+            .ok_or(UseCaseError::NotFound(input.sequential_id.to_i64()))?;
+
+        if task.is_closed() {
+            return Err(UseCaseError::AlreadyClosed(task.sequential_id().to_i64()).into());
+        }
+        if task.is_locked() {
+            return Err(UseCaseError::Locked(task.sequential_id().to_i64()).into());
+        }
+
+        for tag in tags {
+            let command = if input.tagged {
+                TaskCommand::Tag { tag }
+            } else {
+                TaskCommand::Untag { tag }
+            };
+            task.execute(command)?;
+        }
+
+        self.repository().save(&mut task)?;
+        Ok(task.sequential_id())
+    }
+}
+
+impl<T: IESTaskRepositoryComponent> TagTaskUseCase for T {}
+
+/// TagTaskUseCaseComponent returns TagTaskUseCase.
+pub trait TagTaskUseCaseComponent {
+    type TagTaskUseCase: TagTaskUseCase;
+    fn tag_task_usecase(&self) -> &Self::TagTaskUseCase;
+}
+
+/// Usecase to list tags in use.
+pub trait ListTagsUseCase: IESTaskRepositoryComponent {
+    /// execute listing tags ordered by name. Private tasks are left out.
+    fn execute(&self) -> Result<Vec<TagCountDTO>> {
+        let mut counts: BTreeMap<String, (usize, usize)> = BTreeMap::new();
+        for sequential_id in self.repository().load_all_sequential_ids()? {
+            let task = self
+                .repository()
+                .load_by_sequential_id(sequential_id)?
+                .ok_or(UseCaseError::NotFound(sequential_id.to_i64()))?;
+            if task.is_private() {
+                continue;
+            }
+
+            for tag in task.tags() {
+                let count = counts.entry(tag.as_str().to_owned()).or_default();
+                if !task.is_closed() {
+                    count.0 += 1;
+                }
+                count.1 += 1;
+            }
+        }
+
+        Ok(counts
+            .into_iter()
+            .map(|(tag, (open, total))| TagCountDTO { tag, open, total })
+            .collect())
+    }
+}
+
+impl<T: IESTaskRepositoryComponent> ListTagsUseCase for T {}
+
+/// ListTagsUseCaseComponent returns ListTagsUseCase.
+pub trait ListTagsUseCaseComponent {
+    type ListTagsUseCase: ListTagsUseCase;
+    fn list_tags_usecase(&self) -> &Self::ListTagsUseCase;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::es_task::SequentialID;
+    use crate::infra::sqlite::es_tag_repository::TagCatalogRepository;
+    use crate::infra::sqlite::es_task_repository::TaskRepository;
+    use crate::usecase::es_add_task_usecase::{AddTaskUseCase, AddTaskUseCaseInput};
+    use crate::usecase::es_close_task_usecase::{CloseTaskUseCase, CloseTaskUseCaseInput};
+    use crate::usecase::es_list_task_usecase::{ListTaskUseCase, ListTaskUseCaseInput};
+    use crate::usecase::es_lock_task_usecase::{LockTaskUseCase, LockTaskUseCaseInput};
+    use rusqlite::Connection;
+
+    struct TagUseCaseComponentImpl {
+        task_repository: TaskRepository,
+        tag_catalog_repository: TagCatalogRepository,
+    }
+
+    impl IESTaskRepositoryComponent for TagUseCaseComponentImpl {
+        type Repository = TaskRepository;
+        fn repository(&self) -> &Self::Repository {
+            &self.task_repository
+        }
+    }
+
+    impl ITagCatalogRepositoryComponent for TagUseCaseComponentImpl {
+        type TagCatalogRepository = TagCatalogRepository;
+        fn tag_catalog_repository(&self) -> &Self::TagCatalogRepository {
+            &self.tag_catalog_repository
+        }
+    }
+
+    fn temp_db_path() -> std::path::PathBuf {
+        let mut path = std::env::temp_dir();
+        let nanos = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_nanos();
+        path.push(format!("taskmr-tag-{}-{}.db", std::process::id(), nanos));
+        path
+    }
+
+    #[test]
+    fn test_execute() {
+        let path = temp_db_path();
+        let task_repository = TaskRepository::new(Connection::open(&path).unwrap());
+        task_repository.create_table_if_not_exists().unwrap();
+        let component = TagUseCaseComponentImpl {
+            task_repository,
+            tag_catalog_repository: TagCatalogRepository::new(Connection::open(&path).unwrap()),
+        };
+
+        let tasks: [&[&str]; 3] = [&["work", "docs"], &["work", "job"], &["home"]];
+        for tags in tasks {
+            <TagUseCaseComponentImpl as AddTaskUseCase>::execute(
+                &component,
+                AddTaskUseCaseInput {
+                    title: "title".to_owned(),
+                    priority: None,
+                    urgency: None,
+                    cost: None,
+                    tags: tags.iter().map(|t| t.to_string()).collect(),
+                    private: false,
+                    habit: None,
+                    due: None,
+                    someday: false,
+                    parent: None,
+                    rules: Default::default(),
+                    description: None,
+                },
+            )
+            .unwrap();
+        }
+        <TagUseCaseComponentImpl as CloseTaskUseCase>::execute(
+            &component,
+            CloseTaskUseCaseInput {
+                sequential_id: SequentialID::new(3),
+                unlock: false,
+                cascade: false,
+            },
+        )
+        .unwrap();
+
+        <TagUseCaseComponentImpl as RenameTagUseCase>::execute(
+            &component,
+            RenameTagUseCaseInput {
+                from: "work".to_owned(),
+                to: "job".to_owned(),
+            },
+        )
+        .unwrap();
+
+        let count = |tag: &str, open, total| TagCountDTO {
+            tag: tag.to_owned(),
+            open,
+            total,
+        };
+        let got = <TagUseCaseComponentImpl as ListTagsUseCase>::execute(&component).unwrap();
+        assert_eq!(
+            got,
+            vec![count("docs", 1, 1), count("home", 0, 1), count("job", 2, 2)]
+        );
+        // NOTE: the read model of listing is projected again after the rename.
+        let tags: Vec<Vec<String>> = <TagUseCaseComponentImpl as ListTaskUseCase>::execute(
+            &component,
+            ListTaskUseCaseInput {
+                include_closed: true,
+                ..Default::default()
+            },
+        )
+        .unwrap()
+        .into_iter()
+        .map(|t| t.tags)
+        .collect();
+        assert_eq!(tags, [vec!["job", "docs"], vec!["job"], vec!["home"]]);
+
+        <TagUseCaseComponentImpl as AddTaskUseCase>::execute(
+            &component,
+            AddTaskUseCaseInput {
+                title: "title".to_owned(),
+                priority: None,
+                urgency: None,
+                cost: None,
+                tags: vec!["work".to_owned()],
+                private: false,
+                habit: None,
+                due: None,
+                someday: false,
+                parent: None,
+                rules: Default::default(),
+                description: None,
+            },
+        )
+        .unwrap();
+        let got = <TagUseCaseComponentImpl as ListTagsUseCase>::execute(&component).unwrap();
+        assert_eq!(got[3], count("work", 1, 1));
+
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_retag() {
+        let path = temp_db_path();
+        let task_repository = TaskRepository::new(Connection::open(&path).unwrap());
+        task_repository.create_table_if_not_exists().unwrap();
+        let component = TagUseCaseComponentImpl {
+            task_repository,
+            tag_catalog_repository: TagCatalogRepository::new(Connection::open(&path).unwrap()),
+        };
+
+        let tasks: [&[&str]; 5] = [
+            &["proj-x"],
+            &["proj-x", "proj-y"],
+            &["proj-x"],
+            &["proj-x"],
+            &[],
+        ];
+        for tags in tasks {
+            <TagUseCaseComponentImpl as AddTaskUseCase>::execute(
+                &component,
+                AddTaskUseCaseInput {
+                    title: "title".to_owned(),
+                    priority: None,
+                    urgency: None,
+                    cost: None,
+                    tags: tags.iter().map(|t| t.to_string()).collect(),
+                    private: false,
+                    habit: None,
+                    due: None,
+                    someday: false,
+                    parent: None,
+                    rules: Default::default(),
+                    description: None,
+                },
+            )
+            .unwrap();
+        }
+        <TagUseCaseComponentImpl as CloseTaskUseCase>::execute(
+            &component,
+            CloseTaskUseCaseInput {
+                sequential_id: SequentialID::new(3),
+                unlock: false,
+                cascade: false,
+            },
+        )
+        .unwrap();
+        <TagUseCaseComponentImpl as LockTaskUseCase>::execute(
+            &component,
+            LockTaskUseCaseInput {
+                sequential_id: SequentialID::new(4),
+                locked: true,
+            },
+        )
+        .unwrap();
+
+        let got = <TagUseCaseComponentImpl as RetagUseCase>::execute(
+            &component,
+            RetagUseCaseInput {
+                from: "proj-x".to_owned(),
+                to: "proj-y".to_owned(),
+                sequential_ids: None,
+            },
+        )
+        .unwrap();
+        assert_eq!(
+            got,
+            RetagDTO {
+                retagged: vec![1, 2],
+                locked: vec![4],
+            }
+        );
+
+        let count = |tag: &str, open, total| TagCountDTO {
+            tag: tag.to_owned(),
+            open,
+            total,
+        };
+        let got = <TagUseCaseComponentImpl as ListTagsUseCase>::execute(&component).unwrap();
+        assert_eq!(got, vec![count("proj-x", 1, 2), count("proj-y", 2, 2)]);
+
+        let got = <TagUseCaseComponentImpl as RetagUseCase>::execute(
+            &component,
+            RetagUseCaseInput {
+                from: "proj-y".to_owned(),
+                to: "proj-z".to_owned(),
+                sequential_ids: Some(vec![SequentialID::new(2), SequentialID::new(5)]),
+            },
+        )
+        .unwrap();
+        assert_eq!(got.retagged, vec![2]);
+
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_tag_task() {
+        let path = temp_db_path();
+        let task_repository = TaskRepository::new(Connection::open(&path).unwrap());
+        task_repository.create_table_if_not_exists().unwrap();
+        let component = TagUseCaseComponentImpl {
+            task_repository,
+            tag_catalog_repository: TagCatalogRepository::new(Connection::open(&path).unwrap()),
+        };
+        <TagUseCaseComponentImpl as AddTaskUseCase>::execute(
+            &component,
+            AddTaskUseCaseInput {
+                title: "title".to_owned(),
+                priority: None,
+                urgency: None,
+                cost: None,
+                tags: vec!["work".to_owned()],
+                private: false,
+                habit: None,
+                due: None,
+                someday: false,
+                parent: None,
+                rules: Default::default(),
+                description: None,
+            },
+        )
+        .unwrap();
+
+        let tag = |tags: &[&str], tagged| {
+            <TagUseCaseComponentImpl as TagTaskUseCase>::execute(
+                &component,
+                TagTaskUseCaseInput {
+                    sequential_id: SequentialID::new(1),
+                    tags: tags.iter().map(|t| t.to_string()).collect(),
+                    tagged,
+                },
+            )
+        };
+        let tags = || {
+            let task = component
+                .task_repository
+                .load_by_sequential_id(SequentialID::new(1))
+                .unwrap()
+                .unwrap();
+            task.tags()
+                .iter()
+                .map(|t| t.as_str().to_owned())
+                .collect::<Vec<_>>()
+        };
+
+        tag(&["bug", "work"], true).unwrap();
+        assert_eq!(tags(), vec!["work", "bug"]);
+        tag(&["work", "docs"], false).unwrap();
+        assert_eq!(tags(), vec!["bug"]);
+        // NOTE: an invalid tag refuses all of the tags.
+        assert!(tag(&["ui", "a b"], true).is_err());
+        assert_eq!(tags(), vec!["bug"]);
+
+        <TagUseCaseComponentImpl as LockTaskUseCase>::execute(
+            &component,
+            LockTaskUseCaseInput {
+                sequential_id: SequentialID::new(1),
+                locked: true,
+            },
+        )
+        .unwrap();
+        assert!(tag(&["ui"], true).is_err());
+
+        std::fs::remove_file(path).unwrap();
+    }
+}
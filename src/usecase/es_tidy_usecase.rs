@@ -0,0 +1,252 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+
+use crate::Result;
+use chrono::NaiveDateTime;
+
+use crate::domain::es_task::{IESTaskRepository, IESTaskRepositoryComponent, Task};
+use crate::usecase::error::UseCaseError;
+
+/// DUPLICATE_SIMILARITY is the similarity of titles from which tasks are likely duplicates.
+pub const DUPLICATE_SIMILARITY: f64 = 0.75;
+
+/// DTO for input of TidyUseCase.
+#[derive(Debug)]
+pub struct TidyUseCaseInput {
+    /// stale_before in UTC. Tasks created before it are stale.
+    pub stale_before: NaiveDateTime,
+}
+
+/// DTO of a task found by TidyUseCase.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TidyTaskDTO {
+    pub id: i64,
+    pub title: String,
+}
+
+/// TidyFinding is an open task which is likely not worth keeping as it is.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TidyFinding {
+    /// duplicate has a title similar to the older task `original`.
+    Duplicate {
+        original: TidyTaskDTO,
+        duplicate: TidyTaskDTO,
+    },
+    /// the task was created before `stale_before` and is still open.
+    Stale {
+        task: TidyTaskDTO,
+        created_at: NaiveDateTime,
+    },
+    /// the task costs nothing, so that it is probably done or misestimated.
+    ZeroCost { task: TidyTaskDTO },
+}
+
+/// Usecase to find open tasks to tidy up.
+pub trait TidyUseCase: IESTaskRepositoryComponent {
+    /// execute finding tasks. Locked tasks are left out, since they are kept as they are on purpose.
+    /// A task is found at most once, as a duplicate first, then as stale, then as zero cost.
+    fn execute(&self, input: TidyUseCaseInput) -> Result<Vec<TidyFinding>> {
+        let mut tasks: Vec<Task> = vec![];
+        for sequential_id in self.repository().load_all_sequential_ids()? {
+            let task = self
+                .repository()
+                .load_by_sequential_id(sequential_id)?
+                .ok_or(UseCaseError::NotFound(sequential_id.to_i64()))?;
+            if !task.is_closed() && !task.is_locked() {
+                tasks.push(task);
+            }
+        }
+        tasks.sort_by_key(|t| t.sequential_id().to_i64());
+
+        let dto = |task: &Task| TidyTaskDTO {
+            id: task.sequential_id().to_i64(),
+            title: task.title().to_owned(),
+        };
+        let words: Vec<BTreeSet<String>> = tasks.iter().map(|t| title_words(t.title())).collect();
+
+        let mut findings = vec![];
+        for (i, task) in tasks.iter().enumerate() {
+            let original =
+                (0..i).find(|&j| similarity(&words[j], &words[i]) >= DUPLICATE_SIMILARITY);
+            let finding = if let Some(j) = original {
+                TidyFinding::Duplicate {
+                    original: dto(&tasks[j]),
+                    duplicate: dto(task),
+                }
+            } else if task.created_at() < input.stale_before {
+                TidyFinding::Stale {
+                    task: dto(task),
+                    created_at: task.created_at(),
+                }
+            } else if task.cost().to_i32() == 0 {
+                TidyFinding::ZeroCost { task: dto(task) }
+            } else {
+                continue;
+            };
+            findings.push(finding);
+        }
+
+        Ok(findings)
+    }
+}
+
+impl<T: IESTaskRepositoryComponent> TidyUseCase for T {}
+
+/// TidyUseCaseComponent returns TidyUseCase.
+pub trait TidyUseCaseComponent {
+    type TidyUseCase: TidyUseCase;
+    fn tidy_usecase(&self) -> &Self::TidyUseCase;
+}
+
+/// title_words splits the title into lowercase words.
+fn title_words(title: &str) -> BTreeSet<String> {
+    title
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .map(|w| w.to_lowercase())
+        .collect()
+}
+
+/// similarity is the Jaccard index of the words, from 0 for no common word to 1 for the same words.
+fn similarity(a: &BTreeSet<String>, b: &BTreeSet<String>) -> f64 {
+    let union = a.union(b).count();
+    if union == 0 {
+        return 0.0;
+    }
+    a.intersection(b).count() as f64 / union as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::es_task::SequentialID;
+    use crate::infra::sqlite::es_task_repository::TaskRepository;
+    use crate::usecase::es_add_task_usecase::{AddTaskUseCase, AddTaskUseCaseInput};
+    use crate::usecase::es_lock_task_usecase::{LockTaskUseCase, LockTaskUseCaseInput};
+    use rusqlite::Connection;
+
+    struct TidyUseCaseComponentImpl {
+        task_repository: TaskRepository,
+    }
+
+    impl IESTaskRepositoryComponent for TidyUseCaseComponentImpl {
+        type Repository = TaskRepository;
+        fn repository(&self) -> &Self::Repository {
+            &self.task_repository
+        }
+    }
+
+    #[test]
+    fn test_similarity() {
+        #[derive(Debug)]
+        struct TestCase {
+            args: (&'static str, &'static str),
+            want: f64,
+            name: String,
+        }
+
+        let table = [
+            TestCase {
+                name: String::from("normal: same words in another case"),
+                args: ("Write the report", "write the REPORT!"),
+                want: 1.0,
+            },
+            TestCase {
+                name: String::from("normal: some common words"),
+                args: ("write the report", "write the summary"),
+                want: 0.5,
+            },
+            TestCase {
+                name: String::from("normal: no words"),
+                args: ("", "--"),
+                want: 0.0,
+            },
+        ];
+
+        for test_case in table {
+            let (a, b) = test_case.args;
+            assert_eq!(
+                similarity(&title_words(a), &title_words(b)),
+                test_case.want,
+                "Failed in the \"{}\".",
+                test_case.name
+            );
+        }
+    }
+
+    #[test]
+    fn test_execute() {
+        let task_repository = TaskRepository::new(Connection::open_in_memory().unwrap());
+        task_repository.create_table_if_not_exists().unwrap();
+        let component = TidyUseCaseComponentImpl { task_repository };
+
+        let tasks = [
+            ("Write the report", Some(3)),
+            ("write the report.", Some(3)),
+            ("review the design", Some(0)),
+            ("locked and free", Some(0)),
+            ("ship it", None),
+        ];
+        for (title, cost) in tasks {
+            <TidyUseCaseComponentImpl as AddTaskUseCase>::execute(
+                &component,
+                AddTaskUseCaseInput {
+                    title: title.to_owned(),
+                    priority: None,
+                    urgency: None,
+                    cost,
+                    tags: vec![],
+                    private: false,
+                    habit: None,
+                    due: None,
+                    someday: false,
+                    parent: None,
+                    rules: Default::default(),
+                    description: None,
+                },
+            )
+            .unwrap();
+        }
+        <TidyUseCaseComponentImpl as LockTaskUseCase>::execute(
+            &component,
+            LockTaskUseCaseInput {
+                sequential_id: SequentialID::new(4),
+                locked: true,
+            },
+        )
+        .unwrap();
+
+        let dto = |id: i64, title: &str| TidyTaskDTO {
+            id,
+            title: title.to_owned(),
+        };
+        let got = <TidyUseCaseComponentImpl as TidyUseCase>::execute(
+            &component,
+            TidyUseCaseInput {
+                stale_before: NaiveDateTime::default(),
+            },
+        )
+        .unwrap();
+        assert_eq!(
+            got,
+            vec![
+                TidyFinding::Duplicate {
+                    original: dto(1, "Write the report"),
+                    duplicate: dto(2, "write the report."),
+                },
+                TidyFinding::ZeroCost {
+                    task: dto(3, "review the design"),
+                },
+            ]
+        );
+
+        let stale_before = chrono::Utc::now().naive_utc() + chrono::Duration::hours(1);
+        let got = <TidyUseCaseComponentImpl as TidyUseCase>::execute(
+            &component,
+            TidyUseCaseInput { stale_before },
+        )
+        .unwrap();
+        assert_eq!(got.len(), 4);
+        assert!(matches!(&got[3], TidyFinding::Stale { task, .. } if task.id == 5));
+    }
+}
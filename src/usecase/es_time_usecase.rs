@@ -0,0 +1,599 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+use crate::Result;
+use chrono::{Local, NaiveDateTime, TimeZone};
+
+use crate::ddd::component::{AggregateRoot, Repository};
+use crate::domain::es_task::{
+    HabitPeriod, IESTaskRepository, IESTaskRepositoryComponent, SequentialID, Task, TaskCommand,
+};
+use crate::usecase::error::UseCaseError;
+use crate::usecase::es_list_task_usecase::NO_TAG_GROUP;
+
+/// DTO for input of LogTimeUseCase.
+#[derive(Debug)]
+pub struct LogTimeUseCaseInput {
+    pub sequential_id: SequentialID,
+    pub duration: Duration,
+}
+
+/// DTO for input of TimeByTagUseCase.
+#[derive(Debug, Default)]
+pub struct TimeByTagUseCaseInput {
+    /// since in UTC. Time logged before it is left out. All time is summed if None.
+    pub since: Option<NaiveDateTime>,
+}
+
+/// DTO of time logged on tasks having the tag.
+#[derive(Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TimeByTagDTO {
+    pub tag: String,
+    pub elapsed: Duration,
+    /// tasks is the number of tasks which the time was logged on.
+    pub tasks: usize,
+}
+
+/// Quota is the time allowed for a tag in a period.
+#[derive(Debug, Clone)]
+pub struct Quota {
+    pub tag: String,
+    pub limit: Duration,
+    pub period: HabitPeriod,
+}
+
+/// DTO for input of QuotaUsageUseCase.
+#[derive(Debug)]
+pub struct QuotaUsageUseCaseInput {
+    pub quotas: Vec<Quota>,
+    /// now in local time. Periods begin at local midnight.
+    pub now: NaiveDateTime,
+    /// sequential_id limits quotas to tags of the task. All quotas are used if None.
+    pub sequential_id: Option<SequentialID>,
+}
+
+/// DTO of time logged on a tag in the current period of its quota.
+#[derive(Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct QuotaUsageDTO {
+    pub tag: String,
+    pub used: Duration,
+    pub limit: Duration,
+    pub period: HabitPeriod,
+}
+
+impl QuotaUsageDTO {
+    /// is_over returns whether more time than the quota is logged.
+    pub fn is_over(&self) -> bool {
+        self.used > self.limit
+    }
+}
+
+/// Usecase to log time spent on a task.
+pub trait LogTimeUseCase: IESTaskRepositoryComponent {
+    /// execute logging time. A locked task refuses it.
+    fn execute(&self, input: LogTimeUseCaseInput) -> Result<SequentialID> {
+        let mut task = self
+            .repository()
+            .load_by_sequential_id(input.sequential_id)?
+            .ok_or(UseCaseError::NotFound(input.sequential_id.to_i64()))?;
+
+        if task.is_locked() {
+            return Err(UseCaseError::Locked(task.sequential_id().to_i64()).into());
+        }
+
+        task.execute(TaskCommand::LogTime {
+            duration: input.duration,
+        })?;
+
+        self.repository().save(&mut task)?;
+        Ok(task.sequential_id())
+    }
+}
+
+impl<T: IESTaskRepositoryComponent> LogTimeUseCase for T {}
+
+/// LogTimeUseCaseComponent returns LogTimeUseCase.
+pub trait LogTimeUseCaseComponent {
+    type LogTimeUseCase: LogTimeUseCase;
+    fn log_time_usecase(&self) -> &Self::LogTimeUseCase;
+}
+
+/// DTO for input of StartTimerUseCase.
+#[derive(Debug)]
+pub struct StartTimerUseCaseInput {
+    pub sequential_id: SequentialID,
+}
+
+/// Usecase to start the timer of a task.
+pub trait StartTimerUseCase: IESTaskRepositoryComponent {
+    /// execute starting the timer. A closed or locked task refuses it.
+    fn execute(&self, input: StartTimerUseCaseInput) -> Result<SequentialID> {
+        let mut task = self
+            .repository()
+            .load_by_sequential_id(input.sequential_id)?
+            .ok_or(UseCaseError::NotFound(input.sequential_id.to_i64()))?;
+
+        if task.is_closed() {
+            return Err(UseCaseError::AlreadyClosed(task.sequential_id().to_i64()).into());
+        }
+        if task.is_locked() {
+            return Err(UseCaseError::Locked(task.sequential_id().to_i64()).into());
+        }
+
+        task.execute(TaskCommand::StartTimer)?;
+
+        self.repository().save(&mut task)?;
+        Ok(task.sequential_id())
+    }
+}
+
+impl<T: IESTaskRepositoryComponent> StartTimerUseCase for T {}
+
+/// StartTimerUseCaseComponent returns StartTimerUseCase.
+pub trait StartTimerUseCaseComponent {
+    type StartTimerUseCase: StartTimerUseCase;
+    fn start_timer_usecase(&self) -> &Self::StartTimerUseCase;
+}
+
+/// DTO for input of StopTimerUseCase.
+#[derive(Debug)]
+pub struct StopTimerUseCaseInput {
+    pub sequential_id: SequentialID,
+}
+
+/// Usecase to stop the timer of a task, logging the time since it started.
+pub trait StopTimerUseCase: IESTaskRepositoryComponent {
+    /// execute stopping the timer, and returns the time logged. A locked task refuses it.
+    fn execute(&self, input: StopTimerUseCaseInput) -> Result<Duration> {
+        let mut task = self
+            .repository()
+            .load_by_sequential_id(input.sequential_id)?
+            .ok_or(UseCaseError::NotFound(input.sequential_id.to_i64()))?;
+
+        if task.is_locked() {
+            return Err(UseCaseError::Locked(task.sequential_id().to_i64()).into());
+        }
+
+        let before = task.elapsed_time();
+        task.execute(TaskCommand::StopTimer)?;
+
+        self.repository().save(&mut task)?;
+        Ok(task.elapsed_time() - before)
+    }
+}
+
+impl<T: IESTaskRepositoryComponent> StopTimerUseCase for T {}
+
+/// StopTimerUseCaseComponent returns StopTimerUseCase.
+pub trait StopTimerUseCaseComponent {
+    type StopTimerUseCase: StopTimerUseCase;
+    fn stop_timer_usecase(&self) -> &Self::StopTimerUseCase;
+}
+
+/// DTO for input of CompletePomodoroUseCase.
+#[derive(Debug)]
+pub struct CompletePomodoroUseCaseInput {
+    pub sequential_id: SequentialID,
+}
+
+/// Usecase to complete a pomodoro whose work is timed by the timer of a task.
+pub trait CompletePomodoroUseCase: IESTaskRepositoryComponent {
+    /// execute stopping the timer and counting the pomodoro, and returns the number of pomodoros
+    /// completed on the task. A locked task refuses it.
+    fn execute(&self, input: CompletePomodoroUseCaseInput) -> Result<u32> {
+        let mut task = self
+            .repository()
+            .load_by_sequential_id(input.sequential_id)?
+            .ok_or(UseCaseError::NotFound(input.sequential_id.to_i64()))?;
+
+        if task.is_locked() {
+            return Err(UseCaseError::Locked(task.sequential_id().to_i64()).into());
+        }
+
+        task.execute(TaskCommand::CompletePomodoro)?;
+
+        self.repository().save(&mut task)?;
+        Ok(task.pomodoros())
+    }
+}
+
+impl<T: IESTaskRepositoryComponent> CompletePomodoroUseCase for T {}
+
+/// CompletePomodoroUseCaseComponent returns CompletePomodoroUseCase.
+pub trait CompletePomodoroUseCaseComponent {
+    type CompletePomodoroUseCase: CompletePomodoroUseCase;
+    fn complete_pomodoro_usecase(&self) -> &Self::CompletePomodoroUseCase;
+}
+
+/// Usecase to sum logged time per tag.
+pub trait TimeByTagUseCase: IESTaskRepositoryComponent {
+    /// execute summing time. Time of a task counts for each of its tags,
+    /// so the sum of all rows can exceed the time logged. Private tasks are left out.
+    /// Rows are ordered from the longest time, then by tag.
+    fn execute(&self, input: TimeByTagUseCaseInput) -> Result<Vec<TimeByTagDTO>> {
+        let mut by_tag: BTreeMap<String, (Duration, usize)> = BTreeMap::new();
+        for sequential_id in self.repository().load_all_sequential_ids()? {
+            let task = self
+                .repository()
+                .load_by_sequential_id(sequential_id)?
+                .ok_or(UseCaseError::NotFound(sequential_id.to_i64()))?;
+            if task.is_private() {
+                continue;
+            }
+
+            let elapsed: Duration = task
+                .time_logs()
+                .iter()
+                .filter(|log| input.since.is_none_or(|since| log.logged_on >= since))
+                .map(|log| log.duration)
+                .sum();
+            if elapsed.is_zero() {
+                continue;
+            }
+
+            let tags: Vec<String> = match task.tags() {
+                [] => vec![NO_TAG_GROUP.to_owned()],
+                tags => tags.iter().map(|t| t.as_str().to_owned()).collect(),
+            };
+            for tag in tags {
+                let row = by_tag.entry(tag).or_default();
+                row.0 += elapsed;
+                row.1 += 1;
+            }
+        }
+
+        let mut rows: Vec<TimeByTagDTO> = by_tag
+            .into_iter()
+            .map(|(tag, (elapsed, tasks))| TimeByTagDTO {
+                tag,
+                elapsed,
+                tasks,
+            })
+            .collect();
+        rows.sort_by_key(|r| std::cmp::Reverse(r.elapsed));
+        Ok(rows)
+    }
+}
+
+impl<T: IESTaskRepositoryComponent> TimeByTagUseCase for T {}
+
+/// TimeByTagUseCaseComponent returns TimeByTagUseCase.
+pub trait TimeByTagUseCaseComponent {
+    type TimeByTagUseCase: TimeByTagUseCase;
+    fn time_by_tag_usecase(&self) -> &Self::TimeByTagUseCase;
+}
+
+/// Usecase to sum logged time per tag against quotas.
+pub trait QuotaUsageUseCase: IESTaskRepositoryComponent {
+    /// execute summing time logged since the beginning of the current period of each quota.
+    /// Private tasks are left out like TimeByTagUseCase. Rows are in the order of the quotas.
+    fn execute(&self, input: QuotaUsageUseCaseInput) -> Result<Vec<QuotaUsageDTO>> {
+        let mut quotas = input.quotas;
+        if let Some(sequential_id) = input.sequential_id {
+            let task = self
+                .repository()
+                .load_by_sequential_id(sequential_id)?
+                .ok_or(UseCaseError::NotFound(sequential_id.to_i64()))?;
+            quotas.retain(|q| task.tags().iter().any(|t| t.as_str() == q.tag));
+        }
+        if quotas.is_empty() {
+            return Ok(vec![]);
+        }
+
+        let mut tasks: Vec<Task> = vec![];
+        for sequential_id in self.repository().load_all_sequential_ids()? {
+            let task = self
+                .repository()
+                .load_by_sequential_id(sequential_id)?
+                .ok_or(UseCaseError::NotFound(sequential_id.to_i64()))?;
+            if !task.is_private() {
+                tasks.push(task);
+            }
+        }
+
+        Ok(quotas
+            .into_iter()
+            .map(|quota| {
+                let start = quota.period.start(input.now);
+                let start = Local
+                    .from_local_datetime(&start)
+                    .earliest()
+                    .map_or(start, |s| s.naive_utc());
+                let used = tasks
+                    .iter()
+                    .filter(|t| t.tags().iter().any(|tag| tag.as_str() == quota.tag))
+                    .flat_map(|t| t.time_logs())
+                    .filter(|log| log.logged_on >= start)
+                    .map(|log| log.duration)
+                    .sum();
+                QuotaUsageDTO {
+                    tag: quota.tag,
+                    used,
+                    limit: quota.limit,
+                    period: quota.period,
+                }
+            })
+            .collect())
+    }
+}
+
+impl<T: IESTaskRepositoryComponent> QuotaUsageUseCase for T {}
+
+/// QuotaUsageUseCaseComponent returns QuotaUsageUseCase.
+pub trait QuotaUsageUseCaseComponent {
+    type QuotaUsageUseCase: QuotaUsageUseCase;
+    fn quota_usage_usecase(&self) -> &Self::QuotaUsageUseCase;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::infra::sqlite::es_task_repository::TaskRepository;
+    use crate::usecase::es_add_task_usecase::{AddTaskUseCase, AddTaskUseCaseInput};
+    use rusqlite::Connection;
+
+    struct TimeUseCaseComponentImpl {
+        task_repository: TaskRepository,
+    }
+
+    impl IESTaskRepositoryComponent for TimeUseCaseComponentImpl {
+        type Repository = TaskRepository;
+        fn repository(&self) -> &Self::Repository {
+            &self.task_repository
+        }
+    }
+
+    #[test]
+    fn test_execute() {
+        let task_repository = TaskRepository::new(Connection::open_in_memory().unwrap());
+        task_repository.create_table_if_not_exists().unwrap();
+        let component = TimeUseCaseComponentImpl { task_repository };
+
+        let tasks: [(&[&str], bool, u64); 4] = [
+            (&["acme", "docs"], false, 3600),
+            (&["acme"], false, 1800),
+            (&[], false, 600),
+            (&["acme"], true, 7200),
+        ];
+        for (tags, private, seconds) in tasks {
+            let sequential_id = <TimeUseCaseComponentImpl as AddTaskUseCase>::execute(
+                &component,
+                AddTaskUseCaseInput {
+                    title: "title".to_owned(),
+                    priority: None,
+                    urgency: None,
+                    cost: None,
+                    tags: tags.iter().map(|t| t.to_string()).collect(),
+                    private,
+                    habit: None,
+                    due: None,
+                    someday: false,
+                    parent: None,
+                    rules: Default::default(),
+                    description: None,
+                },
+            )
+            .unwrap();
+            <TimeUseCaseComponentImpl as LogTimeUseCase>::execute(
+                &component,
+                LogTimeUseCaseInput {
+                    sequential_id,
+                    duration: Duration::from_secs(seconds),
+                },
+            )
+            .unwrap();
+        }
+
+        let got = <TimeUseCaseComponentImpl as TimeByTagUseCase>::execute(
+            &component,
+            TimeByTagUseCaseInput::default(),
+        )
+        .unwrap();
+        assert_eq!(
+            got,
+            vec![
+                TimeByTagDTO {
+                    tag: String::from("acme"),
+                    elapsed: Duration::from_secs(5400),
+                    tasks: 2,
+                },
+                TimeByTagDTO {
+                    tag: String::from("docs"),
+                    elapsed: Duration::from_secs(3600),
+                    tasks: 1,
+                },
+                TimeByTagDTO {
+                    tag: String::from(NO_TAG_GROUP),
+                    elapsed: Duration::from_secs(600),
+                    tasks: 1,
+                },
+            ]
+        );
+
+        let since = chrono::Utc::now().naive_utc() + chrono::Duration::hours(1);
+        let got = <TimeUseCaseComponentImpl as TimeByTagUseCase>::execute(
+            &component,
+            TimeByTagUseCaseInput { since: Some(since) },
+        )
+        .unwrap();
+        assert_eq!(got, vec![]);
+    }
+
+    #[test]
+    fn test_timer() {
+        let task_repository = TaskRepository::new(Connection::open_in_memory().unwrap());
+        task_repository.create_table_if_not_exists().unwrap();
+        let component = TimeUseCaseComponentImpl { task_repository };
+
+        <TimeUseCaseComponentImpl as AddTaskUseCase>::execute(
+            &component,
+            AddTaskUseCaseInput {
+                title: "title".to_owned(),
+                priority: None,
+                urgency: None,
+                cost: None,
+                tags: vec![],
+                private: false,
+                habit: None,
+                due: None,
+                someday: false,
+                parent: None,
+                rules: Default::default(),
+                description: None,
+            },
+        )
+        .unwrap();
+
+        let start = |id| {
+            <TimeUseCaseComponentImpl as StartTimerUseCase>::execute(
+                &component,
+                StartTimerUseCaseInput {
+                    sequential_id: SequentialID::new(id),
+                },
+            )
+        };
+        let stop = |id| {
+            <TimeUseCaseComponentImpl as StopTimerUseCase>::execute(
+                &component,
+                StopTimerUseCaseInput {
+                    sequential_id: SequentialID::new(id),
+                },
+            )
+        };
+        assert!(stop(1).is_err());
+        start(1).unwrap();
+        assert!(start(1).is_err());
+
+        let task = component
+            .task_repository
+            .load_by_sequential_id(SequentialID::new(1))
+            .unwrap()
+            .unwrap();
+        assert!(task.timer_started_at().is_some());
+
+        let got = stop(1).unwrap();
+        let task = component
+            .task_repository
+            .load_by_sequential_id(SequentialID::new(1))
+            .unwrap()
+            .unwrap();
+        assert_eq!(task.timer_started_at(), None);
+        assert_eq!(task.elapsed_time(), got);
+        assert!(start(2).is_err());
+
+        let complete = || {
+            <TimeUseCaseComponentImpl as CompletePomodoroUseCase>::execute(
+                &component,
+                CompletePomodoroUseCaseInput {
+                    sequential_id: SequentialID::new(1),
+                },
+            )
+        };
+        assert!(complete().is_err());
+        start(1).unwrap();
+        assert_eq!(complete().unwrap(), 1);
+        start(1).unwrap();
+        assert_eq!(complete().unwrap(), 2);
+    }
+
+    #[test]
+    fn test_quota_usage() {
+        let task_repository = TaskRepository::new(Connection::open_in_memory().unwrap());
+        task_repository.create_table_if_not_exists().unwrap();
+        let component = TimeUseCaseComponentImpl { task_repository };
+
+        let tasks: [(&[&str], bool, u64); 4] = [
+            (&["meetings"], false, 3600),
+            (&["meetings", "acme"], false, 1800),
+            (&["meetings"], true, 7200),
+            (&["docs"], false, 600),
+        ];
+        for (tags, private, seconds) in tasks {
+            let sequential_id = <TimeUseCaseComponentImpl as AddTaskUseCase>::execute(
+                &component,
+                AddTaskUseCaseInput {
+                    title: "title".to_owned(),
+                    priority: None,
+                    urgency: None,
+                    cost: None,
+                    tags: tags.iter().map(|t| t.to_string()).collect(),
+                    private,
+                    habit: None,
+                    due: None,
+                    someday: false,
+                    parent: None,
+                    rules: Default::default(),
+                    description: None,
+                },
+            )
+            .unwrap();
+            <TimeUseCaseComponentImpl as LogTimeUseCase>::execute(
+                &component,
+                LogTimeUseCaseInput {
+                    sequential_id,
+                    duration: Duration::from_secs(seconds),
+                },
+            )
+            .unwrap();
+        }
+
+        let quota = |tag: &str, seconds| Quota {
+            tag: tag.to_owned(),
+            limit: Duration::from_secs(seconds),
+            period: HabitPeriod::Week,
+        };
+        let quotas = vec![quota("acme", 3600), quota("meetings", 3600)];
+        let got = <TimeUseCaseComponentImpl as QuotaUsageUseCase>::execute(
+            &component,
+            QuotaUsageUseCaseInput {
+                quotas: quotas.clone(),
+                now: Local::now().naive_local(),
+                sequential_id: None,
+            },
+        )
+        .unwrap();
+        assert_eq!(
+            got,
+            vec![
+                QuotaUsageDTO {
+                    tag: String::from("acme"),
+                    used: Duration::from_secs(1800),
+                    limit: Duration::from_secs(3600),
+                    period: HabitPeriod::Week,
+                },
+                QuotaUsageDTO {
+                    tag: String::from("meetings"),
+                    used: Duration::from_secs(5400),
+                    limit: Duration::from_secs(3600),
+                    period: HabitPeriod::Week,
+                },
+            ]
+        );
+        assert!(!got[0].is_over());
+        assert!(got[1].is_over());
+
+        let got = <TimeUseCaseComponentImpl as QuotaUsageUseCase>::execute(
+            &component,
+            QuotaUsageUseCaseInput {
+                quotas: quotas.clone(),
+                now: Local::now().naive_local(),
+                sequential_id: Some(SequentialID::new(1)),
+            },
+        )
+        .unwrap();
+        assert_eq!(got.len(), 1);
+        assert_eq!(got[0].tag, "meetings");
+
+        let got = <TimeUseCaseComponentImpl as QuotaUsageUseCase>::execute(
+            &component,
+            QuotaUsageUseCaseInput {
+                quotas,
+                now: Local::now().naive_local() + chrono::Duration::weeks(1),
+                sequential_id: None,
+            },
+        )
+        .unwrap();
+        assert!(got.iter().all(|r| r.used.is_zero()));
+    }
+}
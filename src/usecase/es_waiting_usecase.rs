@@ -0,0 +1,214 @@
+use crate::Result;
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+
+use crate::ddd::component::{AggregateRoot, Repository};
+use crate::domain::es_task::{
+    IESTaskRepository, IESTaskRepositoryComponent, SequentialID, TaskCommand, WaitingOn,
+};
+use crate::usecase::error::UseCaseError;
+
+/// DTO for input of WaitOnUseCase.
+#[derive(Debug)]
+pub struct WaitOnUseCaseInput {
+    pub sequential_id: SequentialID,
+    /// person to wait on. The task stops waiting if None.
+    pub person: Option<String>,
+    pub follow_up_on: Option<NaiveDate>,
+}
+
+/// DTO for input of NudgeUseCase.
+#[derive(Debug)]
+pub struct NudgeUseCaseInput {
+    /// today in local time.
+    pub today: NaiveDate,
+}
+
+/// DTO of a task waiting on a person who is due to be followed up.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NudgeDTO {
+    pub id: i64,
+    pub title: String,
+    pub person: String,
+    pub follow_up_on: NaiveDate,
+}
+
+/// Usecase to mark a task as waiting on an external person.
+pub trait WaitOnUseCase: IESTaskRepositoryComponent {
+    /// execute waiting on the person, or stopping waiting. A locked task refuses it.
+    fn execute(&self, input: WaitOnUseCaseInput) -> Result<SequentialID> {
+        let mut task = self
+            .repository()
+            .load_by_sequential_id(input.sequential_id)?
+            .ok_or(UseCaseError::NotFound(input.sequential_id.to_i64()))?;
+
+        if task.is_locked() {
+            return Err(UseCaseError::Locked(task.sequential_id().to_i64()).into());
+        }
+
+        let command = match input.person {
+            Some(person) => TaskCommand::WaitOn {
+                waiting_on: WaitingOn::new(&person, input.follow_up_on)?,
+            },
+            None => TaskCommand::StopWaiting,
+        };
+        task.execute(command)?;
+
+        self.repository().save(&mut task)?;
+        Ok(task.sequential_id())
+    }
+}
+
+impl<T: IESTaskRepositoryComponent> WaitOnUseCase for T {}
+
+/// WaitOnUseCaseComponent returns WaitOnUseCase.
+pub trait WaitOnUseCaseComponent {
+    type WaitOnUseCase: WaitOnUseCase;
+    fn wait_on_usecase(&self) -> &Self::WaitOnUseCase;
+}
+
+/// Usecase to find people to follow up with.
+pub trait NudgeUseCase: IESTaskRepositoryComponent {
+    /// execute finding open tasks whose day to follow up has come, from the oldest day.
+    /// Private tasks are left out, since nudges are printed in reports.
+    fn execute(&self, input: NudgeUseCaseInput) -> Result<Vec<NudgeDTO>> {
+        let mut nudges = vec![];
+        for sequential_id in self.repository().load_all_sequential_ids()? {
+            let task = self
+                .repository()
+                .load_by_sequential_id(sequential_id)?
+                .ok_or(UseCaseError::NotFound(sequential_id.to_i64()))?;
+            if task.is_closed() || task.is_private() {
+                continue;
+            }
+            let Some(waiting_on) = task.waiting_on() else {
+                continue;
+            };
+            let Some(follow_up_on) = waiting_on.follow_up_on() else {
+                continue;
+            };
+            if waiting_on.is_due(input.today) {
+                nudges.push(NudgeDTO {
+                    id: sequential_id.to_i64(),
+                    title: task.title().to_owned(),
+                    person: waiting_on.person().to_owned(),
+                    follow_up_on,
+                });
+            }
+        }
+
+        nudges.sort_by_key(|n| (n.follow_up_on, n.id));
+        Ok(nudges)
+    }
+}
+
+impl<T: IESTaskRepositoryComponent> NudgeUseCase for T {}
+
+/// NudgeUseCaseComponent returns NudgeUseCase.
+pub trait NudgeUseCaseComponent {
+    type NudgeUseCase: NudgeUseCase;
+    fn nudge_usecase(&self) -> &Self::NudgeUseCase;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::infra::sqlite::es_task_repository::TaskRepository;
+    use crate::usecase::es_add_task_usecase::{AddTaskUseCase, AddTaskUseCaseInput};
+    use crate::usecase::es_close_task_usecase::{CloseTaskUseCase, CloseTaskUseCaseInput};
+    use rusqlite::Connection;
+
+    struct WaitingUseCaseComponentImpl {
+        task_repository: TaskRepository,
+    }
+
+    impl IESTaskRepositoryComponent for WaitingUseCaseComponentImpl {
+        type Repository = TaskRepository;
+        fn repository(&self) -> &Self::Repository {
+            &self.task_repository
+        }
+    }
+
+    #[test]
+    fn test_execute() {
+        let task_repository = TaskRepository::new(Connection::open_in_memory().unwrap());
+        task_repository.create_table_if_not_exists().unwrap();
+        let component = WaitingUseCaseComponentImpl { task_repository };
+
+        let tasks = [
+            ("contract", false),
+            ("invoice", false),
+            ("diary", true),
+            ("design", false),
+            ("budget", false),
+        ];
+        for (title, private) in tasks {
+            <WaitingUseCaseComponentImpl as AddTaskUseCase>::execute(
+                &component,
+                AddTaskUseCaseInput {
+                    title: title.to_owned(),
+                    priority: None,
+                    urgency: None,
+                    cost: None,
+                    tags: vec![],
+                    private,
+                    habit: None,
+                    due: None,
+                    someday: false,
+                    parent: None,
+                    rules: Default::default(),
+                    description: None,
+                },
+            )
+            .unwrap();
+        }
+
+        let day = |d| NaiveDate::from_ymd_opt(2024, 5, d).unwrap();
+        let wait_on = |id: i64, person: Option<&str>, follow_up_on| {
+            <WaitingUseCaseComponentImpl as WaitOnUseCase>::execute(
+                &component,
+                WaitOnUseCaseInput {
+                    sequential_id: SequentialID::new(id),
+                    person: person.map(String::from),
+                    follow_up_on,
+                },
+            )
+        };
+        wait_on(1, Some("Alice"), Some(day(10))).unwrap();
+        wait_on(2, Some("Bob"), Some(day(8))).unwrap();
+        wait_on(3, Some("Alice"), Some(day(1))).unwrap();
+        wait_on(4, Some("Carol"), Some(day(20))).unwrap();
+        wait_on(5, Some("Dave"), None).unwrap();
+        assert!(wait_on(5, Some(""), None).is_err());
+        assert!(wait_on(6, Some("Erin"), None).is_err());
+
+        let nudge = |today| {
+            <WaitingUseCaseComponentImpl as NudgeUseCase>::execute(
+                &component,
+                NudgeUseCaseInput { today },
+            )
+            .unwrap()
+        };
+        let got: Vec<(i64, String)> = nudge(day(10))
+            .into_iter()
+            .map(|n| (n.id, n.person))
+            .collect();
+        assert_eq!(
+            got,
+            vec![(2, String::from("Bob")), (1, String::from("Alice"))]
+        );
+
+        wait_on(2, None, None).unwrap();
+        <WaitingUseCaseComponentImpl as CloseTaskUseCase>::execute(
+            &component,
+            CloseTaskUseCaseInput {
+                sequential_id: SequentialID::new(1),
+                unlock: false,
+                cascade: false,
+            },
+        )
+        .unwrap();
+        assert_eq!(nudge(day(10)), vec![]);
+        assert_eq!(nudge(day(20)).len(), 1);
+    }
+}
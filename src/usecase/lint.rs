@@ -0,0 +1,169 @@
+//! # lint
+//!
+//! lint checks titles of tasks against conventions of a shared list.
+
+use std::fmt;
+
+/// LetterCase is the case which titles begin with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LetterCase {
+    Upper,
+    Lower,
+}
+
+/// LintRules are conventions of titles. No rule is checked by default.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LintRules {
+    /// max_length is the maximum number of characters.
+    pub max_length: Option<usize>,
+    /// no_trailing_period forbids a period at the end.
+    pub no_trailing_period: bool,
+    /// first_words are words which titles begin with, such as verbs. Any word is allowed if empty.
+    pub first_words: Vec<String>,
+    /// first_letter is the case of the first letter.
+    pub first_letter: Option<LetterCase>,
+}
+
+/// LintViolation is a broken rule.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LintViolation {
+    TooLong { length: usize, max: usize },
+    TrailingPeriod,
+    FirstWord { word: String },
+    FirstLetter { case: LetterCase },
+}
+
+impl fmt::Display for LintViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LintViolation::TooLong { length, max } => {
+                write!(f, "the title has {} characters, more than {}", length, max)
+            }
+            LintViolation::TrailingPeriod => write!(f, "the title ends with a period"),
+            LintViolation::FirstWord { word } => write!(
+                f,
+                "the title begins with `{}`, which is not one of the first words",
+                word
+            ),
+            LintViolation::FirstLetter { case } => {
+                let case = match case {
+                    LetterCase::Upper => "an uppercase",
+                    LetterCase::Lower => "a lowercase",
+                };
+                write!(f, "the title does not begin with {} letter", case)
+            }
+        }
+    }
+}
+
+impl LintRules {
+    /// check returns the rules which the title breaks in the order of the fields.
+    pub fn check(&self, title: &str) -> Vec<LintViolation> {
+        let mut violations = vec![];
+
+        let length = title.chars().count();
+        if let Some(max) = self.max_length.filter(|max| length > *max) {
+            violations.push(LintViolation::TooLong { length, max });
+        }
+
+        if self.no_trailing_period && title.trim_end().ends_with('.') {
+            violations.push(LintViolation::TrailingPeriod);
+        }
+
+        let word = title.split_whitespace().next().unwrap_or_default();
+        if !self.first_words.is_empty()
+            && !self
+                .first_words
+                .iter()
+                .any(|w| w.eq_ignore_ascii_case(word))
+        {
+            violations.push(LintViolation::FirstWord {
+                word: word.to_owned(),
+            });
+        }
+
+        if let Some(case) = self.first_letter {
+            let first = title.chars().find(|c| c.is_alphabetic());
+            let matched = match case {
+                LetterCase::Upper => first.is_some_and(|c| c.is_uppercase()),
+                LetterCase::Lower => first.is_some_and(|c| c.is_lowercase()),
+            };
+            if !matched {
+                violations.push(LintViolation::FirstLetter { case });
+            }
+        }
+
+        violations
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check() {
+        #[derive(Debug)]
+        struct TestCase {
+            args: &'static str,
+            want: Vec<LintViolation>,
+            name: String,
+        }
+
+        let rules = LintRules {
+            max_length: Some(20),
+            no_trailing_period: true,
+            first_words: vec![String::from("Fix"), String::from("add")],
+            first_letter: Some(LetterCase::Upper),
+        };
+
+        let table = [
+            TestCase {
+                name: String::from("normal: no violation"),
+                args: "Fix the login",
+                want: vec![],
+            },
+            TestCase {
+                name: String::from("normal: first words ignore the case"),
+                args: "Add a button",
+                want: vec![],
+            },
+            TestCase {
+                name: String::from("normal: all violations"),
+                args: "write the documents of the login.",
+                want: vec![
+                    LintViolation::TooLong {
+                        length: 33,
+                        max: 20,
+                    },
+                    LintViolation::TrailingPeriod,
+                    LintViolation::FirstWord {
+                        word: String::from("write"),
+                    },
+                    LintViolation::FirstLetter {
+                        case: LetterCase::Upper,
+                    },
+                ],
+            },
+            TestCase {
+                name: String::from("normal: length in characters"),
+                args: "Fix ログイン画面のボタンの配置を直す",
+                want: vec![],
+            },
+        ];
+
+        for test_case in table {
+            assert_eq!(
+                rules.check(test_case.args),
+                test_case.want,
+                "Failed in the \"{}\".",
+                test_case.name
+            );
+        }
+    }
+
+    #[test]
+    fn test_check_without_rules() {
+        assert!(LintRules::default().check("whatever.").is_empty());
+    }
+}
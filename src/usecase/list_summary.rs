@@ -0,0 +1,64 @@
+use serde::{Deserialize, Serialize};
+
+/// ListSummary is metadata of listed tasks, shown as a footer of lists.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ListSummary {
+    pub open: usize,
+    pub total_cost: i64,
+    /// overdue is the number of tasks open past the due date. Tasks of the legacy engine have none.
+    pub overdue: usize,
+}
+
+impl ListSummary {
+    /// construct ListSummary from costs of the listed tasks.
+    pub fn from_costs<I: IntoIterator<Item = i32>>(costs: I) -> Self {
+        costs
+            .into_iter()
+            .fold(ListSummary::default(), |summary, cost| ListSummary {
+                open: summary.open + 1,
+                total_cost: summary.total_cost + cost as i64,
+                ..summary
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_costs() {
+        #[derive(Debug)]
+        struct TestCase {
+            args: Vec<i32>,
+            want: ListSummary,
+            name: String,
+        }
+
+        let table = [
+            TestCase {
+                name: String::from("normal: some tasks"),
+                args: vec![10, 3, i32::MAX],
+                want: ListSummary {
+                    open: 3,
+                    total_cost: 13 + i32::MAX as i64,
+                    overdue: 0,
+                },
+            },
+            TestCase {
+                name: String::from("normal: empty"),
+                args: vec![],
+                want: ListSummary::default(),
+            },
+        ];
+
+        for test_case in table {
+            assert_eq!(
+                ListSummary::from_costs(test_case.args),
+                test_case.want,
+                "Failed in the \"{}\".",
+                test_case.name,
+            );
+        }
+    }
+}
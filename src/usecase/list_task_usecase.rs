@@ -1,19 +1,36 @@
-use anyhow::Result;
+use crate::Result;
+use serde::{Deserialize, Serialize};
 use std::rc::Rc;
 
-use crate::domain::task::ITaskRepository;
+use crate::domain::task::{ITaskRepository, TaskOrder};
+use crate::usecase::list_summary::ListSummary;
 
 /// DTO for input of AddTaskUseCase.
-#[derive(Debug)]
-pub struct ListTaskUseCaseInput {}
+#[derive(Debug, Default)]
+pub struct ListTaskUseCaseInput {
+    pub order: TaskOrder,
+    /// only tasks updated at or after it, such as `2024-05-01`, are listed.
+    pub updated_since: Option<String>,
+    /// include_deleted lists deleted tasks too after open ones, whether or not they are closed.
+    pub include_deleted: bool,
+}
 
 /// DTO of task
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub struct TaskDTO {
     pub id: i64,
     pub title: String,
     pub priority: i32,
     pub cost: i32,
+    pub is_deleted: bool,
+    /// created_at and updated_at are `YYYY-MM-DD HH:MM:SS` in local time.
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+/// summarize computes ListSummary of the listed tasks.
+pub fn summarize(tasks: &[TaskDTO]) -> ListSummary {
+    ListSummary::from_costs(tasks.iter().map(|t| t.cost))
 }
 
 /// Usecase to list tasks.
@@ -27,17 +44,31 @@ impl ListTaskUseCase {
         ListTaskUseCase { task_repository }
     }
 
-    /// execute addition a task.
-    pub fn execute(&self, _: ListTaskUseCaseInput) -> Result<Vec<TaskDTO>> {
-        let tasks = self.task_repository.find_opening()?;
+    /// execute listing opening tasks in the order.
+    pub fn execute(&self, input: ListTaskUseCaseInput) -> Result<Vec<TaskDTO>> {
+        let mut tasks = self.task_repository.find_opening(input.order)?;
+        if input.include_deleted {
+            tasks.extend(self.task_repository.find_deleted(input.order)?);
+        }
 
         let mut dto_tasks: Vec<TaskDTO> = Vec::new();
         for t in tasks {
+            // NOTE: timestamps are compared as strings, which sort in time order in their format.
+            if input
+                .updated_since
+                .as_ref()
+                .is_some_and(|since| t.updated_at() < since.as_str())
+            {
+                continue;
+            }
             dto_tasks.push(TaskDTO {
                 id: t.id().get(),
                 title: t.title().to_owned(),
                 priority: t.priority().get(),
                 cost: t.cost().get(),
+                is_deleted: t.is_deleted(),
+                created_at: t.created_at().to_owned(),
+                updated_at: t.updated_at().to_owned(),
             })
         }
 
@@ -58,18 +89,29 @@ mod tests {
             ID::new(seed as i64),
             seed.to_string(),
             is_closed,
+            false,
             Priority::new(seed as i32),
             Cost::new(seed as i32),
             Duration::from_secs(seed),
+            String::new(),
+            String::new(),
         )
     }
 
+    fn deleted(mut task: Task) -> Task {
+        task.delete();
+        task
+    }
+
     fn make_task_dto(seed: u64) -> TaskDTO {
         TaskDTO {
             id: seed as i64,
             title: seed.to_string(),
             priority: seed as i32,
             cost: seed as i32,
+            is_deleted: false,
+            created_at: String::new(),
+            updated_at: String::new(),
         }
     }
 
@@ -88,19 +130,62 @@ mod tests {
             name: String,
         }
 
-        let table = [TestCase {
-            name: String::from("normal: with priority and cost"),
-            given: vec![
-                make_task(1, false),
-                make_task(2, false),
-                make_task(3, true),
-                make_task(4, false),
-            ],
-            args: Args {
-                input: ListTaskUseCaseInput {},
+        let table = [
+            TestCase {
+                name: String::from("normal: with priority and cost"),
+                given: vec![
+                    make_task(1, false),
+                    make_task(2, false),
+                    make_task(3, true),
+                    make_task(4, false),
+                ],
+                args: Args {
+                    input: ListTaskUseCaseInput::default(),
+                },
+                want: vec![make_task_dto(1), make_task_dto(2), make_task_dto(4)],
+            },
+            TestCase {
+                name: String::from("normal: in the order of priority"),
+                given: vec![make_task(1, false), make_task(2, false)],
+                args: Args {
+                    input: ListTaskUseCaseInput {
+                        order: TaskOrder::Priority,
+                        updated_since: None,
+                        include_deleted: false,
+                    },
+                },
+                want: vec![make_task_dto(2), make_task_dto(1)],
+            },
+            TestCase {
+                name: String::from("normal: updated since a later day"),
+                given: vec![make_task(1, false)],
+                args: Args {
+                    input: ListTaskUseCaseInput {
+                        order: TaskOrder::Id,
+                        updated_since: Some(String::from("9999-01-01")),
+                        include_deleted: false,
+                    },
+                },
+                want: vec![],
             },
-            want: vec![make_task_dto(1), make_task_dto(2), make_task_dto(4)],
-        }];
+            TestCase {
+                name: String::from("normal: including deleted tasks"),
+                given: vec![make_task(1, false), deleted(make_task(2, true))],
+                args: Args {
+                    input: ListTaskUseCaseInput {
+                        include_deleted: true,
+                        ..Default::default()
+                    },
+                },
+                want: vec![
+                    make_task_dto(1),
+                    TaskDTO {
+                        is_deleted: true,
+                        ..make_task_dto(2)
+                    },
+                ],
+            },
+        ];
 
         for test_case in table {
             let task_repository = TaskRepository::new(Connection::open_in_memory().unwrap());
@@ -111,7 +196,17 @@ mod tests {
             }
 
             let list_task_usecase = ListTaskUseCase::new(Rc::new(task_repository));
-            let got = list_task_usecase.execute(test_case.args.input).unwrap();
+            let mut got = list_task_usecase.execute(test_case.args.input).unwrap();
+            // NOTE: the repository stamps the time of the test, which is checked only to be set.
+            for t in &mut got {
+                assert!(
+                    !t.updated_at.is_empty(),
+                    "Failed in the \"{}\".",
+                    test_case.name
+                );
+                t.created_at.clear();
+                t.updated_at.clear();
+            }
 
             assert_eq!(got, test_case.want, "Failed in the \"{}\".", test_case.name,);
         }
@@ -2,12 +2,75 @@
 //!
 //! usecase is a layer which is called `Application Service` in Onion Architecture.
 
+#[cfg(feature = "legacy")]
 pub mod add_task_usecase;
+#[cfg(feature = "legacy")]
 pub mod close_task_usecase;
+#[cfg(feature = "legacy")]
+pub mod delete_task_usecase;
+#[cfg(feature = "legacy")]
 pub mod edit_task_usecase;
 pub mod error;
+#[cfg(feature = "es")]
 pub mod es_add_task_usecase;
+#[cfg(feature = "es")]
 pub mod es_close_task_usecase;
+#[cfg(feature = "es")]
+pub mod es_cycle_time_usecase;
+#[cfg(feature = "es")]
+pub mod es_delete_task_usecase;
+#[cfg(feature = "es")]
+pub mod es_demo_usecase;
+#[cfg(feature = "es")]
 pub mod es_edit_task_usecase;
+#[cfg(feature = "es")]
+pub mod es_forecast_usecase;
+#[cfg(feature = "devtools")]
+pub mod es_generate_usecase;
+#[cfg(feature = "es")]
+pub mod es_habit_usecase;
+#[cfg(feature = "es")]
+pub mod es_import_usecase;
+#[cfg(feature = "es")]
+pub mod es_inbox_usecase;
+#[cfg(feature = "es")]
 pub mod es_list_task_usecase;
+#[cfg(feature = "es")]
+pub mod es_lock_task_usecase;
+#[cfg(feature = "es")]
+pub mod es_migrate_legacy_usecase;
+#[cfg(feature = "es")]
+pub mod es_next_usecase;
+#[cfg(feature = "es")]
+pub mod es_review_usecase;
+#[cfg(feature = "es")]
+pub mod es_rules_usecase;
+#[cfg(feature = "es")]
+pub mod es_search_task_usecase;
+#[cfg(feature = "es")]
+pub mod es_show_task_usecase;
+#[cfg(feature = "es")]
+pub mod es_simulate_usecase;
+#[cfg(feature = "es")]
+pub mod es_someday_usecase;
+#[cfg(feature = "es")]
+pub mod es_sprint_usecase;
+#[cfg(feature = "es")]
+pub mod es_tag_usecase;
+#[cfg(feature = "es")]
+pub mod es_tidy_usecase;
+#[cfg(feature = "es")]
+pub mod es_time_usecase;
+#[cfg(feature = "es")]
+pub mod es_waiting_usecase;
+pub mod lint;
+pub mod list_summary;
+#[cfg(feature = "legacy")]
 pub mod list_task_usecase;
+pub mod outstanding;
+pub mod percentile;
+pub mod pick;
+#[cfg(feature = "legacy")]
+pub mod search_task_usecase;
+#[cfg(feature = "legacy")]
+pub mod undo_usecase;
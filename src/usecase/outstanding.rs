@@ -0,0 +1,119 @@
+use crate::usecase::list_summary::ListSummary;
+
+/// Outstanding advises how much work is queued, printed after tasks are added or closed.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Outstanding {
+    pub summary: ListSummary,
+    /// weeks is the total cost divided by the weekly capacity.
+    /// It is None if no positive capacity is configured.
+    pub weeks: Option<f64>,
+}
+
+impl Outstanding {
+    /// construct Outstanding from the summary of open tasks.
+    pub fn new(summary: ListSummary, weekly_capacity: Option<i32>) -> Self {
+        let weeks = weekly_capacity
+            .filter(|c| *c > 0)
+            .map(|c| summary.total_cost as f64 / f64::from(c));
+        Outstanding { summary, weeks }
+    }
+
+    /// exceeds is the capacity policy checked after tasks are added.
+    /// It is true if more than max_weeks of work is queued, and never without a capacity.
+    pub fn exceeds(&self, max_weeks: u32) -> bool {
+        self.weeks.is_some_and(|weeks| weeks > f64::from(max_weeks))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_new() {
+        #[derive(Debug)]
+        struct TestCase {
+            args: Option<i32>,
+            want: Option<f64>,
+            name: String,
+        }
+
+        let summary = ListSummary {
+            open: 12,
+            total_cost: 64,
+            overdue: 0,
+        };
+        let table = [
+            TestCase {
+                name: String::from("normal: capacity"),
+                args: Some(20),
+                want: Some(3.2),
+            },
+            TestCase {
+                name: String::from("normal: no capacity"),
+                args: None,
+                want: None,
+            },
+            TestCase {
+                name: String::from("abnormal: zero capacity"),
+                args: Some(0),
+                want: None,
+            },
+        ];
+
+        for test_case in table {
+            assert_eq!(
+                Outstanding::new(summary, test_case.args),
+                Outstanding {
+                    summary,
+                    weeks: test_case.want,
+                },
+                "Failed in the \"{}\".",
+                test_case.name,
+            );
+        }
+    }
+
+    #[test]
+    fn test_exceeds() {
+        #[derive(Debug)]
+        struct TestCase {
+            args: (Option<i32>, u32),
+            want: bool,
+            name: String,
+        }
+
+        let summary = ListSummary {
+            open: 12,
+            total_cost: 80,
+            overdue: 0,
+        };
+        let table = [
+            TestCase {
+                name: String::from("normal: beyond the weeks"),
+                args: (Some(20), 3),
+                want: true,
+            },
+            TestCase {
+                name: String::from("normal: just the weeks"),
+                args: (Some(20), 4),
+                want: false,
+            },
+            TestCase {
+                name: String::from("normal: no capacity"),
+                args: (None, 0),
+                want: false,
+            },
+        ];
+
+        for test_case in table {
+            let (weekly_capacity, max_weeks) = test_case.args;
+            assert_eq!(
+                Outstanding::new(summary, weekly_capacity).exceeds(max_weeks),
+                test_case.want,
+                "Failed in the \"{}\".",
+                test_case.name,
+            );
+        }
+    }
+}
@@ -0,0 +1,66 @@
+//! # percentile
+//!
+//! percentile reads percentiles out of sorted samples, shared by forecasts and flow metrics.
+
+/// PERCENTILES are the percentiles which forecasts and flow metrics report.
+pub const PERCENTILES: [u32; 3] = [50, 85, 95];
+
+/// nearest_rank returns the smallest sample which the percent of samples are at or below.
+/// The samples must be sorted. It returns None if there are no samples.
+pub fn nearest_rank<T: Copy>(sorted: &[T], percent: u32) -> Option<T> {
+    let rank = (sorted.len() * percent as usize).div_ceil(100).max(1);
+    sorted.get(rank.min(sorted.len()).checked_sub(1)?).copied()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_nearest_rank() {
+        #[derive(Debug)]
+        struct TestCase {
+            args: (Vec<i32>, u32),
+            want: Option<i32>,
+            name: String,
+        }
+
+        let table = [
+            TestCase {
+                name: String::from("normal: median of odd samples"),
+                args: (vec![1, 2, 3, 4, 5], 50),
+                want: Some(3),
+            },
+            TestCase {
+                name: String::from("normal: 85th percentile rounds the rank up"),
+                args: (vec![1, 2, 3, 4, 5, 6, 7, 8, 9, 10], 85),
+                want: Some(9),
+            },
+            TestCase {
+                name: String::from("normal: 0th percentile is the smallest"),
+                args: (vec![4, 7], 0),
+                want: Some(4),
+            },
+            TestCase {
+                name: String::from("normal: 100th percentile is the largest"),
+                args: (vec![4, 7], 100),
+                want: Some(7),
+            },
+            TestCase {
+                name: String::from("abnormal: no samples"),
+                args: (vec![], 50),
+                want: None,
+            },
+        ];
+
+        for test_case in table {
+            let (sorted, percent) = test_case.args;
+            assert_eq!(
+                nearest_rank(&sorted, percent),
+                test_case.want,
+                "Failed in the \"{}\".",
+                test_case.name
+            );
+        }
+    }
+}
@@ -0,0 +1,140 @@
+/// Candidate is an open task which can be picked.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Candidate {
+    pub id: i64,
+    pub priority: i32,
+    pub cost: i32,
+}
+
+/// Pick decides which tasks are eligible and how likely each of them is picked.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Pick {
+    /// max_cost leaves out tasks costing more.
+    pub max_cost: Option<i32>,
+    /// weighted makes a task as likely as its priority. Priorities below 1 count as 1.
+    pub weighted: bool,
+}
+
+impl Pick {
+    /// eligible returns the candidates which can be picked.
+    pub fn eligible(&self, candidates: &[Candidate]) -> Vec<Candidate> {
+        candidates
+            .iter()
+            .filter(|c| self.max_cost.is_none_or(|max| c.cost <= max))
+            .copied()
+            .collect()
+    }
+
+    /// choose returns the candidate which `roll` falls on.
+    /// `roll` is a random number, which is reduced to the total weight of the candidates.
+    pub fn choose(&self, candidates: &[Candidate], roll: u64) -> Option<Candidate> {
+        let weight = |c: &Candidate| {
+            if self.weighted {
+                c.priority.max(1) as u64
+            } else {
+                1
+            }
+        };
+        let total: u64 = candidates.iter().map(weight).sum();
+        if total == 0 {
+            return None;
+        }
+
+        let mut roll = roll % total;
+        for c in candidates {
+            if roll < weight(c) {
+                return Some(*c);
+            }
+            roll -= weight(c);
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_choose() {
+        #[derive(Debug)]
+        struct TestCase {
+            args: (Pick, u64),
+            want: Option<i64>,
+            name: String,
+        }
+
+        let candidates = [
+            Candidate {
+                id: 1,
+                priority: 3,
+                cost: 1,
+            },
+            Candidate {
+                id: 2,
+                priority: 0,
+                cost: 8,
+            },
+            Candidate {
+                id: 3,
+                priority: 1,
+                cost: 2,
+            },
+        ];
+        let weighted = Pick {
+            weighted: true,
+            ..Pick::default()
+        };
+
+        let table = [
+            TestCase {
+                name: String::from("normal: uniform"),
+                args: (Pick::default(), 4),
+                want: Some(2),
+            },
+            TestCase {
+                name: String::from("normal: weighted by priority"),
+                args: (weighted, 2),
+                want: Some(1),
+            },
+            TestCase {
+                name: String::from("normal: priority below 1 counts as 1"),
+                args: (weighted, 3),
+                want: Some(2),
+            },
+            TestCase {
+                name: String::from("normal: max cost"),
+                args: (
+                    Pick {
+                        max_cost: Some(5),
+                        weighted: false,
+                    },
+                    1,
+                ),
+                want: Some(3),
+            },
+            TestCase {
+                name: String::from("abnormal: nothing eligible"),
+                args: (
+                    Pick {
+                        max_cost: Some(0),
+                        weighted: false,
+                    },
+                    1,
+                ),
+                want: None,
+            },
+        ];
+
+        for test_case in table {
+            let (pick, roll) = test_case.args;
+            let got = pick.choose(&pick.eligible(&candidates), roll);
+            assert_eq!(
+                got.map(|c| c.id),
+                test_case.want,
+                "Failed in the \"{}\".",
+                test_case.name
+            );
+        }
+    }
+}
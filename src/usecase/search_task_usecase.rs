@@ -0,0 +1,119 @@
+use crate::Result;
+use std::rc::Rc;
+
+use crate::domain::task::ITaskRepository;
+use crate::usecase::list_task_usecase::TaskDTO;
+
+/// DTO for input of SearchTaskUseCase.
+#[derive(Debug, Default)]
+pub struct SearchTaskUseCaseInput {
+    /// query is words which titles have, such as `fix test`. A word matches words it begins.
+    pub query: String,
+    /// closed tasks are found too if true.
+    pub include_closed: bool,
+}
+
+/// Usecase to search tasks by their titles.
+pub struct SearchTaskUseCase {
+    task_repository: Rc<dyn ITaskRepository>,
+}
+
+impl SearchTaskUseCase {
+    /// construct SearchTaskUseCase with ITaskRepository.
+    pub fn new(task_repository: Rc<dyn ITaskRepository>) -> Self {
+        SearchTaskUseCase { task_repository }
+    }
+
+    /// execute searching tasks, the best match first.
+    pub fn execute(&self, input: SearchTaskUseCaseInput) -> Result<Vec<TaskDTO>> {
+        Ok(self
+            .task_repository
+            .search(&input.query)?
+            .into_iter()
+            .filter(|t| input.include_closed || !t.is_closed())
+            .map(|t| TaskDTO {
+                id: t.id().get(),
+                title: t.title().to_owned(),
+                priority: t.priority().get(),
+                cost: t.cost().get(),
+                is_deleted: t.is_deleted(),
+                created_at: t.created_at().to_owned(),
+                updated_at: t.updated_at().to_owned(),
+            })
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::task::{Task, ID};
+    use crate::domain::task_builder::TaskBuilder;
+    use crate::infra::memory::task_repository::TaskRepository;
+
+    #[test]
+    fn test_execute() {
+        #[derive(Debug)]
+        struct TestCase {
+            args: SearchTaskUseCaseInput,
+            want: Vec<i64>,
+            name: String,
+        }
+
+        let task_repository = Rc::new(TaskRepository::new());
+        for title in ["Fix the flaky test", "write tests", "fix the docs"] {
+            task_repository
+                .add(Task::new(TaskBuilder::new(title)))
+                .unwrap();
+        }
+        let mut closed = task_repository.find_by_id(ID::new(3)).unwrap().unwrap();
+        closed.close();
+        task_repository.update(closed).unwrap();
+        let search_task_usecase = SearchTaskUseCase::new(task_repository);
+
+        let table = [
+            TestCase {
+                name: String::from("normal: prefixes of words in any case"),
+                args: SearchTaskUseCaseInput {
+                    query: String::from("test"),
+                    include_closed: false,
+                },
+                want: vec![1, 2],
+            },
+            TestCase {
+                name: String::from("normal: all of the words"),
+                args: SearchTaskUseCaseInput {
+                    query: String::from("FIX te"),
+                    include_closed: false,
+                },
+                want: vec![1],
+            },
+            TestCase {
+                name: String::from("normal: including closed tasks"),
+                args: SearchTaskUseCaseInput {
+                    query: String::from("fix"),
+                    include_closed: true,
+                },
+                want: vec![1, 3],
+            },
+            TestCase {
+                name: String::from("normal: no words"),
+                args: SearchTaskUseCaseInput {
+                    query: String::from("  "),
+                    include_closed: true,
+                },
+                want: vec![],
+            },
+        ];
+
+        for test_case in table {
+            let got: Vec<i64> = search_task_usecase
+                .execute(test_case.args)
+                .unwrap()
+                .iter()
+                .map(|t| t.id)
+                .collect();
+            assert_eq!(got, test_case.want, "Failed in the \"{}\".", test_case.name);
+        }
+    }
+}
@@ -0,0 +1,147 @@
+use crate::Result;
+use serde::{Deserialize, Serialize};
+use std::rc::Rc;
+
+use crate::domain::task::{ITaskRepository, JournalChange, JournalEntry, ID};
+
+/// DTO for input of HistoryUseCase.
+#[derive(Debug)]
+pub struct HistoryUseCaseInput {
+    /// id limits the history to the task. All tasks are included if None.
+    pub id: Option<i64>,
+}
+
+/// DTO of a task before a change.
+#[derive(Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BeforeImageDTO {
+    pub title: String,
+    pub is_closed: bool,
+    pub is_deleted: bool,
+    pub priority: i32,
+    pub cost: i32,
+}
+
+/// DTO of a change which can be undone.
+#[derive(Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChangeDTO {
+    pub id: i64,
+    /// before is the task before the change. It is None if the change added the task.
+    pub before: Option<BeforeImageDTO>,
+    /// recorded_at is `YYYY-MM-DD HH:MM:SS` in local time.
+    pub recorded_at: String,
+}
+
+impl From<JournalEntry> for ChangeDTO {
+    fn from(entry: JournalEntry) -> Self {
+        ChangeDTO {
+            id: entry.task_id.get(),
+            before: match entry.change {
+                JournalChange::Added => None,
+                JournalChange::Updated => entry.before.map(|t| BeforeImageDTO {
+                    title: t.title().to_owned(),
+                    is_closed: t.is_closed(),
+                    is_deleted: t.is_deleted(),
+                    priority: t.priority().get(),
+                    cost: t.cost().get(),
+                }),
+            },
+            recorded_at: entry.recorded_at,
+        }
+    }
+}
+
+/// Usecase to undo the latest change of tasks.
+pub struct UndoUseCase {
+    task_repository: Rc<dyn ITaskRepository>,
+}
+
+impl UndoUseCase {
+    /// construct UndoUseCase with ITaskRepository.
+    pub fn new(task_repository: Rc<dyn ITaskRepository>) -> Self {
+        UndoUseCase { task_repository }
+    }
+
+    /// execute undoing the latest change. It is None if nothing is left to undo.
+    pub fn execute(&self) -> Result<Option<ChangeDTO>> {
+        Ok(self.task_repository.undo()?.map(ChangeDTO::from))
+    }
+}
+
+/// Usecase to list changes which can be undone.
+pub struct HistoryUseCase {
+    task_repository: Rc<dyn ITaskRepository>,
+}
+
+impl HistoryUseCase {
+    /// construct HistoryUseCase with ITaskRepository.
+    pub fn new(task_repository: Rc<dyn ITaskRepository>) -> Self {
+        HistoryUseCase { task_repository }
+    }
+
+    /// execute listing changes from the latest, which `undo` reverts in this order.
+    pub fn execute(&self, input: HistoryUseCaseInput) -> Result<Vec<ChangeDTO>> {
+        Ok(self
+            .task_repository
+            .history(input.id.map(ID::new))?
+            .into_iter()
+            .map(ChangeDTO::from)
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::infra::sqlite::task_repository::TaskRepository;
+    use crate::usecase::add_task_usecase::{AddTaskUseCase, AddTaskUseCaseInput};
+    use crate::usecase::close_task_usecase::{CloseTaskUseCase, CloseTaskUseCaseInput};
+    use rusqlite::Connection;
+
+    #[test]
+    fn test_execute() {
+        let task_repository = TaskRepository::new(Connection::open_in_memory().unwrap());
+        task_repository.create_table_if_not_exists().unwrap();
+        let task_repository: Rc<dyn ITaskRepository> = Rc::new(task_repository);
+
+        AddTaskUseCase::new(task_repository.clone())
+            .execute(AddTaskUseCaseInput {
+                title: "title".to_owned(),
+                priority: Some(20),
+                cost: Some(3),
+            })
+            .unwrap();
+        CloseTaskUseCase::new(task_repository.clone())
+            .execute(CloseTaskUseCaseInput { id: 1 })
+            .unwrap();
+
+        let history = HistoryUseCase::new(task_repository.clone());
+        let got: Vec<Option<BeforeImageDTO>> = history
+            .execute(HistoryUseCaseInput { id: Some(1) })
+            .unwrap()
+            .into_iter()
+            .map(|c| c.before)
+            .collect();
+        let before = BeforeImageDTO {
+            title: "title".to_owned(),
+            is_closed: false,
+            priority: 20,
+            cost: 3,
+            is_deleted: false,
+        };
+        assert_eq!(got, vec![Some(before), None]);
+        assert!(history
+            .execute(HistoryUseCaseInput { id: Some(2) })
+            .unwrap()
+            .is_empty());
+
+        let undo = UndoUseCase::new(task_repository.clone());
+        assert!(undo.execute().unwrap().unwrap().before.is_some());
+        assert!(!task_repository
+            .find_by_id(ID::new(1))
+            .unwrap()
+            .unwrap()
+            .is_closed());
+        assert_eq!(undo.execute().unwrap().unwrap().before, None);
+        assert_eq!(undo.execute().unwrap(), None);
+    }
+}